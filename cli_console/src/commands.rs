@@ -6,36 +6,91 @@ use fs_view::DirectoryView;
 use hal::BlockDevice;
 use services_fs_view::{FileSystemOperations, FileSystemViewService};
 use services_storage::{
-    JournaledStorage, ObjectId, ObjectKind, PersistentFilesystem, TransactionError,
-    TransactionalStorage,
+    CapabilityKind, JournaledStorage, ObjectId, ObjectKind, Ownership, PermissionChecker,
+    PersistentFilesystem, PrincipalId, TransactionError, TransactionalStorage,
 };
 
 /// CLI Command handler with persistent storage backend
+///
+/// Commands run on behalf of the handler's principal and are authorized
+/// against the object they touch with a [`PermissionChecker`]. Directories
+/// record their owner on disk; files are owned by whoever created them, or
+/// by their directory's owner when the filesystem is opened. Other
+/// principals need a capability granted through [`Self::permissions_mut`].
 pub struct PersistentCommandHandler<D: BlockDevice> {
     /// Persistent filesystem
     pub fs: PersistentFilesystem<D>,
     /// Current working directory
     pub current_dir: ObjectId,
+    /// Principal commands are run for
+    principal: PrincipalId,
+    /// Ownership and grants for every object in the filesystem
+    permissions: PermissionChecker,
 }
 
 impl<D: BlockDevice> PersistentCommandHandler<D> {
-    /// Creates a new command handler with a formatted filesystem
-    pub fn new(device: D, owner: impl Into<String>) -> Result<Self, TransactionError> {
-        let fs = PersistentFilesystem::format(device, owner)?;
-        let current_dir = fs.root_dir_id();
-        Ok(Self { fs, current_dir })
+    /// Creates a new command handler with a filesystem formatted for `principal`
+    pub fn new(device: D, principal: PrincipalId) -> Result<Self, TransactionError> {
+        let fs = PersistentFilesystem::format(device, owner_name(principal))?;
+        Self::with_filesystem(fs, principal)
     }
 
-    /// Opens an existing filesystem
-    pub fn open(device: D, root_dir_id: ObjectId) -> Result<Self, TransactionError> {
+    /// Opens an existing filesystem on behalf of `principal`
+    pub fn open(
+        device: D,
+        root_dir_id: ObjectId,
+        principal: PrincipalId,
+    ) -> Result<Self, TransactionError> {
         let fs = PersistentFilesystem::open(device, root_dir_id)?;
+        Self::with_filesystem(fs, principal)
+    }
+
+    fn with_filesystem(
+        mut fs: PersistentFilesystem<D>,
+        principal: PrincipalId,
+    ) -> Result<Self, TransactionError> {
         let current_dir = fs.root_dir_id();
-        Ok(Self { fs, current_dir })
+        let permissions = load_permissions(&mut fs)?;
+        Ok(Self {
+            fs,
+            current_dir,
+            principal,
+            permissions,
+        })
+    }
+
+    /// Returns the principal commands are run for
+    pub fn principal(&self) -> PrincipalId {
+        self.principal
+    }
+
+    /// Returns the permission table
+    pub fn permissions(&self) -> &PermissionChecker {
+        &self.permissions
+    }
+
+    /// Returns the permission table for granting capabilities
+    pub fn permissions_mut(&mut self) -> &mut PermissionChecker {
+        &mut self.permissions
+    }
+
+    /// Checks that the principal may perform `required` on an object
+    fn authorize(&self, object_id: ObjectId, required: CapabilityKind) -> Result<(), String> {
+        self.permissions
+            .authorize(self.principal, object_id, required)
+            .map_err(|reason| TransactionError::AccessDenied(reason).to_string())
+    }
+
+    /// Records the principal as the owner of an object it created
+    fn claim(&mut self, object_id: ObjectId, timestamp: u64) {
+        self.permissions
+            .register_object(object_id, Ownership::new(self.principal, timestamp));
     }
 
     /// Lists directory contents
     pub fn ls(&mut self, path: &str) -> Result<Vec<String>, String> {
         let dir_id = self.resolve_path(path)?;
+        self.authorize(dir_id, CapabilityKind::Read)?;
         let entries = self
             .fs
             .list(dir_id)
@@ -48,6 +103,7 @@ impl<D: BlockDevice> PersistentCommandHandler<D> {
     /// Reads file contents
     pub fn cat(&mut self, path: &str) -> Result<Vec<u8>, String> {
         let file_id = self.resolve_path(path)?;
+        self.authorize(file_id, CapabilityKind::Read)?;
         self.fs
             .read_file(file_id)
             .map_err(|e| format!("Failed to read file '{}': {}", path, e))
@@ -61,17 +117,26 @@ impl<D: BlockDevice> PersistentCommandHandler<D> {
         if name.contains('/') || name.contains('\\') {
             return Err("Directory name cannot contain path separators".to_string());
         }
+        self.authorize(self.current_dir, CapabilityKind::Write)?;
 
         let timestamp = get_timestamp();
         let dir_id = self
             .fs
-            .mkdir(name, self.current_dir, "user", timestamp)
+            .mkdir(
+                name,
+                self.current_dir,
+                owner_name(self.principal),
+                timestamp,
+            )
             .map_err(|e| format!("Failed to create directory '{}': {}", name, e))?;
+        self.claim(dir_id, timestamp);
 
         Ok(format!("Created directory: {} (id: {})", name, dir_id))
     }
 
     /// Writes a file
+    ///
+    /// Writing to an existing file adds a version, keeping its history.
     pub fn write_file(&mut self, name: &str, content: &[u8]) -> Result<String, String> {
         if name.is_empty() {
             return Err("File name cannot be empty".to_string());
@@ -79,12 +144,32 @@ impl<D: BlockDevice> PersistentCommandHandler<D> {
         if name.contains('/') || name.contains('\\') {
             return Err("File name cannot contain path separators".to_string());
         }
+        let existing = self
+            .fs
+            .list(self.current_dir)
+            .map_err(|e| format!("Failed to read current directory: {}", e))?
+            .into_iter()
+            .find(|(entry_name, entry)| entry_name == name && entry.kind != ObjectKind::Map);
+        if let Some((_, entry)) = existing {
+            self.authorize(entry.object_id, CapabilityKind::Write)?;
+            self.fs
+                .update_file(entry.object_id, content)
+                .map_err(|e| format!("Failed to write file '{}': {}", name, e))?;
+            return Ok(format!(
+                "Wrote file: {} ({} bytes, id: {})",
+                name,
+                content.len(),
+                entry.object_id
+            ));
+        }
 
+        self.authorize(self.current_dir, CapabilityKind::Write)?;
         let timestamp = get_timestamp();
         let file_id = self
             .fs
             .write_file(content)
             .map_err(|e| format!("Failed to write file '{}': {}", name, e))?;
+        self.claim(file_id, timestamp);
 
         self.fs
             .link(name, self.current_dir, file_id, ObjectKind::Blob, timestamp)
@@ -99,23 +184,80 @@ impl<D: BlockDevice> PersistentCommandHandler<D> {
     }
 
     /// Removes a file or directory entry
+    ///
+    /// The object is deleted once no other directory links to it.
     pub fn rm(&mut self, name: &str) -> Result<String, String> {
         if name.is_empty() {
             return Err("File name cannot be empty".to_string());
         }
+        self.authorize(self.current_dir, CapabilityKind::Write)?;
+        let target = self
+            .resolve_path(name)
+            .map_err(|_| format!("Not found: '{}'", name))?;
+        self.authorize(target, CapabilityKind::Delete)?;
 
         let timestamp = get_timestamp();
         let removed = self
             .fs
-            .unlink(name, self.current_dir, timestamp)
+            .rm(name, self.current_dir, timestamp)
             .map_err(|e| format!("Failed to remove '{}': {}", name, e))?;
 
+        if removed.is_some() && self.fs.link_count(target) == 0 {
+            self.permissions.unregister_object(target);
+        }
         match removed {
             Some(entry) => Ok(format!("Removed: {} (id: {})", name, entry.object_id)),
             None => Err(format!("Not found: '{}'", name)),
         }
     }
 
+    /// Lists a file's versions, oldest first, numbered for `restore`
+    pub fn history(&mut self, path: &str) -> Result<Vec<String>, String> {
+        let file_id = self.resolve_path(path)?;
+        self.authorize(file_id, CapabilityKind::Read)?;
+        let versions = self
+            .fs
+            .file_history(file_id)
+            .map_err(|e| format!("Failed to read history of '{}': {}", path, e))?;
+
+        Ok(versions
+            .iter()
+            .enumerate()
+            .map(|(index, info)| {
+                format!(
+                    "{:>3}  {}  {} bytes  at {}",
+                    index + 1,
+                    info.version_id,
+                    info.size_bytes,
+                    info.committed_at
+                )
+            })
+            .collect())
+    }
+
+    /// Restores version `number` (as listed by `history`) as the newest version
+    pub fn restore(&mut self, path: &str, number: usize) -> Result<String, String> {
+        let file_id = self.resolve_path(path)?;
+        self.authorize(file_id, CapabilityKind::Write)?;
+        let versions = self
+            .fs
+            .file_history(file_id)
+            .map_err(|e| format!("Failed to read history of '{}': {}", path, e))?;
+        let info = number
+            .checked_sub(1)
+            .and_then(|index| versions.get(index))
+            .ok_or_else(|| format!("No version {} of '{}'", number, path))?;
+
+        let restored = self
+            .fs
+            .restore_file(file_id, info.version_id)
+            .map_err(|e| format!("Failed to restore '{}': {}", path, e))?;
+        Ok(format!(
+            "Restored: {} to version {} (new version: {})",
+            path, number, restored
+        ))
+    }
+
     /// Resolves a path to an ObjectId (simplified - just returns current dir or parses name)
     fn resolve_path(&mut self, path: &str) -> Result<ObjectId, String> {
         let path = path.trim();
@@ -142,6 +284,46 @@ impl<D: BlockDevice> PersistentCommandHandler<D> {
     }
 }
 
+/// Owner recorded on disk for directories `principal` creates
+fn owner_name(principal: PrincipalId) -> String {
+    principal.as_uuid().to_string()
+}
+
+/// Registers every reachable object with the owner recorded on disk
+///
+/// Files are owned by the directory they are first found in. Owners that
+/// are not principal IDs (such as a legacy "root") map to the system
+/// principal.
+fn load_permissions<D: BlockDevice>(
+    fs: &mut PersistentFilesystem<D>,
+) -> Result<PermissionChecker, TransactionError> {
+    let mut permissions = PermissionChecker::new();
+    let mut stack = vec![fs.root_dir_id()];
+    while let Some(dir_id) = stack.pop() {
+        if permissions.get_ownership(dir_id).is_some() {
+            continue;
+        }
+        let dir = fs.read_directory(dir_id)?;
+        let owner = dir
+            .metadata
+            .owner
+            .parse()
+            .unwrap_or_else(|_| PrincipalId::system());
+        permissions.register_object(dir_id, Ownership::new(owner, dir.metadata.created_at));
+        for entry in dir.entries.values() {
+            if entry.kind == ObjectKind::Map {
+                stack.push(entry.object_id);
+            } else if permissions.get_ownership(entry.object_id).is_none() {
+                permissions.register_object(
+                    entry.object_id,
+                    Ownership::new(owner, entry.metadata.created_at),
+                );
+            }
+        }
+    }
+    Ok(permissions)
+}
+
 /// Get current timestamp (nanoseconds since epoch)
 fn get_timestamp() -> u64 {
     // In a real system, this would get actual time
@@ -154,6 +336,9 @@ fn get_timestamp() -> u64 {
 }
 
 /// CLI Command handler
+///
+/// Every command runs on behalf of the handler's principal and is checked
+/// against the filesystem view's permissions.
 pub struct CommandHandler {
     /// Filesystem view service
     pub fs_service: FileSystemViewService,
@@ -161,31 +346,50 @@ pub struct CommandHandler {
     pub root: DirectoryView,
     /// Storage backend for object content reads/writes
     storage: JournaledStorage,
+    /// Principal commands are run for
+    principal: PrincipalId,
 }
 
 impl CommandHandler {
     /// Creates a new command handler with an empty root directory
+    ///
+    /// Commands run as a fresh principal that owns the root.
     pub fn new() -> Self {
+        Self::for_principal(PrincipalId::new())
+    }
+
+    /// Creates a command handler whose root directory is owned by `principal`
+    pub fn for_principal(principal: PrincipalId) -> Self {
         let root_id = ObjectId::new();
         let root = DirectoryView::new(root_id);
         let mut fs_service = FileSystemViewService::new();
 
         // Register the root directory with the service
         fs_service.register_directory(root.clone());
+        fs_service
+            .permissions_mut()
+            .register_object(root_id, Ownership::new(principal, get_timestamp()));
 
         Self {
             fs_service,
             root,
             storage: JournaledStorage::new(),
+            principal,
         }
     }
 
+    /// Returns the principal commands are run for
+    pub fn principal(&self) -> PrincipalId {
+        self.principal
+    }
+
     /// Lists directory contents
     ///
     /// Example: `pg ls docs/`
-    pub fn ls(&self, path: &str) -> Result<Vec<String>, String> {
+    pub fn ls(&mut self, path: &str) -> Result<Vec<String>, String> {
         let entries = self
             .fs_service
+            .as_principal(self.principal)
             .ls(&self.root, path)
             .map_err(|e| format!("ls failed: {}", e))?;
 
@@ -199,6 +403,7 @@ impl CommandHandler {
     pub fn cat(&mut self, path: &str) -> Result<String, String> {
         let obj_id = self
             .fs_service
+            .as_principal(self.principal)
             .open(&self.root, path)
             .map_err(|e| format!("cat failed: {}", e))?;
 
@@ -221,6 +426,7 @@ impl CommandHandler {
     pub fn mkdir(&mut self, path: &str) -> Result<String, String> {
         let dir_id = self
             .fs_service
+            .as_principal(self.principal)
             .mkdir(&mut self.root, path)
             .map_err(|e| format!("mkdir failed: {}", e))?;
 
@@ -237,6 +443,7 @@ impl CommandHandler {
         kind: ObjectKind,
     ) -> Result<String, String> {
         self.fs_service
+            .as_principal(self.principal)
             .link(&mut self.root, path, object_id, kind)
             .map_err(|e| format!("link failed: {}", e))?;
 
@@ -246,9 +453,10 @@ impl CommandHandler {
     /// Displays object information
     ///
     /// Example: `pg stat docs/notes.txt`
-    pub fn stat(&self, path: &str) -> Result<String, String> {
+    pub fn stat(&mut self, path: &str) -> Result<String, String> {
         let stat_info = self
             .fs_service
+            .as_principal(self.principal)
             .stat(&self.root, path)
             .map_err(|e| format!("stat failed: {}", e))?;
 
@@ -275,18 +483,19 @@ impl Default for CommandHandler {
 mod tests {
     use super::*;
     use hal::RamDisk;
+    use services_storage::Capability;
 
     #[test]
     fn test_persistent_handler_creation() {
         let disk = RamDisk::with_capacity_mb(10);
-        let handler = PersistentCommandHandler::new(disk, "test_user");
+        let handler = PersistentCommandHandler::new(disk, PrincipalId::new());
         assert!(handler.is_ok());
     }
 
     #[test]
     fn test_persistent_mkdir_and_ls() {
         let disk = RamDisk::with_capacity_mb(10);
-        let mut handler = PersistentCommandHandler::new(disk, "test_user").unwrap();
+        let mut handler = PersistentCommandHandler::new(disk, PrincipalId::new()).unwrap();
 
         let result = handler.mkdir("docs");
         assert!(result.is_ok());
@@ -301,7 +510,7 @@ mod tests {
     #[test]
     fn test_persistent_write_and_cat() {
         let disk = RamDisk::with_capacity_mb(10);
-        let mut handler = PersistentCommandHandler::new(disk, "test_user").unwrap();
+        let mut handler = PersistentCommandHandler::new(disk, PrincipalId::new()).unwrap();
 
         let content = b"Hello, persistent storage!";
         let write_result = handler.write_file("test.txt", content);
@@ -315,7 +524,7 @@ mod tests {
     #[test]
     fn test_persistent_rm() {
         let disk = RamDisk::with_capacity_mb(10);
-        let mut handler = PersistentCommandHandler::new(disk, "test_user").unwrap();
+        let mut handler = PersistentCommandHandler::new(disk, PrincipalId::new()).unwrap();
 
         handler.write_file("file.txt", b"data").unwrap();
 
@@ -327,6 +536,26 @@ mod tests {
         assert_eq!(ls_result.unwrap().len(), 0);
     }
 
+    #[test]
+    fn test_persistent_history_and_restore() {
+        let disk = RamDisk::with_capacity_mb(10);
+        let mut handler = PersistentCommandHandler::new(disk, PrincipalId::new()).unwrap();
+
+        handler.write_file("notes.txt", b"draft").unwrap();
+        handler.write_file("notes.txt", b"final").unwrap();
+        assert_eq!(handler.ls("/").unwrap().len(), 1);
+
+        let history = handler.history("notes.txt").unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].contains("5 bytes"));
+
+        handler.restore("notes.txt", 1).unwrap();
+        assert_eq!(handler.cat("notes.txt").unwrap(), b"draft");
+        assert_eq!(handler.history("notes.txt").unwrap().len(), 3);
+        assert!(handler.restore("notes.txt", 9).is_err());
+        assert!(handler.restore("notes.txt", 0).is_err());
+    }
+
     #[test]
     fn test_command_handler_creation() {
         let handler = CommandHandler::new();
@@ -403,6 +632,118 @@ mod tests {
         assert!(entries.contains(&"notes".to_string()));
     }
 
+    #[test]
+    fn test_commands_are_checked_for_principal() {
+        let alice = PrincipalId::new();
+        let mut handler = CommandHandler::for_principal(alice);
+        handler.mkdir("docs").unwrap();
+        let obj_id = ObjectId::new();
+        handler.link("file.txt", obj_id, ObjectKind::Blob).unwrap();
+        assert!(handler.fs_service.permissions().is_owner(obj_id, alice));
+
+        // Someone else sharing the same tree gets nothing
+        let mut other = CommandHandler::for_principal(PrincipalId::new());
+        other.fs_service = handler.fs_service.clone();
+        other.root = handler.root.clone();
+        let err = other.ls("/").unwrap_err();
+        assert!(err.contains("requires Read capability"), "{}", err);
+        assert!(other.stat("file.txt").is_err());
+        assert!(other.mkdir("mine").is_err());
+
+        // Default handlers act for a principal of their own
+        assert_ne!(CommandHandler::new().principal(), PrincipalId::system());
+    }
+
+    #[test]
+    fn test_persistent_commands_are_checked_for_principal() {
+        let owner = PrincipalId::new();
+        let disk = RamDisk::with_capacity_mb(10);
+        let mut handler = PersistentCommandHandler::new(disk, owner).unwrap();
+        handler.write_file("notes.txt", b"private").unwrap();
+        let root = handler.fs.root_dir_id();
+
+        let disk = handler.fs.into_device();
+        let mut other = PersistentCommandHandler::open(disk, root, PrincipalId::new()).unwrap();
+        let err = other.cat("notes.txt").unwrap_err();
+        assert!(err.starts_with("Access denied: "), "{}", err);
+        assert!(other.ls("/").is_err());
+        assert!(other.write_file("mine.txt", b"x").is_err());
+        assert!(other.rm("notes.txt").is_err());
+
+        let disk = other.fs.into_device();
+        let mut reopened = PersistentCommandHandler::open(disk, root, owner).unwrap();
+        assert_eq!(reopened.cat("notes.txt").unwrap(), b"private");
+    }
+
+    /// Alice's filesystem with `notes.txt`, reopened for bob
+    fn opened_for_bob() -> (PersistentCommandHandler<RamDisk>, PrincipalId, ObjectId) {
+        let alice = PrincipalId::new();
+        let mut handler =
+            PersistentCommandHandler::new(RamDisk::with_capacity_mb(10), alice).unwrap();
+        handler.write_file("notes.txt", b"private").unwrap();
+        let root = handler.fs.root_dir_id();
+
+        let bob = PrincipalId::new();
+        let disk = handler.fs.into_device();
+        let other = PersistentCommandHandler::open(disk, root, bob).unwrap();
+        (other, bob, root)
+    }
+
+    #[test]
+    fn test_persistent_commands_honor_grants() {
+        let (mut bob_handler, bob, root) = opened_for_bob();
+        assert!(bob_handler.ls("/").is_err());
+
+        bob_handler
+            .permissions_mut()
+            .grant(Capability::new(root, CapabilityKind::Read, bob));
+        assert_eq!(bob_handler.ls("/").unwrap(), vec!["notes.txt".to_string()]);
+        // Read is not Write
+        assert!(bob_handler.write_file("mine.txt", b"x").is_err());
+
+        bob_handler
+            .permissions_mut()
+            .grant(Capability::new(root, CapabilityKind::Write, bob));
+        bob_handler.write_file("mine.txt", b"x").unwrap();
+        // Bob owns what he created
+        assert_eq!(bob_handler.cat("mine.txt").unwrap(), b"x");
+        bob_handler.rm("mine.txt").unwrap();
+    }
+
+    #[test]
+    fn test_persistent_file_acl_is_checked_apart_from_directory() {
+        let (mut bob_handler, bob, root) = opened_for_bob();
+        let notes = bob_handler.resolve_path("notes.txt").unwrap();
+
+        // Reading the directory does not grant reading the file
+        bob_handler
+            .permissions_mut()
+            .grant(Capability::new(root, CapabilityKind::Read, bob));
+        let err = bob_handler.cat("notes.txt").unwrap_err();
+        assert!(err.starts_with("Access denied: "), "{}", err);
+        assert!(bob_handler.history("notes.txt").is_err());
+
+        // Nor does a grant on the file open up the directory
+        bob_handler
+            .permissions_mut()
+            .revoke(root, CapabilityKind::Read, bob);
+        bob_handler
+            .permissions_mut()
+            .grant(Capability::new(notes, CapabilityKind::Read, bob));
+        assert_eq!(bob_handler.cat("notes.txt").unwrap(), b"private");
+        assert_eq!(bob_handler.history("notes.txt").unwrap().len(), 1);
+        assert!(bob_handler.ls("/").is_err());
+        assert!(bob_handler.restore("notes.txt", 1).is_err());
+
+        // Writing the file needs Write on the file itself
+        bob_handler
+            .permissions_mut()
+            .grant(Capability::new(notes, CapabilityKind::Write, bob));
+        bob_handler.write_file("notes.txt", b"edited").unwrap();
+        assert_eq!(bob_handler.cat("notes.txt").unwrap(), b"edited");
+        assert!(bob_handler.rm("notes.txt").is_err());
+    }
+
     #[test]
     fn test_cat_nonexistent_file() {
         let mut handler = CommandHandler::new();
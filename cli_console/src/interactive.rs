@@ -6,6 +6,7 @@
 use core_types::TaskId;
 use input_types::{InputEvent, KeyCode, KeyEvent};
 use ipc::ChannelId;
+use services_clipboard::ClipboardClient;
 use services_focus_manager::FocusManager;
 use services_input::{InputService, InputSubscriptionCap};
 
@@ -18,6 +19,7 @@ use services_input::{InputService, InputSubscriptionCap};
 /// - Translates events to actions
 /// - Maintains command history
 /// - Supports cursor movement and line editing
+/// - Shares killed text through the clipboard service, when connected
 pub struct InteractiveConsole {
     /// Task ID for this console
     task_id: TaskId,
@@ -33,6 +35,8 @@ pub struct InteractiveConsole {
     history: Vec<String>,
     /// Current history position (for up/down arrow navigation)
     history_pos: Option<usize>,
+    /// Clipboard that Ctrl+U/K/W publish to and Ctrl+Y reads from
+    clipboard: Option<ClipboardClient>,
 }
 
 impl InteractiveConsole {
@@ -46,9 +50,15 @@ impl InteractiveConsole {
             cursor_pos: 0,
             history: Vec::new(),
             history_pos: None,
+            clipboard: None,
         }
     }
 
+    /// Connects the console to the clipboard service
+    pub fn connect_clipboard(&mut self, clipboard: ClipboardClient) {
+        self.clipboard = Some(clipboard);
+    }
+
     /// Subscribes to keyboard input
     pub fn subscribe(
         &mut self,
@@ -232,18 +242,26 @@ impl InteractiveConsole {
             }
             KeyCode::U => {
                 // Ctrl+U: Delete from cursor to start of line
-                self.text_buffer.drain(0..self.cursor_pos);
+                let killed: String = self.text_buffer.drain(0..self.cursor_pos).collect();
                 self.cursor_pos = 0;
+                self.publish_killed(&killed)?;
                 Ok(None)
             }
             KeyCode::K => {
                 // Ctrl+K: Delete from cursor to end of line
-                self.text_buffer.truncate(self.cursor_pos);
+                let killed = self.text_buffer.split_off(self.cursor_pos);
+                self.publish_killed(&killed)?;
                 Ok(None)
             }
             KeyCode::W => {
                 // Ctrl+W: Delete word before cursor
-                self.delete_word_before_cursor();
+                let killed = self.delete_word_before_cursor();
+                self.publish_killed(&killed)?;
+                Ok(None)
+            }
+            KeyCode::Y => {
+                // Ctrl+Y: Paste the newest clip at the cursor
+                self.paste_clipboard()?;
                 Ok(None)
             }
             KeyCode::L => {
@@ -271,10 +289,39 @@ impl InteractiveConsole {
         }
     }
 
-    /// Delete word before cursor (for Ctrl+W)
-    fn delete_word_before_cursor(&mut self) {
+    /// Publishes text removed by a kill command
+    fn publish_killed(&mut self, killed: &str) -> Result<(), String> {
+        match &mut self.clipboard {
+            Some(clipboard) if !killed.is_empty() => clipboard
+                .publish(killed, false)
+                .map(|_| ())
+                .map_err(|e| format!("Clipboard: {}", e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Inserts the newest clip at the cursor (for Ctrl+Y)
+    ///
+    /// Only the first line of a multi-line clip is inserted.
+    fn paste_clipboard(&mut self) -> Result<(), String> {
+        let Some(clipboard) = &mut self.clipboard else {
+            return Ok(());
+        };
+        let entry = clipboard
+            .latest()
+            .map_err(|e| format!("Clipboard: {}", e))?;
+        if let Some(entry) = entry {
+            let text = entry.text.lines().next().unwrap_or("");
+            self.text_buffer.insert_str(self.cursor_pos, text);
+            self.cursor_pos += text.len();
+        }
+        Ok(())
+    }
+
+    /// Delete word before cursor (for Ctrl+W), returning the deleted text
+    fn delete_word_before_cursor(&mut self) -> String {
         if self.cursor_pos == 0 {
-            return;
+            return String::new();
         }
 
         let text_before_cursor = &self.text_buffer[0..self.cursor_pos];
@@ -293,9 +340,11 @@ impl InteractiveConsole {
 
         // Reconstruct buffer
         let before: String = chars[0..pos].iter().collect();
+        let deleted = self.text_buffer[before.len()..self.cursor_pos].to_string();
         let after = &self.text_buffer[self.cursor_pos..];
         self.text_buffer = format!("{}{}", before, after);
         self.cursor_pos = before.len();
+        deleted
     }
 
     /// Converts a key event to a character (simplified)
@@ -364,6 +413,9 @@ impl InteractiveConsole {
 mod tests {
     use super::*;
     use input_types::Modifiers;
+    use services_clipboard::{ClipboardAccess, ClipboardService};
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
     #[test]
     fn test_interactive_console_creation() {
@@ -1397,6 +1449,67 @@ mod tests {
         assert_eq!(console.text_buffer(), "hello ");
     }
 
+    #[test]
+    fn test_kills_and_ctrl_y_share_the_clipboard() {
+        let service = Rc::new(RefCell::new(ClipboardService::new()));
+        let grant = service
+            .borrow_mut()
+            .grant("cli", ClipboardAccess::ReadWrite)
+            .unwrap();
+        let mut console = InteractiveConsole::new(TaskId::new());
+        console.connect_clipboard(ClipboardClient::new(grant, service.clone()));
+        let ctrl = |code| InputEvent::key(KeyEvent::pressed(code, Modifiers::CTRL));
+
+        for code in [KeyCode::L, KeyCode::S, KeyCode::Space, KeyCode::A] {
+            console
+                .process_event(InputEvent::key(KeyEvent::pressed(code, Modifiers::none())))
+                .unwrap();
+        }
+        console.process_event(ctrl(KeyCode::W)).unwrap();
+        assert_eq!(console.text_buffer(), "ls ");
+        console.process_event(ctrl(KeyCode::U)).unwrap();
+        assert_eq!(console.text_buffer(), "");
+
+        let grant = service
+            .borrow_mut()
+            .grant("reader", ClipboardAccess::ReadWrite)
+            .unwrap();
+        let texts: Vec<String> = service
+            .borrow()
+            .history(grant.id)
+            .unwrap()
+            .map(|entry| entry.text.clone())
+            .collect();
+        assert_eq!(texts, ["ls ", "a"]);
+
+        // Ctrl+Y pastes the newest clip, from any component
+        service
+            .borrow_mut()
+            .publish(grant.id, "cat\nrest", true)
+            .unwrap();
+        console.process_event(ctrl(KeyCode::Y)).unwrap();
+        console.process_event(ctrl(KeyCode::Y)).unwrap();
+        assert_eq!(console.text_buffer(), "catcat");
+        assert_eq!(console.cursor_pos(), 6);
+    }
+
+    #[test]
+    fn test_ctrl_y_needs_a_read_grant() {
+        let service = Rc::new(RefCell::new(ClipboardService::new()));
+        let grant = service
+            .borrow_mut()
+            .grant("cli", ClipboardAccess::PublishOnly)
+            .unwrap();
+        let mut console = InteractiveConsole::new(TaskId::new());
+        console.connect_clipboard(ClipboardClient::new(grant, service));
+
+        let result = console.process_event(InputEvent::key(KeyEvent::pressed(
+            KeyCode::Y,
+            Modifiers::CTRL,
+        )));
+        assert!(result.unwrap_err().contains("read denied"));
+    }
+
     #[test]
     fn test_ctrl_l_clear_input() {
         let task_id = TaskId::new();
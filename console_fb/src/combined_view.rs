@@ -4,12 +4,13 @@
 //! and the vi editor into a single framebuffer display with proper separation.
 
 use crate::ConsoleFb;
+use alloc::collections::BTreeSet;
 use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use hal::Framebuffer;
 use services_editor_vi::render::EditorView;
-use services_editor_vi::state::EditorState;
+use services_editor_vi::state::{EditorState, LineSpan};
 
 #[cfg(any(debug_assertions, feature = "perf_debug"))]
 use crate::RenderPerfStats;
@@ -42,6 +43,8 @@ struct EditorRenderCache {
     lines: Vec<String>,
     status: String,
     cursor: Option<(usize, usize)>,
+    /// Selection and search match cells drawn in inverse video
+    highlights: Vec<LineSpan>,
     cols: usize,
     rows: usize,
     valid: bool,
@@ -70,9 +73,24 @@ impl EditorRenderCache {
         self.lines.clear();
         self.status.clear();
         self.cursor = None;
+        self.highlights.clear();
     }
 }
 
+/// Selection and search match spans on the rows the editor area shows
+fn build_editor_highlights(state: &EditorState, rows: usize) -> Vec<LineSpan> {
+    let mut spans = state.selection_spans();
+    spans.retain(|span| span.row < rows);
+    spans.extend(state.search_spans(0..rows));
+    spans
+}
+
+fn is_highlighted(spans: &[LineSpan], col: usize, row: usize) -> bool {
+    spans
+        .iter()
+        .any(|span| span.row == row && (span.start..span.end).contains(&col))
+}
+
 fn build_editor_lines(state: &EditorState, rows: usize) -> Vec<String> {
     let mut lines = Vec::with_capacity(rows);
     for row in 0..rows {
@@ -282,6 +300,17 @@ impl<F: Framebuffer> CombinedView<F> {
             for (row, line) in lines.iter().enumerate() {
                 self.console.draw_text_at(0, row, line);
             }
+            let highlights = build_editor_highlights(editor_state, content_rows);
+            for span in &highlights {
+                let line = &lines[span.row];
+                for col in span.start..span.end.min(cols) {
+                    self.console.draw_highlighted_char_at(
+                        col,
+                        span.row,
+                        cursor_cell_byte(line, col),
+                    );
+                }
+            }
 
             let status_row = rows.saturating_sub(1);
             let mut status = self.editor_view.render_status(editor_state);
@@ -307,6 +336,7 @@ impl<F: Framebuffer> CombinedView<F> {
 
             self.editor_cache.lines = lines;
             self.editor_cache.status = status;
+            self.editor_cache.highlights = highlights;
             self.editor_cache.cols = cols;
             self.editor_cache.rows = rows;
             self.editor_cache.valid = true;
@@ -367,6 +397,20 @@ impl<F: Framebuffer> CombinedView<F> {
             plan.cursor_to = cursor_to;
         }
 
+        // Rows whose selection changed, or whose text changed under a
+        // selection, are repainted whole so no cell keeps stale colors
+        let highlights = build_editor_highlights(editor_state, content_rows);
+        let mut repaint_rows = BTreeSet::new();
+        if highlights != self.editor_cache.highlights {
+            repaint_rows.extend(self.editor_cache.highlights.iter().map(|span| span.row));
+            repaint_rows.extend(highlights.iter().map(|span| span.row));
+        }
+        for update in &plan.line_updates {
+            if highlights.iter().any(|span| span.row == update.row) {
+                repaint_rows.insert(update.row);
+            }
+        }
+
         if plan.full_redraw {
             self.editor_cache.invalidate();
             self.render_editor_with_ticks(editor_state, frame_start_ticks, frame_end_ticks);
@@ -388,11 +432,23 @@ impl<F: Framebuffer> CombinedView<F> {
             }
         }
 
+        for &row in &repaint_rows {
+            let line = new_lines.get(row).map(|s| s.as_str()).unwrap_or("~");
+            for col in 0..cols {
+                let cell = cursor_cell_byte(line, col);
+                if is_highlighted(&highlights, col, row) {
+                    self.console.draw_highlighted_char_at(col, row, cell);
+                } else {
+                    self.console.draw_char_at(col, row, cell);
+                }
+            }
+        }
+
         #[cfg(any(debug_assertions, feature = "perf_debug"))]
         {
             let stats = self.console.perf_stats_mut();
-            stats.dirty_lines += plan.line_updates.len();
-            stats.dirty_spans += plan.line_updates.len();
+            stats.dirty_lines += plan.line_updates.len() + repaint_rows.len();
+            stats.dirty_spans += plan.line_updates.len() + repaint_rows.len();
         }
 
         // Redraw status line if changed
@@ -410,12 +466,19 @@ impl<F: Framebuffer> CombinedView<F> {
             if old_row < content_rows {
                 let line = new_lines.get(old_row).map(|s| s.as_str()).unwrap_or("~");
                 let cell = cursor_cell_byte(line, old_col);
-                self.console.draw_char_at(old_col, old_row, cell);
+                if is_highlighted(&highlights, old_col, old_row) {
+                    self.console
+                        .draw_highlighted_char_at(old_col, old_row, cell);
+                } else {
+                    self.console.draw_char_at(old_col, old_row, cell);
+                }
             }
         }
 
-        // Draw new cursor
-        if let Some((new_col, new_row)) = plan.cursor_to {
+        // Draw new cursor, or redraw it over a repainted row
+        let cursor_repainted = cursor_to.is_some_and(|(_, row)| repaint_rows.contains(&row));
+        if let Some((new_col, new_row)) = plan.cursor_to.or(cursor_to.filter(|_| cursor_repainted))
+        {
             if new_row < content_rows {
                 self.console.draw_cursor(new_col, new_row);
             }
@@ -425,6 +488,7 @@ impl<F: Framebuffer> CombinedView<F> {
         self.editor_cache.lines = new_lines;
         self.editor_cache.status = status;
         self.editor_cache.cursor = cursor_to;
+        self.editor_cache.highlights = highlights;
         self.editor_cache.cols = cols;
         self.editor_cache.rows = rows;
         self.editor_cache.valid = true;
@@ -625,6 +689,44 @@ mod tests {
         }
     }
 
+    /// Top-left pixel of a cell, which is background for a space
+    fn cell_pixel(view: &mut CombinedView<MockFramebuffer>, col: usize, row: usize) -> [u8; 4] {
+        let framebuffer = &mut view.console_mut().framebuffer;
+        let info = framebuffer.info();
+        let offset = info.offset(col * crate::FONT_WIDTH, row * crate::FONT_HEIGHT);
+        framebuffer.buffer_mut()[offset..offset + 4]
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render_editor_selection() {
+        let fb = MockFramebuffer::new(640, 480);
+        let console = ConsoleFb::new(fb);
+        let mut view = CombinedView::new(console, 2);
+        let info = view.console_mut().framebuffer.info();
+        let (fg, bg) = (crate::FG_COLOR, crate::BG_COLOR);
+        let fg = info.format.to_bytes(fg.0, fg.1, fg.2);
+        let bg = info.format.to_bytes(bg.0, bg.1, bg.2);
+
+        let mut state = EditorState::new();
+        state.load_content("ab cd\nef gh".to_string());
+        view.render_editor(&state);
+        assert_eq!(cell_pixel(&mut view, 2, 0), bg);
+
+        // Selecting the first line inverts its cells, including the space
+        state.start_visual(services_editor_vi::state::VisualKind::Line);
+        view.render_editor(&state);
+        assert_eq!(cell_pixel(&mut view, 2, 0), fg);
+        assert_eq!(cell_pixel(&mut view, 2, 1), bg);
+        assert_eq!(cell_pixel(&mut view, 5, 0), bg);
+
+        // Leaving Visual mode restores them
+        state.set_mode(services_editor_vi::EditorMode::Normal);
+        view.render_editor(&state);
+        assert_eq!(cell_pixel(&mut view, 2, 0), bg);
+    }
+
     #[test]
     fn test_incremental_render_cursor_move() {
         let fb = MockFramebuffer::new(640, 480);
@@ -204,6 +204,25 @@ impl<F: Framebuffer> ConsoleFb<F> {
     ///
     /// Returns true if the character was drawn (within bounds)
     pub fn draw_char_at(&mut self, col: usize, row: usize, ch: u8) -> bool {
+        self.draw_glyph(col, row, ch, FG_COLOR, BG_COLOR)
+    }
+
+    /// Draw a single character at (col, row) in inverse video
+    ///
+    /// Used for highlighted cells such as an editor selection.
+    /// Returns true if the character was drawn (within bounds)
+    pub fn draw_highlighted_char_at(&mut self, col: usize, row: usize, ch: u8) -> bool {
+        self.draw_glyph(col, row, ch, BG_COLOR, FG_COLOR)
+    }
+
+    fn draw_glyph(
+        &mut self,
+        col: usize,
+        row: usize,
+        ch: u8,
+        fg: (u8, u8, u8),
+        bg: (u8, u8, u8),
+    ) -> bool {
         if col >= self.cols || row >= self.rows {
             return false;
         }
@@ -217,8 +236,8 @@ impl<F: Framebuffer> ConsoleFb<F> {
         let info = self.framebuffer.info();
         let buffer = self.framebuffer.buffer_mut();
 
-        let fg_bytes = info.format.to_bytes(FG_COLOR.0, FG_COLOR.1, FG_COLOR.2);
-        let bg_bytes = info.format.to_bytes(BG_COLOR.0, BG_COLOR.1, BG_COLOR.2);
+        let fg_bytes = info.format.to_bytes(fg.0, fg.1, fg.2);
+        let bg_bytes = info.format.to_bytes(bg.0, bg.1, bg.2);
 
         let x_offset = col * FONT_WIDTH;
         let y_offset = row * FONT_HEIGHT;
@@ -542,6 +561,22 @@ mod tests {
         assert_eq!(drawn, text.len() - 1);
     }
 
+    #[test]
+    fn test_draw_highlighted_char_inverts_colors() {
+        let fb = MockFramebuffer::new(64, 64);
+        let mut console = ConsoleFb::new(fb);
+        let info = console.framebuffer.info();
+        let fg = info.format.to_bytes(FG_COLOR.0, FG_COLOR.1, FG_COLOR.2);
+        let bg = info.format.to_bytes(BG_COLOR.0, BG_COLOR.1, BG_COLOR.2);
+        let offset = info.offset(0, 0);
+
+        assert!(console.draw_char_at(0, 0, b' '));
+        assert_eq!(console.framebuffer.buffer_mut()[offset..offset + 4], bg);
+        assert!(console.draw_highlighted_char_at(0, 0, b' '));
+        assert_eq!(console.framebuffer.buffer_mut()[offset..offset + 4], fg);
+        assert!(!console.draw_highlighted_char_at(1000, 0, b' '));
+    }
+
     #[test]
     fn test_draw_cursor() {
         let fb = MockFramebuffer::new(160, 160);
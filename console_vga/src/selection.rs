@@ -1,9 +1,13 @@
 //! # Text Selection and Clipboard for VGA Console
 //!
 //! This module provides text selection and internal clipboard functionality.
-//! No system clipboard, no Wayland nonsense - just PandaGen's internal buffer.
+//! No Wayland nonsense - just PandaGen's internal buffer. With the `clipboard`
+//! feature, a [`SelectionManager`] can also be connected to the clipboard
+//! service, so copied selections reach the editor and the CLI.
 
 use alloc::vec::Vec;
+#[cfg(feature = "clipboard")]
+use services_clipboard::{ClipboardClient, ClipboardError};
 
 /// Selection range in the VGA buffer
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -117,6 +121,9 @@ pub struct SelectionManager {
     selection: Option<SelectionRange>,
     /// Internal clipboard
     clipboard: Clipboard,
+    /// Clipboard service connection (optional)
+    #[cfg(feature = "clipboard")]
+    service: Option<ClipboardClient>,
 }
 
 impl SelectionManager {
@@ -125,6 +132,34 @@ impl SelectionManager {
         Self {
             selection: None,
             clipboard: Clipboard::new(),
+            #[cfg(feature = "clipboard")]
+            service: None,
+        }
+    }
+
+    /// Connects to the clipboard service
+    ///
+    /// Copies are then published to the service as well as kept locally.
+    #[cfg(feature = "clipboard")]
+    pub fn connect_clipboard(&mut self, client: ClipboardClient) {
+        self.service = Some(client);
+    }
+
+    /// Replaces the local clipboard with the service's newest clip
+    ///
+    /// Returns whether there was a clip to fetch; without a connection
+    /// nothing changes.
+    #[cfg(feature = "clipboard")]
+    pub fn fetch_clipboard(&mut self) -> Result<bool, ClipboardError> {
+        let Some(service) = &mut self.service else {
+            return Ok(false);
+        };
+        match service.latest()? {
+            Some(entry) => {
+                self.clipboard.copy(entry.text.as_bytes());
+                Ok(true)
+            }
+            None => Ok(false),
         }
     }
 
@@ -151,8 +186,15 @@ impl SelectionManager {
     }
 
     /// Copies selected text to clipboard
+    ///
+    /// When connected, the text is also published; if that fails the copy
+    /// still stands locally.
     pub fn copy_selection(&mut self, text: &[u8]) {
         self.clipboard.copy(text);
+        #[cfg(feature = "clipboard")]
+        if let Some(service) = &mut self.service {
+            let _ = service.publish(self.clipboard.as_str(), false);
+        }
     }
 
     /// Pastes clipboard content
@@ -320,4 +362,36 @@ mod tests {
         // Second text in clipboard
         assert_eq!(manager.paste(), b"Second");
     }
+
+    #[cfg(feature = "clipboard")]
+    #[test]
+    fn test_selection_manager_shares_clipboard_service() {
+        use alloc::rc::Rc;
+        use core::cell::RefCell;
+        use services_clipboard::{ClipboardAccess, ClipboardService};
+
+        let service = Rc::new(RefCell::new(ClipboardService::new()));
+        let grant = service
+            .borrow_mut()
+            .grant("console", ClipboardAccess::ReadWrite)
+            .unwrap();
+        let mut manager = SelectionManager::new();
+        assert_eq!(manager.fetch_clipboard(), Ok(false));
+        manager.connect_clipboard(ClipboardClient::new(grant, service.clone()));
+
+        manager.copy_selection(b"from the console");
+        let editor = service
+            .borrow_mut()
+            .grant("editor", ClipboardAccess::ReadWrite)
+            .unwrap();
+        let latest = service.borrow().latest(editor.id).unwrap().cloned();
+        assert_eq!(latest.unwrap().text, "from the console");
+
+        service
+            .borrow_mut()
+            .publish(editor.id, "from the editor", false)
+            .unwrap();
+        assert_eq!(manager.fetch_clipboard(), Ok(true));
+        assert_eq!(manager.paste(), b"from the editor");
+    }
 }
@@ -249,6 +249,12 @@ pub enum CapabilityEvent {
         cap_type: String,
         expired_at_nanos: u64,
     },
+    /// Capability attached to a message and held by the kernel until delivery
+    InTransit {
+        cap_id: u64,
+        from_task: TaskId,
+        cap_type: String,
+    },
 }
 
 /// Reasons why a capability use attempt failed
@@ -37,6 +37,9 @@ pub use memory::{
     AddressSpace, AddressSpaceCap, AddressSpaceId, MemoryAccessType, MemoryBacking, MemoryError,
     MemoryPerms, MemoryRegion, MemoryRegionCap, MemoryRegionId,
 };
-pub use service_ids::{command_service_id, console_service_id, input_service_id, timer_service_id};
+pub use service_ids::{
+    clipboard_service_id, command_service_id, console_service_id, input_service_id,
+    timer_service_id,
+};
 pub use storage_schema::{MigrationLineage, ObjectSchemaId, ObjectSchemaVersion};
 pub use uuid_tools::new_uuid;
@@ -6,6 +6,7 @@ const CONSOLE_SERVICE_ID: u128 = 0x2b2f_8f83_4d77_4d6f_9b9f_0d8a_5c2a_9e11u128;
 const COMMAND_SERVICE_ID: u128 = 0x3c1a_1d5e_2f14_4a4a_8e9c_7b3c_19f0_7a22u128;
 const TIMER_SERVICE_ID: u128 = 0x5d8b_2af1_7d2a_4a97_9c4d_2e4b_1c7e_6b33u128;
 const INPUT_SERVICE_ID: u128 = 0x91a7_2f0e_c9c3_4d8a_8e76_0e8c_9f0a_2d4bu128;
+const CLIPBOARD_SERVICE_ID: u128 = 0x6e4c_0b7a_52d1_4f38_a3e5_8d21_c7f4_1b55u128;
 
 /// Stable service ID for the console service.
 pub fn console_service_id() -> ServiceId {
@@ -27,6 +28,11 @@ pub fn input_service_id() -> ServiceId {
     ServiceId::from_u128(INPUT_SERVICE_ID)
 }
 
+/// Stable service ID for the clipboard service.
+pub fn clipboard_service_id() -> ServiceId {
+    ServiceId::from_u128(CLIPBOARD_SERVICE_ID)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -43,5 +49,9 @@ mod tests {
         );
         assert_eq!(timer_service_id(), ServiceId::from_u128(TIMER_SERVICE_ID));
         assert_eq!(input_service_id(), ServiceId::from_u128(INPUT_SERVICE_ID));
+        assert_eq!(
+            clipboard_service_id(),
+            ServiceId::from_u128(CLIPBOARD_SERVICE_ID)
+        );
     }
 }
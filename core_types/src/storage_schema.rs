@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// Unlike ad-hoc structure inference, this explicitly names the schema.
 /// Examples: "user-profile", "audit-event", "config-v2"
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ObjectSchemaId(String);
 
 impl ObjectSchemaId {
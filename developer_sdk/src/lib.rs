@@ -55,6 +55,10 @@ impl TraceRecorder {
     }
 }
 
+/// Steps through a recorded trace log
+///
+/// This only replays trace events; to reproduce a whole kernel run, record
+/// it with `sim_kernel::replay` instead.
 #[derive(Debug, Clone)]
 pub struct ReplaySession {
     log: TraceLog,
@@ -209,6 +213,13 @@ mod tests {
             Err(KernelError::ReceiveFailed("not supported".to_string()))
         }
 
+        fn select(
+            &mut self,
+            _wait_set: &kernel_api::WaitSet,
+        ) -> Result<kernel_api::WaitOutcome, KernelError> {
+            Err(KernelError::ReceiveFailed("not supported".to_string()))
+        }
+
         fn now(&self) -> kernel_api::Instant {
             kernel_api::Instant::from_nanos(0)
         }
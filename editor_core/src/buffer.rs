@@ -3,12 +3,15 @@
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::fmt;
 
 #[cfg(feature = "serde_support")]
 use serde::{Deserialize, Serialize};
 
 /// Cursor position in the buffer
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+///
+/// Positions order by row, then column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
 pub struct Position {
     pub row: usize,
@@ -148,6 +151,103 @@ impl TextBuffer {
         true
     }
 
+    /// Text between two positions, `end` exclusive, with lines joined by `\n`
+    pub fn text_range(&self, start: Position, end: Position) -> String {
+        let (start, end) = (self.clamp(start), self.clamp(end));
+        if end <= start {
+            return String::new();
+        }
+        if start.row == end.row {
+            return self.lines[start.row][start.col..end.col].into();
+        }
+        let mut text = String::from(&self.lines[start.row][start.col..]);
+        for line in &self.lines[start.row + 1..end.row] {
+            text.push('\n');
+            text.push_str(line);
+        }
+        text.push('\n');
+        text.push_str(&self.lines[end.row][..end.col]);
+        text
+    }
+
+    /// Delete the text between two positions, `end` exclusive
+    pub fn delete_range(&mut self, start: Position, end: Position) -> bool {
+        let (start, end) = (self.clamp(start), self.clamp(end));
+        if end <= start {
+            return false;
+        }
+        let tail = self.lines[end.row].split_off(end.col);
+        self.lines.drain(start.row + 1..=end.row);
+        let line = &mut self.lines[start.row];
+        line.truncate(start.col);
+        line.push_str(&tail);
+        true
+    }
+
+    /// Insert text, which may span lines, at position
+    /// Returns the position just after the inserted text
+    pub fn insert_text(&mut self, pos: Position, text: &str) -> Option<Position> {
+        if pos.row >= self.lines.len() || pos.col > self.lines[pos.row].len() {
+            return None;
+        }
+        let tail = self.lines[pos.row].split_off(pos.col);
+        let mut end = pos;
+        for (index, part) in text.split('\n').enumerate() {
+            if index > 0 {
+                end = Position::new(end.row + 1, 0);
+                self.lines.insert(end.row, String::new());
+            }
+            self.lines[end.row].push_str(part);
+            end.col += part.len();
+        }
+        self.lines[end.row].push_str(&tail);
+        Some(end)
+    }
+
+    /// Remove whole lines `first..=last`, returning them
+    /// The buffer keeps one empty line if every line is removed
+    pub fn remove_lines(&mut self, first: usize, last: usize) -> Vec<String> {
+        if first >= self.lines.len() || last < first {
+            return Vec::new();
+        }
+        let last = last.min(self.lines.len() - 1);
+        let removed: Vec<String> = self.lines.drain(first..=last).collect();
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        removed
+    }
+
+    /// Insert whole lines before `row`; a row past the end appends
+    pub fn insert_lines(&mut self, row: usize, lines: impl IntoIterator<Item = String>) {
+        let row = row.min(self.lines.len());
+        self.lines.splice(row..row, lines);
+    }
+
+    /// Replace the text of one line; returns false past the last line
+    pub fn replace_line(&mut self, row: usize, text: String) -> bool {
+        match self.lines.get_mut(row) {
+            Some(line) => {
+                *line = text;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Column of the first non-blank character on a line
+    pub fn first_non_blank(&self, row: usize) -> usize {
+        self.line(row)
+            .and_then(|line| line.find(|ch: char| ch != ' ' && ch != '\t'))
+            .unwrap_or(0)
+    }
+
+    /// Clamp a position to the buffer
+    pub fn clamp(&self, pos: Position) -> Position {
+        let row = pos.row.min(self.lines.len() - 1);
+        Position::new(row, pos.col.min(self.lines[row].len()))
+    }
+
     pub fn is_empty(&self) -> bool {
         self.lines.len() == 1 && self.lines[0].is_empty()
     }
@@ -159,6 +259,12 @@ impl Default for TextBuffer {
     }
 }
 
+impl fmt::Display for TextBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,4 +359,28 @@ mod tests {
         assert_eq!(buffer.line_count(), 1);
         assert_eq!(buffer.line(0), Some(""));
     }
+
+    #[test]
+    fn test_ranges_across_lines() {
+        let mut buffer = TextBuffer::from_string("one two\nthree\nfour".into());
+        let (start, end) = (Position::new(0, 4), Position::new(2, 2));
+        assert_eq!(buffer.text_range(start, end), "two\nthree\nfo");
+        assert!(buffer.delete_range(start, end));
+        assert_eq!(buffer.as_string(), "one ur");
+
+        let end = buffer.insert_text(Position::new(0, 4), "a\nb\nfo");
+        assert_eq!(end, Some(Position::new(2, 2)));
+        assert_eq!(buffer.as_string(), "one a\nb\nfour");
+    }
+
+    #[test]
+    fn test_remove_and_insert_lines() {
+        let mut buffer = TextBuffer::from_string("a\n  b\nc".into());
+        assert_eq!(buffer.first_non_blank(1), 2);
+        assert_eq!(buffer.remove_lines(0, 1), vec!["a", "  b"]);
+        buffer.insert_lines(1, vec!["d".into()]);
+        assert_eq!(buffer.as_string(), "c\nd");
+        buffer.remove_lines(0, 5);
+        assert!(buffer.is_empty());
+    }
 }
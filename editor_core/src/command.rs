@@ -1,6 +1,61 @@
 //! Command parsing and execution
 
 use alloc::string::String;
+use core::fmt;
+
+use text_pattern::PatternError;
+
+/// Why a command line could not be run
+///
+/// Shared by every host, so `:s`, `:g` and friends report the same errors
+/// wherever they are typed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    /// No command by this name
+    UnknownCommand(String),
+    /// The command is known but its arguments are malformed
+    InvalidSyntax(String),
+    /// Quitting or replacing the buffer would lose changes
+    UnsavedChanges,
+    /// A line address points before the first or past the last line
+    LineOutOfRange(i64),
+    /// The range cannot be used by the command
+    InvalidRange(String),
+    /// A `'x` address names a mark that was never set
+    UnknownMark(char),
+    /// A search, `:s` or `:g` pattern matched nothing
+    PatternNotFound(String),
+    /// A pattern is not a valid regular expression
+    InvalidPattern(PatternError),
+    /// `n` or an empty pattern with nothing searched for yet
+    NoPreviousPattern,
+    /// `:set` named an option the editor does not have
+    UnknownOption(String),
+    /// `:set` gave an option a value it cannot take
+    InvalidOptionValue { option: String, value: String },
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::UnknownCommand(cmd) => write!(f, "Unknown command: {}", cmd),
+            CommandError::InvalidSyntax(msg) => write!(f, "Invalid syntax: {}", msg),
+            CommandError::UnsavedChanges => {
+                write!(f, "Cannot quit: unsaved changes (use :q! to force)")
+            }
+            CommandError::LineOutOfRange(line) => write!(f, "Line out of range: {}", line),
+            CommandError::InvalidRange(msg) => write!(f, "Invalid range: {}", msg),
+            CommandError::UnknownMark(mark) => write!(f, "Mark not set: '{}", mark),
+            CommandError::PatternNotFound(pattern) => write!(f, "Pattern not found: {}", pattern),
+            CommandError::InvalidPattern(error) => write!(f, "Invalid pattern: {}", error),
+            CommandError::NoPreviousPattern => write!(f, "No previous search pattern"),
+            CommandError::UnknownOption(option) => write!(f, "Unknown option: {}", option),
+            CommandError::InvalidOptionValue { option, value } => {
+                write!(f, "Invalid value for {}: {}", option, value)
+            }
+        }
+    }
+}
 
 /// Parsed command
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -3,15 +3,20 @@
 //! A complete, testable, no_std editor state machine following the modal
 //! editing philosophy from services_editor_vi and kernel_bootstrap.
 
-use alloc::string::String;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::ops::Range;
 
 use crate::{
     buffer::{Position, TextBuffer},
-    command::{parse_command, Command},
+    command::{parse_command, Command, CommandError},
+    ex::{Address, EditorOptions, ExCommand, LineRef},
     key::Key,
     mode::EditorMode,
+    normal::{NormalCommand, NormalEngine, Operator},
+    search::SearchState,
     snapshot::EditorSnapshot,
+    visual::{LineSpan, Selection, VisualCommand, VISUAL_BLOCK_KEY},
 };
 
 /// Snapshot for undo/redo
@@ -45,6 +50,9 @@ pub enum CoreIoRequest {
     SaveAs(String),
     /// Save to current file and quit
     SaveAndQuit,
+    /// `:r`: read a file and hand its text to
+    /// [`EditorCore::insert_read`] to go below `line` (0 for the top)
+    Read { path: String, line: usize },
 }
 
 /// Editor core state machine
@@ -54,11 +62,16 @@ pub struct EditorCore {
     cursor: Position,
     dirty: bool,
     command_buffer: String,
-    search_query: String,
-    last_search: Option<String>,
+    /// Pattern being typed, last search and search history
+    search: SearchState,
     status_message: String,
     undo_stack: Vec<BufferSnapshot>,
     redo_stack: Vec<BufferSnapshot>,
+    normal: NormalEngine,
+    /// The end of a Visual-mode selection that stays put
+    visual_anchor: Position,
+    /// Options changed with `:set`
+    options: EditorOptions,
 }
 
 impl EditorCore {
@@ -70,11 +83,13 @@ impl EditorCore {
             cursor: Position::zero(),
             dirty: false,
             command_buffer: String::new(),
-            search_query: String::new(),
-            last_search: None,
+            search: SearchState::new(),
             status_message: String::new(),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            normal: NormalEngine::new(),
+            visual_anchor: Position::zero(),
+            options: EditorOptions::default(),
         }
     }
 
@@ -85,6 +100,9 @@ impl EditorCore {
             EditorMode::Insert => self.handle_insert_mode(key),
             EditorMode::Command => self.handle_command_mode(key),
             EditorMode::Search => self.handle_search_mode(key),
+            EditorMode::Visual | EditorMode::VisualLine | EditorMode::VisualBlock => {
+                self.handle_visual_mode(key)
+            }
         }
     }
 
@@ -96,7 +114,7 @@ impl EditorCore {
             buffer_lines: self.buffer.lines().to_vec(),
             dirty: self.dirty,
             command_buffer: self.command_buffer.clone(),
-            search_query: self.search_query.clone(),
+            search_query: self.search.query().to_string(),
             undo_depth: self.undo_stack.len(),
             redo_depth: self.redo_stack.len(),
         }
@@ -124,13 +142,42 @@ impl EditorCore {
     }
 
     pub fn search_query(&self) -> &str {
-        &self.search_query
+        self.search.query()
+    }
+
+    /// Search direction, last pattern and history
+    pub fn search(&self) -> &SearchState {
+        &self.search
     }
 
     pub fn status_message(&self) -> &str {
         &self.status_message
     }
 
+    /// Normal-mode engine, with the register and pending command
+    pub fn normal(&self) -> &NormalEngine {
+        &self.normal
+    }
+
+    /// Options set with `:set`, for the host's renderer
+    pub fn options(&self) -> &EditorOptions {
+        &self.options
+    }
+
+    /// The selection, while in a Visual mode
+    pub fn selection(&self) -> Option<Selection> {
+        self.mode
+            .visual_kind()
+            .map(|kind| Selection::new(kind, self.visual_anchor, self.cursor))
+    }
+
+    /// Search matches to highlight on `rows`: those of the pattern being
+    /// typed in Search mode, otherwise those of the last search
+    pub fn search_spans(&self, rows: Range<usize>) -> Vec<LineSpan> {
+        self.search
+            .match_spans(&self.buffer, rows, self.mode == EditorMode::Search)
+    }
+
     // Private mode handlers
 
     /// Helper to insert a character in insert mode
@@ -139,6 +186,7 @@ impl EditorCore {
         if self.buffer.insert_char(self.cursor, ch) {
             self.cursor.col += 1;
             self.dirty = true;
+            self.normal.record_insert(ch);
             CoreOutcome::Changed
         } else {
             CoreOutcome::Continue
@@ -146,105 +194,166 @@ impl EditorCore {
     }
 
     fn handle_normal_mode(&mut self, key: Key) -> CoreOutcome {
-        match key {
-            // Enter insert mode
-            Key::I => {
-                self.mode = EditorMode::Insert;
-                CoreOutcome::Changed
-            }
-            Key::A => {
-                self.mode = EditorMode::Insert;
-                // Move cursor right (even to line_len for append)
-                let line_len = self.buffer.line_length(self.cursor.row);
-                if self.cursor.col < line_len {
-                    self.cursor.col += 1;
-                }
-                CoreOutcome::Changed
-            }
-
-            // Navigation
-            Key::H | Key::Left => {
-                self.move_cursor_left();
-                CoreOutcome::Changed
-            }
-            Key::J | Key::Down => {
-                self.move_cursor_down();
-                CoreOutcome::Changed
-            }
-            Key::K | Key::Up => {
-                self.move_cursor_up();
-                CoreOutcome::Changed
-            }
-            Key::L | Key::Right => {
-                self.move_cursor_right();
-                CoreOutcome::Changed
-            }
-
-            // Editing
-            Key::X => {
-                self.save_undo_snapshot();
-                if self.buffer.delete_char(self.cursor) {
-                    self.dirty = true;
-                    CoreOutcome::Changed
-                } else {
-                    CoreOutcome::Continue
-                }
+        let ch = match key {
+            Key::Escape => {
+                self.normal.reset_pending();
+                return CoreOutcome::Continue;
             }
-            Key::D => {
-                self.save_undo_snapshot();
-                if self.buffer.delete_line(self.cursor.row) {
-                    self.dirty = true;
-                    if self.cursor.row >= self.buffer.line_count() {
-                        self.cursor.row = self.buffer.line_count().saturating_sub(1);
-                    }
-                    self.clamp_cursor();
-                    CoreOutcome::Changed
+            Key::CtrlR => {
+                self.normal.reset_pending();
+                return if self.redo() {
+                    CoreOutcome::StatusMessage("Redo".into())
                 } else {
-                    CoreOutcome::Continue
-                }
+                    CoreOutcome::StatusMessage("Already at newest change".into())
+                };
             }
+            Key::CtrlV => VISUAL_BLOCK_KEY,
+            Key::Left => 'h',
+            Key::Down => 'j',
+            Key::Up => 'k',
+            Key::Right => 'l',
+            key => match key.as_char() {
+                Some(ch) => ch,
+                None => return CoreOutcome::Continue,
+            },
+        };
 
-            // Undo/redo
-            Key::U => {
+        let Some(command) = self.normal.push_key(ch) else {
+            return CoreOutcome::Continue;
+        };
+
+        match command {
+            NormalCommand::Undo => {
                 if self.undo() {
                     CoreOutcome::StatusMessage("Undo".into())
                 } else {
                     CoreOutcome::StatusMessage("Already at oldest change".into())
                 }
             }
-            Key::CtrlR => {
-                if self.redo() {
-                    CoreOutcome::StatusMessage("Redo".into())
-                } else {
-                    CoreOutcome::StatusMessage("Already at newest change".into())
-                }
-            }
 
             // Enter command mode
-            Key::Colon => {
+            NormalCommand::EnterCommand => {
                 self.mode = EditorMode::Command;
                 self.command_buffer.clear();
                 CoreOutcome::Changed
             }
 
             // Enter search mode
-            Key::Slash => {
+            NormalCommand::EnterSearch(direction) => {
                 self.mode = EditorMode::Search;
-                self.search_query.clear();
+                self.search.start(direction);
                 CoreOutcome::Changed
             }
 
-            // Repeat last search
-            Key::N => {
-                if self.find_next() {
-                    CoreOutcome::Changed
-                } else {
-                    CoreOutcome::StatusMessage("Pattern not found".into())
+            // Repeat last search, or search for the word under the cursor
+            NormalCommand::SearchNext | NormalCommand::SearchPrevious => {
+                let reverse = command == NormalCommand::SearchPrevious;
+                let found = self.search.repeat(&self.buffer, self.cursor, reverse);
+                self.jump_to_match(found)
+            }
+            NormalCommand::SearchWord(direction) => {
+                let found = self
+                    .search
+                    .search_word(&self.buffer, self.cursor, direction);
+                self.jump_to_match(found)
+            }
+
+            // Start a selection
+            NormalCommand::Visual(kind) => {
+                self.mode = kind.mode();
+                self.visual_anchor = self.cursor;
+                CoreOutcome::Changed
+            }
+
+            // Motions, operators, puts and inserts
+            command => {
+                if command.edits_buffer() {
+                    self.save_undo_snapshot();
                 }
+                let effect = self
+                    .normal
+                    .execute(command, &mut self.buffer, &mut self.cursor);
+                if effect.modified {
+                    self.dirty = true;
+                }
+                if effect.insert {
+                    self.mode = EditorMode::Insert;
+                }
+                CoreOutcome::Changed
             }
+        }
+    }
 
-            _ => CoreOutcome::Continue,
+    fn handle_visual_mode(&mut self, key: Key) -> CoreOutcome {
+        let ch = match key {
+            Key::Escape => {
+                self.normal.reset_pending();
+                self.leave_visual(EditorMode::Normal);
+                return CoreOutcome::Changed;
+            }
+            Key::CtrlV => VISUAL_BLOCK_KEY,
+            Key::Left => 'h',
+            Key::Down => 'j',
+            Key::Up => 'k',
+            Key::Right => 'l',
+            key => match key.as_char() {
+                Some(ch) => ch,
+                None => return CoreOutcome::Continue,
+            },
+        };
+
+        let Some(command) = self.normal.push_visual_key(ch) else {
+            return CoreOutcome::Continue;
+        };
+        match command {
+            VisualCommand::Move { motion, count } => {
+                self.cursor = motion.apply(&self.buffer, self.cursor, count);
+            }
+            VisualCommand::SwapEnds => {
+                core::mem::swap(&mut self.visual_anchor, &mut self.cursor);
+            }
+            VisualCommand::Switch(kind) if self.mode == kind.mode() => {
+                self.leave_visual(EditorMode::Normal);
+            }
+            VisualCommand::Switch(kind) => self.mode = kind.mode(),
+            VisualCommand::EnterCommand => {
+                self.leave_visual(EditorMode::Command);
+                self.command_buffer = "'<,'>".into();
+            }
+            VisualCommand::Operate { operator, register } => {
+                let Some(selection) = self.selection() else {
+                    return CoreOutcome::Continue;
+                };
+                if operator != Operator::Yank {
+                    self.save_undo_snapshot();
+                }
+                self.normal.mark_selection(&selection);
+                let effect = self.normal.operate_selection(
+                    operator,
+                    register,
+                    &selection,
+                    &mut self.buffer,
+                    &mut self.cursor,
+                );
+                if effect.modified {
+                    self.dirty = true;
+                }
+                self.mode = if effect.insert {
+                    EditorMode::Insert
+                } else {
+                    EditorMode::Normal
+                };
+            }
         }
+        CoreOutcome::Changed
+    }
+
+    /// Leave a Visual mode, remembering the selection as `'<` and `'>`
+    fn leave_visual(&mut self, mode: EditorMode) {
+        if let Some(selection) = self.selection() {
+            self.normal.mark_selection(&selection);
+        }
+        self.mode = mode;
     }
 
     fn handle_insert_mode(&mut self, key: Key) -> CoreOutcome {
@@ -252,6 +361,7 @@ impl EditorCore {
             Key::Escape => {
                 // Exit insert mode
                 self.mode = EditorMode::Normal;
+                self.normal.finish_insert();
                 // Move cursor back if possible (vi behavior)
                 if self.cursor.col > 0 {
                     self.cursor.col -= 1;
@@ -279,6 +389,7 @@ impl EditorCore {
                     self.cursor.row += 1;
                     self.cursor.col = 0;
                     self.dirty = true;
+                    self.normal.record_insert('\n');
                     CoreOutcome::Changed
                 } else {
                     CoreOutcome::Continue
@@ -289,6 +400,7 @@ impl EditorCore {
                 if let Some(new_pos) = self.buffer.backspace(self.cursor) {
                     self.cursor = new_pos;
                     self.dirty = true;
+                    self.normal.record_backspace();
                     CoreOutcome::Changed
                 } else {
                     CoreOutcome::Continue
@@ -320,15 +432,14 @@ impl EditorCore {
                 }
                 CoreOutcome::Changed
             }
-            Key::Char(ch) => {
-                self.command_buffer.push(ch);
-                CoreOutcome::Changed
-            }
-            Key::Space => {
-                self.command_buffer.push(' ');
-                CoreOutcome::Changed
-            }
-            _ => CoreOutcome::Continue,
+            // Every typing key, `d`, `/` and `:` included, goes into the line
+            key => match key.as_char() {
+                Some(ch) => {
+                    self.command_buffer.push(ch);
+                    CoreOutcome::Changed
+                }
+                None => CoreOutcome::Continue,
+            },
         }
     }
 
@@ -337,39 +448,54 @@ impl EditorCore {
             Key::Escape => {
                 // Cancel search
                 self.mode = EditorMode::Normal;
-                self.search_query.clear();
+                self.search.clear();
                 CoreOutcome::Changed
             }
             Key::Enter => {
                 // Execute search and exit search mode
-                let outcome = if self.find_next() {
-                    CoreOutcome::Changed
-                } else {
-                    CoreOutcome::StatusMessage("Pattern not found".into())
-                };
                 self.mode = EditorMode::Normal;
-                self.search_query.clear(); // Clear so 'n' knows it's a repeat
-                outcome
+                let found = self.search.submit(&self.buffer, self.cursor);
+                self.jump_to_match(found)
             }
             Key::Backspace => {
-                if !self.search_query.is_empty() {
-                    self.search_query.pop();
-                }
+                self.search.pop();
                 CoreOutcome::Changed
             }
-            Key::Char(ch) => {
-                self.search_query.push(ch);
+            // Step through earlier searches
+            Key::Up => {
+                self.search.history_older();
                 CoreOutcome::Changed
             }
-            Key::Space => {
-                self.search_query.push(' ');
+            Key::Down => {
+                self.search.history_newer();
                 CoreOutcome::Changed
             }
-            _ => CoreOutcome::Continue,
+            key => match key.as_char() {
+                Some(ch) => {
+                    self.search.push(ch);
+                    CoreOutcome::Changed
+                }
+                None => CoreOutcome::Continue,
+            },
+        }
+    }
+
+    fn jump_to_match(&mut self, found: Result<Position, CommandError>) -> CoreOutcome {
+        match found {
+            Ok(pos) => {
+                self.cursor = pos;
+                CoreOutcome::Changed
+            }
+            Err(error) => CoreOutcome::StatusMessage(error.to_string()),
         }
     }
 
     fn execute_command(&mut self) -> CoreOutcome {
+        match ExCommand::parse(&self.command_buffer) {
+            Ok(Some(command)) => return self.execute_ex(command),
+            Ok(None) => {}
+            Err(error) => return CoreOutcome::StatusMessage(error.to_string()),
+        }
         let cmd = parse_command(&self.command_buffer);
 
         match cmd {
@@ -397,13 +523,83 @@ impl EditorCore {
         }
     }
 
+    fn execute_ex(&mut self, command: ExCommand) -> CoreOutcome {
+        let result = match command {
+            ExCommand::Read { line, path } => {
+                let line = line.unwrap_or(Address::new(LineRef::Current)).resolve(
+                    &self.buffer,
+                    self.cursor,
+                    self.normal.marks(),
+                );
+                return match line {
+                    Ok(line) => CoreOutcome::RequestIo(CoreIoRequest::Read { path, line }),
+                    Err(error) => CoreOutcome::StatusMessage(error.to_string()),
+                };
+            }
+            ExCommand::Set(args) => {
+                return match self.options.apply(&args) {
+                    Some(shown) => CoreOutcome::StatusMessage(shown),
+                    None => CoreOutcome::Changed,
+                };
+            }
+            ExCommand::NoHighlight => {
+                self.search.clear_highlight();
+                return CoreOutcome::Changed;
+            }
+            command => {
+                let before = self.buffer_snapshot();
+                let result = self
+                    .normal
+                    .run_ex(&command, &mut self.buffer, &mut self.cursor);
+                if result.as_ref().is_ok_and(|effect| effect.modified) {
+                    self.push_undo_snapshot(before);
+                    self.dirty = true;
+                }
+                result
+            }
+        };
+        match result {
+            Ok(effect) => match effect.message {
+                Some(message) => CoreOutcome::StatusMessage(message),
+                None => CoreOutcome::Changed,
+            },
+            Err(error) => CoreOutcome::StatusMessage(error.to_string()),
+        }
+    }
+
+    /// Insert the text of a file read for [`CoreIoRequest::Read`]
+    pub fn insert_read(&mut self, line: usize, text: &str) -> CoreOutcome {
+        let before = self.buffer_snapshot();
+        let result = self.normal.read_into(
+            Some(Address::number(line)),
+            text,
+            &mut self.buffer,
+            &mut self.cursor,
+        );
+        match result {
+            Ok(effect) => {
+                self.push_undo_snapshot(before);
+                self.dirty = true;
+                CoreOutcome::StatusMessage(effect.message.unwrap_or_default())
+            }
+            Err(error) => CoreOutcome::StatusMessage(error.to_string()),
+        }
+    }
+
     // Undo/redo implementation
 
-    fn save_undo_snapshot(&mut self) {
-        let snapshot = BufferSnapshot {
+    fn buffer_snapshot(&self) -> BufferSnapshot {
+        BufferSnapshot {
             buffer: self.buffer.clone(),
             cursor: self.cursor,
-        };
+        }
+    }
+
+    fn save_undo_snapshot(&mut self) {
+        self.push_undo_snapshot(self.buffer_snapshot());
+    }
+
+    fn push_undo_snapshot(&mut self, snapshot: BufferSnapshot) {
         self.undo_stack.push(snapshot);
         // Clear redo stack on new edit
         self.redo_stack.clear();
@@ -453,101 +649,7 @@ impl EditorCore {
         }
     }
 
-    // Search implementation
-
-    fn find_next(&mut self) -> bool {
-        let query = if !self.search_query.is_empty() {
-            self.last_search = Some(self.search_query.clone());
-            &self.search_query
-        } else if let Some(ref last) = self.last_search {
-            last
-        } else {
-            return false;
-        };
-
-        if query.is_empty() {
-            return false;
-        }
-
-        let start_pos = self.cursor;
-        let line_count = self.buffer.line_count();
-
-        // Start from next position (for repeat search with 'n')
-        let start_col = if !self.search_query.is_empty() {
-            start_pos.col
-        } else {
-            start_pos.col + 1
-        };
-
-        // Search from start position forward
-        let mut row = start_pos.row;
-        let mut col = start_col;
-
-        while row < line_count {
-            if let Some(line) = self.buffer.line(row) {
-                if col < line.len() {
-                    if let Some(pos) = line[col..].find(query.as_str()) {
-                        self.cursor = Position::new(row, col + pos);
-                        return true;
-                    }
-                }
-            }
-            row += 1;
-            col = 0;
-        }
-
-        // Wrap around to beginning
-        for row in 0..start_pos.row {
-            if let Some(line) = self.buffer.line(row) {
-                if let Some(pos) = line.find(query.as_str()) {
-                    self.cursor = Position::new(row, pos);
-                    return true;
-                }
-            }
-        }
-
-        // Check start row up to start column (only for repeat search)
-        if self.search_query.is_empty() && start_pos.row < line_count {
-            if let Some(line) = self.buffer.line(start_pos.row) {
-                let end_col = start_pos.col.min(line.len());
-                if let Some(pos) = line[..end_col].find(query.as_str()) {
-                    self.cursor = Position::new(start_pos.row, pos);
-                    return true;
-                }
-            }
-        }
-
-        false
-    }
-
-    // Cursor movement
-
-    fn move_cursor_up(&mut self) {
-        if self.cursor.row > 0 {
-            self.cursor.row -= 1;
-            self.clamp_cursor();
-        }
-    }
-
-    fn move_cursor_down(&mut self) {
-        if self.cursor.row < self.buffer.line_count().saturating_sub(1) {
-            self.cursor.row += 1;
-            self.clamp_cursor();
-        }
-    }
-
-    fn move_cursor_left(&mut self) {
-        if self.cursor.col > 0 {
-            self.cursor.col -= 1;
-        }
-    }
-
-    fn move_cursor_right(&mut self) {
-        let line_len = self.buffer.line_length(self.cursor.row);
-        if self.cursor.col < line_len {
-            self.cursor.col += 1;
-        }
-    }
+    // Cursor clamping
 
     fn clamp_cursor(&mut self) {
         let line_len = self.buffer.line_length(self.cursor.row);
@@ -702,12 +804,63 @@ mod tests {
         let mut editor = EditorCore::new();
         editor.load_content("line1\nline2\nline3".into());
         editor.apply_key(Key::J); // Move to line 2
-        editor.apply_key(Key::D); // Delete line
+        editor.apply_key(Key::D); // Operator pending
+        assert_eq!(editor.buffer().line_count(), 3);
+        editor.apply_key(Key::D); // dd deletes the line
         assert_eq!(editor.buffer().line_count(), 2);
         assert_eq!(editor.buffer().line(0), Some("line1"));
         assert_eq!(editor.buffer().line(1), Some("line3"));
     }
 
+    fn type_keys(editor: &mut EditorCore, keys: &str) {
+        for byte in keys.bytes() {
+            editor.apply_key(Key::from_ascii(byte).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_vi_motions_and_operators() {
+        let mut editor = EditorCore::new();
+        editor.load_content("one two three\nfour five\nsix".into());
+
+        type_keys(&mut editor, "2w");
+        assert_eq!(editor.cursor(), Position::new(0, 8));
+        type_keys(&mut editor, "d$");
+        assert_eq!(editor.buffer().line(0), Some("one two "));
+
+        type_keys(&mut editor, "Gyyggp");
+        assert_eq!(editor.buffer().line(1), Some("six"));
+        assert_eq!(editor.cursor(), Position::new(1, 0));
+
+        type_keys(&mut editor, "jcwFOUR\x1b");
+        assert_eq!(editor.mode(), EditorMode::Normal);
+        assert_eq!(editor.buffer().line(2), Some("FOUR five"));
+
+        type_keys(&mut editor, "j0.");
+        assert_eq!(editor.buffer().line(3), Some("FOUR"));
+        assert!(editor.dirty());
+
+        type_keys(&mut editor, "u");
+        assert_eq!(editor.buffer().line(3), Some("six"));
+    }
+
+    #[test]
+    fn test_open_line_and_counted_delete() {
+        let mut editor = EditorCore::new();
+        editor.load_content("a\nb\nc\nd".into());
+
+        type_keys(&mut editor, "oab\x1b");
+        assert_eq!(editor.buffer().lines(), ["a", "ab", "b", "c", "d"]);
+        assert_eq!(editor.cursor(), Position::new(1, 1));
+
+        type_keys(&mut editor, "3dd");
+        assert_eq!(editor.buffer().lines(), ["a", "d"]);
+
+        // A half-typed command is dropped by Escape
+        type_keys(&mut editor, "d\x1bx");
+        assert_eq!(editor.buffer().lines(), ["a", ""]);
+    }
+
     #[test]
     fn test_undo_redo() {
         let mut editor = EditorCore::new();
@@ -844,6 +997,48 @@ mod tests {
         assert_eq!(editor.cursor().col, 8); // Second "foo"
     }
 
+    #[test]
+    fn test_regex_and_reverse_search() {
+        let mut editor = EditorCore::new();
+        editor.load_content("foo1 bar\nfoo22 baz\nbar foo1".into());
+
+        type_keys(&mut editor, "/fo+\\d{2}");
+        assert_eq!(editor.search_query(), "fo+\\d{2}");
+        let typing: Vec<_> = editor.search_spans(0..3).iter().map(|s| s.row).collect();
+        assert_eq!(typing, [1]);
+        type_keys(&mut editor, "\n");
+        assert_eq!(editor.cursor(), Position::new(1, 0));
+        assert_eq!(editor.search_spans(0..3).len(), 1);
+
+        type_keys(&mut editor, "?ba[rz]\n");
+        assert_eq!(editor.cursor(), Position::new(0, 5));
+        type_keys(&mut editor, "n");
+        assert_eq!(editor.cursor(), Position::new(2, 0));
+        type_keys(&mut editor, "N");
+        assert_eq!(editor.cursor(), Position::new(0, 5));
+
+        // Whole-word search for "foo1", skipping "foo22"
+        type_keys(&mut editor, "gg*");
+        assert_eq!(editor.cursor(), Position::new(2, 4));
+        type_keys(&mut editor, "#");
+        assert_eq!(editor.cursor(), Position::new(0, 0));
+        assert_eq!(editor.search().history().last(), Some("\\bfoo1\\b"));
+
+        // Up brings back earlier patterns
+        editor.apply_key(Key::Slash);
+        editor.apply_key(Key::Up);
+        editor.apply_key(Key::Up);
+        assert_eq!(editor.search_query(), "ba[rz]");
+        type_keys(&mut editor, "\x1b:noh\n");
+        assert!(editor.search_spans(0..3).is_empty());
+
+        type_keys(&mut editor, "/[");
+        assert_eq!(
+            editor.apply_key(Key::Enter),
+            CoreOutcome::StatusMessage("Invalid pattern: Unclosed character class at 0".into())
+        );
+    }
+
     #[test]
     fn test_search_wraps() {
         let mut editor = EditorCore::new();
@@ -914,6 +1109,96 @@ mod tests {
         assert_eq!(editor.cursor(), Position::new(0, 1)); // After current char
     }
 
+    #[test]
+    fn test_visual_selections() {
+        let mut editor = EditorCore::new();
+        editor.load_content("alpha beta\ngamma\ndelta".into());
+
+        type_keys(&mut editor, "wve");
+        assert_eq!(editor.mode(), EditorMode::Visual);
+        let selection = editor.selection().unwrap();
+        assert_eq!(
+            selection.ordered(),
+            (Position::new(0, 6), Position::new(0, 9))
+        );
+        type_keys(&mut editor, "d");
+        assert_eq!(editor.mode(), EditorMode::Normal);
+        assert_eq!(editor.buffer().line(0), Some("alpha "));
+        assert!(editor.selection().is_none());
+
+        type_keys(&mut editor, "jVjy");
+        assert_eq!(
+            editor.normal().registers().get(None).unwrap().text,
+            "gamma\ndelta"
+        );
+        assert!(editor.normal().registers().get(None).unwrap().linewise);
+
+        type_keys(&mut editor, "gg");
+        editor.apply_key(Key::CtrlV);
+        assert_eq!(editor.mode(), EditorMode::VisualBlock);
+        type_keys(&mut editor, "jjlx");
+        assert_eq!(editor.buffer().lines(), ["pha ", "mma", "lta"]);
+        assert_eq!(
+            editor.normal().registers().get(None).unwrap().text,
+            "al\nga\nde"
+        );
+
+        type_keys(&mut editor, "vl\x1b");
+        assert_eq!(editor.mode(), EditorMode::Normal);
+        assert_eq!(editor.buffer().line(0), Some("pha "));
+    }
+
+    #[test]
+    fn test_ex_commands() {
+        let mut editor = EditorCore::new();
+        editor.load_content("a1\nb1\na2\nb2".into());
+
+        type_keys(&mut editor, ":%s/a/x/\n");
+        assert_eq!(editor.buffer().lines(), ["x1", "b1", "x2", "b2"]);
+        assert_eq!(editor.status_message(), "");
+        assert!(editor.dirty());
+
+        type_keys(&mut editor, ":g/b/d\n:1\n");
+        assert_eq!(editor.buffer().lines(), ["x1", "x2"]);
+        assert_eq!(editor.cursor(), Position::zero());
+        type_keys(&mut editor, "u");
+        assert_eq!(editor.buffer().lines(), ["x1", "b1", "x2", "b2"]);
+
+        type_keys(&mut editor, "ggjVj:");
+        assert_eq!(editor.mode(), EditorMode::Command);
+        assert_eq!(editor.command_buffer(), "'<,'>");
+        type_keys(&mut editor, "m0\n");
+        assert_eq!(editor.buffer().lines(), ["b1", "x2", "x1", "b2"]);
+
+        assert_eq!(editor.apply_key(Key::Colon), CoreOutcome::Changed);
+        type_keys(&mut editor, "$r notes.txt");
+        assert_eq!(
+            editor.apply_key(Key::Enter),
+            CoreOutcome::RequestIo(CoreIoRequest::Read {
+                path: "notes.txt".into(),
+                line: 4
+            })
+        );
+        editor.insert_read(4, "n1\nn2\n");
+        assert_eq!(
+            editor.buffer().lines(),
+            ["b1", "x2", "x1", "b2", "n1", "n2"]
+        );
+
+        type_keys(&mut editor, ":set nu ts=4\n");
+        assert!(editor.options().number);
+        assert_eq!(editor.options().tabstop, 4);
+
+        let mut outcome = CoreOutcome::Continue;
+        for byte in b":9\n" {
+            outcome = editor.apply_key(Key::from_ascii(*byte).unwrap());
+        }
+        assert_eq!(
+            outcome,
+            CoreOutcome::StatusMessage("Line out of range: 9".into())
+        );
+    }
+
     #[test]
     fn test_escape_cancels_command() {
         let mut editor = EditorCore::new();
@@ -0,0 +1,1110 @@
+//! Ex command lines
+//!
+//! A command line may start with a range of lines: a line number, `.` for
+//! the cursor line, `$` for the last, `'x` for a mark, each optionally
+//! followed by `+n` or `-n`, with two of them separated by `,`; `%` stands
+//! for every line. [`ExCommand::parse`] reads the range and the commands
+//! that act on lines (`:s`, `:g`, `:d`, `:m`, `:r`, `:set`, `:nohlsearch`
+//! and a bare address to go to) and leaves every other name to the host,
+//! which parses its own commands (`:w`, `:q`, ...).
+//!
+//! [`NormalEngine::run_ex`] carries out the commands that only touch the
+//! buffer. Reading a file and setting options need the host: it loads the
+//! text and hands it to [`NormalEngine::read_into`], and applies `:set` to
+//! its [`EditorOptions`].
+//!
+//! Patterns are regular expressions, as in search; in a replacement `&`
+//! stands for the matched text.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use text_pattern::Regex;
+
+use crate::buffer::{Position, TextBuffer};
+use crate::command::CommandError;
+use crate::mark::Marks;
+use crate::normal::{NormalEngine, Operator};
+use crate::register::Registers;
+use crate::search;
+
+/// What an address counts from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineRef {
+    /// A line number; 1 is the first line and 0 the place before it
+    Number(usize),
+    /// `.`: the cursor line
+    Current,
+    /// `$`: the last line
+    Last,
+    /// `'x`: the line of mark `x`
+    Mark(char),
+}
+
+/// A line address, such as `12`, `.+2`, `$-1` or `'a`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address {
+    pub line: LineRef,
+    pub offset: i64,
+}
+
+impl Address {
+    pub fn new(line: LineRef) -> Self {
+        Self { line, offset: 0 }
+    }
+
+    /// Address of line `number`, counting from 1
+    pub fn number(number: usize) -> Self {
+        Self::new(LineRef::Number(number))
+    }
+
+    pub fn with_offset(mut self, offset: i64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// The line number the address names, 0 for before the first line
+    pub fn resolve(
+        &self,
+        buffer: &TextBuffer,
+        cursor: Position,
+        marks: &Marks,
+    ) -> Result<usize, CommandError> {
+        let base = match self.line {
+            LineRef::Number(number) => number as i64,
+            LineRef::Current => cursor.row as i64 + 1,
+            LineRef::Last => buffer.line_count() as i64,
+            LineRef::Mark(name) => {
+                let mark = marks.get(name).ok_or(CommandError::UnknownMark(name))?;
+                mark.row as i64 + 1
+            }
+        };
+        let line = base.saturating_add(self.offset);
+        if line < 0 || line > buffer.line_count() as i64 {
+            return Err(CommandError::LineOutOfRange(line));
+        }
+        Ok(line as usize)
+    }
+}
+
+/// The lines from one address to another, both included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LineRange {
+    pub start: Address,
+    pub end: Address,
+}
+
+impl LineRange {
+    pub fn new(start: Address, end: Address) -> Self {
+        Self { start, end }
+    }
+
+    /// A range of one line
+    pub fn single(address: Address) -> Self {
+        Self::new(address, address)
+    }
+
+    /// `%`: every line
+    pub fn whole() -> Self {
+        Self::new(Address::number(1), Address::new(LineRef::Last))
+    }
+
+    /// First and last row the range covers
+    ///
+    /// A backwards range is turned around, and line 0 counts as the first
+    /// line.
+    pub fn rows(
+        &self,
+        buffer: &TextBuffer,
+        cursor: Position,
+        marks: &Marks,
+    ) -> Result<(usize, usize), CommandError> {
+        let start = self.start.resolve(buffer, cursor, marks)?.max(1) - 1;
+        let end = self.end.resolve(buffer, cursor, marks)?.max(1) - 1;
+        Ok((start.min(end), start.max(end)))
+    }
+}
+
+/// An option `:set` can change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditorOption {
+    /// `number` / `nu`: show line numbers
+    Number,
+    /// `wrap`: wrap long lines
+    Wrap,
+    /// `tabstop` / `ts`: columns a tab takes
+    TabStop,
+}
+
+impl EditorOption {
+    /// Every option, in the order `:set` lists them
+    pub const ALL: [EditorOption; 3] = [
+        EditorOption::Number,
+        EditorOption::Wrap,
+        EditorOption::TabStop,
+    ];
+
+    /// Option with this full or short name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "number" | "nu" => Some(EditorOption::Number),
+            "wrap" => Some(EditorOption::Wrap),
+            "tabstop" | "ts" => Some(EditorOption::TabStop),
+            _ => None,
+        }
+    }
+
+    /// Full name
+    pub fn name(self) -> &'static str {
+        match self {
+            EditorOption::Number => "number",
+            EditorOption::Wrap => "wrap",
+            EditorOption::TabStop => "tabstop",
+        }
+    }
+
+    /// Whether the option is switched on and off rather than given a number
+    pub fn is_boolean(self) -> bool {
+        !matches!(self, EditorOption::TabStop)
+    }
+}
+
+/// Value of an option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptionValue {
+    Bool(bool),
+    Number(usize),
+}
+
+/// One argument to `:set`: an option and its new value, or `None` to show it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SetOption {
+    pub option: EditorOption,
+    pub value: Option<OptionValue>,
+}
+
+impl SetOption {
+    /// Parse `number`, `nonumber`, `number?`, `tabstop=4` or `tabstop`
+    pub fn parse(arg: &str) -> Result<Self, CommandError> {
+        let invalid = |option: EditorOption, value: &str| CommandError::InvalidOptionValue {
+            option: option.name().to_string(),
+            value: value.to_string(),
+        };
+
+        if let Some(name) = arg.strip_suffix('?') {
+            let option = lookup(name)?;
+            return Ok(Self {
+                option,
+                value: None,
+            });
+        }
+        if let Some((name, value)) = arg.split_once('=') {
+            let option = lookup(name)?;
+            return match value.parse::<usize>() {
+                Ok(number) if !option.is_boolean() && number > 0 => Ok(Self {
+                    option,
+                    value: Some(OptionValue::Number(number)),
+                }),
+                _ => Err(invalid(option, value)),
+            };
+        }
+        if let Some(option) = EditorOption::from_name(arg) {
+            let value = option.is_boolean().then_some(OptionValue::Bool(true));
+            return Ok(Self { option, value });
+        }
+        match arg.strip_prefix("no").and_then(EditorOption::from_name) {
+            Some(option) if option.is_boolean() => Ok(Self {
+                option,
+                value: Some(OptionValue::Bool(false)),
+            }),
+            Some(option) => Err(invalid(option, arg)),
+            None => Err(CommandError::UnknownOption(arg.to_string())),
+        }
+    }
+}
+
+fn lookup(name: &str) -> Result<EditorOption, CommandError> {
+    EditorOption::from_name(name).ok_or_else(|| CommandError::UnknownOption(name.to_string()))
+}
+
+/// Options a host keeps for `:set`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EditorOptions {
+    pub number: bool,
+    pub wrap: bool,
+    pub tabstop: usize,
+}
+
+impl EditorOptions {
+    pub fn get(&self, option: EditorOption) -> OptionValue {
+        match option {
+            EditorOption::Number => OptionValue::Bool(self.number),
+            EditorOption::Wrap => OptionValue::Bool(self.wrap),
+            EditorOption::TabStop => OptionValue::Number(self.tabstop),
+        }
+    }
+
+    /// Sets an option; a value of the wrong kind is ignored
+    pub fn set(&mut self, option: EditorOption, value: OptionValue) {
+        match (option, value) {
+            (EditorOption::Number, OptionValue::Bool(on)) => self.number = on,
+            (EditorOption::Wrap, OptionValue::Bool(on)) => self.wrap = on,
+            (EditorOption::TabStop, OptionValue::Number(columns)) => self.tabstop = columns,
+            _ => {}
+        }
+    }
+
+    /// How `:set` shows an option: `number`, `nowrap`, `tabstop=8`
+    pub fn show(&self, option: EditorOption) -> String {
+        match self.get(option) {
+            OptionValue::Bool(true) => option.name().to_string(),
+            OptionValue::Bool(false) => format!("no{}", option.name()),
+            OptionValue::Number(number) => format!("{}={}", option.name(), number),
+        }
+    }
+
+    /// Apply the arguments of a `:set`
+    ///
+    /// Returns the text to show for the options that were asked about, or
+    /// for every option when `:set` has no arguments.
+    pub fn apply(&mut self, args: &[SetOption]) -> Option<String> {
+        if args.is_empty() {
+            let all: Vec<String> = EditorOption::ALL.iter().map(|o| self.show(*o)).collect();
+            return Some(all.join(" "));
+        }
+        let mut shown = Vec::new();
+        for arg in args {
+            match arg.value {
+                Some(value) => self.set(arg.option, value),
+                None => shown.push(self.show(arg.option)),
+            }
+        }
+        (!shown.is_empty()).then(|| shown.join(" "))
+    }
+}
+
+impl Default for EditorOptions {
+    fn default() -> Self {
+        Self {
+            number: false,
+            wrap: true,
+            tabstop: 8,
+        }
+    }
+}
+
+/// A parsed ex command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExCommand {
+    /// `:N`, `:$`, `:'a`: move the cursor to a line
+    Goto(Address),
+    /// `:s/pattern/replacement/flags`: `g` replaces every match on a line
+    /// rather than the first, `i` ignores case
+    Substitute {
+        range: Option<LineRange>,
+        pattern: String,
+        replacement: String,
+        global: bool,
+        ignore_case: bool,
+    },
+    /// `:g/pattern/command` runs a command on every line that matches;
+    /// `:g!` and `:v` on every line that does not
+    Global {
+        range: Option<LineRange>,
+        pattern: String,
+        invert: bool,
+        command: Box<ExCommand>,
+    },
+    /// `:d [x]`: delete lines, into register `x` if given
+    Delete {
+        range: Option<LineRange>,
+        register: Option<char>,
+    },
+    /// `:m address`: move lines below `address` (0 for the top)
+    Move {
+        range: Option<LineRange>,
+        destination: Address,
+    },
+    /// `:r path`: insert a file's lines below a line (the cursor line if
+    /// none is given)
+    Read { line: Option<Address>, path: String },
+    /// `:set`: change or show options
+    Set(Vec<SetOption>),
+    /// `:noh`: stop highlighting matches of the last search
+    NoHighlight,
+}
+
+impl ExCommand {
+    /// Parse a command line, without the leading `:`
+    ///
+    /// Returns `Ok(None)` for commands that are not ex line commands, so the
+    /// host can try its own; with a range in front they are an error, since
+    /// none of the host's commands take one.
+    pub fn parse(line: &str) -> Result<Option<Self>, CommandError> {
+        let trimmed = line.trim();
+        let mut scanner = Scanner::new(trimmed);
+        let range = scanner.range()?;
+        scanner.skip_spaces();
+        let name = scanner.name();
+        let bang = scanner.eat('!');
+        let rest = scanner.rest();
+
+        let command = match name {
+            "" if !bang && rest.is_empty() => match range {
+                Some(range) => ExCommand::Goto(range.end),
+                None => return Ok(None),
+            },
+            "s" | "su" | "substitute" if !bang => parse_substitute(range, rest)?,
+            "g" | "global" => parse_global(range, rest, bang)?,
+            "v" | "vglobal" if !bang => parse_global(range, rest, true)?,
+            "d" | "de" | "del" | "delete" if !bang => {
+                let register = match rest.trim() {
+                    "" => None,
+                    name => {
+                        let mut chars = name.chars();
+                        match (chars.next(), chars.next()) {
+                            (Some(ch), None) if Registers::is_valid_name(ch) => Some(ch),
+                            _ => {
+                                return Err(CommandError::InvalidSyntax(format!(
+                                    "Invalid register: {}",
+                                    name
+                                )))
+                            }
+                        }
+                    }
+                };
+                ExCommand::Delete { range, register }
+            }
+            "m" | "mo" | "move" if !bang => {
+                let mut scanner = Scanner::new(rest.trim());
+                let destination = scanner.address()?.ok_or_else(|| {
+                    CommandError::InvalidSyntax("Missing destination".to_string())
+                })?;
+                if !scanner.rest().is_empty() {
+                    return Err(CommandError::InvalidSyntax(format!(
+                        "Trailing characters: {}",
+                        scanner.rest()
+                    )));
+                }
+                ExCommand::Move { range, destination }
+            }
+            "r" | "read" if !bang => {
+                let path = rest.trim();
+                if path.is_empty() {
+                    return Err(CommandError::InvalidSyntax("Missing file name".to_string()));
+                }
+                ExCommand::Read {
+                    line: range.map(|range| range.end),
+                    path: path.to_string(),
+                }
+            }
+            "se" | "set" if !bang => {
+                if range.is_some() {
+                    return Err(CommandError::InvalidRange("No range allowed".to_string()));
+                }
+                let args = rest
+                    .split_whitespace()
+                    .map(SetOption::parse)
+                    .collect::<Result<Vec<_>, _>>()?;
+                ExCommand::Set(args)
+            }
+            "noh" | "nohlsearch" if !bang => {
+                if range.is_some() {
+                    return Err(CommandError::InvalidRange("No range allowed".to_string()));
+                }
+                if !rest.trim().is_empty() {
+                    return Err(CommandError::InvalidSyntax(format!(
+                        "Trailing characters: {}",
+                        rest.trim()
+                    )));
+                }
+                ExCommand::NoHighlight
+            }
+            _ if range.is_some() => {
+                return Err(CommandError::UnknownCommand(trimmed.to_string()));
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(command))
+    }
+
+    /// Whether the command may run for each line of a `:g`
+    fn runs_under_global(&self) -> bool {
+        matches!(
+            self,
+            ExCommand::Substitute { .. } | ExCommand::Delete { .. } | ExCommand::Move { .. }
+        )
+    }
+}
+
+fn parse_substitute(range: Option<LineRange>, rest: &str) -> Result<ExCommand, CommandError> {
+    let mut scanner = Scanner::new(rest);
+    let delimiter = scanner.delimiter()?;
+    let pattern = scanner.delimited(delimiter);
+    if pattern.is_empty() {
+        return Err(CommandError::InvalidSyntax("Empty pattern".to_string()));
+    }
+    let replacement = scanner.delimited(delimiter);
+
+    let mut global = false;
+    let mut ignore_case = false;
+    for flag in scanner.rest().trim().chars() {
+        match flag {
+            'g' => global = true,
+            'i' => ignore_case = true,
+            'I' => ignore_case = false,
+            flag => {
+                return Err(CommandError::InvalidSyntax(format!(
+                    "Unknown flag: {}",
+                    flag
+                )))
+            }
+        }
+    }
+    Ok(ExCommand::Substitute {
+        range,
+        pattern,
+        replacement,
+        global,
+        ignore_case,
+    })
+}
+
+fn parse_global(
+    range: Option<LineRange>,
+    rest: &str,
+    invert: bool,
+) -> Result<ExCommand, CommandError> {
+    let mut scanner = Scanner::new(rest);
+    let delimiter = scanner.delimiter()?;
+    let pattern = scanner.delimited(delimiter);
+    if pattern.is_empty() {
+        return Err(CommandError::InvalidSyntax("Empty pattern".to_string()));
+    }
+    let text = scanner.rest().trim();
+    if text.is_empty() {
+        return Err(CommandError::InvalidSyntax(
+            "Missing command after pattern".to_string(),
+        ));
+    }
+    let command = match ExCommand::parse(text)? {
+        Some(command) if command.runs_under_global() => command,
+        Some(_) => {
+            return Err(CommandError::InvalidSyntax(format!(
+                "Cannot run under :global: {}",
+                text
+            )))
+        }
+        None => return Err(CommandError::UnknownCommand(text.to_string())),
+    };
+    Ok(ExCommand::Global {
+        range,
+        pattern,
+        invert,
+        command: Box::new(command),
+    })
+}
+
+/// Reads a command line from left to right
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&accept) {
+            self.next();
+        }
+        &self.text[start..self.pos]
+    }
+
+    fn name(&mut self) -> &'a str {
+        self.take_while(|ch| ch.is_ascii_alphabetic())
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let digits = self.take_while(|ch| ch.is_ascii_digit());
+        // Too many digits to fit is as out of range as any other huge line
+        (!digits.is_empty()).then(|| digits.parse().unwrap_or(usize::MAX))
+    }
+
+    /// `%`, or up to two addresses separated by `,`
+    fn range(&mut self) -> Result<Option<LineRange>, CommandError> {
+        if self.eat('%') {
+            return Ok(Some(LineRange::whole()));
+        }
+        let start = self.address()?;
+        if !self.eat(',') {
+            return Ok(start.map(LineRange::single));
+        }
+        let current = Address::new(LineRef::Current);
+        let end = self.address()?;
+        Ok(Some(LineRange::new(
+            start.unwrap_or(current),
+            end.unwrap_or(current),
+        )))
+    }
+
+    fn address(&mut self) -> Result<Option<Address>, CommandError> {
+        let line = match self.peek() {
+            Some(ch) if ch.is_ascii_digit() => self.number().map(LineRef::Number),
+            Some('.') => {
+                self.next();
+                Some(LineRef::Current)
+            }
+            Some('$') => {
+                self.next();
+                Some(LineRef::Last)
+            }
+            Some('\'') => {
+                self.next();
+                match self.next() {
+                    Some(name) if Marks::is_valid_name(name) => Some(LineRef::Mark(name)),
+                    _ => return Err(CommandError::InvalidSyntax("Invalid mark name".to_string())),
+                }
+            }
+            _ => None,
+        };
+
+        let mut offset: i64 = 0;
+        let mut has_offset = false;
+        while let Some(sign @ ('+' | '-')) = self.peek() {
+            self.next();
+            let amount = self.number().unwrap_or(1).min(i64::MAX as usize) as i64;
+            offset = if sign == '+' {
+                offset.saturating_add(amount)
+            } else {
+                offset.saturating_sub(amount)
+            };
+            has_offset = true;
+        }
+
+        Ok(match line {
+            Some(line) => Some(Address::new(line).with_offset(offset)),
+            // `+2` alone counts from the cursor line
+            None if has_offset => Some(Address::new(LineRef::Current).with_offset(offset)),
+            None => None,
+        })
+    }
+
+    fn delimiter(&mut self) -> Result<char, CommandError> {
+        match self.next() {
+            Some(ch) if !ch.is_alphanumeric() && !ch.is_whitespace() && ch != '\\' => Ok(ch),
+            _ => Err(CommandError::InvalidSyntax(
+                "Expected a pattern delimiter such as /".to_string(),
+            )),
+        }
+    }
+
+    /// Text up to an unescaped `delimiter`, which is consumed
+    ///
+    /// `\` before the delimiter makes it part of the text; other escapes are
+    /// kept as typed.
+    fn delimited(&mut self, delimiter: char) -> String {
+        let mut text = String::new();
+        while let Some(ch) = self.next() {
+            if ch == delimiter {
+                break;
+            }
+            if ch == '\\' && self.eat(delimiter) {
+                text.push(delimiter);
+                continue;
+            }
+            text.push(ch);
+        }
+        text
+    }
+}
+
+/// What an ex command did, for the host to follow up on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExEffect {
+    /// The buffer changed
+    pub modified: bool,
+    /// Text for the status line
+    pub message: Option<String>,
+}
+
+/// How a command moved lines around, so `:g` can find the lines it has
+/// still to visit
+enum LineShift {
+    None,
+    Removed {
+        first: usize,
+        count: usize,
+    },
+    Moved {
+        first: usize,
+        count: usize,
+        to: usize,
+    },
+}
+
+impl LineShift {
+    /// Where a row ends up, or `None` if it was removed
+    fn apply(&self, row: usize) -> Option<usize> {
+        match *self {
+            LineShift::None => Some(row),
+            LineShift::Removed { first, count } => {
+                if row < first {
+                    Some(row)
+                } else if row < first + count {
+                    None
+                } else {
+                    Some(row - count)
+                }
+            }
+            LineShift::Moved { first, count, to } => {
+                if (first..first + count).contains(&row) {
+                    return Some(to + row - first);
+                }
+                let row = if row > first { row - count } else { row };
+                Some(if row >= to { row + count } else { row })
+            }
+        }
+    }
+}
+
+impl NormalEngine {
+    /// Run an ex command on the buffer
+    ///
+    /// `:r` and `:set` are left to the host; for those this does nothing.
+    pub fn run_ex(
+        &mut self,
+        command: &ExCommand,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> Result<ExEffect, CommandError> {
+        self.run_ex_lines(command, buffer, cursor)
+            .map(|(effect, _)| effect)
+    }
+
+    /// Insert text read from a file below `line` (the cursor line if `None`)
+    pub fn read_into(
+        &mut self,
+        line: Option<Address>,
+        text: &str,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> Result<ExEffect, CommandError> {
+        let line = line.unwrap_or(Address::new(LineRef::Current));
+        let row = line.resolve(buffer, *cursor, self.marks())?;
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        let lines: Vec<String> = text.split('\n').map(String::from).collect();
+        let count = lines.len();
+        buffer.insert_lines(row, lines);
+        *cursor = Position::new(row, buffer.first_non_blank(row));
+        Ok(ExEffect {
+            modified: true,
+            message: Some(format!("{} lines read", count)),
+        })
+    }
+
+    fn run_ex_lines(
+        &mut self,
+        command: &ExCommand,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> Result<(ExEffect, LineShift), CommandError> {
+        let rows = |range: &Option<LineRange>, marks: &Marks| {
+            let range = range.unwrap_or(LineRange::single(Address::new(LineRef::Current)));
+            range.rows(buffer, *cursor, marks)
+        };
+        match command {
+            ExCommand::Goto(address) => {
+                let row = address.resolve(buffer, *cursor, self.marks())?.max(1) - 1;
+                *cursor = Position::new(row, buffer.first_non_blank(row));
+                Ok((ExEffect::default(), LineShift::None))
+            }
+            ExCommand::Substitute {
+                range,
+                pattern,
+                replacement,
+                global,
+                ignore_case,
+            } => {
+                let (first, last) = rows(range, self.marks())?;
+                let regex = search::compile(pattern)?.ignore_case(*ignore_case);
+                let mut substitutions = 0;
+                let mut lines = 0;
+                for row in first..=last {
+                    let line = buffer.line(row).unwrap_or_default();
+                    let (text, count) = substitute(line, &regex, replacement, *global);
+                    if count > 0 {
+                        buffer.replace_line(row, text);
+                        substitutions += count;
+                        lines += 1;
+                        *cursor = Position::new(row, buffer.first_non_blank(row));
+                    }
+                }
+                if substitutions == 0 {
+                    return Err(CommandError::PatternNotFound(pattern.clone()));
+                }
+                let effect = ExEffect {
+                    modified: true,
+                    message: Some(format!(
+                        "{} substitution{} on {} line{}",
+                        substitutions,
+                        plural(substitutions),
+                        lines,
+                        plural(lines)
+                    )),
+                };
+                Ok((effect, LineShift::None))
+            }
+            ExCommand::Global {
+                range,
+                pattern,
+                invert,
+                command,
+            } => {
+                let (first, last) =
+                    range
+                        .unwrap_or(LineRange::whole())
+                        .rows(buffer, *cursor, self.marks())?;
+                let regex = search::compile(pattern)?;
+                let mut pending: Vec<usize> = (first..=last)
+                    .filter(|row| {
+                        let line = buffer.line(*row).unwrap_or_default();
+                        regex.is_match(line) != *invert
+                    })
+                    .collect();
+                if pending.is_empty() {
+                    return Err(CommandError::PatternNotFound(pattern.clone()));
+                }
+
+                let mut modified = false;
+                while !pending.is_empty() {
+                    let row = pending.remove(0);
+                    *cursor = Position::new(row, 0);
+                    let shift = match self.run_ex_lines(command, buffer, cursor) {
+                        Ok((effect, shift)) => {
+                            modified |= effect.modified;
+                            shift
+                        }
+                        // A substitution that misses one line is fine here
+                        Err(CommandError::PatternNotFound(_)) => LineShift::None,
+                        Err(error) => return Err(error),
+                    };
+                    pending = pending.iter().filter_map(|row| shift.apply(*row)).collect();
+                }
+                let effect = ExEffect {
+                    modified,
+                    message: None,
+                };
+                Ok((effect, LineShift::None))
+            }
+            ExCommand::Delete { range, register } => {
+                let (first, last) = rows(range, self.marks())?;
+                self.operate_lines(Operator::Delete, *register, first, last, buffer, cursor);
+                let effect = ExEffect {
+                    modified: true,
+                    message: None,
+                };
+                let count = last - first + 1;
+                Ok((effect, LineShift::Removed { first, count }))
+            }
+            ExCommand::Move { range, destination } => {
+                let (first, last) = rows(range, self.marks())?;
+                let destination = destination.resolve(buffer, *cursor, self.marks())?;
+                if destination > first && destination <= last {
+                    return Err(CommandError::InvalidRange(
+                        "Cannot move lines into themselves".to_string(),
+                    ));
+                }
+                let count = last - first + 1;
+                // Right above or below where they are: nothing moves
+                if destination == first || destination == last + 1 {
+                    *cursor = Position::new(last, buffer.first_non_blank(last));
+                    return Ok((ExEffect::default(), LineShift::None));
+                }
+                let lines = buffer.remove_lines(first, last);
+                let to = if destination > last {
+                    destination - count
+                } else {
+                    destination
+                };
+                buffer.insert_lines(to, lines);
+                let row = to + count - 1;
+                *cursor = Position::new(row, buffer.first_non_blank(row));
+                let effect = ExEffect {
+                    modified: true,
+                    message: None,
+                };
+                Ok((effect, LineShift::Moved { first, count, to }))
+            }
+            ExCommand::Read { .. } | ExCommand::Set(_) | ExCommand::NoHighlight => {
+                Ok((ExEffect::default(), LineShift::None))
+            }
+        }
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+/// Replace the first match, or every match when `global`, returning the new
+/// line and the number of replacements
+fn substitute(line: &str, regex: &Regex, replacement: &str, global: bool) -> (String, usize) {
+    let mut result = String::new();
+    let mut count = 0;
+    let mut from = 0;
+    for found in regex.find_iter(line) {
+        result.push_str(&line[from..found.start]);
+        result.push_str(&expand_replacement(replacement, found.as_str(line)));
+        count += 1;
+        from = found.end;
+        if !global {
+            break;
+        }
+    }
+    result.push_str(&line[from..]);
+    (result, count)
+}
+
+/// `&` in a replacement stands for the matched text; `\` makes the next
+/// character literal
+fn expand_replacement(replacement: &str, matched: &str) -> String {
+    let mut text = String::new();
+    let mut chars = replacement.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '&' => text.push_str(matched),
+            '\\' => text.extend(chars.next()),
+            ch => text.push(ch),
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> ExCommand {
+        ExCommand::parse(line).unwrap().unwrap()
+    }
+
+    /// Run a command line on `text` with the cursor on `row`
+    fn run(text: &str, row: usize, line: &str) -> Result<(String, Position), CommandError> {
+        let mut buffer = TextBuffer::from_string(text.into());
+        let mut cursor = Position::new(row, 0);
+        let mut engine = NormalEngine::new();
+        engine.marks_mut().set('a', Position::new(1, 0));
+        let command = ExCommand::parse(line)?.unwrap();
+        engine.run_ex(&command, &mut buffer, &mut cursor)?;
+        Ok((buffer.as_string(), cursor))
+    }
+
+    #[test]
+    fn test_parse_ranges() {
+        let current = Address::new(LineRef::Current);
+        assert_eq!(parse("12"), ExCommand::Goto(Address::number(12)));
+        assert_eq!(
+            parse("$-1"),
+            ExCommand::Goto(Address::new(LineRef::Last).with_offset(-1))
+        );
+        assert_eq!(
+            parse(",+2d x"),
+            ExCommand::Delete {
+                range: Some(LineRange::new(current, current.with_offset(2))),
+                register: Some('x')
+            }
+        );
+        assert_eq!(
+            parse("'a,'<m0"),
+            ExCommand::Move {
+                range: Some(LineRange::new(
+                    Address::new(LineRef::Mark('a')),
+                    Address::new(LineRef::Mark('<'))
+                )),
+                destination: Address::number(0)
+            }
+        );
+        assert_eq!(
+            parse("%s#a\\#b#c#gi"),
+            ExCommand::Substitute {
+                range: Some(LineRange::whole()),
+                pattern: "a#b".into(),
+                replacement: "c".into(),
+                global: true,
+                ignore_case: true
+            }
+        );
+        assert_eq!(ExCommand::parse("wq"), Ok(None));
+        assert_eq!(ExCommand::parse("q!"), Ok(None));
+        assert_eq!(
+            ExCommand::parse("1,2w"),
+            Err(CommandError::UnknownCommand("1,2w".into()))
+        );
+        assert!(matches!(
+            ExCommand::parse("s/a/b/z"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
+        assert!(matches!(
+            ExCommand::parse("g/a/g/b/d"),
+            Err(CommandError::InvalidSyntax(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_set() {
+        assert_eq!(
+            parse("set nu nowrap ts=4 tabstop?"),
+            ExCommand::Set(alloc::vec![
+                SetOption {
+                    option: EditorOption::Number,
+                    value: Some(OptionValue::Bool(true))
+                },
+                SetOption {
+                    option: EditorOption::Wrap,
+                    value: Some(OptionValue::Bool(false))
+                },
+                SetOption {
+                    option: EditorOption::TabStop,
+                    value: Some(OptionValue::Number(4))
+                },
+                SetOption {
+                    option: EditorOption::TabStop,
+                    value: None
+                },
+            ])
+        );
+        assert_eq!(
+            ExCommand::parse("set ts=0"),
+            Err(CommandError::InvalidOptionValue {
+                option: "tabstop".into(),
+                value: "0".into()
+            })
+        );
+        assert_eq!(
+            ExCommand::parse("set spell"),
+            Err(CommandError::UnknownOption("spell".into()))
+        );
+
+        let mut options = EditorOptions::default();
+        let ExCommand::Set(args) = parse("set nu ts=2 ts? wrap?") else {
+            panic!("Expected Set");
+        };
+        assert_eq!(options.apply(&args), Some("tabstop=2 wrap".into()));
+        assert_eq!(options.apply(&[]), Some("number wrap tabstop=2".into()));
+
+        assert_eq!(parse("noh"), ExCommand::NoHighlight);
+        assert!(matches!(
+            ExCommand::parse("%nohlsearch"),
+            Err(CommandError::InvalidRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_goto_and_ranges() {
+        let text = "one\ntwo\nthree\nfour";
+        assert_eq!(run(text, 0, "3").unwrap().1, Position::new(2, 0));
+        assert_eq!(run(text, 3, "'a").unwrap().1, Position::new(1, 0));
+        assert_eq!(run(text, 0, "5"), Err(CommandError::LineOutOfRange(5)));
+        assert_eq!(run(text, 0, ".-2"), Err(CommandError::LineOutOfRange(-1)));
+        assert_eq!(run(text, 0, "'q"), Err(CommandError::UnknownMark('q')));
+        assert_eq!(run(text, 0, "3,2d").unwrap().0, "one\nfour");
+        assert_eq!(run(text, 0, "'a,$d").unwrap().0, "one");
+    }
+
+    #[test]
+    fn test_substitute() {
+        let text = "a.a.A\nbab\nccc";
+        assert_eq!(run(text, 0, "s/a/x/").unwrap().0, "x.a.A\nbab\nccc");
+        assert_eq!(
+            run(text, 0, "%s/a/[&]/gi").unwrap().0,
+            "[a].[a].[A]\nb[a]b\nccc"
+        );
+        assert_eq!(run(text, 0, "%s/a/\\&/g").unwrap().0, "&.&.A\nb&b\nccc");
+        assert_eq!(run(text, 0, "s/\\.a*/-/g").unwrap().0, "a--A\nbab\nccc");
+        assert_eq!(run(text, 1, "s/^/> /").unwrap().0, "a.a.A\n> bab\nccc");
+        assert_eq!(run(text, 2, "s/c$/C/").unwrap().0, "a.a.A\nbab\nccC");
+        assert!(matches!(
+            run(text, 0, "s/(/x/"),
+            Err(CommandError::InvalidPattern(_))
+        ));
+        assert_eq!(
+            run(text, 0, "2,3s/q/x/"),
+            Err(CommandError::PatternNotFound("q".into()))
+        );
+    }
+
+    #[test]
+    fn test_global_delete_and_move() {
+        let text = "a1\nb1\na2\nb2\na3";
+        assert_eq!(run(text, 0, "g/a/d").unwrap().0, "b1\nb2");
+        assert_eq!(run(text, 0, "v/a/d").unwrap().0, "a1\na2\na3");
+        assert_eq!(run(text, 0, "g/a/m$").unwrap().0, "b1\nb2\na1\na2\na3");
+        assert_eq!(run(text, 0, "g/2/s/2/!/").unwrap().0, "a1\nb1\na!\nb!\na3");
+        assert_eq!(run(text, 0, "g/^b|3$/d").unwrap().0, "a1\na2");
+        assert_eq!(
+            run(text, 0, "1,2m$").unwrap(),
+            ("a2\nb2\na3\na1\nb1".into(), Position::new(4, 0))
+        );
+        assert_eq!(run(text, 4, "m0").unwrap().0, "a3\na1\nb1\na2\nb2");
+        assert!(matches!(
+            run(text, 0, "1,3m2"),
+            Err(CommandError::InvalidRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_into() {
+        let mut buffer = TextBuffer::from_string("one\ntwo".into());
+        let mut cursor = Position::zero();
+        let mut engine = NormalEngine::new();
+        let effect = engine
+            .read_into(Some(Address::number(0)), "x\ny\n", &mut buffer, &mut cursor)
+            .unwrap();
+        assert_eq!(buffer.as_string(), "x\ny\none\ntwo");
+        assert_eq!(effect.message.as_deref(), Some("2 lines read"));
+        engine
+            .read_into(None, "z", &mut buffer, &mut cursor)
+            .unwrap();
+        assert_eq!(buffer.as_string(), "x\nz\ny\none\ntwo");
+        assert_eq!(cursor, Position::new(1, 0));
+    }
+}
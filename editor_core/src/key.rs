@@ -42,6 +42,8 @@ pub enum Key {
 
     // Modifiers (for Ctrl+R etc)
     CtrlR,
+    /// Ctrl+V, which starts a block selection
+    CtrlV,
 }
 
 impl Key {
@@ -49,6 +51,7 @@ impl Key {
     pub fn from_ascii(byte: u8) -> Option<Self> {
         match byte {
             0x1B => Some(Key::Escape),
+            0x16 => Some(Key::CtrlV),
             0x08 | 0x7F => Some(Key::Backspace),
             b'\r' | b'\n' => Some(Key::Enter),
             b'\t' => Some(Key::Tab),
@@ -69,6 +72,27 @@ impl Key {
             _ => None,
         }
     }
+
+    /// Character the key types, if it types one
+    pub fn as_char(&self) -> Option<char> {
+        match self {
+            Key::Char(ch) => Some(*ch),
+            Key::Space => Some(' '),
+            Key::H => Some('h'),
+            Key::J => Some('j'),
+            Key::K => Some('k'),
+            Key::L => Some('l'),
+            Key::I => Some('i'),
+            Key::A => Some('a'),
+            Key::X => Some('x'),
+            Key::D => Some('d'),
+            Key::U => Some('u'),
+            Key::N => Some('n'),
+            Key::Colon => Some(':'),
+            Key::Slash => Some('/'),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -82,7 +106,17 @@ mod tests {
         assert_eq!(Key::from_ascii(b' '), Some(Key::Space));
         assert_eq!(Key::from_ascii(b':'), Some(Key::Colon));
         assert_eq!(Key::from_ascii(0x1B), Some(Key::Escape));
+        assert_eq!(Key::from_ascii(0x16), Some(Key::CtrlV));
         assert_eq!(Key::from_ascii(b'a'), Some(Key::A));
         assert_eq!(Key::from_ascii(b'Z'), Some(Key::Char('Z')));
     }
+
+    #[test]
+    fn test_as_char_round_trips_printable_bytes() {
+        for byte in 0x20..0x7Fu8 {
+            let key = Key::from_ascii(byte).unwrap();
+            assert_eq!(key.as_char(), Some(byte as char));
+        }
+        assert_eq!(Key::Escape.as_char(), None);
+    }
 }
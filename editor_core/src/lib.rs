@@ -8,7 +8,7 @@
 //!
 //! - **No_std compatible**: Uses alloc but not std
 //! - **Deterministic**: Same input trace => same editor state
-//! - **Modal editing**: Normal, Insert, Command, Search modes
+//! - **Modal editing**: Normal, Insert, Command, Search and Visual modes
 //! - **Mechanism over policy**: Core provides editing primitives, hosts decide rendering
 //! - **No ambient authority**: IO requests are explicit, never automatic
 //!
@@ -19,19 +19,41 @@
 //! - CoreOutcome: Structured results from operations
 //! - EditorSnapshot: Deterministic state for parity testing
 //! - Key event abstraction: Platform-independent input representation
+//! - NormalEngine: vi counts, motions and operators, shared by every host
+//! - Registers: unnamed, named and clipboard yank registers
+//! - Selection: character, line and block selections for Visual mode
+//! - ExCommand: line ranges, `:s`, `:g`, `:d`, `:m`, `:r` and `:set`
+//! - SearchState: regex search both ways, `*`/`#`, match highlights and history
 
 extern crate alloc;
 
 pub mod buffer;
 pub mod command;
 pub mod core;
+pub mod ex;
 pub mod key;
+pub mod mark;
 pub mod mode;
+pub mod motion;
+pub mod normal;
+pub mod register;
+pub mod search;
 pub mod snapshot;
+pub mod visual;
 
 pub use buffer::{Position, TextBuffer};
-pub use command::{Command, CommandOutcome};
+pub use command::{Command, CommandError, CommandOutcome};
 pub use core::{CoreIoRequest, CoreOutcome, EditorCore};
+pub use ex::{
+    Address, EditorOption, EditorOptions, ExCommand, ExEffect, LineRange, LineRef, OptionValue,
+    SetOption,
+};
 pub use key::Key;
+pub use mark::{Marks, SELECTION_END_MARK, SELECTION_START_MARK};
 pub use mode::EditorMode;
+pub use motion::Motion;
+pub use normal::{InsertPoint, NormalCommand, NormalEffect, NormalEngine, NormalParser, Operator};
+pub use register::{RegisterContent, Registers, CLIPBOARD_REGISTER, UNNAMED_REGISTER};
+pub use search::{SearchDirection, SearchHistory, SearchState, SEARCH_HISTORY_LIMIT};
 pub use snapshot::EditorSnapshot;
+pub use visual::{LineSpan, Selection, VisualCommand, VisualKind, VisualParser, VISUAL_BLOCK_KEY};
@@ -0,0 +1,70 @@
+//! Line marks
+//!
+//! `ma` remembers the cursor position under the name `a` and `'a` jumps back
+//! to its line. Ex ranges use marks as addresses (`:'a,'bd`), and leaving
+//! Visual mode sets `'<` and `'>` to the first and last line of the
+//! selection so `:'<,'>` acts on what was selected.
+
+use alloc::collections::BTreeMap;
+
+use crate::buffer::Position;
+
+/// Mark set to the start of the last selection
+pub const SELECTION_START_MARK: char = '<';
+
+/// Mark set to the end of the last selection
+pub const SELECTION_END_MARK: char = '>';
+
+/// The marks of one editor
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Marks {
+    marks: BTreeMap<char, Position>,
+}
+
+impl Marks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `m` can set a mark by this name
+    pub fn is_settable(name: char) -> bool {
+        name.is_ascii_lowercase()
+    }
+
+    /// Whether `'` can name this mark
+    pub fn is_valid_name(name: char) -> bool {
+        Self::is_settable(name) || name == SELECTION_START_MARK || name == SELECTION_END_MARK
+    }
+
+    /// Position of a mark, if it has been set
+    pub fn get(&self, name: char) -> Option<Position> {
+        self.marks.get(&name).copied()
+    }
+
+    /// Sets a mark; returns false for names marks cannot have
+    pub fn set(&mut self, name: char, pos: Position) -> bool {
+        if !Self::is_valid_name(name) {
+            return false;
+        }
+        self.marks.insert(name, pos);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_get_marks() {
+        let mut marks = Marks::new();
+        assert!(marks.set('a', Position::new(3, 1)));
+        assert!(marks.set(SELECTION_END_MARK, Position::new(5, 0)));
+        assert!(!marks.set('A', Position::new(1, 0)));
+        assert!(!Marks::is_settable(SELECTION_START_MARK));
+
+        assert_eq!(marks.get('a'), Some(Position::new(3, 1)));
+        assert_eq!(marks.get('>'), Some(Position::new(5, 0)));
+        assert_eq!(marks.get('b'), None);
+    }
+}
@@ -1,5 +1,7 @@
 //! Editor modes
 
+use crate::visual::VisualKind;
+
 #[cfg(feature = "serde_support")]
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +17,12 @@ pub enum EditorMode {
     Command,
     /// Search mode (search prompt)
     Search,
+    /// Visual mode (`v`, character selection)
+    Visual,
+    /// Visual Line mode (`V`, whole-line selection)
+    VisualLine,
+    /// Visual Block mode (Ctrl+V, column selection)
+    VisualBlock,
 }
 
 impl EditorMode {
@@ -24,8 +32,25 @@ impl EditorMode {
             EditorMode::Insert => "INSERT",
             EditorMode::Command => "COMMAND",
             EditorMode::Search => "SEARCH",
+            EditorMode::Visual => "VISUAL",
+            EditorMode::VisualLine => "VISUAL LINE",
+            EditorMode::VisualBlock => "VISUAL BLOCK",
         }
     }
+
+    /// Kind of selection the mode shows, if it is a Visual mode
+    pub fn visual_kind(&self) -> Option<VisualKind> {
+        match self {
+            EditorMode::Visual => Some(VisualKind::Char),
+            EditorMode::VisualLine => Some(VisualKind::Line),
+            EditorMode::VisualBlock => Some(VisualKind::Block),
+            _ => None,
+        }
+    }
+
+    pub fn is_visual(&self) -> bool {
+        self.visual_kind().is_some()
+    }
 }
 
 #[cfg(test)]
@@ -38,5 +63,8 @@ mod tests {
         assert_eq!(EditorMode::Insert.as_str(), "INSERT");
         assert_eq!(EditorMode::Command.as_str(), "COMMAND");
         assert_eq!(EditorMode::Search.as_str(), "SEARCH");
+        assert_eq!(EditorMode::VisualBlock.as_str(), "VISUAL BLOCK");
+        assert_eq!(EditorMode::VisualLine.visual_kind(), Some(VisualKind::Line));
+        assert!(!EditorMode::Insert.is_visual());
     }
 }
@@ -0,0 +1,305 @@
+//! Cursor motions
+//!
+//! A motion moves the cursor on its own, and gives an operator such as `d`
+//! the span of text to act on. Word motions follow vi: a word is a run of
+//! letters, digits and underscores, or a run of other non-blank characters,
+//! and an empty line counts as a word of its own.
+
+use crate::buffer::{Position, TextBuffer};
+
+#[cfg(feature = "serde_support")]
+use serde::{Deserialize, Serialize};
+
+/// A cursor motion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub enum Motion {
+    /// `h`: one column left
+    Left,
+    /// `l`: one column right
+    Right,
+    /// `k`: one line up
+    Up,
+    /// `j`: one line down
+    Down,
+    /// `w`: start of the next word
+    WordForward,
+    /// `b`: start of the previous word
+    WordBackward,
+    /// `e`: end of the next word
+    WordEnd,
+    /// `0`: start of the line
+    LineStart,
+    /// `$`: end of the line
+    LineEnd,
+    /// `gg`: first line, or line N with a count
+    FirstLine,
+    /// `G`: last line, or line N with a count
+    LastLine,
+}
+
+impl Motion {
+    /// Motion bound to a single key, if any
+    ///
+    /// `gg` takes two keys and is recognized by the
+    /// [`NormalParser`](crate::normal::NormalParser).
+    pub fn from_key(ch: char) -> Option<Self> {
+        match ch {
+            'h' => Some(Motion::Left),
+            'l' | ' ' => Some(Motion::Right),
+            'k' => Some(Motion::Up),
+            'j' => Some(Motion::Down),
+            'w' => Some(Motion::WordForward),
+            'b' => Some(Motion::WordBackward),
+            'e' => Some(Motion::WordEnd),
+            '0' => Some(Motion::LineStart),
+            '$' => Some(Motion::LineEnd),
+            'G' => Some(Motion::LastLine),
+            _ => None,
+        }
+    }
+
+    /// Whether an operator applied with this motion acts on whole lines
+    pub fn is_linewise(self) -> bool {
+        matches!(
+            self,
+            Motion::Up | Motion::Down | Motion::FirstLine | Motion::LastLine
+        )
+    }
+
+    /// Whether the character under the motion's target is part of the span
+    pub fn is_inclusive(self) -> bool {
+        matches!(self, Motion::WordEnd | Motion::LineEnd)
+    }
+
+    /// Where the motion takes the cursor
+    ///
+    /// The count repeats the motion, except for `gg` and `G`, where it names
+    /// a line.
+    pub fn apply(self, buffer: &TextBuffer, from: Position, count: Option<usize>) -> Position {
+        let from = buffer.clamp(from);
+        let times = count.unwrap_or(1).max(1);
+        let last_row = buffer.line_count() - 1;
+        match self {
+            Motion::Left => Position::new(from.row, from.col.saturating_sub(times)),
+            Motion::Right => buffer.clamp(Position::new(from.row, from.col.saturating_add(times))),
+            Motion::Up => buffer.clamp(Position::new(from.row.saturating_sub(times), from.col)),
+            Motion::Down => buffer.clamp(Position::new(from.row.saturating_add(times), from.col)),
+            Motion::WordForward => repeat(from, times, |pos| word_forward(buffer, pos)),
+            Motion::WordBackward => repeat(from, times, |pos| word_backward(buffer, pos)),
+            Motion::WordEnd => repeat(from, times, |pos| word_end(buffer, pos)),
+            Motion::LineStart => Position::new(from.row, 0),
+            Motion::LineEnd => {
+                let row = from.row.saturating_add(times - 1).min(last_row);
+                Position::new(row, buffer.line_length(row).saturating_sub(1))
+            }
+            Motion::FirstLine | Motion::LastLine => {
+                let row = match (self, count) {
+                    (_, Some(line)) => line.saturating_sub(1).min(last_row),
+                    (Motion::FirstLine, None) => 0,
+                    _ => last_row,
+                };
+                Position::new(row, buffer.first_non_blank(row))
+            }
+        }
+    }
+}
+
+fn repeat(from: Position, times: usize, step: impl Fn(Position) -> Position) -> Position {
+    let mut pos = from;
+    for _ in 0..times {
+        let next = step(pos);
+        if next == pos {
+            break;
+        }
+        pos = next;
+    }
+    pos
+}
+
+/// What sits at a position, for word motions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    /// An empty line
+    Empty,
+    Blank,
+    Word,
+    Punctuation,
+}
+
+fn class_at(buffer: &TextBuffer, pos: Position) -> CharClass {
+    match buffer
+        .line(pos.row)
+        .and_then(|line| line.as_bytes().get(pos.col))
+    {
+        None => CharClass::Empty,
+        Some(b' ' | b'\t') => CharClass::Blank,
+        Some(&byte) if byte.is_ascii_alphanumeric() || byte == b'_' || !byte.is_ascii() => {
+            CharClass::Word
+        }
+        Some(_) => CharClass::Punctuation,
+    }
+}
+
+/// The next character, or the start of the next line
+fn step_forward(buffer: &TextBuffer, pos: Position) -> Option<Position> {
+    if pos.col + 1 < buffer.line_length(pos.row) {
+        Some(Position::new(pos.row, pos.col + 1))
+    } else if pos.row + 1 < buffer.line_count() {
+        Some(Position::new(pos.row + 1, 0))
+    } else {
+        None
+    }
+}
+
+/// The previous character, or the end of the previous line
+fn step_backward(buffer: &TextBuffer, pos: Position) -> Option<Position> {
+    if pos.col > 0 {
+        Some(Position::new(pos.row, pos.col - 1))
+    } else if pos.row > 0 {
+        let row = pos.row - 1;
+        Some(Position::new(
+            row,
+            buffer.line_length(row).saturating_sub(1),
+        ))
+    } else {
+        None
+    }
+}
+
+/// Start of the next word; the end of the buffer if there is none
+pub(crate) fn word_forward(buffer: &TextBuffer, from: Position) -> Position {
+    let start = class_at(buffer, from);
+    let mut pos = from;
+    let mut left_word = false;
+    loop {
+        let Some(next) = step_forward(buffer, pos) else {
+            return Position::new(pos.row, buffer.line_length(pos.row));
+        };
+        left_word |= next.row != pos.row;
+        pos = next;
+        match class_at(buffer, pos) {
+            CharClass::Empty => return pos,
+            CharClass::Blank => left_word = true,
+            class if left_word || class != start => return pos,
+            _ => {}
+        }
+    }
+}
+
+/// Start of the current or previous word
+pub(crate) fn word_backward(buffer: &TextBuffer, from: Position) -> Position {
+    let mut pos = from;
+    loop {
+        let Some(previous) = step_backward(buffer, pos) else {
+            return pos;
+        };
+        pos = previous;
+        if class_at(buffer, pos) != CharClass::Blank {
+            break;
+        }
+    }
+    word_start(buffer, pos)
+}
+
+/// End of the next word, skipping the rest of the current one
+pub(crate) fn word_end(buffer: &TextBuffer, from: Position) -> Position {
+    let mut pos = from;
+    loop {
+        let Some(next) = step_forward(buffer, pos) else {
+            return from;
+        };
+        pos = next;
+        if !matches!(class_at(buffer, pos), CharClass::Blank | CharClass::Empty) {
+            break;
+        }
+    }
+    current_word_end(buffer, pos)
+}
+
+/// Last character of the word at a position
+pub(crate) fn current_word_end(buffer: &TextBuffer, from: Position) -> Position {
+    let class = class_at(buffer, from);
+    let mut pos = from;
+    while pos.col + 1 < buffer.line_length(pos.row)
+        && class_at(buffer, Position::new(pos.row, pos.col + 1)) == class
+    {
+        pos.col += 1;
+    }
+    pos
+}
+
+fn word_start(buffer: &TextBuffer, from: Position) -> Position {
+    let class = class_at(buffer, from);
+    let mut pos = from;
+    while pos.col > 0 && class_at(buffer, Position::new(pos.row, pos.col - 1)) == class {
+        pos.col -= 1;
+    }
+    pos
+}
+
+/// Whether a position sits on a blank or an empty line
+pub(crate) fn is_blank(buffer: &TextBuffer, pos: Position) -> bool {
+    matches!(class_at(buffer, pos), CharClass::Blank | CharClass::Empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(text: &str) -> TextBuffer {
+        TextBuffer::from_string(text.into())
+    }
+
+    #[test]
+    fn test_word_motions() {
+        let buffer = buffer("foo.bar  baz\n\n  qux");
+        let at = |row, col| Position::new(row, col);
+
+        assert_eq!(Motion::WordForward.apply(&buffer, at(0, 0), None), at(0, 3));
+        assert_eq!(
+            Motion::WordForward.apply(&buffer, at(0, 0), Some(3)),
+            at(0, 9)
+        );
+        assert_eq!(Motion::WordForward.apply(&buffer, at(0, 9), None), at(1, 0));
+        assert_eq!(Motion::WordForward.apply(&buffer, at(1, 0), None), at(2, 2));
+        assert_eq!(Motion::WordForward.apply(&buffer, at(2, 2), None), at(2, 5));
+
+        assert_eq!(
+            Motion::WordBackward.apply(&buffer, at(2, 2), None),
+            at(1, 0)
+        );
+        assert_eq!(
+            Motion::WordBackward.apply(&buffer, at(1, 0), None),
+            at(0, 9)
+        );
+        assert_eq!(
+            Motion::WordBackward.apply(&buffer, at(0, 5), None),
+            at(0, 4)
+        );
+        assert_eq!(
+            Motion::WordBackward.apply(&buffer, at(0, 0), None),
+            at(0, 0)
+        );
+
+        assert_eq!(Motion::WordEnd.apply(&buffer, at(0, 0), None), at(0, 2));
+        assert_eq!(Motion::WordEnd.apply(&buffer, at(0, 2), None), at(0, 3));
+        assert_eq!(Motion::WordEnd.apply(&buffer, at(0, 9), None), at(0, 11));
+        assert_eq!(Motion::WordEnd.apply(&buffer, at(0, 11), None), at(2, 4));
+    }
+
+    #[test]
+    fn test_line_motions() {
+        let buffer = buffer("one\n  two\nthree");
+        let at = |row, col| Position::new(row, col);
+
+        assert_eq!(Motion::LineEnd.apply(&buffer, at(0, 0), None), at(0, 2));
+        assert_eq!(Motion::LineEnd.apply(&buffer, at(0, 0), Some(2)), at(1, 4));
+        assert_eq!(Motion::LineStart.apply(&buffer, at(1, 3), None), at(1, 0));
+        assert_eq!(Motion::LastLine.apply(&buffer, at(0, 0), None), at(2, 0));
+        assert_eq!(Motion::LastLine.apply(&buffer, at(0, 0), Some(2)), at(1, 2));
+        assert_eq!(Motion::FirstLine.apply(&buffer, at(2, 3), None), at(0, 0));
+        assert_eq!(Motion::Down.apply(&buffer, at(0, 2), Some(5)), at(2, 2));
+        assert_eq!(Motion::Right.apply(&buffer, at(0, 1), Some(9)), at(0, 3));
+    }
+}
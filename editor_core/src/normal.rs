@@ -0,0 +1,1108 @@
+//! Normal-mode command grammar
+//!
+//! Keys typed in Normal mode build commands the way vi reads them: an
+//! optional count, then either an operator (`d`, `c`, `y`) followed by a
+//! motion, or a command of its own (`x`, `p`, `o`, ...), any of them
+//! optionally naming a register with `"x` first. [`NormalParser`] turns keys
+//! into [`NormalCommand`]s and [`NormalEngine`] carries them out on a
+//! [`TextBuffer`], keeping the [`Registers`], the [`Marks`] and the last
+//! change for `.`.
+//!
+//! Hosts act on the commands that concern them (undo, entering Command,
+//! Search or Visual mode) and hand everything else to the engine, so every
+//! editor built on the core edits text the same way. In Visual mode keys go to
+//! [`NormalEngine::push_visual_key`] instead, and operators act on the
+//! [`Selection`].
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::buffer::{Position, TextBuffer};
+use crate::mark::{Marks, SELECTION_END_MARK, SELECTION_START_MARK};
+use crate::motion::{self, Motion};
+use crate::register::{RegisterContent, Registers};
+use crate::search::SearchDirection;
+use crate::visual::{Selection, VisualCommand, VisualKind, VisualParser};
+
+/// Largest count accepted before a command
+pub const MAX_COUNT: usize = 9_999;
+
+/// An operator that acts on the text a motion covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// `d`: delete into a register
+    Delete,
+    /// `c`: delete into a register, then insert
+    Change,
+    /// `y`: copy into a register
+    Yank,
+}
+
+impl Operator {
+    /// Operator bound to a key, if any
+    pub fn from_key(ch: char) -> Option<Self> {
+        match ch {
+            'd' => Some(Operator::Delete),
+            'c' => Some(Operator::Change),
+            'y' => Some(Operator::Yank),
+            _ => None,
+        }
+    }
+}
+
+/// Where an insert command starts inserting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InsertPoint {
+    /// `i`
+    BeforeCursor,
+    /// `a`
+    AfterCursor,
+    /// `I`: before the first non-blank character
+    LineStart,
+    /// `A`
+    LineEnd,
+    /// `o`: on a new line below
+    LineBelow,
+    /// `O`: on a new line above
+    LineAbove,
+}
+
+/// A complete Normal-mode command
+///
+/// Commands that read or write text carry the register named with `"x`, or
+/// `None` for the unnamed register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NormalCommand {
+    /// Move the cursor
+    Move {
+        motion: Motion,
+        count: Option<usize>,
+    },
+    /// Apply an operator over a motion (`dw`, `c$`, `y2j`)
+    Operate {
+        operator: Operator,
+        motion: Motion,
+        count: Option<usize>,
+        register: Option<char>,
+    },
+    /// Apply an operator to whole lines (`dd`, `cc`, `yy`)
+    OperateLines {
+        operator: Operator,
+        count: usize,
+        register: Option<char>,
+    },
+    /// `x`: delete characters under the cursor
+    DeleteChar {
+        count: usize,
+        register: Option<char>,
+    },
+    /// Enter Insert mode
+    Insert(InsertPoint),
+    /// `p` / `P`: put a register after or before the cursor
+    Put {
+        before: bool,
+        count: usize,
+        register: Option<char>,
+    },
+    /// `.`: repeat the last change, optionally with a new count
+    Repeat { count: Option<usize> },
+    /// `u`
+    Undo,
+    /// `:`
+    EnterCommand,
+    /// `/` or `?`: type a pattern to search for
+    EnterSearch(SearchDirection),
+    /// `n`: repeat the last search
+    SearchNext,
+    /// `N`: repeat the last search the other way
+    SearchPrevious,
+    /// `*` or `#`: search for the word under the cursor
+    SearchWord(SearchDirection),
+    /// `v`, `V` or Ctrl+V: start a selection at the cursor
+    Visual(VisualKind),
+    /// `mx`: remember the cursor position as mark `x`
+    SetMark(char),
+    /// `'x`: jump to the first non-blank of mark `x`'s line
+    JumpToMark(char),
+}
+
+impl NormalCommand {
+    /// Whether the command is a change that `.` repeats
+    ///
+    /// This includes entering Insert mode, since what is typed there becomes
+    /// part of the change.
+    pub fn is_change(&self) -> bool {
+        match self {
+            NormalCommand::Operate { operator, .. }
+            | NormalCommand::OperateLines { operator, .. } => *operator != Operator::Yank,
+            NormalCommand::DeleteChar { .. }
+            | NormalCommand::Insert(_)
+            | NormalCommand::Put { .. }
+            | NormalCommand::Repeat { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether running the command may edit the buffer
+    ///
+    /// Hosts take an undo snapshot before running one.
+    pub fn edits_buffer(&self) -> bool {
+        match self {
+            NormalCommand::Insert(point) => {
+                matches!(point, InsertPoint::LineBelow | InsertPoint::LineAbove)
+            }
+            command => command.is_change(),
+        }
+    }
+
+    /// Register the command names, if any
+    pub fn register(&self) -> Option<char> {
+        match self {
+            NormalCommand::Operate { register, .. }
+            | NormalCommand::OperateLines { register, .. }
+            | NormalCommand::DeleteChar { register, .. }
+            | NormalCommand::Put { register, .. } => *register,
+            _ => None,
+        }
+    }
+
+    /// Whether the command stores text in its register
+    pub fn writes_register(&self) -> bool {
+        matches!(
+            self,
+            NormalCommand::Operate { .. }
+                | NormalCommand::OperateLines { .. }
+                | NormalCommand::DeleteChar { .. }
+        )
+    }
+
+    fn with_count(mut self, new_count: usize) -> Self {
+        match &mut self {
+            NormalCommand::Operate { count, .. } => *count = Some(new_count),
+            NormalCommand::OperateLines { count, .. }
+            | NormalCommand::DeleteChar { count, .. }
+            | NormalCommand::Put { count, .. } => *count = new_count,
+            _ => {}
+        }
+        self
+    }
+}
+
+/// What a command did, for the host to follow up on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NormalEffect {
+    /// The buffer changed
+    pub modified: bool,
+    /// The host should switch to Insert mode
+    pub insert: bool,
+}
+
+/// Reads Normal-mode keys into commands
+#[derive(Debug, Clone, Default)]
+pub struct NormalParser {
+    count: Option<usize>,
+    operator: Option<(Operator, Option<usize>)>,
+    register: Option<char>,
+    register_prefix: bool,
+    g_prefix: bool,
+    /// `m` or `'`, waiting for a mark name
+    mark_prefix: Option<char>,
+}
+
+impl NormalParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one key; returns a command once one is complete
+    ///
+    /// Keys that make no sense where they are typed drop what was pending.
+    pub fn push(&mut self, ch: char) -> Option<NormalCommand> {
+        if core::mem::take(&mut self.g_prefix) {
+            return if ch == 'g' {
+                self.motion(Motion::FirstLine)
+            } else {
+                self.reset();
+                None
+            };
+        }
+        if core::mem::take(&mut self.register_prefix) {
+            if Registers::is_valid_name(ch) {
+                self.register = Some(ch);
+            } else {
+                self.reset();
+            }
+            return None;
+        }
+        if let Some(prefix) = self.mark_prefix.take() {
+            let command = match prefix {
+                'm' if Marks::is_settable(ch) => Some(NormalCommand::SetMark(ch)),
+                '\'' if Marks::is_valid_name(ch) => Some(NormalCommand::JumpToMark(ch)),
+                _ => None,
+            };
+            self.reset();
+            return command;
+        }
+
+        if let Some(digit) = ch.to_digit(10) {
+            if digit != 0 || self.count.is_some() {
+                let count = self.count.unwrap_or(0);
+                self.count = Some((count * 10 + digit as usize).min(MAX_COUNT));
+                return None;
+            }
+        }
+
+        if let Some(motion) = Motion::from_key(ch) {
+            return self.motion(motion);
+        }
+        if ch == 'g' {
+            self.g_prefix = true;
+            return None;
+        }
+        if ch == '"' && self.operator.is_none() {
+            self.register_prefix = true;
+            return None;
+        }
+        if matches!(ch, 'm' | '\'') && self.operator.is_none() {
+            self.mark_prefix = Some(ch);
+            return None;
+        }
+        if let Some(kind) = VisualKind::from_key(ch) {
+            let command = self
+                .operator
+                .is_none()
+                .then_some(NormalCommand::Visual(kind));
+            self.reset();
+            return command;
+        }
+
+        if let Some(operator) = Operator::from_key(ch) {
+            let command = match self.operator.take() {
+                None => {
+                    self.operator = Some((operator, self.count.take()));
+                    return None;
+                }
+                Some((pending, count)) if pending == operator => {
+                    let count = combine_counts(count, self.count).unwrap_or(1);
+                    Some(NormalCommand::OperateLines {
+                        operator,
+                        count,
+                        register: self.register,
+                    })
+                }
+                Some(_) => None,
+            };
+            self.reset();
+            return command;
+        }
+
+        if self.operator.is_some() {
+            self.reset();
+            return None;
+        }
+
+        let count = self.count.take();
+        let times = count.unwrap_or(1);
+        let register = self.register;
+        let command = match ch {
+            'x' => Some(NormalCommand::DeleteChar {
+                count: times,
+                register,
+            }),
+            'i' => Some(NormalCommand::Insert(InsertPoint::BeforeCursor)),
+            'a' => Some(NormalCommand::Insert(InsertPoint::AfterCursor)),
+            'I' => Some(NormalCommand::Insert(InsertPoint::LineStart)),
+            'A' => Some(NormalCommand::Insert(InsertPoint::LineEnd)),
+            'o' => Some(NormalCommand::Insert(InsertPoint::LineBelow)),
+            'O' => Some(NormalCommand::Insert(InsertPoint::LineAbove)),
+            'p' => Some(NormalCommand::Put {
+                before: false,
+                count: times,
+                register,
+            }),
+            'P' => Some(NormalCommand::Put {
+                before: true,
+                count: times,
+                register,
+            }),
+            '.' => Some(NormalCommand::Repeat { count }),
+            'u' => Some(NormalCommand::Undo),
+            ':' => Some(NormalCommand::EnterCommand),
+            '/' | '?' => SearchDirection::from_key(ch).map(NormalCommand::EnterSearch),
+            'n' => Some(NormalCommand::SearchNext),
+            'N' => Some(NormalCommand::SearchPrevious),
+            '*' => Some(NormalCommand::SearchWord(SearchDirection::Forward)),
+            '#' => Some(NormalCommand::SearchWord(SearchDirection::Backward)),
+            _ => None,
+        };
+        self.reset();
+        command
+    }
+
+    /// Whether part of a command has been typed
+    pub fn is_pending(&self) -> bool {
+        self.count.is_some()
+            || self.operator.is_some()
+            || self.register.is_some()
+            || self.register_prefix
+            || self.g_prefix
+            || self.mark_prefix.is_some()
+    }
+
+    /// Drop whatever has been typed so far
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn motion(&mut self, motion: Motion) -> Option<NormalCommand> {
+        let count = self.count.take();
+        let command = match self.operator.take() {
+            Some((operator, operator_count)) => NormalCommand::Operate {
+                operator,
+                motion,
+                count: combine_counts(operator_count, count),
+                register: self.register,
+            },
+            None => NormalCommand::Move { motion, count },
+        };
+        self.reset();
+        Some(command)
+    }
+}
+
+/// `2d3w` deletes six words
+fn combine_counts(first: Option<usize>, second: Option<usize>) -> Option<usize> {
+    match (first, second) {
+        (None, None) => None,
+        _ => Some(
+            first
+                .unwrap_or(1)
+                .saturating_mul(second.unwrap_or(1))
+                .min(MAX_COUNT),
+        ),
+    }
+}
+
+/// A change, with the text typed after it if it entered Insert mode
+#[derive(Debug, Clone)]
+struct Change {
+    command: NormalCommand,
+    inserted: String,
+}
+
+/// Runs Normal-mode commands against a buffer
+///
+/// The engine owns the parser, the registers, the marks and the last change,
+/// so a host keeps one per editor. Text typed in Insert mode is reported with
+/// [`record_insert`](Self::record_insert) so `.` can replay it.
+#[derive(Debug, Clone, Default)]
+pub struct NormalEngine {
+    parser: NormalParser,
+    visual: VisualParser,
+    registers: Registers,
+    marks: Marks,
+    last_change: Option<Change>,
+    recording: Option<Change>,
+}
+
+impl NormalEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one key to the parser
+    pub fn push_key(&mut self, ch: char) -> Option<NormalCommand> {
+        self.parser.push(ch)
+    }
+
+    /// Feed one key typed in Visual mode
+    pub fn push_visual_key(&mut self, ch: char) -> Option<VisualCommand> {
+        self.visual.push(ch)
+    }
+
+    /// Whether part of a command has been typed
+    pub fn is_pending(&self) -> bool {
+        self.parser.is_pending() || self.visual.is_pending()
+    }
+
+    /// Drop a partly typed command
+    pub fn reset_pending(&mut self) {
+        self.parser.reset();
+        self.visual.reset();
+    }
+
+    /// The yank registers
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    /// The yank registers, for hosts that sync `+` with a clipboard
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    /// The marks set with `m` and by leaving Visual mode
+    pub fn marks(&self) -> &Marks {
+        &self.marks
+    }
+
+    /// The marks, for hosts that set them from elsewhere
+    pub fn marks_mut(&mut self) -> &mut Marks {
+        &mut self.marks
+    }
+
+    /// Set `'<` and `'>` to the ends of a selection that is being left
+    pub fn mark_selection(&mut self, selection: &Selection) {
+        let (start, end) = selection.ordered();
+        self.marks.set(SELECTION_START_MARK, start);
+        self.marks.set(SELECTION_END_MARK, end);
+    }
+
+    /// Record a character typed in Insert mode (`\n` for Enter)
+    pub fn record_insert(&mut self, ch: char) {
+        if let Some(change) = &mut self.recording {
+            change.inserted.push(ch);
+        }
+    }
+
+    /// Record a backspace typed in Insert mode
+    pub fn record_backspace(&mut self) {
+        if let Some(change) = &mut self.recording {
+            change.inserted.pop();
+        }
+    }
+
+    /// Leaving Insert mode completes the change that entered it
+    pub fn finish_insert(&mut self) {
+        if let Some(change) = self.recording.take() {
+            self.last_change = Some(change);
+        }
+    }
+
+    /// Run a command, moving the cursor and editing the buffer
+    ///
+    /// Undo, search and mode switches are left to the host; for those this
+    /// does nothing.
+    pub fn execute(
+        &mut self,
+        command: NormalCommand,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> NormalEffect {
+        if let NormalCommand::Repeat { count } = command {
+            return self.repeat(count, buffer, cursor);
+        }
+        let effect = self.run(command, buffer, cursor);
+        if command.is_change() {
+            let change = Change {
+                command,
+                inserted: String::new(),
+            };
+            if effect.insert {
+                self.recording = Some(change);
+            } else {
+                self.recording = None;
+                self.last_change = Some(change);
+            }
+        }
+        effect
+    }
+
+    fn repeat(
+        &mut self,
+        count: Option<usize>,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> NormalEffect {
+        let Some(change) = self.last_change.as_mut() else {
+            return NormalEffect::default();
+        };
+        if let Some(count) = count {
+            change.command = change.command.with_count(count);
+        }
+        let Change { command, inserted } = change.clone();
+
+        let effect = self.run(command, buffer, cursor);
+        if !effect.insert {
+            return effect;
+        }
+        if let Some(end) = buffer.insert_text(*cursor, &inserted) {
+            if !inserted.is_empty() {
+                *cursor = Position::new(end.row, end.col.saturating_sub(1));
+            }
+        }
+        NormalEffect {
+            modified: effect.modified || !inserted.is_empty(),
+            insert: false,
+        }
+    }
+
+    fn run(
+        &mut self,
+        command: NormalCommand,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> NormalEffect {
+        *cursor = buffer.clamp(*cursor);
+        match command {
+            NormalCommand::Move { motion, count } => {
+                *cursor = motion.apply(buffer, *cursor, count);
+                NormalEffect::default()
+            }
+            NormalCommand::Operate {
+                operator,
+                motion,
+                count,
+                register,
+            } => self.operate(operator, motion, count, register, buffer, cursor),
+            NormalCommand::OperateLines {
+                operator,
+                count,
+                register,
+            } => {
+                let last = cursor.row.saturating_add(count.max(1) - 1);
+                self.operate_lines(operator, register, cursor.row, last, buffer, cursor)
+            }
+            NormalCommand::DeleteChar { count, register } => {
+                let end = Position::new(cursor.row, cursor.col.saturating_add(count));
+                self.operate_chars(Operator::Delete, register, *cursor, end, buffer, cursor)
+            }
+            NormalCommand::Insert(point) => insert(point, buffer, cursor),
+            NormalCommand::Put {
+                before,
+                count,
+                register,
+            } => self.put(before, count, register, buffer, cursor),
+            NormalCommand::SetMark(name) => {
+                self.marks.set(name, *cursor);
+                NormalEffect::default()
+            }
+            NormalCommand::JumpToMark(name) => {
+                if let Some(mark) = self.marks.get(name) {
+                    let row = buffer.clamp(mark).row;
+                    *cursor = Position::new(row, buffer.first_non_blank(row));
+                }
+                NormalEffect::default()
+            }
+            NormalCommand::Repeat { .. }
+            | NormalCommand::Undo
+            | NormalCommand::EnterCommand
+            | NormalCommand::EnterSearch(_)
+            | NormalCommand::SearchNext
+            | NormalCommand::SearchPrevious
+            | NormalCommand::SearchWord(_)
+            | NormalCommand::Visual(_) => NormalEffect::default(),
+        }
+    }
+
+    /// Apply an operator to a Visual-mode selection
+    ///
+    /// A block is stored as its rows joined by `\n` and put back as plain
+    /// text; changing a block deletes it and inserts on its first line.
+    /// Selection changes are not repeated by `.`.
+    pub fn operate_selection(
+        &mut self,
+        operator: Operator,
+        register: Option<char>,
+        selection: &Selection,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> NormalEffect {
+        self.recording = None;
+        let (start, end) = selection.ordered();
+        let (start, end) = (buffer.clamp(start), buffer.clamp(end));
+        match selection.kind {
+            VisualKind::Char => {
+                // The end is included; past the end of a line that means the
+                // line break
+                let to = if end.col < buffer.line_length(end.row) {
+                    Position::new(end.row, end.col + 1)
+                } else if end.row + 1 < buffer.line_count() {
+                    Position::new(end.row + 1, 0)
+                } else {
+                    end
+                };
+                self.operate_chars(operator, register, start, to, buffer, cursor)
+            }
+            VisualKind::Line => {
+                *cursor = start;
+                self.operate_lines(operator, register, start.row, end.row, buffer, cursor)
+            }
+            VisualKind::Block => self.operate_block(operator, register, selection, buffer, cursor),
+        }
+    }
+
+    fn operate_block(
+        &mut self,
+        operator: Operator,
+        register: Option<char>,
+        selection: &Selection,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> NormalEffect {
+        let (first, last) = selection.rows();
+        let last = last.min(buffer.line_count() - 1);
+        let (left, right) = selection.columns();
+        let cells = |buffer: &TextBuffer, row: usize| {
+            let length = buffer.line_length(row);
+            (
+                Position::new(row, left.min(length)),
+                Position::new(row, (right + 1).min(length)),
+            )
+        };
+        let text: Vec<String> = (first..=last)
+            .map(|row| {
+                let (from, to) = cells(buffer, row);
+                buffer.text_range(from, to)
+            })
+            .collect();
+        self.registers.store(
+            register,
+            RegisterContent {
+                text: text.join("\n"),
+                linewise: false,
+            },
+        );
+
+        let top_left = Position::new(first, left);
+        if operator == Operator::Yank {
+            *cursor = buffer.clamp(top_left);
+            return NormalEffect::default();
+        }
+        let mut modified = false;
+        for row in first..=last {
+            let (from, to) = cells(buffer, row);
+            modified |= buffer.delete_range(from, to);
+        }
+        let insert = operator == Operator::Change;
+        *cursor = if insert {
+            buffer.clamp(top_left)
+        } else {
+            normal_cursor(buffer, top_left)
+        };
+        NormalEffect { modified, insert }
+    }
+
+    fn operate(
+        &mut self,
+        operator: Operator,
+        motion: Motion,
+        count: Option<usize>,
+        register: Option<char>,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> NormalEffect {
+        let start = *cursor;
+        if motion.is_linewise() {
+            let target = motion.apply(buffer, start, count);
+            if target.row == start.row && matches!(motion, Motion::Up | Motion::Down) {
+                return NormalEffect::default();
+            }
+            let (first, last) = (start.row.min(target.row), start.row.max(target.row));
+            return self.operate_lines(operator, register, first, last, buffer, cursor);
+        }
+
+        let times = count.unwrap_or(1).max(1);
+        let (target, inclusive) = match motion {
+            // `cw` on a word changes to the end of it, like `ce`
+            Motion::WordForward
+                if operator == Operator::Change && !motion::is_blank(buffer, start) =>
+            {
+                let mut end = motion::current_word_end(buffer, start);
+                for _ in 1..times {
+                    end = motion::word_end(buffer, end);
+                }
+                (end, true)
+            }
+            // The last word moved over stops at the end of its line
+            Motion::WordForward => {
+                let mut pos = start;
+                for step in 0..times {
+                    let next = motion::word_forward(buffer, pos);
+                    if step + 1 == times && next.row > pos.row {
+                        pos = Position::new(pos.row, buffer.line_length(pos.row));
+                    } else {
+                        pos = next;
+                    }
+                }
+                (pos, false)
+            }
+            _ => (motion.apply(buffer, start, count), motion.is_inclusive()),
+        };
+
+        let (from, mut to) = (start.min(target), start.max(target));
+        if inclusive {
+            to.col += 1;
+        }
+        self.operate_chars(operator, register, from, to, buffer, cursor)
+    }
+
+    fn operate_chars(
+        &mut self,
+        operator: Operator,
+        register: Option<char>,
+        from: Position,
+        to: Position,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> NormalEffect {
+        let text = buffer.text_range(from, to);
+        if !text.is_empty() {
+            self.registers.store(
+                register,
+                RegisterContent {
+                    text,
+                    linewise: false,
+                },
+            );
+        }
+        match operator {
+            Operator::Yank => {
+                *cursor = from;
+                NormalEffect::default()
+            }
+            Operator::Delete => {
+                let modified = buffer.delete_range(from, to);
+                *cursor = normal_cursor(buffer, from);
+                NormalEffect {
+                    modified,
+                    insert: false,
+                }
+            }
+            Operator::Change => {
+                let modified = buffer.delete_range(from, to);
+                *cursor = buffer.clamp(from);
+                NormalEffect {
+                    modified,
+                    insert: true,
+                }
+            }
+        }
+    }
+
+    pub(crate) fn operate_lines(
+        &mut self,
+        operator: Operator,
+        register: Option<char>,
+        first: usize,
+        last: usize,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> NormalEffect {
+        let last = last.min(buffer.line_count() - 1);
+        self.registers.store(
+            register,
+            RegisterContent {
+                text: buffer.lines()[first..=last].join("\n"),
+                linewise: true,
+            },
+        );
+        match operator {
+            Operator::Yank => {
+                *cursor = buffer.clamp(Position::new(first, cursor.col));
+                NormalEffect::default()
+            }
+            Operator::Delete => {
+                buffer.remove_lines(first, last);
+                let row = first.min(buffer.line_count() - 1);
+                *cursor = Position::new(row, buffer.first_non_blank(row));
+                NormalEffect {
+                    modified: true,
+                    insert: false,
+                }
+            }
+            Operator::Change => {
+                buffer.remove_lines(first + 1, last);
+                let end = Position::new(first, buffer.line_length(first));
+                buffer.delete_range(Position::new(first, 0), end);
+                *cursor = Position::new(first, 0);
+                NormalEffect {
+                    modified: true,
+                    insert: true,
+                }
+            }
+        }
+    }
+
+    fn put(
+        &mut self,
+        before: bool,
+        count: usize,
+        register: Option<char>,
+        buffer: &mut TextBuffer,
+        cursor: &mut Position,
+    ) -> NormalEffect {
+        let Some(content) = self.registers.get(register) else {
+            return NormalEffect::default();
+        };
+        let count = count.max(1);
+
+        if content.linewise {
+            let row = if before { cursor.row } else { cursor.row + 1 };
+            let lines: Vec<String> = (0..count)
+                .flat_map(|_| content.text.split('\n').map(String::from))
+                .collect();
+            buffer.insert_lines(row, lines);
+            *cursor = Position::new(row, buffer.first_non_blank(row));
+        } else {
+            let after = !before && buffer.line_length(cursor.row) > 0;
+            let at = buffer.clamp(Position::new(cursor.row, cursor.col + usize::from(after)));
+            let text = content.text.repeat(count);
+            let Some(end) = buffer.insert_text(at, &text) else {
+                return NormalEffect::default();
+            };
+            *cursor = if text.contains('\n') {
+                at
+            } else {
+                Position::new(end.row, end.col.saturating_sub(1))
+            };
+        }
+        NormalEffect {
+            modified: true,
+            insert: false,
+        }
+    }
+}
+
+fn insert(point: InsertPoint, buffer: &mut TextBuffer, cursor: &mut Position) -> NormalEffect {
+    let mut modified = false;
+    match point {
+        InsertPoint::BeforeCursor => {}
+        InsertPoint::AfterCursor => {
+            if cursor.col < buffer.line_length(cursor.row) {
+                cursor.col += 1;
+            }
+        }
+        InsertPoint::LineStart => cursor.col = buffer.first_non_blank(cursor.row),
+        InsertPoint::LineEnd => cursor.col = buffer.line_length(cursor.row),
+        InsertPoint::LineBelow | InsertPoint::LineAbove => {
+            let row = cursor.row + usize::from(point == InsertPoint::LineBelow);
+            buffer.insert_lines(row, [String::new()]);
+            *cursor = Position::new(row, 0);
+            modified = true;
+        }
+    }
+    NormalEffect {
+        modified,
+        insert: true,
+    }
+}
+
+/// A Normal-mode cursor rests on a character, not past the end of the line
+fn normal_cursor(buffer: &TextBuffer, pos: Position) -> Position {
+    let pos = buffer.clamp(pos);
+    Position::new(
+        pos.row,
+        pos.col.min(buffer.line_length(pos.row).saturating_sub(1)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed keys to an engine, typing `inserted` whenever a command enters Insert mode
+    fn run(text: &str, keys: &str, inserted: &str) -> (TextBuffer, Position, NormalEngine) {
+        let mut buffer = TextBuffer::from_string(text.into());
+        let mut cursor = Position::zero();
+        let mut engine = NormalEngine::new();
+        for ch in keys.chars() {
+            let Some(command) = engine.push_key(ch) else {
+                continue;
+            };
+            if engine.execute(command, &mut buffer, &mut cursor).insert {
+                for ch in inserted.chars() {
+                    buffer.insert_char(cursor, ch);
+                    cursor.col += 1;
+                    engine.record_insert(ch);
+                }
+                engine.finish_insert();
+            }
+        }
+        (buffer, cursor, engine)
+    }
+
+    #[test]
+    fn test_parse_counts_and_operators() {
+        let mut parser = NormalParser::new();
+        let mut feed = |keys: &str| {
+            keys.chars()
+                .filter_map(|ch| parser.push(ch))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            feed("2d3w"),
+            [NormalCommand::Operate {
+                operator: Operator::Delete,
+                motion: Motion::WordForward,
+                count: Some(6),
+                register: None,
+            }]
+        );
+        assert_eq!(
+            feed("3dd"),
+            [NormalCommand::OperateLines {
+                operator: Operator::Delete,
+                count: 3,
+                register: None,
+            }]
+        );
+        assert_eq!(
+            feed("10gg0"),
+            [
+                NormalCommand::Move {
+                    motion: Motion::FirstLine,
+                    count: Some(10),
+                },
+                NormalCommand::Move {
+                    motion: Motion::LineStart,
+                    count: None,
+                },
+            ]
+        );
+        assert_eq!(
+            feed("dyx"),
+            [NormalCommand::DeleteChar {
+                count: 1,
+                register: None,
+            }]
+        );
+        assert_eq!(
+            feed("\"a2yy\"+P"),
+            [
+                NormalCommand::OperateLines {
+                    operator: Operator::Yank,
+                    count: 2,
+                    register: Some('a'),
+                },
+                NormalCommand::Put {
+                    before: true,
+                    count: 1,
+                    register: Some('+'),
+                },
+            ]
+        );
+        assert_eq!(
+            feed("?N#"),
+            [
+                NormalCommand::EnterSearch(SearchDirection::Backward),
+                NormalCommand::SearchPrevious,
+                NormalCommand::SearchWord(SearchDirection::Backward),
+            ]
+        );
+        assert!(!parser.is_pending());
+        parser.push('c');
+        assert!(parser.is_pending());
+    }
+
+    #[test]
+    fn test_operators_with_motions() {
+        let (buffer, cursor, _) = run("one two three", "dw", "");
+        assert_eq!(buffer.as_string(), "two three");
+        assert_eq!(cursor, Position::zero());
+
+        let (buffer, _, _) = run("one two three", "w2dw", "");
+        assert_eq!(buffer.as_string(), "one ");
+
+        let (buffer, _, _) = run("one two\nthree", "wdw", "");
+        assert_eq!(buffer.as_string(), "one \nthree");
+
+        let (buffer, _, _) = run("one two three", "cw", "ONE");
+        assert_eq!(buffer.as_string(), "ONE two three");
+
+        let (buffer, _, _) = run("one two three", "wd$", "");
+        assert_eq!(buffer.as_string(), "one ");
+
+        let (buffer, _, _) = run("a\nb\nc\nd", "jdj", "");
+        assert_eq!(buffer.as_string(), "a\nd");
+
+        let (buffer, _, _) = run("a\nb\nc", "jdG", "");
+        assert_eq!(buffer.as_string(), "a");
+
+        let (buffer, _, engine) = run("one two", "ye$p", "");
+        assert_eq!(buffer.as_string(), "one twoone");
+        assert_eq!(engine.registers().get(None).unwrap().text, "one");
+    }
+
+    #[test]
+    fn test_line_commands_and_put() {
+        let (buffer, cursor, _) = run("a\nb\nc", "2ddp", "");
+        assert_eq!(buffer.as_string(), "c\na\nb");
+        assert_eq!(cursor, Position::new(1, 0));
+
+        let (buffer, _, _) = run("a\nb", "yyjP", "");
+        assert_eq!(buffer.as_string(), "a\na\nb");
+
+        let (buffer, _, _) = run("a\nb", "cc", "new");
+        assert_eq!(buffer.as_string(), "new\nb");
+
+        let (buffer, _, _) = run("a\nb", "o", "mid");
+        assert_eq!(buffer.as_string(), "a\nmid\nb");
+
+        let (buffer, _, _) = run("a\nb", "jO", "mid");
+        assert_eq!(buffer.as_string(), "a\nmid\nb");
+
+        let (buffer, _, _) = run("  a", "I", ">");
+        assert_eq!(buffer.as_string(), "  >a");
+
+        let (buffer, _, _) = run("ab", "A", "!");
+        assert_eq!(buffer.as_string(), "ab!");
+    }
+
+    #[test]
+    fn test_named_registers() {
+        let (buffer, _, engine) = run("one\ntwo\nthree", "\"ayyjddjx\"ap", "");
+        assert_eq!(buffer.as_string(), "one\nhree\none");
+        assert_eq!(engine.registers().get(None).unwrap().text, "t");
+
+        let (buffer, _, _) = run("a\nb", "\"qyyj\"Qyy\"qP", "");
+        assert_eq!(buffer.as_string(), "a\na\nb\nb");
+    }
+
+    #[test]
+    fn test_marks() {
+        let (_, cursor, engine) = run("one\n  two\nthree", "jllmaG'a", "");
+        assert_eq!(engine.marks().get('a'), Some(Position::new(1, 2)));
+        assert_eq!(cursor, Position::new(1, 2));
+
+        let (_, cursor, _) = run("one\ntwo", "j'bmZ", "");
+        assert_eq!(cursor, Position::new(1, 0));
+    }
+
+    #[test]
+    fn test_repeat_last_change() {
+        let (buffer, _, _) = run("a b c d e", "dw..", "");
+        assert_eq!(buffer.as_string(), "d e");
+
+        let (buffer, _, _) = run("a b c d e", "dw3.", "");
+        assert_eq!(buffer.as_string(), "e");
+
+        let mut buffer = TextBuffer::from_string("one\ntwo".into());
+        let mut cursor = Position::new(1, 0);
+        let mut engine = NormalEngine::new();
+        engine.execute(
+            NormalCommand::Insert(InsertPoint::LineEnd),
+            &mut buffer,
+            &mut cursor,
+        );
+        for ch in "?x".chars() {
+            buffer.insert_char(cursor, ch);
+            cursor.col += 1;
+            engine.record_insert(ch);
+        }
+        cursor = buffer.backspace(cursor).unwrap();
+        engine.record_backspace();
+        buffer.insert_char(cursor, '!');
+        engine.record_insert('!');
+        engine.finish_insert();
+        cursor = Position::zero();
+        let effect = engine.execute(
+            NormalCommand::Repeat { count: None },
+            &mut buffer,
+            &mut cursor,
+        );
+        assert!(effect.modified && !effect.insert);
+        assert_eq!(buffer.as_string(), "one?!\ntwo?!");
+    }
+}
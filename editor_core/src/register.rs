@@ -0,0 +1,126 @@
+//! Yank registers
+//!
+//! Deletes and yanks land in the unnamed register unless a command names one
+//! with `"x`. The named registers `a`–`z` keep their text until overwritten;
+//! naming one in uppercase (`"Ayy`) appends to it instead. The `+` register
+//! stands for the system clipboard: the core keeps it like any other, and
+//! hosts with a clipboard connection fill it before a put and publish it
+//! after a write.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+/// Register written when a command names none
+pub const UNNAMED_REGISTER: char = '"';
+
+/// Register that mirrors the system clipboard
+pub const CLIPBOARD_REGISTER: char = '+';
+
+/// Text held by a register
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterContent {
+    /// The text, with lines joined by `\n`
+    pub text: String,
+    /// Whether the text is whole lines (from `dd`, `yj`, ...)
+    pub linewise: bool,
+}
+
+/// The unnamed, named and clipboard registers of one editor
+#[derive(Debug, Clone, Default)]
+pub struct Registers {
+    unnamed: Option<RegisterContent>,
+    named: BTreeMap<char, RegisterContent>,
+}
+
+impl Registers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `name` can follow `"`
+    pub fn is_valid_name(name: char) -> bool {
+        name == UNNAMED_REGISTER || name == CLIPBOARD_REGISTER || name.is_ascii_alphabetic()
+    }
+
+    /// Contents of a register; `None` names the unnamed register
+    pub fn get(&self, name: Option<char>) -> Option<&RegisterContent> {
+        match name.map(|name| name.to_ascii_lowercase()) {
+            None | Some(UNNAMED_REGISTER) => self.unnamed.as_ref(),
+            Some(name) => self.named.get(&name),
+        }
+    }
+
+    /// Stores deleted or yanked text
+    ///
+    /// An uppercase name appends to the register. Whatever was stored also
+    /// becomes the unnamed register's contents, so a plain `p` puts it.
+    pub fn store(&mut self, name: Option<char>, content: RegisterContent) {
+        let content = match name {
+            None | Some(UNNAMED_REGISTER) => content,
+            Some(name) => {
+                let key = name.to_ascii_lowercase();
+                let stored = match self.named.remove(&key) {
+                    Some(existing) if name.is_ascii_uppercase() => append(existing, content),
+                    _ => content,
+                };
+                self.named.insert(key, stored.clone());
+                stored
+            }
+        };
+        self.unnamed = Some(content);
+    }
+
+    /// Replaces one register without touching the unnamed register
+    ///
+    /// Hosts use this to load the clipboard into `+` before a put.
+    pub fn set(&mut self, name: char, content: RegisterContent) {
+        match name.to_ascii_lowercase() {
+            UNNAMED_REGISTER => self.unnamed = Some(content),
+            name => {
+                self.named.insert(name, content);
+            }
+        }
+    }
+}
+
+/// Appending across kinds makes the result linewise, as in vi
+fn append(mut existing: RegisterContent, content: RegisterContent) -> RegisterContent {
+    if existing.linewise || content.linewise {
+        existing.text.push('\n');
+        existing.linewise = true;
+    }
+    existing.text.push_str(&content.text);
+    existing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str, linewise: bool) -> RegisterContent {
+        RegisterContent {
+            text: text.into(),
+            linewise,
+        }
+    }
+
+    #[test]
+    fn test_named_registers_and_append() {
+        let mut registers = Registers::new();
+        registers.store(Some('a'), text("one", false));
+        registers.store(None, text("two", false));
+        assert_eq!(registers.get(Some('a')), Some(&text("one", false)));
+        assert_eq!(registers.get(None), Some(&text("two", false)));
+
+        registers.store(Some('A'), text("more", false));
+        assert_eq!(registers.get(Some('a')), Some(&text("onemore", false)));
+        registers.store(Some('A'), text("line", true));
+        assert_eq!(registers.get(Some('a')), Some(&text("onemore\nline", true)));
+        assert_eq!(registers.get(None), registers.get(Some('a')));
+
+        registers.set(CLIPBOARD_REGISTER, text("clip", false));
+        assert_eq!(registers.get(Some('+')), Some(&text("clip", false)));
+        assert_eq!(registers.get(None), Some(&text("onemore\nline", true)));
+        assert!(Registers::is_valid_name('Z') && !Registers::is_valid_name('1'));
+    }
+}
@@ -0,0 +1,509 @@
+//! Pattern search
+//!
+//! `/` and `?` read a pattern and search forward or backward from the
+//! cursor, wrapping around the ends of the buffer; `n` repeats the last
+//! search and `N` repeats it the other way. `*` and `#` search for the word
+//! under the cursor as a whole word. Patterns are [`Regex`]es from
+//! `text_pattern`, the engine the filesystem content search and command
+//! palette filtering use too.
+//!
+//! Hosts keep a [`SearchState`]: the pattern being typed, the direction of
+//! the last search and the [`SearchHistory`]. It also says which matches to
+//! highlight: those of the pattern as it is typed, then those of the last
+//! search until `:nohlsearch`.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use text_pattern::{escape, Regex};
+
+use crate::buffer::{Position, TextBuffer};
+use crate::command::CommandError;
+use crate::visual::LineSpan;
+
+#[cfg(feature = "serde_support")]
+use serde::{Deserialize, Serialize};
+
+/// Most patterns the history keeps
+pub const SEARCH_HISTORY_LIMIT: usize = 50;
+
+/// Which way a search goes from the cursor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub enum SearchDirection {
+    /// `/`, `*`: towards the end of the buffer
+    #[default]
+    Forward,
+    /// `?`, `#`: towards the start of the buffer
+    Backward,
+}
+
+impl SearchDirection {
+    /// Direction a key starts a search in
+    pub fn from_key(ch: char) -> Option<Self> {
+        match ch {
+            '/' => Some(SearchDirection::Forward),
+            '?' => Some(SearchDirection::Backward),
+            _ => None,
+        }
+    }
+
+    /// Key that starts a search this way, shown before the pattern
+    pub fn key(self) -> char {
+        match self {
+            SearchDirection::Forward => '/',
+            SearchDirection::Backward => '?',
+        }
+    }
+
+    /// The other direction, for `N`
+    pub fn reverse(self) -> Self {
+        match self {
+            SearchDirection::Forward => SearchDirection::Backward,
+            SearchDirection::Backward => SearchDirection::Forward,
+        }
+    }
+}
+
+/// Compiles a search pattern
+pub fn compile(pattern: &str) -> Result<Regex, CommandError> {
+    Regex::new(pattern).map_err(CommandError::InvalidPattern)
+}
+
+/// Start of the nearest match from `from`, wrapping around the buffer
+///
+/// Only a match starting past `from` in the search direction counts, unless
+/// `accept_current` allows one starting right at it.
+pub fn find(
+    buffer: &TextBuffer,
+    regex: &Regex,
+    from: Position,
+    direction: SearchDirection,
+    accept_current: bool,
+) -> Option<Position> {
+    let line_count = buffer.line_count();
+    let starts = |row: usize| -> Vec<usize> {
+        let line = buffer.line(row).unwrap_or_default();
+        regex.find_iter(line).map(|found| found.start).collect()
+    };
+    let on_row = |row: usize, col: Option<usize>| col.map(|col| Position::new(row, col));
+
+    let current = starts(from.row);
+    match direction {
+        SearchDirection::Forward => {
+            let ahead = current
+                .iter()
+                .find(|&&col| col > from.col || (accept_current && col == from.col));
+            if let Some(&col) = ahead {
+                return Some(Position::new(from.row, col));
+            }
+            // Coming back round to the cursor line takes its first match
+            (1..=line_count)
+                .map(|step| (from.row + step) % line_count)
+                .find_map(|row| on_row(row, starts(row).first().copied()))
+        }
+        SearchDirection::Backward => {
+            let behind = current
+                .iter()
+                .rev()
+                .find(|&&col| col < from.col || (accept_current && col == from.col));
+            if let Some(&col) = behind {
+                return Some(Position::new(from.row, col));
+            }
+            (1..=line_count)
+                .map(|step| (from.row + line_count - step % line_count) % line_count)
+                .find_map(|row| on_row(row, starts(row).last().copied()))
+        }
+    }
+}
+
+/// Every non-empty match on `rows`, one span per match
+pub fn match_spans(buffer: &TextBuffer, regex: &Regex, rows: Range<usize>) -> Vec<LineSpan> {
+    let rows = rows.start..rows.end.min(buffer.line_count());
+    rows.flat_map(|row| {
+        let line = buffer.line(row).unwrap_or_default();
+        regex
+            .find_iter(line)
+            .filter(|found| !found.is_empty())
+            .map(move |found| LineSpan {
+                row,
+                start: found.start,
+                end: found.end,
+            })
+    })
+    .collect()
+}
+
+/// The whole-word pattern `*` and `#` search for, and where its word starts
+///
+/// The word is the one under the cursor, or the next one on its line.
+pub fn word_pattern(buffer: &TextBuffer, pos: Position) -> Option<(Position, String)> {
+    let line = buffer.line(pos.row)?;
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let at = pos.col.min(line.len());
+    let rest = line.get(at..)?;
+    let start = if rest.starts_with(is_word) {
+        line[..at]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| is_word(c))
+            .last()
+            .map_or(at, |(start, _)| start)
+    } else {
+        at + rest.find(is_word)?
+    };
+    let end = line[start..]
+        .find(|c| !is_word(c))
+        .map_or(line.len(), |len| start + len);
+    let word = &line[start..end];
+    Some((
+        Position::new(pos.row, start),
+        format!("\\b{}\\b", escape(word)),
+    ))
+}
+
+/// Patterns searched for, oldest first
+///
+/// Searching for a pattern again moves it to the end rather than keeping a
+/// second copy. While a pattern is typed, Up and Down step through the
+/// history.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchHistory {
+    entries: Vec<String>,
+    /// Entry shown while stepping through the history
+    browsing: Option<usize>,
+}
+
+impl SearchHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a pattern as the most recent
+    pub fn push(&mut self, pattern: &str) {
+        self.browsing = None;
+        if pattern.is_empty() {
+            return;
+        }
+        self.entries.retain(|entry| entry != pattern);
+        if self.entries.len() == SEARCH_HISTORY_LIMIT {
+            self.entries.remove(0);
+        }
+        self.entries.push(pattern.to_string());
+    }
+
+    /// The most recent pattern, which `n` repeats
+    pub fn last(&self) -> Option<&str> {
+        self.entries.last().map(String::as_str)
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Step to the next older pattern, stopping at the oldest
+    pub fn older(&mut self) -> Option<&str> {
+        let index = match self.browsing {
+            Some(index) => index.saturating_sub(1),
+            None => self.entries.len().checked_sub(1)?,
+        };
+        self.browsing = Some(index);
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Step to the next newer pattern; `None` once past the newest
+    pub fn newer(&mut self) -> Option<&str> {
+        let index = self.browsing? + 1;
+        self.browsing = (index < self.entries.len()).then_some(index);
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Stop stepping through the history
+    pub fn reset_browsing(&mut self) {
+        self.browsing = None;
+    }
+}
+
+/// Search state of one editor
+#[derive(Debug, Clone, Default)]
+pub struct SearchState {
+    /// Pattern typed in Search mode
+    query: String,
+    /// Direction of the search being typed, or of the last one
+    direction: SearchDirection,
+    history: SearchHistory,
+    /// Whether matches of the last pattern are highlighted
+    highlight: bool,
+}
+
+impl SearchState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start typing a pattern to search for in `direction`
+    pub fn start(&mut self, direction: SearchDirection) {
+        self.query.clear();
+        self.direction = direction;
+        self.history.reset_browsing();
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn direction(&self) -> SearchDirection {
+        self.direction
+    }
+
+    pub fn history(&self) -> &SearchHistory {
+        &self.history
+    }
+
+    /// Add a character to the pattern being typed
+    pub fn push(&mut self, ch: char) {
+        self.query.push(ch);
+    }
+
+    /// Remove the last character of the pattern being typed
+    pub fn pop(&mut self) {
+        self.query.pop();
+    }
+
+    /// Drop the pattern being typed
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.history.reset_browsing();
+    }
+
+    /// Replace the pattern being typed with an older one from the history
+    pub fn history_older(&mut self) {
+        if let Some(pattern) = self.history.older() {
+            self.query = pattern.to_string();
+        }
+    }
+
+    /// Replace the pattern being typed with a newer one from the history,
+    /// or clear it after the newest
+    pub fn history_newer(&mut self) {
+        self.query = self.history.newer().unwrap_or_default().to_string();
+    }
+
+    /// Search for the typed pattern, or the last one if nothing was typed
+    ///
+    /// A match at the cursor itself counts. The typed text is cleared either
+    /// way, but only a pattern that compiles goes into the history.
+    pub fn submit(
+        &mut self,
+        buffer: &TextBuffer,
+        from: Position,
+    ) -> Result<Position, CommandError> {
+        let query = core::mem::take(&mut self.query);
+        if !query.is_empty() {
+            compile(&query)?;
+            self.history.push(&query);
+        }
+        self.history.reset_browsing();
+        self.search(buffer, from, self.direction, true)
+    }
+
+    /// `n`, or `N` with `reverse`: repeat the last search
+    pub fn repeat(
+        &mut self,
+        buffer: &TextBuffer,
+        from: Position,
+        reverse: bool,
+    ) -> Result<Position, CommandError> {
+        let direction = if reverse {
+            self.direction.reverse()
+        } else {
+            self.direction
+        };
+        self.search(buffer, from, direction, false)
+    }
+
+    /// `*` or `#`: search for the word under the cursor
+    ///
+    /// The word's pattern becomes the last search, so `n` finds the next one.
+    pub fn search_word(
+        &mut self,
+        buffer: &TextBuffer,
+        from: Position,
+        direction: SearchDirection,
+    ) -> Result<Position, CommandError> {
+        let (start, pattern) = word_pattern(buffer, from)
+            .ok_or_else(|| CommandError::InvalidSyntax("No word under cursor".to_string()))?;
+        self.history.push(&pattern);
+        self.direction = direction;
+        // Backwards, the word the cursor is on must not count as the match
+        let from = match direction {
+            SearchDirection::Forward => from,
+            SearchDirection::Backward => start,
+        };
+        self.search(buffer, from, direction, false)
+    }
+
+    /// Stop highlighting matches of the last pattern
+    pub fn clear_highlight(&mut self) {
+        self.highlight = false;
+    }
+
+    /// Matches to highlight on `rows`
+    ///
+    /// While `typing`, these are the matches of the pattern typed so far;
+    /// otherwise those of the last search, unless highlighting was turned
+    /// off. A pattern that does not compile yet highlights nothing.
+    pub fn match_spans(
+        &self,
+        buffer: &TextBuffer,
+        rows: Range<usize>,
+        typing: bool,
+    ) -> Vec<LineSpan> {
+        let pattern = if typing {
+            Some(self.query.as_str()).filter(|query| !query.is_empty())
+        } else if self.highlight {
+            self.history.last()
+        } else {
+            None
+        };
+        match pattern.map(Regex::new) {
+            Some(Ok(regex)) => match_spans(buffer, &regex, rows),
+            _ => Vec::new(),
+        }
+    }
+
+    fn search(
+        &mut self,
+        buffer: &TextBuffer,
+        from: Position,
+        direction: SearchDirection,
+        accept_current: bool,
+    ) -> Result<Position, CommandError> {
+        let pattern = self.history.last().ok_or(CommandError::NoPreviousPattern)?;
+        let regex = compile(pattern)?;
+        self.highlight = true;
+        find(buffer, &regex, from, direction, accept_current)
+            .ok_or_else(|| CommandError::PatternNotFound(pattern.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(text: &str) -> TextBuffer {
+        TextBuffer::from_string(text.into())
+    }
+
+    #[test]
+    fn test_find_wraps_both_ways() {
+        let buffer = buffer("foo bar\nbaz foo\nfoo");
+        let regex = compile("fo+").unwrap();
+        let find = |row, col, direction, accept| {
+            find(&buffer, &regex, Position::new(row, col), direction, accept)
+        };
+        let forward = SearchDirection::Forward;
+        let backward = SearchDirection::Backward;
+
+        assert_eq!(find(0, 0, forward, true), Some(Position::new(0, 0)));
+        assert_eq!(find(0, 0, forward, false), Some(Position::new(1, 4)));
+        assert_eq!(find(2, 0, forward, false), Some(Position::new(0, 0)));
+        assert_eq!(find(1, 4, backward, false), Some(Position::new(0, 0)));
+        assert_eq!(find(0, 0, backward, false), Some(Position::new(2, 0)));
+        assert_eq!(find(1, 6, backward, false), Some(Position::new(1, 4)));
+
+        // The only match is found again after going all the way round
+        let single = TextBuffer::from_string("a foo".into());
+        let at = Position::new(0, 2);
+        let regex = compile("foo").unwrap();
+        assert_eq!(super::find(&single, &regex, at, forward, false), Some(at));
+        assert_eq!(super::find(&single, &regex, at, backward, false), Some(at));
+        let regex = compile("x").unwrap();
+        assert_eq!(super::find(&single, &regex, at, forward, false), None);
+    }
+
+    #[test]
+    fn test_match_spans_and_words() {
+        let buffer = buffer("one two one\n\nsome_one one.");
+        let regex = compile(r"\bone\b").unwrap();
+        let spans = match_spans(&buffer, &regex, 0..10);
+        let ends: Vec<_> = spans.iter().map(|s| (s.row, s.start, s.end)).collect();
+        assert_eq!(ends, [(0, 0, 3), (0, 8, 11), (2, 9, 12)]);
+
+        let word = |row, col| word_pattern(&buffer, Position::new(row, col));
+        assert_eq!(word(0, 5), Some((Position::new(0, 4), r"\btwo\b".into())));
+        assert_eq!(word(0, 3), Some((Position::new(0, 4), r"\btwo\b".into())));
+        assert_eq!(
+            word(2, 2),
+            Some((Position::new(2, 0), r"\bsome_one\b".into()))
+        );
+        assert_eq!(word(1, 0), None);
+        assert_eq!(word(2, 12), None);
+    }
+
+    #[test]
+    fn test_search_history() {
+        let mut history = SearchHistory::new();
+        history.push("a");
+        history.push("b");
+        history.push("a");
+        history.push("");
+        assert_eq!(history.entries(), ["b", "a"]);
+        assert_eq!(history.last(), Some("a"));
+
+        assert_eq!(history.older(), Some("a"));
+        assert_eq!(history.older(), Some("b"));
+        assert_eq!(history.older(), Some("b"));
+        assert_eq!(history.newer(), Some("a"));
+        assert_eq!(history.newer(), None);
+        assert_eq!(history.newer(), None);
+    }
+
+    #[test]
+    fn test_search_state() {
+        let buffer = buffer("foo bar\nbar foo");
+        let origin = Position::zero();
+        let mut search = SearchState::new();
+        assert_eq!(
+            search.repeat(&buffer, origin, false),
+            Err(CommandError::NoPreviousPattern)
+        );
+
+        search.start(SearchDirection::Backward);
+        "ba.".chars().for_each(|ch| search.push(ch));
+        assert_eq!(search.match_spans(&buffer, 0..2, true).len(), 2);
+        assert!(search.match_spans(&buffer, 0..2, false).is_empty());
+        let at = search.submit(&buffer, origin).unwrap();
+        assert_eq!(at, Position::new(1, 0));
+        assert_eq!(search.query(), "");
+        assert_eq!(search.match_spans(&buffer, 0..2, false).len(), 2);
+
+        assert_eq!(search.repeat(&buffer, at, false), Ok(Position::new(0, 4)));
+        assert_eq!(search.repeat(&buffer, at, true), Ok(Position::new(0, 4)));
+
+        let at = search
+            .search_word(&buffer, Position::new(1, 5), SearchDirection::Backward)
+            .unwrap();
+        assert_eq!(at, Position::new(0, 0));
+        assert_eq!(search.history().last(), Some(r"\bfoo\b"));
+
+        search.start(SearchDirection::Forward);
+        search.history_older();
+        assert_eq!(search.query(), r"\bfoo\b");
+        search.history_older();
+        assert_eq!(search.query(), "ba.");
+        search.history_newer();
+        search.history_newer();
+        assert_eq!(search.query(), "");
+
+        search.push('(');
+        assert!(matches!(
+            search.submit(&buffer, origin),
+            Err(CommandError::InvalidPattern(_))
+        ));
+        search.clear_highlight();
+        assert!(search.match_spans(&buffer, 0..2, false).is_empty());
+    }
+}
@@ -0,0 +1,327 @@
+//! Visual-mode selections
+//!
+//! `v`, `V` and Ctrl+V start a selection at the cursor. Motions then move the
+//! cursor while the other end, the anchor, stays put, and an operator acts on
+//! everything in between: characters from one end to the other, whole lines,
+//! or a rectangle of columns. Hosts keep the anchor and the mode; the
+//! [`Selection`] built from them tells renderers which cells to highlight
+//! and [`NormalEngine::operate_selection`](crate::normal::NormalEngine::operate_selection)
+//! carries out the operator.
+
+use alloc::vec::Vec;
+
+use crate::buffer::{Position, TextBuffer};
+use crate::mode::EditorMode;
+use crate::motion::Motion;
+use crate::normal::{Operator, MAX_COUNT};
+use crate::register::Registers;
+
+#[cfg(feature = "serde_support")]
+use serde::{Deserialize, Serialize};
+
+/// Character Ctrl+V types, which starts a block selection
+pub const VISUAL_BLOCK_KEY: char = '\x16';
+
+/// What a selection covers between its two ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub enum VisualKind {
+    /// `v`: every character from one end to the other
+    Char,
+    /// `V`: whole lines
+    Line,
+    /// Ctrl+V: the columns between the ends, on every line between them
+    Block,
+}
+
+impl VisualKind {
+    /// Kind started by a key, if any
+    pub fn from_key(ch: char) -> Option<Self> {
+        match ch {
+            'v' => Some(VisualKind::Char),
+            'V' => Some(VisualKind::Line),
+            VISUAL_BLOCK_KEY => Some(VisualKind::Block),
+            _ => None,
+        }
+    }
+
+    /// Editor mode showing a selection of this kind
+    pub fn mode(self) -> EditorMode {
+        match self {
+            VisualKind::Char => EditorMode::Visual,
+            VisualKind::Line => EditorMode::VisualLine,
+            VisualKind::Block => EditorMode::VisualBlock,
+        }
+    }
+}
+
+/// Columns `start..end` of one line that a selection covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub struct LineSpan {
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A selection between an anchor and the cursor, both ends included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub struct Selection {
+    pub kind: VisualKind,
+    pub anchor: Position,
+    pub cursor: Position,
+}
+
+impl Selection {
+    pub fn new(kind: VisualKind, anchor: Position, cursor: Position) -> Self {
+        Self {
+            kind,
+            anchor,
+            cursor,
+        }
+    }
+
+    /// The two ends in buffer order
+    pub fn ordered(&self) -> (Position, Position) {
+        (self.anchor.min(self.cursor), self.anchor.max(self.cursor))
+    }
+
+    /// First and last row the selection touches
+    pub fn rows(&self) -> (usize, usize) {
+        let (start, end) = self.ordered();
+        (start.row, end.row)
+    }
+
+    /// Leftmost and rightmost column of a block, both included
+    pub fn columns(&self) -> (usize, usize) {
+        (
+            self.anchor.col.min(self.cursor.col),
+            self.anchor.col.max(self.cursor.col),
+        )
+    }
+
+    /// The cells to highlight, one span per line
+    ///
+    /// An empty line inside a character or line selection gets a one-cell
+    /// span so it shows as selected; block spans stop at the end of short
+    /// lines and lines the block misses entirely are left out.
+    pub fn line_spans(&self, buffer: &TextBuffer) -> Vec<LineSpan> {
+        let (start, end) = self.ordered();
+        let last_row = end.row.min(buffer.line_count() - 1);
+        let (left, right) = self.columns();
+        let mut spans = Vec::new();
+        for row in start.row..=last_row {
+            let length = buffer.line_length(row);
+            let (from, to) = match self.kind {
+                VisualKind::Line => (0, length.max(1)),
+                VisualKind::Char => {
+                    let from = if row == start.row { start.col } else { 0 };
+                    let to = if row == end.row {
+                        end.col + 1
+                    } else {
+                        length.max(from + 1)
+                    };
+                    (from, to.min(length.max(from + 1)))
+                }
+                VisualKind::Block => (left.min(length), (right + 1).min(length)),
+            };
+            if to > from {
+                spans.push(LineSpan {
+                    row,
+                    start: from,
+                    end: to,
+                });
+            }
+        }
+        spans
+    }
+}
+
+/// A complete Visual-mode command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VisualCommand {
+    /// Move the cursor end of the selection
+    Move {
+        motion: Motion,
+        count: Option<usize>,
+    },
+    /// Apply an operator to the selection and leave Visual mode
+    Operate {
+        operator: Operator,
+        register: Option<char>,
+    },
+    /// `v`, `V` or Ctrl+V: switch kind, or leave Visual mode if already in it
+    Switch(VisualKind),
+    /// `o`: move the cursor to the other end
+    SwapEnds,
+    /// `:`: leave Visual mode for a command line over the selected lines
+    EnterCommand,
+}
+
+/// Reads Visual-mode keys into commands
+///
+/// Counts, `gg` and `"x` work as in Normal mode; an operator applies at once
+/// since the selection is its span.
+#[derive(Debug, Clone, Default)]
+pub struct VisualParser {
+    count: Option<usize>,
+    register: Option<char>,
+    register_prefix: bool,
+    g_prefix: bool,
+}
+
+impl VisualParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one key; returns a command once one is complete
+    pub fn push(&mut self, ch: char) -> Option<VisualCommand> {
+        if core::mem::take(&mut self.g_prefix) {
+            let command = (ch == 'g').then_some(VisualCommand::Move {
+                motion: Motion::FirstLine,
+                count: self.count,
+            });
+            self.reset();
+            return command;
+        }
+        if core::mem::take(&mut self.register_prefix) {
+            if Registers::is_valid_name(ch) {
+                self.register = Some(ch);
+            } else {
+                self.reset();
+            }
+            return None;
+        }
+
+        if let Some(digit) = ch.to_digit(10) {
+            if digit != 0 || self.count.is_some() {
+                let count = self.count.unwrap_or(0);
+                self.count = Some((count * 10 + digit as usize).min(MAX_COUNT));
+                return None;
+            }
+        }
+        match ch {
+            'g' => {
+                self.g_prefix = true;
+                return None;
+            }
+            '"' => {
+                self.register_prefix = true;
+                return None;
+            }
+            _ => {}
+        }
+
+        let register = self.register;
+        let command = if let Some(motion) = Motion::from_key(ch) {
+            Some(VisualCommand::Move {
+                motion,
+                count: self.count,
+            })
+        } else if let Some(kind) = VisualKind::from_key(ch) {
+            Some(VisualCommand::Switch(kind))
+        } else {
+            let operator = match ch {
+                'x' => Some(Operator::Delete),
+                's' => Some(Operator::Change),
+                ch => Operator::from_key(ch),
+            };
+            match operator {
+                Some(operator) => Some(VisualCommand::Operate { operator, register }),
+                None if ch == 'o' => Some(VisualCommand::SwapEnds),
+                None if ch == ':' => Some(VisualCommand::EnterCommand),
+                None => None,
+            }
+        };
+        self.reset();
+        command
+    }
+
+    /// Whether part of a command has been typed
+    pub fn is_pending(&self) -> bool {
+        self.count.is_some() || self.register.is_some() || self.register_prefix || self.g_prefix
+    }
+
+    /// Drop whatever has been typed so far
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(row: usize, start: usize, end: usize) -> LineSpan {
+        LineSpan { row, start, end }
+    }
+
+    #[test]
+    fn test_line_spans() {
+        let buffer = TextBuffer::from_string("hello\n\nworld wide\nab".into());
+        let at = Position::new;
+
+        let chars = Selection::new(VisualKind::Char, at(2, 3), at(0, 1));
+        assert_eq!(
+            chars.line_spans(&buffer),
+            [span(0, 1, 5), span(1, 0, 1), span(2, 0, 4)]
+        );
+
+        let lines = Selection::new(VisualKind::Line, at(1, 0), at(2, 4));
+        assert_eq!(lines.line_spans(&buffer), [span(1, 0, 1), span(2, 0, 10)]);
+
+        let block = Selection::new(VisualKind::Block, at(0, 4), at(3, 1));
+        assert_eq!(
+            block.line_spans(&buffer),
+            [span(0, 1, 5), span(2, 1, 5), span(3, 1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_parse_visual_keys() {
+        let mut parser = VisualParser::new();
+        let mut feed = |keys: &str| {
+            keys.chars()
+                .filter_map(|ch| parser.push(ch))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            feed("3jgg"),
+            [
+                VisualCommand::Move {
+                    motion: Motion::Down,
+                    count: Some(3)
+                },
+                VisualCommand::Move {
+                    motion: Motion::FirstLine,
+                    count: None
+                }
+            ]
+        );
+        assert_eq!(
+            feed("\"ayxo"),
+            [
+                VisualCommand::Operate {
+                    operator: Operator::Yank,
+                    register: Some('a')
+                },
+                VisualCommand::Operate {
+                    operator: Operator::Delete,
+                    register: None
+                },
+                VisualCommand::SwapEnds
+            ]
+        );
+        assert_eq!(feed(":"), [VisualCommand::EnterCommand]);
+        assert_eq!(
+            feed("V\x16q"),
+            [
+                VisualCommand::Switch(VisualKind::Line),
+                VisualCommand::Switch(VisualKind::Block)
+            ]
+        );
+    }
+}
@@ -3,7 +3,7 @@
 //! This module defines how directories are represented in the filesystem view.
 
 use serde::{Deserialize, Serialize};
-use services_storage::{ObjectId, ObjectKind};
+use services_storage::{EntryMetadata, ObjectId, ObjectKind};
 use std::collections::HashMap;
 
 /// Marker type for object capabilities in the filesystem view
@@ -12,7 +12,7 @@ pub struct ObjectCapability;
 
 /// A single entry in a directory
 ///
-/// Maps a name to an object capability.
+/// Maps a name to an object capability, with the metadata kept for that name.
 #[derive(Debug, Clone)]
 pub struct DirectoryEntry {
     /// Name of this entry
@@ -21,6 +21,8 @@ pub struct DirectoryEntry {
     pub object_id: ObjectId,
     /// Kind of object
     pub kind: ObjectKind,
+    /// Timestamps, content type and attributes
+    pub metadata: EntryMetadata,
 }
 
 impl DirectoryEntry {
@@ -30,8 +32,15 @@ impl DirectoryEntry {
             name,
             object_id,
             kind,
+            metadata: EntryMetadata::default(),
         }
     }
+
+    /// Sets the entry's metadata
+    pub fn with_metadata(mut self, metadata: EntryMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 /// A directory view
@@ -79,6 +88,11 @@ impl DirectoryView {
         self.entries.get(name)
     }
 
+    /// Gets a mutable entry by name
+    pub fn get_entry_mut(&mut self, name: &str) -> Option<&mut DirectoryEntry> {
+        self.entries.get_mut(name)
+    }
+
     /// Lists all entries in the directory
     pub fn list_entries(&self) -> Vec<&DirectoryEntry> {
         self.entries.values().collect()
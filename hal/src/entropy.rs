@@ -0,0 +1,72 @@
+//! # Entropy Source
+//!
+//! Hardware abstraction for unpredictable random bytes.
+//!
+//! ## Philosophy
+//!
+//! **Secrets never come from counters.**
+//!
+//! Capability tokens and similar secrets must not be derivable from IDs,
+//! clocks or sequence numbers. They are drawn from an [`EntropySource`],
+//! which either fills the buffer with random bytes or reports that it
+//! cannot. Callers fail closed on [`EntropyError::Unavailable`] instead of
+//! falling back to something guessable.
+
+/// Error from an entropy source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntropyError {
+    /// The platform has no entropy source, or it stopped producing bytes
+    Unavailable,
+}
+
+/// Source of random bytes suitable for secrets
+pub trait EntropySource {
+    /// Fills `dest` entirely with random bytes
+    ///
+    /// On error the contents of `dest` are unspecified and must not be used.
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), EntropyError>;
+}
+
+/// Entropy from the host operating system, for hosted builds
+///
+/// Draws on the OS random generator behind version 4 UUIDs. The version and
+/// variant bits of each UUID are fixed, so only its other 14 bytes are used.
+#[cfg(not(target_os = "none"))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HostEntropy;
+
+#[cfg(not(target_os = "none"))]
+impl EntropySource for HostEntropy {
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), EntropyError> {
+        let mut filled = 0;
+        while filled < dest.len() {
+            let uuid = uuid::Uuid::new_v4();
+            let random = uuid
+                .as_bytes()
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| *index != 6 && *index != 8)
+                .map(|(_, byte)| *byte);
+            for (slot, byte) in dest[filled..].iter_mut().zip(random) {
+                *slot = byte;
+                filled += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(target_os = "none")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_entropy_fills_whole_buffer() {
+        let mut first = [0u8; 37];
+        let mut second = [0u8; 37];
+        HostEntropy.fill_bytes(&mut first).unwrap();
+        HostEntropy.fill_bytes(&mut second).unwrap();
+        assert_ne!(first, second);
+        assert!(first[30..].iter().any(|&byte| byte != 0));
+    }
+}
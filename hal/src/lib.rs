@@ -22,6 +22,7 @@ extern crate alloc;
 
 pub mod block_device;
 pub mod cpu;
+pub mod entropy;
 pub mod framebuffer;
 pub mod interrupts;
 pub mod keyboard;
@@ -33,6 +34,9 @@ pub mod timer;
 pub use block_device::RamDisk;
 pub use block_device::{BlockDevice, BlockError, BLOCK_SIZE};
 pub use cpu::CpuHal;
+#[cfg(not(target_os = "none"))]
+pub use entropy::HostEntropy;
+pub use entropy::{EntropyError, EntropySource};
 pub use framebuffer::{Framebuffer, FramebufferInfo, PixelFormat};
 pub use interrupts::InterruptHal;
 pub use keyboard::{HalKeyEvent, HalScancode, KeyboardDevice};
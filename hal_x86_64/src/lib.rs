@@ -21,6 +21,7 @@ pub mod interrupts;
 pub mod keyboard;
 pub mod paging;
 pub mod port_io;
+pub mod rdrand;
 pub mod tick;
 pub mod timer;
 pub mod virtio;
@@ -35,6 +36,7 @@ pub use paging::{
     USER_SPACE_END,
 };
 pub use port_io::{FakePortIo, PortIo, RealPortIo};
+pub use rdrand::RdRandEntropy;
 pub use tick::{KernelTickCounter, TickSource};
 pub use timer::{FakeTimerDevice, HpetTimer, PitTimer};
 pub use virtio::{VirtioMmioDevice, VirtqAvail, VirtqDesc, VirtqUsed, Virtqueue};
@@ -0,0 +1,84 @@
+//! RDRAND-backed entropy source.
+//!
+//! The CPU's hardware random number generator, detected through CPUID.
+//! RDRAND may transiently report no data; it is retried a bounded number
+//! of times before the source reports itself unavailable.
+
+use core::prelude::v1::*;
+
+use hal::{EntropyError, EntropySource};
+
+/// Attempts per 64-bit word before giving up (Intel recommends 10).
+const RDRAND_RETRIES: usize = 10;
+
+/// Entropy from the RDRAND instruction.
+#[derive(Debug)]
+pub struct RdRandEntropy {
+    _private: (),
+}
+
+impl RdRandEntropy {
+    /// Returns the source if the CPU supports RDRAND.
+    pub fn detect() -> Option<Self> {
+        if rdrand_supported() {
+            Some(Self { _private: () })
+        } else {
+            None
+        }
+    }
+
+    fn next_word(&mut self) -> Result<u64, EntropyError> {
+        for _ in 0..RDRAND_RETRIES {
+            // SAFETY: `detect` only hands out a source after CPUID reported
+            // RDRAND support, so the instruction exists on this CPU.
+            if let Some(word) = unsafe { rdrand64() } {
+                return Ok(word);
+            }
+        }
+        Err(EntropyError::Unavailable)
+    }
+}
+
+impl EntropySource for RdRandEntropy {
+    fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), EntropyError> {
+        for chunk in dest.chunks_mut(8) {
+            let word = self.next_word()?.to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
+
+fn rdrand_supported() -> bool {
+    // CPUID leaf 1, ECX bit 30
+    let leaf = core::arch::x86_64::__cpuid(1);
+    leaf.ecx & (1 << 30) != 0
+}
+
+/// Reads one word from RDRAND; `None` if the generator had no data ready.
+#[target_feature(enable = "rdrand")]
+unsafe fn rdrand64() -> Option<u64> {
+    let mut word = 0u64;
+    if core::arch::x86_64::_rdrand64_step(&mut word) == 1 {
+        Some(word)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rdrand_fills_buffer_when_supported() {
+        let Some(mut source) = RdRandEntropy::detect() else {
+            return;
+        };
+        let mut first = [0u8; 13];
+        let mut second = [0u8; 13];
+        source.fill_bytes(&mut first).unwrap();
+        source.fill_bytes(&mut second).unwrap();
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,73 @@
+//! Compares JSON and binary payload encodings on hot-path message types
+//!
+//! Run with `cargo bench -p ipc`. Uses a plain timing loop so it builds
+//! without extra dependencies.
+
+use input_types::{InputEvent, KeyCode, KeyEvent, Modifiers};
+use ipc::{MessagePayload, PayloadEncoding};
+use serde::{Deserialize, Serialize};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use view_types::{CursorPosition, ViewContent, ViewFrame, ViewId, ViewKind};
+
+const ITERATIONS: u32 = 20_000;
+
+fn input_event() -> InputEvent {
+    InputEvent::key(KeyEvent::pressed(KeyCode::A, Modifiers::CTRL))
+}
+
+fn view_frame() -> ViewFrame {
+    let lines = (0..40)
+        .map(|i| {
+            format!(
+                "line {:>3}: the quick brown panda jumps over the lazy fox",
+                i
+            )
+        })
+        .collect();
+    ViewFrame::new(
+        ViewId::new(),
+        ViewKind::TextBuffer,
+        1_024,
+        ViewContent::text_buffer(lines),
+        123_456_789,
+    )
+    .with_cursor(CursorPosition::new(12, 7))
+    .with_title("notes.txt")
+}
+
+fn time_per_op(mut op: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        op();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn bench<T>(name: &str, value: &T)
+where
+    T: Serialize + for<'de> Deserialize<'de>,
+{
+    println!("{}", name);
+    for encoding in [PayloadEncoding::Json, PayloadEncoding::Binary] {
+        let payload = MessagePayload::with_encoding(value, encoding).unwrap();
+        let encode = time_per_op(|| {
+            black_box(MessagePayload::with_encoding(black_box(value), encoding).unwrap());
+        });
+        let decode = time_per_op(|| {
+            black_box(black_box(&payload).deserialize::<T>().unwrap());
+        });
+        println!(
+            "  {:<6} {:>6} bytes  encode {:>9.2?}  decode {:>9.2?}",
+            encoding.to_string(),
+            payload.as_bytes().len(),
+            encode,
+            decode
+        );
+    }
+}
+
+fn main() {
+    bench("InputEvent", &input_event());
+    bench("ViewFrame", &view_frame());
+}
@@ -0,0 +1,979 @@
+//! Compact binary serde format for message payloads
+//!
+//! JSON is convenient for debugging but slow and bloated on hot paths such as
+//! input events and view frames. This module implements a small binary format
+//! in the spirit of postcard:
+//!
+//! - `u8`/`i8` are raw bytes; wider unsigned integers are LEB128 varints and
+//!   signed integers are zigzag-encoded varints
+//! - Floats are little-endian IEEE 754
+//! - Strings, byte arrays, sequences and maps are length-prefixed
+//! - Options carry a one-byte tag; enum variants are encoded by index
+//! - Struct fields are written as `field index + 1` followed by the value, and
+//!   the struct ends with `0`. Fields skipped via `skip_serializing_if` are
+//!   simply absent, so `#[serde(default)]` behaves as it does with JSON.
+//!
+//! The format is not self-describing: both ends must agree on the Rust type.
+//! `deserialize_any` (untagged or internally tagged enums) is not supported,
+//! and fields marked `skip_serializing` without `skip_deserializing` would
+//! shift field indices. Use [`EncodingPolicy`](crate::EncodingPolicy) to opt
+//! in per action or schema version.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use serde::de::value::{BorrowedStrDeserializer, U32Deserializer};
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+
+/// Binary codec errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    /// Input ended in the middle of a value
+    UnexpectedEnd,
+    /// A varint was longer than its target type allows
+    VarintOverflow,
+    /// A decoded integer does not fit the requested type
+    IntegerOverflow,
+    /// A bool byte other than 0 or 1
+    InvalidBool(u8),
+    /// An option tag other than 0 or 1
+    InvalidOptionTag(u8),
+    /// A char outside the Unicode scalar range
+    InvalidChar(u32),
+    /// String bytes were not valid UTF-8
+    InvalidUtf8,
+    /// A struct field index not known to the target type
+    UnknownField(u64),
+    /// Sequences and maps must report their length up front
+    LengthRequired,
+    /// The target type needs a self-describing format
+    NotSelfDescribing,
+    /// Bytes left over after the value was decoded
+    TrailingBytes(usize),
+    /// Error reported by a `Serialize`/`Deserialize` implementation
+    Message(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            CodecError::VarintOverflow => write!(f, "varint overflow"),
+            CodecError::IntegerOverflow => write!(f, "integer out of range"),
+            CodecError::InvalidBool(byte) => write!(f, "invalid bool byte {}", byte),
+            CodecError::InvalidOptionTag(byte) => write!(f, "invalid option tag {}", byte),
+            CodecError::InvalidChar(value) => write!(f, "invalid char {:#x}", value),
+            CodecError::InvalidUtf8 => write!(f, "invalid UTF-8 in string"),
+            CodecError::UnknownField(index) => write!(f, "unknown field index {}", index),
+            CodecError::LengthRequired => write!(f, "sequence length must be known"),
+            CodecError::NotSelfDescribing => {
+                write!(f, "type requires a self-describing format")
+            }
+            CodecError::TrailingBytes(count) => write!(f, "{} trailing bytes", count),
+            CodecError::Message(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl core::error::Error for CodecError {}
+
+impl ser::Error for CodecError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CodecError::Message(msg.to_string())
+    }
+}
+
+impl de::Error for CodecError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CodecError::Message(msg.to_string())
+    }
+}
+
+/// Serializes a value into the compact binary format
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, CodecError> {
+    let mut serializer = Serializer { output: Vec::new() };
+    value.serialize(&mut serializer)?;
+    Ok(serializer.output)
+}
+
+/// Deserializes a value from the compact binary format
+///
+/// The whole input must be consumed.
+pub fn from_slice<'de, T: de::Deserialize<'de>>(input: &'de [u8]) -> Result<T, CodecError> {
+    let mut deserializer = Deserializer { input };
+    let value = T::deserialize(&mut deserializer)?;
+    if !deserializer.input.is_empty() {
+        return Err(CodecError::TrailingBytes(deserializer.input.len()));
+    }
+    Ok(value)
+}
+
+struct Serializer {
+    output: Vec<u8>,
+}
+
+impl Serializer {
+    fn write_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.output.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.output.push(value as u8);
+    }
+
+    fn write_varint_u128(&mut self, mut value: u128) {
+        while value >= 0x80 {
+            self.output.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.output.push(value as u8);
+    }
+
+    fn write_signed(&mut self, value: i64) {
+        self.write_varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn write_len(&mut self, len: Option<usize>) -> Result<(), CodecError> {
+        let len = len.ok_or(CodecError::LengthRequired)?;
+        self.write_varint(len as u64);
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = ();
+    type Error = CodecError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = StructSerializer<'a>;
+    type SerializeStructVariant = StructSerializer<'a>;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<(), CodecError> {
+        self.output.push(v as u8);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), CodecError> {
+        self.output.push(v as u8);
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), CodecError> {
+        self.write_signed(v as i64);
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), CodecError> {
+        self.write_signed(v as i64);
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), CodecError> {
+        self.write_signed(v);
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), CodecError> {
+        self.write_varint_u128(((v << 1) ^ (v >> 127)) as u128);
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), CodecError> {
+        self.output.push(v);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), CodecError> {
+        self.write_varint(v as u64);
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), CodecError> {
+        self.write_varint(v as u64);
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), CodecError> {
+        self.write_varint(v);
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), CodecError> {
+        self.write_varint_u128(v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), CodecError> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), CodecError> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), CodecError> {
+        self.write_varint(v as u64);
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), CodecError> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), CodecError> {
+        self.write_varint(v.len() as u64);
+        self.output.extend_from_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), CodecError> {
+        self.output.push(0);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), CodecError> {
+        self.output.push(1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), CodecError> {
+        self.write_varint(variant_index as u64);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        self.write_varint(variant_index as u64);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, CodecError> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, CodecError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, CodecError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, CodecError> {
+        self.write_varint(variant_index as u64);
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, CodecError> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<StructSerializer<'a>, CodecError> {
+        Ok(StructSerializer {
+            serializer: self,
+            next_field: 0,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<StructSerializer<'a>, CodecError> {
+        self.write_varint(variant_index as u64);
+        Ok(StructSerializer {
+            serializer: self,
+            next_field: 0,
+        })
+    }
+}
+
+impl ser::SerializeSeq for &mut Serializer {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut Serializer {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Serializer {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Serializer {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut Serializer {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), CodecError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+/// Writes struct fields as `index + 1, value` pairs terminated by `0`
+struct StructSerializer<'a> {
+    serializer: &'a mut Serializer,
+    next_field: u64,
+}
+
+impl StructSerializer<'_> {
+    fn field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        self.next_field += 1;
+        self.serializer.write_varint(self.next_field);
+        value.serialize(&mut *self.serializer)
+    }
+
+    fn finish(self) -> Result<(), CodecError> {
+        self.serializer.output.push(0);
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for StructSerializer<'_> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        self.field(value)
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), CodecError> {
+        self.next_field += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for StructSerializer<'_> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        self.field(value)
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), CodecError> {
+        self.next_field += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        self.finish()
+    }
+}
+
+struct Deserializer<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> Deserializer<'de> {
+    fn take_byte(&mut self) -> Result<u8, CodecError> {
+        let (&byte, rest) = self.input.split_first().ok_or(CodecError::UnexpectedEnd)?;
+        self.input = rest;
+        Ok(byte)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'de [u8], CodecError> {
+        if self.input.len() < len {
+            return Err(CodecError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    fn read_varint(&mut self) -> Result<u64, CodecError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take_byte()?;
+            if shift == 63 && byte > 1 {
+                return Err(CodecError::VarintOverflow);
+            }
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CodecError::VarintOverflow)
+    }
+
+    fn read_varint_u128(&mut self) -> Result<u128, CodecError> {
+        let mut value = 0u128;
+        for shift in (0..128).step_by(7) {
+            let byte = self.take_byte()?;
+            if shift == 126 && byte > 3 {
+                return Err(CodecError::VarintOverflow);
+            }
+            value |= ((byte & 0x7f) as u128) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CodecError::VarintOverflow)
+    }
+
+    fn read_signed(&mut self) -> Result<i64, CodecError> {
+        let raw = self.read_varint()?;
+        Ok(((raw >> 1) as i64) ^ -((raw & 1) as i64))
+    }
+
+    fn read_len(&mut self) -> Result<usize, CodecError> {
+        usize::try_from(self.read_varint()?).map_err(|_| CodecError::IntegerOverflow)
+    }
+
+    fn read_bytes(&mut self) -> Result<&'de [u8], CodecError> {
+        let len = self.read_len()?;
+        self.take(len)
+    }
+
+    fn read_str(&mut self) -> Result<&'de str, CodecError> {
+        let bytes = self.read_bytes()?;
+        core::str::from_utf8(bytes).map_err(|_| CodecError::InvalidUtf8)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], CodecError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+}
+
+macro_rules! deserialize_varint {
+    ($method:ident, $visit:ident, $ty:ty, $read:ident) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+            let value = self.$read()?;
+            visitor.$visit(<$ty>::try_from(value).map_err(|_| CodecError::IntegerOverflow)?)
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = CodecError;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, CodecError> {
+        Err(CodecError::NotSelfDescribing)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        match self.take_byte()? {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            other => Err(CodecError::InvalidBool(other)),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_i8(self.take_byte()? as i8)
+    }
+
+    deserialize_varint!(deserialize_i16, visit_i16, i16, read_signed);
+    deserialize_varint!(deserialize_i32, visit_i32, i32, read_signed);
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_i64(self.read_signed()?)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let raw = self.read_varint_u128()?;
+        visitor.visit_i128(((raw >> 1) as i128) ^ -((raw & 1) as i128))
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u8(self.take_byte()?)
+    }
+
+    deserialize_varint!(deserialize_u16, visit_u16, u16, read_varint);
+    deserialize_varint!(deserialize_u32, visit_u32, u32, read_varint);
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u64(self.read_varint()?)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u128(self.read_varint_u128()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_f32(f32::from_le_bytes(self.read_array()?))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_f64(f64::from_le_bytes(self.read_array()?))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let value = u32::try_from(self.read_varint()?).map_err(|_| CodecError::IntegerOverflow)?;
+        visitor.visit_char(char::from_u32(value).ok_or(CodecError::InvalidChar(value))?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_borrowed_str(self.read_str()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_borrowed_bytes(self.read_bytes()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        match self.take_byte()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            other => Err(CodecError::InvalidOptionTag(other)),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let remaining = self.read_len()?;
+        visitor.visit_seq(SeqAccess {
+            deserializer: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_seq(SeqAccess {
+            deserializer: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let remaining = self.read_len()?;
+        visitor.visit_map(SeqAccess {
+            deserializer: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_map(StructAccess {
+            deserializer: self,
+            fields,
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.deserialize_u32(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, CodecError> {
+        Err(CodecError::NotSelfDescribing)
+    }
+}
+
+/// Length-prefixed sequences and maps
+struct SeqAccess<'a, 'de> {
+    deserializer: &'a mut Deserializer<'de>,
+    remaining: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'_, 'de> {
+    type Error = CodecError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, CodecError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> de::MapAccess<'de> for SeqAccess<'_, 'de> {
+    type Error = CodecError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, CodecError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, CodecError> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Struct fields keyed by index, terminated by `0`
+struct StructAccess<'a, 'de> {
+    deserializer: &'a mut Deserializer<'de>,
+    fields: &'static [&'static str],
+}
+
+impl<'de> de::MapAccess<'de> for StructAccess<'_, 'de> {
+    type Error = CodecError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, CodecError> {
+        let tag = self.deserializer.read_varint()?;
+        if tag == 0 {
+            return Ok(None);
+        }
+        let name = usize::try_from(tag - 1)
+            .ok()
+            .and_then(|index| self.fields.get(index))
+            .ok_or(CodecError::UnknownField(tag - 1))?;
+        seed.deserialize(BorrowedStrDeserializer::new(name))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, CodecError> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut Deserializer<'de> {
+    type Error = CodecError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), CodecError> {
+        let index = u32::try_from(self.read_varint()?).map_err(|_| CodecError::IntegerOverflow)?;
+        let deserializer: U32Deserializer<CodecError> = index.into_deserializer();
+        let value = seed.deserialize(deserializer)?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Deserializer<'de> {
+    type Error = CodecError;
+
+    fn unit_variant(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, CodecError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+    use alloc::vec;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f32),
+        Line(i32, i32),
+        Rect { width: u16, height: u16 },
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        id: u64,
+        delta: i64,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        shapes: Vec<Shape>,
+        tags: BTreeMap<String, u32>,
+        flag: bool,
+        letter: char,
+    }
+
+    fn sample() -> Sample {
+        let mut tags = BTreeMap::new();
+        tags.insert("a".to_string(), 1);
+        tags.insert("b".to_string(), 300);
+        Sample {
+            id: 42,
+            delta: -7,
+            name: "panda".to_string(),
+            label: None,
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(1.5),
+                Shape::Line(-3, 9),
+                Shape::Rect {
+                    width: 80,
+                    height: 25,
+                },
+            ],
+            tags,
+            flag: true,
+            letter: 'λ',
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let value = sample();
+        let bytes = to_vec(&value).unwrap();
+        assert_eq!(from_slice::<Sample>(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_skipped_field_uses_default() {
+        let mut value = sample();
+        let without_label = to_vec(&value).unwrap();
+        value.label = Some("title".to_string());
+        let with_label = to_vec(&value).unwrap();
+
+        assert!(without_label.len() < with_label.len());
+        assert_eq!(from_slice::<Sample>(&with_label).unwrap(), value);
+    }
+
+    #[test]
+    fn test_varint_encoding() {
+        assert_eq!(to_vec(&127u32).unwrap(), vec![0x7f]);
+        assert_eq!(to_vec(&128u32).unwrap(), vec![0x80, 0x01]);
+        assert_eq!(to_vec(&-1i32).unwrap(), vec![0x01]);
+        assert_eq!(
+            from_slice::<u64>(&to_vec(&u64::MAX).unwrap()).unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            from_slice::<i64>(&to_vec(&i64::MIN).unwrap()).unwrap(),
+            i64::MIN
+        );
+    }
+
+    #[test]
+    fn test_smaller_than_json() {
+        let value = sample();
+        let binary = to_vec(&value).unwrap();
+        let json = serde_json::to_vec(&value).unwrap();
+        assert!(binary.len() * 2 < json.len());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let bytes = to_vec(&sample()).unwrap();
+        assert_eq!(
+            from_slice::<Sample>(&bytes[..bytes.len() - 1]),
+            Err(CodecError::UnexpectedEnd)
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            from_slice::<Sample>(&trailing),
+            Err(CodecError::TrailingBytes(1))
+        );
+
+        assert_eq!(from_slice::<bool>(&[2]), Err(CodecError::InvalidBool(2)));
+        assert_eq!(
+            from_slice::<u8>(&[0xff; 11][..]),
+            Err(CodecError::TrailingBytes(10))
+        );
+        assert_eq!(
+            from_slice::<u64>(&[0xff; 11]),
+            Err(CodecError::VarintOverflow)
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Payload encodings and encoding selection
+//!
+//! Payloads default to JSON, which is easy to inspect. Hot paths can opt in
+//! to the compact [`codec`](crate::codec) format instead. The encoding is
+//! recorded alongside the payload bytes, so receivers decode either format
+//! transparently via [`MessagePayload::deserialize`](crate::MessagePayload::deserialize).
+
+use crate::codec::{self, CodecError};
+use crate::message::{MessagePayload, SchemaVersion};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// Wire encoding of a message payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum PayloadEncoding {
+    /// JSON (self-describing, human-readable)
+    #[default]
+    Json,
+    /// Compact binary format from [`codec`](crate::codec)
+    Binary,
+}
+
+impl PayloadEncoding {
+    /// Encodes a value with this encoding
+    pub fn encode<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>, PayloadError> {
+        match self {
+            PayloadEncoding::Json => Ok(serde_json::to_vec(value)?),
+            PayloadEncoding::Binary => Ok(codec::to_vec(value)?),
+        }
+    }
+
+    /// Decodes a value previously encoded with this encoding
+    pub fn decode<T: for<'de> Deserialize<'de>>(self, bytes: &[u8]) -> Result<T, PayloadError> {
+        match self {
+            PayloadEncoding::Json => Ok(serde_json::from_slice(bytes)?),
+            PayloadEncoding::Binary => Ok(codec::from_slice(bytes)?),
+        }
+    }
+}
+
+impl fmt::Display for PayloadEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadEncoding::Json => write!(f, "json"),
+            PayloadEncoding::Binary => write!(f, "binary"),
+        }
+    }
+}
+
+/// Error encoding or decoding a payload
+#[derive(Debug)]
+pub enum PayloadError {
+    /// JSON encoding failed
+    Json(serde_json::Error),
+    /// Binary encoding failed
+    Binary(CodecError),
+}
+
+impl fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadError::Json(err) => write!(f, "json payload: {}", err),
+            PayloadError::Binary(err) => write!(f, "binary payload: {}", err),
+        }
+    }
+}
+
+impl core::error::Error for PayloadError {}
+
+impl From<serde_json::Error> for PayloadError {
+    fn from(err: serde_json::Error) -> Self {
+        PayloadError::Json(err)
+    }
+}
+
+impl From<CodecError> for PayloadError {
+    fn from(err: CodecError) -> Self {
+        PayloadError::Binary(err)
+    }
+}
+
+/// Chooses a payload encoding per action and schema version
+///
+/// Resolution order:
+/// 1. An explicit per-action override
+/// 2. Binary, if the schema version is at or above `binary_since`
+///    (same major version only, since binary layouts are not portable
+///    across breaking changes)
+/// 3. The default encoding
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncodingPolicy {
+    default: PayloadEncoding,
+    binary_since: Option<SchemaVersion>,
+    actions: BTreeMap<String, PayloadEncoding>,
+}
+
+impl EncodingPolicy {
+    /// Creates a policy that always selects JSON
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the fallback encoding
+    pub fn with_default(mut self, encoding: PayloadEncoding) -> Self {
+        self.default = encoding;
+        self
+    }
+
+    /// Selects binary for schema versions at or above `version`
+    pub fn with_binary_since(mut self, version: SchemaVersion) -> Self {
+        self.binary_since = Some(version);
+        self
+    }
+
+    /// Overrides the encoding for a specific action
+    pub fn with_action(mut self, action: impl Into<String>, encoding: PayloadEncoding) -> Self {
+        self.actions.insert(action.into(), encoding);
+        self
+    }
+
+    /// Returns the encoding to use for a message
+    pub fn select(&self, action: &str, schema_version: SchemaVersion) -> PayloadEncoding {
+        if let Some(encoding) = self.actions.get(action) {
+            return *encoding;
+        }
+
+        match self.binary_since {
+            Some(since)
+                if schema_version.is_compatible_with(&since)
+                    && !schema_version.is_older_than(&since) =>
+            {
+                PayloadEncoding::Binary
+            }
+            _ => self.default,
+        }
+    }
+
+    /// Encodes a payload with the encoding selected for a message
+    pub fn encode<T: Serialize>(
+        &self,
+        action: &str,
+        schema_version: SchemaVersion,
+        value: &T,
+    ) -> Result<MessagePayload, PayloadError> {
+        MessagePayload::with_encoding(value, self.select(action, schema_version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding_round_trip() {
+        for encoding in [PayloadEncoding::Json, PayloadEncoding::Binary] {
+            let bytes = encoding.encode(&(7u32, "seven")).unwrap();
+            let decoded: (u32, String) = encoding.decode(&bytes).unwrap();
+            assert_eq!(decoded, (7, "seven".into()));
+        }
+    }
+
+    #[test]
+    fn test_policy_resolution_order() {
+        let policy = EncodingPolicy::new()
+            .with_binary_since(SchemaVersion::new(1, 2))
+            .with_action("debug.dump", PayloadEncoding::Json);
+
+        assert_eq!(
+            policy.select("input.event", SchemaVersion::new(1, 1)),
+            PayloadEncoding::Json
+        );
+        assert_eq!(
+            policy.select("input.event", SchemaVersion::new(1, 2)),
+            PayloadEncoding::Binary
+        );
+        assert_eq!(
+            policy.select("input.event", SchemaVersion::new(2, 0)),
+            PayloadEncoding::Json
+        );
+        assert_eq!(
+            policy.select("debug.dump", SchemaVersion::new(1, 5)),
+            PayloadEncoding::Json
+        );
+    }
+
+    #[test]
+    fn test_policy_encode_uses_selected_encoding() {
+        let policy = EncodingPolicy::new().with_binary_since(SchemaVersion::new(1, 0));
+
+        let binary = policy
+            .encode("view.frame", SchemaVersion::new(1, 0), &42u64)
+            .unwrap();
+        assert_eq!(binary.encoding(), PayloadEncoding::Binary);
+        assert_eq!(binary.deserialize::<u64>().unwrap(), 42);
+
+        let json = policy
+            .encode("view.frame", SchemaVersion::new(0, 9), &42u64)
+            .unwrap();
+        assert_eq!(json.encoding(), PayloadEncoding::Json);
+    }
+}
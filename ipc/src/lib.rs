@@ -26,10 +26,14 @@
 extern crate alloc;
 
 pub mod channel;
+pub mod codec;
+pub mod encoding;
 pub mod message;
 pub mod typed;
 
 pub use channel::{ChannelEnd, ChannelId};
+pub use codec::CodecError;
+pub use encoding::{EncodingPolicy, PayloadEncoding, PayloadError};
 pub use message::{
     Compatibility, Message, MessageEnvelope, MessageId, MessagePayload, SchemaMismatchError,
     SchemaVersion, VersionPolicy,
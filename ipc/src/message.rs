@@ -1,9 +1,10 @@
 //! Message types and envelope structure
 
+use crate::encoding::{PayloadEncoding, PayloadError};
 use alloc::string::String;
 use alloc::vec::Vec;
 use core::fmt;
-use core_types::{new_uuid, ServiceId, TaskId};
+use core_types::{new_uuid, Cap, ServiceId, TaskId};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -247,6 +248,12 @@ pub struct MessageEnvelope {
     pub correlation_id: Option<MessageId>,
     /// Serialized payload (type-erased)
     pub payload: MessagePayload,
+    /// Capabilities transferred with this message
+    ///
+    /// The kernel moves these from the sender to the receiving task as part
+    /// of delivery, so authority never travels separately from the message.
+    #[serde(default)]
+    pub capabilities: Vec<Cap<()>>,
 }
 
 impl MessageEnvelope {
@@ -265,6 +272,7 @@ impl MessageEnvelope {
             schema_version,
             correlation_id: None,
             payload,
+            capabilities: Vec::new(),
         }
     }
 
@@ -280,6 +288,18 @@ impl MessageEnvelope {
         self
     }
 
+    /// Attaches a capability to be transferred with the message
+    pub fn with_capability(mut self, capability: Cap<()>) -> Self {
+        self.capabilities.push(capability);
+        self
+    }
+
+    /// Checks if the message carries any capabilities
+    #[inline]
+    pub fn carries_capabilities(&self) -> bool {
+        !self.capabilities.is_empty()
+    }
+
     /// Checks if this is a response to another message
     #[inline]
     pub fn is_response(&self) -> bool {
@@ -289,30 +309,59 @@ impl MessageEnvelope {
 
 /// Type-erased message payload
 ///
-/// In a real system, this would use a more sophisticated serialization
-/// mechanism (e.g., Cap'n Proto, protobuf). For now, we use JSON.
+/// Payloads are JSON by default. Hot paths can pick the compact binary
+/// encoding; the encoding travels with the bytes so receivers decode either
+/// format through the same [`MessagePayload::deserialize`] call.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessagePayload {
-    /// Serialized data (JSON for now)
+    /// Encoding of `data` (absent in payloads from before encodings existed)
+    #[serde(default)]
+    encoding: PayloadEncoding,
+    /// Serialized data
     data: Vec<u8>,
 }
 
 impl MessagePayload {
-    /// Creates a new payload from serializable data
+    /// Creates a new JSON payload from serializable data
     pub fn new<T: Serialize>(data: &T) -> Result<Self, serde_json::Error> {
         let json = serde_json::to_vec(data)?;
-        Ok(Self { data: json })
+        Ok(Self {
+            encoding: PayloadEncoding::Json,
+            data: json,
+        })
+    }
+
+    /// Creates a new payload using the given encoding
+    pub fn with_encoding<T: Serialize>(
+        data: &T,
+        encoding: PayloadEncoding,
+    ) -> Result<Self, PayloadError> {
+        Ok(Self {
+            encoding,
+            data: encoding.encode(data)?,
+        })
     }
 
     /// Deserializes the payload into a specific type
-    pub fn deserialize<T: for<'de> Deserialize<'de>>(&self) -> Result<T, serde_json::Error> {
-        serde_json::from_slice(&self.data)
+    pub fn deserialize<T: for<'de> Deserialize<'de>>(&self) -> Result<T, PayloadError> {
+        self.encoding.decode(&self.data)
+    }
+
+    /// Returns the payload encoding
+    #[inline]
+    pub fn encoding(&self) -> PayloadEncoding {
+        self.encoding
     }
 
     /// Returns the raw bytes
     pub fn as_bytes(&self) -> &[u8] {
         &self.data
     }
+
+    /// Wraps bytes that are already encoded with `encoding`
+    pub fn from_bytes(data: Vec<u8>, encoding: PayloadEncoding) -> Self {
+        Self { encoding, data }
+    }
 }
 
 /// A complete message with typed payload
@@ -339,6 +388,19 @@ impl<T: Serialize> Message<T> {
         Ok(Self { envelope, payload })
     }
 
+    /// Creates a new message with an explicit payload encoding
+    pub fn with_encoding(
+        destination: ServiceId,
+        action: String,
+        schema_version: SchemaVersion,
+        payload: T,
+        encoding: PayloadEncoding,
+    ) -> Result<Self, PayloadError> {
+        let payload_bytes = MessagePayload::with_encoding(&payload, encoding)?;
+        let envelope = MessageEnvelope::new(destination, action, schema_version, payload_bytes);
+        Ok(Self { envelope, payload })
+    }
+
     /// Converts this message into an envelope (consuming the payload)
     pub fn into_envelope(self) -> MessageEnvelope {
         self.envelope
@@ -382,6 +444,28 @@ mod tests {
         assert_eq!(deserialized, payload);
     }
 
+    #[test]
+    fn test_message_payload_binary_encoding() {
+        let payload = TestPayload { value: -42 };
+        let json = MessagePayload::new(&payload).unwrap();
+        let binary = MessagePayload::with_encoding(&payload, PayloadEncoding::Binary).unwrap();
+
+        assert_eq!(json.encoding(), PayloadEncoding::Json);
+        assert_eq!(binary.encoding(), PayloadEncoding::Binary);
+        assert!(binary.as_bytes().len() < json.as_bytes().len());
+        assert_eq!(binary.deserialize::<TestPayload>().unwrap(), payload);
+    }
+
+    #[test]
+    fn test_message_payload_without_encoding_tag_is_json() {
+        let legacy =
+            serde_json::json!({ "data": serde_json::to_vec(&TestPayload { value: 3 }).unwrap() });
+        let payload: MessagePayload = serde_json::from_value(legacy).unwrap();
+
+        assert_eq!(payload.encoding(), PayloadEncoding::Json);
+        assert_eq!(payload.deserialize::<TestPayload>().unwrap().value, 3);
+    }
+
     #[test]
     fn test_message_envelope_creation() {
         let dest = ServiceId::new();
@@ -449,6 +533,31 @@ mod tests {
         assert_eq!(envelope.source, Some(source));
     }
 
+    #[test]
+    fn test_message_with_capabilities() {
+        let payload = MessagePayload::new(&TestPayload { value: 1 }).unwrap();
+        let envelope = MessageEnvelope::new(
+            ServiceId::new(),
+            "share".to_string(),
+            SchemaVersion::new(1, 0),
+            payload,
+        );
+        assert!(!envelope.carries_capabilities());
+
+        let envelope = envelope
+            .with_capability(Cap::new(7))
+            .with_capability(Cap::new(9));
+        assert!(envelope.carries_capabilities());
+        assert_eq!(
+            envelope
+                .capabilities
+                .iter()
+                .map(|cap| cap.id())
+                .collect::<Vec<_>>(),
+            vec![7, 9]
+        );
+    }
+
     // ===== SchemaVersion comparison tests =====
 
     #[test]
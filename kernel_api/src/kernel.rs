@@ -1,6 +1,6 @@
 //! Kernel API trait and task management types
 
-use crate::{Duration, Instant, KernelError};
+use crate::{Duration, Instant, KernelError, WaitOutcome, WaitSet};
 use alloc::string::String;
 use alloc::vec::Vec;
 use core_types::{Cap, ServiceId, TaskId};
@@ -148,6 +148,40 @@ pub trait KernelApi {
         timeout: Option<Duration>,
     ) -> Result<MessageEnvelope, KernelError>;
 
+    /// Waits until one channel in a wait set has a message or the deadline passes
+    ///
+    /// This replaces polling channels one at a time. The calling task is
+    /// blocked (and stops consuming scheduler quanta) until the kernel can
+    /// report a ready channel. The message itself is not consumed; follow up
+    /// with `receive_message` on the returned channel, or use `receive_any`.
+    ///
+    /// # Arguments
+    ///
+    /// * `wait_set` - Channels to watch (in priority order) and an optional deadline
+    ///
+    /// # Returns
+    ///
+    /// `WaitOutcome::Ready` with the first ready channel, or
+    /// `WaitOutcome::TimedOut` if the deadline passed first.
+    fn select(&mut self, wait_set: &WaitSet) -> Result<WaitOutcome, KernelError>;
+
+    /// Receives the next message from whichever channel in the wait set is ready
+    ///
+    /// Convenience wrapper around `select` + `receive_message`. A deadline
+    /// expiring is reported as `KernelError::Timeout`.
+    fn receive_any(
+        &mut self,
+        wait_set: &WaitSet,
+    ) -> Result<(ChannelId, MessageEnvelope), KernelError> {
+        match self.select(wait_set)? {
+            WaitOutcome::Ready(channel) => {
+                let message = self.receive_message(channel, None)?;
+                Ok((channel, message))
+            }
+            WaitOutcome::TimedOut => Err(KernelError::Timeout),
+        }
+    }
+
     /// Returns the current time
     ///
     /// Unlike POSIX `time()`, this is explicit. In simulated kernels,
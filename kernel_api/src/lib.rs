@@ -33,6 +33,7 @@ pub mod kernel;
 pub mod syscalls;
 pub mod time;
 pub mod v0;
+pub mod wait;
 
 pub use error::KernelError;
 pub use kernel::{KernelApi, TaskDescriptor, TaskHandle};
@@ -43,3 +44,4 @@ pub use syscalls::{
 };
 pub use time::{Duration, Instant};
 pub use v0::KernelApiV0;
+pub use wait::{WaitOutcome, WaitSet};
@@ -4,7 +4,9 @@
 //! serialized into MessageEnvelope payloads, routed via a transport, and
 //! decoded back into typed responses.
 
-use crate::{Duration, Instant, KernelApi, KernelError, TaskDescriptor, TaskHandle};
+use crate::{
+    Duration, Instant, KernelApi, KernelError, TaskDescriptor, TaskHandle, WaitOutcome, WaitSet,
+};
 use alloc::format;
 use alloc::string::{String, ToString};
 use core::cell::RefCell;
@@ -39,6 +41,9 @@ pub enum SyscallRequestPayload {
         channel: ChannelId,
         timeout: Option<Duration>,
     },
+    Select {
+        wait_set: WaitSet,
+    },
     Now,
     Sleep {
         duration: Duration,
@@ -70,6 +75,7 @@ pub enum SyscallResponsePayload {
     CreateChannel(SyscallResult<ChannelId>),
     SendMessage(SyscallResult<()>),
     ReceiveMessage(SyscallResult<MessageEnvelope>),
+    Select(SyscallResult<WaitOutcome>),
     Now(SyscallResult<Instant>),
     Sleep(SyscallResult<()>),
     GrantCapability(SyscallResult<()>),
@@ -395,6 +401,9 @@ impl<K: KernelApi> SyscallServer<K> {
                         .map_err(SyscallError::from),
                 )
             }
+            SyscallRequestPayload::Select { wait_set } => SyscallResponsePayload::Select(
+                self.kernel.select(&wait_set).map_err(SyscallError::from),
+            ),
             SyscallRequestPayload::Now => SyscallResponsePayload::Now(Ok(self.kernel.now())),
             SyscallRequestPayload::Sleep { duration } => SyscallResponsePayload::Sleep(
                 self.kernel.sleep(duration).map_err(SyscallError::from),
@@ -545,6 +554,16 @@ impl<T: SyscallTransport> KernelApi for SyscallClient<T> {
         })
     }
 
+    fn select(&mut self, wait_set: &WaitSet) -> Result<WaitOutcome, KernelError> {
+        let response = self.round_trip(SyscallRequestPayload::Select {
+            wait_set: wait_set.clone(),
+        })?;
+        Self::extract(response, |payload| match payload {
+            SyscallResponsePayload::Select(result) => Some(result),
+            _ => None,
+        })
+    }
+
     fn now(&self) -> Instant {
         let response = self
             .round_trip(SyscallRequestPayload::Now)
@@ -667,6 +686,14 @@ mod tests {
             Err(KernelError::ReceiveFailed("No messages".to_string()))
         }
 
+        fn select(&mut self, wait_set: &WaitSet) -> Result<WaitOutcome, KernelError> {
+            Ok(wait_set
+                .channels()
+                .first()
+                .map(|channel| WaitOutcome::Ready(*channel))
+                .unwrap_or(WaitOutcome::TimedOut))
+        }
+
         fn now(&self) -> Instant {
             Instant::from_nanos(42)
         }
@@ -717,6 +744,28 @@ mod tests {
         assert_eq!(last.as_ref().unwrap().name, descriptor.name);
     }
 
+    #[test]
+    fn test_syscall_select_round_trip() {
+        let codec = SyscallCodec::new(ServiceId::new());
+        let server =
+            SyscallServer::new(MockKernel::default(), SyscallCodec::new(codec.service_id()));
+        let transport = LoopbackTransport::new(server);
+        let mut client = SyscallClient::new(transport, codec);
+
+        let channel = ChannelId::new();
+        let wait_set = WaitSet::new()
+            .with_channel(channel)
+            .with_deadline(Instant::from_nanos(1_000));
+        assert_eq!(
+            client.select(&wait_set).unwrap(),
+            WaitOutcome::Ready(channel)
+        );
+        assert_eq!(
+            client.select(&WaitSet::new()).unwrap(),
+            WaitOutcome::TimedOut
+        );
+    }
+
     #[test]
     fn test_syscall_codec_round_trip() {
         let codec = SyscallCodec::new(ServiceId::new());
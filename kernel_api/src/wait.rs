@@ -0,0 +1,151 @@
+//! Wait-set primitives for multi-channel receive
+//!
+//! A task that services several channels should not poll them one by one.
+//! Instead it builds a [`WaitSet`] naming the channels it is interested in,
+//! optionally bounded by a deadline, and asks the kernel to `select` on it.
+//! The kernel reports which channel became ready first (or that the deadline
+//! passed), and the task then receives from that channel.
+
+use crate::Instant;
+use alloc::vec::Vec;
+use ipc::ChannelId;
+use serde::{Deserialize, Serialize};
+
+/// A set of channels a task is waiting on, plus an optional deadline
+///
+/// Channel order is significant: when several channels are ready at the
+/// same time, the kernel reports the one that appears first in the set.
+/// This keeps `select` deterministic under simulation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaitSet {
+    channels: Vec<ChannelId>,
+    deadline: Option<Instant>,
+}
+
+impl WaitSet {
+    /// Creates an empty wait set with no deadline
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a channel to the wait set (builder style)
+    pub fn with_channel(mut self, channel: ChannelId) -> Self {
+        self.add(channel);
+        self
+    }
+
+    /// Sets the absolute deadline for the wait (builder style)
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Adds a channel to the wait set
+    ///
+    /// Adding a channel that is already present has no effect.
+    pub fn add(&mut self, channel: ChannelId) {
+        if !self.channels.contains(&channel) {
+            self.channels.push(channel);
+        }
+    }
+
+    /// Removes a channel from the wait set
+    pub fn remove(&mut self, channel: ChannelId) {
+        self.channels.retain(|existing| *existing != channel);
+    }
+
+    /// Sets or clears the absolute deadline
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
+    /// Returns the channels in priority order
+    pub fn channels(&self) -> &[ChannelId] {
+        &self.channels
+    }
+
+    /// Returns the absolute deadline, if any
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns true if the channel is part of the wait set
+    pub fn contains(&self, channel: ChannelId) -> bool {
+        self.channels.contains(&channel)
+    }
+
+    /// Returns true if no channels are being waited on
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Returns the number of channels in the wait set
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+}
+
+/// Result of waiting on a [`WaitSet`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WaitOutcome {
+    /// A message is available on this channel
+    Ready(ChannelId),
+    /// The deadline passed before any channel became ready
+    TimedOut,
+}
+
+impl WaitOutcome {
+    /// Returns the ready channel, if any
+    pub fn ready_channel(&self) -> Option<ChannelId> {
+        match self {
+            WaitOutcome::Ready(channel) => Some(*channel),
+            WaitOutcome::TimedOut => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_set_deduplicates_channels() {
+        let channel = ChannelId::new();
+        let mut wait_set = WaitSet::new().with_channel(channel);
+        wait_set.add(channel);
+
+        assert_eq!(wait_set.len(), 1);
+        assert!(wait_set.contains(channel));
+    }
+
+    #[test]
+    fn test_wait_set_preserves_order() {
+        let first = ChannelId::new();
+        let second = ChannelId::new();
+        let wait_set = WaitSet::new().with_channel(first).with_channel(second);
+
+        assert_eq!(wait_set.channels(), &[first, second]);
+    }
+
+    #[test]
+    fn test_wait_set_remove_and_deadline() {
+        let channel = ChannelId::new();
+        let mut wait_set = WaitSet::new()
+            .with_channel(channel)
+            .with_deadline(Instant::from_nanos(500));
+
+        assert_eq!(wait_set.deadline(), Some(Instant::from_nanos(500)));
+
+        wait_set.remove(channel);
+        wait_set.set_deadline(None);
+        assert!(wait_set.is_empty());
+        assert_eq!(wait_set.deadline(), None);
+    }
+
+    #[test]
+    fn test_wait_outcome_ready_channel() {
+        let channel = ChannelId::new();
+        assert_eq!(WaitOutcome::Ready(channel).ready_channel(), Some(channel));
+        assert_eq!(WaitOutcome::TimedOut.ready_channel(), None);
+    }
+}
@@ -168,8 +168,8 @@ impl BareMetalFilesystem {
         name: &str,
         content: &[u8],
     ) -> Result<ObjectId, TransactionError> {
-        // Try to unlink existing file first
-        let _ = self.fs.unlink(name, self.root_id, 0);
+        // Remove the existing file first (reclaiming it)
+        let _ = self.fs.rm(name, self.root_id, 0);
 
         // Create new file
         self.create_file(name, content)
@@ -183,7 +183,7 @@ impl BareMetalFilesystem {
 
     /// Delete a file
     pub fn delete_file(&mut self, name: &str) -> Result<(), TransactionError> {
-        self.fs.unlink(name, self.root_id, 0)?;
+        self.fs.rm(name, self.root_id, 0)?;
         Ok(())
     }
 
@@ -2450,7 +2450,10 @@ impl Ps2ParserState {
                 }
             }
             0x2F => {
-                if self.shift_pressed {
+                // V key - Ctrl+V starts a block selection in the editor
+                if self.ctrl_pressed {
+                    return Some(0x16); // Ctrl+V
+                } else if self.shift_pressed {
                     b'V'
                 } else {
                     b'v'
@@ -2,6 +2,9 @@
 //!
 //! This is a thin wrapper around editor_core that provides viewport management
 //! and rendering logic for bare-metal VGA environment.
+//!
+//! Normal-mode keys go straight to [`EditorCore`], so counts, motions,
+//! operators and `.` repeat match services_editor_vi.
 
 #[cfg(not(test))]
 extern crate alloc;
@@ -109,6 +112,9 @@ impl MinimalEditor {
             EditorMode::Insert => "-- INSERT --",
             EditorMode::Command => "-- COMMAND --",
             EditorMode::Search => "-- SEARCH --",
+            EditorMode::Visual => "-- VISUAL --",
+            EditorMode::VisualLine => "-- VISUAL LINE --",
+            EditorMode::VisualBlock => "-- VISUAL BLOCK --",
         }
     }
 
@@ -197,6 +203,22 @@ impl MinimalEditor {
                                 }
                                 true // Quit anyway
                             }
+                            CoreIoRequest::Read { path, line } => {
+                                match io.open(&path) {
+                                    Ok((content, _)) => {
+                                        if let CoreOutcome::StatusMessage(msg) =
+                                            self.core.insert_read(line, &content)
+                                        {
+                                            self.status = msg;
+                                        }
+                                        self.adjust_viewport();
+                                    }
+                                    Err(_) => {
+                                        self.status = String::from("Error: failed to read file");
+                                    }
+                                }
+                                false
+                            }
                         }
                     } else {
                         // No filesystem available - use old behavior
@@ -211,6 +233,10 @@ impl MinimalEditor {
                                 self.status = String::from("Filesystem unavailable");
                                 true // Quit anyway
                             }
+                            CoreIoRequest::Read { .. } => {
+                                self.status = String::from("Filesystem unavailable");
+                                false
+                            }
                         }
                     }
                 }
@@ -228,6 +254,10 @@ impl MinimalEditor {
                             self.status = String::from("Filesystem unavailable in test mode");
                             true // Quit anyway
                         }
+                        CoreIoRequest::Read { .. } => {
+                            self.status = String::from("Filesystem unavailable in test mode");
+                            false
+                        }
                     }
                 }
             }
@@ -251,7 +281,7 @@ impl MinimalEditor {
                 self.status.push_str(self.core.command_buffer());
             }
             EditorMode::Search => {
-                self.status.push('/');
+                self.status.push(self.core.search().direction().key());
                 self.status.push_str(self.core.search_query());
             }
             _ => {}
@@ -335,4 +335,25 @@ mod tests {
         assert_eq!(cursor.row, 0);
         assert_eq!(cursor.col, 3); // After 'c'
     }
+
+    #[test]
+    fn test_vi_operators_shared_with_core() {
+        let mut editor = MinimalEditor::new(24);
+        for &byte in b"ialpha beta gamma\nsecond line\nthird\x1bgg0" {
+            editor.process_byte(byte);
+        }
+
+        for &byte in b"dwjdd" {
+            editor.process_byte(byte);
+        }
+        assert_eq!(editor.get_viewport_line(0), Some("beta gamma"));
+        assert_eq!(editor.get_viewport_line(1), Some("third"));
+
+        for &byte in b"ggA!\x1bj." {
+            editor.process_byte(byte);
+        }
+        assert_eq!(editor.get_viewport_line(0), Some("beta gamma!"));
+        assert_eq!(editor.get_viewport_line(1), Some("third!"));
+        assert_eq!(editor.mode(), EditorMode::Normal);
+    }
 }
@@ -0,0 +1,279 @@
+//! Clipboard over kernel channels
+//!
+//! [`KernelTransport`] lets a component reach a clipboard service running as
+//! a separate task. Each client gets a [`ClipboardEndpoint`]: requests go out
+//! on one channel and correlated responses come back on the other.
+//! [`ClipboardService::serve`] is the service's side of the same endpoint.
+
+use crate::client::ClipboardTransport;
+use crate::{ClipboardError, ClipboardService};
+use alloc::format;
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use ipc::{ChannelId, MessageEnvelope};
+use kernel_api::{Duration, KernelApi, KernelError, WaitOutcome, WaitSet};
+
+/// How long a call waits for its response unless configured otherwise
+pub const DEFAULT_CALL_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// The pair of channels joining one client to the clipboard service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipboardEndpoint {
+    /// Carries requests from the client to the service
+    pub requests: ChannelId,
+    /// Carries responses from the service back to the client
+    pub responses: ChannelId,
+}
+
+impl ClipboardEndpoint {
+    /// Creates both channels on `kernel`
+    pub fn create<K: KernelApi>(kernel: &mut K) -> Result<Self, ClipboardError> {
+        Ok(Self {
+            requests: kernel.create_channel().map_err(transport_error)?,
+            responses: kernel.create_channel().map_err(transport_error)?,
+        })
+    }
+}
+
+/// Sends request envelopes through the kernel and waits for the response
+pub struct KernelTransport<K: KernelApi> {
+    kernel: Rc<RefCell<K>>,
+    endpoint: ClipboardEndpoint,
+    timeout: Duration,
+}
+
+impl<K: KernelApi> KernelTransport<K> {
+    /// Creates a transport calling the service over `endpoint`
+    pub fn new(kernel: Rc<RefCell<K>>, endpoint: ClipboardEndpoint) -> Self {
+        Self {
+            kernel,
+            endpoint,
+            timeout: DEFAULT_CALL_TIMEOUT,
+        }
+    }
+
+    /// Sets how long a call waits for its response
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The channels this transport uses
+    pub fn endpoint(&self) -> ClipboardEndpoint {
+        self.endpoint
+    }
+}
+
+impl<K: KernelApi> ClipboardTransport for KernelTransport<K> {
+    fn call(&mut self, request: MessageEnvelope) -> Result<MessageEnvelope, ClipboardError> {
+        let mut kernel = self.kernel.borrow_mut();
+        let request_id = request.id;
+        kernel
+            .send_message(self.endpoint.requests, request)
+            .map_err(transport_error)?;
+
+        let wait_set = WaitSet::new()
+            .with_channel(self.endpoint.responses)
+            .with_deadline(kernel.now() + self.timeout);
+        loop {
+            let (_, response) = kernel.receive_any(&wait_set).map_err(transport_error)?;
+            // Responses to earlier calls that gave up waiting are dropped
+            if response.correlation_id == Some(request_id) {
+                return Ok(response);
+            }
+        }
+    }
+}
+
+impl ClipboardService {
+    /// Answers every request waiting on `endpoint` without blocking
+    ///
+    /// Returns how many requests were answered. Envelopes that are not
+    /// clipboard requests are dropped unanswered.
+    pub fn serve<K: KernelApi>(
+        &mut self,
+        kernel: &mut K,
+        endpoint: ClipboardEndpoint,
+    ) -> Result<usize, ClipboardError> {
+        let wait_set = WaitSet::new()
+            .with_channel(endpoint.requests)
+            .with_deadline(kernel.now());
+        let mut served = 0;
+        loop {
+            match kernel.select(&wait_set).map_err(transport_error)? {
+                WaitOutcome::Ready(_) => {}
+                WaitOutcome::TimedOut => return Ok(served),
+            }
+            let request = kernel
+                .receive_message(endpoint.requests, None)
+                .map_err(transport_error)?;
+            let Ok(response) = self.handle_envelope(&request) else {
+                continue;
+            };
+            kernel
+                .send_message(endpoint.responses, response)
+                .map_err(transport_error)?;
+            served += 1;
+        }
+    }
+}
+
+fn transport_error(error: KernelError) -> ClipboardError {
+    ClipboardError::Transport(format!("{}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClipboardAccess, ClipboardClient};
+    use alloc::vec::Vec;
+    use core_types::{ServiceId, TaskId};
+    use kernel_api::{Instant, TaskDescriptor, TaskHandle};
+    use sim_kernel::SimulatedKernel;
+
+    /// Runs the clipboard service as soon as a request reaches it, standing
+    /// in for the scheduler switching to the service task
+    struct ClipboardHost {
+        inner: SimulatedKernel,
+        service: ClipboardService,
+        endpoints: Vec<ClipboardEndpoint>,
+        served: usize,
+    }
+
+    impl ClipboardHost {
+        fn new() -> Self {
+            Self {
+                inner: SimulatedKernel::new(),
+                service: ClipboardService::new(),
+                endpoints: Vec::new(),
+                served: 0,
+            }
+        }
+
+        fn connect(&mut self, component: &str, access: ClipboardAccess) -> ClipboardConnection {
+            let endpoint = ClipboardEndpoint::create(&mut self.inner).unwrap();
+            self.endpoints.push(endpoint);
+            let grant = self.service.grant(component, access).unwrap();
+            ClipboardConnection { endpoint, grant }
+        }
+    }
+
+    struct ClipboardConnection {
+        endpoint: ClipboardEndpoint,
+        grant: crate::ClipboardGrant,
+    }
+
+    impl KernelApi for ClipboardHost {
+        fn spawn_task(&mut self, descriptor: TaskDescriptor) -> Result<TaskHandle, KernelError> {
+            self.inner.spawn_task(descriptor)
+        }
+
+        fn create_channel(&mut self) -> Result<ChannelId, KernelError> {
+            self.inner.create_channel()
+        }
+
+        fn send_message(
+            &mut self,
+            channel: ChannelId,
+            message: MessageEnvelope,
+        ) -> Result<(), KernelError> {
+            self.inner.send_message(channel, message)?;
+            if let Some(endpoint) = self.endpoints.iter().find(|e| e.requests == channel) {
+                self.served += self
+                    .service
+                    .serve(&mut self.inner, *endpoint)
+                    .map_err(|e| KernelError::SendFailed(format!("{}", e)))?;
+            }
+            Ok(())
+        }
+
+        fn receive_message(
+            &mut self,
+            channel: ChannelId,
+            timeout: Option<Duration>,
+        ) -> Result<MessageEnvelope, KernelError> {
+            self.inner.receive_message(channel, timeout)
+        }
+
+        fn select(&mut self, wait_set: &WaitSet) -> Result<WaitOutcome, KernelError> {
+            self.inner.select(wait_set)
+        }
+
+        fn now(&self) -> Instant {
+            self.inner.now()
+        }
+
+        fn sleep(&mut self, duration: Duration) -> Result<(), KernelError> {
+            self.inner.sleep(duration)
+        }
+
+        fn grant_capability(
+            &mut self,
+            task: TaskId,
+            capability: core_types::Cap<()>,
+        ) -> Result<(), KernelError> {
+            self.inner.grant_capability(task, capability)
+        }
+
+        fn register_service(
+            &mut self,
+            service_id: ServiceId,
+            channel: ChannelId,
+        ) -> Result<(), KernelError> {
+            self.inner.register_service(service_id, channel)
+        }
+
+        fn lookup_service(&self, service_id: ServiceId) -> Result<ChannelId, KernelError> {
+            self.inner.lookup_service(service_id)
+        }
+    }
+
+    #[test]
+    fn test_copy_and_paste_round_trip_through_simulated_kernel() {
+        let host = Rc::new(RefCell::new(ClipboardHost::new()));
+        let console = host
+            .borrow_mut()
+            .connect("console", ClipboardAccess::PublishOnly);
+        let editor = host
+            .borrow_mut()
+            .connect("editor", ClipboardAccess::ReadWrite);
+        let mut console = ClipboardClient::new(
+            console.grant,
+            KernelTransport::new(host.clone(), console.endpoint),
+        );
+        let mut editor = ClipboardClient::new(
+            editor.grant,
+            KernelTransport::new(host.clone(), editor.endpoint),
+        );
+
+        console.publish("selected text", false).unwrap();
+        let pasted = editor.latest().unwrap().unwrap();
+        assert_eq!(pasted.text, "selected text");
+        assert_eq!(pasted.source, "console");
+        assert!(matches!(
+            console.latest(),
+            Err(ClipboardError::ReadDenied { .. })
+        ));
+        assert_eq!(host.borrow().served, 3);
+    }
+
+    #[test]
+    fn test_call_times_out_when_nothing_serves_the_endpoint() {
+        let kernel = Rc::new(RefCell::new(SimulatedKernel::new()));
+        let endpoint = ClipboardEndpoint::create(&mut *kernel.borrow_mut()).unwrap();
+        let grant = ClipboardService::new()
+            .grant("cli", ClipboardAccess::ReadWrite)
+            .unwrap();
+        let mut client = ClipboardClient::new(
+            grant,
+            KernelTransport::new(kernel.clone(), endpoint).with_timeout(Duration::from_millis(50)),
+        );
+
+        let before = kernel.borrow().now();
+        assert!(matches!(
+            client.publish("lost", false),
+            Err(ClipboardError::Transport(_))
+        ));
+        assert_eq!(kernel.borrow().now(), before + Duration::from_millis(50));
+    }
+}
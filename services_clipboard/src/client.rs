@@ -0,0 +1,156 @@
+//! Clipboard client
+//!
+//! A [`ClipboardClient`] is what a component holds to use the clipboard: its
+//! grant plus a [`ClipboardTransport`] that carries request envelopes to the
+//! service and brings the responses back.
+
+use crate::{
+    ClipEntry, ClipboardError, ClipboardGrant, ClipboardOp, ClipboardReply, ClipboardRequest,
+    ClipboardResponse, ClipboardService,
+};
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
+use ipc::MessageEnvelope;
+
+/// Delivers a request envelope and returns the response
+pub trait ClipboardTransport {
+    fn call(&mut self, request: MessageEnvelope) -> Result<MessageEnvelope, ClipboardError>;
+}
+
+/// Direct delivery to a service in the same address space
+impl ClipboardTransport for ClipboardService {
+    fn call(&mut self, request: MessageEnvelope) -> Result<MessageEnvelope, ClipboardError> {
+        self.handle_envelope(&request)
+    }
+}
+
+/// A transport shared by several clients
+impl<T: ClipboardTransport> ClipboardTransport for Rc<RefCell<T>> {
+    fn call(&mut self, request: MessageEnvelope) -> Result<MessageEnvelope, ClipboardError> {
+        self.borrow_mut().call(request)
+    }
+}
+
+/// A component's handle on the clipboard
+pub struct ClipboardClient {
+    grant: ClipboardGrant,
+    transport: Box<dyn ClipboardTransport>,
+}
+
+impl ClipboardClient {
+    /// Creates a client acting under `grant`
+    pub fn new(grant: ClipboardGrant, transport: impl ClipboardTransport + 'static) -> Self {
+        Self {
+            grant,
+            transport: Box::new(transport),
+        }
+    }
+
+    /// The grant requests are made under
+    pub fn grant(&self) -> &ClipboardGrant {
+        &self.grant
+    }
+
+    /// Whether the grant allows reading clips
+    pub fn can_read(&self) -> bool {
+        self.grant.access.can_read()
+    }
+
+    /// Publishes text; returns the clip's ID
+    pub fn publish(&mut self, text: &str, linewise: bool) -> Result<u64, ClipboardError> {
+        match self.call(ClipboardOp::Publish {
+            text: text.to_string(),
+            linewise,
+        })? {
+            ClipboardReply::Published(id) => Ok(id),
+            _ => Err(unexpected_reply()),
+        }
+    }
+
+    /// Reads the newest clip
+    pub fn latest(&mut self) -> Result<Option<ClipEntry>, ClipboardError> {
+        self.read(0)
+    }
+
+    /// Reads a clip by age, 0 being the newest
+    pub fn read(&mut self, index: usize) -> Result<Option<ClipEntry>, ClipboardError> {
+        match self.call(ClipboardOp::Read { index })? {
+            ClipboardReply::Entry(entry) => Ok(entry),
+            _ => Err(unexpected_reply()),
+        }
+    }
+
+    /// Reads the whole history, newest first
+    pub fn history(&mut self) -> Result<Vec<ClipEntry>, ClipboardError> {
+        match self.call(ClipboardOp::History)? {
+            ClipboardReply::History(entries) => Ok(entries),
+            _ => Err(unexpected_reply()),
+        }
+    }
+
+    fn call(&mut self, op: ClipboardOp) -> Result<ClipboardReply, ClipboardError> {
+        let request = ClipboardRequest {
+            grant: self.grant.id,
+            op,
+        }
+        .into_envelope()?;
+        let request_id = request.id;
+        let response = self.transport.call(request)?;
+        if response.correlation_id != Some(request_id) {
+            return Err(ClipboardError::Codec("correlation mismatch".to_string()));
+        }
+        ClipboardResponse::from_envelope(&response)?.result
+    }
+}
+
+impl fmt::Debug for ClipboardClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClipboardClient")
+            .field("grant", &self.grant)
+            .finish_non_exhaustive()
+    }
+}
+
+fn unexpected_reply() -> ClipboardError {
+    ClipboardError::Codec("unexpected reply".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClipboardAccess;
+
+    #[test]
+    fn test_clients_share_one_service() {
+        let service = Rc::new(RefCell::new(ClipboardService::new()));
+        let grant = service
+            .borrow_mut()
+            .grant("console", ClipboardAccess::PublishOnly)
+            .unwrap();
+        let mut console = ClipboardClient::new(grant, service.clone());
+        let grant = service
+            .borrow_mut()
+            .grant("editor", ClipboardAccess::ReadWrite)
+            .unwrap();
+        let mut editor = ClipboardClient::new(grant, service.clone());
+
+        console.publish("first", false).unwrap();
+        editor.publish("second\n", true).unwrap();
+
+        assert!(!console.can_read());
+        assert!(matches!(
+            console.latest(),
+            Err(ClipboardError::ReadDenied { .. })
+        ));
+        let latest = editor.latest().unwrap().unwrap();
+        assert!(latest.linewise);
+        assert_eq!(latest.source, "editor");
+        let history = editor.history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].source, "console");
+    }
+}
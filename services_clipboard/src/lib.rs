@@ -0,0 +1,622 @@
+#![no_std]
+
+//! # Clipboard Service
+//!
+//! A system-wide clipboard shared by the editor, the CLI and console
+//! selection, reached over IPC.
+//!
+//! ## Philosophy
+//!
+//! - **No ambient access**: Components act through a [`ClipboardGrant`]
+//!   issued by the service; without one there is nothing to call. Grants
+//!   are named by [`GrantToken`]s drawn from an [`EntropySource`], so one
+//!   cannot be reached by counting from another. Without entropy no grant
+//!   is issued
+//! - **Publishing is cheap, reading is granted**: Every grant may publish,
+//!   but only [`ClipboardAccess::ReadWrite`] grants may read what others
+//!   copied
+//! - **History, not a single slot**: Recent clips are kept newest first,
+//!   bounded by a fixed limit
+//! - **Attributed**: Each clip records the component that published it,
+//!   taken from the grant rather than from the request
+//!
+//! ## Example
+//!
+//! ```ignore
+//! use services_clipboard::{ClipboardAccess, ClipboardClient, ClipboardService};
+//!
+//! let service = Rc::new(RefCell::new(ClipboardService::new()));
+//! let grant = service.borrow_mut().grant("editor", ClipboardAccess::ReadWrite)?;
+//! let mut client = ClipboardClient::new(grant, service.clone());
+//!
+//! client.publish("hello", false)?;
+//! assert_eq!(client.latest()?.unwrap().text, "hello");
+//! ```
+
+extern crate alloc;
+
+pub mod channel;
+pub mod client;
+
+pub use channel::{ClipboardEndpoint, KernelTransport};
+pub use client::{ClipboardClient, ClipboardTransport};
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core_types::clipboard_service_id;
+use hal::EntropySource;
+use ipc::{MessageEnvelope, MessageId, MessagePayload, SchemaVersion};
+use serde::{Deserialize, Serialize};
+
+/// Clipboard message schema version (v1.0)
+pub const CLIPBOARD_SCHEMA_VERSION: SchemaVersion = SchemaVersion::new(1, 0);
+
+/// Envelope action for clipboard requests
+pub const CLIPBOARD_REQUEST_ACTION: &str = "clipboard.request";
+
+/// Envelope action for clipboard responses
+pub const CLIPBOARD_RESPONSE_ACTION: &str = "clipboard.response";
+
+/// Number of clips kept unless configured otherwise
+pub const DEFAULT_HISTORY_LIMIT: usize = 32;
+
+/// What a grant allows its holder to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ClipboardAccess {
+    /// May publish clips but not read any
+    PublishOnly,
+    /// May publish clips and read the history
+    ReadWrite,
+}
+
+impl ClipboardAccess {
+    /// Whether the holder may read clips
+    pub fn can_read(self) -> bool {
+        self == ClipboardAccess::ReadWrite
+    }
+}
+
+/// Secret naming an issued grant
+///
+/// Tokens are 128 bits from an [`EntropySource`] rather than a counter or
+/// an ID generator, so holding one grant says nothing about the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct GrantToken(u128);
+
+impl GrantToken {
+    fn generate(entropy: &mut dyn EntropySource) -> Result<Self, ClipboardError> {
+        let mut bytes = [0u8; 16];
+        entropy
+            .fill_bytes(&mut bytes)
+            .map_err(|_| ClipboardError::EntropyUnavailable)?;
+        Ok(Self(u128::from_le_bytes(bytes)))
+    }
+}
+
+impl fmt::Display for GrantToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+/// Right of one component to use the clipboard
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipboardGrant {
+    /// Grant token, sent with every request
+    pub id: GrantToken,
+    /// Component the grant was issued to
+    pub component: String,
+    /// What the grant allows
+    pub access: ClipboardAccess,
+}
+
+/// A piece of copied text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipEntry {
+    /// Sequence number, increasing with each publish
+    pub id: u64,
+    /// The copied text
+    pub text: String,
+    /// Whether the text is whole lines
+    pub linewise: bool,
+    /// Component that published the clip
+    pub source: String,
+}
+
+/// Clipboard errors
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClipboardError {
+    /// The grant was never issued or has been revoked
+    UnknownGrant(GrantToken),
+    /// The grant does not allow reading
+    ReadDenied { component: String },
+    /// Empty text is not published
+    EmptyClip,
+    /// No grant token could be generated
+    EntropyUnavailable,
+    /// A message could not be encoded or decoded
+    Codec(String),
+    /// The kernel could not carry a request or its response
+    Transport(String),
+}
+
+impl fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClipboardError::UnknownGrant(id) => write!(f, "Unknown clipboard grant: {}", id),
+            ClipboardError::ReadDenied { component } => {
+                write!(f, "Clipboard read denied for {}", component)
+            }
+            ClipboardError::EmptyClip => write!(f, "Nothing to copy"),
+            ClipboardError::EntropyUnavailable => {
+                write!(f, "No entropy source for clipboard grants")
+            }
+            ClipboardError::Codec(message) => write!(f, "Clipboard codec error: {}", message),
+            ClipboardError::Transport(message) => {
+                write!(f, "Clipboard transport error: {}", message)
+            }
+        }
+    }
+}
+
+/// An operation requested of the clipboard
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClipboardOp {
+    /// Add a clip to the front of the history
+    Publish { text: String, linewise: bool },
+    /// Read a clip by age, 0 being the newest
+    Read { index: usize },
+    /// Read the whole history, newest first
+    History,
+}
+
+/// Clipboard request payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipboardRequest {
+    /// Grant the request is made under
+    pub grant: GrantToken,
+    /// What to do
+    pub op: ClipboardOp,
+}
+
+impl ClipboardRequest {
+    /// Wraps this request in an envelope addressed to the clipboard service
+    pub fn into_envelope(self) -> Result<MessageEnvelope, ClipboardError> {
+        let payload = MessagePayload::new(&self).map_err(codec_error)?;
+        Ok(MessageEnvelope::new(
+            clipboard_service_id(),
+            CLIPBOARD_REQUEST_ACTION,
+            CLIPBOARD_SCHEMA_VERSION,
+            payload,
+        ))
+    }
+
+    /// Reads a request out of an envelope
+    pub fn from_envelope(envelope: &MessageEnvelope) -> Result<Self, ClipboardError> {
+        decode(envelope, CLIPBOARD_REQUEST_ACTION)
+    }
+}
+
+/// Successful result of a clipboard operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClipboardReply {
+    /// The clip was published with this ID
+    Published(u64),
+    /// The clip read, if the history reaches that far
+    Entry(Option<ClipEntry>),
+    /// The history, newest first
+    History(Vec<ClipEntry>),
+}
+
+/// Clipboard response payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipboardResponse {
+    pub result: Result<ClipboardReply, ClipboardError>,
+}
+
+impl ClipboardResponse {
+    /// Wraps this response in an envelope correlated to a request
+    pub fn into_envelope(self, correlation: MessageId) -> Result<MessageEnvelope, ClipboardError> {
+        let payload = MessagePayload::new(&self).map_err(codec_error)?;
+        Ok(MessageEnvelope::new(
+            clipboard_service_id(),
+            CLIPBOARD_RESPONSE_ACTION,
+            CLIPBOARD_SCHEMA_VERSION,
+            payload,
+        )
+        .with_correlation(correlation))
+    }
+
+    /// Reads a response out of an envelope
+    pub fn from_envelope(envelope: &MessageEnvelope) -> Result<Self, ClipboardError> {
+        decode(envelope, CLIPBOARD_RESPONSE_ACTION)
+    }
+}
+
+fn decode<T: for<'de> Deserialize<'de>>(
+    envelope: &MessageEnvelope,
+    action: &str,
+) -> Result<T, ClipboardError> {
+    if envelope.action != action {
+        return Err(ClipboardError::Codec("unexpected action".to_string()));
+    }
+    envelope.payload.deserialize().map_err(codec_error)
+}
+
+fn codec_error(error: impl fmt::Display) -> ClipboardError {
+    ClipboardError::Codec(error.to_string())
+}
+
+/// The clipboard service
+///
+/// Holds the issued grants and the clip history. Components reach it with
+/// [`ClipboardRequest`] envelopes, usually through a [`ClipboardClient`].
+///
+/// Hosted builds draw grant tokens from [`hal::HostEntropy`]. Bare-metal
+/// builds have no default source: until one is supplied with
+/// [`Self::with_entropy`], every grant fails with
+/// [`ClipboardError::EntropyUnavailable`].
+pub struct ClipboardService {
+    grants: BTreeMap<GrantToken, ClipboardGrant>,
+    history: VecDeque<ClipEntry>,
+    history_limit: usize,
+    next_entry: u64,
+    entropy: Option<Box<dyn EntropySource>>,
+}
+
+impl ClipboardService {
+    /// Creates an empty clipboard keeping [`DEFAULT_HISTORY_LIMIT`] clips
+    pub fn new() -> Self {
+        Self::with_history_limit(DEFAULT_HISTORY_LIMIT)
+    }
+
+    /// Creates an empty clipboard keeping at most `limit` clips (at least one)
+    pub fn with_history_limit(limit: usize) -> Self {
+        Self {
+            grants: BTreeMap::new(),
+            history: VecDeque::new(),
+            history_limit: limit.max(1),
+            next_entry: 1,
+            entropy: default_entropy(),
+        }
+    }
+
+    /// Draws grant tokens from `entropy` (builder style)
+    pub fn with_entropy(mut self, entropy: impl EntropySource + 'static) -> Self {
+        self.entropy = Some(Box::new(entropy));
+        self
+    }
+
+    /// Issues a grant to a component
+    ///
+    /// Fails closed with [`ClipboardError::EntropyUnavailable`] when no
+    /// token can be generated.
+    pub fn grant(
+        &mut self,
+        component: impl Into<String>,
+        access: ClipboardAccess,
+    ) -> Result<ClipboardGrant, ClipboardError> {
+        let entropy = self
+            .entropy
+            .as_deref_mut()
+            .ok_or(ClipboardError::EntropyUnavailable)?;
+        let mut id = GrantToken::generate(entropy)?;
+        while self.grants.contains_key(&id) {
+            id = GrantToken::generate(entropy)?;
+        }
+        let grant = ClipboardGrant {
+            id,
+            component: component.into(),
+            access,
+        };
+        self.grants.insert(grant.id, grant.clone());
+        Ok(grant)
+    }
+
+    /// Revokes a grant; returns false if it was not active
+    pub fn revoke(&mut self, grant_id: GrantToken) -> bool {
+        self.grants.remove(&grant_id).is_some()
+    }
+
+    /// Adds a clip to the front of the history
+    pub fn publish(
+        &mut self,
+        grant_id: GrantToken,
+        text: impl Into<String>,
+        linewise: bool,
+    ) -> Result<u64, ClipboardError> {
+        let source = self.check(grant_id)?.component.clone();
+        let text = text.into();
+        if text.is_empty() {
+            return Err(ClipboardError::EmptyClip);
+        }
+        let id = self.next_entry;
+        self.next_entry += 1;
+        self.history.push_front(ClipEntry {
+            id,
+            text,
+            linewise,
+            source,
+        });
+        self.history.truncate(self.history_limit);
+        Ok(id)
+    }
+
+    /// Reads a clip by age, 0 being the newest
+    pub fn read(
+        &self,
+        grant_id: GrantToken,
+        index: usize,
+    ) -> Result<Option<&ClipEntry>, ClipboardError> {
+        self.check_read(grant_id)?;
+        Ok(self.history.get(index))
+    }
+
+    /// Reads the newest clip
+    pub fn latest(&self, grant_id: GrantToken) -> Result<Option<&ClipEntry>, ClipboardError> {
+        self.read(grant_id, 0)
+    }
+
+    /// Reads the whole history, newest first
+    pub fn history(
+        &self,
+        grant_id: GrantToken,
+    ) -> Result<impl Iterator<Item = &ClipEntry>, ClipboardError> {
+        self.check_read(grant_id)?;
+        Ok(self.history.iter())
+    }
+
+    /// Serves a request envelope, returning the correlated response
+    ///
+    /// Failures of the operation itself travel back in the response; only an
+    /// envelope that is not a clipboard request is an error here.
+    pub fn handle_envelope(
+        &mut self,
+        envelope: &MessageEnvelope,
+    ) -> Result<MessageEnvelope, ClipboardError> {
+        let request = ClipboardRequest::from_envelope(envelope)?;
+        let result = match request.op {
+            ClipboardOp::Publish { text, linewise } => self
+                .publish(request.grant, text, linewise)
+                .map(ClipboardReply::Published),
+            ClipboardOp::Read { index } => self
+                .read(request.grant, index)
+                .map(|entry| ClipboardReply::Entry(entry.cloned())),
+            ClipboardOp::History => self
+                .history(request.grant)
+                .map(|entries| ClipboardReply::History(entries.cloned().collect())),
+        };
+        ClipboardResponse { result }.into_envelope(envelope.id)
+    }
+
+    fn check(&self, grant_id: GrantToken) -> Result<&ClipboardGrant, ClipboardError> {
+        self.grants
+            .get(&grant_id)
+            .ok_or(ClipboardError::UnknownGrant(grant_id))
+    }
+
+    fn check_read(&self, grant_id: GrantToken) -> Result<(), ClipboardError> {
+        let grant = self.check(grant_id)?;
+        if grant.access.can_read() {
+            Ok(())
+        } else {
+            Err(ClipboardError::ReadDenied {
+                component: grant.component.clone(),
+            })
+        }
+    }
+}
+
+impl Default for ClipboardService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ClipboardService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClipboardService")
+            .field("grants", &self.grants)
+            .field("history", &self.history)
+            .field("history_limit", &self.history_limit)
+            .field("next_entry", &self.next_entry)
+            .field("entropy", &self.entropy.is_some())
+            .finish()
+    }
+}
+
+#[cfg(not(target_os = "none"))]
+fn default_entropy() -> Option<Box<dyn EntropySource>> {
+    Some(Box::new(hal::HostEntropy))
+}
+
+#[cfg(target_os = "none")]
+fn default_entropy() -> Option<Box<dyn EntropySource>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use hal::EntropyError;
+
+    /// Replays fixed bytes, then runs dry
+    struct ScriptedEntropy(VecDeque<u8>);
+
+    impl EntropySource for ScriptedEntropy {
+        fn fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), EntropyError> {
+            if self.0.len() < dest.len() {
+                return Err(EntropyError::Unavailable);
+            }
+            for byte in dest.iter_mut() {
+                *byte = self.0.pop_front().unwrap();
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_grants_scope_reads() {
+        let mut service = ClipboardService::new();
+        let editor = service.grant("editor", ClipboardAccess::ReadWrite).unwrap();
+        let console = service
+            .grant("console", ClipboardAccess::PublishOnly)
+            .unwrap();
+
+        service.publish(console.id, "selected", false).unwrap();
+        assert_eq!(
+            service.read(console.id, 0),
+            Err(ClipboardError::ReadDenied {
+                component: "console".to_string()
+            })
+        );
+        let entry = service.latest(editor.id).unwrap().unwrap();
+        assert_eq!(
+            (entry.text.as_str(), entry.source.as_str()),
+            ("selected", "console")
+        );
+
+        assert!(service.revoke(editor.id));
+        assert_eq!(
+            service.latest(editor.id),
+            Err(ClipboardError::UnknownGrant(editor.id))
+        );
+        assert_eq!(
+            service.publish(console.id, "", false),
+            Err(ClipboardError::EmptyClip)
+        );
+    }
+
+    #[test]
+    fn test_history_is_bounded_newest_first() {
+        let mut service = ClipboardService::with_history_limit(2);
+        let grant = service.grant("cli", ClipboardAccess::ReadWrite).unwrap();
+        for text in ["one", "two", "three"] {
+            service.publish(grant.id, text, false).unwrap();
+        }
+
+        let texts: Vec<&str> = service
+            .history(grant.id)
+            .unwrap()
+            .map(|entry| entry.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["three", "two"]);
+        assert_eq!(service.read(grant.id, 1).unwrap().unwrap().id, 2);
+        assert_eq!(service.read(grant.id, 2).unwrap(), None);
+    }
+
+    #[test]
+    fn test_envelope_round_trip() {
+        let mut service = ClipboardService::new();
+        let grant = service
+            .grant("editor", ClipboardAccess::PublishOnly)
+            .unwrap();
+
+        let request = ClipboardRequest {
+            grant: grant.id,
+            op: ClipboardOp::Publish {
+                text: "line\n".to_string(),
+                linewise: true,
+            },
+        }
+        .into_envelope()
+        .unwrap();
+        assert_eq!(request.destination, clipboard_service_id());
+
+        let response = service.handle_envelope(&request).unwrap();
+        assert_eq!(response.correlation_id, Some(request.id));
+        assert_eq!(
+            ClipboardResponse::from_envelope(&response).unwrap().result,
+            Ok(ClipboardReply::Published(1))
+        );
+
+        let read = ClipboardRequest {
+            grant: grant.id,
+            op: ClipboardOp::History,
+        }
+        .into_envelope()
+        .unwrap();
+        let response = service.handle_envelope(&read).unwrap();
+        assert!(matches!(
+            ClipboardResponse::from_envelope(&response).unwrap().result,
+            Err(ClipboardError::ReadDenied { .. })
+        ));
+
+        assert!(matches!(
+            service.handle_envelope(&response),
+            Err(ClipboardError::Codec(_))
+        ));
+    }
+
+    #[test]
+    fn test_forged_grant_is_rejected() {
+        let mut service = ClipboardService::new();
+        let console = service
+            .grant("console", ClipboardAccess::PublishOnly)
+            .unwrap();
+        let editor = service.grant("editor", ClipboardAccess::ReadWrite).unwrap();
+        service.publish(editor.id, "secret", false).unwrap();
+
+        // Knowing one grant does not lead to the next
+        assert_ne!(editor.id, GrantToken(console.id.0.wrapping_add(1)));
+        for forged in [
+            GrantToken(1),
+            GrantToken(2),
+            GrantToken(console.id.0.wrapping_add(1)),
+        ] {
+            let request = ClipboardRequest {
+                grant: forged,
+                op: ClipboardOp::History,
+            }
+            .into_envelope()
+            .unwrap();
+            let response = service.handle_envelope(&request).unwrap();
+            assert_eq!(
+                ClipboardResponse::from_envelope(&response).unwrap().result,
+                Err(ClipboardError::UnknownGrant(forged))
+            );
+        }
+        assert_eq!(service.latest(editor.id).unwrap().unwrap().text, "secret");
+    }
+
+    #[test]
+    fn test_grant_tokens_come_from_the_entropy_source() {
+        let bytes: VecDeque<u8> = (0..48).collect();
+        let mut service = ClipboardService::new().with_entropy(ScriptedEntropy(bytes));
+
+        let first = service.grant("editor", ClipboardAccess::ReadWrite).unwrap();
+        assert_eq!(
+            first.id,
+            GrantToken(u128::from_le_bytes(core::array::from_fn(|i| i as u8)))
+        );
+        // A token already in use is drawn again rather than shared
+        let mut again = ClipboardService::new().with_entropy(ScriptedEntropy(
+            (0..16).chain(0..16).chain(16..32).collect(),
+        ));
+        let a = again.grant("a", ClipboardAccess::ReadWrite).unwrap();
+        let b = again.grant("b", ClipboardAccess::ReadWrite).unwrap();
+        assert_eq!(a.id, first.id);
+        assert_ne!(b.id, a.id);
+    }
+
+    #[test]
+    fn test_grant_fails_closed_without_entropy() {
+        let mut service = ClipboardService::new().with_entropy(ScriptedEntropy(VecDeque::new()));
+        assert_eq!(
+            service.grant("console", ClipboardAccess::PublishOnly),
+            Err(ClipboardError::EntropyUnavailable)
+        );
+
+        let mut bare = ClipboardService::new();
+        bare.entropy = None;
+        assert_eq!(
+            bare.grant("console", ClipboardAccess::PublishOnly),
+            Err(ClipboardError::EntropyUnavailable)
+        );
+        assert!(bare.grants.is_empty());
+    }
+}
@@ -15,6 +15,7 @@
 //!
 //! - Ctrl+P opens the palette
 //! - Type to filter commands with fuzzy matching
+//! - Filter by regular expression, with the same engine as editor search
 //! - Commands are capability-gated
 //! - Clean failure for unauthorized commands
 //!
@@ -48,6 +49,7 @@ use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use core::fmt;
 use serde::{Deserialize, Serialize};
+use text_pattern::Regex;
 
 /// Unique identifier for a command
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -221,6 +223,15 @@ impl CommandDescriptor {
         false
     }
 
+    /// Checks if the name, description, a tag or the ID matches a pattern
+    pub fn matches_pattern(&self, pattern: &Regex) -> bool {
+        self.enabled
+            && (pattern.is_match(&self.name)
+                || pattern.is_match(&self.description)
+                || self.tags.iter().any(|tag| pattern.is_match(tag))
+                || pattern.is_match(self.id.as_str()))
+    }
+
     /// Calculates a relevance score for the given query (higher is better)
     /// Scoring uses deterministic rules: prefix match > substring match > lexicographic
     pub fn relevance_score(&self, query: &str) -> u32 {
@@ -341,6 +352,25 @@ impl CommandPalette {
         matches.into_iter().map(|(_, _, desc)| desc).collect()
     }
 
+    /// Filters commands by a pattern
+    /// Commands whose name matches come first, each group sorted by name
+    pub fn filter_commands_by_pattern(&self, pattern: &Regex) -> Vec<CommandDescriptor> {
+        let mut matches: Vec<_> = self
+            .commands
+            .iter()
+            .map(|cmd| &cmd.descriptor)
+            .filter(|desc| desc.matches_pattern(pattern))
+            .cloned()
+            .collect();
+        matches.sort_by(|a, b| {
+            let by_name = |desc: &CommandDescriptor| !pattern.is_match(&desc.name);
+            by_name(a)
+                .cmp(&by_name(b))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        matches
+    }
+
     /// Executes a command by ID with the given arguments
     pub fn execute_command(&self, id: &CommandId, args: &[String]) -> CommandResult {
         if let Some(cmd) = self.commands.iter().find(|cmd| cmd.descriptor.id == *id) {
@@ -534,6 +564,22 @@ mod tests {
         let matches = palette.filter_commands("editor");
         assert_eq!(matches.len(), 1);
         assert_eq!(matches[0].id.as_str(), "open_editor");
+
+        // Name matches first, then the save tag
+        let pattern = Regex::new("^open|save$").unwrap().ignore_case(true);
+        let ids: Vec<_> = palette
+            .filter_commands_by_pattern(&pattern)
+            .into_iter()
+            .map(|desc| desc.id)
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                CommandId::new("open_editor"),
+                CommandId::new("open_file"),
+                CommandId::new("save_file")
+            ]
+        );
     }
 
     #[test]
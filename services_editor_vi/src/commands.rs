@@ -2,27 +2,11 @@
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
-use core::fmt;
-
-/// Command parsing error
-#[derive(Debug, PartialEq, Eq)]
-pub enum CommandError {
-    UnknownCommand(String),
-    InvalidSyntax(String),
-    UnsavedChanges,
-}
+use editor_core::ExCommand;
 
-impl fmt::Display for CommandError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            CommandError::UnknownCommand(cmd) => write!(f, "Unknown command: {}", cmd),
-            CommandError::InvalidSyntax(msg) => write!(f, "Invalid syntax: {}", msg),
-            CommandError::UnsavedChanges => {
-                write!(f, "Cannot quit: unsaved changes (use :q! to force)")
-            }
-        }
-    }
-}
+/// Command errors are shared with `editor_core`, which parses and runs the
+/// ex line commands.
+pub use editor_core::CommandError;
 
 /// Editor command
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -39,6 +23,13 @@ pub enum Command {
     ForceQuit,
     /// Write and quit
     WriteQuit,
+    /// Show the document's saved versions
+    History,
+    /// Load saved version `number` (1 = oldest) into the buffer
+    Restore { number: usize, force: bool },
+    /// A line command from `editor_core`: ranges, `:s`, `:g`, `:d`, `:m`,
+    /// `:r`, `:set` and going to a line
+    Ex(ExCommand),
 }
 
 /// Command parser
@@ -54,6 +45,10 @@ impl CommandParser {
             return Err(CommandError::InvalidSyntax("Empty command".to_string()));
         }
 
+        if let Some(command) = ExCommand::parse(trimmed)? {
+            return Ok(Command::Ex(command));
+        }
+
         // Split command and arguments
         let parts: Vec<&str> = trimmed.split_whitespace().collect();
         let command = parts[0];
@@ -88,6 +83,16 @@ impl CommandParser {
             "q" | "quit" => Ok(Command::Quit),
             "q!" | "quit!" => Ok(Command::ForceQuit),
             "wq" | "x" => Ok(Command::WriteQuit),
+            "history" | "hist" => Ok(Command::History),
+            "restore" | "restore!" => {
+                let force = command.ends_with('!');
+                match parts.get(1).and_then(|n| n.parse().ok()) {
+                    Some(number) if parts.len() == 2 => Ok(Command::Restore { number, force }),
+                    _ => Err(CommandError::InvalidSyntax(
+                        "Usage: :restore <version number>".to_string(),
+                    )),
+                }
+            }
             _ => Err(CommandError::UnknownCommand(command.to_string())),
         }
     }
@@ -180,6 +185,48 @@ mod tests {
         assert_eq!(CommandParser::parse("x"), Ok(Command::WriteQuit));
     }
 
+    #[test]
+    fn test_parse_history_and_restore() {
+        assert_eq!(CommandParser::parse("history"), Ok(Command::History));
+        assert_eq!(CommandParser::parse("hist"), Ok(Command::History));
+        assert_eq!(
+            CommandParser::parse("restore 2"),
+            Ok(Command::Restore {
+                number: 2,
+                force: false
+            })
+        );
+        assert_eq!(
+            CommandParser::parse("restore! 1"),
+            Ok(Command::Restore {
+                number: 1,
+                force: true
+            })
+        );
+        assert!(CommandParser::parse("restore").is_err());
+        assert!(CommandParser::parse("restore two").is_err());
+    }
+
+    #[test]
+    fn test_parse_ex_commands() {
+        assert!(matches!(
+            CommandParser::parse("%s/a/b/g"),
+            Ok(Command::Ex(ExCommand::Substitute { global: true, .. }))
+        ));
+        assert!(matches!(
+            CommandParser::parse("r notes.txt"),
+            Ok(Command::Ex(ExCommand::Read { .. }))
+        ));
+        assert_eq!(
+            CommandParser::parse("set bogus"),
+            Err(CommandError::UnknownOption("bogus".to_string()))
+        );
+        assert_eq!(
+            CommandParser::parse("2,3x"),
+            Err(CommandError::UnknownCommand("2,3x".to_string()))
+        );
+    }
+
     #[test]
     fn test_parse_empty_command() {
         assert_eq!(
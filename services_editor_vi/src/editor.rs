@@ -9,7 +9,13 @@ use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use core::fmt;
+use editor_core::{
+    EditorOption, ExCommand, ExEffect, NormalCommand, OptionValue, RegisterContent, SetOption,
+    VisualCommand, CLIPBOARD_REGISTER, VISUAL_BLOCK_KEY,
+};
 use input_types::{InputEvent, KeyCode, KeyEvent};
+use services_clipboard::ClipboardClient;
+use services_settings::{keys, SettingKey, SettingValue, SettingsRegistry};
 use services_storage::{ObjectId, VersionId};
 use services_view_host::{ViewHandleCap, ViewHost};
 use view_types::{CursorPosition, ViewContent, ViewFrame};
@@ -60,6 +66,15 @@ pub enum EditorAction {
     Quit,
     /// Document was saved
     Saved(VersionId),
+    /// `:set` changed these settings; their new values are in
+    /// [`Editor::settings`]
+    SettingsChanged(Vec<String>),
+}
+
+/// The settings `:set` reads and writes, as one user's overrides
+struct SettingsBacking {
+    registry: SettingsRegistry,
+    user: String,
 }
 
 /// The vi-like editor
@@ -67,6 +82,10 @@ pub struct Editor {
     state: EditorState,
     document: Option<DocumentHandle>,
     io: Option<Box<dyn EditorIo>>,
+    /// System clipboard behind the `"+` register (optional)
+    clipboard: Option<ClipboardClient>,
+    /// Settings behind `:set` (optional)
+    settings: Option<SettingsBacking>,
     view: EditorView,
     /// View handles for publishing (optional)
     main_view_handle: Option<ViewHandleCap>,
@@ -83,6 +102,8 @@ impl Editor {
             state: EditorState::new(),
             document: None,
             io: None,
+            clipboard: None,
+            settings: None,
             view: EditorView::default(),
             main_view_handle: None,
             status_view_handle: None,
@@ -97,6 +118,8 @@ impl Editor {
             state: EditorState::new(),
             document: None,
             io: None,
+            clipboard: None,
+            settings: None,
             view: EditorView::new(viewport_lines),
             main_view_handle: None,
             status_view_handle: None,
@@ -116,6 +139,60 @@ impl Editor {
         self.io = Some(io);
     }
 
+    /// Connects the `"+` register to the system clipboard
+    ///
+    /// Without a connection `"+` is kept locally like a named register.
+    pub fn set_clipboard(&mut self, clipboard: ClipboardClient) {
+        self.clipboard = Some(clipboard);
+    }
+
+    /// Backs `:set` with `user`'s settings
+    ///
+    /// The editor options are loaded from the registry, and every option
+    /// `:set` changes is written back as an override for `user`. Without
+    /// settings, `:set` only changes this editor.
+    pub fn set_settings(&mut self, registry: SettingsRegistry, user: impl Into<String>) {
+        let user = user.into();
+        for option in EditorOption::ALL {
+            let key = SettingKey::new(setting_key(option));
+            if let Some(value) = registry
+                .get(&user, &key)
+                .and_then(|v| option_value(option, v))
+            {
+                self.state.options_mut().set(option, value);
+            }
+        }
+        self.settings = Some(SettingsBacking { registry, user });
+        self.state.mark_all_dirty(100);
+    }
+
+    /// Settings behind `:set`, if any
+    pub fn settings(&self) -> Option<&SettingsRegistry> {
+        self.settings.as_ref().map(|settings| &settings.registry)
+    }
+
+    /// Applies a setting changed outside the editor
+    ///
+    /// Keys that are not editor options are ignored.
+    pub fn apply_setting(&mut self, key: &str, value: &SettingValue) {
+        let Some(option) = EditorOption::ALL
+            .into_iter()
+            .find(|option| setting_key(*option) == key)
+        else {
+            return;
+        };
+        let Some(option_value) = option_value(option, value) else {
+            return;
+        };
+        self.state.options_mut().set(option, option_value);
+        if let Some(settings) = &mut self.settings {
+            settings
+                .registry
+                .set_user_override(settings.user.as_str(), key, value.clone());
+        }
+        self.state.mark_all_dirty(100);
+    }
+
     /// Get current editor state
     pub fn state(&self) -> &EditorState {
         &self.state
@@ -196,6 +273,9 @@ impl Editor {
             EditorMode::Insert => self.handle_insert_mode(key_event),
             EditorMode::Command => self.handle_command_mode(key_event),
             EditorMode::Search => self.handle_search_mode(key_event),
+            EditorMode::Visual | EditorMode::VisualLine | EditorMode::VisualBlock => {
+                self.handle_visual_mode(key_event)
+            }
         }
     }
 
@@ -208,47 +288,42 @@ impl Editor {
     }
 
     /// Handle normal mode key event
+    ///
+    /// Keys go through the shared vi grammar in `editor_core`, so counts,
+    /// motions and operators behave as in every other editor built on it.
     fn handle_normal_mode(&mut self, event: &KeyEvent) -> EditorResult<EditorAction> {
-        match event.code {
-            // Navigation (only without modifiers)
-            KeyCode::H | KeyCode::Left if event.modifiers.is_empty() => {
-                self.state.move_cursor_left();
-                Ok(EditorAction::Continue)
-            }
-            KeyCode::J | KeyCode::Down if event.modifiers.is_empty() => {
-                self.state.move_cursor_down();
-                Ok(EditorAction::Continue)
-            }
-            KeyCode::K | KeyCode::Up if event.modifiers.is_empty() => {
-                self.state.move_cursor_up();
-                Ok(EditorAction::Continue)
-            }
-            KeyCode::L | KeyCode::Right if event.modifiers.is_empty() => {
-                self.state.move_cursor_right();
-                Ok(EditorAction::Continue)
+        let ch = match event.code {
+            // Redo (Ctrl+R)
+            KeyCode::R if event.modifiers.is_ctrl() => {
+                self.state.normal_mut().reset_pending();
+                if self.state.redo() {
+                    self.state.set_status_message("Redo");
+                    // Mark all lines dirty on redo
+                    self.state.mark_all_dirty(100);
+                } else {
+                    self.state.set_status_message("Already at newest change");
+                }
+                return Ok(EditorAction::Continue);
             }
 
-            // Enter insert mode (only without modifiers)
-            KeyCode::I if event.modifiers.is_empty() => {
-                self.state.save_undo_snapshot();
-                self.state.set_mode(EditorMode::Insert);
-                self.state.set_status_message("");
-                Ok(EditorAction::Continue)
+            // Drop a half-typed command
+            KeyCode::Escape => {
+                self.state.normal_mut().reset_pending();
+                return Ok(EditorAction::Continue);
             }
 
-            // Delete character under cursor (only without modifiers)
-            KeyCode::X if event.modifiers.is_empty() => {
-                self.state.save_undo_snapshot();
-                let pos = self.state.cursor().position();
-                if self.state.buffer_mut().delete_char(pos) {
-                    self.state.mark_dirty();
-                    self.state.mark_line_dirty(pos.row);
-                }
-                Ok(EditorAction::Continue)
-            }
+            _ => match self.vi_key_to_char(event) {
+                Some(ch) => ch,
+                None => return Ok(EditorAction::Continue),
+            },
+        };
+
+        let Some(command) = self.state.normal_mut().push_key(ch) else {
+            return Ok(EditorAction::Continue);
+        };
 
-            // Undo (only without modifiers)
-            KeyCode::U if event.modifiers.is_empty() => {
+        match command {
+            NormalCommand::Undo => {
                 if self.state.undo() {
                     self.state.set_status_message("Undo");
                     // Mark all lines dirty on undo
@@ -256,48 +331,87 @@ impl Editor {
                 } else {
                     self.state.set_status_message("Already at oldest change");
                 }
-                Ok(EditorAction::Continue)
-            }
-
-            // Redo (Ctrl+R)
-            KeyCode::R if event.modifiers.is_ctrl() => {
-                if self.state.redo() {
-                    self.state.set_status_message("Redo");
-                    // Mark all lines dirty on redo
-                    self.state.mark_all_dirty(100);
-                } else {
-                    self.state.set_status_message("Already at newest change");
-                }
-                Ok(EditorAction::Continue)
             }
 
             // Enter command mode
-            KeyCode::Semicolon if event.modifiers.is_shift() => {
-                // Shift+; = ':'
+            NormalCommand::EnterCommand => {
                 self.state.set_mode(EditorMode::Command);
                 self.state.set_status_message("");
-                Ok(EditorAction::Continue)
             }
 
             // Enter search mode
-            KeyCode::Slash if event.modifiers.is_empty() => {
+            NormalCommand::EnterSearch(direction) => {
                 self.state.set_mode(EditorMode::Search);
+                self.state.search_mut().start(direction);
                 self.state.set_status_message("");
-                Ok(EditorAction::Continue)
             }
 
-            // Repeat last search
-            KeyCode::N if event.modifiers.is_empty() => {
-                if self.state.find_next(true) {
-                    self.state.set_status_message("Next match");
-                } else {
-                    self.state.set_status_message("Pattern not found");
+            // Repeat last search, or search for the word under the cursor
+            NormalCommand::SearchNext | NormalCommand::SearchPrevious => {
+                let reverse = command == NormalCommand::SearchPrevious;
+                let found = self.state.repeat_search(reverse);
+                self.report_search(found, "Next match");
+            }
+            NormalCommand::SearchWord(direction) => {
+                let found = self.state.search_word(direction);
+                self.report_search(found, "Match found");
+            }
+
+            // Start a selection
+            NormalCommand::Visual(kind) => {
+                self.state.start_visual(kind);
+                self.state.set_status_message("");
+            }
+
+            // Motions, operators, puts and inserts
+            command => {
+                let clipboard = command.register() == Some(CLIPBOARD_REGISTER);
+                if clipboard && !command.writes_register() {
+                    self.fetch_clipboard();
+                }
+                let effect = self.state.apply_normal(command);
+                if clipboard && command.writes_register() {
+                    self.publish_clipboard();
+                }
+                if effect.insert {
+                    self.state.set_mode(EditorMode::Insert);
+                    self.state.set_status_message("");
                 }
-                Ok(EditorAction::Continue)
             }
+        }
+        Ok(EditorAction::Continue)
+    }
+
+    /// Handle visual mode key event
+    ///
+    /// Motions extend the selection from its anchor; an operator acts on the
+    /// selection and returns to Normal mode, or Insert mode for `c`.
+    fn handle_visual_mode(&mut self, event: &KeyEvent) -> EditorResult<EditorAction> {
+        if event.code == KeyCode::Escape {
+            self.state.normal_mut().reset_pending();
+            self.state.set_mode(EditorMode::Normal);
+            return Ok(EditorAction::Continue);
+        }
+        let Some(ch) = self.vi_key_to_char(event) else {
+            return Ok(EditorAction::Continue);
+        };
+        let Some(command) = self.state.normal_mut().push_visual_key(ch) else {
+            return Ok(EditorAction::Continue);
+        };
 
-            _ => Ok(EditorAction::Continue),
+        let effect = self.state.apply_visual(command);
+        if let VisualCommand::Operate {
+            register: Some(CLIPBOARD_REGISTER),
+            ..
+        } = command
+        {
+            self.publish_clipboard();
+        }
+        if effect.insert {
+            self.state.set_mode(EditorMode::Insert);
+            self.state.set_status_message("");
         }
+        Ok(EditorAction::Continue)
     }
 
     /// Handle insert mode key event
@@ -305,6 +419,7 @@ impl Editor {
         match event.code {
             // Exit insert mode
             KeyCode::Escape => {
+                self.state.normal_mut().finish_insert();
                 self.state.set_mode(EditorMode::Normal);
                 Ok(EditorAction::Continue)
             }
@@ -314,6 +429,7 @@ impl Editor {
                 let pos = self.state.cursor().position();
                 if self.state.buffer_mut().insert_newline(pos) {
                     self.state.mark_dirty();
+                    self.state.normal_mut().record_insert('\n');
                     // Mark current line and all following lines dirty (due to line shift)
                     let viewport_end = pos.row + 20; // Mark next 20 lines
                     self.state.mark_lines_dirty(pos.row, viewport_end);
@@ -328,6 +444,7 @@ impl Editor {
                 let pos = self.state.cursor().position();
                 if let Some(new_pos) = self.state.buffer_mut().backspace(pos) {
                     self.state.mark_dirty();
+                    self.state.normal_mut().record_backspace();
                     // If we joined lines, mark current and following lines dirty
                     if new_pos.row < pos.row {
                         let viewport_end = new_pos.row + 20;
@@ -347,6 +464,7 @@ impl Editor {
                     let pos = self.state.cursor().position();
                     if self.state.buffer_mut().insert_char(pos, ch) {
                         self.state.mark_dirty();
+                        self.state.normal_mut().record_insert(ch);
                         // Mark current line dirty (text shifted to the right)
                         self.state.mark_line_dirty(pos.row);
                         let new_pos = Position::new(pos.row, pos.col + 1);
@@ -405,13 +523,9 @@ impl Editor {
             // Execute search
             KeyCode::Enter => {
                 // Execute search before changing mode (mode change clears search_query)
-                let found = self.state.find_next(true);
+                let found = self.state.submit_search();
                 self.state.set_mode(EditorMode::Normal);
-                if found {
-                    self.state.set_status_message("Match found");
-                } else {
-                    self.state.set_status_message("Pattern not found");
-                }
+                self.report_search(found, "Match found");
                 Ok(EditorAction::Continue)
             }
 
@@ -421,6 +535,16 @@ impl Editor {
                 Ok(EditorAction::Continue)
             }
 
+            // Step through earlier searches
+            KeyCode::Up => {
+                self.state.search_mut().history_older();
+                Ok(EditorAction::Continue)
+            }
+            KeyCode::Down => {
+                self.state.search_mut().history_newer();
+                Ok(EditorAction::Continue)
+            }
+
             // Build search query
             _ => {
                 if let Some(ch) = self.key_to_char(event) {
@@ -432,8 +556,10 @@ impl Editor {
     }
 
     /// Execute a parsed command
+    ///
+    /// Command errors are shown in the status line as well as returned.
     fn execute_command(&mut self, cmd_str: &str) -> EditorResult<EditorAction> {
-        let command = CommandParser::parse(cmd_str)?;
+        let command = CommandParser::parse(cmd_str).map_err(|e| self.command_error(e))?;
 
         match command {
             Command::Write => {
@@ -474,9 +600,146 @@ impl Editor {
                 let _ = self.save_document()?;
                 Ok(EditorAction::Quit)
             }
+
+            Command::History => {
+                self.show_history()?;
+                Ok(EditorAction::Continue)
+            }
+
+            Command::Restore { number, force } => {
+                if self.state.is_dirty() && !force {
+                    self.state.set_status_message(
+                        "No write since last change (use :w or :restore! to discard)",
+                    );
+                } else {
+                    self.restore_version(number)?;
+                }
+                Ok(EditorAction::Continue)
+            }
+
+            Command::Ex(command) => self.execute_ex(command),
+        }
+    }
+
+    /// Shows where a search ended up in the status line
+    fn report_search(&mut self, found: Result<(), CommandError>, message: &str) {
+        match found {
+            Ok(()) => self.state.set_status_message(message),
+            Err(error) => self.state.set_status_message(error.to_string()),
+        }
+    }
+
+    /// Runs an ex line command
+    fn execute_ex(&mut self, command: ExCommand) -> EditorResult<EditorAction> {
+        let result = match command {
+            ExCommand::Read { line, path } => {
+                let io = self.io.as_mut().ok_or_else(|| {
+                    EditorError::NotSupported("No I/O handler configured".to_string())
+                })?;
+                let content = io.open(OpenOptions::new().with_path(path))?.content;
+                self.state.read_into(line, &content)
+            }
+            ExCommand::Set(args) => return Ok(self.set_options(&args)),
+            ExCommand::NoHighlight => {
+                self.state.search_mut().clear_highlight();
+                self.state.set_status_message("");
+                return Ok(EditorAction::Continue);
+            }
+            command => self.state.apply_ex(&command),
+        };
+        let ExEffect { message, .. } = result.map_err(|e| self.command_error(e))?;
+        self.state.set_status_message(message.unwrap_or_default());
+        Ok(EditorAction::Continue)
+    }
+
+    /// Applies `:set`, writing changed options to the settings if any
+    fn set_options(&mut self, args: &[SetOption]) -> EditorAction {
+        let shown = self.state.options_mut().apply(args);
+        self.state.set_status_message(shown.unwrap_or_default());
+        self.state.mark_all_dirty(100);
+
+        let Some(settings) = &mut self.settings else {
+            return EditorAction::Continue;
+        };
+        let mut changed: Vec<String> = Vec::new();
+        for arg in args {
+            let Some(value) = arg.value else {
+                continue;
+            };
+            let key = setting_key(arg.option);
+            settings
+                .registry
+                .set_user_override(settings.user.as_str(), key, setting_value(value));
+            if !changed.iter().any(|changed| changed == key) {
+                changed.push(key.to_string());
+            }
+        }
+        if changed.is_empty() {
+            EditorAction::Continue
+        } else {
+            EditorAction::SettingsChanged(changed)
         }
     }
 
+    /// Shows a command error in the status line
+    fn command_error(&mut self, error: CommandError) -> EditorError {
+        self.state.set_status_message(error.to_string());
+        EditorError::Command(error)
+    }
+
+    /// Lists the document's saved versions in the status line
+    fn show_history(&mut self) -> EditorResult<()> {
+        let (io, handle) = self.io_and_document()?;
+        let versions = io.history(&handle)?;
+        let listing: Vec<String> = versions
+            .iter()
+            .enumerate()
+            .map(|(index, info)| {
+                let current = if info.version_id == handle.version_id {
+                    "*"
+                } else {
+                    ""
+                };
+                format!("{}{} ({}B)", index + 1, current, info.size_bytes)
+            })
+            .collect();
+        self.state
+            .set_status_message(format!("History: {} — :restore <n>", listing.join(" ")));
+        Ok(())
+    }
+
+    /// Loads a saved version into the buffer; `:w` commits it as a new version
+    fn restore_version(&mut self, number: usize) -> EditorResult<()> {
+        let (io, handle) = self.io_and_document()?;
+        let versions = io.history(&handle)?;
+        let Some(info) = number.checked_sub(1).and_then(|index| versions.get(index)) else {
+            self.state
+                .set_status_message(format!("No version {} (see :history)", number));
+            return Ok(());
+        };
+        let content = io.open_version(&handle, info.version_id)?;
+
+        self.state.save_undo_snapshot();
+        self.state.load_content(content);
+        self.state.mark_dirty();
+        self.state.mark_all_dirty(100);
+        self.state
+            .set_status_message(format!("Restored version {} — :w to keep it", number));
+        Ok(())
+    }
+
+    fn io_and_document(&mut self) -> EditorResult<(&mut Box<dyn EditorIo>, DocumentHandle)> {
+        let handle = self
+            .document
+            .clone()
+            .ok_or_else(|| EditorError::InvalidState("No document open".to_string()))?;
+        let io = self
+            .io
+            .as_mut()
+            .ok_or_else(|| EditorError::NotSupported("No I/O handler configured".to_string()))?;
+        Ok((io, handle))
+    }
+
     fn save_document(&mut self) -> EditorResult<VersionId> {
         if let (Some(io), Some(handle)) = (self.io.as_mut(), self.document.clone()) {
             let content = self.state.buffer().as_string();
@@ -535,6 +798,53 @@ impl Editor {
         Ok(result.new_version_id)
     }
 
+    /// Loads the newest clip into `"+`
+    fn fetch_clipboard(&mut self) {
+        let Some(clipboard) = self.clipboard.as_mut().filter(|client| client.can_read()) else {
+            return;
+        };
+        match clipboard.latest() {
+            Ok(Some(entry)) => self.state.registers_mut().set(
+                CLIPBOARD_REGISTER,
+                RegisterContent {
+                    text: entry.text,
+                    linewise: entry.linewise,
+                },
+            ),
+            Ok(None) => {}
+            Err(e) => self.state.set_status_message(e.to_string()),
+        }
+    }
+
+    /// Publishes `"+` after a yank or delete into it
+    fn publish_clipboard(&mut self) {
+        let Some(clipboard) = self.clipboard.as_mut() else {
+            return;
+        };
+        let Some(content) = self.state.registers().get(Some(CLIPBOARD_REGISTER)) else {
+            return;
+        };
+        if let Err(e) = clipboard.publish(&content.text, content.linewise) {
+            self.state.set_status_message(e.to_string());
+        }
+    }
+
+    /// Key as typed to the vi grammar in Normal and Visual mode
+    ///
+    /// Arrow keys stand for `hjkl` and Ctrl+V for the block-selection key;
+    /// other chords type nothing.
+    fn vi_key_to_char(&self, event: &KeyEvent) -> Option<char> {
+        match event.code {
+            KeyCode::V if event.modifiers.is_ctrl() => Some(VISUAL_BLOCK_KEY),
+            KeyCode::Left if event.modifiers.is_empty() => Some('h'),
+            KeyCode::Down if event.modifiers.is_empty() => Some('j'),
+            KeyCode::Up if event.modifiers.is_empty() => Some('k'),
+            KeyCode::Right if event.modifiers.is_empty() => Some('l'),
+            _ if event.modifiers.is_ctrl() || event.modifiers.is_alt() => None,
+            _ => self.key_to_char(event),
+        }
+    }
+
     /// Convert key event to character (simple mapping)
     fn key_to_char(&self, event: &KeyEvent) -> Option<char> {
         let shift = event.modifiers.is_shift();
@@ -636,7 +946,8 @@ impl Editor {
                 content,
                 timestamp_ns,
             )
-            .with_cursor(cursor);
+            .with_cursor(cursor)
+            .with_highlights(self.view.highlights(&self.state));
 
             view_host
                 .publish_frame(handle, frame)
@@ -687,11 +998,43 @@ impl Default for Editor {
     }
 }
 
+/// Setting that stores an editor option
+fn setting_key(option: EditorOption) -> &'static str {
+    match option {
+        EditorOption::Number => keys::EDITOR_LINE_NUMBERS,
+        EditorOption::Wrap => keys::EDITOR_WORD_WRAP,
+        EditorOption::TabStop => keys::EDITOR_TAB_SIZE,
+    }
+}
+
+fn setting_value(value: OptionValue) -> SettingValue {
+    match value {
+        OptionValue::Bool(on) => SettingValue::Boolean(on),
+        OptionValue::Number(number) => SettingValue::Integer(number as i64),
+    }
+}
+
+/// Option value a setting holds, if it is one the option can take
+fn option_value(option: EditorOption, value: &SettingValue) -> Option<OptionValue> {
+    if option.is_boolean() {
+        value.as_boolean().map(OptionValue::Bool)
+    } else {
+        match value.as_integer() {
+            Some(number) if number > 0 => Some(OptionValue::Number(number as usize)),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
     use input_types::Modifiers;
+    use services_clipboard::{ClipboardAccess, ClipboardService};
     use services_storage::ObjectId;
+    use view_types::HighlightKind;
 
     fn press_key(code: KeyCode) -> InputEvent {
         InputEvent::key(KeyEvent::pressed(code, Modifiers::none()))
@@ -832,6 +1175,290 @@ mod tests {
         assert!(editor.state().is_dirty());
     }
 
+    #[test]
+    fn test_vi_counts_motions_and_operators() {
+        let mut editor = Editor::new();
+        editor.load_document(
+            "one two three\nfour\nfive".to_string(),
+            DocumentHandle::new(ObjectId::new(), VersionId::new(), None, false),
+        );
+
+        // 2dw
+        for code in [KeyCode::Num2, KeyCode::D, KeyCode::W] {
+            editor.process_input(press_key(code)).unwrap();
+        }
+        assert_eq!(editor.get_content(), "three\nfour\nfive");
+
+        // yy, G, P puts the line above the last one
+        for code in [KeyCode::Y, KeyCode::Y] {
+            editor.process_input(press_key(code)).unwrap();
+        }
+        editor.process_input(press_key_shift(KeyCode::G)).unwrap();
+        editor.process_input(press_key_shift(KeyCode::P)).unwrap();
+        assert_eq!(editor.get_content(), "three\nfour\nthree\nfive");
+        assert_eq!(editor.state().cursor().position(), Position::new(2, 0));
+
+        // c$ replaces to the end of the line as one undoable change
+        editor.process_input(press_key(KeyCode::C)).unwrap();
+        editor
+            .process_input(press_key_shift(KeyCode::Num4))
+            .unwrap();
+        assert_eq!(editor.state().mode(), EditorMode::Insert);
+        editor.process_input(press_key(KeyCode::X)).unwrap();
+        editor.process_input(press_key(KeyCode::Escape)).unwrap();
+        assert_eq!(editor.get_content(), "three\nfour\nx\nfive");
+
+        // . repeats it on the first line
+        for code in [KeyCode::G, KeyCode::G, KeyCode::Period] {
+            editor.process_input(press_key(code)).unwrap();
+        }
+        assert_eq!(editor.get_content(), "x\nfour\nx\nfive");
+
+        editor.process_input(press_key(KeyCode::U)).unwrap();
+        assert_eq!(editor.get_content(), "three\nfour\nx\nfive");
+
+        // o opens a line below; Escape drops a half-typed operator
+        editor.process_input(press_key(KeyCode::O)).unwrap();
+        editor.process_input(press_key(KeyCode::Escape)).unwrap();
+        editor.process_input(press_key(KeyCode::D)).unwrap();
+        editor.process_input(press_key(KeyCode::Escape)).unwrap();
+        editor.process_input(press_key(KeyCode::D)).unwrap();
+        assert_eq!(editor.get_content(), "three\n\nfour\nx\nfive");
+        assert!(editor.state().normal().is_pending());
+    }
+
+    #[test]
+    fn test_visual_mode_selections() {
+        let mut editor = Editor::new();
+        editor.load_document(
+            "alpha beta\ngamma\ndelta".to_string(),
+            DocumentHandle::new(ObjectId::new(), VersionId::new(), None, false),
+        );
+
+        // vjd deletes from the cursor to the same column on the next line
+        editor.process_input(press_key(KeyCode::L)).unwrap();
+        editor.process_input(press_key(KeyCode::V)).unwrap();
+        assert_eq!(editor.state().mode(), EditorMode::Visual);
+        editor.process_input(press_key(KeyCode::J)).unwrap();
+        let highlights = editor.view.highlights(editor.state());
+        assert_eq!(highlights.len(), 2);
+        assert_eq!(
+            (highlights[1].start_column, highlights[1].end_column),
+            (0, 2)
+        );
+        editor.process_input(press_key(KeyCode::D)).unwrap();
+        assert_eq!(editor.state().mode(), EditorMode::Normal);
+        assert_eq!(editor.get_content(), "amma\ndelta");
+        assert!(editor.view.highlights(editor.state()).is_empty());
+
+        // Ctrl+V block, then c changes it on the first line
+        editor
+            .process_input(InputEvent::key(KeyEvent::pressed(
+                KeyCode::V,
+                Modifiers::CTRL,
+            )))
+            .unwrap();
+        assert_eq!(editor.state().mode(), EditorMode::VisualBlock);
+        editor.process_input(press_key(KeyCode::J)).unwrap();
+        editor.process_input(press_key(KeyCode::C)).unwrap();
+        assert_eq!(editor.state().mode(), EditorMode::Insert);
+        editor.process_input(press_key(KeyCode::X)).unwrap();
+        editor.process_input(press_key(KeyCode::Escape)).unwrap();
+        assert_eq!(editor.get_content(), "axma\ndlta");
+
+        // V then Escape leaves the buffer alone; u undoes the block change
+        editor.process_input(press_key_shift(KeyCode::V)).unwrap();
+        assert_eq!(editor.state().mode(), EditorMode::VisualLine);
+        editor.process_input(press_key(KeyCode::Escape)).unwrap();
+        assert_eq!(editor.state().mode(), EditorMode::Normal);
+        editor.process_input(press_key(KeyCode::U)).unwrap();
+        assert_eq!(editor.get_content(), "amma\ndelta");
+    }
+
+    #[test]
+    fn test_search_keys_and_highlights() {
+        let mut editor = Editor::new();
+        editor.load_document(
+            "let x = 1;\nlet xs = x + 2;\nx".to_string(),
+            DocumentHandle::new(ObjectId::new(), VersionId::new(), None, false),
+        );
+        let match_count = |editor: &Editor| {
+            editor
+                .view
+                .highlights(editor.state())
+                .iter()
+                .filter(|h| h.kind == HighlightKind::SearchMatch)
+                .count()
+        };
+
+        // ? searches backwards, highlighting matches as the pattern is typed
+        editor
+            .process_input(press_key_shift(KeyCode::Slash))
+            .unwrap();
+        for ch in "x\\b".chars() {
+            editor.state_mut().append_to_search(ch);
+        }
+        assert!(editor.view.render_status(editor.state()).contains("?x\\b"));
+        assert_eq!(match_count(&editor), 3);
+        editor.process_input(press_key(KeyCode::Enter)).unwrap();
+        assert_eq!(editor.state().cursor().position(), Position::new(2, 0));
+        assert_eq!(match_count(&editor), 3);
+
+        editor.process_input(press_key(KeyCode::N)).unwrap();
+        assert_eq!(editor.state().cursor().position(), Position::new(1, 9));
+        editor.process_input(press_key_shift(KeyCode::N)).unwrap();
+        assert_eq!(editor.state().cursor().position(), Position::new(2, 0));
+
+        // * matches the whole word only, so "xs" is skipped
+        editor
+            .process_input(press_key_shift(KeyCode::Num8))
+            .unwrap();
+        assert_eq!(editor.state().cursor().position(), Position::new(0, 4));
+        assert_eq!(editor.state().search().history().last(), Some("\\bx\\b"));
+        assert_eq!(match_count(&editor), 3);
+
+        // Up in the prompt recalls earlier patterns
+        editor.process_input(press_key(KeyCode::Slash)).unwrap();
+        editor.process_input(press_key(KeyCode::Up)).unwrap();
+        editor.process_input(press_key(KeyCode::Up)).unwrap();
+        assert_eq!(editor.state().search_query(), "x\\b");
+        editor.process_input(press_key(KeyCode::Escape)).unwrap();
+
+        editor
+            .process_input(press_key_shift(KeyCode::Semicolon))
+            .unwrap();
+        for ch in "noh".chars() {
+            editor.state_mut().append_to_command(ch);
+        }
+        editor.process_input(press_key(KeyCode::Enter)).unwrap();
+        assert_eq!(match_count(&editor), 0);
+
+        editor.process_input(press_key(KeyCode::Slash)).unwrap();
+        editor.state_mut().append_to_search('q');
+        editor.process_input(press_key(KeyCode::Enter)).unwrap();
+        assert_eq!(editor.state().status_message(), "Pattern not found: q");
+    }
+
+    #[test]
+    fn test_ex_commands_and_set() {
+        fn run(editor: &mut Editor, line: &str) -> EditorResult<EditorAction> {
+            editor.process_input(press_key_shift(KeyCode::Semicolon))?;
+            for ch in line.chars() {
+                editor.state_mut().append_to_command(ch);
+            }
+            editor.process_input(press_key(KeyCode::Enter))
+        }
+
+        let mut editor = Editor::new();
+        editor.load_document(
+            "a1\nb1\na2".to_string(),
+            DocumentHandle::new(ObjectId::new(), VersionId::new(), None, false),
+        );
+        editor.set_settings(services_settings::create_default_registry(), "alice");
+        assert!(editor.state().options().number);
+        assert_eq!(editor.state().options().tabstop, 4);
+
+        assert_eq!(
+            run(&mut editor, "g/a/s/a/x/").unwrap(),
+            EditorAction::Continue
+        );
+        assert_eq!(editor.get_content(), "x1\nb1\nx2");
+        assert!(editor.state().is_dirty());
+
+        // : from a selection runs over its lines
+        editor.process_input(press_key_shift(KeyCode::V)).unwrap();
+        editor.process_input(press_key(KeyCode::K)).unwrap();
+        editor
+            .process_input(press_key_shift(KeyCode::Semicolon))
+            .unwrap();
+        assert_eq!(editor.state().command_buffer(), "'<,'>");
+        editor.state_mut().append_to_command('d');
+        editor.process_input(press_key(KeyCode::Enter)).unwrap();
+        assert_eq!(editor.get_content(), "x1");
+        editor.process_input(press_key(KeyCode::U)).unwrap();
+        assert_eq!(editor.get_content(), "x1\nb1\nx2");
+
+        assert!(matches!(
+            run(&mut editor, "9"),
+            Err(EditorError::Command(CommandError::LineOutOfRange(9)))
+        ));
+        assert_eq!(editor.state().status_message(), "Line out of range: 9");
+
+        assert_eq!(
+            run(&mut editor, "set nonu ts=2").unwrap(),
+            EditorAction::SettingsChanged(alloc::vec![
+                keys::EDITOR_LINE_NUMBERS.to_string(),
+                keys::EDITOR_TAB_SIZE.to_string()
+            ])
+        );
+        let settings = editor.settings().unwrap();
+        assert_eq!(
+            settings.get("alice", &SettingKey::new(keys::EDITOR_TAB_SIZE)),
+            Some(&SettingValue::Integer(2))
+        );
+        assert!(!editor.state().options().number);
+        assert_eq!(run(&mut editor, "set ts?").unwrap(), EditorAction::Continue);
+        assert_eq!(editor.state().status_message(), "tabstop=2");
+    }
+
+    #[test]
+    fn test_named_and_clipboard_registers() {
+        let service = Rc::new(RefCell::new(ClipboardService::new()));
+        let grant = service
+            .borrow_mut()
+            .grant("editor", ClipboardAccess::ReadWrite)
+            .unwrap();
+        let mut editor = Editor::new();
+        editor.set_clipboard(ClipboardClient::new(grant, service.clone()));
+        editor.load_document(
+            "one\ntwo".to_string(),
+            DocumentHandle::new(ObjectId::new(), VersionId::new(), None, false),
+        );
+        let register = |name: InputEvent| [press_key_shift(KeyCode::Quote), name];
+
+        // "ayy, then yy into the unnamed register, then "ap
+        for event in register(press_key(KeyCode::A)) {
+            editor.process_input(event).unwrap();
+        }
+        for code in [KeyCode::Y, KeyCode::Y, KeyCode::J, KeyCode::Y, KeyCode::Y] {
+            editor.process_input(press_key(code)).unwrap();
+        }
+        for event in register(press_key(KeyCode::A)) {
+            editor.process_input(event).unwrap();
+        }
+        editor.process_input(press_key(KeyCode::P)).unwrap();
+        assert_eq!(editor.get_content(), "one\ntwo\none");
+        assert_eq!(editor.state().registers().get(None).unwrap().text, "two");
+
+        // "+yy publishes the line
+        for event in register(press_key_shift(KeyCode::Equal)) {
+            editor.process_input(event).unwrap();
+        }
+        assert!(editor.state().normal().is_pending());
+        editor.process_input(press_key(KeyCode::Y)).unwrap();
+        editor.process_input(press_key(KeyCode::Y)).unwrap();
+        let grant = service
+            .borrow_mut()
+            .grant("reader", ClipboardAccess::ReadWrite)
+            .unwrap();
+        let latest = service.borrow().latest(grant.id).unwrap().cloned().unwrap();
+        assert_eq!((latest.text.as_str(), latest.linewise), ("one", true));
+        assert_eq!(latest.source, "editor");
+
+        // "+P puts whatever was copied elsewhere since
+        service
+            .borrow_mut()
+            .publish(grant.id, "pasted", false)
+            .unwrap();
+        editor.process_input(press_key(KeyCode::G)).unwrap();
+        editor.process_input(press_key(KeyCode::G)).unwrap();
+        for event in register(press_key_shift(KeyCode::Equal)) {
+            editor.process_input(event).unwrap();
+        }
+        editor.process_input(press_key_shift(KeyCode::P)).unwrap();
+        assert_eq!(editor.get_content(), "pastedone\ntwo\none");
+    }
+
     #[test]
     fn test_enter_command_mode() {
         let mut editor = Editor::new();
@@ -2,11 +2,13 @@
 
 use alloc::format;
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use core::fmt;
 use fs_view::DirectoryView;
 use services_fs_view::{FileSystemOperations, FileSystemViewService};
 use services_storage::{
-    JournaledStorage, ObjectId, TransactionError, TransactionalStorage, VersionId,
+    CheckedStorage, JournaledStorage, ObjectId, PrincipalId, TransactionError, VersionId,
+    VersionInfo,
 };
 
 /// Document I/O error
@@ -123,21 +125,40 @@ pub trait EditorIo {
     fn save(&mut self, handle: &DocumentHandle, content: &str) -> Result<SaveResult, IoError>;
     /// Save to a new path (Save As)
     fn save_as(&mut self, path: &str, content: &str) -> Result<SaveResult, IoError>;
+    /// Saved versions of a document, oldest first
+    fn history(&mut self, _handle: &DocumentHandle) -> Result<Vec<VersionInfo>, IoError> {
+        Err(IoError::StorageError("History not supported".to_string()))
+    }
+    /// Read a document as it was at a saved version
+    fn open_version(
+        &mut self,
+        _handle: &DocumentHandle,
+        _version_id: VersionId,
+    ) -> Result<String, IoError> {
+        Err(IoError::StorageError("History not supported".to_string()))
+    }
 }
 
 /// Storage-backed editor I/O using JournaledStorage and optional fs_view.
+///
+/// Every document access is made on behalf of the editor's principal and
+/// checked by [`CheckedStorage`]. With an fs_view, the checked storage starts
+/// from the fs_view's permissions, so paths and contents agree on who may
+/// read and write a document.
 pub struct StorageEditorIo {
-    storage: JournaledStorage,
+    storage: CheckedStorage<JournaledStorage>,
     fs_view: Option<FileSystemViewService>,
     root: Option<DirectoryView>,
+    principal: PrincipalId,
 }
 
 impl StorageEditorIo {
-    pub fn new(storage: JournaledStorage) -> Self {
+    pub fn new(storage: CheckedStorage<JournaledStorage>, principal: PrincipalId) -> Self {
         Self {
             storage,
             fs_view: None,
             root: None,
+            principal,
         }
     }
 
@@ -145,21 +166,41 @@ impl StorageEditorIo {
         storage: JournaledStorage,
         fs_view: FileSystemViewService,
         root: DirectoryView,
+        principal: PrincipalId,
     ) -> Self {
+        let mut storage = CheckedStorage::with_permissions(storage, fs_view.permissions().clone());
+        storage.set_timestamp(fs_view.timestamp());
         Self {
             storage,
             fs_view: Some(fs_view),
             root: Some(root),
+            principal,
         }
     }
 
+    /// Returns the principal document accesses are made for
+    pub fn principal(&self) -> PrincipalId {
+        self.principal
+    }
+
     pub fn storage(&self) -> &JournaledStorage {
-        &self.storage
+        self.storage.inner()
     }
 
     fn map_tx_error(err: TransactionError) -> IoError {
         match err {
             TransactionError::ObjectNotFound(_) => IoError::NotFound,
+            TransactionError::AccessDenied(reason) => IoError::PermissionDenied(reason.to_string()),
+            other => IoError::StorageError(other.to_string()),
+        }
+    }
+
+    fn map_fs_error(err: services_fs_view::OperationError) -> IoError {
+        match err {
+            services_fs_view::OperationError::NotFound(_) => IoError::NotFound,
+            services_fs_view::OperationError::AccessDenied(reason) => {
+                IoError::PermissionDenied(reason.to_string())
+            }
             other => IoError::StorageError(other.to_string()),
         }
     }
@@ -172,22 +213,15 @@ impl EditorIo for StorageEditorIo {
         } else if let Some(path) = options.path.clone() {
             let fs = self
                 .fs_view
-                .as_ref()
+                .as_mut()
                 .ok_or_else(|| IoError::PermissionDenied("No fs_view available".to_string()))?;
             let root = self
                 .root
                 .as_ref()
                 .ok_or_else(|| IoError::PermissionDenied("No root directory".to_string()))?;
-            match fs.open(root, &path) {
-                Ok(id) => id,
-                Err(services_fs_view::OperationError::NotFound(_)) => {
-                    return Err(IoError::NotFound)
-                }
-                Err(services_fs_view::OperationError::AccessDenied(reason)) => {
-                    return Err(IoError::PermissionDenied(reason))
-                }
-                Err(err) => return Err(IoError::StorageError(err.to_string())),
-            }
+            fs.as_principal(self.principal)
+                .open(root, &path)
+                .map_err(Self::map_fs_error)?
         } else {
             return Err(IoError::NotFound);
         };
@@ -199,11 +233,11 @@ impl EditorIo for StorageEditorIo {
 
         let version_id = self
             .storage
-            .read(&tx, object_id)
+            .read(self.principal, &tx, object_id)
             .map_err(Self::map_tx_error)?;
         let data = self
             .storage
-            .read_data(&tx, object_id)
+            .read_data(self.principal, &tx, object_id)
             .map_err(Self::map_tx_error)?;
         let _ = self.storage.rollback(&mut tx);
 
@@ -226,7 +260,12 @@ impl EditorIo for StorageEditorIo {
 
         let new_version_id = self
             .storage
-            .write(&mut tx, handle.object_id, content.as_bytes())
+            .write(
+                self.principal,
+                &mut tx,
+                handle.object_id,
+                content.as_bytes(),
+            )
             .map_err(Self::map_tx_error)?;
         self.storage.commit(&mut tx).map_err(Self::map_tx_error)?;
 
@@ -254,21 +293,27 @@ impl EditorIo for StorageEditorIo {
             .begin_transaction()
             .map_err(|err| IoError::StorageError(err.to_string()))?;
 
-        let object_id = ObjectId::new();
-        let version_id = self
+        let (object_id, version_id) = self
             .storage
-            .write(&mut tx, object_id, content.as_bytes())
+            .create(self.principal, &mut tx, content.as_bytes())
             .map_err(Self::map_tx_error)?;
         self.storage.commit(&mut tx).map_err(Self::map_tx_error)?;
 
         // Link to filesystem (simplified - assumes path is just a name in current dir)
         // In a full implementation, this would parse the path and create directories as needed
         let name = path.split('/').next_back().unwrap_or(path);
-        if let Err(err) = fs.link(root, name, object_id, services_storage::ObjectKind::Blob) {
-            return Err(IoError::StorageError(format!(
-                "Failed to link file: {}",
-                err
-            )));
+        let mut fs = fs.as_principal(self.principal);
+        match fs.link(root, name, object_id, services_storage::ObjectKind::Blob) {
+            Ok(()) => {}
+            Err(err @ services_fs_view::OperationError::AccessDenied(_)) => {
+                return Err(Self::map_fs_error(err))
+            }
+            Err(err) => {
+                return Err(IoError::StorageError(format!(
+                    "Failed to link file: {}",
+                    err
+                )))
+            }
         }
 
         Ok(SaveResult::new(
@@ -278,6 +323,24 @@ impl EditorIo for StorageEditorIo {
             Some(object_id),
         ))
     }
+
+    fn history(&mut self, handle: &DocumentHandle) -> Result<Vec<VersionInfo>, IoError> {
+        self.storage
+            .versions(self.principal, handle.object_id)
+            .map_err(Self::map_tx_error)
+    }
+
+    fn open_version(
+        &mut self,
+        handle: &DocumentHandle,
+        version_id: VersionId,
+    ) -> Result<String, IoError> {
+        let data = self
+            .storage
+            .read_version(self.principal, handle.object_id, version_id)
+            .map_err(Self::map_tx_error)?;
+        String::from_utf8(data).map_err(|_| IoError::InvalidUtf8)
+    }
 }
 
 impl SaveResult {
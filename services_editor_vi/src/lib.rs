@@ -6,7 +6,7 @@
 //!
 //! ## Philosophy
 //!
-//! - **Modal editing**: Normal, Insert, Command modes
+//! - **Modal editing**: Normal, Insert, Command and Visual modes
 //! - **Capability-based**: No ambient file access; documents opened via capabilities
 //! - **Versioned saves**: Creating new immutable versions instead of overwriting
 //! - **Testable**: Fully testable with injected keyboard events under SimKernel
@@ -26,6 +26,18 @@
 //! - Input arrives as structured KeyEvent messages
 //! - Saves create new object versions
 //! - Directory link updates are separate operations requiring write authority
+//! - Normal-mode counts, motions and operators come from `editor_core`, shared
+//!   with the bare-metal editor
+//! - The `"+` register reads and publishes through a granted clipboard
+//!   client, never an ambient system clipboard
+//! - Visual-mode selections are published as frame highlights, so every
+//!   renderer shows the cells an operator will act on
+//! - Ex line commands (ranges, `:s`, `:g`, `:d`, `:m`, `:r`) come from
+//!   `editor_core`; `:set` options are backed by the user's settings when
+//!   the host provides them
+//! - `/`, `?`, `n`, `N`, `*` and `#` search with `editor_core`'s regex
+//!   search; matches are published as frame highlights while the pattern is
+//!   typed and after the search, until `:nohlsearch`
 
 extern crate alloc;
 
@@ -4,6 +4,8 @@ use crate::state::{EditorMode, EditorState};
 use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
+use editor_core::LineSpan;
+use view_types::{HighlightKind, TextHighlight};
 
 /// Editor view for rendering
 ///
@@ -20,18 +22,29 @@ impl EditorView {
     }
 
     /// Render the editor state to a string
+    ///
+    /// The cursor shows as `[c]`, and a selection and search matches are
+    /// wrapped in `{` and `}` on each line they cover. With `:set number`
+    /// each line starts with its number.
     pub fn render(&self, state: &EditorState) -> String {
         let mut output = String::new();
 
         // Render viewport (buffer lines)
         let buffer = state.buffer();
         let cursor_pos = state.cursor().position();
+        let mut spans = state.selection_spans();
+        spans.extend(state.search_spans(0..self.viewport_lines));
 
         for row in 0..self.viewport_lines {
             if let Some(line) = buffer.line(row) {
-                // Show cursor if on this line
-                if row == cursor_pos.row {
-                    output.push_str(&self.render_line_with_cursor(line, cursor_pos.col));
+                if state.options().number {
+                    output.push_str(&format!("{:>3} ", row + 1));
+                }
+                let cursor = (row == cursor_pos.row).then_some(cursor_pos.col);
+                let line_spans: Vec<&LineSpan> =
+                    spans.iter().filter(|span| span.row == row).collect();
+                if cursor.is_some() || !line_spans.is_empty() {
+                    output.push_str(&self.render_decorated_line(line, cursor, &line_spans));
                 } else {
                     output.push_str(line);
                 }
@@ -47,18 +60,36 @@ impl EditorView {
         output
     }
 
-    fn render_line_with_cursor(&self, line: &str, col: usize) -> String {
+    fn render_decorated_line(
+        &self,
+        line: &str,
+        cursor: Option<usize>,
+        spans: &[&LineSpan],
+    ) -> String {
+        let chars: Vec<char> = line.chars().collect();
+        // Cursor at end of line, and selected empty lines, show as a blank
+        let mut width = chars.len();
+        if cursor == Some(chars.len()) {
+            width += 1;
+        }
+        for span in spans {
+            width = width.max(span.end);
+        }
+
         let mut result = String::new();
-        for (i, ch) in line.chars().enumerate() {
-            if i == col {
+        for i in 0..width {
+            for _ in spans.iter().filter(|span| span.start == i) {
+                result.push('{');
+            }
+            let ch = chars.get(i).copied().unwrap_or(' ');
+            if cursor == Some(i) {
                 result.push_str(&format!("[{}]", ch));
             } else {
                 result.push(ch);
             }
-        }
-        // Cursor at end of line
-        if col == line.len() {
-            result.push_str("[ ]");
+            for _ in spans.iter().filter(|span| span.end == i + 1) {
+                result.push('}');
+            }
         }
         result
     }
@@ -90,7 +121,7 @@ impl EditorView {
 
         // Search query in search mode
         if state.mode() == EditorMode::Search {
-            status.push('/');
+            status.push(state.search().direction().key());
             status.push_str(state.search_query());
         }
 
@@ -120,6 +151,23 @@ impl EditorView {
     pub fn render_status(&self, state: &EditorState) -> String {
         self.render_status_line(state)
     }
+
+    /// Selection and search match highlights for a published text buffer
+    /// frame
+    pub fn highlights(&self, state: &EditorState) -> Vec<TextHighlight> {
+        let highlight =
+            |kind| move |span: LineSpan| TextHighlight::new(span.row, span.start, span.end, kind);
+        let selection = state.selection_spans().into_iter();
+        let matches = state.search_spans(0..state.buffer().line_count());
+        selection
+            .map(highlight(HighlightKind::Selection))
+            .chain(
+                matches
+                    .into_iter()
+                    .map(highlight(HighlightKind::SearchMatch)),
+            )
+            .collect()
+    }
 }
 
 /// Get mode-specific hint text
@@ -129,6 +177,9 @@ fn get_mode_hint(mode: EditorMode) -> &'static str {
         EditorMode::Insert => "Insert — Esc=Normal",
         EditorMode::Command => "Command — Enter=Run Esc=Cancel :w :q :wq",
         EditorMode::Search => "Search — Enter=Find Esc=Cancel",
+        EditorMode::Visual | EditorMode::VisualLine | EditorMode::VisualBlock => {
+            "Visual — d=Delete c=Change y=Yank o=Other end Esc=Normal"
+        }
     }
 }
 
@@ -227,6 +278,65 @@ mod tests {
         assert!(lines[0].contains("[l]")); // Cursor on 'l'
     }
 
+    #[test]
+    fn test_render_selection() {
+        let view = EditorView::new(3);
+        let mut state = EditorState::new();
+        state.load_content("hello\n\nworld".to_string());
+        state.cursor_mut().set_position(Position::new(0, 1));
+        state.start_visual(editor_core::VisualKind::Char);
+        state.cursor_mut().set_position(Position::new(2, 2));
+
+        let output = view.render(&state);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[0], "h{ello}");
+        assert_eq!(lines[1], "{ }");
+        assert_eq!(lines[2], "{wo[r]}ld");
+        assert!(lines[3].starts_with("VISUAL"));
+        assert_eq!(view.highlights(&state).len(), 3);
+    }
+
+    #[test]
+    fn test_render_search_matches() {
+        let view = EditorView::new(2);
+        let mut state = EditorState::new();
+        state.load_content("cat cot\ndog".to_string());
+        state.set_mode(EditorMode::Search);
+        state
+            .search_mut()
+            .start(editor_core::SearchDirection::Backward);
+        state.append_to_search('c');
+        state.append_to_search('.');
+
+        let output = view.render(&state);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "{[c]a}t {co}t");
+        assert_eq!(lines[1], "dog");
+        assert!(lines[2].starts_with("SEARCH ?c."));
+
+        state.append_to_search('t');
+        state.submit_search().unwrap();
+        state.set_mode(EditorMode::Normal);
+        let output = view.render(&state);
+        assert!(output.starts_with("{[c]at} {cot}\n"));
+    }
+
+    #[test]
+    fn test_render_line_numbers() {
+        let view = EditorView::new(3);
+        let mut state = EditorState::new();
+        state.load_content("one\ntwo".to_string());
+        state.options_mut().number = true;
+
+        let output = view.render(&state);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[0], "  1 [o]ne");
+        assert_eq!(lines[1], "  2 two");
+        assert_eq!(lines[2], "~");
+    }
+
     #[test]
     fn test_render_status_normal_mode() {
         let view = EditorView::new(3);
@@ -1,12 +1,23 @@
 //! Editor state and buffer management
 
 use alloc::collections::BTreeSet;
-use alloc::string::{String, ToString};
-use alloc::vec;
+use alloc::string::String;
 use alloc::vec::Vec;
-use core::fmt;
+use core::ops::Range;
+use editor_core::{
+    Address, CommandError, EditorOptions, ExCommand, ExEffect, NormalCommand, NormalEffect,
+    NormalEngine, Operator, Registers, SearchDirection, SearchState, VisualCommand,
+};
 use serde::{Deserialize, Serialize};
 
+/// Buffer and position types are shared with `editor_core`, so the vi
+/// grammar in [`NormalEngine`] edits this editor's buffer directly.
+pub use editor_core::{Position, TextBuffer};
+
+/// Selections are shared with `editor_core` too, so renderers highlight the
+/// same cells an operator acts on.
+pub use editor_core::{LineSpan, Selection, VisualKind};
+
 /// Editor mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EditorMode {
@@ -18,6 +29,12 @@ pub enum EditorMode {
     Command,
     /// Search mode (search prompt)
     Search,
+    /// Visual mode (character selection)
+    Visual,
+    /// Visual Line mode (whole-line selection)
+    VisualLine,
+    /// Visual Block mode (column selection)
+    VisualBlock,
 }
 
 impl EditorMode {
@@ -27,24 +44,33 @@ impl EditorMode {
             EditorMode::Insert => "INSERT",
             EditorMode::Command => "COMMAND",
             EditorMode::Search => "SEARCH",
+            EditorMode::Visual => "VISUAL",
+            EditorMode::VisualLine => "VISUAL LINE",
+            EditorMode::VisualBlock => "VISUAL BLOCK",
         }
     }
-}
 
-/// Cursor position in the buffer
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub struct Position {
-    pub row: usize,
-    pub col: usize,
-}
+    /// Visual mode showing a selection of this kind
+    pub fn visual(kind: VisualKind) -> Self {
+        match kind {
+            VisualKind::Char => EditorMode::Visual,
+            VisualKind::Line => EditorMode::VisualLine,
+            VisualKind::Block => EditorMode::VisualBlock,
+        }
+    }
 
-impl Position {
-    pub fn new(row: usize, col: usize) -> Self {
-        Self { row, col }
+    /// Kind of selection the mode shows, if it is a Visual mode
+    pub fn visual_kind(&self) -> Option<VisualKind> {
+        match self {
+            EditorMode::Visual => Some(VisualKind::Char),
+            EditorMode::VisualLine => Some(VisualKind::Line),
+            EditorMode::VisualBlock => Some(VisualKind::Block),
+            _ => None,
+        }
     }
 
-    pub fn zero() -> Self {
-        Self { row: 0, col: 0 }
+    pub fn is_visual(&self) -> bool {
+        self.visual_kind().is_some()
     }
 }
 
@@ -111,129 +137,6 @@ impl Default for Cursor {
     }
 }
 
-/// Text buffer
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TextBuffer {
-    lines: Vec<String>,
-}
-
-impl TextBuffer {
-    pub fn new() -> Self {
-        Self {
-            lines: vec![String::new()],
-        }
-    }
-
-    pub fn from_string(content: String) -> Self {
-        let lines = if content.is_empty() {
-            vec![String::new()]
-        } else {
-            content.lines().map(|s| s.to_string()).collect()
-        };
-        Self { lines }
-    }
-
-    pub fn as_string(&self) -> String {
-        self.lines.join("\n")
-    }
-
-    pub fn line_count(&self) -> usize {
-        self.lines.len()
-    }
-
-    pub fn line(&self, row: usize) -> Option<&str> {
-        self.lines.get(row).map(|s| s.as_str())
-    }
-
-    pub fn line_length(&self, row: usize) -> usize {
-        self.lines.get(row).map(|s| s.len()).unwrap_or(0)
-    }
-
-    pub fn lines(&self) -> &[String] {
-        &self.lines
-    }
-
-    /// Insert a character at position
-    pub fn insert_char(&mut self, pos: Position, ch: char) -> bool {
-        if pos.row >= self.lines.len() {
-            return false;
-        }
-
-        let line = &mut self.lines[pos.row];
-        if pos.col > line.len() {
-            return false;
-        }
-
-        line.insert(pos.col, ch);
-        true
-    }
-
-    /// Insert a newline at position
-    pub fn insert_newline(&mut self, pos: Position) -> bool {
-        if pos.row >= self.lines.len() {
-            return false;
-        }
-
-        let line = &mut self.lines[pos.row];
-        if pos.col > line.len() {
-            return false;
-        }
-
-        let rest = line.split_off(pos.col);
-        self.lines.insert(pos.row + 1, rest);
-        true
-    }
-
-    /// Delete character at position
-    pub fn delete_char(&mut self, pos: Position) -> bool {
-        if pos.row >= self.lines.len() {
-            return false;
-        }
-
-        let line = &mut self.lines[pos.row];
-        if pos.col >= line.len() {
-            return false;
-        }
-
-        line.remove(pos.col);
-        true
-    }
-
-    /// Delete character before position (backspace)
-    pub fn backspace(&mut self, pos: Position) -> Option<Position> {
-        if pos.col > 0 {
-            // Delete character on same line
-            let line = &mut self.lines[pos.row];
-            line.remove(pos.col - 1);
-            return Some(Position::new(pos.row, pos.col - 1));
-        } else if pos.row > 0 {
-            // Join with previous line
-            let current_line = self.lines.remove(pos.row);
-            let prev_line = &mut self.lines[pos.row - 1];
-            let new_col = prev_line.len();
-            prev_line.push_str(&current_line);
-            return Some(Position::new(pos.row - 1, new_col));
-        }
-        None
-    }
-
-    pub fn is_empty(&self) -> bool {
-        self.lines.len() == 1 && self.lines[0].is_empty()
-    }
-}
-
-impl Default for TextBuffer {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl fmt::Display for TextBuffer {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.as_string())
-    }
-}
-
 /// Editor state snapshot for undo/redo
 #[derive(Debug, Clone)]
 struct EditorSnapshot {
@@ -255,14 +158,18 @@ pub struct EditorState {
     undo_stack: Vec<EditorSnapshot>,
     /// Redo history (stack of undone states)
     redo_stack: Vec<EditorSnapshot>,
-    /// Current search query
-    search_query: String,
-    /// Last search query (for 'n' repeat search)
-    last_search: Option<String>,
+    /// Pattern being typed, last search and search history
+    search: SearchState,
     /// Dirty lines tracking (line indices that have changed since last render)
     dirty_lines: BTreeSet<usize>,
     /// Cursor position changed flag
     cursor_dirty: bool,
+    /// Counts, operators, register and `.` repeat
+    normal: NormalEngine,
+    /// The end of a Visual-mode selection that stays put
+    visual_anchor: Position,
+    /// Options changed with `:set`
+    options: EditorOptions,
 }
 
 impl EditorState {
@@ -277,10 +184,12 @@ impl EditorState {
             document_label: None,
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
-            search_query: String::new(),
-            last_search: None,
+            search: SearchState::new(),
             dirty_lines: BTreeSet::new(),
             cursor_dirty: false,
+            normal: NormalEngine::new(),
+            visual_anchor: Position::zero(),
+            options: EditorOptions::default(),
         }
     }
 
@@ -288,13 +197,21 @@ impl EditorState {
         self.mode
     }
 
+    /// Switch mode; leaving Visual mode sets `'<` and `'>` to the selection
     pub fn set_mode(&mut self, mode: EditorMode) {
+        if let Some(selection) = self.selection() {
+            let (first, last) = selection.rows();
+            self.mark_lines_dirty(first, last);
+            if !mode.is_visual() {
+                self.normal.mark_selection(&selection);
+            }
+        }
         self.mode = mode;
         if mode != EditorMode::Command {
             self.command_buffer.clear();
         }
         if mode != EditorMode::Search {
-            self.search_query.clear();
+            self.search.clear();
         }
     }
 
@@ -396,6 +313,180 @@ impl EditorState {
         self.document_label = label;
     }
 
+    /// Normal-mode engine
+    pub fn normal(&self) -> &NormalEngine {
+        &self.normal
+    }
+
+    /// Normal-mode engine, to feed keys and record inserted text
+    pub fn normal_mut(&mut self) -> &mut NormalEngine {
+        &mut self.normal
+    }
+
+    /// Yank registers, unnamed and `"a`–`"z` and `"+`
+    pub fn registers(&self) -> &Registers {
+        self.normal.registers()
+    }
+
+    /// Yank registers, to load or read the clipboard register
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        self.normal.registers_mut()
+    }
+
+    /// Run a Normal-mode command on the buffer
+    ///
+    /// Changes are undoable as one step, including the text typed if the
+    /// command enters Insert mode; switching mode is left to the caller.
+    pub fn apply_normal(&mut self, command: NormalCommand) -> NormalEffect {
+        if command.is_change() {
+            self.save_undo_snapshot();
+        }
+        let mut position = self.cursor.position();
+        let effect = self
+            .normal
+            .execute(command, &mut self.buffer, &mut position);
+        self.cursor.set_position(position);
+        self.cursor_dirty = true;
+        if effect.modified {
+            self.dirty = true;
+            self.mark_all_dirty(100);
+        }
+        effect
+    }
+
+    /// Options set with `:set`
+    pub fn options(&self) -> &EditorOptions {
+        &self.options
+    }
+
+    pub fn options_mut(&mut self) -> &mut EditorOptions {
+        &mut self.options
+    }
+
+    /// Run an ex command on the buffer, undoable as one step
+    ///
+    /// `:r` and `:set` are left to the caller.
+    pub fn apply_ex(&mut self, command: &ExCommand) -> Result<ExEffect, CommandError> {
+        let before = self.snapshot();
+        let mut position = self.cursor.position();
+        let result = self.normal.run_ex(command, &mut self.buffer, &mut position);
+        self.finish_ex(before, position, result)
+    }
+
+    /// Insert text read by `:r` below `line` (the cursor line if `None`)
+    pub fn read_into(
+        &mut self,
+        line: Option<Address>,
+        text: &str,
+    ) -> Result<ExEffect, CommandError> {
+        let before = self.snapshot();
+        let mut position = self.cursor.position();
+        let result = self
+            .normal
+            .read_into(line, text, &mut self.buffer, &mut position);
+        self.finish_ex(before, position, result)
+    }
+
+    fn finish_ex(
+        &mut self,
+        before: EditorSnapshot,
+        position: Position,
+        result: Result<ExEffect, CommandError>,
+    ) -> Result<ExEffect, CommandError> {
+        let effect = result?;
+        if effect.modified {
+            self.push_undo_snapshot(before);
+            self.dirty = true;
+            self.mark_all_dirty(100);
+        }
+        self.cursor.set_position(position);
+        self.cursor_dirty = true;
+        Ok(effect)
+    }
+
+    /// Start a selection of `kind` at the cursor
+    pub fn start_visual(&mut self, kind: VisualKind) {
+        self.visual_anchor = self.cursor.position();
+        self.set_mode(EditorMode::visual(kind));
+        self.mark_line_dirty(self.visual_anchor.row);
+    }
+
+    /// The selection, while in a Visual mode
+    pub fn selection(&self) -> Option<Selection> {
+        self.mode
+            .visual_kind()
+            .map(|kind| Selection::new(kind, self.visual_anchor, self.cursor.position()))
+    }
+
+    /// Cells the selection covers, one span per line
+    pub fn selection_spans(&self) -> Vec<LineSpan> {
+        self.selection()
+            .map(|selection| selection.line_spans(&self.buffer))
+            .unwrap_or_default()
+    }
+
+    /// Run a Visual-mode command
+    ///
+    /// Motions move the cursor end of the selection and `v`/`V`/Ctrl+V switch
+    /// kind or leave Visual mode. An operator is undoable as one step and
+    /// returns to Normal mode; entering Insert mode after `c` is left to the
+    /// caller.
+    pub fn apply_visual(&mut self, command: VisualCommand) -> NormalEffect {
+        let Some(selection) = self.selection() else {
+            return NormalEffect::default();
+        };
+        let (first, last) = selection.rows();
+        self.mark_lines_dirty(first, last);
+        self.cursor_dirty = true;
+        let mut position = self.cursor.position();
+        let effect = match command {
+            VisualCommand::Move { motion, count } => {
+                position = motion.apply(&self.buffer, position, count);
+                NormalEffect::default()
+            }
+            VisualCommand::SwapEnds => {
+                core::mem::swap(&mut self.visual_anchor, &mut position);
+                NormalEffect::default()
+            }
+            VisualCommand::Switch(kind) => {
+                if self.mode == EditorMode::visual(kind) {
+                    self.set_mode(EditorMode::Normal);
+                } else {
+                    self.set_mode(EditorMode::visual(kind));
+                }
+                NormalEffect::default()
+            }
+            VisualCommand::EnterCommand => {
+                self.set_mode(EditorMode::Command);
+                self.command_buffer = "'<,'>".into();
+                NormalEffect::default()
+            }
+            VisualCommand::Operate { operator, register } => {
+                if operator != Operator::Yank {
+                    self.save_undo_snapshot();
+                }
+                let effect = self.normal.operate_selection(
+                    operator,
+                    register,
+                    &selection,
+                    &mut self.buffer,
+                    &mut position,
+                );
+                if effect.modified {
+                    self.dirty = true;
+                    self.mark_all_dirty(100);
+                }
+                self.set_mode(EditorMode::Normal);
+                effect
+            }
+        };
+        self.cursor.set_position(position);
+        if let Some((first, last)) = self.selection().map(|selection| selection.rows()) {
+            self.mark_lines_dirty(first, last);
+        }
+        effect
+    }
+
     pub fn load_content(&mut self, content: String) {
         self.buffer = TextBuffer::from_string(content);
         self.cursor = Cursor::new();
@@ -404,99 +495,81 @@ impl EditorState {
 
     /// Get current search query
     pub fn search_query(&self) -> &str {
-        &self.search_query
+        self.search.query()
+    }
+
+    /// Search direction, last pattern and history
+    pub fn search(&self) -> &SearchState {
+        &self.search
+    }
+
+    pub fn search_mut(&mut self) -> &mut SearchState {
+        &mut self.search
     }
 
     /// Append character to search query
     pub fn append_to_search(&mut self, ch: char) {
-        self.search_query.push(ch);
+        self.search.push(ch);
     }
 
     /// Backspace in search query
     pub fn backspace_search(&mut self) {
-        self.search_query.pop();
+        self.search.pop();
     }
 
     /// Clear search query
     pub fn clear_search(&mut self) {
-        self.search_query.clear();
+        self.search.clear();
     }
 
-    /// Execute search: find next occurrence of query from current position
-    pub fn find_next(&mut self, forward: bool) -> bool {
-        let query = if !self.search_query.is_empty() {
-            self.last_search = Some(self.search_query.clone());
-            &self.search_query
-        } else if let Some(ref last) = self.last_search {
-            last
-        } else {
-            return false;
-        };
-
-        if query.is_empty() {
-            return false;
-        }
+    /// Search for the typed pattern and move the cursor to the match
+    pub fn submit_search(&mut self) -> Result<(), CommandError> {
+        let found = self.search.submit(&self.buffer, self.cursor.position());
+        self.jump_to_match(found)
+    }
 
-        let start_pos = self.cursor.position();
-        let line_count = self.buffer.line_count();
+    /// `n`, or `N` with `reverse`: repeat the last search
+    pub fn repeat_search(&mut self, reverse: bool) -> Result<(), CommandError> {
+        let found = self
+            .search
+            .repeat(&self.buffer, self.cursor.position(), reverse);
+        self.jump_to_match(found)
+    }
 
-        // For first search (when search_query not empty), start from current position
-        // For repeat search (n), start from next position
-        let start_col = if !self.search_query.is_empty() {
-            start_pos.col
-        } else {
-            start_pos.col + 1
-        };
+    /// `*` or `#`: search for the word under the cursor
+    pub fn search_word(&mut self, direction: SearchDirection) -> Result<(), CommandError> {
+        let found = self
+            .search
+            .search_word(&self.buffer, self.cursor.position(), direction);
+        self.jump_to_match(found)
+    }
 
-        // Search from start position
-        if forward {
-            let mut row = start_pos.row;
-            let mut col = start_col;
-
-            while row < line_count {
-                if let Some(line) = self.buffer.line(row) {
-                    if col < line.len() {
-                        if let Some(pos) = line[col..].find(query) {
-                            self.cursor.set_position(Position::new(row, col + pos));
-                            return true;
-                        }
-                    }
-                }
-                row += 1;
-                col = 0;
-            }
+    fn jump_to_match(&mut self, found: Result<Position, CommandError>) -> Result<(), CommandError> {
+        self.cursor.set_position(found?);
+        self.cursor_dirty = true;
+        Ok(())
+    }
 
-            // Wrap around to beginning
-            for row in 0..start_pos.row {
-                if let Some(line) = self.buffer.line(row) {
-                    if let Some(pos) = line.find(query) {
-                        self.cursor.set_position(Position::new(row, pos));
-                        return true;
-                    }
-                }
-            }
+    /// Search matches to highlight on `rows`: those of the pattern being
+    /// typed in Search mode, otherwise those of the last search
+    pub fn search_spans(&self, rows: Range<usize>) -> Vec<LineSpan> {
+        self.search
+            .match_spans(&self.buffer, rows, self.mode == EditorMode::Search)
+    }
 
-            // Check start row up to start column (only for repeat search)
-            if self.search_query.is_empty() && start_pos.row < line_count {
-                if let Some(line) = self.buffer.line(start_pos.row) {
-                    let end_col = start_pos.col.min(line.len());
-                    if let Some(pos) = line[..end_col].find(query) {
-                        self.cursor.set_position(Position::new(start_pos.row, pos));
-                        return true;
-                    }
-                }
-            }
+    fn snapshot(&self) -> EditorSnapshot {
+        EditorSnapshot {
+            buffer: self.buffer.clone(),
+            cursor: self.cursor.clone(),
         }
-
-        false
     }
 
     /// Save current state for undo
     pub fn save_undo_snapshot(&mut self) {
-        let snapshot = EditorSnapshot {
-            buffer: self.buffer.clone(),
-            cursor: self.cursor.clone(),
-        };
+        self.push_undo_snapshot(self.snapshot());
+    }
+
+    fn push_undo_snapshot(&mut self, snapshot: EditorSnapshot) {
         self.undo_stack.push(snapshot);
         // Clear redo stack when making a new edit
         self.redo_stack.clear();
@@ -602,12 +675,19 @@ impl Default for EditorState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
 
     #[test]
     fn test_editor_mode() {
         assert_eq!(EditorMode::Normal.as_str(), "NORMAL");
         assert_eq!(EditorMode::Insert.as_str(), "INSERT");
         assert_eq!(EditorMode::Command.as_str(), "COMMAND");
+        assert_eq!(EditorMode::VisualLine.as_str(), "VISUAL LINE");
+        assert_eq!(
+            EditorMode::visual(VisualKind::Block).visual_kind(),
+            Some(VisualKind::Block)
+        );
     }
 
     #[test]
@@ -639,7 +719,7 @@ mod tests {
     #[test]
     fn test_text_buffer_to_string() {
         let mut buffer = TextBuffer::new();
-        buffer.lines = vec!["hello".to_string(), "world".to_string()];
+        buffer.insert_text(Position::zero(), "hello\nworld");
         assert_eq!(buffer.as_string(), "hello\nworld");
     }
 
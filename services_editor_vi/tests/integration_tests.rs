@@ -8,7 +8,9 @@ use services_editor_vi::{
     DocumentHandle, Editor, EditorAction, EditorIo, OpenOptions, OpenResult, SaveResult,
     StorageEditorIo,
 };
-use services_storage::{JournaledStorage, ObjectId, TransactionalStorage, VersionId};
+use services_storage::{
+    CheckedStorage, JournaledStorage, ObjectId, PrincipalId, TransactionalStorage, VersionId,
+};
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -209,14 +211,14 @@ fn test_navigation_with_hjkl() {
 
 #[test]
 fn test_editor_open_and_save_with_storage_io() {
-    let mut storage = JournaledStorage::new();
-    let object_id = ObjectId::new();
+    let owner = PrincipalId::new();
+    let mut storage = CheckedStorage::new(JournaledStorage::new());
 
     let mut tx = storage.begin_transaction().unwrap();
-    let initial_version = storage.write(&mut tx, object_id, b"hello").unwrap();
+    let (object_id, initial_version) = storage.create(owner, &mut tx, b"hello").unwrap();
     storage.commit(&mut tx).unwrap();
 
-    let io = StorageEditorIo::new(storage);
+    let io = StorageEditorIo::new(storage, owner);
     let mut editor = Editor::new();
     editor.set_io(Box::new(io));
 
@@ -240,6 +242,54 @@ fn test_editor_open_and_save_with_storage_io() {
     assert_ne!(editor.document().unwrap().version_id, initial_version);
 }
 
+fn run_command(editor: &mut Editor, command: &str) -> EditorAction {
+    editor
+        .process_input(press_key_shift(KeyCode::Semicolon))
+        .unwrap();
+    for ch in command.chars() {
+        editor.state_mut().append_to_command(ch);
+    }
+    editor.process_input(press_key(KeyCode::Enter)).unwrap()
+}
+
+#[test]
+fn test_history_and_restore_with_storage_io() {
+    let owner = PrincipalId::new();
+    let mut storage = CheckedStorage::new(JournaledStorage::new());
+    let mut tx = storage.begin_transaction().unwrap();
+    let (object_id, _) = storage.create(owner, &mut tx, b"first").unwrap();
+    storage.commit(&mut tx).unwrap();
+    let mut tx = storage.begin_transaction().unwrap();
+    storage.write(owner, &mut tx, object_id, b"second").unwrap();
+    storage.commit(&mut tx).unwrap();
+
+    let mut editor = Editor::new();
+    editor.set_io(Box::new(StorageEditorIo::new(storage, owner)));
+    editor
+        .open_with(OpenOptions::new().with_object(object_id))
+        .unwrap();
+
+    run_command(&mut editor, "history");
+    assert!(editor.state().status_message().contains("1 (5B) 2* (6B)"));
+
+    // Restoring over unsaved changes needs the bang
+    editor.process_input(press_key(KeyCode::X)).unwrap();
+    run_command(&mut editor, "restore 1");
+    assert_eq!(editor.get_content(), "econd");
+
+    run_command(&mut editor, "restore! 1");
+    assert_eq!(editor.get_content(), "first");
+    assert!(editor.state().is_dirty());
+
+    let action = run_command(&mut editor, "w");
+    assert!(matches!(action, EditorAction::Saved(_)));
+    run_command(&mut editor, "history");
+    assert!(editor.state().status_message().contains("3* (5B)"));
+
+    run_command(&mut editor, "restore 7");
+    assert!(editor.state().status_message().contains("No version 7"));
+}
+
 #[test]
 fn test_delete_char_in_normal_mode() {
     let mut editor = Editor::new();
@@ -468,14 +518,18 @@ fn test_save_as_with_storage_io() {
     use fs_view::DirectoryView;
     use services_fs_view::FileSystemViewService;
 
+    let owner = PrincipalId::new();
     let root_id = ObjectId::new();
     let root = DirectoryView::new(root_id);
     let mut fs_view = FileSystemViewService::new();
     fs_view.register_directory(root.clone());
+    fs_view
+        .permissions_mut()
+        .register_object(root_id, services_storage::Ownership::new(owner, 0));
 
     // Create editor with I/O
     let mut editor = Editor::new();
-    let io = Box::new(StorageEditorIo::with_fs_view(storage, fs_view, root));
+    let io = Box::new(StorageEditorIo::with_fs_view(storage, fs_view, root, owner));
     editor.set_io(io);
 
     // Create some content
@@ -513,6 +567,56 @@ fn test_save_as_with_storage_io() {
     assert!(editor.state().status_message().contains("Saved as"));
 }
 
+#[test]
+fn test_storage_io_acts_for_its_principal() {
+    use fs_view::DirectoryView;
+    use services_fs_view::{FileSystemOperations, FileSystemViewService};
+    use services_storage::{ObjectKind, Ownership};
+
+    let alice = PrincipalId::new();
+    let bob = PrincipalId::new();
+    let mut storage = JournaledStorage::new();
+    let object_id = ObjectId::new();
+    let mut tx = storage.begin_transaction().unwrap();
+    storage.write(&mut tx, object_id, b"private").unwrap();
+    storage.commit(&mut tx).unwrap();
+
+    let mut root = DirectoryView::new(ObjectId::new());
+    let mut fs_view = FileSystemViewService::new();
+    fs_view
+        .permissions_mut()
+        .register_object(root.id, Ownership::new(alice, 0));
+    fs_view
+        .as_principal(alice)
+        .link(&mut root, "notes.txt", object_id, ObjectKind::Blob)
+        .unwrap();
+
+    let mut bob_io =
+        StorageEditorIo::with_fs_view(storage.clone(), fs_view.clone(), root.clone(), bob);
+    match bob_io.open(OpenOptions::new().with_path("notes.txt")) {
+        Err(IoError::PermissionDenied(reason)) => assert!(reason.contains("requires Read")),
+        other => panic!(
+            "expected permission denied, got {:?}",
+            other.map(|r| r.content)
+        ),
+    }
+    assert!(matches!(
+        bob_io.open(OpenOptions::new().with_object(object_id)),
+        Err(IoError::PermissionDenied(_))
+    ));
+    assert!(matches!(
+        bob_io.save_as("mine.txt", "x"),
+        Err(IoError::PermissionDenied(_))
+    ));
+
+    let mut alice_io = StorageEditorIo::with_fs_view(storage, fs_view, root, alice);
+    let opened = alice_io
+        .open(OpenOptions::new().with_path("notes.txt"))
+        .unwrap();
+    assert_eq!(opened.content, "private");
+    alice_io.save(&opened.handle, "edited").unwrap();
+}
+
 #[test]
 fn test_open_nonexistent_file_shows_new_file() {
     // Test opening a nonexistent file shows [New File] status
@@ -526,7 +630,12 @@ fn test_open_nonexistent_file_shows_new_file() {
     let fs_view = FileSystemViewService::new();
 
     let mut editor = Editor::new();
-    let io = Box::new(StorageEditorIo::with_fs_view(storage, fs_view, root));
+    let io = Box::new(StorageEditorIo::with_fs_view(
+        storage,
+        fs_view,
+        root,
+        PrincipalId::new(),
+    ));
     editor.set_io(io);
 
     // Try to open a nonexistent file
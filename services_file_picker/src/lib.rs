@@ -17,6 +17,12 @@
 //! - Select files (Enter on file)
 //! - Go up one level (Esc or Back)
 //! - Deterministic sorting (dirs before files, lexicographic within each)
+//! - Sort by modification time, creation time or content type instead of
+//!   name (`s` cycles through the orders)
+//! - Filter files by content type or attribute (`set_filter`); directories
+//!   stay listed so they can still be entered
+//! - Show search results (`show_matches`) from a filesystem glob; Esc returns
+//!   to the directory listing
 //!
 //! ## Example
 //!
@@ -59,10 +65,13 @@ extern crate alloc;
 
 use alloc::string::String;
 use alloc::vec::Vec;
+use core::cmp::Ordering;
 use fs_view::{DirectoryEntry, DirectoryView};
 use input_types::{InputEvent, KeyCode, KeyState};
+use services_fs_view::PathMatch;
 use services_storage::ObjectId;
 use services_storage::ObjectKind;
+use services_storage::{ContentType, EntryMetadata};
 use thiserror::Error;
 
 // Re-export DirectoryResolver for convenience - allows users to implement
@@ -109,16 +118,114 @@ pub struct PickerEntry {
     pub kind: ObjectKind,
     /// Whether this is a directory
     pub is_directory: bool,
+    /// Explicit or guessed content type
+    pub content_type: ContentType,
+    /// Timestamps and attributes
+    pub metadata: EntryMetadata,
 }
 
 impl PickerEntry {
     /// Creates a new picker entry from a directory entry
     fn from_directory_entry(entry: &DirectoryEntry) -> Self {
+        Self::with_metadata(
+            entry.name.clone(),
+            entry.object_id,
+            entry.kind,
+            entry.metadata.clone(),
+        )
+    }
+
+    fn with_metadata(
+        name: String,
+        object_id: ObjectId,
+        kind: ObjectKind,
+        metadata: EntryMetadata,
+    ) -> Self {
         Self {
-            name: entry.name.clone(),
-            object_id: entry.object_id,
-            kind: entry.kind,
-            is_directory: entry.kind == ObjectKind::Map,
+            content_type: metadata.content_type_for(&name, kind),
+            name,
+            object_id,
+            kind,
+            is_directory: kind == ObjectKind::Map,
+            metadata,
+        }
+    }
+}
+
+/// Order of entries within the directory and file groups
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Lexicographic by name
+    #[default]
+    Name,
+    /// Most recently modified first
+    Modified,
+    /// Most recently created first
+    Created,
+    /// By content type, then name
+    ContentType,
+}
+
+impl SortOrder {
+    /// The order `s` switches to next
+    pub fn next(self) -> Self {
+        match self {
+            SortOrder::Name => SortOrder::Modified,
+            SortOrder::Modified => SortOrder::Created,
+            SortOrder::Created => SortOrder::ContentType,
+            SortOrder::ContentType => SortOrder::Name,
+        }
+    }
+
+    /// Lowercase label for the status line
+    pub fn label(self) -> &'static str {
+        match self {
+            SortOrder::Name => "name",
+            SortOrder::Modified => "modified",
+            SortOrder::Created => "created",
+            SortOrder::ContentType => "type",
+        }
+    }
+
+    fn compare(self, a: &PickerEntry, b: &PickerEntry) -> Ordering {
+        let by_key = match self {
+            SortOrder::Name => Ordering::Equal,
+            SortOrder::Modified => b.metadata.modified_at.cmp(&a.metadata.modified_at),
+            SortOrder::Created => b.metadata.created_at.cmp(&a.metadata.created_at),
+            SortOrder::ContentType => a.content_type.cmp(&b.content_type),
+        };
+        by_key.then_with(|| a.name.cmp(&b.name))
+    }
+}
+
+/// Which files the picker lists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryFilter {
+    /// Files of one content type
+    ContentType(ContentType),
+    /// Files with an attribute, optionally set to a given value
+    Attribute {
+        /// Attribute key
+        key: String,
+        /// Required value; any value matches when absent
+        value: Option<String>,
+    },
+}
+
+impl EntryFilter {
+    /// Whether the picker lists `entry` under this filter
+    ///
+    /// Directories always match so they can still be entered.
+    pub fn matches(&self, entry: &PickerEntry) -> bool {
+        if entry.is_directory {
+            return true;
+        }
+        match self {
+            EntryFilter::ContentType(content_type) => entry.content_type == *content_type,
+            EntryFilter::Attribute { key, value } => entry
+                .metadata
+                .attribute(key)
+                .is_some_and(|found| value.as_deref().is_none_or(|expected| found == expected)),
         }
     }
 }
@@ -128,12 +235,20 @@ impl PickerEntry {
 pub struct FilePicker {
     /// Current directory being displayed
     current_directory: DirectoryView,
-    /// Sorted list of entries (directories first, then files, lexicographic)
+    /// Entries of the directory or search, before filtering
+    listed: Vec<PickerEntry>,
+    /// Listed entries that pass the filter, sorted (directories first)
     entries: Vec<PickerEntry>,
     /// Currently selected index
     selected_index: usize,
     /// Directory navigation stack (for going back)
     directory_stack: Vec<DirectoryView>,
+    /// Glob whose matches are listed instead of the current directory
+    search: Option<String>,
+    /// Order within the directory and file groups
+    sort_order: SortOrder,
+    /// Which files are listed
+    filter: Option<EntryFilter>,
 }
 
 impl FilePicker {
@@ -141,32 +256,102 @@ impl FilePicker {
     pub fn new(root: DirectoryView) -> Self {
         let mut picker = Self {
             current_directory: root.clone(),
+            listed: Vec::new(),
             entries: Vec::new(),
             selected_index: 0,
             directory_stack: Vec::new(),
+            search: None,
+            sort_order: SortOrder::default(),
+            filter: None,
         };
         picker.refresh_entries();
         picker
     }
 
     /// Refreshes the entry list from the current directory
-    /// Applies deterministic sorting: directories first, then files, lexicographic within each group
     fn refresh_entries(&mut self) {
+        self.search = None;
         let raw_entries = self.current_directory.list_entries();
 
         // Convert to picker entries
-        let mut entries: Vec<PickerEntry> = raw_entries
+        let entries: Vec<PickerEntry> = raw_entries
             .iter()
             .map(|entry| PickerEntry::from_directory_entry(entry))
             .collect();
+        self.set_entries(entries);
+    }
+
+    /// Lists search matches in place of the current directory
+    ///
+    /// Entries are named by their path relative to the search root and sorted
+    /// like a directory listing. Selecting one behaves as in a directory;
+    /// Esc goes back to the listing.
+    pub fn show_matches(&mut self, glob: &str, matches: &[PathMatch]) {
+        let entries = matches
+            .iter()
+            .map(|found| {
+                PickerEntry::with_metadata(
+                    found.path.clone(),
+                    found.object_id,
+                    found.kind,
+                    found.metadata.clone(),
+                )
+            })
+            .collect();
+        self.selected_index = 0;
+        self.set_entries(entries);
+        self.search = Some(glob.to_string());
+    }
+
+    /// Returns the glob whose matches are shown, if any
+    pub fn search(&self) -> Option<&str> {
+        self.search.as_deref()
+    }
+
+    /// Returns the order entries are sorted in
+    pub fn sort_order(&self) -> SortOrder {
+        self.sort_order
+    }
+
+    /// Sorts entries in `order`, keeping directories first
+    pub fn set_sort_order(&mut self, order: SortOrder) {
+        self.sort_order = order;
+        self.apply_view();
+    }
+
+    /// Returns the active filter, if any
+    pub fn filter(&self) -> Option<&EntryFilter> {
+        self.filter.as_ref()
+    }
+
+    /// Lists only the files `filter` matches, or every file with `None`
+    pub fn set_filter(&mut self, filter: Option<EntryFilter>) {
+        self.filter = filter;
+        self.selected_index = 0;
+        self.apply_view();
+    }
+
+    /// Replaces the entry list
+    fn set_entries(&mut self, entries: Vec<PickerEntry>) {
+        self.listed = entries;
+        self.apply_view();
+    }
 
-        // Sort deterministically:
-        // 1. Directories first (is_directory = true comes before false)
-        // 2. Within each group, lexicographic by name
+    /// Rebuilds the visible entries from the listed ones
+    /// Applies deterministic sorting: directories first, then files, ordered
+    /// by the sort order (ties by name) within each group
+    fn apply_view(&mut self) {
+        let mut entries: Vec<PickerEntry> = self
+            .listed
+            .iter()
+            .filter(|entry| self.filter.as_ref().is_none_or(|f| f.matches(entry)))
+            .cloned()
+            .collect();
+        let order = self.sort_order;
         entries.sort_by(|a, b| {
             b.is_directory
                 .cmp(&a.is_directory)
-                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| order.compare(a, b))
         });
 
         self.entries = entries;
@@ -203,6 +388,10 @@ impl FilePicker {
                 FilePickerResult::Continue
             }
             KeyCode::Enter => self.handle_selection(resolver),
+            KeyCode::S => {
+                self.set_sort_order(self.sort_order.next());
+                FilePickerResult::Continue
+            }
             KeyCode::Escape => self.handle_back(),
             _ => FilePickerResult::Continue,
         }
@@ -263,6 +452,10 @@ impl FilePicker {
 
     /// Handles the Escape key (go back or cancel)
     fn handle_back(&mut self) -> FilePickerResult {
+        if self.search.is_some() {
+            self.refresh_entries();
+            return FilePickerResult::Continue;
+        }
         if let Some(parent_dir) = self.directory_stack.pop() {
             // Go back to parent directory
             self.current_directory = parent_dir;
@@ -477,6 +670,105 @@ mod tests {
         assert_eq!(result, FilePickerResult::Cancelled);
     }
 
+    #[test]
+    fn test_show_glob_matches() {
+        use services_fs_view::{FileSystemOperations, FileSystemViewService, Glob, SearchOptions};
+        use services_storage::PrincipalId;
+
+        let mut service = FileSystemViewService::new();
+        let mut root = DirectoryView::new(ObjectId::new());
+        service
+            .as_principal(PrincipalId::system())
+            .mkdir(&mut root, "docs")
+            .unwrap();
+        let notes = ObjectId::new();
+        service
+            .as_principal(PrincipalId::system())
+            .link(&mut root, "docs/notes.md", notes, ObjectKind::Blob)
+            .unwrap();
+        service
+            .as_principal(PrincipalId::system())
+            .link(&mut root, "todo.md", ObjectId::new(), ObjectKind::Blob)
+            .unwrap();
+        let results = service.as_principal(PrincipalId::system()).glob(
+            &root,
+            &Glob::new("**/*.md").unwrap(),
+            &SearchOptions::new(),
+        );
+
+        let mut picker = FilePicker::new(root);
+        picker.show_matches("**/*.md", &results.matches);
+        assert_eq!(picker.search(), Some("**/*.md"));
+        assert_eq!(picker.entry_count(), 2);
+
+        let enter_event = InputEvent::Key(KeyEvent::pressed(KeyCode::Enter, Modifiers::none()));
+        assert_eq!(
+            picker.process_input(enter_event, no_resolver()),
+            FilePickerResult::FileSelected {
+                object_id: notes,
+                name: "docs/notes.md".to_string(),
+            }
+        );
+
+        // Esc leaves the results before it cancels
+        let escape_event = InputEvent::Key(KeyEvent::pressed(KeyCode::Escape, Modifiers::none()));
+        assert_eq!(
+            picker.process_input(escape_event, no_resolver()),
+            FilePickerResult::Continue
+        );
+        assert_eq!(picker.search(), None);
+        assert_eq!(picker.entry_count(), 2);
+        assert_eq!(picker.entries()[0].name, "docs");
+    }
+
+    #[test]
+    fn test_sort_and_filter_by_metadata() {
+        use services_fs_view::{FileSystemOperations, FileSystemViewService};
+        use services_storage::PrincipalId;
+
+        let mut service = FileSystemViewService::new();
+        let mut root = DirectoryView::new(ObjectId::new());
+        for (timestamp, name) in [(30, "a.md"), (10, "b.txt"), (20, "c.md")] {
+            service.set_timestamp(timestamp);
+            service
+                .as_principal(PrincipalId::system())
+                .link(&mut root, name, ObjectId::new(), ObjectKind::Blob)
+                .unwrap();
+        }
+        service.set_timestamp(40);
+        service
+            .as_principal(PrincipalId::system())
+            .mkdir(&mut root, "docs")
+            .unwrap();
+        service
+            .as_principal(PrincipalId::system())
+            .set_attribute(&mut root, "b.txt", "tag", "todo")
+            .unwrap();
+
+        let names = |picker: &FilePicker| -> Vec<String> {
+            picker.entries().iter().map(|e| e.name.clone()).collect()
+        };
+        let mut picker = FilePicker::new(root);
+        let sort_event = InputEvent::Key(KeyEvent::pressed(KeyCode::S, Modifiers::none()));
+        picker.process_input(sort_event, no_resolver());
+        assert_eq!(picker.sort_order(), SortOrder::Modified);
+        // The attribute change made b.txt the most recently modified file
+        assert_eq!(names(&picker), vec!["docs", "b.txt", "a.md", "c.md"]);
+
+        picker.set_sort_order(SortOrder::Created);
+        assert_eq!(names(&picker), vec!["docs", "a.md", "c.md", "b.txt"]);
+
+        picker.set_filter(Some(EntryFilter::ContentType(ContentType::Markdown)));
+        assert_eq!(names(&picker), vec!["docs", "a.md", "c.md"]);
+        picker.set_filter(Some(EntryFilter::Attribute {
+            key: "tag".to_string(),
+            value: Some("todo".to_string()),
+        }));
+        assert_eq!(names(&picker), vec!["docs", "b.txt"]);
+        picker.set_filter(None);
+        assert_eq!(picker.entry_count(), 4);
+    }
+
     #[test]
     fn test_empty_directory() {
         let dir_id = ObjectId::new();
@@ -7,7 +7,7 @@ use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use view_types::{CursorPosition, ViewContent, ViewFrame, ViewId, ViewKind};
 
-use crate::{FilePicker, PickerEntry};
+use crate::{FilePicker, PickerEntry, SortOrder};
 
 impl FilePicker {
     /// Renders the file picker as a text buffer view frame
@@ -19,7 +19,9 @@ impl FilePicker {
     ) -> ViewFrame {
         let mut lines = Vec::new();
 
-        if self.entries.is_empty() {
+        if self.entries.is_empty() && self.search.is_some() {
+            lines.push("(no matches)".to_string());
+        } else if self.entries.is_empty() {
             lines.push("(empty directory)".to_string());
         } else {
             for (index, entry) in self.entries.iter().enumerate() {
@@ -49,7 +51,7 @@ impl FilePicker {
     ) -> ViewFrame {
         let entry_count = self.entry_count();
 
-        let status_text = if entry_count == 0 {
+        let mut status_text = if entry_count == 0 {
             format!("{} — Empty", breadcrumb)
         } else {
             let selected_display = display_index_one_based(self.selected_index);
@@ -58,6 +60,12 @@ impl FilePicker {
                 breadcrumb, selected_display, entry_count
             )
         };
+        if self.sort_order != SortOrder::Name {
+            status_text.push_str(&format!(" — by {}", self.sort_order.label()));
+        }
+        if self.filter.is_some() {
+            status_text.push_str(" — filtered");
+        }
 
         ViewFrame::new(
             view_id,
@@ -86,7 +94,7 @@ fn format_entry(entry: &PickerEntry, is_selected: bool) -> String {
 mod tests {
     use super::*;
     use fs_view::{DirectoryEntry, DirectoryView};
-    use services_storage::{ObjectId, ObjectKind};
+    use services_storage::{ContentType, EntryMetadata, ObjectId, ObjectKind};
 
     fn create_test_directory() -> DirectoryView {
         let dir_id = ObjectId::new();
@@ -113,6 +121,8 @@ mod tests {
             object_id: ObjectId::new(),
             kind: ObjectKind::Map,
             is_directory: true,
+            content_type: ContentType::Directory,
+            metadata: EntryMetadata::default(),
         };
 
         let formatted = format_entry(&entry, false);
@@ -129,6 +139,8 @@ mod tests {
             object_id: ObjectId::new(),
             kind: ObjectKind::Blob,
             is_directory: false,
+            content_type: ContentType::Markdown,
+            metadata: EntryMetadata::default(),
         };
 
         let formatted = format_entry(&entry, false);
@@ -17,9 +17,62 @@
 //! - `mkdir(path)`: Create a new directory
 //! - `link(path, object_cap)`: Create a name -> object link
 //! - `unlink(path)`: Remove a name -> object link
+//! - `rename(from, to)`: Move a link, within or across directories
+//! - `copy(from, to, recursive)`: Link a copy of an entry, optionally its subtree
+//! - `remove_tree(path)`: Remove a link and unregister the directories below it
+//! - `set_attribute(path, key, value)` / `remove_attribute(path, key)`: Edit
+//!   user-defined attributes
+//! - `set_content_type(path, type)`: Override the guessed content type
+//!
+//! Entries carry an `EntryMetadata` stamped with the service's timestamp.
+//! `stat` reports it along with the owner and the content type; use
+//! `stat_with_history` to add size and version count from storage.
+//!
+//! Operations touching several directories stage their updates and commit
+//! them together, so a failure leaves the tree unchanged.
+//!
+//! ## Permissions
+//!
+//! The operations are only reachable through `as_principal(principal)`,
+//! which returns a view that authorizes each one against the service's
+//! `PermissionChecker` before running it:
+//!
+//! - `ls`, `stat` and `open` need `Read` on the object the path names
+//! - `mkdir`, `link` and `unlink` need `Write` on the parent directory
+//! - `rename` needs `Write` on both parent directories
+//! - `copy` needs `Read` on every directory copied and `Write` on the
+//!   destination's parent
+//! - `remove_tree` needs `Write` on the parent and every directory removed
+//! - Metadata changes need `Write` on the entry's object
+//!
+//! Denials surface as `OperationError::AccessDenied` with the reason.
+//!
+//! ## Search
+//!
+//! - `glob(pattern)`: Paths matching a glob such as `docs/**/*.md`
+//! - `grep(files, regex, source)`: Lines matching a regex in the selected files
+//!
+//! Searches take a result limit and a `CancellationToken`. Through a
+//! principal's view they skip directories and files it cannot read.
+//!
+//! ## Snapshots
+//!
+//! - `snapshot(path, name)`: Capture a read-only copy of a subtree
+//! - `diff_snapshot(name)`: Compare a snapshot with the live tree
+//! - `restore_snapshot(name)`: Put the captured directories back
+//! - `clone_snapshot(name, path)` / `clone_tree(src, dest)`: Writable copies
 
 pub mod operations;
+pub mod principal;
+pub mod search;
 pub mod service;
+pub mod snapshot;
+mod transaction;
 
 pub use operations::{FileSystemOperations, OperationError, StatInfo};
+pub use principal::PrincipalView;
+pub use search::{ContentMatch, ContentSource, Glob, PathMatch, SearchOptions, SearchResults};
 pub use service::FileSystemViewService;
+pub use services_storage::{ContentType, EntryMetadata, TreeChange};
+pub use snapshot::FsSnapshot;
+pub use text_pattern::Regex;
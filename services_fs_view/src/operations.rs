@@ -3,7 +3,8 @@
 //! This module defines the operations provided by the filesystem view service.
 
 use fs_view::{DirectoryEntry, DirectoryView, PathError};
-use services_storage::{ObjectId, ObjectKind};
+use services_storage::{AccessDenialReason, ContentType, ObjectId, ObjectKind, PrincipalId};
+use std::collections::BTreeMap;
 use thiserror::Error;
 
 /// Errors that can occur during filesystem operations
@@ -25,9 +26,13 @@ pub enum OperationError {
     #[error("Not a directory: {0}")]
     NotADirectory(String),
 
-    /// Access denied
+    /// Access denied, with the reason reported by the permission checker
     #[error("Access denied: {0}")]
-    AccessDenied(String),
+    AccessDenied(AccessDenialReason),
+
+    /// Glob or search pattern that does not compile
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
 
     /// Invalid operation
     #[error("Invalid operation: {0}")]
@@ -45,6 +50,18 @@ pub struct StatInfo {
     pub size: Option<usize>,
     /// Entry count (for directories)
     pub entry_count: Option<usize>,
+    /// When the entry was created (0 if unknown)
+    pub created_at: u64,
+    /// When the entry or, with history, the object's content last changed
+    pub modified_at: u64,
+    /// Owner registered with the permission checker
+    pub owner: Option<PrincipalId>,
+    /// Number of committed versions (only with history)
+    pub version_count: Option<usize>,
+    /// Explicit or guessed content type
+    pub content_type: ContentType,
+    /// User-defined attributes
+    pub attributes: BTreeMap<String, String>,
 }
 
 /// Filesystem operations trait
@@ -87,6 +104,68 @@ pub trait FileSystemOperations {
     /// Removes the name -> object link at the given path.
     /// Note: This does NOT delete the object itself.
     fn unlink(&mut self, root: &mut DirectoryView, path: &str) -> Result<(), OperationError>;
+
+    /// Rename or move an entry
+    ///
+    /// Moves the link at `from` to `to`, which may be in another directory.
+    /// Both directories are updated together or not at all. A directory
+    /// cannot be moved into its own subtree.
+    fn rename(
+        &mut self,
+        root: &mut DirectoryView,
+        from: &str,
+        to: &str,
+    ) -> Result<(), OperationError>;
+
+    /// Copy an entry
+    ///
+    /// Leaf objects are linked again under the new name, not duplicated.
+    /// Directories get fresh IDs. A non-empty directory is only copied when
+    /// `recursive` is set, and then its whole subtree is copied.
+    /// Returns the object the new entry links to.
+    fn copy(
+        &mut self,
+        root: &mut DirectoryView,
+        from: &str,
+        to: &str,
+        recursive: bool,
+    ) -> Result<ObjectId, OperationError>;
+
+    /// Remove an entry and everything below it
+    ///
+    /// Directories no longer reachable from `root` are unregistered.
+    /// Like `unlink`, this does NOT delete leaf objects.
+    /// Returns the number of entries removed.
+    fn remove_tree(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+    ) -> Result<usize, OperationError>;
+
+    /// Set a user-defined attribute on an entry
+    fn set_attribute(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), OperationError>;
+
+    /// Remove a user-defined attribute, returning its old value
+    fn remove_attribute(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+        key: &str,
+    ) -> Result<Option<String>, OperationError>;
+
+    /// Set an entry's content type, or go back to guessing it from the name
+    fn set_content_type(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+        content_type: Option<ContentType>,
+    ) -> Result<(), OperationError>;
 }
 
 // Helper functions for testing path resolution
@@ -158,6 +237,12 @@ mod tests {
             kind: ObjectKind::Blob,
             size: Some(1024),
             entry_count: None,
+            created_at: 0,
+            modified_at: 0,
+            owner: None,
+            version_count: None,
+            content_type: ContentType::Binary,
+            attributes: BTreeMap::new(),
         };
 
         assert_eq!(stat.id, obj_id);
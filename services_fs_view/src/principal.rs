@@ -0,0 +1,443 @@
+//! Permission-checked operations on behalf of a principal
+//!
+//! A [`PrincipalView`] borrows the service and authorizes each operation
+//! with its [`PermissionChecker`](services_storage::PermissionChecker)
+//! before running it. Directories created and objects linked through the
+//! view are owned by its principal.
+
+use crate::operations::{FileSystemOperations, OperationError, StatInfo};
+use crate::search::{ContentMatch, ContentSource, Glob, PathMatch, SearchOptions, SearchResults};
+use crate::service::FileSystemViewService;
+use fs_view::{DirectoryEntry, DirectoryView};
+use services_storage::{
+    CapabilityKind, ContentType, ObjectId, ObjectKind, Ownership, PrincipalId, VersionHistory,
+};
+use text_pattern::Regex;
+
+/// Filesystem operations made on behalf of one principal
+pub struct PrincipalView<'a> {
+    service: &'a mut FileSystemViewService,
+    principal: PrincipalId,
+}
+
+impl<'a> PrincipalView<'a> {
+    pub(crate) fn new(service: &'a mut FileSystemViewService, principal: PrincipalId) -> Self {
+        Self { service, principal }
+    }
+
+    /// Returns the principal operations are made for
+    pub fn principal(&self) -> PrincipalId {
+        self.principal
+    }
+
+    fn authorize(
+        &self,
+        object_id: ObjectId,
+        required: CapabilityKind,
+    ) -> Result<(), OperationError> {
+        self.service
+            .permissions()
+            .authorize(self.principal, object_id, required)
+            .map_err(OperationError::AccessDenied)
+    }
+
+    /// Authorizes `required` on the object `path` names
+    fn authorize_path(
+        &self,
+        root: &DirectoryView,
+        path: &str,
+        required: CapabilityKind,
+    ) -> Result<(), OperationError> {
+        let object_id = self.service.open(root, path)?;
+        self.authorize(object_id, required)
+    }
+
+    /// Authorizes `Write` on the directory that holds `path`'s last component
+    fn authorize_parent(&self, root: &DirectoryView, path: &str) -> Result<(), OperationError> {
+        let (parent, _) = self.service.resolve_parent(root, path)?;
+        self.authorize(parent.id, CapabilityKind::Write)
+    }
+
+    /// Authorizes `required` on the directory at `path` and every directory below it
+    fn authorize_tree(
+        &self,
+        root: &DirectoryView,
+        path: &str,
+        required: CapabilityKind,
+    ) -> Result<(), OperationError> {
+        let stat = self.service.stat(root, path)?;
+        if stat.kind != ObjectKind::Map {
+            return self.authorize(stat.id, required);
+        }
+        for dir_id in self.service.directory_tree(stat.id) {
+            self.authorize(dir_id, required)?;
+        }
+        Ok(())
+    }
+
+    /// Gets metadata with size and version count from the object's history
+    pub fn stat_with_history(
+        &self,
+        root: &DirectoryView,
+        path: &str,
+        history: &impl VersionHistory,
+    ) -> Result<StatInfo, OperationError> {
+        self.authorize_path(root, path, CapabilityKind::Read)?;
+        self.service.stat_with_history(root, path, history)
+    }
+
+    /// Finds the paths matching `glob` in directories the principal can read
+    pub fn glob(
+        &self,
+        root: &DirectoryView,
+        glob: &Glob,
+        options: &SearchOptions,
+    ) -> SearchResults<PathMatch> {
+        self.service
+            .glob_readable(root, glob, options, &|id| self.can_read(id))
+    }
+
+    /// Searches the files matching `files` that the principal can read
+    pub fn grep(
+        &self,
+        root: &DirectoryView,
+        files: &Glob,
+        regex: &Regex,
+        source: &mut dyn ContentSource,
+        options: &SearchOptions,
+    ) -> SearchResults<ContentMatch> {
+        self.service
+            .grep_readable(root, files, regex, source, options, &|id| self.can_read(id))
+    }
+
+    fn can_read(&self, object_id: ObjectId) -> bool {
+        self.authorize(object_id, CapabilityKind::Read).is_ok()
+    }
+
+    /// Makes the principal the owner of an object nobody owns yet
+    fn claim(&mut self, object_id: ObjectId) {
+        let timestamp = self.service.timestamp();
+        let permissions = self.service.permissions_mut();
+        if permissions.get_ownership(object_id).is_none() {
+            permissions.register_object(object_id, Ownership::new(self.principal, timestamp));
+        }
+    }
+}
+
+impl FileSystemOperations for PrincipalView<'_> {
+    fn ls(&self, root: &DirectoryView, path: &str) -> Result<Vec<DirectoryEntry>, OperationError> {
+        self.authorize_path(root, path, CapabilityKind::Read)?;
+        self.service.ls(root, path)
+    }
+
+    fn stat(&self, root: &DirectoryView, path: &str) -> Result<StatInfo, OperationError> {
+        self.authorize_path(root, path, CapabilityKind::Read)?;
+        self.service.stat(root, path)
+    }
+
+    fn open(&self, root: &DirectoryView, path: &str) -> Result<ObjectId, OperationError> {
+        self.authorize_path(root, path, CapabilityKind::Read)?;
+        self.service.open(root, path)
+    }
+
+    fn mkdir(&mut self, root: &mut DirectoryView, path: &str) -> Result<ObjectId, OperationError> {
+        self.authorize_parent(root, path)?;
+        let dir_id = self.service.mkdir(root, path)?;
+        self.claim(dir_id);
+        Ok(dir_id)
+    }
+
+    fn link(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+        object_id: ObjectId,
+        kind: ObjectKind,
+    ) -> Result<(), OperationError> {
+        self.authorize_parent(root, path)?;
+        self.service.link(root, path, object_id, kind)?;
+        self.claim(object_id);
+        Ok(())
+    }
+
+    fn unlink(&mut self, root: &mut DirectoryView, path: &str) -> Result<(), OperationError> {
+        self.authorize_parent(root, path)?;
+        self.service.unlink(root, path)
+    }
+
+    fn rename(
+        &mut self,
+        root: &mut DirectoryView,
+        from: &str,
+        to: &str,
+    ) -> Result<(), OperationError> {
+        self.authorize_parent(root, from)?;
+        self.authorize_parent(root, to)?;
+        self.service.rename(root, from, to)
+    }
+
+    fn copy(
+        &mut self,
+        root: &mut DirectoryView,
+        from: &str,
+        to: &str,
+        recursive: bool,
+    ) -> Result<ObjectId, OperationError> {
+        self.authorize_tree(root, from, CapabilityKind::Read)?;
+        self.authorize_parent(root, to)?;
+        let kind = self.service.stat(root, from)?.kind;
+        let copied = self.service.copy(root, from, to, recursive)?;
+        if kind == ObjectKind::Map {
+            for dir_id in self.service.directory_tree(copied) {
+                self.claim(dir_id);
+            }
+        }
+        Ok(copied)
+    }
+
+    fn remove_tree(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+    ) -> Result<usize, OperationError> {
+        self.authorize_parent(root, path)?;
+        self.authorize_tree(root, path, CapabilityKind::Write)?;
+        self.service.remove_tree(root, path)
+    }
+
+    fn set_attribute(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), OperationError> {
+        self.authorize_path(root, path, CapabilityKind::Write)?;
+        self.service.set_attribute(root, path, key, value)
+    }
+
+    fn remove_attribute(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+        key: &str,
+    ) -> Result<Option<String>, OperationError> {
+        self.authorize_path(root, path, CapabilityKind::Write)?;
+        self.service.remove_attribute(root, path, key)
+    }
+
+    fn set_content_type(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+        content_type: Option<ContentType>,
+    ) -> Result<(), OperationError> {
+        self.authorize_path(root, path, CapabilityKind::Write)?;
+        self.service.set_content_type(root, path, content_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use services_storage::{AccessDenialReason, Capability};
+
+    fn owned_root(owner: PrincipalId) -> (FileSystemViewService, DirectoryView) {
+        let mut service = FileSystemViewService::new();
+        let root = DirectoryView::new(ObjectId::new());
+        service
+            .permissions_mut()
+            .register_object(root.id, Ownership::new(owner, 0));
+        (service, root)
+    }
+
+    #[test]
+    fn test_owner_can_build_and_read_tree() {
+        let alice = PrincipalId::new();
+        let (mut service, mut root) = owned_root(alice);
+        let mut fs = service.as_principal(alice);
+
+        let docs = fs.mkdir(&mut root, "docs").unwrap();
+        let file = ObjectId::new();
+        fs.link(&mut root, "docs/a.txt", file, ObjectKind::Blob)
+            .unwrap();
+        assert_eq!(fs.open(&root, "docs/a.txt").unwrap(), file);
+        assert_eq!(fs.ls(&root, "docs").unwrap().len(), 1);
+        fs.unlink(&mut root, "docs/a.txt").unwrap();
+
+        assert!(service.permissions().is_owner(docs, alice));
+        assert!(service.permissions().is_owner(file, alice));
+    }
+
+    #[test]
+    fn test_other_principal_is_denied_with_reason() {
+        let alice = PrincipalId::new();
+        let bob = PrincipalId::new();
+        let (mut service, mut root) = owned_root(alice);
+        let docs = service
+            .as_principal(alice)
+            .mkdir(&mut root, "docs")
+            .unwrap();
+
+        let mut fs = service.as_principal(bob);
+        match fs.ls(&root, "docs") {
+            Err(OperationError::AccessDenied(AccessDenialReason::MissingCapability {
+                required,
+                object_id,
+                principal,
+            })) => {
+                assert_eq!(required, CapabilityKind::Read);
+                assert_eq!(object_id, docs);
+                assert_eq!(principal, bob);
+            }
+            other => panic!("expected access denied, got {:?}", other),
+        }
+        assert!(matches!(
+            fs.mkdir(&mut root, "docs/bob"),
+            Err(OperationError::AccessDenied(_))
+        ));
+
+        // Read on docs lets bob list it, but not write into it
+        service
+            .permissions_mut()
+            .grant(Capability::new(docs, CapabilityKind::Read, bob));
+        let mut fs = service.as_principal(bob);
+        assert!(fs.ls(&root, "docs").unwrap().is_empty());
+        assert!(matches!(
+            fs.mkdir(&mut root, "docs/bob"),
+            Err(OperationError::AccessDenied(
+                AccessDenialReason::WrongCapabilityKind { .. }
+            ))
+        ));
+
+        // Unregistered objects are denied to everyone but the system
+        let stray = ObjectId::new();
+        service
+            .link(&mut root, "stray", stray, ObjectKind::Blob)
+            .unwrap();
+        assert!(matches!(
+            service.as_principal(alice).open(&root, "stray"),
+            Err(OperationError::AccessDenied(
+                AccessDenialReason::ObjectNotFound { .. }
+            ))
+        ));
+        assert_eq!(
+            service
+                .as_principal(PrincipalId::system())
+                .open(&root, "stray")
+                .unwrap(),
+            stray
+        );
+    }
+
+    #[test]
+    fn test_tree_operations_check_every_directory() {
+        let alice = PrincipalId::new();
+        let bob = PrincipalId::new();
+        let (mut service, mut root) = owned_root(alice);
+        let mut fs = service.as_principal(alice);
+        let docs = fs.mkdir(&mut root, "docs").unwrap();
+        let private = fs.mkdir(&mut root, "docs/private").unwrap();
+        fs.rename(&mut root, "docs", "papers").unwrap();
+
+        // Bob may write into the root and read docs, but not docs/private
+        let permissions = service.permissions_mut();
+        permissions.grant(Capability::new(root.id, CapabilityKind::Write, bob));
+        permissions.grant(Capability::new(docs, CapabilityKind::Read, bob));
+        let mut fs = service.as_principal(bob);
+        match fs.copy(&mut root, "papers", "mine", true) {
+            Err(OperationError::AccessDenied(AccessDenialReason::MissingCapability {
+                object_id,
+                ..
+            })) => assert_eq!(object_id, private),
+            other => panic!("expected access denied, got {:?}", other),
+        }
+        assert!(matches!(
+            fs.remove_tree(&mut root, "papers"),
+            Err(OperationError::AccessDenied(_))
+        ));
+
+        service
+            .permissions_mut()
+            .grant(Capability::new(private, CapabilityKind::Read, bob));
+        let mut fs = service.as_principal(bob);
+        let copy = fs.copy(&mut root, "papers", "mine", true).unwrap();
+        let copied_private = fs.open(&root, "mine/private").unwrap();
+        assert_eq!(fs.remove_tree(&mut root, "mine").unwrap(), 2);
+        assert!(service.permissions().is_owner(copy, bob));
+        assert!(service.permissions().is_owner(copied_private, bob));
+    }
+
+    #[test]
+    fn test_metadata_changes_need_write() {
+        let alice = PrincipalId::new();
+        let bob = PrincipalId::new();
+        let (mut service, mut root) = owned_root(alice);
+        service.set_timestamp(42);
+        let docs = service
+            .as_principal(alice)
+            .mkdir(&mut root, "docs")
+            .unwrap();
+        service
+            .permissions_mut()
+            .grant(Capability::new(docs, CapabilityKind::Read, bob));
+
+        let mut fs = service.as_principal(bob);
+        let stat = fs.stat(&root, "docs").unwrap();
+        assert_eq!(stat.owner, Some(alice));
+        assert_eq!(stat.created_at, 42);
+        assert!(matches!(
+            fs.set_attribute(&mut root, "docs", "color", "blue"),
+            Err(OperationError::AccessDenied(_))
+        ));
+
+        let mut fs = service.as_principal(alice);
+        fs.set_attribute(&mut root, "docs", "color", "blue")
+            .unwrap();
+        assert_eq!(
+            service
+                .permissions()
+                .get_ownership(docs)
+                .unwrap()
+                .created_at,
+            42
+        );
+    }
+
+    #[test]
+    fn test_search_skips_unreadable_directories() {
+        let alice = PrincipalId::new();
+        let bob = PrincipalId::new();
+        let (mut service, mut root) = owned_root(alice);
+        let mut fs = service.as_principal(alice);
+        let docs = fs.mkdir(&mut root, "docs").unwrap();
+        fs.mkdir(&mut root, "docs/private").unwrap();
+        fs.link(
+            &mut root,
+            "docs/private/key.txt",
+            ObjectId::new(),
+            ObjectKind::Blob,
+        )
+        .unwrap();
+
+        let glob = Glob::new("**/*.txt").unwrap();
+        let options = SearchOptions::new();
+        assert_eq!(
+            service
+                .as_principal(alice)
+                .glob(&root, &glob, &options)
+                .matches
+                .len(),
+            1
+        );
+
+        let permissions = service.permissions_mut();
+        permissions.grant(Capability::new(root.id, CapabilityKind::Read, bob));
+        permissions.grant(Capability::new(docs, CapabilityKind::Read, bob));
+        let results = service
+            .as_principal(bob)
+            .glob(&root, &Glob::new("**").unwrap(), &options);
+        let paths: Vec<_> = results.matches.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(paths, vec!["docs", "docs/private"]);
+    }
+}
@@ -0,0 +1,576 @@
+//! Path globs and content search
+//!
+//! A [`Glob`] matches paths one component at a time: `*`, `?` and `[...]`
+//! match within a single name, and a `**` component matches any number of
+//! directories. Content search runs a [`Regex`] over the lines of every file
+//! a glob selects, reading contents through a [`ContentSource`].
+//!
+//! Walks visit entries in name order, stop at the result limit, and check
+//! the cancellation token before each entry.
+
+use crate::operations::OperationError;
+use crate::service::FileSystemViewService;
+use fs_view::{DirectoryEntry, DirectoryView, PathResolver};
+use lifecycle::CancellationToken;
+use services_storage::{
+    EntryMetadata, JournaledStorage, ObjectId, ObjectKind, TransactionalStorage,
+};
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+use text_pattern::Regex;
+
+#[derive(Debug, Clone)]
+enum GlobComponent {
+    /// `**`: zero or more directories
+    AnyDepth,
+    /// A name pattern
+    Name(Regex),
+}
+
+/// A compiled path glob such as `docs/**/*.md`
+#[derive(Debug, Clone)]
+pub struct Glob {
+    pattern: String,
+    components: Vec<GlobComponent>,
+}
+
+impl Glob {
+    /// Compiles a glob
+    pub fn new(pattern: &str) -> Result<Self, OperationError> {
+        let components = PathResolver::split_path(pattern)?
+            .into_iter()
+            .map(|component| {
+                if component == "**" {
+                    return Ok(GlobComponent::AnyDepth);
+                }
+                Regex::new(&name_pattern(component))
+                    .map(GlobComponent::Name)
+                    .map_err(|e| OperationError::InvalidPattern(format!("{}: {}", pattern, e)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            components,
+        })
+    }
+
+    /// Returns the glob as written
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns whether `path` matches the whole glob
+    pub fn matches(&self, path: &str) -> bool {
+        let Ok(components) = PathResolver::split_path(path) else {
+            return false;
+        };
+        let states = components
+            .iter()
+            .fold(self.start(), |states, name| self.step(&states, name));
+        self.accepts(&states)
+    }
+
+    /// Positions reachable before any component is consumed
+    fn start(&self) -> Vec<usize> {
+        self.closure(vec![0])
+    }
+
+    /// Positions reachable after consuming `name`
+    fn step(&self, states: &[usize], name: &str) -> Vec<usize> {
+        let mut next = Vec::new();
+        for &state in states {
+            let target = match self.components.get(state) {
+                Some(GlobComponent::AnyDepth) => state,
+                Some(GlobComponent::Name(regex)) if regex.is_match(name) => state + 1,
+                _ => continue,
+            };
+            if !next.contains(&target) {
+                next.push(target);
+            }
+        }
+        self.closure(next)
+    }
+
+    /// Adds the positions after each `**`, which may match nothing
+    fn closure(&self, mut states: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
+        while i < states.len() {
+            let state = states[i];
+            if matches!(self.components.get(state), Some(GlobComponent::AnyDepth))
+                && !states.contains(&(state + 1))
+            {
+                states.push(state + 1);
+            }
+            i += 1;
+        }
+        states
+    }
+
+    fn accepts(&self, states: &[usize]) -> bool {
+        states.contains(&self.components.len())
+    }
+}
+
+/// Translates one glob component to an anchored regex
+fn name_pattern(component: &str) -> String {
+    let chars: Vec<char> = component.chars().collect();
+    let mut pattern = String::from("^");
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            '[' => match class_end(&chars, i) {
+                Some(end) => {
+                    pattern.push('[');
+                    let mut body = &chars[i + 1..end];
+                    if body.first() == Some(&'!') {
+                        pattern.push('^');
+                        body = &body[1..];
+                    }
+                    for &c in body {
+                        if c == '\\' || c == '[' {
+                            pattern.push('\\');
+                        }
+                        pattern.push(c);
+                    }
+                    pattern.push(']');
+                    i = end;
+                }
+                None => pattern.push_str("\\["),
+            },
+            c => pattern.push_str(&text_pattern::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+        i += 1;
+    }
+    pattern.push('$');
+    pattern
+}
+
+/// Index of the `]` closing the class opened at `start`
+///
+/// A `]` right after `[` or `[!` is part of the class.
+fn class_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    if chars.get(i) == Some(&'!') {
+        i += 1;
+    }
+    if chars.get(i) == Some(&']') {
+        i += 1;
+    }
+    chars[i.min(chars.len())..]
+        .iter()
+        .position(|&c| c == ']')
+        .map(|offset| i + offset)
+}
+
+/// Limits and cancellation for a search
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// Maximum number of matches to return
+    pub limit: Option<usize>,
+    /// Stops the walk when cancelled
+    pub cancel: CancellationToken,
+}
+
+impl SearchOptions {
+    /// Unlimited, uncancellable search
+    pub fn new() -> Self {
+        Self {
+            limit: None,
+            cancel: CancellationToken::none(),
+        }
+    }
+
+    /// Stops after `limit` matches
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Stops when `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Matches found by a search, and whether it stopped early
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResults<T> {
+    /// Matches in walk order
+    pub matches: Vec<T>,
+    /// More matches exist beyond the limit
+    pub truncated: bool,
+    /// The search was cancelled before the walk finished
+    pub cancelled: bool,
+}
+
+impl<T> SearchResults<T> {
+    fn new() -> Self {
+        Self {
+            matches: Vec::new(),
+            truncated: false,
+            cancelled: false,
+        }
+    }
+
+    /// Returns whether every match was found
+    pub fn is_complete(&self) -> bool {
+        !self.truncated && !self.cancelled
+    }
+
+    /// Adds a match unless the limit is reached
+    fn push(&mut self, found: T, options: &SearchOptions) -> ControlFlow<()> {
+        if options
+            .limit
+            .is_some_and(|limit| self.matches.len() >= limit)
+        {
+            self.truncated = true;
+            return ControlFlow::Break(());
+        }
+        self.matches.push(found);
+        ControlFlow::Continue(())
+    }
+}
+
+/// A path matched by a glob
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathMatch {
+    /// Path relative to the search root
+    pub path: String,
+    /// Object the path links to
+    pub object_id: ObjectId,
+    /// Object kind
+    pub kind: ObjectKind,
+    /// Metadata of the matched entry
+    pub metadata: EntryMetadata,
+}
+
+/// A line matched by a content search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentMatch {
+    /// Path of the file, relative to the search root
+    pub path: String,
+    /// File object
+    pub object_id: ObjectId,
+    /// Line number, starting at 1
+    pub line_number: usize,
+    /// The matching line, without its newline
+    pub line: String,
+    /// Byte offset of the first match in `line`
+    pub start: usize,
+    /// Byte offset just past the first match in `line`
+    pub end: usize,
+}
+
+/// Reads file contents for content search
+pub trait ContentSource {
+    /// Returns an object's latest contents, or `None` if it cannot be read
+    fn read_content(&mut self, object_id: ObjectId) -> Option<Vec<u8>>;
+}
+
+impl ContentSource for JournaledStorage {
+    fn read_content(&mut self, object_id: ObjectId) -> Option<Vec<u8>> {
+        let mut tx = self.begin_transaction().ok()?;
+        let data = self.read_data(&tx, object_id).ok();
+        let _ = self.rollback(&mut tx);
+        data
+    }
+}
+
+impl FileSystemViewService {
+    /// Glob search that only lists directories `readable` allows
+    pub(crate) fn glob_readable(
+        &self,
+        root: &DirectoryView,
+        glob: &Glob,
+        options: &SearchOptions,
+        readable: &dyn Fn(ObjectId) -> bool,
+    ) -> SearchResults<PathMatch> {
+        let mut results = SearchResults::new();
+        let cancelled = self.walk(root, glob, options, readable, &mut |found| {
+            results.push(found, options)
+        });
+        results.cancelled = cancelled;
+        results
+    }
+
+    /// Content search that only lists directories and reads files `readable` allows
+    pub(crate) fn grep_readable(
+        &self,
+        root: &DirectoryView,
+        files: &Glob,
+        regex: &Regex,
+        source: &mut dyn ContentSource,
+        options: &SearchOptions,
+        readable: &dyn Fn(ObjectId) -> bool,
+    ) -> SearchResults<ContentMatch> {
+        let mut results = SearchResults::new();
+        let cancelled = self.walk(root, files, options, readable, &mut |found| {
+            if found.kind == ObjectKind::Map || !readable(found.object_id) {
+                return ControlFlow::Continue(());
+            }
+            let Some(bytes) = source.read_content(found.object_id) else {
+                return ControlFlow::Continue(());
+            };
+            let Ok(text) = std::str::from_utf8(&bytes) else {
+                return ControlFlow::Continue(());
+            };
+            for (index, line) in text.lines().enumerate() {
+                if let Some(m) = regex.find(line) {
+                    let line_match = ContentMatch {
+                        path: found.path.clone(),
+                        object_id: found.object_id,
+                        line_number: index + 1,
+                        line: line.to_string(),
+                        start: m.start,
+                        end: m.end,
+                    };
+                    results.push(line_match, options)?;
+                }
+            }
+            ControlFlow::Continue(())
+        });
+        results.cancelled = cancelled;
+        results
+    }
+
+    /// Visits the paths matching `glob` in name order
+    ///
+    /// Returns whether the walk was cancelled.
+    fn walk(
+        &self,
+        root: &DirectoryView,
+        glob: &Glob,
+        options: &SearchOptions,
+        readable: &dyn Fn(ObjectId) -> bool,
+        visit: &mut dyn FnMut(PathMatch) -> ControlFlow<()>,
+    ) -> bool {
+        if !readable(root.id) {
+            return false;
+        }
+        let mut visited = HashSet::from([root.id]);
+        let mut stack = Vec::new();
+        push_children(&mut stack, glob, "", root, &glob.start());
+
+        while let Some((path, entry, states)) = stack.pop() {
+            if options.cancel.is_cancelled() {
+                return true;
+            }
+            let (entry_id, kind) = (entry.object_id, entry.kind);
+            if glob.accepts(&states) {
+                let found = PathMatch {
+                    path: path.clone(),
+                    object_id: entry_id,
+                    kind,
+                    metadata: entry.metadata,
+                };
+                if visit(found).is_break() {
+                    return false;
+                }
+            }
+            if kind != ObjectKind::Map || !readable(entry_id) || !visited.insert(entry_id) {
+                continue;
+            }
+            if let Some(dir) = self.get_directory(&entry_id) {
+                push_children(&mut stack, glob, &path, dir, &states);
+            }
+        }
+        false
+    }
+}
+
+type WalkItem = (String, DirectoryEntry, Vec<usize>);
+
+/// Pushes the entries of `dir` that can still match, last name first
+fn push_children(
+    stack: &mut Vec<WalkItem>,
+    glob: &Glob,
+    prefix: &str,
+    dir: &DirectoryView,
+    states: &[usize],
+) {
+    let mut entries = dir.list_entries();
+    entries.sort_by(|a, b| b.name.cmp(&a.name));
+    for entry in entries {
+        let next = glob.step(states, &entry.name);
+        if next.is_empty() {
+            continue;
+        }
+        let path = if prefix.is_empty() {
+            entry.name.clone()
+        } else {
+            format!("{}/{}", prefix, entry.name)
+        };
+        stack.push((path, entry.clone(), next));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lifecycle::{CancellationReason, CancellationSource};
+    use services_storage::PrincipalId;
+
+    fn write_file(
+        storage: &mut JournaledStorage,
+        service: &mut FileSystemViewService,
+        root: &mut DirectoryView,
+        path: &str,
+        text: &str,
+    ) {
+        let object_id = ObjectId::new();
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.write(&mut tx, object_id, text.as_bytes()).unwrap();
+        storage.commit(&mut tx).unwrap();
+        service
+            .link(root, path, object_id, ObjectKind::Blob)
+            .unwrap();
+    }
+
+    fn fixture() -> (JournaledStorage, FileSystemViewService, DirectoryView) {
+        let mut storage = JournaledStorage::new();
+        let mut service = FileSystemViewService::new();
+        let mut root = DirectoryView::new(ObjectId::new());
+        service.mkdir(&mut root, "docs").unwrap();
+        service.mkdir(&mut root, "docs/guide").unwrap();
+        let files = [
+            ("README.md", "PandaGen\n"),
+            ("docs/intro.md", "Hello\nTODO: write intro\n"),
+            ("docs/guide/setup.md", "todo later\nrun it\nTODO: more\n"),
+            ("docs/guide/notes.txt", "TODO: not markdown\n"),
+        ];
+        for (path, text) in files {
+            write_file(&mut storage, &mut service, &mut root, path, text);
+        }
+        (storage, service, root)
+    }
+
+    fn paths(results: &SearchResults<PathMatch>) -> Vec<&str> {
+        results.matches.iter().map(|m| m.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_glob_matches_components() {
+        let glob = Glob::new("docs/**/*.md").unwrap();
+        assert!(glob.matches("docs/intro.md"));
+        assert!(glob.matches("docs/a/b/c.md"));
+        assert!(!glob.matches("docs/intro.txt"));
+        assert!(!glob.matches("other/intro.md"));
+
+        assert!(Glob::new("file?.[!a-c]x").unwrap().matches("file1.dx"));
+        assert!(!Glob::new("file?.[!a-c]x").unwrap().matches("file1.ax"));
+        assert!(Glob::new("a+b[").unwrap().matches("a+b["));
+        assert!(Glob::new("**").unwrap().matches("any/depth"));
+        assert!(matches!(
+            Glob::new("docs/../x"),
+            Err(OperationError::PathError(_))
+        ));
+    }
+
+    #[test]
+    fn test_glob_walks_tree_in_name_order() {
+        let (_, mut service, root) = fixture();
+        let options = SearchOptions::new();
+
+        let results = service.as_principal(PrincipalId::system()).glob(
+            &root,
+            &Glob::new("**/*.md").unwrap(),
+            &options,
+        );
+        assert!(results.is_complete());
+        assert_eq!(
+            paths(&results),
+            vec!["README.md", "docs/guide/setup.md", "docs/intro.md"]
+        );
+
+        let results = service.as_principal(PrincipalId::system()).glob(
+            &root,
+            &Glob::new("docs/*").unwrap(),
+            &options,
+        );
+        assert_eq!(paths(&results), vec!["docs/guide", "docs/intro.md"]);
+        assert_eq!(results.matches[0].kind, ObjectKind::Map);
+
+        let limited = SearchOptions::new().with_limit(2);
+        let results = service.as_principal(PrincipalId::system()).glob(
+            &root,
+            &Glob::new("**").unwrap(),
+            &limited,
+        );
+        assert_eq!(results.matches.len(), 2);
+        assert!(results.truncated);
+    }
+
+    #[test]
+    fn test_grep_reports_lines() {
+        let (mut storage, mut service, root) = fixture();
+        let files = Glob::new("**/*.md").unwrap();
+        let regex = Regex::new("^TODO").unwrap();
+
+        let results = service.as_principal(PrincipalId::system()).grep(
+            &root,
+            &files,
+            &regex,
+            &mut storage,
+            &SearchOptions::new(),
+        );
+        let found: Vec<_> = results
+            .matches
+            .iter()
+            .map(|m| (m.path.as_str(), m.line_number))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("docs/guide/setup.md", 3), ("docs/intro.md", 2)]
+        );
+        assert_eq!(results.matches[1].line, "TODO: write intro");
+        assert_eq!((results.matches[1].start, results.matches[1].end), (0, 4));
+
+        let regex = Regex::new("todo").unwrap().ignore_case(true);
+        let limited = SearchOptions::new().with_limit(1);
+        let results = service.as_principal(PrincipalId::system()).grep(
+            &root,
+            &files,
+            &regex,
+            &mut storage,
+            &limited,
+        );
+        assert_eq!(results.matches.len(), 1);
+        assert!(results.truncated);
+    }
+
+    #[test]
+    fn test_cancelled_search_stops() {
+        let (mut storage, mut service, root) = fixture();
+        let source = CancellationSource::new();
+        let options = SearchOptions::new().with_cancellation(source.token());
+        source.cancel(CancellationReason::UserCancel);
+
+        let results = service.as_principal(PrincipalId::system()).glob(
+            &root,
+            &Glob::new("**").unwrap(),
+            &options,
+        );
+        assert!(results.cancelled);
+        assert!(results.matches.is_empty());
+
+        let regex = Regex::new("TODO").unwrap();
+        let results = service.as_principal(PrincipalId::system()).grep(
+            &root,
+            &Glob::new("**").unwrap(),
+            &regex,
+            &mut storage,
+            &options,
+        );
+        assert!(results.cancelled);
+        assert!(!results.is_complete());
+    }
+}
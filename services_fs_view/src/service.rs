@@ -2,19 +2,35 @@
 //!
 //! This module provides the actual service that implements filesystem operations.
 
-use crate::operations::{FileSystemOperations, OperationError, StatInfo};
+use crate::operations::{OperationError, StatInfo};
+use crate::principal::PrincipalView;
+use crate::snapshot::FsSnapshot;
+use crate::transaction::DirectoryTransaction;
 use fs_view::{DirectoryEntry, DirectoryResolver, DirectoryView, PathResolver};
-use services_storage::{ObjectId, ObjectKind};
-use std::collections::HashMap;
+use services_storage::{
+    ContentType, EntryMetadata, ObjectId, ObjectKind, PermissionChecker, PrincipalId, TreeChange,
+    VersionHistory,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// The Filesystem View Service
 ///
 /// Maintains a view of the directory hierarchy and provides operations
 /// to manipulate it.
+///
+/// Filesystem operations are only reachable through
+/// [`FileSystemViewService::as_principal`], which checks each one against
+/// the permission table on behalf of a principal.
 #[derive(Debug, Clone)]
 pub struct FileSystemViewService {
     /// All directories in the system, indexed by ObjectId
     directories: HashMap<ObjectId, DirectoryView>,
+    /// Named snapshots
+    snapshots: BTreeMap<String, FsSnapshot>,
+    /// Ownership and capabilities for directories and linked objects
+    permissions: PermissionChecker,
+    /// Timestamp recorded for subsequent changes
+    timestamp: u64,
 }
 
 impl FileSystemViewService {
@@ -22,9 +38,49 @@ impl FileSystemViewService {
     pub fn new() -> Self {
         Self {
             directories: HashMap::new(),
+            snapshots: BTreeMap::new(),
+            permissions: PermissionChecker::new(),
+            timestamp: 0,
         }
     }
 
+    /// Sets the timestamp recorded for subsequent changes
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        self.timestamp = timestamp;
+    }
+
+    /// Returns the timestamp recorded for subsequent changes
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Returns the permission table
+    pub fn permissions(&self) -> &PermissionChecker {
+        &self.permissions
+    }
+
+    /// Returns the permission table for registering owners and grants
+    pub fn permissions_mut(&mut self) -> &mut PermissionChecker {
+        &mut self.permissions
+    }
+
+    /// Owner the permission checker has on record for an object
+    fn owner_of(&self, object_id: ObjectId) -> Option<PrincipalId> {
+        self.permissions
+            .get_ownership(object_id)
+            .map(|ownership| ownership.owner)
+    }
+
+    /// Returns a view whose operations are checked on behalf of `principal`
+    pub fn as_principal(&mut self, principal: PrincipalId) -> PrincipalView<'_> {
+        PrincipalView::new(self, principal)
+    }
+
+    /// Number of registered directories
+    pub fn directory_count(&self) -> usize {
+        self.directories.len()
+    }
+
     /// Registers a directory with the service
     ///
     /// This allows the service to traverse into this directory.
@@ -32,6 +88,11 @@ impl FileSystemViewService {
         self.directories.insert(dir.id, dir);
     }
 
+    /// Unregisters a directory
+    pub(crate) fn unregister_directory(&mut self, id: &ObjectId) -> Option<DirectoryView> {
+        self.directories.remove(id)
+    }
+
     /// Gets a directory by ID
     pub fn get_directory(&self, id: &ObjectId) -> Option<&DirectoryView> {
         self.directories.get(id)
@@ -43,7 +104,7 @@ impl FileSystemViewService {
     }
 
     /// Resolves a path and returns the final directory and entry name
-    fn resolve_parent<'a>(
+    pub(crate) fn resolve_parent<'a>(
         &'a self,
         root: &'a DirectoryView,
         path: &str,
@@ -80,6 +141,52 @@ impl FileSystemViewService {
         Ok((current_dir, final_component))
     }
 
+    /// Edits the metadata of the entry at `path`, recording the change
+    fn update_metadata<T>(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+        update: impl FnOnce(&mut EntryMetadata) -> T,
+    ) -> Result<T, OperationError> {
+        let (parent_dir, name) = self.resolve_parent(root, path)?;
+        let parent_id = parent_dir.id;
+        let parent = if parent_id == root.id {
+            root
+        } else {
+            self.directories
+                .get_mut(&parent_id)
+                .ok_or_else(|| OperationError::NotFound("parent".to_string()))?
+        };
+        let entry = parent
+            .get_entry_mut(&name)
+            .ok_or(OperationError::NotFound(name))?;
+        let result = update(&mut entry.metadata);
+        entry.metadata.touch(self.timestamp);
+        Ok(result)
+    }
+
+    /// Gets metadata, filling in size, version count and content
+    /// modification time from the object's history
+    pub(crate) fn stat_with_history(
+        &self,
+        root: &DirectoryView,
+        path: &str,
+        history: &impl VersionHistory,
+    ) -> Result<StatInfo, OperationError> {
+        let mut stat = self.stat(root, path)?;
+        if stat.kind == ObjectKind::Map {
+            return Ok(stat);
+        }
+        // Objects never written have no history
+        let versions = history.versions(stat.id).unwrap_or_default();
+        if let Some(latest) = versions.last() {
+            stat.size = Some(latest.size_bytes as usize);
+            stat.modified_at = stat.modified_at.max(latest.committed_at);
+        }
+        stat.version_count = Some(versions.len());
+        Ok(stat)
+    }
+
     /// Resolves a full path to a directory entry
     fn resolve_path<'a>(
         &'a self,
@@ -91,6 +198,277 @@ impl FileSystemViewService {
             .get_entry(&name)
             .ok_or(OperationError::NotFound(name))
     }
+
+    /// Resolves a path to a directory view
+    fn resolve_dir<'a>(
+        &'a self,
+        root: &'a DirectoryView,
+        path: &str,
+    ) -> Result<&'a DirectoryView, OperationError> {
+        if path.trim_matches('/').is_empty() {
+            return Ok(root);
+        }
+        let entry = self.resolve_path(root, path)?;
+        if entry.kind != ObjectKind::Map {
+            return Err(OperationError::NotADirectory(path.to_string()));
+        }
+        self.directories
+            .get(&entry.object_id)
+            .ok_or_else(|| OperationError::NotFound(path.to_string()))
+    }
+
+    /// Registered directories reachable from `dir`, excluding `dir` itself
+    fn subtree(&self, dir: &DirectoryView) -> HashMap<ObjectId, DirectoryView> {
+        let mut found = HashMap::new();
+        let mut stack = vec![dir];
+        while let Some(current) = stack.pop() {
+            for entry in current.list_entries() {
+                if entry.kind != ObjectKind::Map
+                    || entry.object_id == dir.id
+                    || found.contains_key(&entry.object_id)
+                {
+                    continue;
+                }
+                if let Some(child) = self.directories.get(&entry.object_id) {
+                    found.insert(child.id, child.clone());
+                    stack.push(child);
+                }
+            }
+        }
+        found
+    }
+
+    /// IDs of `dir_id` and the registered directories below it
+    pub(crate) fn directory_tree(&self, dir_id: ObjectId) -> Vec<ObjectId> {
+        let mut ids = vec![dir_id];
+        if let Some(dir) = self.directories.get(&dir_id) {
+            ids.extend(self.subtree(dir).into_keys());
+        }
+        ids
+    }
+
+    /// Maps every path under `dir` to its target
+    fn tree_paths(
+        dir: &DirectoryView,
+        directories: &HashMap<ObjectId, DirectoryView>,
+    ) -> BTreeMap<String, (ObjectId, ObjectKind)> {
+        let mut paths = BTreeMap::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![(String::new(), dir)];
+        while let Some((prefix, current)) = stack.pop() {
+            if !visited.insert(current.id) {
+                continue;
+            }
+            for entry in current.list_entries() {
+                let path = if prefix.is_empty() {
+                    entry.name.clone()
+                } else {
+                    format!("{}/{}", prefix, entry.name)
+                };
+                if entry.kind == ObjectKind::Map {
+                    if let Some(child) = directories.get(&entry.object_id) {
+                        stack.push((path.clone(), child));
+                    }
+                }
+                paths.insert(path, (entry.object_id, entry.kind));
+            }
+        }
+        paths
+    }
+
+    /// Captures the subtree at `path` as a named, read-only snapshot
+    pub fn snapshot(
+        &mut self,
+        root: &DirectoryView,
+        path: &str,
+        name: &str,
+    ) -> Result<&FsSnapshot, OperationError> {
+        if self.snapshots.contains_key(name) {
+            return Err(OperationError::AlreadyExists(name.to_string()));
+        }
+        let snapshot = self.capture(root, path, name)?;
+        Ok(self.snapshots.entry(name.to_string()).or_insert(snapshot))
+    }
+
+    fn capture(
+        &self,
+        root: &DirectoryView,
+        path: &str,
+        name: &str,
+    ) -> Result<FsSnapshot, OperationError> {
+        let dir = self.resolve_dir(root, path)?;
+        Ok(FsSnapshot {
+            name: name.to_string(),
+            root: dir.clone(),
+            directories: FileSystemViewService {
+                directories: self.subtree(dir),
+                snapshots: BTreeMap::new(),
+                permissions: PermissionChecker::new(),
+                timestamp: self.timestamp,
+            },
+        })
+    }
+
+    /// Gets a snapshot by name
+    pub fn get_snapshot(&self, name: &str) -> Option<&FsSnapshot> {
+        self.snapshots.get(name)
+    }
+
+    /// Names of all snapshots, sorted
+    pub fn snapshot_names(&self) -> Vec<&str> {
+        self.snapshots.keys().map(String::as_str).collect()
+    }
+
+    /// Deletes a snapshot
+    pub fn delete_snapshot(&mut self, name: &str) -> Result<FsSnapshot, OperationError> {
+        self.snapshots
+            .remove(name)
+            .ok_or_else(|| OperationError::NotFound(name.to_string()))
+    }
+
+    /// Compares a snapshot with the live subtree it was taken of
+    ///
+    /// Directories are reported only when added, removed, or relinked;
+    /// changes inside them are reported by path.
+    pub fn diff_snapshot(
+        &self,
+        root: &DirectoryView,
+        name: &str,
+    ) -> Result<Vec<TreeChange>, OperationError> {
+        let snapshot = self
+            .snapshots
+            .get(name)
+            .ok_or_else(|| OperationError::NotFound(name.to_string()))?;
+        let live = if snapshot.root.id == root.id {
+            root
+        } else {
+            self.directories
+                .get(&snapshot.root.id)
+                .ok_or_else(|| OperationError::NotFound(name.to_string()))?
+        };
+
+        let then = Self::tree_paths(&snapshot.root, &snapshot.directories.directories);
+        let now = Self::tree_paths(live, &self.directories);
+        let mut changes = Vec::new();
+        for (path, target) in &then {
+            match now.get(path) {
+                None => changes.push(TreeChange::Removed(path.clone())),
+                Some(now_target) if now_target != target => {
+                    changes.push(TreeChange::Modified(path.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for path in now.keys().filter(|path| !then.contains_key(*path)) {
+            changes.push(TreeChange::Added(path.clone()));
+        }
+        changes.sort();
+        Ok(changes)
+    }
+
+    /// Puts a snapshot's directories back, returning the changes undone
+    ///
+    /// Directories created since the snapshot and no longer reachable from
+    /// `root` are unregistered.
+    pub fn restore_snapshot(
+        &mut self,
+        root: &mut DirectoryView,
+        name: &str,
+    ) -> Result<Vec<TreeChange>, OperationError> {
+        let changes = self.diff_snapshot(root, name)?;
+        let snapshot = self.snapshots[name].clone();
+        let before = if snapshot.root.id == root.id {
+            self.subtree(root)
+        } else {
+            self.subtree(&self.directories[&snapshot.root.id])
+        };
+
+        for (id, dir) in snapshot.directories.directories {
+            self.directories.insert(id, dir);
+        }
+        if snapshot.root.id == root.id {
+            *root = snapshot.root;
+        } else {
+            self.directories.insert(snapshot.root.id, snapshot.root);
+        }
+
+        let reachable = self.subtree(root);
+        for id in before.keys() {
+            if !reachable.contains_key(id) {
+                self.directories.remove(id);
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Links a writable clone of a snapshot at `dest_path`
+    ///
+    /// Directories get fresh IDs; leaf objects are shared until the clone
+    /// links new versions over them. Returns the clone's root directory.
+    pub fn clone_snapshot(
+        &mut self,
+        root: &mut DirectoryView,
+        name: &str,
+        dest_path: &str,
+    ) -> Result<ObjectId, OperationError> {
+        let snapshot = self
+            .snapshots
+            .get(name)
+            .cloned()
+            .ok_or_else(|| OperationError::NotFound(name.to_string()))?;
+        self.clone_captured(root, snapshot, dest_path)
+    }
+
+    /// Links a writable clone of the live subtree at `src_path` at `dest_path`
+    pub fn clone_tree(
+        &mut self,
+        root: &mut DirectoryView,
+        src_path: &str,
+        dest_path: &str,
+    ) -> Result<ObjectId, OperationError> {
+        let snapshot = self.capture(root, src_path, "")?;
+        self.clone_captured(root, snapshot, dest_path)
+    }
+
+    fn clone_captured(
+        &mut self,
+        root: &mut DirectoryView,
+        snapshot: FsSnapshot,
+        dest_path: &str,
+    ) -> Result<ObjectId, OperationError> {
+        let (parent_dir, dest_name) = self.resolve_parent(root, dest_path)?;
+        if parent_dir.get_entry(&dest_name).is_some() {
+            return Err(OperationError::AlreadyExists(dest_name));
+        }
+        let parent_id = parent_dir.id;
+
+        let mut tx = DirectoryTransaction::new();
+        let mut dirs = snapshot.directories.directories;
+        dirs.insert(snapshot.root.id, snapshot.root.clone());
+        let renamed: HashMap<ObjectId, ObjectId> =
+            dirs.keys().map(|id| (*id, ObjectId::new())).collect();
+
+        for (id, dir) in dirs {
+            let mut clone = DirectoryView::new(renamed[&id]);
+            for entry in dir.list_entries() {
+                let mut entry = entry.clone();
+                if entry.kind == ObjectKind::Map {
+                    if let Some(new_id) = renamed.get(&entry.object_id) {
+                        entry.object_id = *new_id;
+                    }
+                }
+                clone.add_entry(entry);
+            }
+            tx.insert(clone);
+        }
+
+        let clone_id = renamed[&snapshot.root.id];
+        let entry = DirectoryEntry::new(dest_name, clone_id, ObjectKind::Map)
+            .with_metadata(EntryMetadata::new(self.timestamp));
+        tx.directory_mut(self, root, parent_id)?.add_entry(entry);
+        tx.commit(self, root);
+        Ok(clone_id)
+    }
 }
 
 impl Default for FileSystemViewService {
@@ -99,8 +477,13 @@ impl Default for FileSystemViewService {
     }
 }
 
-impl FileSystemOperations for FileSystemViewService {
-    fn ls(&self, root: &DirectoryView, path: &str) -> Result<Vec<DirectoryEntry>, OperationError> {
+/// Unchecked operations behind [`PrincipalView`], which authorizes each one
+impl FileSystemViewService {
+    pub(crate) fn ls(
+        &self,
+        root: &DirectoryView,
+        path: &str,
+    ) -> Result<Vec<DirectoryEntry>, OperationError> {
         // Special case: if path is empty or just "/", list root
         let trimmed_path = path.trim_matches('/');
         if trimmed_path.is_empty() {
@@ -121,7 +504,11 @@ impl FileSystemOperations for FileSystemViewService {
         Ok(dir.list_entries().into_iter().cloned().collect())
     }
 
-    fn stat(&self, root: &DirectoryView, path: &str) -> Result<StatInfo, OperationError> {
+    pub(crate) fn stat(
+        &self,
+        root: &DirectoryView,
+        path: &str,
+    ) -> Result<StatInfo, OperationError> {
         // Special case: if path is empty or just "/", return root stat
         let trimmed_path = path.trim_matches('/');
         if trimmed_path.is_empty() {
@@ -130,18 +517,24 @@ impl FileSystemOperations for FileSystemViewService {
                 kind: ObjectKind::Map,
                 size: None,
                 entry_count: Some(root.count()),
+                created_at: 0,
+                modified_at: 0,
+                owner: self.owner_of(root.id),
+                version_count: None,
+                content_type: ContentType::Directory,
+                attributes: BTreeMap::new(),
             });
         }
 
         let entry = self.resolve_path(root, path)?;
+        let metadata = &entry.metadata;
 
         let (size, entry_count) = if entry.kind == ObjectKind::Map {
             let dir = self.directories.get(&entry.object_id);
             (None, dir.map(|d| d.count()))
         } else {
-            // Note: Size is None for non-directory objects since we don't store
-            // actual blob data in this view service. A real implementation would
-            // query the storage service for actual object sizes.
+            // The view holds no object data; `stat_with_history` asks storage
+            // for sizes.
             (None, None)
         };
 
@@ -150,10 +543,20 @@ impl FileSystemOperations for FileSystemViewService {
             kind: entry.kind,
             size,
             entry_count,
+            created_at: metadata.created_at,
+            modified_at: metadata.modified_at,
+            owner: self.owner_of(entry.object_id),
+            version_count: None,
+            content_type: metadata.content_type_for(&entry.name, entry.kind),
+            attributes: metadata.attributes.clone(),
         })
     }
 
-    fn open(&self, root: &DirectoryView, path: &str) -> Result<ObjectId, OperationError> {
+    pub(crate) fn open(
+        &self,
+        root: &DirectoryView,
+        path: &str,
+    ) -> Result<ObjectId, OperationError> {
         // Special case: if path is empty or just "/", return root ID
         let trimmed_path = path.trim_matches('/');
         if trimmed_path.is_empty() {
@@ -164,7 +567,11 @@ impl FileSystemOperations for FileSystemViewService {
         Ok(entry.object_id)
     }
 
-    fn mkdir(&mut self, root: &mut DirectoryView, path: &str) -> Result<ObjectId, OperationError> {
+    pub(crate) fn mkdir(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+    ) -> Result<ObjectId, OperationError> {
         let (parent_dir, name) = self.resolve_parent(root, path)?;
 
         // Check if already exists
@@ -178,7 +585,8 @@ impl FileSystemOperations for FileSystemViewService {
 
         // Add entry to parent
         let parent_id = parent_dir.id;
-        let entry = DirectoryEntry::new(name.clone(), new_dir_id, ObjectKind::Map);
+        let entry = DirectoryEntry::new(name.clone(), new_dir_id, ObjectKind::Map)
+            .with_metadata(EntryMetadata::new(self.timestamp));
 
         // We need to get mutable parent from the service's directories
         // But we also need to handle the case where parent is root
@@ -198,7 +606,7 @@ impl FileSystemOperations for FileSystemViewService {
         Ok(new_dir_id)
     }
 
-    fn link(
+    pub(crate) fn link(
         &mut self,
         root: &mut DirectoryView,
         path: &str,
@@ -213,7 +621,8 @@ impl FileSystemOperations for FileSystemViewService {
         }
 
         // Create entry
-        let entry = DirectoryEntry::new(name.clone(), object_id, kind);
+        let entry = DirectoryEntry::new(name.clone(), object_id, kind)
+            .with_metadata(EntryMetadata::new(self.timestamp));
         let parent_id = parent_dir.id;
 
         // Add to parent
@@ -230,7 +639,11 @@ impl FileSystemOperations for FileSystemViewService {
         Ok(())
     }
 
-    fn unlink(&mut self, root: &mut DirectoryView, path: &str) -> Result<(), OperationError> {
+    pub(crate) fn unlink(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+    ) -> Result<(), OperationError> {
         let (parent_dir, name) = self.resolve_parent(root, path)?;
 
         // Check if exists
@@ -253,6 +666,145 @@ impl FileSystemOperations for FileSystemViewService {
 
         Ok(())
     }
+
+    pub(crate) fn rename(
+        &mut self,
+        root: &mut DirectoryView,
+        from: &str,
+        to: &str,
+    ) -> Result<(), OperationError> {
+        let (src_parent, name) = self.resolve_parent(root, from)?;
+        let entry = src_parent
+            .get_entry(&name)
+            .cloned()
+            .ok_or(OperationError::NotFound(name.clone()))?;
+        let src_parent_id = src_parent.id;
+
+        let (dest_parent, dest_name) = self.resolve_parent(root, to)?;
+        let dest_parent_id = dest_parent.id;
+        if src_parent_id == dest_parent_id && name == dest_name {
+            return Ok(());
+        }
+        if dest_parent.get_entry(&dest_name).is_some() {
+            return Err(OperationError::AlreadyExists(dest_name));
+        }
+        if entry.kind == ObjectKind::Map
+            && self
+                .directory_tree(entry.object_id)
+                .contains(&dest_parent_id)
+        {
+            return Err(OperationError::InvalidOperation(format!(
+                "Cannot move {} into itself",
+                from
+            )));
+        }
+
+        let mut tx = DirectoryTransaction::new();
+        tx.directory_mut(self, root, src_parent_id)?
+            .remove_entry(&name);
+        let moved = DirectoryEntry {
+            name: dest_name,
+            ..entry
+        };
+        tx.directory_mut(self, root, dest_parent_id)?
+            .add_entry(moved);
+        tx.commit(self, root);
+        Ok(())
+    }
+
+    pub(crate) fn copy(
+        &mut self,
+        root: &mut DirectoryView,
+        from: &str,
+        to: &str,
+        recursive: bool,
+    ) -> Result<ObjectId, OperationError> {
+        let entry = self.resolve_path(root, from)?.clone();
+        if entry.kind != ObjectKind::Map {
+            self.link(root, to, entry.object_id, entry.kind)?;
+            // The copy is a new entry, but keeps the type and attributes
+            self.update_metadata(root, to, |metadata| {
+                metadata.content_type = entry.metadata.content_type;
+                metadata.attributes = entry.metadata.attributes;
+            })?;
+            return Ok(entry.object_id);
+        }
+
+        let snapshot = self.capture(root, from, "")?;
+        if !recursive && !snapshot.root.list_entries().is_empty() {
+            return Err(OperationError::InvalidOperation(format!(
+                "{} is not empty; copy it recursively",
+                from
+            )));
+        }
+        self.clone_captured(root, snapshot, to)
+    }
+
+    pub(crate) fn remove_tree(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+    ) -> Result<usize, OperationError> {
+        let (parent_dir, name) = self.resolve_parent(root, path)?;
+        let entry = parent_dir
+            .get_entry(&name)
+            .cloned()
+            .ok_or(OperationError::NotFound(name.clone()))?;
+        let parent_id = parent_dir.id;
+
+        let mut tx = DirectoryTransaction::new();
+        tx.directory_mut(self, root, parent_id)?.remove_entry(&name);
+
+        let mut removed = 1;
+        if let Some(dir) = self.directories.get(&entry.object_id) {
+            if entry.kind == ObjectKind::Map {
+                removed += Self::tree_paths(dir, &self.directories).len();
+                // Directories also linked from elsewhere stay registered
+                let reachable = tx.reachable(self, root);
+                for id in self.directory_tree(dir.id) {
+                    if !reachable.contains(&id) {
+                        tx.remove(id);
+                    }
+                }
+            }
+        }
+        tx.commit(self, root);
+        Ok(removed)
+    }
+
+    pub(crate) fn set_attribute(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), OperationError> {
+        self.update_metadata(root, path, |metadata| {
+            metadata
+                .attributes
+                .insert(key.to_string(), value.to_string());
+        })
+    }
+
+    pub(crate) fn remove_attribute(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+        key: &str,
+    ) -> Result<Option<String>, OperationError> {
+        self.update_metadata(root, path, |metadata| metadata.attributes.remove(key))
+    }
+
+    pub(crate) fn set_content_type(
+        &mut self,
+        root: &mut DirectoryView,
+        path: &str,
+        content_type: Option<ContentType>,
+    ) -> Result<(), OperationError> {
+        self.update_metadata(root, path, |metadata| {
+            metadata.content_type = content_type;
+        })
+    }
 }
 
 // Implement DirectoryResolver for FileSystemViewService to enable file picker integration
@@ -389,4 +941,248 @@ mod tests {
         assert!(result.is_err());
         assert!(matches!(result, Err(OperationError::NotFound(_))));
     }
+
+    fn snapshot_fixture() -> (FileSystemViewService, DirectoryView, ObjectId) {
+        let mut service = FileSystemViewService::new();
+        let mut root = DirectoryView::new(ObjectId::new());
+        service.mkdir(&mut root, "docs").unwrap();
+        let file_id = ObjectId::new();
+        service
+            .link(&mut root, "docs/a.txt", file_id, ObjectKind::Blob)
+            .unwrap();
+        (service, root, file_id)
+    }
+
+    #[test]
+    fn test_snapshot_diff_and_restore() {
+        let (mut service, mut root, file_id) = snapshot_fixture();
+        service.snapshot(&root, "", "before").unwrap();
+        assert!(matches!(
+            service.snapshot(&root, "", "before"),
+            Err(OperationError::AlreadyExists(_))
+        ));
+
+        service.unlink(&mut root, "docs/a.txt").unwrap();
+        service
+            .link(&mut root, "docs/a.txt", ObjectId::new(), ObjectKind::Blob)
+            .unwrap();
+        service.mkdir(&mut root, "tmp").unwrap();
+
+        let snapshot = service.get_snapshot("before").unwrap();
+        assert_eq!(snapshot.open("docs/a.txt").unwrap(), file_id);
+        assert!(snapshot.open("tmp").is_err());
+        assert_eq!(
+            service.diff_snapshot(&root, "before").unwrap(),
+            vec![
+                TreeChange::Added("tmp".to_string()),
+                TreeChange::Modified("docs/a.txt".to_string()),
+            ]
+        );
+
+        let undone = service.restore_snapshot(&mut root, "before").unwrap();
+        assert_eq!(undone.len(), 2);
+        assert_eq!(service.open(&root, "docs/a.txt").unwrap(), file_id);
+        assert!(service.open(&root, "tmp").is_err());
+        assert_eq!(service.directory_count(), 1);
+        assert!(service.diff_snapshot(&root, "before").unwrap().is_empty());
+
+        assert_eq!(service.snapshot_names(), vec!["before"]);
+        service.delete_snapshot("before").unwrap();
+        assert!(service.delete_snapshot("before").is_err());
+    }
+
+    #[test]
+    fn test_clone_is_independent() {
+        let (mut service, mut root, file_id) = snapshot_fixture();
+        service.snapshot(&root, "docs", "docs-v1").unwrap();
+
+        let clone_id = service
+            .clone_snapshot(&mut root, "docs-v1", "copy")
+            .unwrap();
+        assert_ne!(clone_id, service.open(&root, "docs").unwrap());
+        assert_eq!(service.open(&root, "copy/a.txt").unwrap(), file_id);
+
+        service.unlink(&mut root, "copy/a.txt").unwrap();
+        assert_eq!(service.open(&root, "docs/a.txt").unwrap(), file_id);
+
+        service.mkdir(&mut root, "docs/sub").unwrap();
+        let tree_id = service.clone_tree(&mut root, "docs", "copy2").unwrap();
+        let sub_id = service.open(&root, "copy2/sub").unwrap();
+        assert_ne!(sub_id, service.open(&root, "docs/sub").unwrap());
+        assert!(service.get_directory(&sub_id).is_some());
+        assert!(service.get_directory(&tree_id).is_some());
+        assert!(matches!(
+            service.clone_tree(&mut root, "docs", "copy2"),
+            Err(OperationError::AlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn test_rename_moves_across_directories() {
+        let (mut service, mut root, file_id) = snapshot_fixture();
+        service.mkdir(&mut root, "archive").unwrap();
+
+        service
+            .rename(&mut root, "docs/a.txt", "archive/b.txt")
+            .unwrap();
+        assert!(service.open(&root, "docs/a.txt").is_err());
+        assert_eq!(service.open(&root, "archive/b.txt").unwrap(), file_id);
+
+        let docs_id = service.open(&root, "docs").unwrap();
+        service.rename(&mut root, "docs", "archive/docs").unwrap();
+        assert_eq!(service.open(&root, "archive/docs").unwrap(), docs_id);
+        assert!(root.get_entry("docs").is_none());
+
+        // Failed moves change nothing
+        assert!(matches!(
+            service.rename(&mut root, "archive", "archive/docs/inner"),
+            Err(OperationError::InvalidOperation(_))
+        ));
+        service.mkdir(&mut root, "other").unwrap();
+        assert!(matches!(
+            service.rename(&mut root, "other", "archive/docs"),
+            Err(OperationError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            service.rename(&mut root, "missing", "elsewhere"),
+            Err(OperationError::NotFound(_))
+        ));
+        assert!(root.get_entry("other").is_some());
+        assert_eq!(service.ls(&root, "archive").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_copy_shallow_and_recursive() {
+        let (mut service, mut root, file_id) = snapshot_fixture();
+
+        assert_eq!(
+            service
+                .copy(&mut root, "docs/a.txt", "a-copy.txt", false)
+                .unwrap(),
+            file_id
+        );
+        assert!(matches!(
+            service.copy(&mut root, "docs", "docs2", false),
+            Err(OperationError::InvalidOperation(_))
+        ));
+        assert!(root.get_entry("docs2").is_none());
+
+        service.mkdir(&mut root, "empty").unwrap();
+        let empty_copy = service.copy(&mut root, "empty", "empty2", false).unwrap();
+        assert_ne!(empty_copy, service.open(&root, "empty").unwrap());
+
+        service.mkdir(&mut root, "docs/sub").unwrap();
+        let docs_copy = service.copy(&mut root, "docs", "docs2", true).unwrap();
+        assert!(service.get_directory(&docs_copy).is_some());
+        assert_eq!(service.open(&root, "docs2/a.txt").unwrap(), file_id);
+        service.unlink(&mut root, "docs2/sub").unwrap();
+        assert!(service.open(&root, "docs/sub").is_ok());
+    }
+
+    #[test]
+    fn test_remove_tree_unregisters_directories() {
+        let (mut service, mut root, _) = snapshot_fixture();
+        service.mkdir(&mut root, "docs/sub").unwrap();
+        service
+            .link(
+                &mut root,
+                "docs/sub/b.txt",
+                ObjectId::new(),
+                ObjectKind::Blob,
+            )
+            .unwrap();
+        let kept = service.mkdir(&mut root, "docs/kept").unwrap();
+        service
+            .link(&mut root, "kept", kept, ObjectKind::Map)
+            .unwrap();
+        assert_eq!(service.directory_count(), 3);
+
+        // docs, a.txt, sub, sub/b.txt and kept
+        assert_eq!(service.remove_tree(&mut root, "docs").unwrap(), 5);
+        assert!(root.get_entry("docs").is_none());
+        assert_eq!(service.directory_count(), 1);
+        assert!(service.get_directory(&kept).is_some());
+
+        assert_eq!(service.remove_tree(&mut root, "kept").unwrap(), 1);
+        assert_eq!(service.directory_count(), 0);
+        assert!(matches!(
+            service.remove_tree(&mut root, "docs"),
+            Err(OperationError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_stat_reports_metadata() {
+        use services_storage::{JournaledStorage, TransactionalStorage};
+
+        let mut service = FileSystemViewService::new();
+        let mut root = DirectoryView::new(ObjectId::new());
+        let mut storage = JournaledStorage::new();
+        let file_id = ObjectId::new();
+        for (timestamp, text) in [(100, "one"), (300, "three")] {
+            storage.set_commit_timestamp(timestamp);
+            let mut tx = storage.begin_transaction().unwrap();
+            storage.write(&mut tx, file_id, text.as_bytes()).unwrap();
+            storage.commit(&mut tx).unwrap();
+        }
+
+        service.set_timestamp(200);
+        service.mkdir(&mut root, "docs").unwrap();
+        service
+            .link(&mut root, "docs/notes", file_id, ObjectKind::Blob)
+            .unwrap();
+        service.set_timestamp(250);
+        service
+            .set_attribute(&mut root, "docs/notes", "tag", "draft")
+            .unwrap();
+
+        let stat = service.stat(&root, "docs/notes").unwrap();
+        assert_eq!((stat.created_at, stat.modified_at), (200, 250));
+        assert_eq!(stat.content_type, ContentType::Text);
+        assert_eq!(
+            stat.attributes.get("tag").map(String::as_str),
+            Some("draft")
+        );
+        assert_eq!(stat.version_count, None);
+        assert_eq!(
+            service.stat(&root, "docs").unwrap().content_type,
+            ContentType::Directory
+        );
+
+        let stat = service
+            .stat_with_history(&root, "docs/notes", &storage)
+            .unwrap();
+        assert_eq!(stat.version_count, Some(2));
+        assert_eq!(stat.size, Some(5));
+        assert_eq!(stat.modified_at, 300);
+
+        // Renames keep metadata; copies keep type and attributes only
+        service
+            .set_content_type(&mut root, "docs/notes", Some(ContentType::Markdown))
+            .unwrap();
+        service
+            .rename(&mut root, "docs/notes", "docs/notes.txt")
+            .unwrap();
+        service.set_timestamp(400);
+        service
+            .copy(&mut root, "docs/notes.txt", "copy", false)
+            .unwrap();
+        let copy = service.stat(&root, "copy").unwrap();
+        assert_eq!(
+            (copy.created_at, copy.content_type),
+            (400, ContentType::Markdown)
+        );
+        assert_eq!(copy.attributes.len(), 1);
+        assert_eq!(
+            service
+                .remove_attribute(&mut root, "copy", "tag")
+                .unwrap()
+                .as_deref(),
+            Some("draft")
+        );
+        assert!(matches!(
+            service.set_attribute(&mut root, "missing", "k", "v"),
+            Err(OperationError::NotFound(_))
+        ));
+    }
 }
@@ -0,0 +1,49 @@
+//! Snapshots of directory views
+//!
+//! A snapshot copies the directory views of a subtree. Leaf objects are
+//! immutable and shared by ID, so a snapshot only has to remember which
+//! names pointed where. Snapshots are read-only; clone one to get a
+//! writable tree.
+
+use crate::operations::{OperationError, StatInfo};
+use crate::service::FileSystemViewService;
+use fs_view::{DirectoryEntry, DirectoryView};
+use services_storage::ObjectId;
+
+/// A named, read-only capture of a directory subtree
+#[derive(Debug, Clone)]
+pub struct FsSnapshot {
+    /// Snapshot name
+    pub name: String,
+    /// Directory the snapshot was taken of, as it was
+    pub root: DirectoryView,
+    /// Captured directories below the root
+    pub(crate) directories: FileSystemViewService,
+}
+
+impl FsSnapshot {
+    /// ID of the directory the snapshot was taken of
+    pub fn root_id(&self) -> ObjectId {
+        self.root.id
+    }
+
+    /// Number of directories captured, root included
+    pub fn directory_count(&self) -> usize {
+        self.directories.directory_count() + 1
+    }
+
+    /// List a directory as it was (path relative to the snapshot root)
+    pub fn ls(&self, path: &str) -> Result<Vec<DirectoryEntry>, OperationError> {
+        self.directories.ls(&self.root, path)
+    }
+
+    /// Get metadata as it was
+    pub fn stat(&self, path: &str) -> Result<StatInfo, OperationError> {
+        self.directories.stat(&self.root, path)
+    }
+
+    /// Resolve a path as it was
+    pub fn open(&self, path: &str) -> Result<ObjectId, OperationError> {
+        self.directories.open(&self.root, path)
+    }
+}
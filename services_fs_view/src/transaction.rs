@@ -0,0 +1,104 @@
+//! Staged directory updates
+//!
+//! Operations that touch more than one directory stage their changes in a
+//! [`DirectoryTransaction`] and commit them together. Until then the live
+//! tree is untouched, so an operation that fails halfway leaves nothing
+//! behind.
+
+use crate::operations::OperationError;
+use crate::service::FileSystemViewService;
+use fs_view::DirectoryView;
+use services_storage::{ObjectId, ObjectKind};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+/// Directory updates waiting to be committed
+#[derive(Debug, Default)]
+pub(crate) struct DirectoryTransaction {
+    /// Modified or new directories, including the root when it changes
+    staged: HashMap<ObjectId, DirectoryView>,
+    /// Directories to unregister
+    removed: HashSet<ObjectId>,
+}
+
+impl DirectoryTransaction {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the staged copy of a directory, staging it on first use
+    pub(crate) fn directory_mut(
+        &mut self,
+        service: &FileSystemViewService,
+        root: &DirectoryView,
+        id: ObjectId,
+    ) -> Result<&mut DirectoryView, OperationError> {
+        match self.staged.entry(id) {
+            Entry::Occupied(staged) => Ok(staged.into_mut()),
+            Entry::Vacant(slot) => {
+                let current = if id == root.id {
+                    root.clone()
+                } else {
+                    service
+                        .get_directory(&id)
+                        .cloned()
+                        .ok_or_else(|| OperationError::NotFound("parent".to_string()))?
+                };
+                Ok(slot.insert(current))
+            }
+        }
+    }
+
+    /// Stages a new directory
+    pub(crate) fn insert(&mut self, dir: DirectoryView) {
+        self.removed.remove(&dir.id);
+        self.staged.insert(dir.id, dir);
+    }
+
+    /// Stages the removal of a registered directory
+    pub(crate) fn remove(&mut self, id: ObjectId) {
+        self.staged.remove(&id);
+        self.removed.insert(id);
+    }
+
+    /// Directories reachable from the root once the transaction commits
+    pub(crate) fn reachable(
+        &self,
+        service: &FileSystemViewService,
+        root: &DirectoryView,
+    ) -> HashSet<ObjectId> {
+        let lookup = |id: &ObjectId| {
+            if self.removed.contains(id) {
+                None
+            } else {
+                self.staged.get(id).or_else(|| service.get_directory(id))
+            }
+        };
+        let mut reachable = HashSet::new();
+        let mut stack = vec![self.staged.get(&root.id).unwrap_or(root)];
+        while let Some(dir) = stack.pop() {
+            for entry in dir.list_entries() {
+                if entry.kind == ObjectKind::Map && reachable.insert(entry.object_id) {
+                    if let Some(child) = lookup(&entry.object_id) {
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Applies every staged update
+    pub(crate) fn commit(self, service: &mut FileSystemViewService, root: &mut DirectoryView) {
+        for id in self.removed {
+            service.unregister_directory(&id);
+        }
+        for (id, dir) in self.staged {
+            if id == root.id {
+                *root = dir;
+            } else {
+                service.register_directory(dir);
+            }
+        }
+    }
+}
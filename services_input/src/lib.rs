@@ -19,7 +19,7 @@
 
 use core_types::{ServiceId, TaskId};
 use input_types::InputEvent;
-use ipc::{ChannelId, MessageEnvelope, MessagePayload, SchemaVersion};
+use ipc::{ChannelId, EncodingPolicy, MessageEnvelope, SchemaVersion};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
@@ -71,6 +71,14 @@ pub const INPUT_SCHEMA_VERSION: SchemaVersion = SchemaVersion::new(1, 0);
 /// Action identifier for input event messages.
 pub const INPUT_EVENT_ACTION: &str = "input.event";
 
+/// Returns the encoding policy for input service messages.
+///
+/// Input events are on the hot path, so schema v1.0 and later use the compact
+/// binary codec.
+pub fn input_encoding_policy() -> EncodingPolicy {
+    EncodingPolicy::new().with_binary_since(INPUT_SCHEMA_VERSION)
+}
+
 /// Returns the stable service ID for the input service.
 pub fn input_service_id() -> ServiceId {
     core_types::input_service_id()
@@ -81,9 +89,11 @@ pub fn build_input_event_envelope(
     event: &InputEvent,
     source: Option<TaskId>,
 ) -> Result<MessageEnvelope, InputServiceError> {
-    let payload = MessagePayload::new(event).map_err(|err| InputServiceError::DeliveryFailed {
-        reason: err.to_string(),
-    })?;
+    let payload = input_encoding_policy()
+        .encode(INPUT_EVENT_ACTION, INPUT_SCHEMA_VERSION, event)
+        .map_err(|err| InputServiceError::DeliveryFailed {
+            reason: err.to_string(),
+        })?;
     let mut envelope = MessageEnvelope::new(
         input_service_id(),
         INPUT_EVENT_ACTION,
@@ -547,6 +557,7 @@ mod tests {
 
         assert_eq!(envelope.action, INPUT_EVENT_ACTION);
         assert_eq!(envelope.schema_version, INPUT_SCHEMA_VERSION);
+        assert_eq!(envelope.payload.encoding(), ipc::PayloadEncoding::Binary);
         let decoded: InputEvent = envelope.payload.deserialize().unwrap();
         assert_eq!(decoded, event);
     }
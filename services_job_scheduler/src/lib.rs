@@ -24,6 +24,7 @@
 //! - "index workspace" job
 //! - "format document" job
 //! - "sync settings" job
+//! - "scrub storage" job ([`scrub_job`])
 //!
 //! ## Example
 //!
@@ -48,6 +49,8 @@
 
 extern crate alloc;
 
+mod scrub;
+
 use alloc::boxed::Box;
 use alloc::collections::VecDeque;
 use alloc::string::String;
@@ -57,6 +60,8 @@ use core_types::uuid_tools::new_uuid;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+pub use scrub::scrub_job;
+
 /// Job identifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct JobId(Uuid);
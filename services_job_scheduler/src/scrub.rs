@@ -0,0 +1,35 @@
+//! Storage scrubbing as a background job
+//!
+//! Storage knows nothing about the scheduler; this module wraps its
+//! incremental [`Scrubber`] in a [`JobDescriptor`].
+
+use crate::{JobDescriptor, JobPriority, JobResult};
+use alloc::boxed::Box;
+use alloc::format;
+use services_storage::{BlockStorageError, Scrubber};
+
+/// Builds a low-priority "scrub_storage" job checking `budget` versions per tick
+///
+/// The job owns the [`Scrubber`]. `step` runs one scrub step against the
+/// shared storage, e.g. `|scrubber, budget| scrubber.step(&mut lock(), budget)`,
+/// so the caller chooses how the storage is shared. The job completes once a
+/// full pass is clean and fails if any version was damaged.
+pub fn scrub_job<F>(budget: usize, mut step: F) -> JobDescriptor
+where
+    F: FnMut(&mut Scrubber, usize) -> Result<bool, BlockStorageError> + Send + 'static,
+{
+    let mut scrubber = Scrubber::new();
+    JobDescriptor::new(
+        "scrub_storage",
+        JobPriority::Low,
+        Box::new(move |_ctx| match step(&mut scrubber, budget) {
+            Ok(false) => JobResult::Yielded,
+            Ok(true) if scrubber.report().is_clean() => JobResult::Completed,
+            Ok(true) => JobResult::Failed(format!(
+                "{} damaged version(s)",
+                scrubber.report().damaged.len()
+            )),
+            Err(e) => JobResult::Failed(format!("{:?}", e)),
+        }),
+    )
+}
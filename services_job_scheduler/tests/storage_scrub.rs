@@ -0,0 +1,89 @@
+//! Integration test running a storage scrub as a background job
+//!
+//! The scrubber checks a few versions per tick, yielding in between, and
+//! the job fails if any damage was found.
+
+use hal::{BlockDevice, RamDisk};
+use services_job_scheduler::{JobDescriptor, JobScheduler, JobStatus};
+use services_storage::{BlockStorage, ObjectId, TransactionalStorage};
+use std::sync::{Arc, Mutex};
+
+/// Versions checked per tick
+const SCRUB_BUDGET: usize = 2;
+
+fn populated_storage(objects: u8) -> BlockStorage<RamDisk> {
+    let mut storage = BlockStorage::format(RamDisk::with_capacity_mb(1)).unwrap();
+    for i in 0..objects {
+        let mut tx = storage.begin_transaction().unwrap();
+        storage
+            .write(&mut tx, ObjectId::new(), &[0xA0 + i; 4096])
+            .unwrap();
+        storage.commit(&mut tx).unwrap();
+    }
+    storage
+}
+
+/// Flips a byte in the block holding `pattern`, behind the storage's back
+fn corrupt_block(storage: BlockStorage<RamDisk>, pattern: u8) -> BlockStorage<RamDisk> {
+    let mut disk = storage.into_device();
+    let mut buffer = vec![0u8; disk.block_size()];
+    let block = (0..disk.block_count())
+        .find(|&idx| {
+            disk.read_block(idx, &mut buffer).unwrap();
+            buffer.iter().all(|&b| b == pattern)
+        })
+        .expect("pattern block not found");
+    buffer[0] ^= 0xFF;
+    disk.write_block(block, &buffer).unwrap();
+    BlockStorage::open(disk).unwrap()
+}
+
+fn scrub_job(storage: Arc<Mutex<BlockStorage<RamDisk>>>) -> JobDescriptor {
+    services_job_scheduler::scrub_job(SCRUB_BUDGET, move |scrubber, budget| {
+        scrubber.step(&mut storage.lock().unwrap(), budget)
+    })
+}
+
+fn run_to_completion(scheduler: &mut JobScheduler) {
+    for _ in 0..100 {
+        scheduler.tick();
+        if scheduler.pending_count() == 0 && !scheduler.has_running_job() {
+            return;
+        }
+    }
+    panic!("job did not finish");
+}
+
+#[test]
+fn test_scrub_job_completes_on_clean_storage() {
+    let storage = Arc::new(Mutex::new(populated_storage(5)));
+    let mut scheduler = JobScheduler::new();
+    let job_id = scheduler.schedule_job(scrub_job(storage.clone()));
+
+    run_to_completion(&mut scheduler);
+
+    assert_eq!(scheduler.get_job_status(job_id), Some(JobStatus::Completed));
+    // Five versions at two per tick
+    assert_eq!(scheduler.tick_count(), 3);
+    assert_eq!(storage.lock().unwrap().stats().quarantined_blocks, 0);
+}
+
+#[test]
+fn test_scrub_job_reports_and_quarantines_damage() {
+    let storage = corrupt_block(populated_storage(5), 0xA3);
+    let storage = Arc::new(Mutex::new(storage));
+    let mut scheduler = JobScheduler::new();
+    let job_id = scheduler.schedule_job(scrub_job(storage.clone()));
+
+    run_to_completion(&mut scheduler);
+
+    assert_eq!(scheduler.get_job_status(job_id), Some(JobStatus::Failed));
+
+    // The quarantine survives a restart
+    let storage = Arc::try_unwrap(storage).ok().unwrap().into_inner().unwrap();
+    let mut storage = BlockStorage::open(storage.into_device()).unwrap();
+    assert_eq!(storage.stats().quarantined_blocks, 1);
+    let report = storage.scrub().unwrap();
+    assert_eq!(report.damaged.len(), 1);
+    assert_eq!(report.blocks_quarantined, 0);
+}
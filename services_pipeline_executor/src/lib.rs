@@ -20,7 +20,7 @@
 
 use identity::IdentityMetadata;
 use ipc::{MessageEnvelope, MessagePayload, SchemaVersion};
-use kernel_api::{Duration, KernelApi};
+use kernel_api::{Duration, KernelApi, KernelError, WaitSet};
 use lifecycle::{CancellationToken, Deadline};
 use pipeline::{
     ExecutionTrace, PipelineError, PipelineExecutionResult, PipelineSpec, StageExecutionResult,
@@ -579,7 +579,7 @@ impl PipelineExecutor {
     /// This implementation:
     /// 1. Looks up the handler service in registry
     /// 2. Sends a stage invocation request with typed input
-    /// 3. Waits for a correlated response
+    /// 3. Waits for a correlated response with `select`, bounded by the stage timeout
     /// 4. Deserializes the returned stage result
     ///
     /// Contract:
@@ -623,10 +623,26 @@ impl PipelineExecutor {
             ExecutorError::KernelError(format!("Failed to send stage request: {}", e))
         })?;
 
-        let timeout = stage.timeout_ms.map(Duration::from_millis);
-        let response = kernel.receive_message(channel, timeout).map_err(|e| {
-            ExecutorError::KernelError(format!("Failed to receive stage response: {}", e))
-        })?;
+        let mut wait_set = WaitSet::new().with_channel(channel);
+        if let Some(timeout_ms) = stage.timeout_ms {
+            wait_set.set_deadline(Some(kernel.now() + Duration::from_millis(timeout_ms)));
+        }
+
+        // The request travels on the handler's channel, so it can still be
+        // queued there if no handler has picked it up yet. Skip it and keep
+        // waiting for the response until the deadline.
+        let response = loop {
+            let (_, message) = kernel.receive_any(&wait_set).map_err(|e| match e {
+                KernelError::Timeout => ExecutorError::KernelError(format!(
+                    "Stage {} timed out waiting for a response",
+                    stage.name
+                )),
+                _ => ExecutorError::KernelError(format!("Failed to receive stage response: {}", e)),
+            })?;
+            if message.id != request_id {
+                break message;
+            }
+        };
 
         if response.correlation_id != Some(request_id) {
             return Err(ExecutorError::KernelError(format!(
@@ -873,6 +889,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_execute_stage_once_times_out_on_simulated_kernel() {
+        let executor = PipelineExecutor::new();
+        let mut kernel = sim_kernel::SimulatedKernel::new();
+        let handler_id = ServiceId::from_u128(0x5104);
+        let channel = kernel.create_channel().unwrap();
+        kernel.register_service(handler_id, channel).unwrap();
+
+        // Nothing serves the handler channel, so only the request sits on it
+        let stage = pipeline::StageSpec::new(
+            "silent".to_string(),
+            handler_id,
+            "invoke".to_string(),
+            PayloadSchemaId::new("in"),
+            PayloadSchemaId::new("out"),
+        )
+        .with_timeout_ms(50);
+        let input = TypedPayload::new(
+            PayloadSchemaId::new("in"),
+            PayloadSchemaVersion::new(1, 0),
+            vec![],
+        );
+
+        let start = kernel.now();
+        let result = executor.execute_stage_once(&mut kernel, &stage, input);
+        match result {
+            Err(ExecutorError::KernelError(msg)) => assert!(msg.contains("timed out")),
+            other => panic!("Expected stage timeout, got {:?}", other),
+        }
+        assert_eq!(
+            kernel.now().duration_since(start),
+            Duration::from_millis(50)
+        );
+    }
+
     // Mock kernel for testing
     struct MockKernel {
         time_ms: u64,
@@ -974,6 +1025,20 @@ mod tests {
             Ok(response)
         }
 
+        fn select(
+            &mut self,
+            wait_set: &kernel_api::WaitSet,
+        ) -> Result<kernel_api::WaitOutcome, kernel_api::KernelError> {
+            // Every channel with a sent request has a staged response ready
+            Ok(wait_set
+                .channels()
+                .iter()
+                .copied()
+                .find(|channel| self.sent.iter().any(|(sent, _)| sent == channel))
+                .map(kernel_api::WaitOutcome::Ready)
+                .unwrap_or(kernel_api::WaitOutcome::TimedOut))
+        }
+
         fn now(&self) -> kernel_api::Instant {
             // Mock instant based on our counter
             kernel_api::Instant::from_nanos(self.time_ms * 1_000_000)
@@ -2,7 +2,7 @@
 
 use core_types::ServiceId;
 use ipc::ChannelId;
-use ipc::{MessageEnvelope, MessagePayload, SchemaVersion};
+use ipc::{EncodingPolicy, MessageEnvelope, SchemaVersion};
 use kernel_api::{KernelApi, KernelError};
 use serde::{Deserialize, Serialize};
 use services_workspace_manager::WorkspaceRenderSnapshot;
@@ -12,6 +12,13 @@ use thiserror::Error;
 const REMOTE_UI_ACTION: &str = "ui.snapshot";
 const REMOTE_UI_SCHEMA: SchemaVersion = SchemaVersion::new(1, 0);
 
+/// Encoding policy for snapshot frames sent over IPC.
+///
+/// Snapshots carry every visible view frame, so they use the binary codec.
+pub fn remote_ui_encoding_policy() -> EncodingPolicy {
+    EncodingPolicy::new().with_binary_since(REMOTE_UI_SCHEMA)
+}
+
 /// Snapshot frame streamed to remote UI clients.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoteSnapshotFrame {
@@ -93,6 +100,7 @@ pub struct IpcSnapshotSink<K: KernelApi> {
     kernel: K,
     channel: ChannelId,
     destination: ServiceId,
+    policy: EncodingPolicy,
 }
 
 impl<K: KernelApi> IpcSnapshotSink<K> {
@@ -101,14 +109,23 @@ impl<K: KernelApi> IpcSnapshotSink<K> {
             kernel,
             channel,
             destination,
+            policy: remote_ui_encoding_policy(),
         }
     }
+
+    /// Overrides the payload encoding policy (e.g. JSON for debugging).
+    pub fn with_policy(mut self, policy: EncodingPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
 }
 
 impl<K: KernelApi> SnapshotSink for IpcSnapshotSink<K> {
     fn send(&mut self, frame: RemoteSnapshotFrame) -> Result<(), RemoteUiError> {
-        let payload =
-            MessagePayload::new(&frame).map_err(|err| RemoteUiError::Encode(err.to_string()))?;
+        let payload = self
+            .policy
+            .encode(REMOTE_UI_ACTION, REMOTE_UI_SCHEMA, &frame)
+            .map_err(|err| RemoteUiError::Encode(err.to_string()))?;
         let message = MessageEnvelope::new(
             self.destination,
             REMOTE_UI_ACTION,
@@ -198,6 +215,13 @@ mod tests {
             Err(KernelError::ReceiveFailed("not supported".to_string()))
         }
 
+        fn select(
+            &mut self,
+            _wait_set: &kernel_api::WaitSet,
+        ) -> Result<kernel_api::WaitOutcome, KernelError> {
+            Err(KernelError::ReceiveFailed("not supported".to_string()))
+        }
+
         fn now(&self) -> kernel_api::Instant {
             kernel_api::Instant::from_nanos(0)
         }
@@ -305,12 +329,20 @@ mod tests {
             snapshot: sample_snapshot(),
         };
 
-        sink.send(frame).unwrap();
+        sink.send(frame.clone()).unwrap();
 
         let sent = sent.lock().expect("lock sent");
         assert_eq!(sent.len(), 1);
         assert_eq!(sent[0].0, channel);
         assert_eq!(sent[0].1.action, REMOTE_UI_ACTION.to_string());
+        assert_eq!(sent[0].1.payload.encoding(), ipc::PayloadEncoding::Binary);
+        let decoded: RemoteSnapshotFrame = sent[0].1.payload.deserialize().unwrap();
+        assert_eq!(decoded.revision, 1);
+        assert_eq!(
+            decoded.snapshot.main_view.as_ref().map(|view| view.view_id),
+            frame.snapshot.main_view.as_ref().map(|view| view.view_id)
+        );
+        assert_eq!(decoded.snapshot.tiles.len(), frame.snapshot.tiles.len());
     }
 
     #[test]
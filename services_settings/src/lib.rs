@@ -177,6 +177,7 @@ impl fmt::Display for SettingValue {
 pub type UserId = String;
 
 /// Settings registry
+#[derive(Debug, Clone)]
 pub struct SettingsRegistry {
     /// Default settings (read-only)
     defaults: BTreeMap<SettingKey, SettingValue>,
@@ -9,8 +9,52 @@
 //! - Then writes a commit record with checksum (atomic point of truth)
 //! - On recovery, scans for valid commit records
 //! - Incomplete transactions (no commit record or bad checksum) are discarded
+//!
+//! ## Space Reclamation
+//! The commit log is a fixed ring, so it cannot be the only record of what
+//! is live. Checkpoints of every live allocation are written to two
+//! alternating slots in the metadata area; recovery loads the newest valid
+//! checkpoint and replays only the commits after it.
+//! - A checkpoint is written before the commit log wraps over uncovered commits
+//! - Garbage collection drops versions outside the [`RetentionPolicy`]
+//! - Compaction moves live data towards the start of the data area
+//!
+//! Both write the new checkpoint before any freed block is reused, so a
+//! crash leaves either the old layout or the new one.
+//!
+//! Each checkpoint slot is sized at format time (half of the metadata area,
+//! at least one block) and a checkpoint must fit in one slot. Once the live
+//! allocation table outgrows it, a commit that needs a checkpoint fails with
+//! [`BlockStorageError::CheckpointTooLarge`] and is not applied; everything
+//! committed before it stays readable.
+//!
+//! ## Extents
+//! A version's data is a list of extents (runs of blocks). Appends share
+//! the previous version's full blocks and only write the partial tail block
+//! again, and streaming writers (see [`crate::stream`]) allocate extents as
+//! data arrives. Blocks are never overwritten while referenced, so the
+//! commit record stays the single point of truth.
+//!
+//! ## Deduplication
+//! With deduplication enabled, new data is split into chunks of
+//! [`DEDUP_CHUNK_SIZE`] bytes, each indexed by its CRC32 once committed. A
+//! chunk whose bytes match an indexed one (compared in full, never by hash
+//! alone) shares its extent instead of being written again. Reference counts
+//! travel in the commit record when it has room and always in checkpoints;
+//! recovery recounts them from the allocations it rebuilds.
+//!
+//! ## Integrity
+//! Every extent records the CRC32 of its data, checked whenever the extent
+//! is read in full; a mismatch is reported as
+//! [`BlockStorageError::Corrupted`] rather than returned as data. A partial
+//! final block always gets an extent of its own, so appends can drop it
+//! without losing the checksum of the full blocks before it. The
+//! [`crate::scrub`] module walks every live version and quarantines the
+//! blocks of damaged extents; quarantined blocks are never reused.
+use crate::stream::{ObjectReader, ObjectWriter, STREAM_CHUNK_SIZE};
 use crate::{
-    ObjectId, Transaction, TransactionError, TransactionId, TransactionalStorage, VersionId,
+    ObjectId, Transaction, TransactionError, TransactionId, TransactionalStorage, VersionHistory,
+    VersionId, VersionInfo,
 };
 use alloc::collections::BTreeMap;
 use alloc::collections::BTreeSet;
@@ -30,9 +74,9 @@ struct Superblock {
     version: u32,
     /// Total number of blocks on device
     total_blocks: u64,
-    /// First block of allocation bitmap
+    /// First block of the metadata area (holds the two checkpoint slots)
     bitmap_start: u64,
-    /// Number of bitmap blocks
+    /// Number of metadata blocks
     bitmap_blocks: u64,
     /// First block of data area
     data_start: u64,
@@ -45,7 +89,7 @@ struct Superblock {
 }
 
 const SUPERBLOCK_MAGIC: u64 = 0x50414E44_47454E00; // "PANDAGEN\0"
-const STORAGE_VERSION: u32 = 2; // Bumped for crash-safe storage
+const STORAGE_VERSION: u32 = 3; // Bumped for checkpoints
 
 /// Commit record for crash-safe transactions
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +100,12 @@ struct CommitRecord {
     sequence: u64,
     /// List of allocations made in this transaction
     allocations: Vec<AllocationEntry>,
+    /// Objects deleted by this transaction (applied before `allocations`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tombstones: Vec<ObjectId>,
+    /// Content-addressed chunks the allocations reference, with new counts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    chunks: Vec<ChunkRef>,
     /// CRC32 checksum of the commit record (excluding this field)
     checksum: u32,
 }
@@ -66,11 +116,15 @@ impl CommitRecord {
         transaction_id: TransactionId,
         sequence: u64,
         allocations: Vec<AllocationEntry>,
+        tombstones: Vec<ObjectId>,
+        chunks: Vec<ChunkRef>,
     ) -> Self {
         let mut record = Self {
             transaction_id,
             sequence,
             allocations,
+            tombstones,
+            chunks,
             checksum: 0,
         };
         record.checksum = record.compute_checksum();
@@ -92,6 +146,121 @@ impl CommitRecord {
     }
 }
 
+/// Snapshot of every live allocation, written to the metadata area
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Checkpoint {
+    /// Increases with every checkpoint; picks the slot and the newest one
+    generation: u64,
+    /// Commits up to and including this sequence are covered
+    sequence: u64,
+    /// Live allocations, each object's versions oldest first
+    allocations: Vec<AllocationEntry>,
+    /// Content-addressed chunk index
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    chunks: Vec<ChunkRef>,
+    /// Blocks holding damaged data, kept out of the free set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    quarantined: Vec<u64>,
+    /// CRC32 checksum of the checkpoint (excluding this field)
+    checksum: u32,
+}
+
+impl Checkpoint {
+    /// Create a new checkpoint with computed checksum
+    fn new(
+        generation: u64,
+        sequence: u64,
+        allocations: Vec<AllocationEntry>,
+        chunks: Vec<ChunkRef>,
+        quarantined: Vec<u64>,
+    ) -> Self {
+        let mut checkpoint = Self {
+            generation,
+            sequence,
+            allocations,
+            chunks,
+            quarantined,
+            checksum: 0,
+        };
+        checkpoint.checksum = checkpoint.compute_checksum();
+        checkpoint
+    }
+
+    /// Compute CRC32 checksum of checkpoint (excluding checksum field)
+    fn compute_checksum(&self) -> u32 {
+        let mut temp = self.clone();
+        temp.checksum = 0;
+        let data = serde_json::to_vec(&temp).unwrap_or_default();
+        crc32fast::hash(&data)
+    }
+
+    /// Validate checksum
+    fn is_valid(&self) -> bool {
+        self.compute_checksum() == self.checksum
+    }
+}
+
+/// Which superseded versions garbage collection keeps
+///
+/// The latest version of every object is always kept. An older version
+/// survives if any configured rule keeps it; with no rules set, only the
+/// latest version survives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetentionPolicy {
+    /// Keep the newest N versions of each object (including the latest)
+    pub keep_last: Option<usize>,
+    /// Keep versions committed at or after this timestamp
+    pub keep_since: Option<u64>,
+}
+
+impl RetentionPolicy {
+    /// Keeps only the latest version of each object
+    pub fn latest_only() -> Self {
+        Self::default()
+    }
+
+    /// Keeps every version (garbage collection only reclaims leaked blocks)
+    pub fn keep_all() -> Self {
+        Self::keep_last(usize::MAX)
+    }
+
+    /// Keeps the newest `count` versions of each object
+    pub fn keep_last(count: usize) -> Self {
+        Self {
+            keep_last: Some(count),
+            keep_since: None,
+        }
+    }
+
+    /// Keeps versions committed at or after `timestamp`
+    pub fn keep_since(timestamp: u64) -> Self {
+        Self {
+            keep_last: None,
+            keep_since: Some(timestamp),
+        }
+    }
+
+    /// Adds a keep-last rule
+    pub fn with_keep_last(mut self, count: usize) -> Self {
+        self.keep_last = Some(count);
+        self
+    }
+
+    /// Adds a keep-since rule
+    pub fn with_keep_since(mut self, timestamp: u64) -> Self {
+        self.keep_since = Some(timestamp);
+        self
+    }
+
+    /// Whether the version at `index` of `len` (oldest first) is kept
+    fn retains(&self, index: usize, len: usize, committed_at: u64) -> bool {
+        let age = len - 1 - index;
+        age == 0
+            || self.keep_last.is_some_and(|count| age < count)
+            || self.keep_since.is_some_and(|since| committed_at >= since)
+    }
+}
+
 /// Storage recovery report
 #[derive(Debug, Clone)]
 pub struct StorageRecoveryReport {
@@ -101,19 +270,269 @@ pub struct StorageRecoveryReport {
     pub discarded_transactions: usize,
     /// Last valid commit sequence number
     pub last_sequence: u64,
+    /// Sequence covered by the checkpoint recovery started from, if any
+    pub checkpoint_sequence: Option<u64>,
     /// Whether recovery was successful
     pub success: bool,
     /// Recovery error message if any
     pub error: Option<String>,
 }
 
+/// Garbage collection report
+#[derive(Debug, Clone)]
+pub struct StorageGcReport {
+    /// Versions dropped by the retention policy
+    pub versions_collected: usize,
+    /// Blocks returned to the free set (including leaked ones)
+    pub blocks_freed: u64,
+    /// Payload bytes of the dropped versions
+    pub bytes_reclaimed: u64,
+    /// Commit sequence covered by the new checkpoint (None if nothing changed)
+    pub checkpoint_sequence: Option<u64>,
+}
+
+/// Compaction report
+#[derive(Debug, Clone)]
+pub struct StorageCompactionReport {
+    /// Runs of blocks moved to a lower position
+    pub extents_moved: usize,
+    /// Blocks copied
+    pub blocks_moved: u64,
+    /// Passes made (each ends with a checkpoint)
+    pub passes: usize,
+    /// Largest run of free blocks before compaction
+    pub largest_free_extent_before: u64,
+    /// Largest run of free blocks after compaction
+    pub largest_free_extent_after: u64,
+}
+
+/// Space usage report
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageStats {
+    /// Committed versions
+    pub versions: usize,
+    /// Bytes of all committed versions, as readers see them
+    pub logical_bytes: u64,
+    /// Bytes of the data blocks they occupy, counting shared blocks once
+    pub physical_bytes: u64,
+    /// Chunks in the content-addressed index
+    pub chunks: usize,
+    /// Indexed chunks referenced more than once
+    pub shared_chunks: usize,
+    /// Blocks quarantined by scrubbing
+    pub quarantined_blocks: u64,
+}
+
+/// Bytes per content-addressed chunk
+///
+/// Matches the streaming chunk size, so streamed and buffered writes of the
+/// same data split at the same boundaries.
+pub const DEDUP_CHUNK_SIZE: usize = STREAM_CHUNK_SIZE;
+
+/// An indexed chunk of content stored in one extent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ChunkRef {
+    block_idx: u64,
+    length: u64,
+    /// CRC32 of the chunk's bytes
+    hash: u32,
+    /// References from committed versions (a version may count twice)
+    refs: u32,
+}
+
+impl ChunkRef {
+    /// The chunk's blocks as one run
+    fn run(&self) -> Extent {
+        Extent::new(self.block_idx, self.length)
+    }
+
+    /// Extents for sharing the chunk, sealed with the checksums of `data`
+    fn extents(&self, data: &[u8]) -> Vec<Extent> {
+        let partial = self.length % BLOCK_SIZE as u64;
+        let full = self.length - partial;
+        let mut extents = Vec::new();
+        if full > 0 {
+            extents.push(Extent::new(self.block_idx, full));
+        }
+        if partial > 0 {
+            extents.push(Extent::new(
+                self.block_idx + full / BLOCK_SIZE as u64,
+                partial,
+            ));
+        }
+        seal_extents(&mut extents, data);
+        extents
+    }
+}
+
+/// Sets each extent's checksum from its slice of `data`
+fn seal_extents(extents: &mut [Extent], data: &[u8]) {
+    let mut offset = 0;
+    for extent in extents {
+        let end = offset + extent.length as usize;
+        extent.checksum = Some(crc32fast::hash(&data[offset..end]));
+        offset = end;
+    }
+}
+
+/// Counts references to each chunk (by first block) from `allocations`
+fn count_chunk_refs<'a>(
+    chunk_blocks: impl Iterator<Item = u64>,
+    allocations: impl Iterator<Item = &'a AllocationEntry>,
+) -> BTreeMap<u64, u32> {
+    let mut counts: BTreeMap<u64, u32> = chunk_blocks.map(|block| (block, 0)).collect();
+    if counts.is_empty() {
+        return counts;
+    }
+    for alloc in allocations {
+        for extent in alloc.extent_list() {
+            for (_, count) in counts.range_mut(extent.blocks()) {
+                *count += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Appends an extent, merging it into the previous one when contiguous
+///
+/// Only full-block extents merge; a partial block stays an extent of its
+/// own. The merged checksum is combined without reading anything back.
+pub(crate) fn push_extent(extents: &mut Vec<Extent>, extent: Extent) {
+    match extents.last_mut() {
+        Some(last)
+            if last.length.is_multiple_of(BLOCK_SIZE as u64)
+                && extent.length.is_multiple_of(BLOCK_SIZE as u64)
+                && last.block_idx + last.block_count() == extent.block_idx =>
+        {
+            last.checksum = match (last.checksum, extent.checksum) {
+                (Some(first), Some(second)) => {
+                    let mut hasher = crc32fast::Hasher::new_with_initial_len(first, last.length);
+                    hasher.combine(&crc32fast::Hasher::new_with_initial_len(
+                        second,
+                        extent.length,
+                    ));
+                    Some(hasher.finalize())
+                }
+                _ => None,
+            };
+            last.length += extent.length;
+        }
+        _ => extents.push(extent),
+    }
+}
+
+/// A run of blocks holding `length` bytes of a version's data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Extent {
+    pub(crate) block_idx: u64,
+    pub(crate) length: u64,
+    /// CRC32 of the extent's data (absent in records from before checksums)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) checksum: Option<u32>,
+}
+
+impl Extent {
+    /// An extent with no checksum yet
+    pub(crate) fn new(block_idx: u64, length: u64) -> Self {
+        Self {
+            block_idx,
+            length,
+            checksum: None,
+        }
+    }
+
+    /// Number of blocks occupied by this extent
+    pub(crate) fn block_count(&self) -> u64 {
+        (self.length as usize).div_ceil(BLOCK_SIZE) as u64
+    }
+
+    /// Blocks occupied by this extent
+    pub(crate) fn blocks(&self) -> core::ops::Range<u64> {
+        self.block_idx..self.block_idx + self.block_count()
+    }
+}
+
 /// Block allocation status
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct AllocationEntry {
+pub(crate) struct AllocationEntry {
     object_id: ObjectId,
     version_id: VersionId,
     block_idx: u64,
     size_bytes: u64,
+    /// Commit timestamp (absent in records from before retention policies)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    committed_at: u64,
+    /// Data extents in order; empty means one extent at `block_idx`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    extents: Vec<Extent>,
+    /// Transaction that committed this version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transaction_id: Option<TransactionId>,
+    /// Checksum of the single extent when `extents` is empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    checksum: Option<u32>,
+}
+
+/// Keeps fields added later out of the JSON so old checksums still match
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+impl AllocationEntry {
+    /// Creates an entry for data stored in `extents`
+    pub(crate) fn from_extents(
+        object_id: ObjectId,
+        version_id: VersionId,
+        extents: Vec<Extent>,
+    ) -> Self {
+        let mut entry = Self {
+            object_id,
+            version_id,
+            block_idx: 0,
+            size_bytes: extents.iter().map(|extent| extent.length).sum(),
+            committed_at: 0,
+            extents: Vec::new(),
+            transaction_id: None,
+            checksum: None,
+        };
+        entry.set_extents(extents);
+        entry
+    }
+
+    /// Data extents in order
+    pub(crate) fn extent_list(&self) -> Vec<Extent> {
+        if !self.extents.is_empty() {
+            self.extents.clone()
+        } else if self.size_bytes > 0 {
+            alloc::vec![Extent {
+                block_idx: self.block_idx,
+                length: self.size_bytes,
+                checksum: self.checksum,
+            }]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Replaces the extents, keeping the single-extent form compact
+    fn set_extents(&mut self, extents: Vec<Extent>) {
+        self.block_idx = extents.first().map_or(0, |extent| extent.block_idx);
+        if extents.len() > 1 {
+            self.checksum = None;
+            self.extents = extents;
+        } else {
+            self.checksum = extents.first().and_then(|extent| extent.checksum);
+            self.extents = Vec::new();
+        }
+    }
+
+    /// Blocks occupied by this allocation (shared blocks included)
+    fn blocks(&self) -> impl Iterator<Item = u64> {
+        self.extent_list()
+            .into_iter()
+            .flat_map(|extent| extent.blocks())
+    }
 }
 
 /// Block-backed storage backend with crash-safe commits
@@ -124,12 +543,32 @@ pub struct BlockStorage<D: BlockDevice> {
     allocations: BTreeMap<(ObjectId, VersionId), AllocationEntry>,
     /// Track the latest version for each object
     latest_versions: BTreeMap<ObjectId, VersionId>,
+    /// Committed versions of each object, oldest first
+    history: BTreeMap<ObjectId, Vec<VersionId>>,
     /// Free blocks
     free_blocks: BTreeSet<u64>,
-    /// Pending writes for active transactions
-    pending: BTreeMap<TransactionId, Vec<PendingWrite>>,
+    /// Pending changes for active transactions
+    pending: BTreeMap<TransactionId, Vec<PendingChange>>,
     /// Recovery report (if opened from existing storage)
     recovery_report: Option<StorageRecoveryReport>,
+    /// Which versions garbage collection keeps
+    retention: RetentionPolicy,
+    /// Versions kept regardless of the retention policy, with pin counts
+    pinned: BTreeMap<(ObjectId, VersionId), usize>,
+    /// Whether new data is stored as content-addressed chunks
+    dedup: bool,
+    /// Committed chunks by first block
+    chunks: BTreeMap<u64, ChunkRef>,
+    /// Chunks written by active transactions, indexed once they commit
+    pending_chunks: BTreeMap<TransactionId, Vec<ChunkRef>>,
+    /// Blocks holding damaged data, never returned to the free set
+    quarantined: BTreeSet<u64>,
+    /// Timestamp recorded on subsequent commits
+    commit_timestamp: u64,
+    /// Generation of the newest checkpoint on disk (0 if none)
+    checkpoint_generation: u64,
+    /// Commit sequence covered by the newest checkpoint
+    checkpoint_sequence: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -139,13 +578,60 @@ struct PendingWrite {
     data: Vec<u8>,
 }
 
+/// Where a version's data currently lives
+enum VersionData {
+    Buffered(Vec<u8>),
+    OnDisk(AllocationEntry),
+}
+
+/// A change buffered until its transaction commits
+#[derive(Debug, Clone)]
+enum PendingChange {
+    Write(PendingWrite),
+    /// A version whose data is already on disk in reserved extents
+    Written(AllocationEntry),
+    Delete(ObjectId),
+}
+
+impl PendingChange {
+    fn object_id(&self) -> ObjectId {
+        match self {
+            PendingChange::Write(write) => write.object_id,
+            PendingChange::Written(entry) => entry.object_id,
+            PendingChange::Delete(object_id) => *object_id,
+        }
+    }
+
+    fn version_id(&self) -> Option<VersionId> {
+        match self {
+            PendingChange::Write(write) => Some(write.version_id),
+            PendingChange::Written(entry) => Some(entry.version_id),
+            PendingChange::Delete(_) => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum BlockStorageError {
     BlockError(BlockError),
     InvalidSuperblock,
+    /// The device was formatted with another on-disk format version
+    UnsupportedVersion {
+        found: u32,
+        expected: u32,
+    },
     NoFreeSpace,
     ObjectNotFound,
     SerializationError,
+    /// The live allocation table no longer fits in a checkpoint slot
+    CheckpointTooLarge,
+    /// Data read back does not match its checksum
+    Corrupted {
+        object_id: ObjectId,
+        version_id: VersionId,
+        /// First block of the damaged extent
+        block_idx: u64,
+    },
 }
 
 impl From<BlockError> for BlockStorageError {
@@ -160,6 +646,20 @@ impl From<BlockStorageError> for TransactionError {
             BlockStorageError::ObjectNotFound => {
                 TransactionError::ObjectNotFound("object not found".to_string())
             }
+            BlockStorageError::Corrupted {
+                object_id,
+                version_id,
+                block_idx,
+            } => TransactionError::Corrupted(format!(
+                "{} at version {} (block {})",
+                object_id, version_id, block_idx
+            )),
+            BlockStorageError::UnsupportedVersion { found, expected } => {
+                TransactionError::StorageError(format!(
+                    "unsupported storage format version {} (expected {})",
+                    found, expected
+                ))
+            }
             _ => TransactionError::StorageError("block storage error".to_string()),
         }
     }
@@ -173,7 +673,8 @@ impl<D: BlockDevice> BlockStorage<D> {
         // Reserve blocks:
         // - Block 0: superblock
         // - Blocks 1-N: commit log (5% of disk or 1 block min, 256 blocks max)
-        // - Blocks N+1-M: allocation bitmap (10% of remaining or 1 block min)
+        // - Blocks N+1-M: checkpoint area, two equal slots (10% of remaining,
+        //   at least one block per slot)
         // - Blocks M+1...: data area
         let commit_log_blocks = ((total_blocks * 5) / 100).clamp(1, 256);
         let bitmap_start = 1 + commit_log_blocks;
@@ -184,7 +685,7 @@ impl<D: BlockDevice> BlockStorage<D> {
         }
 
         let remaining_after_log = total_blocks - bitmap_start;
-        let bitmap_blocks = (remaining_after_log / 10).max(1);
+        let bitmap_blocks = (remaining_after_log / 10).max(2);
         let data_start = bitmap_start + bitmap_blocks;
 
         if data_start >= total_blocks {
@@ -222,9 +723,19 @@ impl<D: BlockDevice> BlockStorage<D> {
             superblock,
             allocations: BTreeMap::new(),
             latest_versions: BTreeMap::new(),
+            history: BTreeMap::new(),
             free_blocks,
             pending: BTreeMap::new(),
             recovery_report: None,
+            retention: RetentionPolicy::default(),
+            pinned: BTreeMap::new(),
+            dedup: false,
+            chunks: BTreeMap::new(),
+            pending_chunks: BTreeMap::new(),
+            quarantined: BTreeSet::new(),
+            commit_timestamp: 0,
+            checkpoint_generation: 0,
+            checkpoint_sequence: 0,
         })
     }
 
@@ -243,6 +754,15 @@ impl<D: BlockDevice> BlockStorage<D> {
             return Err(BlockStorageError::InvalidSuperblock);
         }
 
+        // Older formats have no checkpoint area, so their commit log alone
+        // would be replayed over a layout this version does not use
+        if superblock.version != STORAGE_VERSION {
+            return Err(BlockStorageError::UnsupportedVersion {
+                found: superblock.version,
+                expected: STORAGE_VERSION,
+            });
+        }
+
         // Create storage instance
         let free_blocks: BTreeSet<u64> = (superblock.data_start..superblock.total_blocks).collect();
 
@@ -251,9 +771,19 @@ impl<D: BlockDevice> BlockStorage<D> {
             superblock,
             allocations: BTreeMap::new(),
             latest_versions: BTreeMap::new(),
+            history: BTreeMap::new(),
             free_blocks,
             pending: BTreeMap::new(),
             recovery_report: None,
+            retention: RetentionPolicy::default(),
+            pinned: BTreeMap::new(),
+            dedup: false,
+            chunks: BTreeMap::new(),
+            pending_chunks: BTreeMap::new(),
+            quarantined: BTreeSet::new(),
+            commit_timestamp: 0,
+            checkpoint_generation: 0,
+            checkpoint_sequence: 0,
         };
 
         // Perform crash recovery
@@ -263,13 +793,28 @@ impl<D: BlockDevice> BlockStorage<D> {
         Ok(storage)
     }
 
-    /// Perform crash recovery by scanning commit log
+    /// Perform crash recovery from the newest checkpoint and the commit log
     fn perform_recovery(&mut self) -> Result<StorageRecoveryReport, BlockStorageError> {
         let mut recovered_commits = 0;
         let mut discarded_transactions = 0;
-        let mut last_sequence = 0;
+
+        let checkpoint = self.read_newest_checkpoint();
+        let checkpoint_sequence = checkpoint.as_ref().map(|c| c.sequence);
+        if let Some(checkpoint) = checkpoint {
+            self.checkpoint_generation = checkpoint.generation;
+            self.checkpoint_sequence = checkpoint.sequence;
+            for alloc in checkpoint.allocations {
+                self.apply_allocation(alloc);
+            }
+            for chunk in checkpoint.chunks {
+                self.chunks.insert(chunk.block_idx, chunk);
+            }
+            self.quarantined = checkpoint.quarantined.into_iter().collect();
+        }
+        let mut last_sequence = self.checkpoint_sequence;
 
         // Scan commit log blocks
+        let mut records = Vec::new();
         for i in 0..self.superblock.commit_log_blocks {
             let block_idx = self.superblock.commit_log_start + i;
             let mut block = [0u8; BLOCK_SIZE];
@@ -283,25 +828,10 @@ impl<D: BlockDevice> BlockStorage<D> {
                             serde_json::from_slice::<CommitRecord>(&block[..json_end])
                         {
                             // Validate checksum
-                            if record.is_valid() && record.sequence > last_sequence {
-                                // Apply this commit
-                                for alloc in &record.allocations {
-                                    self.allocations
-                                        .insert((alloc.object_id, alloc.version_id), alloc.clone());
-                                    self.latest_versions
-                                        .insert(alloc.object_id, alloc.version_id);
-
-                                    // Mark blocks as allocated
-                                    let blocks_needed =
-                                        (alloc.size_bytes as usize).div_ceil(BLOCK_SIZE) as u64;
-                                    for j in 0..blocks_needed {
-                                        self.free_blocks.remove(&(alloc.block_idx + j));
-                                    }
-                                }
-                                last_sequence = record.sequence;
-                                recovered_commits += 1;
-                            } else {
+                            if !record.is_valid() {
                                 discarded_transactions += 1;
+                            } else if record.sequence > self.checkpoint_sequence {
+                                records.push(record);
                             }
                         }
                     }
@@ -313,36 +843,263 @@ impl<D: BlockDevice> BlockStorage<D> {
             }
         }
 
+        // The log is a ring, so slot order is not commit order
+        records.sort_by_key(|record| record.sequence);
+        for record in records {
+            for object_id in &record.tombstones {
+                self.remove_object(*object_id);
+            }
+            for alloc in record.allocations {
+                self.apply_allocation(alloc);
+            }
+            for chunk in record.chunks {
+                self.chunks.insert(chunk.block_idx, chunk);
+            }
+            last_sequence = record.sequence;
+            recovered_commits += 1;
+        }
+
+        // Never reuse a sequence number, even if the superblock update was lost
+        self.superblock.commit_sequence = self.superblock.commit_sequence.max(last_sequence);
+        self.rebuild_free_blocks();
+
         Ok(StorageRecoveryReport {
             recovered_commits,
             discarded_transactions,
             last_sequence,
+            checkpoint_sequence,
             success: true,
             error: None,
         })
     }
 
+    /// Records a committed allocation in the in-memory indexes
+    fn apply_allocation(&mut self, alloc: AllocationEntry) {
+        self.history
+            .entry(alloc.object_id)
+            .or_default()
+            .push(alloc.version_id);
+        self.latest_versions
+            .insert(alloc.object_id, alloc.version_id);
+        self.allocations
+            .insert((alloc.object_id, alloc.version_id), alloc);
+    }
+
+    /// Drops every version of an object
+    ///
+    /// Blocks may be shared with other versions, so callers rebuild the
+    /// free set afterwards.
+    fn remove_object(&mut self, object_id: ObjectId) {
+        self.latest_versions.remove(&object_id);
+        for version_id in self.history.remove(&object_id).unwrap_or_default() {
+            self.allocations.remove(&(object_id, version_id));
+        }
+    }
+
+    /// Allocations staged on disk by active transactions
+    fn pending_written(&self) -> impl Iterator<Item = &AllocationEntry> {
+        self.pending
+            .values()
+            .flatten()
+            .filter_map(|change| match change {
+                PendingChange::Written(entry) => Some(entry),
+                _ => None,
+            })
+    }
+
+    /// Blocks referenced by committed or staged allocations
+    fn referenced_blocks(&self) -> BTreeSet<u64> {
+        self.allocations
+            .values()
+            .chain(self.pending_written())
+            .flat_map(AllocationEntry::blocks)
+            .collect()
+    }
+
+    /// Recomputes the free set from the live allocations
+    fn rebuild_free_blocks(&mut self) {
+        let referenced = self.referenced_blocks();
+        self.free_blocks = (self.superblock.data_start..self.superblock.total_blocks)
+            .filter(|block| !referenced.contains(block) && !self.quarantined.contains(block))
+            .collect();
+        self.reconcile_chunks();
+    }
+
+    /// Recounts chunk references, dropping chunks nothing references
+    fn reconcile_chunks(&mut self) {
+        let counts = count_chunk_refs(self.chunks.keys().copied(), self.allocations.values());
+        let free_blocks = &self.free_blocks;
+        self.chunks.retain(|block_idx, chunk| {
+            chunk.refs = counts[block_idx];
+            chunk.refs > 0
+                && chunk
+                    .run()
+                    .blocks()
+                    .all(|block| !free_blocks.contains(&block))
+        });
+    }
+
+    /// Chunks referenced by allocations about to commit, with their new counts
+    fn commit_chunks(
+        &mut self,
+        tx_id: TransactionId,
+        allocations: &[AllocationEntry],
+        tombstones: &[ObjectId],
+    ) -> Vec<ChunkRef> {
+        let mut candidates = self.chunks.clone();
+        for chunk in self.pending_chunks.remove(&tx_id).unwrap_or_default() {
+            candidates.insert(chunk.block_idx, chunk);
+        }
+        let touched = count_chunk_refs(candidates.keys().copied(), allocations.iter());
+        let counts = count_chunk_refs(
+            touched
+                .iter()
+                .filter(|(_, count)| **count > 0)
+                .map(|(block, _)| *block),
+            self.allocations
+                .values()
+                .filter(|alloc| !tombstones.contains(&alloc.object_id))
+                .chain(allocations),
+        );
+        counts
+            .into_iter()
+            .map(|(block_idx, refs)| ChunkRef {
+                refs,
+                ..candidates[&block_idx]
+            })
+            .collect()
+    }
+    /// Live allocations in checkpoint order (each object's versions oldest first)
+    fn live_allocations(&self) -> Vec<AllocationEntry> {
+        self.history
+            .iter()
+            .flat_map(|(object_id, versions)| {
+                versions
+                    .iter()
+                    .filter_map(move |version_id| self.allocations.get(&(*object_id, *version_id)))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Number of blocks in each checkpoint slot
+    fn checkpoint_slot_blocks(&self) -> u64 {
+        self.superblock.bitmap_blocks / 2
+    }
+
+    /// Reads the valid checkpoint with the highest generation, if any
+    fn read_newest_checkpoint(&mut self) -> Option<Checkpoint> {
+        let slot_blocks = self.checkpoint_slot_blocks();
+        let mut newest: Option<Checkpoint> = None;
+        for slot in 0..2 {
+            let start = self.superblock.bitmap_start + slot * slot_blocks;
+            let blocks: Vec<u64> = (start..start + slot_blocks).collect();
+            let Ok(data) = self.read_data(&blocks, slot_blocks * BLOCK_SIZE as u64) else {
+                continue;
+            };
+            let json_end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+            let Ok(checkpoint) = serde_json::from_slice::<Checkpoint>(&data[..json_end]) else {
+                continue;
+            };
+            if checkpoint.is_valid()
+                && newest
+                    .as_ref()
+                    .is_none_or(|current| checkpoint.generation > current.generation)
+            {
+                newest = Some(checkpoint);
+            }
+        }
+        newest
+    }
+
+    /// Writes a checkpoint to the slot not holding the newest one
+    ///
+    /// Commits up to `sequence` must all be reflected in `allocations`.
+    fn write_checkpoint(
+        &mut self,
+        sequence: u64,
+        allocations: Vec<AllocationEntry>,
+    ) -> Result<(), BlockStorageError> {
+        let generation = self.checkpoint_generation + 1;
+        let counts = count_chunk_refs(self.chunks.keys().copied(), allocations.iter());
+        let chunks = self
+            .chunks
+            .values()
+            .filter(|chunk| counts[&chunk.block_idx] > 0)
+            .map(|chunk| ChunkRef {
+                refs: counts[&chunk.block_idx],
+                ..*chunk
+            })
+            .collect();
+        let quarantined = self.quarantined.iter().copied().collect();
+        let checkpoint = Checkpoint::new(generation, sequence, allocations, chunks, quarantined);
+        let mut data =
+            serde_json::to_vec(&checkpoint).map_err(|_| BlockStorageError::SerializationError)?;
+        // Null terminator so a shorter checkpoint hides an older, longer one
+        data.push(0);
+
+        let slot_blocks = self.checkpoint_slot_blocks();
+        if data.len() as u64 > slot_blocks * BLOCK_SIZE as u64 {
+            return Err(BlockStorageError::CheckpointTooLarge);
+        }
+        let start = self.superblock.bitmap_start + (generation % 2) * slot_blocks;
+        let blocks: Vec<u64> = (start..start + data.len().div_ceil(BLOCK_SIZE) as u64).collect();
+        self.write_data(&blocks, &data)?;
+
+        self.checkpoint_generation = generation;
+        self.checkpoint_sequence = sequence;
+        Ok(())
+    }
+
+    /// Consumes the storage, returning the device (e.g. to reopen it)
+    pub fn into_device(self) -> D {
+        self.device
+    }
+
     /// Get recovery report (if storage was opened from existing device)
     pub fn recovery_report(&self) -> Option<&StorageRecoveryReport> {
         self.recovery_report.as_ref()
     }
 
     /// Write commit record to commit log
+    ///
+    /// Chunk references are left out if they would not fit; the next
+    /// checkpoint persists them instead.
     fn write_commit_record(
         &mut self,
         transaction_id: TransactionId,
         allocations: Vec<AllocationEntry>,
+        tombstones: Vec<ObjectId>,
+        chunks: Vec<ChunkRef>,
     ) -> Result<(), BlockStorageError> {
+        // The slot about to be reused must already be covered by a checkpoint
+        let sequence = self.superblock.commit_sequence + 1;
+        if sequence > self.checkpoint_sequence + self.superblock.commit_log_blocks {
+            let live = self.live_allocations();
+            self.write_checkpoint(sequence - 1, live)?;
+        }
+
         // Increment commit sequence
-        self.superblock.commit_sequence += 1;
-        let sequence = self.superblock.commit_sequence;
+        self.superblock.commit_sequence = sequence;
 
         // Create commit record with checksum
-        let record = CommitRecord::new(transaction_id, sequence, allocations);
+        let mut record =
+            CommitRecord::new(transaction_id, sequence, allocations, tombstones, chunks);
 
         // Serialize commit record
-        let record_json =
+        let mut record_json =
             serde_json::to_vec(&record).map_err(|_| BlockStorageError::SerializationError)?;
+        if record_json.len() > BLOCK_SIZE && !record.chunks.is_empty() {
+            record = CommitRecord::new(
+                transaction_id,
+                sequence,
+                record.allocations,
+                record.tombstones,
+                Vec::new(),
+            );
+            record_json =
+                serde_json::to_vec(&record).map_err(|_| BlockStorageError::SerializationError)?;
+        }
 
         if record_json.len() > BLOCK_SIZE {
             return Err(BlockStorageError::SerializationError);
@@ -378,22 +1135,243 @@ impl<D: BlockDevice> BlockStorage<D> {
         Ok(())
     }
 
-    /// Allocate blocks for data
-    fn allocate_blocks(&mut self, size_bytes: u64) -> Result<Vec<u64>, BlockStorageError> {
+    /// Allocate extents for `size_bytes` of data
+    ///
+    /// Prefers one contiguous run and falls back to the lowest free blocks,
+    /// split into as few extents as they allow.
+    pub(crate) fn allocate_extents(
+        &mut self,
+        size_bytes: u64,
+    ) -> Result<Vec<Extent>, BlockStorageError> {
         let blocks_needed = (size_bytes as usize).div_ceil(BLOCK_SIZE) as u64;
+        if blocks_needed == 0 {
+            return Ok(Vec::new());
+        }
         if (self.free_blocks.len() as u64) < blocks_needed {
             return Err(BlockStorageError::NoFreeSpace);
         }
 
-        let mut allocated = Vec::new();
-        for _ in 0..blocks_needed {
-            if let Some(&block) = self.free_blocks.iter().next() {
-                self.free_blocks.remove(&block);
-                allocated.push(block);
+        let blocks: Vec<u64> = match self.find_free_run(blocks_needed, u64::MAX, &BTreeSet::new()) {
+            Some(start) => (start..start + blocks_needed).collect(),
+            None => self
+                .free_blocks
+                .iter()
+                .take(blocks_needed as usize)
+                .copied()
+                .collect(),
+        };
+
+        let mut extents: Vec<Extent> = Vec::new();
+        let mut remaining = size_bytes;
+        for block in blocks {
+            self.free_blocks.remove(&block);
+            let length = remaining.min(BLOCK_SIZE as u64);
+            remaining -= length;
+            push_extent(&mut extents, Extent::new(block, length));
+        }
+
+        Ok(extents)
+    }
+
+    /// Allocates and writes `data`, sharing indexed chunks when deduplicating
+    ///
+    /// New chunks are staged under `tx_id` and indexed when it commits.
+    pub(crate) fn store_data(
+        &mut self,
+        tx_id: TransactionId,
+        data: &[u8],
+    ) -> Result<Vec<Extent>, BlockStorageError> {
+        if !self.dedup {
+            let mut extents = self.allocate_extents(data.len() as u64)?;
+            self.write_extents(&mut extents, data)?;
+            return Ok(extents);
+        }
+
+        let mut extents = Vec::new();
+        // Chunks written by this call, as (offset in data, chunk)
+        let mut written: Vec<(usize, ChunkRef)> = Vec::new();
+        for (index, chunk) in data.chunks(DEDUP_CHUNK_SIZE).enumerate() {
+            let offset = index * DEDUP_CHUNK_SIZE;
+            let hash = crc32fast::hash(chunk);
+            let local = written.iter().find(|(at, written)| {
+                written.hash == hash
+                    && written.length == chunk.len() as u64
+                    && &data[*at..*at + chunk.len()] == chunk
+            });
+            let found = match local {
+                Some((_, written)) => Some(*written),
+                None => self.find_chunk(hash, chunk)?,
+            };
+            let new = match found {
+                Some(found) => found.extents(chunk),
+                None => {
+                    let mut new = self.allocate_extents(chunk.len() as u64)?;
+                    self.write_extents(&mut new, chunk)?;
+                    // Only chunks stored in one run of blocks are indexed
+                    let contiguous = new
+                        .windows(2)
+                        .all(|pair| pair[0].block_idx + pair[0].block_count() == pair[1].block_idx);
+                    if let (Some(first), true) = (new.first(), contiguous) {
+                        let chunk = ChunkRef {
+                            block_idx: first.block_idx,
+                            length: chunk.len() as u64,
+                            hash,
+                            refs: 0,
+                        };
+                        written.push((offset, chunk));
+                        self.pending_chunks.entry(tx_id).or_default().push(chunk);
+                    }
+                    new
+                }
+            };
+            for extent in new {
+                push_extent(&mut extents, extent);
+            }
+        }
+        Ok(extents)
+    }
+
+    /// Finds a committed chunk holding exactly `data`
+    fn find_chunk(
+        &mut self,
+        hash: u32,
+        data: &[u8],
+    ) -> Result<Option<ChunkRef>, BlockStorageError> {
+        let candidates: Vec<ChunkRef> = self
+            .chunks
+            .values()
+            .filter(|chunk| chunk.hash == hash && chunk.length == data.len() as u64)
+            .filter(|chunk| chunk.run().blocks().all(|b| !self.free_blocks.contains(&b)))
+            .copied()
+            .collect();
+        for chunk in candidates {
+            let blocks: Vec<u64> = chunk.run().blocks().collect();
+            if self.read_data(&blocks, chunk.length)? == data {
+                return Ok(Some(chunk));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns blocks to the free set unless something still references them
+    pub(crate) fn release_extents(&mut self, extents: &[Extent]) {
+        let referenced = self.referenced_blocks();
+        for block in extents.iter().flat_map(Extent::blocks) {
+            if !referenced.contains(&block) {
+                self.free_blocks.insert(block);
+            }
+        }
+    }
+
+    /// Write data into extents and record their checksums
+    ///
+    /// `data` must match the extents' total length.
+    pub(crate) fn write_extents(
+        &mut self,
+        extents: &mut [Extent],
+        data: &[u8],
+    ) -> Result<(), BlockStorageError> {
+        seal_extents(extents, data);
+        let mut offset = 0;
+        for extent in extents.iter() {
+            let blocks: Vec<u64> = extent.blocks().collect();
+            let end = offset + extent.length as usize;
+            self.write_data(&blocks, &data[offset..end])?;
+            offset = end;
+        }
+        Ok(())
+    }
+
+    /// Read `length` bytes starting at `offset` from an allocation
+    fn read_entry_range(
+        &mut self,
+        entry: &AllocationEntry,
+        offset: u64,
+        length: u64,
+    ) -> Result<Vec<u8>, BlockStorageError> {
+        let end = offset.saturating_add(length).min(entry.size_bytes);
+        let mut data = Vec::with_capacity(end.saturating_sub(offset) as usize);
+        let mut extent_start = 0;
+        for extent in entry.extent_list() {
+            let extent_end = extent_start + extent.length;
+            if extent_end > offset && extent_start < end {
+                let from = offset.max(extent_start) - extent_start;
+                let to = end.min(extent_end) - extent_start;
+                let read_from = data.len();
+                let block_size = BLOCK_SIZE as u64;
+                for block in (from / block_size)..to.div_ceil(block_size) {
+                    let mut buffer = [0u8; BLOCK_SIZE];
+                    self.device
+                        .read_block(extent.block_idx + block, &mut buffer)?;
+                    let block_start = block * block_size;
+                    let lo = from.max(block_start) - block_start;
+                    let hi = to.min(block_start + block_size) - block_start;
+                    data.extend_from_slice(&buffer[lo as usize..hi as usize]);
+                }
+                // Only an extent read in full can be checked
+                if let (Some(checksum), true) = (extent.checksum, from == 0 && to == extent.length)
+                {
+                    if crc32fast::hash(&data[read_from..]) != checksum {
+                        return Err(BlockStorageError::Corrupted {
+                            object_id: entry.object_id,
+                            version_id: entry.version_id,
+                            block_idx: extent.block_idx,
+                        });
+                    }
+                }
+            }
+            extent_start = extent_end;
+            if extent_start >= end {
+                break;
+            }
+        }
+        Ok(data)
+    }
+
+    /// Finds the lowest run of `blocks_needed` free blocks starting below
+    /// `below`, ignoring `claimed` blocks
+    fn find_free_run(
+        &self,
+        blocks_needed: u64,
+        below: u64,
+        claimed: &BTreeSet<u64>,
+    ) -> Option<u64> {
+        let mut run_start = 0;
+        let mut run_len = 0;
+        let mut previous: Option<u64> = None;
+        for &block in self.free_blocks.iter().filter(|b| !claimed.contains(b)) {
+            if previous.is_some_and(|p| p + 1 == block) {
+                run_len += 1;
+            } else {
+                if block >= below {
+                    break;
+                }
+                run_start = block;
+                run_len = 1;
+            }
+            previous = Some(block);
+            if run_len == blocks_needed {
+                return Some(run_start);
             }
         }
+        None
+    }
 
-        Ok(allocated)
+    /// Length of the longest run of free blocks
+    fn largest_free_extent(&self) -> u64 {
+        let mut longest = 0;
+        let mut run_len = 0;
+        let mut previous: Option<u64> = None;
+        for &block in &self.free_blocks {
+            run_len = if previous.is_some_and(|p| p + 1 == block) {
+                run_len + 1
+            } else {
+                1
+            };
+            longest = longest.max(run_len);
+            previous = Some(block);
+        }
+        longest
     }
 
     /// Write data to allocated blocks
@@ -435,28 +1413,534 @@ impl<D: BlockDevice> BlockStorage<D> {
         object_id: ObjectId,
         version_id: VersionId,
     ) -> Result<Vec<u8>, BlockStorageError> {
-        // Check pending writes first
-        for pending_list in self.pending.values() {
-            if let Some(entry) = pending_list
-                .iter()
-                .rev()
-                .find(|p| p.object_id == object_id && p.version_id == version_id)
-            {
-                return Ok(entry.data.clone());
+        self.read_object_range(object_id, version_id, 0, u64::MAX)
+    }
+
+    /// Read up to `length` bytes of a version starting at `offset`
+    ///
+    /// Only the blocks covering the range are read. A range past the end
+    /// is clamped, so reading at or beyond the size returns no data.
+    pub fn read_object_range(
+        &mut self,
+        object_id: ObjectId,
+        version_id: VersionId,
+        offset: u64,
+        length: u64,
+    ) -> Result<Vec<u8>, BlockStorageError> {
+        match self.find_version(object_id, version_id)? {
+            VersionData::Buffered(data) => {
+                let start = (offset.min(data.len() as u64)) as usize;
+                let end = (offset.saturating_add(length).min(data.len() as u64)) as usize;
+                Ok(data[start..end].to_vec())
             }
+            VersionData::OnDisk(entry) => self.read_entry_range(&entry, offset, length),
         }
-
-        // Look up in allocations
-        let entry = self
-            .allocations
-            .get(&(object_id, version_id))
-            .ok_or(BlockStorageError::ObjectNotFound)?;
-
-        // Calculate blocks needed
-        let blocks_needed = (entry.size_bytes as usize).div_ceil(BLOCK_SIZE) as u64;
-        let blocks: Vec<u64> = (entry.block_idx..entry.block_idx + blocks_needed).collect();
-
-        self.read_data(&blocks, entry.size_bytes)
+    }
+
+    /// Size in bytes of a version
+    pub fn object_size(
+        &self,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<u64, BlockStorageError> {
+        Ok(match self.find_version(object_id, version_id)? {
+            VersionData::Buffered(data) => data.len() as u64,
+            VersionData::OnDisk(entry) => entry.size_bytes,
+        })
+    }
+
+    /// Locates a version among pending and committed data
+    fn find_version(
+        &self,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<VersionData, BlockStorageError> {
+        // Check pending writes first
+        let pending = self.pending.values().flatten().rev().find(|change| {
+            change.object_id() == object_id && change.version_id() == Some(version_id)
+        });
+        match pending {
+            Some(PendingChange::Write(write)) => Ok(VersionData::Buffered(write.data.clone())),
+            Some(PendingChange::Written(entry)) => Ok(VersionData::OnDisk(entry.clone())),
+            _ => self
+                .allocations
+                .get(&(object_id, version_id))
+                .cloned()
+                .map(VersionData::OnDisk)
+                .ok_or(BlockStorageError::ObjectNotFound),
+        }
+    }
+
+    /// Opens a writer that streams a new version of `object_id`
+    pub fn stream_writer(
+        &mut self,
+        tx: &Transaction,
+        object_id: ObjectId,
+    ) -> Result<ObjectWriter<'_, D>, TransactionError> {
+        ObjectWriter::new(self, tx, object_id, Vec::new(), Vec::new())
+    }
+
+    /// Opens a writer whose version continues the object's current data
+    ///
+    /// The new version shares every full block of the previous one; only
+    /// the partial tail block is written again.
+    pub fn append_writer(
+        &mut self,
+        tx: &Transaction,
+        object_id: ObjectId,
+    ) -> Result<ObjectWriter<'_, D>, TransactionError> {
+        let (base, tail) = self
+            .append_base(tx.id(), object_id)
+            .map_err(|e| TransactionError::StorageError(format!("{:?}", e)))?;
+        ObjectWriter::new(self, tx, object_id, base, tail)
+    }
+
+    /// Appends data to an object as a new version, creating it if missing
+    pub fn append(
+        &mut self,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        data: &[u8],
+    ) -> Result<VersionId, TransactionError> {
+        let mut writer = self.append_writer(tx, object_id)?;
+        writer.write(data)?;
+        writer.finish()
+    }
+
+    /// Opens a reader over a version
+    pub fn reader(
+        &mut self,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<ObjectReader<'_, D>, BlockStorageError> {
+        ObjectReader::new(self, object_id, version_id)
+    }
+
+    /// Stages a version whose data a stream already wrote to disk
+    pub(crate) fn stage_written(&mut self, tx_id: TransactionId, entry: AllocationEntry) {
+        self.pending
+            .entry(tx_id)
+            .or_default()
+            .push(PendingChange::Written(entry));
+    }
+
+    /// Resolves what an append in `tx_id` builds on
+    ///
+    /// Returns the base's full-block extents (shared, never rewritten) and
+    /// the bytes of its partial tail block, which the new version rewrites.
+    pub(crate) fn append_base(
+        &mut self,
+        tx_id: TransactionId,
+        object_id: ObjectId,
+    ) -> Result<(Vec<Extent>, Vec<u8>), BlockStorageError> {
+        let in_tx = self
+            .pending
+            .get(&tx_id)
+            .and_then(|pending| pending.iter().rev().find(|p| p.object_id() == object_id))
+            .cloned();
+        let entry = match in_tx {
+            Some(PendingChange::Write(write)) => return Ok((Vec::new(), write.data)),
+            Some(PendingChange::Written(entry)) => entry,
+            Some(PendingChange::Delete(_)) => return Ok((Vec::new(), Vec::new())),
+            None => match self.latest_versions.get(&object_id) {
+                Some(version_id) => self.allocations[&(object_id, *version_id)].clone(),
+                None => return Ok((Vec::new(), Vec::new())),
+            },
+        };
+
+        let mut extents = entry.extent_list();
+        let mut tail = Vec::new();
+        if let Some(last) = extents.last_mut() {
+            let partial = last.length % BLOCK_SIZE as u64;
+            if partial > 0 {
+                let full = last.length - partial;
+                let mut block = [0u8; BLOCK_SIZE];
+                self.device
+                    .read_block(last.block_idx + full / BLOCK_SIZE as u64, &mut block)?;
+                tail.extend_from_slice(&block[..partial as usize]);
+                if full == 0 && last.checksum.is_some_and(|c| c != crc32fast::hash(&tail)) {
+                    return Err(BlockStorageError::Corrupted {
+                        object_id,
+                        version_id: entry.version_id,
+                        block_idx: last.block_idx,
+                    });
+                }
+                // Only older records merge a partial block into a longer extent
+                last.checksum = None;
+                last.length = full;
+            }
+        }
+        extents.retain(|extent| extent.length > 0);
+        Ok((extents, tail))
+    }
+
+    /// Sets the timestamp recorded on subsequent commits
+    ///
+    /// Used by [`RetentionPolicy::keep_since`]; callers pass the same clock
+    /// they use for other storage metadata.
+    pub fn set_commit_timestamp(&mut self, timestamp: u64) {
+        self.commit_timestamp = timestamp;
+    }
+
+    /// Returns the retention policy used by garbage collection
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        self.retention
+    }
+
+    /// Sets the retention policy used by garbage collection
+    pub fn set_retention_policy(&mut self, policy: RetentionPolicy) {
+        self.retention = policy;
+    }
+
+    /// Sequence number of the last commit
+    pub fn commit_sequence(&self) -> u64 {
+        self.superblock.commit_sequence
+    }
+
+    /// Keeps a version through garbage collection until it is unpinned
+    ///
+    /// Pins are counted and held in memory; owners such as snapshots
+    /// re-pin after reopening.
+    pub fn pin_version(&mut self, object_id: ObjectId, version_id: VersionId) {
+        *self.pinned.entry((object_id, version_id)).or_insert(0) += 1;
+    }
+
+    /// Releases one pin on a version
+    pub fn unpin_version(&mut self, object_id: ObjectId, version_id: VersionId) {
+        if let Some(count) = self.pinned.get_mut(&(object_id, version_id)) {
+            *count -= 1;
+            if *count == 0 {
+                self.pinned.remove(&(object_id, version_id));
+            }
+        }
+    }
+
+    /// Stages a new version of `target` that shares a committed version's blocks
+    ///
+    /// Nothing is copied; the new version diverges once it is written to.
+    pub fn clone_version(
+        &mut self,
+        tx: &mut Transaction,
+        source: ObjectId,
+        version_id: VersionId,
+        target: ObjectId,
+    ) -> Result<VersionId, TransactionError> {
+        if tx.state() != crate::transaction::TransactionState::Active {
+            return Err(TransactionError::AlreadyFinalized);
+        }
+        let old = self.allocations.get(&(source, version_id)).ok_or_else(|| {
+            TransactionError::ObjectNotFound(format!("{} at version {}", source, version_id))
+        })?;
+
+        let new_version = VersionId::new();
+        let entry = AllocationEntry::from_extents(target, new_version, old.extent_list());
+        self.stage_written(tx.id(), entry);
+        Ok(new_version)
+    }
+
+    /// Number of free data blocks
+    pub fn free_block_count(&self) -> u64 {
+        self.free_blocks.len() as u64
+    }
+
+    /// Number of transactions holding staged, uncommitted changes
+    pub fn pending_transaction_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Number of versions currently pinned against garbage collection
+    pub fn pinned_version_count(&self) -> usize {
+        self.pinned.len()
+    }
+
+    /// Whether new data is stored as content-addressed chunks
+    pub fn deduplication(&self) -> bool {
+        self.dedup
+    }
+
+    /// Enables or disables content-addressed storage of new data
+    ///
+    /// Existing data is not rewritten; disabling keeps sharing what is
+    /// already shared.
+    pub fn set_deduplication(&mut self, enabled: bool) {
+        self.dedup = enabled;
+    }
+
+    /// Logical versus physical space used by committed versions
+    pub fn stats(&self) -> StorageStats {
+        let blocks: BTreeSet<u64> = self
+            .allocations
+            .values()
+            .flat_map(AllocationEntry::blocks)
+            .collect();
+        StorageStats {
+            versions: self.allocations.len(),
+            logical_bytes: self.allocations.values().map(|a| a.size_bytes).sum(),
+            physical_bytes: blocks.len() as u64 * BLOCK_SIZE as u64,
+            chunks: self.chunks.len(),
+            shared_chunks: self.chunks.values().filter(|c| c.refs > 1).count(),
+            quarantined_blocks: self.quarantined.len() as u64,
+        }
+    }
+
+    /// Committed versions in allocation order
+    pub(crate) fn version_keys(&self) -> Vec<(ObjectId, VersionId)> {
+        self.allocations.keys().copied().collect()
+    }
+
+    /// Whether a version is the latest of its object
+    pub(crate) fn is_latest(&self, object_id: ObjectId, version_id: VersionId) -> bool {
+        self.latest_versions.get(&object_id) == Some(&version_id)
+    }
+
+    /// Reads a committed version in full, verifying every extent
+    ///
+    /// Returns the number of bytes checked.
+    pub(crate) fn verify_version(
+        &mut self,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<u64, BlockStorageError> {
+        let entry = self
+            .allocations
+            .get(&(object_id, version_id))
+            .cloned()
+            .ok_or(BlockStorageError::ObjectNotFound)?;
+        self.read_entry_range(&entry, 0, entry.size_bytes)?;
+        Ok(entry.size_bytes)
+    }
+
+    /// Quarantines the blocks of the extent starting at `block_idx`
+    ///
+    /// They stay out of the free set even once nothing references them,
+    /// and chunks overlapping them are dropped from the dedup index.
+    /// Returns the number of newly quarantined blocks.
+    pub(crate) fn quarantine_extent(
+        &mut self,
+        object_id: ObjectId,
+        version_id: VersionId,
+        block_idx: u64,
+    ) -> u64 {
+        let Some(extent) = self
+            .allocations
+            .get(&(object_id, version_id))
+            .and_then(|a| {
+                a.extent_list()
+                    .into_iter()
+                    .find(|extent| extent.block_idx == block_idx)
+            })
+        else {
+            return 0;
+        };
+        let before = self.quarantined.len();
+        self.quarantined.extend(extent.blocks());
+        let quarantined = &self.quarantined;
+        self.chunks.retain(|_, chunk| {
+            !chunk
+                .run()
+                .blocks()
+                .any(|block| quarantined.contains(&block))
+        });
+        (self.quarantined.len() - before) as u64
+    }
+
+    /// Persists the quarantine with a checkpoint of the live allocations
+    pub(crate) fn checkpoint_quarantine(&mut self) -> Result<u64, BlockStorageError> {
+        let sequence = self.superblock.commit_sequence;
+        let live = self.live_allocations();
+        self.write_checkpoint(sequence, live)?;
+        Ok(sequence)
+    }
+
+    /// Frees versions outside the retention policy and leaked blocks
+    ///
+    /// The surviving allocations are checkpointed before any block is
+    /// returned to the free set, so a crash mid-collection reopens with
+    /// either the old or the new set of versions, never a mix.
+    pub fn collect_garbage(&mut self) -> Result<StorageGcReport, BlockStorageError> {
+        let mut doomed = BTreeSet::new();
+        let mut bytes_reclaimed = 0;
+        for (object_id, versions) in &self.history {
+            for (index, version_id) in versions.iter().enumerate() {
+                let Some(alloc) = self.allocations.get(&(*object_id, *version_id)) else {
+                    continue;
+                };
+                let pinned = self.pinned.contains_key(&(*object_id, *version_id));
+                if !pinned
+                    && !self
+                        .retention
+                        .retains(index, versions.len(), alloc.committed_at)
+                {
+                    doomed.insert((*object_id, *version_id));
+                    bytes_reclaimed += alloc.size_bytes;
+                }
+            }
+        }
+
+        let live: Vec<AllocationEntry> = self
+            .live_allocations()
+            .into_iter()
+            .filter(|alloc| !doomed.contains(&(alloc.object_id, alloc.version_id)))
+            .collect();
+        let free_before = self.free_block_count();
+        let referenced = self.referenced_blocks();
+        let leaked = (self.superblock.data_start..self.superblock.total_blocks).any(|block| {
+            !referenced.contains(&block)
+                && !self.free_blocks.contains(&block)
+                && !self.quarantined.contains(&block)
+        });
+        if doomed.is_empty() && !leaked {
+            return Ok(StorageGcReport {
+                versions_collected: 0,
+                blocks_freed: 0,
+                bytes_reclaimed: 0,
+                checkpoint_sequence: None,
+            });
+        }
+
+        // Point of no return: the checkpoint no longer references the doomed versions
+        let sequence = self.superblock.commit_sequence;
+        self.write_checkpoint(sequence, live)?;
+
+        for key in &doomed {
+            self.allocations.remove(key);
+        }
+        for (object_id, versions) in self.history.iter_mut() {
+            versions.retain(|version_id| !doomed.contains(&(*object_id, *version_id)));
+        }
+        self.rebuild_free_blocks();
+
+        Ok(StorageGcReport {
+            versions_collected: doomed.len(),
+            blocks_freed: self.free_block_count() - free_before,
+            bytes_reclaimed,
+            checkpoint_sequence: Some(sequence),
+        })
+    }
+
+    /// Moves live data towards the start of the data area
+    ///
+    /// Each pass only copies into blocks that were already free, then
+    /// checkpoints the new locations before releasing the old ones; passes
+    /// repeat until nothing can move lower. Blocks shared between versions
+    /// move together; blocks staged by open transactions or quarantined
+    /// stay put.
+    pub fn compact(&mut self) -> Result<StorageCompactionReport, BlockStorageError> {
+        let largest_free_extent_before = self.largest_free_extent();
+        let mut extents_moved = 0;
+        let mut blocks_moved = 0;
+        let mut passes = 0;
+
+        loop {
+            let pinned: BTreeSet<u64> = self
+                .pending_written()
+                .flat_map(AllocationEntry::blocks)
+                .chain(self.quarantined.iter().copied())
+                .collect();
+            let mut claimed = BTreeSet::new();
+            let mut moves: BTreeMap<u64, (u64, u64)> = BTreeMap::new();
+            for (start, len) in self.block_runs() {
+                if (start..start + len).any(|block| pinned.contains(&block)) {
+                    continue;
+                }
+                if let Some(target) = self.find_free_run(len, start, &claimed) {
+                    claimed.extend(target..target + len);
+                    moves.insert(start, (len, target));
+                }
+            }
+            if moves.is_empty() {
+                break;
+            }
+
+            // Copy into free blocks; the old copies stay valid until the checkpoint lands
+            for (&start, &(len, target)) in &moves {
+                let mut block = [0u8; BLOCK_SIZE];
+                for i in 0..len {
+                    self.device.read_block(start + i, &mut block)?;
+                    self.device.write_block(target + i, &block)?;
+                }
+                blocks_moved += len;
+            }
+            self.device.flush()?;
+
+            let relocate = |block: u64| match moves.range(..=block).next_back() {
+                Some((&start, &(len, target))) if block < start + len => target + (block - start),
+                _ => block,
+            };
+            let remap = |alloc: &mut AllocationEntry| {
+                let extents = alloc
+                    .extent_list()
+                    .into_iter()
+                    .map(|mut extent| {
+                        extent.block_idx = relocate(extent.block_idx);
+                        extent
+                    })
+                    .collect();
+                alloc.set_extents(extents);
+            };
+
+            // Chunks lie within one run, so they move with their first block
+            self.chunks = core::mem::take(&mut self.chunks)
+                .into_values()
+                .map(|mut chunk| {
+                    chunk.block_idx = relocate(chunk.block_idx);
+                    (chunk.block_idx, chunk)
+                })
+                .collect();
+            let mut live = self.live_allocations();
+            live.iter_mut().for_each(remap);
+            let sequence = self.superblock.commit_sequence;
+            self.write_checkpoint(sequence, live)?;
+
+            self.allocations.values_mut().for_each(remap);
+            self.rebuild_free_blocks();
+            extents_moved += moves.len();
+            passes += 1;
+        }
+
+        Ok(StorageCompactionReport {
+            extents_moved,
+            blocks_moved,
+            passes,
+            largest_free_extent_before,
+            largest_free_extent_after: self.largest_free_extent(),
+        })
+    }
+
+    /// Committed blocks grouped into runs that must move together
+    ///
+    /// Extents sharing blocks are merged, so a run is `(start, len)` over
+    /// the union of overlapping extents. A version's back-to-back extents
+    /// (such as its partial tail block) also stay together.
+    fn block_runs(&self) -> Vec<(u64, u64)> {
+        let mut ranges: Vec<core::ops::Range<u64>> = Vec::new();
+        for alloc in self.allocations.values() {
+            let mut previous: Option<core::ops::Range<u64>> = None;
+            for range in alloc.extent_list().iter().map(Extent::blocks) {
+                previous = match previous {
+                    Some(prev) if prev.end == range.start => Some(prev.start..range.end),
+                    Some(prev) => {
+                        ranges.push(prev);
+                        Some(range)
+                    }
+                    None => Some(range),
+                };
+            }
+            ranges.extend(previous);
+        }
+        ranges.retain(|range| !range.is_empty());
+        ranges.sort_by_key(|range| range.start);
+
+        let mut runs: Vec<(u64, u64)> = Vec::new();
+        for range in ranges {
+            match runs.last_mut() {
+                Some((start, len)) if range.start < *start + *len => {
+                    *len = (*len).max(range.end - *start);
+                }
+                _ => runs.push((range.start, range.end - range.start)),
+            }
+        }
+        runs
     }
 }
 
@@ -470,10 +1954,15 @@ impl<D: BlockDevice> TransactionalStorage for BlockStorage<D> {
             return Err(TransactionError::AlreadyFinalized);
         }
 
-        // Check pending writes first
+        // Check pending changes first
         if let Some(pending) = self.pending.get(&tx.id()) {
-            if let Some(entry) = pending.iter().rev().find(|p| p.object_id == object_id) {
-                return Ok(entry.version_id);
+            match pending.iter().rev().find(|p| p.object_id() == object_id) {
+                Some(PendingChange::Write(write)) => return Ok(write.version_id),
+                Some(PendingChange::Written(entry)) => return Ok(entry.version_id),
+                Some(PendingChange::Delete(_)) => {
+                    return Err(TransactionError::ObjectNotFound(object_id.to_string()))
+                }
+                None => {}
             }
         }
 
@@ -497,55 +1986,109 @@ impl<D: BlockDevice> TransactionalStorage for BlockStorage<D> {
         let version_id = VersionId::new();
 
         // Add to pending writes
-        self.pending.entry(tx.id()).or_default().push(PendingWrite {
-            object_id,
-            version_id,
-            data: data.to_vec(),
-        });
+        self.pending
+            .entry(tx.id())
+            .or_default()
+            .push(PendingChange::Write(PendingWrite {
+                object_id,
+                version_id,
+                data: data.to_vec(),
+            }));
 
         Ok(version_id)
     }
 
+    fn delete(
+        &mut self,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+    ) -> Result<(), TransactionError> {
+        // Fails for finalized transactions and objects that do not exist
+        self.read(tx, object_id)?;
+
+        self.pending
+            .entry(tx.id())
+            .or_default()
+            .push(PendingChange::Delete(object_id));
+
+        Ok(())
+    }
+
     fn commit(&mut self, tx: &mut Transaction) -> Result<(), TransactionError> {
         if tx.state() != crate::transaction::TransactionState::Active {
             return Err(TransactionError::AlreadyFinalized);
         }
 
-        // Write all pending writes to disk
+        // Write all pending changes to disk
         if let Some(pending) = self.pending.remove(&tx.id()) {
             let mut allocations_to_commit = Vec::new();
 
-            // Step 1: Write all data blocks
-            for write in pending {
-                let size_bytes = write.data.len() as u64;
-                let blocks = self
-                    .allocate_blocks(size_bytes)
-                    .map_err(|e| TransactionError::StorageError(format!("{:?}", e)))?;
-
-                let first_block = blocks[0];
-                self.write_data(&blocks, &write.data)
-                    .map_err(|e| TransactionError::StorageError(format!("{:?}", e)))?;
-
-                let alloc = AllocationEntry {
-                    object_id: write.object_id,
-                    version_id: write.version_id,
-                    block_idx: first_block,
-                    size_bytes,
+            // A delete wipes the object, including writes made earlier in this
+            // transaction; tombstones are applied before the surviving writes
+            let mut tombstones: Vec<ObjectId> = Vec::new();
+            let mut writes = Vec::new();
+            for change in pending {
+                match change {
+                    PendingChange::Delete(object_id) => {
+                        writes.retain(|write: &PendingChange| write.object_id() != object_id);
+                        if !tombstones.contains(&object_id) {
+                            tombstones.push(object_id);
+                        }
+                    }
+                    change => writes.push(change),
+                }
+            }
+
+            // Step 1: Write all data blocks (streamed versions are already on disk)
+            for change in writes {
+                let mut alloc = match change {
+                    PendingChange::Write(write) => match self.store_data(tx.id(), &write.data) {
+                        Ok(extents) => AllocationEntry::from_extents(
+                            write.object_id,
+                            write.version_id,
+                            extents,
+                        ),
+                        Err(e) => {
+                            // Release extents stored for earlier writes
+                            self.rebuild_free_blocks();
+                            return Err(TransactionError::StorageError(format!("{:?}", e)));
+                        }
+                    },
+                    PendingChange::Written(entry) => entry,
+                    PendingChange::Delete(_) => continue,
                 };
+                alloc.committed_at = self.commit_timestamp;
+                alloc.transaction_id = Some(tx.id());
 
                 allocations_to_commit.push(alloc);
             }
 
             // Step 2: Write commit record (atomic point of truth)
-            self.write_commit_record(tx.id(), allocations_to_commit.clone())
-                .map_err(|e| TransactionError::StorageError(format!("{:?}", e)))?;
+            let chunks = self.commit_chunks(tx.id(), &allocations_to_commit, &tombstones);
+            if let Err(e) = self.write_commit_record(
+                tx.id(),
+                allocations_to_commit.clone(),
+                tombstones.clone(),
+                chunks.clone(),
+            ) {
+                // Nothing references the blocks written in step 1
+                self.rebuild_free_blocks();
+                return Err(TransactionError::StorageError(format!("{:?}", e)));
+            }
 
             // Step 3: Update in-memory state (only after commit record is written)
+            let deleted = !tombstones.is_empty();
+            for object_id in tombstones {
+                self.remove_object(object_id);
+            }
             for alloc in allocations_to_commit {
-                self.allocations
-                    .insert((alloc.object_id, alloc.version_id), alloc.clone());
-                self.latest_versions
-                    .insert(alloc.object_id, alloc.version_id);
+                self.apply_allocation(alloc);
+            }
+            for chunk in chunks {
+                self.chunks.insert(chunk.block_idx, chunk);
+            }
+            if deleted {
+                self.rebuild_free_blocks();
             }
         }
 
@@ -558,16 +2101,68 @@ impl<D: BlockDevice> TransactionalStorage for BlockStorage<D> {
             return Err(TransactionError::AlreadyFinalized);
         }
 
-        // Discard pending writes
-        self.pending.remove(&tx.id());
+        // Discard pending writes and release blocks staged by streams
+        self.pending_chunks.remove(&tx.id());
+        if self.pending.remove(&tx.id()).is_some() {
+            self.rebuild_free_blocks();
+        }
         let _ = tx.rollback();
         Ok(())
     }
 }
 
+impl<D: BlockDevice> VersionHistory for BlockStorage<D> {
+    fn versions(&self, object_id: ObjectId) -> Result<Vec<VersionInfo>, TransactionError> {
+        let versions = self
+            .history
+            .get(&object_id)
+            .ok_or_else(|| TransactionError::ObjectNotFound(object_id.to_string()))?;
+        Ok(versions
+            .iter()
+            .filter_map(|version_id| self.allocations.get(&(object_id, *version_id)))
+            .map(|alloc| VersionInfo {
+                version_id: alloc.version_id,
+                transaction_id: alloc.transaction_id,
+                committed_at: alloc.committed_at,
+                size_bytes: alloc.size_bytes,
+            })
+            .collect())
+    }
+
+    fn read_version(
+        &mut self,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<Vec<u8>, TransactionError> {
+        if !self.allocations.contains_key(&(object_id, version_id)) {
+            return Err(TransactionError::ObjectNotFound(format!(
+                "{} at version {}",
+                object_id, version_id
+            )));
+        }
+        self.read_object_data(object_id, version_id)
+            .map_err(|e| match e {
+                BlockStorageError::Corrupted { .. } => e.into(),
+                e => TransactionError::StorageError(format!("{:?}", e)),
+            })
+    }
+
+    /// Restores without copying: the new version shares the old one's blocks
+    fn restore_version(
+        &mut self,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<VersionId, TransactionError> {
+        self.clone_version(tx, object_id, version_id, object_id)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scrub::{DamagedVersion, Scrubber};
+    use crate::stream::STREAM_CHUNK_SIZE;
     use alloc::format;
     use alloc::vec;
     use hal::RamDisk;
@@ -809,9 +2404,14 @@ mod tests {
             version_id: VersionId::new(),
             block_idx: 100,
             size_bytes: 128,
+            committed_at: 0,
+            extents: Vec::new(),
+            transaction_id: None,
+            checksum: None,
         };
 
-        let mut record = CommitRecord::new(TransactionId::new(), 1, vec![alloc]);
+        let mut record =
+            CommitRecord::new(TransactionId::new(), 1, vec![alloc], Vec::new(), Vec::new());
 
         // Record should be valid initially
         assert!(record.is_valid());
@@ -842,6 +2442,753 @@ mod tests {
         assert_eq!(storage.superblock.commit_sequence, (log_size + 5) as u64);
     }
 
+    fn commit_object(
+        storage: &mut BlockStorage<impl BlockDevice>,
+        obj: ObjectId,
+        data: &[u8],
+    ) -> VersionId {
+        let mut tx = storage.begin_transaction().unwrap();
+        let version = storage.write(&mut tx, obj, data).unwrap();
+        storage.commit(&mut tx).unwrap();
+        version
+    }
+
+    #[test]
+    fn test_gc_frees_superseded_versions() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        let free_at_start = storage.free_block_count();
+
+        let obj = ObjectId::new();
+        let big = vec![7u8; BLOCK_SIZE * 2];
+        let v1 = commit_object(&mut storage, obj, &big);
+        let v2 = commit_object(&mut storage, obj, &big);
+        let v3 = commit_object(&mut storage, obj, b"latest");
+        assert_eq!(storage.free_block_count(), free_at_start - 5);
+
+        let report = storage.collect_garbage().unwrap();
+        assert_eq!(report.versions_collected, 2);
+        assert_eq!(report.blocks_freed, 4);
+        assert_eq!(report.bytes_reclaimed, (BLOCK_SIZE * 4) as u64);
+        assert_eq!(storage.free_block_count(), free_at_start - 1);
+
+        assert!(matches!(
+            storage.read_object_data(obj, v1),
+            Err(BlockStorageError::ObjectNotFound)
+        ));
+        assert!(storage.read_object_data(obj, v2).is_err());
+        assert_eq!(storage.read_object_data(obj, v3).unwrap(), b"latest");
+
+        // Nothing left to collect
+        let report = storage.collect_garbage().unwrap();
+        assert_eq!(report.versions_collected, 0);
+        assert_eq!(report.checkpoint_sequence, None);
+    }
+
+    #[test]
+    fn test_gc_retention_policies() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+
+        let obj = ObjectId::new();
+        let versions: Vec<VersionId> = (0..5u64)
+            .map(|i| {
+                storage.set_commit_timestamp(i * 100);
+                commit_object(&mut storage, obj, format!("v{}", i).as_bytes())
+            })
+            .collect();
+
+        // A version survives if either rule keeps it
+        storage.set_retention_policy(RetentionPolicy::keep_last(2).with_keep_since(200));
+        let report = storage.collect_garbage().unwrap();
+        assert_eq!(report.versions_collected, 2);
+        assert!(storage.read_object_data(obj, versions[1]).is_err());
+        assert!(storage.read_object_data(obj, versions[2]).is_ok());
+
+        storage.set_retention_policy(RetentionPolicy::keep_last(2));
+        assert_eq!(storage.collect_garbage().unwrap().versions_collected, 1);
+        assert!(storage.read_object_data(obj, versions[2]).is_err());
+        assert!(storage.read_object_data(obj, versions[3]).is_ok());
+
+        storage.set_retention_policy(RetentionPolicy::keep_all());
+        assert_eq!(storage.collect_garbage().unwrap().versions_collected, 0);
+    }
+
+    #[test]
+    fn test_gc_survives_reopen() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+
+        let obj = ObjectId::new();
+        let old = commit_object(&mut storage, obj, b"old");
+        let new = commit_object(&mut storage, obj, b"new");
+        storage.collect_garbage().unwrap();
+        let other = ObjectId::new();
+        commit_object(&mut storage, other, b"after gc");
+        let free = storage.free_block_count();
+
+        let mut recovered = BlockStorage::open(storage.device).unwrap();
+        let report = recovered.recovery_report().unwrap();
+        assert_eq!(report.checkpoint_sequence, Some(2));
+        assert_eq!(report.recovered_commits, 1);
+        assert_eq!(recovered.free_block_count(), free);
+        assert!(recovered.read_object_data(obj, old).is_err());
+        assert_eq!(recovered.read_object_data(obj, new).unwrap(), b"new");
+        let tx = recovered.begin_transaction().unwrap();
+        assert!(recovered.read(&tx, other).is_ok());
+    }
+
+    #[test]
+    fn test_crash_during_gc_keeps_old_versions() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let failing_disk = FailingBlockDevice::new(disk, FailurePolicy::Never);
+        let mut storage = BlockStorage::format(failing_disk).unwrap();
+
+        let obj = ObjectId::new();
+        let old = commit_object(&mut storage, obj, b"old");
+        commit_object(&mut storage, obj, b"new");
+
+        // Fail the checkpoint write
+        let metadata: Vec<u64> = (storage.superblock.bitmap_start
+            ..storage.superblock.bitmap_start + storage.superblock.bitmap_blocks)
+            .collect();
+        storage.device.set_policy(FailurePolicy::OnBlocks(metadata));
+        assert!(storage.collect_garbage().is_err());
+        assert_eq!(storage.read_object_data(obj, old).unwrap(), b"old");
+
+        storage.device.set_policy(FailurePolicy::Never);
+        let mut recovered = BlockStorage::open(storage.device).unwrap();
+        assert_eq!(recovered.read_object_data(obj, old).unwrap(), b"old");
+    }
+
+    #[test]
+    fn test_small_device_commits_past_log_wrap() {
+        let disk = RamDisk::new(16);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        assert!(storage.checkpoint_slot_blocks() >= 1);
+        let log_size = storage.superblock.commit_log_blocks as usize;
+
+        let objects: Vec<(ObjectId, VersionId)> = (0..log_size + 3)
+            .map(|i| {
+                let obj = ObjectId::new();
+                (
+                    obj,
+                    commit_object(&mut storage, obj, format!("small_{}", i).as_bytes()),
+                )
+            })
+            .collect();
+
+        let mut recovered = BlockStorage::open(storage.device).unwrap();
+        for (i, (obj, version)) in objects.iter().enumerate() {
+            let data = recovered.read_object_data(*obj, *version).unwrap();
+            assert_eq!(data, format!("small_{}", i).as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_open_rejects_older_format_version() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        let mut superblock = storage.superblock.clone();
+        superblock.version = 2;
+        let mut block = [0u8; BLOCK_SIZE];
+        let sb_json = serde_json::to_vec(&superblock).unwrap();
+        block[..sb_json.len()].copy_from_slice(&sb_json);
+        storage.device.write_block(0, &block).unwrap();
+
+        match BlockStorage::open(storage.into_device()) {
+            Err(BlockStorageError::UnsupportedVersion { found, expected }) => {
+                assert_eq!(found, 2);
+                assert_eq!(expected, STORAGE_VERSION);
+            }
+            other => panic!("expected UnsupportedVersion, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_commit_fails_once_checkpoint_outgrows_slot() {
+        let disk = RamDisk::new(40);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        assert_eq!(storage.checkpoint_slot_blocks(), 1);
+
+        let mut committed = Vec::new();
+        let overflow = loop {
+            let obj = ObjectId::new();
+            let mut tx = storage.begin_transaction().unwrap();
+            let version = storage.write(&mut tx, obj, b"entry").unwrap();
+            match storage.commit(&mut tx) {
+                Ok(()) => committed.push((obj, version)),
+                Err(err) => {
+                    storage.rollback(&mut tx).unwrap();
+                    break (obj, err);
+                }
+            }
+        };
+        assert_eq!(
+            overflow.1,
+            TransactionError::StorageError("CheckpointTooLarge".to_string())
+        );
+        // The last checkpoint that fit still covers every earlier commit
+        assert!(!committed.is_empty());
+        // The failed commit's data block went back to the free set
+        let data_blocks = storage.superblock.total_blocks - storage.superblock.data_start;
+        assert_eq!(
+            storage.free_block_count(),
+            data_blocks - committed.len() as u64
+        );
+
+        let mut recovered = BlockStorage::open(storage.into_device()).unwrap();
+        for (obj, version) in &committed {
+            assert_eq!(
+                recovered.read_object_data(*obj, *version).unwrap(),
+                b"entry"
+            );
+        }
+        let tx = recovered.begin_transaction().unwrap();
+        assert!(recovered.read(&tx, overflow.0).is_err());
+    }
+
+    #[test]
+    fn test_commit_log_wrap_keeps_all_objects_across_open() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        let log_size = storage.superblock.commit_log_blocks as usize;
+
+        let objects: Vec<(ObjectId, VersionId)> = (0..log_size * 2 + 3)
+            .map(|i| {
+                let obj = ObjectId::new();
+                (
+                    obj,
+                    commit_object(&mut storage, obj, format!("data_{}", i).as_bytes()),
+                )
+            })
+            .collect();
+        let free = storage.free_block_count();
+
+        let mut recovered = BlockStorage::open(storage.device).unwrap();
+        assert_eq!(recovered.free_block_count(), free);
+        for (i, (obj, version)) in objects.iter().enumerate() {
+            let data = recovered.read_object_data(*obj, *version).unwrap();
+            assert_eq!(data, format!("data_{}", i).as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_compaction_merges_free_space() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+
+        // Interleave short-lived and long-lived objects, then drop the short-lived ones
+        let mut keep = Vec::new();
+        for i in 0..6u8 {
+            let scratch = ObjectId::new();
+            commit_object(&mut storage, scratch, &vec![i; BLOCK_SIZE * 2]);
+            commit_object(&mut storage, scratch, b"");
+            let obj = ObjectId::new();
+            let data = vec![i + 100; BLOCK_SIZE + 1];
+            keep.push((obj, commit_object(&mut storage, obj, &data), data));
+        }
+        storage.collect_garbage().unwrap();
+
+        let report = storage.compact().unwrap();
+        assert!(report.extents_moved >= keep.len());
+        assert_eq!(report.blocks_moved, report.extents_moved as u64 * 2);
+        assert!(report.largest_free_extent_after > report.largest_free_extent_before);
+        assert_eq!(report.largest_free_extent_after, storage.free_block_count());
+        assert_eq!(storage.compact().unwrap().extents_moved, 0);
+
+        let mut recovered = BlockStorage::open(storage.device).unwrap();
+        for (obj, version, data) in &keep {
+            assert_eq!(&recovered.read_object_data(*obj, *version).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_delete_frees_blocks_and_survives_reopen() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        let free_at_start = storage.free_block_count();
+
+        let doomed = ObjectId::new();
+        let kept = ObjectId::new();
+        commit_object(&mut storage, doomed, &vec![1u8; BLOCK_SIZE * 3]);
+        commit_object(&mut storage, kept, b"kept");
+
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.delete(&mut tx, doomed).unwrap();
+        assert!(storage.read(&tx, doomed).is_err());
+        storage.commit(&mut tx).unwrap();
+        assert_eq!(storage.free_block_count(), free_at_start - 1);
+
+        let tx = storage.begin_transaction().unwrap();
+        assert!(matches!(
+            storage.read(&tx, doomed),
+            Err(TransactionError::ObjectNotFound(_))
+        ));
+
+        let mut recovered = BlockStorage::open(storage.device).unwrap();
+        assert_eq!(recovered.free_block_count(), free_at_start - 1);
+        let tx = recovered.begin_transaction().unwrap();
+        assert!(recovered.read(&tx, doomed).is_err());
+        assert!(recovered.read(&tx, kept).is_ok());
+    }
+
+    fn dedup_payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_dedup_shares_identical_chunks() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        storage.set_deduplication(true);
+        let free_at_start = storage.free_block_count();
+        let payload = dedup_payload(DEDUP_CHUNK_SIZE * 2 + 100);
+
+        let (a, b, c, d) = (
+            ObjectId::new(),
+            ObjectId::new(),
+            ObjectId::new(),
+            ObjectId::new(),
+        );
+        commit_object(&mut storage, a, &payload);
+        let free = storage.free_block_count();
+        let version = commit_object(&mut storage, b, &payload);
+        assert_eq!(storage.free_block_count(), free);
+        assert_eq!(storage.read_object_data(b, version).unwrap(), payload);
+
+        let stats = storage.stats();
+        assert_eq!(stats.logical_bytes, 2 * payload.len() as u64);
+        assert_eq!(
+            stats.physical_bytes,
+            payload.len().div_ceil(BLOCK_SIZE) as u64 * BLOCK_SIZE as u64
+        );
+        assert_eq!(stats.chunks, 3);
+        assert_eq!(stats.shared_chunks, 3);
+
+        // Streamed writes split at the same boundaries
+        let mut tx = storage.begin_transaction().unwrap();
+        let mut writer = storage.stream_writer(&tx, c).unwrap();
+        writer.write(&payload).unwrap();
+        writer.finish().unwrap();
+        storage.commit(&mut tx).unwrap();
+        assert_eq!(storage.free_block_count(), free);
+
+        let mut storage = BlockStorage::open(storage.device).unwrap();
+        storage.set_deduplication(true);
+        assert_eq!(storage.stats().shared_chunks, 3);
+        commit_object(&mut storage, d, &payload);
+        assert_eq!(storage.free_block_count(), free);
+
+        let mut tx = storage.begin_transaction().unwrap();
+        for obj in [a, b, c] {
+            storage.delete(&mut tx, obj).unwrap();
+        }
+        storage.commit(&mut tx).unwrap();
+        assert_eq!(storage.free_block_count(), free);
+        assert_eq!(storage.stats().shared_chunks, 0);
+
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.delete(&mut tx, d).unwrap();
+        storage.commit(&mut tx).unwrap();
+        assert_eq!(storage.free_block_count(), free_at_start);
+        assert_eq!(storage.stats().chunks, 0);
+    }
+
+    #[test]
+    fn test_dedup_is_opt_in_and_survives_compaction() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        let payload = dedup_payload(BLOCK_SIZE * 2);
+
+        commit_object(&mut storage, ObjectId::new(), &payload);
+        let free = storage.free_block_count();
+        commit_object(&mut storage, ObjectId::new(), &payload);
+        assert_eq!(storage.free_block_count(), free - 2);
+        assert_eq!(storage.stats().chunks, 0);
+
+        storage.set_deduplication(true);
+        let filler = ObjectId::new();
+        commit_object(&mut storage, filler, &[7u8; BLOCK_SIZE * 4]);
+        let obj = ObjectId::new();
+        let version = commit_object(&mut storage, obj, b"unique chunk");
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.delete(&mut tx, filler).unwrap();
+        storage.commit(&mut tx).unwrap();
+        assert!(storage.compact().unwrap().blocks_moved > 0);
+
+        let free = storage.free_block_count();
+        let copy = ObjectId::new();
+        let copy_version = commit_object(&mut storage, copy, b"unique chunk");
+        assert_eq!(storage.free_block_count(), free);
+        assert_eq!(
+            storage.read_object_data(obj, version).unwrap(),
+            b"unique chunk"
+        );
+
+        let mut storage = BlockStorage::open(storage.device).unwrap();
+        assert_eq!(storage.stats().shared_chunks, 1);
+        assert_eq!(
+            storage.read_object_data(copy, copy_version).unwrap(),
+            b"unique chunk"
+        );
+    }
+
+    #[test]
+    fn test_dedup_index_larger_than_commit_record() {
+        let disk = RamDisk::with_capacity_mb(16);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        storage.set_deduplication(true);
+        let payload = dedup_payload(DEDUP_CHUNK_SIZE * 80);
+
+        let first = ObjectId::new();
+        commit_object(&mut storage, first, &payload);
+        let free = storage.free_block_count();
+        let copy = ObjectId::new();
+        let version = commit_object(&mut storage, copy, &payload);
+        assert_eq!(storage.free_block_count(), free);
+
+        let mut storage = BlockStorage::open(storage.device).unwrap();
+        assert_eq!(storage.read_object_data(copy, version).unwrap(), payload);
+        assert_eq!(storage.free_block_count(), free);
+    }
+
+    /// Storage whose next data write to the lowest free block is corrupted
+    fn corrupting_storage() -> BlockStorage<FailingBlockDevice<RamDisk>> {
+        let disk = FailingBlockDevice::new(RamDisk::with_capacity_mb(1), FailurePolicy::Never);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        let next = *storage.free_blocks.first().unwrap();
+        storage
+            .device
+            .set_policy(FailurePolicy::CorruptBlocks(vec![next]));
+        storage
+    }
+
+    #[test]
+    fn test_corrupted_extent_is_detected_on_read() {
+        let mut storage = corrupting_storage();
+        let obj = ObjectId::new();
+        let version = commit_object(&mut storage, obj, &vec![9u8; BLOCK_SIZE * 2 + 10]);
+        storage.device.set_policy(FailurePolicy::Never);
+
+        assert!(matches!(
+            storage.read_object_data(obj, version),
+            Err(BlockStorageError::Corrupted { object_id, .. }) if object_id == obj
+        ));
+        assert!(matches!(
+            storage.read_version(obj, version),
+            Err(TransactionError::Corrupted(_))
+        ));
+        // A partial read of an extent cannot be checked
+        assert_eq!(
+            storage.read_object_range(obj, version, 1, 4).unwrap(),
+            vec![9u8; 4]
+        );
+        // The intact tail extent still verifies
+        assert_eq!(
+            storage
+                .read_object_range(obj, version, BLOCK_SIZE as u64 * 2, 10)
+                .unwrap(),
+            vec![9u8; 10]
+        );
+    }
+
+    #[test]
+    fn test_append_checks_tail_and_combines_checksums() {
+        let mut storage = corrupting_storage();
+        let damaged = ObjectId::new();
+        commit_object(&mut storage, damaged, b"tail");
+        storage.device.set_policy(FailurePolicy::Never);
+
+        let mut tx = storage.begin_transaction().unwrap();
+        assert!(storage.append(&mut tx, damaged, b"more").is_err());
+        storage.rollback(&mut tx).unwrap();
+
+        let log = ObjectId::new();
+        let mut expected = Vec::new();
+        for i in 0..5u8 {
+            let chunk = vec![i; BLOCK_SIZE / 2 + 7];
+            expected.extend_from_slice(&chunk);
+            let mut tx = storage.begin_transaction().unwrap();
+            storage.append(&mut tx, log, &chunk).unwrap();
+            storage.commit(&mut tx).unwrap();
+        }
+        let tx = storage.begin_transaction().unwrap();
+        let version = storage.read(&tx, log).unwrap();
+        let entry = storage.allocations[&(log, version)].clone();
+        assert!(entry.extent_list().iter().all(|e| e.checksum.is_some()));
+        assert_eq!(storage.read_object_data(log, version).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_scrub_quarantines_damaged_extents() {
+        let mut storage = corrupting_storage();
+        let damaged = ObjectId::new();
+        let bad_version = commit_object(&mut storage, damaged, &vec![1u8; BLOCK_SIZE]);
+        storage.device.set_policy(FailurePolicy::Never);
+        let good_version = commit_object(&mut storage, damaged, b"repaired");
+        let other = ObjectId::new();
+        commit_object(&mut storage, other, b"fine");
+        storage.set_retention_policy(RetentionPolicy::keep_all());
+
+        let mut scrubber = Scrubber::new();
+        assert!(!scrubber.step(&mut storage, 1).unwrap());
+        assert_eq!(scrubber.remaining(), Some(2));
+        while !scrubber.step(&mut storage, 1).unwrap() {}
+        let report = scrubber.into_report();
+        assert_eq!(report.versions_checked, 3);
+        assert_eq!(
+            report.damaged,
+            vec![DamagedVersion {
+                object_id: damaged,
+                version_id: bad_version,
+                block_idx: storage.allocations[&(damaged, bad_version)].block_idx,
+                latest: false,
+            }]
+        );
+        assert_eq!(report.blocks_quarantined, 1);
+        assert!(report.checkpoint_sequence.is_some());
+        assert_eq!(
+            storage.read_object_data(damaged, good_version).unwrap(),
+            b"repaired"
+        );
+
+        // Quarantined blocks stay out of the free set, even after reopening
+        let free = storage.free_block_count();
+        storage.set_retention_policy(RetentionPolicy::latest_only());
+        storage.collect_garbage().unwrap();
+        assert_eq!(storage.free_block_count(), free);
+        let mut storage = BlockStorage::open(storage.device).unwrap();
+        assert_eq!(storage.stats().quarantined_blocks, 1);
+        assert_eq!(storage.free_block_count(), free);
+        assert!(storage.scrub().unwrap().is_clean());
+    }
+
+    #[test]
+    fn test_delete_then_write_in_one_transaction() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+
+        let obj = ObjectId::new();
+        let old = commit_object(&mut storage, obj, b"old");
+
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.delete(&mut tx, obj).unwrap();
+        let new = storage.write(&mut tx, obj, b"recreated").unwrap();
+        storage.commit(&mut tx).unwrap();
+
+        let mut recovered = BlockStorage::open(storage.device).unwrap();
+        let tx = recovered.begin_transaction().unwrap();
+        assert_eq!(recovered.read(&tx, obj).unwrap(), new);
+        assert!(recovered.read_object_data(obj, old).is_err());
+        assert_eq!(recovered.read_object_data(obj, new).unwrap(), b"recreated");
+    }
+
+    #[test]
+    fn test_rolled_back_delete_keeps_object() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+
+        let obj = ObjectId::new();
+        let version = commit_object(&mut storage, obj, b"data");
+
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.delete(&mut tx, obj).unwrap();
+        storage.rollback(&mut tx).unwrap();
+
+        let tx = storage.begin_transaction().unwrap();
+        assert_eq!(storage.read(&tx, obj).unwrap(), version);
+
+        let mut tx = storage.begin_transaction().unwrap();
+        assert!(storage.delete(&mut tx, ObjectId::new()).is_err());
+    }
+
+    #[test]
+    fn test_read_object_range() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+
+        let obj = ObjectId::new();
+        let data: Vec<u8> = (0..3 * BLOCK_SIZE + 100).map(|i| (i % 251) as u8).collect();
+        let version = commit_object(&mut storage, obj, &data);
+
+        let start = BLOCK_SIZE as u64 - 10;
+        let range = storage.read_object_range(obj, version, start, 20).unwrap();
+        assert_eq!(range, &data[start as usize..start as usize + 20]);
+
+        let tail = storage
+            .read_object_range(obj, version, 3 * BLOCK_SIZE as u64, 1000)
+            .unwrap();
+        assert_eq!(tail, &data[3 * BLOCK_SIZE..]);
+        assert!(storage
+            .read_object_range(obj, version, data.len() as u64, 10)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            storage.object_size(obj, version).unwrap(),
+            data.len() as u64
+        );
+    }
+
+    #[test]
+    fn test_append_shares_full_blocks() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+
+        let obj = ObjectId::new();
+        let first = vec![1u8; 2 * BLOCK_SIZE + 10];
+        let old = commit_object(&mut storage, obj, &first);
+        let free_before = storage.free_block_count();
+
+        let mut tx = storage.begin_transaction().unwrap();
+        let new = storage.append(&mut tx, obj, b"more").unwrap();
+        storage.commit(&mut tx).unwrap();
+
+        // Only the partial tail block is written again
+        assert_eq!(storage.free_block_count(), free_before - 1);
+        let mut expected = first.clone();
+        expected.extend_from_slice(b"more");
+        assert_eq!(storage.read_object_data(obj, new).unwrap(), expected);
+        assert_eq!(storage.read_object_data(obj, old).unwrap(), first);
+
+        let mut recovered = BlockStorage::open(storage.into_device()).unwrap();
+        let tx = recovered.begin_transaction().unwrap();
+        assert_eq!(recovered.read(&tx, obj).unwrap(), new);
+        assert_eq!(recovered.read_object_data(obj, new).unwrap(), expected);
+        assert_eq!(recovered.free_block_count(), free_before - 1);
+    }
+
+    #[test]
+    fn test_streaming_write_and_read() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+
+        let obj = ObjectId::new();
+        let data: Vec<u8> = (0..STREAM_CHUNK_SIZE * 2 + 777)
+            .map(|i| (i % 253) as u8)
+            .collect();
+
+        let mut tx = storage.begin_transaction().unwrap();
+        let mut writer = storage.stream_writer(&tx, obj).unwrap();
+        for chunk in data.chunks(1000) {
+            writer.write(chunk).unwrap();
+        }
+        let version = writer.finish().unwrap();
+        storage.commit(&mut tx).unwrap();
+
+        let mut reader = storage.reader(obj, version).unwrap();
+        assert_eq!(reader.len(), data.len() as u64);
+        let mut read_back = Vec::new();
+        loop {
+            let chunk = reader.read(3000).unwrap();
+            if chunk.is_empty() {
+                break;
+            }
+            read_back.extend_from_slice(&chunk);
+        }
+        assert_eq!(read_back, data);
+
+        // Data read back after compaction moves the extents
+        assert!(storage.collect_garbage().is_ok());
+        storage.compact().unwrap();
+        assert_eq!(storage.read_object_data(obj, version).unwrap(), data);
+    }
+
+    #[test]
+    fn test_unfinished_streams_release_blocks() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        let obj = ObjectId::new();
+        let free_before = storage.free_block_count();
+
+        // Dropped writer
+        let tx = storage.begin_transaction().unwrap();
+        let mut writer = storage.stream_writer(&tx, obj).unwrap();
+        writer.write(&vec![7u8; STREAM_CHUNK_SIZE + 1]).unwrap();
+        drop(writer);
+        assert_eq!(storage.free_block_count(), free_before);
+
+        // Rolled back transaction
+        let mut tx = storage.begin_transaction().unwrap();
+        storage
+            .append(&mut tx, obj, &vec![7u8; BLOCK_SIZE * 3])
+            .unwrap();
+        assert_eq!(storage.free_block_count(), free_before - 3);
+        storage.rollback(&mut tx).unwrap();
+        assert_eq!(storage.free_block_count(), free_before);
+
+        // Crash before commit: the staged blocks are free after recovery
+        let tx = storage.begin_transaction().unwrap();
+        let mut writer = storage.stream_writer(&tx, obj).unwrap();
+        writer.write(&vec![7u8; BLOCK_SIZE * 3]).unwrap();
+        writer.finish().unwrap();
+        let mut recovered = BlockStorage::open(storage.into_device()).unwrap();
+        assert_eq!(recovered.free_block_count(), free_before);
+        let tx = recovered.begin_transaction().unwrap();
+        assert!(recovered.read(&tx, obj).is_err());
+    }
+
+    #[test]
+    fn test_pinned_versions_survive_gc() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        let obj = ObjectId::new();
+
+        let old = commit_object(&mut storage, obj, b"pinned");
+        storage.pin_version(obj, old);
+        storage.pin_version(obj, old);
+        commit_object(&mut storage, obj, b"latest");
+
+        storage.collect_garbage().unwrap();
+        assert_eq!(storage.read_version(obj, old).unwrap(), b"pinned");
+
+        storage.unpin_version(obj, old);
+        storage.collect_garbage().unwrap();
+        assert!(storage.read_version(obj, old).is_ok());
+
+        storage.unpin_version(obj, old);
+        assert_eq!(storage.collect_garbage().unwrap().versions_collected, 1);
+        assert!(storage.read_version(obj, old).is_err());
+    }
+
+    #[test]
+    fn test_version_history_and_restore() {
+        let disk = RamDisk::with_capacity_mb(1);
+        let mut storage = BlockStorage::format(disk).unwrap();
+        let obj = ObjectId::new();
+
+        storage.set_commit_timestamp(100);
+        let v1 = commit_object(&mut storage, obj, b"first draft");
+        storage.set_commit_timestamp(200);
+        let v2 = commit_object(&mut storage, obj, b"second draft");
+
+        let versions = storage.versions(obj).unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].version_id, v1);
+        assert_eq!(versions[0].committed_at, 100);
+        assert_eq!(versions[1].version_id, v2);
+        assert_eq!(versions[1].size_bytes, 12);
+        assert!(versions[1].transaction_id.is_some());
+        assert_eq!(storage.read_version(obj, v1).unwrap(), b"first draft");
+        assert!(storage.read_version(obj, VersionId::new()).is_err());
+
+        // Restoring shares the old blocks and appends to history
+        let free_before = storage.free_block_count();
+        storage.set_commit_timestamp(300);
+        let mut tx = storage.begin_transaction().unwrap();
+        let v3 = storage.restore_version(&mut tx, obj, v1).unwrap();
+        storage.commit(&mut tx).unwrap();
+        assert_eq!(storage.free_block_count(), free_before);
+
+        let mut recovered = BlockStorage::open(storage.into_device()).unwrap();
+        let tx = recovered.begin_transaction().unwrap();
+        assert_eq!(recovered.read(&tx, obj).unwrap(), v3);
+        assert_eq!(recovered.read_version(obj, v3).unwrap(), b"first draft");
+        let versions = recovered.versions(obj).unwrap();
+        assert_eq!(versions.len(), 3);
+        assert_eq!(versions[2].committed_at, 300);
+    }
+
     #[test]
     fn test_recovery_with_no_commits() {
         let disk = RamDisk::with_capacity_mb(1);
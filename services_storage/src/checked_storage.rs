@@ -0,0 +1,348 @@
+//! Capability-checked storage facade
+//!
+//! [`CheckedStorage`] wraps any transactional backend and makes every object
+//! operation on behalf of a [`PrincipalId`]. Each call is authorized with the
+//! [`PermissionChecker`] before it reaches the backend, and denials come back
+//! as [`TransactionError::AccessDenied`] carrying the reason.
+//!
+//! Objects created through the facade are owned by their creator. Transactions
+//! themselves are not objects, so beginning, committing and rolling back need
+//! no capability.
+
+use crate::{
+    Capability, CapabilityKind, JournaledStorage, ObjectId, Ownership, PermissionChecker,
+    PrincipalId, Transaction, TransactionError, TransactionId, TransactionalStorage,
+    VersionHistory, VersionId, VersionInfo,
+};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Storage whose operations are checked against a principal's capabilities
+#[derive(Debug)]
+pub struct CheckedStorage<S> {
+    storage: S,
+    permissions: PermissionChecker,
+    /// Objects deleted by each open transaction, forgotten on commit
+    pending_deletes: BTreeMap<TransactionId, Vec<ObjectId>>,
+    /// Timestamp recorded in ownership metadata
+    timestamp: u64,
+}
+
+impl<S: TransactionalStorage> CheckedStorage<S> {
+    /// Wraps a backend with an empty permission table
+    pub fn new(storage: S) -> Self {
+        Self::with_permissions(storage, PermissionChecker::new())
+    }
+
+    /// Wraps a backend with existing ownership and grants
+    pub fn with_permissions(storage: S, permissions: PermissionChecker) -> Self {
+        Self {
+            storage,
+            permissions,
+            pending_deletes: BTreeMap::new(),
+            timestamp: 0,
+        }
+    }
+
+    /// Sets the timestamp recorded for subsequent creations and modifications
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        self.timestamp = timestamp;
+    }
+
+    /// Returns the permission table
+    pub fn permissions(&self) -> &PermissionChecker {
+        &self.permissions
+    }
+
+    /// Returns the permission table for registering existing objects
+    pub fn permissions_mut(&mut self) -> &mut PermissionChecker {
+        &mut self.permissions
+    }
+
+    /// Returns the unchecked backend
+    pub fn inner(&self) -> &S {
+        &self.storage
+    }
+
+    /// Unwraps the backend
+    pub fn into_inner(self) -> S {
+        self.storage
+    }
+
+    fn authorize(
+        &self,
+        principal: PrincipalId,
+        object_id: ObjectId,
+        required: CapabilityKind,
+    ) -> Result<(), TransactionError> {
+        self.permissions
+            .authorize(principal, object_id, required)
+            .map_err(TransactionError::AccessDenied)
+    }
+
+    /// Begins a new transaction
+    pub fn begin_transaction(&mut self) -> Result<Transaction, TransactionError> {
+        self.storage.begin_transaction()
+    }
+
+    /// Creates an object owned by `principal`
+    pub fn create(
+        &mut self,
+        principal: PrincipalId,
+        tx: &mut Transaction,
+        data: &[u8],
+    ) -> Result<(ObjectId, VersionId), TransactionError> {
+        let object_id = ObjectId::new();
+        let version_id = self.storage.write(tx, object_id, data)?;
+        self.permissions
+            .register_object(object_id, Ownership::new(principal, self.timestamp));
+        Ok((object_id, version_id))
+    }
+
+    /// Reads an object's latest version; requires `Read`
+    pub fn read(
+        &self,
+        principal: PrincipalId,
+        tx: &Transaction,
+        object_id: ObjectId,
+    ) -> Result<VersionId, TransactionError> {
+        self.authorize(principal, object_id, CapabilityKind::Read)?;
+        self.storage.read(tx, object_id)
+    }
+
+    /// Writes a new version; requires `Write`
+    pub fn write(
+        &mut self,
+        principal: PrincipalId,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        data: &[u8],
+    ) -> Result<VersionId, TransactionError> {
+        self.authorize(principal, object_id, CapabilityKind::Write)?;
+        let version_id = self.storage.write(tx, object_id, data)?;
+        self.permissions.touch(object_id, principal, self.timestamp);
+        Ok(version_id)
+    }
+
+    /// Deletes an object; requires `Delete`
+    ///
+    /// Its ownership and grants are dropped when the transaction commits.
+    pub fn delete(
+        &mut self,
+        principal: PrincipalId,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+    ) -> Result<(), TransactionError> {
+        self.authorize(principal, object_id, CapabilityKind::Delete)?;
+        self.storage.delete(tx, object_id)?;
+        self.pending_deletes
+            .entry(tx.id())
+            .or_default()
+            .push(object_id);
+        Ok(())
+    }
+
+    /// Commits a transaction
+    pub fn commit(&mut self, tx: &mut Transaction) -> Result<(), TransactionError> {
+        self.storage.commit(tx)?;
+        for object_id in self.pending_deletes.remove(&tx.id()).unwrap_or_default() {
+            self.permissions.unregister_object(object_id);
+        }
+        Ok(())
+    }
+
+    /// Rolls back a transaction
+    pub fn rollback(&mut self, tx: &mut Transaction) -> Result<(), TransactionError> {
+        self.pending_deletes.remove(&tx.id());
+        self.storage.rollback(tx)
+    }
+
+    /// Grants `holder` a capability on an object; `granter` needs `Grant`
+    pub fn grant(
+        &mut self,
+        granter: PrincipalId,
+        object_id: ObjectId,
+        kind: CapabilityKind,
+        holder: PrincipalId,
+    ) -> Result<Capability, TransactionError> {
+        self.authorize(granter, object_id, CapabilityKind::Grant)?;
+        let capability = Capability::new(object_id, kind, holder);
+        self.permissions.grant(capability.clone());
+        Ok(capability)
+    }
+
+    /// Revokes `holder`'s capabilities of `kind`; `granter` needs `Grant`
+    ///
+    /// Returns how many were revoked.
+    pub fn revoke(
+        &mut self,
+        granter: PrincipalId,
+        object_id: ObjectId,
+        kind: CapabilityKind,
+        holder: PrincipalId,
+    ) -> Result<usize, TransactionError> {
+        self.authorize(granter, object_id, CapabilityKind::Grant)?;
+        Ok(self.permissions.revoke(object_id, kind, holder))
+    }
+}
+
+impl<S: TransactionalStorage + VersionHistory> CheckedStorage<S> {
+    /// Lists an object's versions; requires `Read`
+    pub fn versions(
+        &self,
+        principal: PrincipalId,
+        object_id: ObjectId,
+    ) -> Result<Vec<VersionInfo>, TransactionError> {
+        self.authorize(principal, object_id, CapabilityKind::Read)?;
+        self.storage.versions(object_id)
+    }
+
+    /// Reads a committed version; requires `Read`
+    pub fn read_version(
+        &mut self,
+        principal: PrincipalId,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<Vec<u8>, TransactionError> {
+        self.authorize(principal, object_id, CapabilityKind::Read)?;
+        self.storage.read_version(object_id, version_id)
+    }
+
+    /// Restores an old version as a new one; requires `Write`
+    pub fn restore_version(
+        &mut self,
+        principal: PrincipalId,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<VersionId, TransactionError> {
+        self.authorize(principal, object_id, CapabilityKind::Write)?;
+        let restored = self.storage.restore_version(tx, object_id, version_id)?;
+        self.permissions.touch(object_id, principal, self.timestamp);
+        Ok(restored)
+    }
+}
+
+impl CheckedStorage<JournaledStorage> {
+    /// Reads an object's latest data; requires `Read`
+    pub fn read_data(
+        &self,
+        principal: PrincipalId,
+        tx: &Transaction,
+        object_id: ObjectId,
+    ) -> Result<Vec<u8>, TransactionError> {
+        self.authorize(principal, object_id, CapabilityKind::Read)?;
+        self.storage.read_data(tx, object_id)
+    }
+
+    /// Appends to an object; requires `Write`
+    pub fn append(
+        &mut self,
+        principal: PrincipalId,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        data: &[u8],
+    ) -> Result<VersionId, TransactionError> {
+        self.authorize(principal, object_id, CapabilityKind::Write)?;
+        let version_id = self.storage.append(tx, object_id, data)?;
+        self.permissions.touch(object_id, principal, self.timestamp);
+        Ok(version_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccessDenialReason;
+
+    fn storage_with_object(owner: PrincipalId) -> (CheckedStorage<JournaledStorage>, ObjectId) {
+        let mut storage = CheckedStorage::new(JournaledStorage::new());
+        let mut tx = storage.begin_transaction().unwrap();
+        let (object_id, _) = storage.create(owner, &mut tx, b"secret").unwrap();
+        storage.commit(&mut tx).unwrap();
+        (storage, object_id)
+    }
+
+    #[test]
+    fn test_owner_and_system_have_full_access() {
+        let owner = PrincipalId::new();
+        let (mut storage, object_id) = storage_with_object(owner);
+        let mut tx = storage.begin_transaction().unwrap();
+
+        assert_eq!(storage.read_data(owner, &tx, object_id).unwrap(), b"secret");
+        storage.write(owner, &mut tx, object_id, b"v2").unwrap();
+        storage
+            .append(PrincipalId::system(), &mut tx, object_id, b"!")
+            .unwrap();
+        storage.commit(&mut tx).unwrap();
+        assert_eq!(storage.versions(owner, object_id).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_denials_carry_reason() {
+        let owner = PrincipalId::new();
+        let other = PrincipalId::new();
+        let (mut storage, object_id) = storage_with_object(owner);
+        let mut tx = storage.begin_transaction().unwrap();
+
+        assert_eq!(
+            storage.read(other, &tx, object_id),
+            Err(TransactionError::AccessDenied(
+                AccessDenialReason::MissingCapability {
+                    required: CapabilityKind::Read,
+                    object_id,
+                    principal: other,
+                }
+            ))
+        );
+
+        storage
+            .grant(owner, object_id, CapabilityKind::Read, other)
+            .unwrap();
+        assert!(storage.read(other, &tx, object_id).is_ok());
+        assert_eq!(
+            storage.write(other, &mut tx, object_id, b"nope"),
+            Err(TransactionError::AccessDenied(
+                AccessDenialReason::WrongCapabilityKind {
+                    capability_kind: CapabilityKind::Read,
+                    required_kind: CapabilityKind::Write,
+                }
+            ))
+        );
+        // Holding Read does not allow passing it on
+        assert!(matches!(
+            storage.grant(other, object_id, CapabilityKind::Read, PrincipalId::new()),
+            Err(TransactionError::AccessDenied(_))
+        ));
+
+        assert_eq!(
+            storage
+                .revoke(owner, object_id, CapabilityKind::Read, other)
+                .unwrap(),
+            1
+        );
+        assert!(storage.read(other, &tx, object_id).is_err());
+    }
+
+    #[test]
+    fn test_delete_forgets_ownership_only_on_commit() {
+        let owner = PrincipalId::new();
+        let (mut storage, object_id) = storage_with_object(owner);
+
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.delete(owner, &mut tx, object_id).unwrap();
+        storage.rollback(&mut tx).unwrap();
+        assert!(storage.permissions().is_owner(object_id, owner));
+
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.delete(owner, &mut tx, object_id).unwrap();
+        storage.commit(&mut tx).unwrap();
+        let tx = storage.begin_transaction().unwrap();
+        assert_eq!(
+            storage.read(owner, &tx, object_id),
+            Err(TransactionError::AccessDenied(
+                AccessDenialReason::ObjectNotFound { object_id }
+            ))
+        );
+    }
+}
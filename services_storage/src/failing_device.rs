@@ -18,6 +18,8 @@ pub enum FailurePolicy {
     OnBlocks(Vec<u64>),
     /// Fail after N writes to specific blocks
     AfterWritesToBlocks { count: usize, blocks: Vec<u64> },
+    /// Silently flip a bit in data written to specific blocks
+    CorruptBlocks(Vec<u64>),
 }
 
 /// Wrapper around a BlockDevice that can simulate failures
@@ -45,6 +47,7 @@ impl<D: BlockDevice> FailingBlockDevice<D> {
             FailurePolicy::Never => false,
             FailurePolicy::AfterWrites(n) => self.write_count >= *n,
             FailurePolicy::OnBlocks(blocks) => blocks.contains(&block_idx),
+            FailurePolicy::CorruptBlocks(_) => false,
             FailurePolicy::AfterWritesToBlocks { count, blocks } => {
                 if blocks.contains(&block_idx) {
                     let block_count = self.block_write_counts.entry(block_idx).or_insert(0);
@@ -100,6 +103,14 @@ impl<D: BlockDevice> BlockDevice for FailingBlockDevice<D> {
             }
         }
 
+        if let FailurePolicy::CorruptBlocks(blocks) = &self.policy {
+            if blocks.contains(&block_idx) {
+                let mut corrupted = buffer.to_vec();
+                corrupted[0] ^= 0x01;
+                return self.inner.write_block(block_idx, &corrupted);
+            }
+        }
+
         self.inner.write_block(block_idx, buffer)
     }
 
@@ -204,4 +215,19 @@ mod tests {
         failing.write_block(1, &data).unwrap();
         assert_eq!(failing.write_count(), 2);
     }
+
+    #[test]
+    fn test_failing_device_corrupts_silently() {
+        let disk = RamDisk::new(10);
+        let mut failing = FailingBlockDevice::new(disk, FailurePolicy::CorruptBlocks(vec![4]));
+
+        let data = [0x42u8; BLOCK_SIZE];
+        let mut buffer = [0u8; BLOCK_SIZE];
+        assert!(failing.write_block(3, &data).is_ok());
+        assert!(failing.write_block(4, &data).is_ok());
+        failing.read_block(3, &mut buffer).unwrap();
+        assert_eq!(buffer, data);
+        failing.read_block(4, &mut buffer).unwrap();
+        assert_ne!(buffer, data);
+    }
 }
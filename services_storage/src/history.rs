@@ -0,0 +1,49 @@
+//! Version history browsing and point-in-time restore.
+//!
+//! Every commit adds an immutable version to an object. [`VersionHistory`]
+//! lists those versions, reads any one of them, and restores an old version
+//! by committing its content as a new version: history is only ever
+//! appended to, never rewound.
+
+use crate::{
+    ObjectId, Transaction, TransactionError, TransactionId, TransactionalStorage, VersionId,
+};
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// A committed version of an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    /// Version identifier
+    pub version_id: VersionId,
+    /// Transaction that committed the version (unknown for old records)
+    pub transaction_id: Option<TransactionId>,
+    /// Commit timestamp (0 if none was set)
+    pub committed_at: u64,
+    /// Size of the version's content in bytes
+    pub size_bytes: u64,
+}
+
+/// Storage that can browse and restore object versions
+pub trait VersionHistory: TransactionalStorage {
+    /// Committed versions of an object, oldest first
+    fn versions(&self, object_id: ObjectId) -> Result<Vec<VersionInfo>, TransactionError>;
+
+    /// Read the content of a committed version
+    fn read_version(
+        &mut self,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<Vec<u8>, TransactionError>;
+
+    /// Stage an old version's content as the object's newest version
+    fn restore_version(
+        &mut self,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<VersionId, TransactionError> {
+        let data = self.read_version(object_id, version_id)?;
+        self.write(tx, object_id, &data)
+    }
+}
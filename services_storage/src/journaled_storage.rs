@@ -1,12 +1,15 @@
 //! Journaled storage backend with crash-consistent recovery.
 
+use crate::schema::{SchemaMigrationError, SchemaRegistry, SchemaTag};
 use crate::{
-    ObjectId, Transaction, TransactionError, TransactionId, TransactionalStorage, VersionId,
+    Migrator, ObjectId, Transaction, TransactionError, TransactionId, TransactionalStorage,
+    VersionHistory, VersionId, VersionInfo,
 };
 use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::string::ToString;
 use alloc::vec::Vec;
+use core_types::{ObjectSchemaId, ObjectSchemaVersion};
 use identity::ExecutionId;
 use kernel_api::KernelError;
 use serde::{Deserialize, Serialize};
@@ -14,7 +17,10 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone)]
 struct VersionEntry {
     version_id: VersionId,
+    transaction_id: TransactionId,
+    committed_at: u64,
     data: Vec<u8>,
+    schema: Option<SchemaTag>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,9 +30,29 @@ pub enum JournalEntry {
         object_id: ObjectId,
         version_id: VersionId,
         data: Vec<u8>,
+        /// Schema of the written data (absent for untyped writes)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        schema: Option<SchemaTag>,
+    },
+    /// Adds `data` to the end of the object's latest version at commit time
+    ///
+    /// The new version keeps the schema of the version it extends.
+    Append {
+        tx_id: TransactionId,
+        object_id: ObjectId,
+        version_id: VersionId,
+        data: Vec<u8>,
+    },
+    /// Tombstone: the object and all its versions are gone once committed
+    Delete {
+        tx_id: TransactionId,
+        object_id: ObjectId,
     },
     Commit {
         tx_id: TransactionId,
+        /// Commit timestamp (absent in journals from before version history)
+        #[serde(default)]
+        timestamp: u64,
     },
 }
 
@@ -35,6 +61,24 @@ struct PendingWrite {
     object_id: ObjectId,
     version_id: VersionId,
     data: Vec<u8>,
+    schema: Option<SchemaTag>,
+}
+
+#[derive(Debug, Clone)]
+enum PendingChange {
+    Write(PendingWrite),
+    /// Like a write, but `data` only holds the appended bytes
+    Append(PendingWrite),
+    Delete(ObjectId),
+}
+
+impl PendingChange {
+    fn object_id(&self) -> ObjectId {
+        match self {
+            PendingChange::Write(write) | PendingChange::Append(write) => write.object_id,
+            PendingChange::Delete(object_id) => *object_id,
+        }
+    }
 }
 
 /// In-memory journaled storage backend.
@@ -42,7 +86,8 @@ struct PendingWrite {
 pub struct JournaledStorage {
     objects: BTreeMap<ObjectId, Vec<VersionEntry>>,
     journal: Vec<JournalEntry>,
-    pending: BTreeMap<TransactionId, Vec<PendingWrite>>,
+    pending: BTreeMap<TransactionId, Vec<PendingChange>>,
+    commit_timestamp: u64,
 }
 
 impl JournaledStorage {
@@ -51,9 +96,20 @@ impl JournaledStorage {
             objects: BTreeMap::new(),
             journal: Vec::new(),
             pending: BTreeMap::new(),
+            commit_timestamp: 0,
         }
     }
 
+    /// Sets the timestamp recorded for subsequent commits.
+    pub fn set_commit_timestamp(&mut self, timestamp: u64) {
+        self.commit_timestamp = timestamp;
+    }
+
+    /// Returns the timestamp recorded for subsequent commits.
+    pub fn commit_timestamp(&self) -> u64 {
+        self.commit_timestamp
+    }
+
     /// Returns the journal entries (for testing).
     pub fn journal_entries(&self) -> &[JournalEntry] {
         &self.journal
@@ -74,6 +130,7 @@ impl JournaledStorage {
             objects: BTreeMap::new(),
             journal: entries,
             pending: BTreeMap::new(),
+            commit_timestamp: 0,
         };
         storage.recover();
         storage
@@ -85,24 +142,172 @@ impl JournaledStorage {
         tx: &Transaction,
         object_id: ObjectId,
     ) -> Result<Vec<u8>, TransactionError> {
+        Ok(self.read_tagged(tx, object_id)?.0)
+    }
+
+    /// Returns the schema of an object's latest data (including pending writes).
+    pub fn schema(
+        &self,
+        tx: &Transaction,
+        object_id: ObjectId,
+    ) -> Result<Option<SchemaTag>, TransactionError> {
+        Ok(self.read_tagged(tx, object_id)?.1)
+    }
+
+    /// Returns the schema recorded with a committed version.
+    pub fn schema_at(
+        &self,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<Option<SchemaTag>, TransactionError> {
+        Ok(self.version_entry(object_id, version_id)?.schema.clone())
+    }
+
+    /// Reads the latest data and its schema (including pending writes).
+    fn read_tagged(
+        &self,
+        tx: &Transaction,
+        object_id: ObjectId,
+    ) -> Result<(Vec<u8>, Option<SchemaTag>), TransactionError> {
         if tx.state() != crate::transaction::TransactionState::Active {
             return Err(TransactionError::AlreadyFinalized);
         }
 
+        let mut latest = self
+            .objects
+            .get(&object_id)
+            .and_then(|versions| versions.last())
+            .map(|entry| (entry.data.clone(), entry.schema.clone()));
         if let Some(pending) = self.pending.get(&tx.id()) {
-            if let Some(entry) = pending.iter().rev().find(|p| p.object_id == object_id) {
-                return Ok(entry.data.clone());
+            for change in pending.iter().filter(|p| p.object_id() == object_id) {
+                match change {
+                    PendingChange::Write(write) => {
+                        latest = Some((write.data.clone(), write.schema.clone()))
+                    }
+                    PendingChange::Append(append) => {
+                        latest
+                            .get_or_insert_with(|| (Vec::new(), None))
+                            .0
+                            .extend_from_slice(&append.data);
+                    }
+                    PendingChange::Delete(_) => latest = None,
+                }
             }
         }
 
-        let versions = self
-            .objects
+        latest.ok_or_else(|| TransactionError::ObjectNotFound(object_id.to_string()))
+    }
+
+    fn version_entry(
+        &self,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<&VersionEntry, TransactionError> {
+        self.objects
             .get(&object_id)
-            .ok_or_else(|| TransactionError::ObjectNotFound(object_id.to_string()))?;
-        versions
-            .last()
-            .map(|entry| entry.data.clone())
-            .ok_or_else(|| TransactionError::ObjectNotFound(object_id.to_string()))
+            .and_then(|versions| versions.iter().find(|entry| entry.version_id == version_id))
+            .ok_or_else(|| {
+                TransactionError::ObjectNotFound(alloc::format!(
+                    "{} at version {}",
+                    object_id,
+                    version_id
+                ))
+            })
+    }
+
+    /// Writes an object whose data follows `schema`.
+    pub fn write_with_schema(
+        &mut self,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        data: &[u8],
+        schema: SchemaTag,
+    ) -> Result<VersionId, TransactionError> {
+        self.write_tagged(tx, object_id, data, Some(schema))
+    }
+
+    fn write_tagged(
+        &mut self,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        data: &[u8],
+        schema: Option<SchemaTag>,
+    ) -> Result<VersionId, TransactionError> {
+        if tx.state() != crate::transaction::TransactionState::Active {
+            return Err(TransactionError::AlreadyFinalized);
+        }
+
+        let version_id = VersionId::new();
+        let pending_write = PendingWrite {
+            object_id,
+            version_id,
+            data: data.to_vec(),
+            schema,
+        };
+
+        self.pending
+            .entry(tx.id())
+            .or_default()
+            .push(PendingChange::Write(pending_write.clone()));
+        self.journal.push(JournalEntry::Write {
+            tx_id: tx.id(),
+            object_id,
+            version_id,
+            data: pending_write.data,
+            schema: pending_write.schema,
+        });
+
+        tx.modify(object_id)?;
+        Ok(version_id)
+    }
+
+    /// Reads up to `len` bytes of the latest data starting at `offset`.
+    pub fn read_range(
+        &self,
+        tx: &Transaction,
+        object_id: ObjectId,
+        offset: usize,
+        len: usize,
+    ) -> Result<Vec<u8>, TransactionError> {
+        let data = self.read_data(tx, object_id)?;
+        let start = offset.min(data.len());
+        let end = offset.saturating_add(len).min(data.len());
+        Ok(data[start..end].to_vec())
+    }
+
+    /// Appends data to an object, creating it if missing.
+    ///
+    /// Only the appended bytes are journaled; they are added to whatever
+    /// version is latest when the transaction commits.
+    pub fn append(
+        &mut self,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        data: &[u8],
+    ) -> Result<VersionId, TransactionError> {
+        if tx.state() != crate::transaction::TransactionState::Active {
+            return Err(TransactionError::AlreadyFinalized);
+        }
+
+        let version_id = VersionId::new();
+        self.pending
+            .entry(tx.id())
+            .or_default()
+            .push(PendingChange::Append(PendingWrite {
+                object_id,
+                version_id,
+                data: data.to_vec(),
+                schema: None,
+            }));
+        self.journal.push(JournalEntry::Append {
+            tx_id: tx.id(),
+            object_id,
+            version_id,
+            data: data.to_vec(),
+        });
+
+        tx.modify(object_id)?;
+        Ok(version_id)
     }
 
     /// Recovers committed transactions from the journal.
@@ -110,7 +315,7 @@ impl JournaledStorage {
         self.objects.clear();
         self.pending.clear();
 
-        let mut writes: BTreeMap<TransactionId, Vec<PendingWrite>> = BTreeMap::new();
+        let mut changes: BTreeMap<TransactionId, Vec<PendingChange>> = BTreeMap::new();
 
         for entry in &self.journal {
             match entry {
@@ -119,24 +324,43 @@ impl JournaledStorage {
                     object_id,
                     version_id,
                     data,
+                    schema,
                 } => {
-                    writes.entry(*tx_id).or_default().push(PendingWrite {
-                        object_id: *object_id,
-                        version_id: *version_id,
-                        data: data.clone(),
-                    });
+                    changes
+                        .entry(*tx_id)
+                        .or_default()
+                        .push(PendingChange::Write(PendingWrite {
+                            object_id: *object_id,
+                            version_id: *version_id,
+                            data: data.clone(),
+                            schema: schema.clone(),
+                        }));
+                }
+                JournalEntry::Append {
+                    tx_id,
+                    object_id,
+                    version_id,
+                    data,
+                } => {
+                    changes
+                        .entry(*tx_id)
+                        .or_default()
+                        .push(PendingChange::Append(PendingWrite {
+                            object_id: *object_id,
+                            version_id: *version_id,
+                            data: data.clone(),
+                            schema: None,
+                        }));
                 }
-                JournalEntry::Commit { tx_id } => {
-                    if let Some(pending) = writes.remove(tx_id) {
-                        for write in pending {
-                            self.objects
-                                .entry(write.object_id)
-                                .or_default()
-                                .push(VersionEntry {
-                                    version_id: write.version_id,
-                                    data: write.data,
-                                });
-                        }
+                JournalEntry::Delete { tx_id, object_id } => {
+                    changes
+                        .entry(*tx_id)
+                        .or_default()
+                        .push(PendingChange::Delete(*object_id));
+                }
+                JournalEntry::Commit { tx_id, timestamp } => {
+                    if let Some(pending) = changes.remove(tx_id) {
+                        apply_changes(&mut self.objects, *tx_id, *timestamp, pending);
                     }
                 }
             }
@@ -144,6 +368,49 @@ impl JournaledStorage {
     }
 }
 
+/// Applies a committed transaction's changes in order
+fn apply_changes(
+    objects: &mut BTreeMap<ObjectId, Vec<VersionEntry>>,
+    tx_id: TransactionId,
+    committed_at: u64,
+    changes: Vec<PendingChange>,
+) {
+    for change in changes {
+        match change {
+            PendingChange::Write(write) => {
+                objects
+                    .entry(write.object_id)
+                    .or_default()
+                    .push(VersionEntry {
+                        version_id: write.version_id,
+                        transaction_id: tx_id,
+                        committed_at,
+                        data: write.data,
+                        schema: write.schema,
+                    });
+            }
+            PendingChange::Append(append) => {
+                let versions = objects.entry(append.object_id).or_default();
+                let (mut data, schema) = versions
+                    .last()
+                    .map(|entry| (entry.data.clone(), entry.schema.clone()))
+                    .unwrap_or_default();
+                data.extend_from_slice(&append.data);
+                versions.push(VersionEntry {
+                    version_id: append.version_id,
+                    transaction_id: tx_id,
+                    committed_at,
+                    data,
+                    schema,
+                });
+            }
+            PendingChange::Delete(object_id) => {
+                objects.remove(&object_id);
+            }
+        }
+    }
+}
+
 impl Default for JournaledStorage {
     fn default() -> Self {
         Self::new()
@@ -161,8 +428,14 @@ impl TransactionalStorage for JournaledStorage {
         }
 
         if let Some(pending) = self.pending.get(&tx.id()) {
-            if let Some(entry) = pending.iter().rev().find(|p| p.object_id == object_id) {
-                return Ok(entry.version_id);
+            match pending.iter().rev().find(|p| p.object_id() == object_id) {
+                Some(PendingChange::Write(write) | PendingChange::Append(write)) => {
+                    return Ok(write.version_id)
+                }
+                Some(PendingChange::Delete(_)) => {
+                    return Err(TransactionError::ObjectNotFound(object_id.to_string()))
+                }
+                None => {}
             }
         }
 
@@ -182,30 +455,28 @@ impl TransactionalStorage for JournaledStorage {
         object_id: ObjectId,
         data: &[u8],
     ) -> Result<VersionId, TransactionError> {
-        if tx.state() != crate::transaction::TransactionState::Active {
-            return Err(TransactionError::AlreadyFinalized);
-        }
+        self.write_tagged(tx, object_id, data, None)
+    }
 
-        let version_id = VersionId::new();
-        let pending_write = PendingWrite {
-            object_id,
-            version_id,
-            data: data.to_vec(),
-        };
+    fn delete(
+        &mut self,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+    ) -> Result<(), TransactionError> {
+        // Fails for finalized transactions and objects that do not exist
+        self.read(tx, object_id)?;
 
         self.pending
             .entry(tx.id())
             .or_default()
-            .push(pending_write.clone());
-        self.journal.push(JournalEntry::Write {
+            .push(PendingChange::Delete(object_id));
+        self.journal.push(JournalEntry::Delete {
             tx_id: tx.id(),
             object_id,
-            version_id,
-            data: pending_write.data,
         });
 
         tx.modify(object_id)?;
-        Ok(version_id)
+        Ok(())
     }
 
     fn commit(&mut self, tx: &mut Transaction) -> Result<(), TransactionError> {
@@ -214,18 +485,13 @@ impl TransactionalStorage for JournaledStorage {
         }
 
         if let Some(pending) = self.pending.remove(&tx.id()) {
-            for write in pending {
-                self.objects
-                    .entry(write.object_id)
-                    .or_default()
-                    .push(VersionEntry {
-                        version_id: write.version_id,
-                        data: write.data,
-                    });
-            }
+            apply_changes(&mut self.objects, tx.id(), self.commit_timestamp, pending);
         }
 
-        self.journal.push(JournalEntry::Commit { tx_id: tx.id() });
+        self.journal.push(JournalEntry::Commit {
+            tx_id: tx.id(),
+            timestamp: self.commit_timestamp,
+        });
         tx.commit()?;
         Ok(())
     }
@@ -241,6 +507,44 @@ impl TransactionalStorage for JournaledStorage {
     }
 }
 
+impl VersionHistory for JournaledStorage {
+    fn versions(&self, object_id: ObjectId) -> Result<Vec<VersionInfo>, TransactionError> {
+        let versions = self
+            .objects
+            .get(&object_id)
+            .ok_or_else(|| TransactionError::ObjectNotFound(object_id.to_string()))?;
+        Ok(versions
+            .iter()
+            .map(|entry| VersionInfo {
+                version_id: entry.version_id,
+                transaction_id: Some(entry.transaction_id),
+                committed_at: entry.committed_at,
+                size_bytes: entry.data.len() as u64,
+            })
+            .collect())
+    }
+
+    fn read_version(
+        &mut self,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<Vec<u8>, TransactionError> {
+        Ok(self.version_entry(object_id, version_id)?.data.clone())
+    }
+
+    /// Restores an old version along with the schema it was written in
+    fn restore_version(
+        &mut self,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<VersionId, TransactionError> {
+        let entry = self.version_entry(object_id, version_id)?;
+        let (data, schema) = (entry.data.clone(), entry.schema.clone());
+        self.write_tagged(tx, object_id, &data, schema)
+    }
+}
+
 /// Storage budget enforcement trait.
 pub trait StorageBudget {
     fn consume_storage_op(
@@ -279,6 +583,7 @@ pub enum StorageOperation {
 pub enum StorageServiceError {
     Transaction(String),
     Budget(String),
+    Migration(SchemaMigrationError),
 }
 
 impl core::fmt::Display for StorageServiceError {
@@ -286,6 +591,7 @@ impl core::fmt::Display for StorageServiceError {
         match self {
             StorageServiceError::Transaction(msg) => write!(f, "Transaction error: {}", msg),
             StorageServiceError::Budget(msg) => write!(f, "Budget error: {}", msg),
+            StorageServiceError::Migration(error) => write!(f, "Migration error: {}", error),
         }
     }
 }
@@ -297,6 +603,12 @@ impl From<TransactionError> for StorageServiceError {
     }
 }
 
+impl From<SchemaMigrationError> for StorageServiceError {
+    fn from(error: SchemaMigrationError) -> Self {
+        StorageServiceError::Migration(error)
+    }
+}
+
 impl From<KernelError> for StorageServiceError {
     fn from(error: KernelError) -> Self {
         use alloc::format;
@@ -304,15 +616,156 @@ impl From<KernelError> for StorageServiceError {
     }
 }
 
+/// Result of a read that migrates data to a requested schema version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigratedRead {
+    /// Data in the requested schema version
+    pub data: Vec<u8>,
+    /// Schema of `data` (`None` for untyped objects, which are never migrated)
+    pub schema: Option<SchemaTag>,
+    /// Version read, or the upgraded version when written back
+    pub version_id: VersionId,
+    /// Whether any migration ran
+    pub migrated: bool,
+    /// Whether the upgraded data was written back in the reader's transaction
+    pub written_back: bool,
+}
+
 /// Budgeted storage service wrapper.
+///
+/// Objects written with a schema are migrated lazily: stored bytes stay in
+/// the version they were written in until a reader asks for a newer one.
 pub struct StorageService<B: StorageBudget> {
     storage: JournaledStorage,
     budget: B,
+    schemas: SchemaRegistry,
+    write_back: bool,
 }
 
 impl<B: StorageBudget> StorageService<B> {
     pub fn new(storage: JournaledStorage, budget: B) -> Self {
-        Self { storage, budget }
+        Self {
+            storage,
+            budget,
+            schemas: SchemaRegistry::new(),
+            write_back: false,
+        }
+    }
+
+    /// Registers the migrator for a schema and its current version
+    pub fn register_schema(
+        &mut self,
+        schema_id: impl Into<ObjectSchemaId>,
+        current: ObjectSchemaVersion,
+        migrator: impl Migrator + 'static,
+    ) {
+        self.schemas.register(schema_id, current, migrator);
+    }
+
+    /// Returns the registered schemas
+    pub fn schemas(&self) -> &SchemaRegistry {
+        &self.schemas
+    }
+
+    /// Sets whether migrated reads write the upgraded data back
+    ///
+    /// Written-back data becomes a new version when the reader's transaction
+    /// commits, so later reads skip the migration. Off by default.
+    pub fn set_migration_write_back(&mut self, enabled: bool) {
+        self.write_back = enabled;
+    }
+
+    /// Writes an object tagged with its schema; charged as a write
+    pub fn write_with_schema(
+        &mut self,
+        execution_id: ExecutionId,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        data: &[u8],
+        schema_id: impl Into<ObjectSchemaId>,
+        schema_version: ObjectSchemaVersion,
+    ) -> Result<VersionId, StorageServiceError> {
+        self.budget
+            .consume_storage_op(execution_id, StorageOperation::Write)?;
+        let schema = SchemaTag::new(schema_id, schema_version);
+        Ok(self
+            .storage
+            .write_with_schema(tx, object_id, data, schema)?)
+    }
+
+    /// Reads an object migrated to `target`; charged as a read
+    ///
+    /// With write-back enabled, a migration is also charged as a write.
+    pub fn read_migrated(
+        &mut self,
+        execution_id: ExecutionId,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        target: ObjectSchemaVersion,
+    ) -> Result<MigratedRead, StorageServiceError> {
+        self.migrated_read(execution_id, tx, object_id, Some(target))
+    }
+
+    /// Reads an object migrated to its schema's registered current version
+    ///
+    /// Objects whose schema is not registered are returned as stored.
+    pub fn read_current(
+        &mut self,
+        execution_id: ExecutionId,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+    ) -> Result<MigratedRead, StorageServiceError> {
+        self.migrated_read(execution_id, tx, object_id, None)
+    }
+
+    fn migrated_read(
+        &mut self,
+        execution_id: ExecutionId,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        target: Option<ObjectSchemaVersion>,
+    ) -> Result<MigratedRead, StorageServiceError> {
+        self.budget
+            .consume_storage_op(execution_id, StorageOperation::Read)?;
+        let version_id = self.storage.read(tx, object_id)?;
+        let (data, schema) = self.storage.read_tagged(tx, object_id)?;
+        let unchanged = |data, schema| MigratedRead {
+            data,
+            schema,
+            version_id,
+            migrated: false,
+            written_back: false,
+        };
+
+        let Some(tag) = schema else {
+            return Ok(unchanged(data, None));
+        };
+        let target = match target.or_else(|| self.schemas.current_version(&tag.schema_id)) {
+            Some(target) if target != tag.version => target,
+            _ => return Ok(unchanged(data, Some(tag))),
+        };
+
+        let migrated_at = Some(self.storage.commit_timestamp());
+        let (data, tag) = self.schemas.migrate(&tag, &data, target, migrated_at)?;
+        if !self.write_back {
+            return Ok(MigratedRead {
+                migrated: true,
+                ..unchanged(data, Some(tag))
+            });
+        }
+
+        self.budget
+            .consume_storage_op(execution_id, StorageOperation::Write)?;
+        let version_id = self
+            .storage
+            .write_with_schema(tx, object_id, &data, tag.clone())?;
+        Ok(MigratedRead {
+            data,
+            schema: Some(tag),
+            version_id,
+            migrated: true,
+            written_back: true,
+        })
     }
 
     pub fn begin_transaction(&mut self) -> Result<Transaction, StorageServiceError> {
@@ -342,6 +795,31 @@ impl<B: StorageBudget> StorageService<B> {
         Ok(self.storage.write(tx, object_id, data)?)
     }
 
+    /// Appends to an object; charged as a write
+    pub fn append(
+        &mut self,
+        execution_id: ExecutionId,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        data: &[u8],
+    ) -> Result<VersionId, StorageServiceError> {
+        self.budget
+            .consume_storage_op(execution_id, StorageOperation::Write)?;
+        Ok(self.storage.append(tx, object_id, data)?)
+    }
+
+    /// Deletes an object; charged as a write
+    pub fn delete(
+        &mut self,
+        execution_id: ExecutionId,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+    ) -> Result<(), StorageServiceError> {
+        self.budget
+            .consume_storage_op(execution_id, StorageOperation::Write)?;
+        Ok(self.storage.delete(tx, object_id)?)
+    }
+
     pub fn commit(
         &mut self,
         execution_id: ExecutionId,
@@ -352,6 +830,42 @@ impl<B: StorageBudget> StorageService<B> {
         Ok(self.storage.commit(tx)?)
     }
 
+    /// Lists an object's committed versions; charged as a read
+    pub fn versions(
+        &mut self,
+        execution_id: ExecutionId,
+        object_id: ObjectId,
+    ) -> Result<Vec<VersionInfo>, StorageServiceError> {
+        self.budget
+            .consume_storage_op(execution_id, StorageOperation::Read)?;
+        Ok(self.storage.versions(object_id)?)
+    }
+
+    /// Reads a committed version; charged as a read
+    pub fn read_version(
+        &mut self,
+        execution_id: ExecutionId,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<Vec<u8>, StorageServiceError> {
+        self.budget
+            .consume_storage_op(execution_id, StorageOperation::Read)?;
+        Ok(self.storage.read_version(object_id, version_id)?)
+    }
+
+    /// Restores an old version as a new one; charged as a write
+    pub fn restore_version(
+        &mut self,
+        execution_id: ExecutionId,
+        tx: &mut Transaction,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<VersionId, StorageServiceError> {
+        self.budget
+            .consume_storage_op(execution_id, StorageOperation::Write)?;
+        Ok(self.storage.restore_version(tx, object_id, version_id)?)
+    }
+
     pub fn rollback(&mut self, tx: &mut Transaction) -> Result<(), StorageServiceError> {
         Ok(self.storage.rollback(tx)?)
     }
@@ -457,6 +971,106 @@ mod tests {
         assert_eq!(storage.read_data(&read_tx, object).unwrap(), b"data");
     }
 
+    #[test]
+    fn test_journal_recovery_honors_tombstones() {
+        let mut storage = JournaledStorage::new();
+        let deleted = ObjectId::new();
+        let recreated = ObjectId::new();
+
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.write(&mut tx, deleted, b"gone").unwrap();
+        storage.write(&mut tx, recreated, b"first").unwrap();
+        storage.commit(&mut tx).unwrap();
+
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.delete(&mut tx, deleted).unwrap();
+        storage.delete(&mut tx, recreated).unwrap();
+        assert!(storage.read(&tx, deleted).is_err());
+        let version = storage.write(&mut tx, recreated, b"second").unwrap();
+        storage.commit(&mut tx).unwrap();
+
+        // An uncommitted tombstone must not apply
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.delete(&mut tx, recreated).unwrap();
+
+        let recovered = JournaledStorage::from_journal(storage.journal_clone());
+        let read_tx = Transaction::new();
+        assert!(matches!(
+            recovered.read(&read_tx, deleted),
+            Err(TransactionError::ObjectNotFound(_))
+        ));
+        assert_eq!(recovered.read(&read_tx, recreated).unwrap(), version);
+        assert_eq!(recovered.read_data(&read_tx, recreated).unwrap(), b"second");
+    }
+
+    #[test]
+    fn test_journal_version_history_survives_recovery() {
+        let mut storage = JournaledStorage::new();
+        let obj = ObjectId::new();
+
+        storage.set_commit_timestamp(10);
+        let mut tx = storage.begin_transaction().unwrap();
+        let v1 = storage.write(&mut tx, obj, b"v1").unwrap();
+        storage.commit(&mut tx).unwrap();
+        storage.set_commit_timestamp(20);
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.write(&mut tx, obj, b"v2").unwrap();
+        storage.commit(&mut tx).unwrap();
+
+        storage.set_commit_timestamp(30);
+        let mut tx = storage.begin_transaction().unwrap();
+        let tx_id = tx.id();
+        let v3 = storage.restore_version(&mut tx, obj, v1).unwrap();
+        storage.commit(&mut tx).unwrap();
+
+        let mut recovered = JournaledStorage::from_journal(storage.journal_clone());
+        let versions = recovered.versions(obj).unwrap();
+        assert_eq!(versions.len(), 3);
+        assert_eq!(versions[0].version_id, v1);
+        assert_eq!(versions[2].version_id, v3);
+        assert_eq!(versions[2].transaction_id, Some(tx_id));
+        assert_eq!(
+            versions.iter().map(|v| v.committed_at).collect::<Vec<_>>(),
+            vec![10, 20, 30]
+        );
+        assert_eq!(recovered.read_version(obj, v3).unwrap(), b"v1");
+        assert!(recovered.read_version(obj, VersionId::new()).is_err());
+    }
+
+    #[test]
+    fn test_journal_append_applies_to_latest_version() {
+        let mut storage = JournaledStorage::new();
+        let log = ObjectId::new();
+
+        let mut tx = storage.begin_transaction().unwrap();
+        storage.append(&mut tx, log, b"one,").unwrap();
+        storage.append(&mut tx, log, b"two,").unwrap();
+        assert_eq!(storage.read_data(&tx, log).unwrap(), b"one,two,");
+        storage.commit(&mut tx).unwrap();
+
+        // A concurrent append lands on whatever is latest at commit time
+        let mut slow = storage.begin_transaction().unwrap();
+        storage.append(&mut slow, log, b"four").unwrap();
+        let mut fast = storage.begin_transaction().unwrap();
+        storage.append(&mut fast, log, b"three,").unwrap();
+        storage.commit(&mut fast).unwrap();
+        let version = storage.read(&slow, log).unwrap();
+        storage.commit(&mut slow).unwrap();
+
+        let recovered = JournaledStorage::from_journal(storage.journal_clone());
+        let read_tx = Transaction::new();
+        assert_eq!(recovered.read(&read_tx, log).unwrap(), version);
+        assert_eq!(
+            recovered.read_data(&read_tx, log).unwrap(),
+            b"one,two,three,four"
+        );
+        assert_eq!(recovered.read_range(&read_tx, log, 4, 4).unwrap(), b"two,");
+        assert!(recovered
+            .read_range(&read_tx, log, 100, 4)
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_storage_service_budget_enforcement() {
         let storage = JournaledStorage::new();
@@ -471,4 +1085,130 @@ mod tests {
         let result = service.write(exec_id, &mut tx, object, b"data2");
         assert!(matches!(result, Err(StorageServiceError::Budget(_))));
     }
+
+    fn note_service() -> StorageService<MockBudget> {
+        let mut service =
+            StorageService::new(JournaledStorage::new(), MockBudget { remaining: 100 });
+        let migrator = crate::SequentialMigrator::new()
+            .add_migration(|data| Ok([data, b" v2"].concat()))
+            .add_migration(|data| Ok([data, b" v3"].concat()));
+        service.register_schema("note", ObjectSchemaVersion::new(3), migrator);
+        service
+    }
+
+    #[test]
+    fn test_schema_tags_survive_recovery_and_appends() {
+        let mut service = note_service();
+        let exec_id = ExecutionId::new();
+        let object = ObjectId::new();
+        let mut tx = service.begin_transaction().unwrap();
+        let first = service
+            .write_with_schema(
+                exec_id,
+                &mut tx,
+                object,
+                b"a",
+                "note",
+                ObjectSchemaVersion::new(1),
+            )
+            .unwrap();
+        service.append(exec_id, &mut tx, object, b"b").unwrap();
+        service.commit(exec_id, &mut tx).unwrap();
+
+        let storage = JournaledStorage::from_journal(service.storage().journal_clone());
+        let tx = Transaction::new();
+        let tag = storage.schema(&tx, object).unwrap().unwrap();
+        assert_eq!(tag, SchemaTag::new("note", ObjectSchemaVersion::new(1)));
+        assert_eq!(storage.schema_at(object, first).unwrap(), Some(tag));
+        assert_eq!(storage.read_data(&tx, object).unwrap(), b"ab");
+    }
+
+    #[test]
+    fn test_read_migrated_is_lazy_unless_written_back() {
+        let mut service = note_service();
+        let exec_id = ExecutionId::new();
+        let object = ObjectId::new();
+        let mut tx = service.begin_transaction().unwrap();
+        let original = service
+            .write_with_schema(
+                exec_id,
+                &mut tx,
+                object,
+                b"n",
+                "note",
+                ObjectSchemaVersion::new(1),
+            )
+            .unwrap();
+        service.commit(exec_id, &mut tx).unwrap();
+
+        let mut tx = service.begin_transaction().unwrap();
+        let read = service
+            .read_migrated(exec_id, &mut tx, object, ObjectSchemaVersion::new(2))
+            .unwrap();
+        assert_eq!(read.data, b"n v2");
+        assert_eq!(
+            (read.version_id, read.migrated, read.written_back),
+            (original, true, false)
+        );
+        assert_eq!(service.storage().read_data(&tx, object).unwrap(), b"n");
+
+        service.set_migration_write_back(true);
+        let read = service.read_current(exec_id, &mut tx, object).unwrap();
+        assert_eq!(read.data, b"n v2 v3");
+        assert!(read.written_back);
+        service.commit(exec_id, &mut tx).unwrap();
+
+        let tx = service.begin_transaction().unwrap();
+        let tag = service.storage().schema(&tx, object).unwrap().unwrap();
+        assert_eq!(tag.version, ObjectSchemaVersion::new(3));
+        assert_eq!(tag.lineage.len(), 2);
+        assert_eq!(service.versions(exec_id, object).unwrap().len(), 2);
+        assert_eq!(
+            service
+                .storage()
+                .schema_at(object, original)
+                .unwrap()
+                .unwrap()
+                .version,
+            ObjectSchemaVersion::new(1)
+        );
+
+        // Already current: nothing to migrate or write
+        let mut tx = service.begin_transaction().unwrap();
+        let read = service.read_current(exec_id, &mut tx, object).unwrap();
+        assert!(!read.migrated && !read.written_back);
+    }
+
+    #[test]
+    fn test_read_migrated_reports_failure_with_lineage() {
+        let mut service = note_service();
+        let exec_id = ExecutionId::new();
+        let object = ObjectId::new();
+        let mut tx = service.begin_transaction().unwrap();
+        service
+            .write_with_schema(
+                exec_id,
+                &mut tx,
+                object,
+                b"n",
+                "note",
+                ObjectSchemaVersion::new(1),
+            )
+            .unwrap();
+        service.commit(exec_id, &mut tx).unwrap();
+
+        let mut tx = service.begin_transaction().unwrap();
+        let err = service
+            .read_migrated(exec_id, &mut tx, object, ObjectSchemaVersion::new(4))
+            .unwrap_err();
+        let StorageServiceError::Migration(err) = err else {
+            panic!("expected a migration error");
+        };
+        assert_eq!(err.lineage.len(), 3);
+        assert!(matches!(
+            err.error,
+            crate::MigrationError::UnsupportedMigration { .. }
+        ));
+        assert!(alloc::format!("{}", err).contains("v1 -> v2 -> v3 -> v4"));
+    }
 }
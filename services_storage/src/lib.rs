@@ -20,32 +20,58 @@
 //! - **VersionId**: Every object version is immutable and addressable
 //! - **Transactions**: Atomic operations with rollback
 //! - **Schema Evolution**: Objects have schema identity and version
-//! - **Migration**: Deterministic, testable data transformations
+//! - **Migration**: Deterministic, testable data transformations, applied
+//!   lazily when a reader asks for a newer schema version
+//! - **History**: Old versions can be listed, read, and restored
+//! - **Metadata**: Directory entries carry timestamps, a content type hint and
+//!   user-defined attributes
+//! - **Snapshots**: Read-only captures of a subtree and copy-on-write clones
+//! - **Integrity**: Checksummed extents and an incremental scrubber
+//! - **Access control**: Every checked operation is made on behalf of a
+//!   principal and authorized against its capabilities
 
 #![cfg_attr(not(test), no_std)]
 
 extern crate alloc;
 
 pub mod block_storage;
+pub mod checked_storage;
 pub mod failing_device;
+pub mod history;
 pub mod journaled_storage;
+pub mod metadata;
 pub mod migration;
 pub mod object;
 pub mod permissions;
 pub mod persistent_fs;
+pub mod schema;
+pub mod scrub;
+pub mod snapshot;
+pub mod stream;
 pub mod transaction;
 
-pub use block_storage::{BlockStorage, BlockStorageError, StorageRecoveryReport};
+pub use block_storage::{
+    BlockStorage, BlockStorageError, RetentionPolicy, StorageCompactionReport, StorageGcReport,
+    StorageRecoveryReport, StorageStats, DEDUP_CHUNK_SIZE,
+};
+pub use checked_storage::CheckedStorage;
 pub use failing_device::{FailingBlockDevice, FailurePolicy};
+pub use history::{VersionHistory, VersionInfo};
 pub use journaled_storage::{
-    JournaledStorage, StorageBudget, StorageOperation, StorageService, StorageServiceError,
+    JournaledStorage, MigratedRead, StorageBudget, StorageOperation, StorageService,
+    StorageServiceError,
 };
+pub use metadata::{ContentType, EntryMetadata};
 pub use migration::{create_lineage, MigrationError, Migrator, SequentialMigrator};
 pub use object::{Object, ObjectId, ObjectKind, VersionId};
 pub use permissions::{
     AccessDenialReason, Capability, CapabilityKind, Ownership, PermissionChecker, PrincipalId,
 };
 pub use persistent_fs::{DirectoryMetadata, PersistentDirectory, PersistentFilesystem};
+pub use schema::{SchemaMigrationError, SchemaRegistry, SchemaTag};
+pub use scrub::{DamagedVersion, ScrubReport, Scrubber};
+pub use snapshot::{Snapshot, TreeChange};
+pub use stream::{ObjectReader, ObjectWriter, STREAM_CHUNK_SIZE};
 pub use transaction::{
     Transaction, TransactionError, TransactionId, TransactionState, TransactionalStorage,
 };
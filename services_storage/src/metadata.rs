@@ -0,0 +1,221 @@
+//! Directory entry metadata
+//!
+//! Each name a directory holds carries an [`EntryMetadata`]: when it was
+//! created and last changed, an optional explicit [`ContentType`], and
+//! free-form key/value attributes. The same type is kept by the filesystem
+//! view and persisted in [`PersistentDirectory`](crate::PersistentDirectory)
+//! entries.
+
+use crate::ObjectKind;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// What an object holds, as a MIME-like hint
+///
+/// Content types are hints for presentation and filtering; nothing checks
+/// that an object's bytes agree with its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ContentType {
+    /// A directory
+    Directory,
+    /// Plain text
+    Text,
+    /// Markdown text
+    Markdown,
+    /// Program source code
+    Source,
+    /// JSON document
+    Json,
+    /// TOML document
+    Toml,
+    /// Append-only log
+    Log,
+    /// Anything else
+    Binary,
+}
+
+impl ContentType {
+    /// Every content type, in sort order
+    pub const ALL: [ContentType; 8] = [
+        ContentType::Directory,
+        ContentType::Text,
+        ContentType::Markdown,
+        ContentType::Source,
+        ContentType::Json,
+        ContentType::Toml,
+        ContentType::Log,
+        ContentType::Binary,
+    ];
+
+    /// Guesses a content type from an entry's name and object kind
+    ///
+    /// Names without an extension are taken to be text.
+    pub fn guess(name: &str, kind: ObjectKind) -> Self {
+        match kind {
+            ObjectKind::Map => return ContentType::Directory,
+            ObjectKind::Log => return ContentType::Log,
+            ObjectKind::Blob => {}
+        }
+        let Some((_, extension)) = name.rsplit_once('.').filter(|(stem, _)| !stem.is_empty())
+        else {
+            return ContentType::Text;
+        };
+        match extension.to_ascii_lowercase().as_str() {
+            "txt" | "text" | "cfg" | "conf" | "ini" => ContentType::Text,
+            "md" | "markdown" => ContentType::Markdown,
+            "rs" | "c" | "h" | "py" | "js" | "ts" | "sh" | "asm" | "s" | "ld" => {
+                ContentType::Source
+            }
+            "json" => ContentType::Json,
+            "toml" => ContentType::Toml,
+            "log" => ContentType::Log,
+            _ => ContentType::Binary,
+        }
+    }
+
+    /// Parses a MIME type or a short name such as `markdown`
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|content_type| value == content_type.mime_type() || value == content_type.name())
+    }
+
+    /// MIME type for this content type
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ContentType::Directory => "inode/directory",
+            ContentType::Text => "text/plain",
+            ContentType::Markdown => "text/markdown",
+            ContentType::Source => "text/x-source",
+            ContentType::Json => "application/json",
+            ContentType::Toml => "application/toml",
+            ContentType::Log => "text/x-log",
+            ContentType::Binary => "application/octet-stream",
+        }
+    }
+
+    /// Short lowercase name
+    pub fn name(self) -> &'static str {
+        match self {
+            ContentType::Directory => "directory",
+            ContentType::Text => "text",
+            ContentType::Markdown => "markdown",
+            ContentType::Source => "source",
+            ContentType::Json => "json",
+            ContentType::Toml => "toml",
+            ContentType::Log => "log",
+            ContentType::Binary => "binary",
+        }
+    }
+
+    /// Whether the content is meant to be read as text
+    pub fn is_text(self) -> bool {
+        !matches!(self, ContentType::Directory | ContentType::Binary)
+    }
+}
+
+impl fmt::Display for ContentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.mime_type())
+    }
+}
+
+/// Metadata kept with a directory entry
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryMetadata {
+    /// When the entry was created (0 if unknown)
+    #[serde(default)]
+    pub created_at: u64,
+    /// When the entry last changed (0 if unknown)
+    #[serde(default)]
+    pub modified_at: u64,
+    /// Explicit content type; guessed from the name when absent
+    #[serde(default)]
+    pub content_type: Option<ContentType>,
+    /// User-defined attributes
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl EntryMetadata {
+    /// Creates metadata for an entry made at `timestamp`
+    pub fn new(timestamp: u64) -> Self {
+        Self {
+            created_at: timestamp,
+            modified_at: timestamp,
+            ..Self::default()
+        }
+    }
+
+    /// Records a change at `timestamp`
+    pub fn touch(&mut self, timestamp: u64) {
+        self.modified_at = self.modified_at.max(timestamp);
+    }
+
+    /// Content type of an entry called `name`, explicit or guessed
+    pub fn content_type_for(&self, name: &str, kind: ObjectKind) -> ContentType {
+        self.content_type
+            .unwrap_or_else(|| ContentType::guess(name, kind))
+    }
+
+    /// Returns an attribute's value
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guess_and_parse_content_types() {
+        assert_eq!(
+            ContentType::guess("notes.MD", ObjectKind::Blob),
+            ContentType::Markdown
+        );
+        assert_eq!(
+            ContentType::guess("README", ObjectKind::Blob),
+            ContentType::Text
+        );
+        assert_eq!(
+            ContentType::guess(".hidden", ObjectKind::Blob),
+            ContentType::Text
+        );
+        assert_eq!(
+            ContentType::guess("image.png", ObjectKind::Blob),
+            ContentType::Binary
+        );
+        assert_eq!(
+            ContentType::guess("src.rs", ObjectKind::Map),
+            ContentType::Directory
+        );
+
+        assert_eq!(
+            ContentType::parse("text/markdown"),
+            Some(ContentType::Markdown)
+        );
+        assert_eq!(ContentType::parse(" JSON "), Some(ContentType::Json));
+        assert_eq!(ContentType::parse("image/png"), None);
+        assert!(!ContentType::Binary.is_text());
+    }
+
+    #[test]
+    fn test_metadata_defaults_when_absent() {
+        let metadata: EntryMetadata = serde_json::from_str("{}").unwrap();
+        assert_eq!(metadata, EntryMetadata::default());
+
+        let mut metadata = EntryMetadata::new(10);
+        metadata.touch(5);
+        assert_eq!(metadata.modified_at, 10);
+        metadata.touch(20);
+        assert_eq!((metadata.created_at, metadata.modified_at), (10, 20));
+        assert_eq!(
+            metadata.content_type_for("a.toml", ObjectKind::Blob),
+            ContentType::Toml
+        );
+    }
+}
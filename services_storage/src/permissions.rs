@@ -12,11 +12,20 @@
 //! 2. **Explicit ownership**: Track which component/user created each object
 //! 3. **Clear error messages**: Explain WHY access failed, not just "no"
 //! 4. **Typed access**: Read/Write/Execute are distinct capabilities
+//!
+//! ## Enforcement
+//!
+//! [`PermissionChecker::authorize`] decides whether a principal may act on
+//! an object: owners and the system principal always may, anyone else needs
+//! a granted capability. [`crate::CheckedStorage`] applies it to every
+//! storage call.
 
 use alloc::collections::BTreeMap;
 use alloc::string::String;
+use alloc::vec::Vec;
 use core::fmt;
 use core_types::new_uuid;
+use identity::ExecutionId;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -49,12 +58,28 @@ impl PrincipalId {
     }
 }
 
+/// A running execution acts as the principal of the same ID
+impl From<ExecutionId> for PrincipalId {
+    fn from(execution_id: ExecutionId) -> Self {
+        Self(execution_id.as_uuid())
+    }
+}
+
 impl Default for PrincipalId {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Parses the bare UUID form, as recorded in on-disk owner fields
+impl core::str::FromStr for PrincipalId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s).map(Self)
+    }
+}
+
 impl fmt::Display for PrincipalId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if *self == Self::system() {
@@ -236,7 +261,7 @@ impl fmt::Display for AccessDenialReason {
             } => {
                 write!(
                     f,
-                    "Access denied: {} requires {} capability on {}, but none was provided",
+                    "{} requires {} capability on {}, but none was provided",
                     principal, required, object_id
                 )
             }
@@ -246,7 +271,7 @@ impl fmt::Display for AccessDenialReason {
             } => {
                 write!(
                     f,
-                    "Access denied: Capability is for {}, but access to {} was requested",
+                    "Capability is for {}, but access to {} was requested",
                     capability_object, requested_object
                 )
             }
@@ -256,7 +281,7 @@ impl fmt::Display for AccessDenialReason {
             } => {
                 write!(
                     f,
-                    "Access denied: Capability is held by {}, but {} is requesting access",
+                    "Capability is held by {}, but {} is requesting access",
                     capability_holder, requesting_principal
                 )
             }
@@ -266,24 +291,27 @@ impl fmt::Display for AccessDenialReason {
             } => {
                 write!(
                     f,
-                    "Access denied: Operation requires {} capability, but only {} was provided",
+                    "Operation requires {} capability, but only {} was provided",
                     required_kind, capability_kind
                 )
             }
             AccessDenialReason::ObjectNotFound { object_id } => {
-                write!(f, "Access denied: Object {} does not exist", object_id)
+                write!(f, "Object {} does not exist", object_id)
             }
             AccessDenialReason::VersionNotFound { version_id } => {
-                write!(f, "Access denied: Version {} does not exist", version_id)
+                write!(f, "Version {} does not exist", version_id)
             }
         }
     }
 }
 
 /// Permission checker for validating capabilities
+#[derive(Debug, Clone)]
 pub struct PermissionChecker {
     /// Map of object ID to ownership
     ownership: BTreeMap<ObjectId, Ownership>,
+    /// Capabilities granted on each object
+    grants: BTreeMap<ObjectId, Vec<Capability>>,
 }
 
 impl PermissionChecker {
@@ -291,6 +319,7 @@ impl PermissionChecker {
     pub fn new() -> Self {
         Self {
             ownership: BTreeMap::new(),
+            grants: BTreeMap::new(),
         }
     }
 
@@ -299,6 +328,93 @@ impl PermissionChecker {
         self.ownership.insert(object_id, ownership);
     }
 
+    /// Forgets an object along with every capability granted on it
+    pub fn unregister_object(&mut self, object_id: ObjectId) -> Option<Ownership> {
+        self.grants.remove(&object_id);
+        self.ownership.remove(&object_id)
+    }
+
+    /// Records a modification of a registered object
+    pub fn touch(&mut self, object_id: ObjectId, modifier: PrincipalId, timestamp: u64) {
+        if let Some(ownership) = self.ownership.get_mut(&object_id) {
+            ownership.update(modifier, timestamp);
+        }
+    }
+
+    /// Hands a capability to its holder
+    pub fn grant(&mut self, capability: Capability) {
+        self.grants
+            .entry(capability.object_id)
+            .or_default()
+            .push(capability);
+    }
+
+    /// Revokes the capabilities of `kind` that `holder` has on an object
+    ///
+    /// Returns how many were revoked.
+    pub fn revoke(
+        &mut self,
+        object_id: ObjectId,
+        kind: CapabilityKind,
+        holder: PrincipalId,
+    ) -> usize {
+        let Some(granted) = self.grants.get_mut(&object_id) else {
+            return 0;
+        };
+        let before = granted.len();
+        granted.retain(|cap| cap.kind != kind || cap.holder != holder);
+        before - granted.len()
+    }
+
+    /// Capabilities `principal` holds on an object
+    pub fn capabilities(&self, object_id: ObjectId, principal: PrincipalId) -> Vec<&Capability> {
+        self.grants
+            .get(&object_id)
+            .map(|granted| {
+                granted
+                    .iter()
+                    .filter(|cap| cap.holder == principal)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Checks whether `principal` may perform a `required` operation on an object
+    ///
+    /// The system principal and the object's owner may do anything. Anyone
+    /// else needs a granted capability that passes [`Self::check_access`].
+    pub fn authorize(
+        &self,
+        principal: PrincipalId,
+        object_id: ObjectId,
+        required: CapabilityKind,
+    ) -> Result<(), AccessDenialReason> {
+        if principal == PrincipalId::system() {
+            return Ok(());
+        }
+        if !self.ownership.contains_key(&object_id) {
+            return Err(AccessDenialReason::ObjectNotFound { object_id });
+        }
+        if self.is_owner(object_id, principal) {
+            return Ok(());
+        }
+
+        let mut denial = None;
+        for capability in self.capabilities(object_id, principal) {
+            match self.check_access(capability, object_id, required, principal) {
+                Ok(()) => return Ok(()),
+                Err(reason) => {
+                    denial.get_or_insert(reason);
+                }
+            }
+        }
+        Err(denial.unwrap_or(AccessDenialReason::MissingCapability {
+            required,
+            object_id,
+            principal,
+        }))
+    }
+
     /// Checks if a capability is valid for an operation
     pub fn check_access(
         &self,
@@ -569,7 +685,9 @@ mod tests {
         };
 
         let display = format!("{}", reason);
-        assert!(display.contains("Access denied"));
         assert!(display.contains("Write"));
+        // The error wrapping the reason says what happened
+        let error = crate::TransactionError::AccessDenied(reason).to_string();
+        assert_eq!(error, format!("Access denied: {}", display));
     }
 }
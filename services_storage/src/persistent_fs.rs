@@ -2,11 +2,33 @@
 //!
 //! This module integrates fs_view with block-backed storage to provide
 //! persistent filesystem capabilities.
+//!
+//! Objects may be linked from several directories. Link counts are derived
+//! from the directory tree (rebuilt on open), and `rm` deletes an object
+//! only when its last link goes away.
+//!
+//! Files can be read in ranges and written as streams, and `Log` entries
+//! can be appended to without rewriting their earlier blocks.
+//!
+//! Updating a file adds a version instead of replacing the object, so its
+//! history can be listed and an earlier version restored.
+//!
+//! Entries carry [`EntryMetadata`]: `link` and `mkdir` stamp their times,
+//! relinking a name keeps its creation time and attributes, and
+//! [`PersistentFilesystem::update_metadata`] edits the rest. Directories
+//! written before entries had metadata read back with empty metadata.
+//!
+//! Named snapshots capture a subtree (see [`crate::snapshot`]). Objects a
+//! snapshot captured are not deleted by `rm` while the snapshot exists;
+//! deleting the snapshot reclaims the ones nothing links to any more.
 
+use crate::snapshot::{catalog_id, SnapshotCatalog};
 use crate::{
-    BlockStorage, ObjectId, ObjectKind, TransactionError, TransactionalStorage, VersionId,
+    BlockStorage, BlockStorageError, EntryMetadata, ObjectId, ObjectKind, ObjectReader,
+    ObjectWriter, Snapshot, Transaction, TransactionError, TransactionalStorage, TreeChange,
+    VersionHistory, VersionId, VersionInfo,
 };
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
@@ -22,6 +44,9 @@ pub struct DirectoryEntry {
     pub object_id: ObjectId,
     /// Object kind (Blob, Map, Log)
     pub kind: ObjectKind,
+    /// Timestamps, content type and attributes
+    #[serde(default)]
+    pub metadata: EntryMetadata,
 }
 
 impl DirectoryEntry {
@@ -31,8 +56,15 @@ impl DirectoryEntry {
             name,
             object_id,
             kind,
+            metadata: EntryMetadata::default(),
         }
     }
+
+    /// Sets the entry's metadata
+    pub fn with_metadata(mut self, metadata: EntryMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 /// Persistent directory storage backed by blocks
@@ -104,8 +136,15 @@ impl PersistentDirectory {
 pub struct PersistentFilesystem<D: BlockDevice> {
     storage: BlockStorage<D>,
     root_dir_id: ObjectId,
+    /// Number of directory entries pointing at each object
+    link_counts: BTreeMap<ObjectId, usize>,
+    /// Named snapshots (persisted in the catalog object)
+    snapshots: BTreeMap<String, Snapshot>,
 }
 
+/// Where each path under a directory points: (object, version, is directory)
+type TreePaths = BTreeMap<String, (ObjectId, VersionId, bool)>;
+
 impl<D: BlockDevice> PersistentFilesystem<D> {
     /// Create a new filesystem with an empty root directory
     pub fn format(device: D, owner: impl Into<String>) -> Result<Self, TransactionError> {
@@ -126,6 +165,8 @@ impl<D: BlockDevice> PersistentFilesystem<D> {
         Ok(Self {
             storage,
             root_dir_id,
+            link_counts: BTreeMap::new(),
+            snapshots: BTreeMap::new(),
         })
     }
 
@@ -134,10 +175,65 @@ impl<D: BlockDevice> PersistentFilesystem<D> {
         let storage = BlockStorage::open(device)
             .map_err(|e| TransactionError::StorageError(format!("open failed: {:?}", e)))?;
 
-        Ok(Self {
+        let mut fs = Self {
             storage,
             root_dir_id,
-        })
+            link_counts: BTreeMap::new(),
+            snapshots: BTreeMap::new(),
+        };
+        fs.rebuild_link_counts()?;
+        fs.load_snapshots()?;
+        Ok(fs)
+    }
+
+    /// Recounts links by walking the directory tree from the root
+    fn rebuild_link_counts(&mut self) -> Result<(), TransactionError> {
+        self.link_counts.clear();
+        let mut visited = BTreeSet::new();
+        let mut stack = alloc::vec![self.root_dir_id];
+        while let Some(dir_id) = stack.pop() {
+            if !visited.insert(dir_id) {
+                continue;
+            }
+            let dir = self.read_directory(dir_id)?;
+            for entry in dir.entries.values() {
+                *self.link_counts.entry(entry.object_id).or_insert(0) += 1;
+                if entry.kind == ObjectKind::Map && self.is_directory(entry.object_id) {
+                    stack.push(entry.object_id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether an object holds a directory
+    fn is_directory(&mut self, object_id: ObjectId) -> bool {
+        self.read_directory(object_id).is_ok()
+    }
+
+    /// Number of directory entries linking to an object
+    pub fn link_count(&self, object_id: ObjectId) -> usize {
+        self.link_counts.get(&object_id).copied().unwrap_or(0)
+    }
+
+    fn add_link(&mut self, object_id: ObjectId) {
+        *self.link_counts.entry(object_id).or_insert(0) += 1;
+    }
+
+    /// Drops one link, returning how many remain
+    fn drop_link(&mut self, object_id: ObjectId) -> usize {
+        let remaining = self.link_count(object_id).saturating_sub(1);
+        if remaining == 0 {
+            self.link_counts.remove(&object_id);
+        } else {
+            self.link_counts.insert(object_id, remaining);
+        }
+        remaining
+    }
+
+    /// Consumes the filesystem, returning the underlying device
+    pub fn into_device(self) -> D {
+        self.storage.into_device()
     }
 
     /// Get the root directory ID
@@ -198,9 +294,11 @@ impl<D: BlockDevice> PersistentFilesystem<D> {
 
         // Add entry to parent directory
         let mut parent = self.read_directory(parent_dir_id)?;
-        let entry = DirectoryEntry::new(name.into(), new_dir_id, ObjectKind::Map);
+        let entry = DirectoryEntry::new(name.into(), new_dir_id, ObjectKind::Map)
+            .with_metadata(EntryMetadata::new(timestamp));
         parent.add_entry(entry.name.clone(), entry, timestamp);
         self.write_directory(parent_dir_id, &parent)?;
+        self.add_link(new_dir_id);
 
         Ok(new_dir_id)
     }
@@ -215,13 +313,50 @@ impl<D: BlockDevice> PersistentFilesystem<D> {
         timestamp: u64,
     ) -> Result<(), TransactionError> {
         let mut dir = self.read_directory(dir_id)?;
-        let entry = DirectoryEntry::new(name.into(), object_id, kind);
+        let name = name.into();
+        let replaced = dir.get_entry(&name).cloned();
+        let metadata = match &replaced {
+            Some(old) => {
+                let mut metadata = old.metadata.clone();
+                metadata.touch(timestamp);
+                metadata
+            }
+            None => EntryMetadata::new(timestamp),
+        };
+        let entry = DirectoryEntry::new(name, object_id, kind).with_metadata(metadata);
+        let replaced = replaced.map(|old| old.object_id);
         dir.add_entry(entry.name.clone(), entry, timestamp);
         self.write_directory(dir_id, &dir)?;
+        if let Some(replaced) = replaced {
+            self.drop_link(replaced);
+        }
+        self.add_link(object_id);
+        Ok(())
+    }
+
+    /// Edits an entry's metadata, recording the change at `timestamp`
+    pub fn update_metadata(
+        &mut self,
+        name: &str,
+        dir_id: ObjectId,
+        timestamp: u64,
+        update: impl FnOnce(&mut EntryMetadata),
+    ) -> Result<(), TransactionError> {
+        let mut dir = self.read_directory(dir_id)?;
+        let entry = dir
+            .entries
+            .get_mut(name)
+            .ok_or_else(|| TransactionError::ObjectNotFound(format!("no entry: {}", name)))?;
+        update(&mut entry.metadata);
+        entry.metadata.touch(timestamp);
+        self.write_directory(dir_id, &dir)?;
         Ok(())
     }
 
     /// Unlink an entry from a directory
+    ///
+    /// Only the name goes away; the object is kept even if this was its last
+    /// link. Use [`Self::rm`] to reclaim it.
     pub fn unlink(
         &mut self,
         name: &str,
@@ -231,9 +366,61 @@ impl<D: BlockDevice> PersistentFilesystem<D> {
         let mut dir = self.read_directory(dir_id)?;
         let entry = dir.remove_entry(name, timestamp);
         self.write_directory(dir_id, &dir)?;
+        if let Some(entry) = &entry {
+            self.drop_link(entry.object_id);
+        }
         Ok(entry)
     }
 
+    /// Remove an entry, deleting the object once nothing links to it
+    ///
+    /// The directory update and the delete commit in one transaction.
+    /// Removing the last link to a non-empty directory fails.
+    pub fn rm(
+        &mut self,
+        name: &str,
+        dir_id: ObjectId,
+        timestamp: u64,
+    ) -> Result<Option<DirectoryEntry>, TransactionError> {
+        let mut dir = self.read_directory(dir_id)?;
+        let Some(entry) = dir.remove_entry(name, timestamp) else {
+            return Ok(None);
+        };
+
+        let last_link = self.link_count(entry.object_id) <= 1;
+        if last_link && entry.kind == ObjectKind::Map {
+            if let Ok(target) = self.read_directory(entry.object_id) {
+                if !target.entries.is_empty() {
+                    return Err(TransactionError::InvalidOperation(format!(
+                        "directory not empty: {}",
+                        name
+                    )));
+                }
+            }
+        }
+
+        let dir_json = serde_json::to_vec(&dir)
+            .map_err(|e| TransactionError::StorageError(format!("serialize failed: {:?}", e)))?;
+        let delete_target = last_link && !self.in_snapshot(entry.object_id);
+        let mut tx = self.storage.begin_transaction()?;
+        let result = self
+            .storage
+            .write(&mut tx, dir_id, &dir_json)
+            .and_then(|_| {
+                if delete_target {
+                    self.storage.delete(&mut tx, entry.object_id)?;
+                }
+                self.storage.commit(&mut tx)
+            });
+        if let Err(e) = result {
+            let _ = self.storage.rollback(&mut tx);
+            return Err(e);
+        }
+
+        self.drop_link(entry.object_id);
+        Ok(Some(entry))
+    }
+
     /// List directory contents
     pub fn list(
         &mut self,
@@ -252,6 +439,458 @@ impl<D: BlockDevice> PersistentFilesystem<D> {
         Ok(file_id)
     }
 
+    /// Write new content to an existing file as a new version
+    pub fn update_file(
+        &mut self,
+        file_id: ObjectId,
+        content: &[u8],
+    ) -> Result<VersionId, TransactionError> {
+        let mut tx = self.storage.begin_transaction()?;
+        self.storage.read(&tx, file_id)?;
+        let version_id = self.storage.write(&mut tx, file_id, content)?;
+        self.storage.commit(&mut tx)?;
+        Ok(version_id)
+    }
+
+    /// List a file's versions, oldest first
+    pub fn file_history(&self, file_id: ObjectId) -> Result<Vec<VersionInfo>, TransactionError> {
+        self.storage.versions(file_id)
+    }
+
+    /// Read a file as it was at `version_id`
+    pub fn read_file_version(
+        &mut self,
+        file_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<Vec<u8>, TransactionError> {
+        self.storage.read_version(file_id, version_id)
+    }
+
+    /// Make an earlier version the file's current content
+    ///
+    /// The restore is committed as a new version, so it can itself be undone.
+    pub fn restore_file(
+        &mut self,
+        file_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<VersionId, TransactionError> {
+        let mut tx = self.storage.begin_transaction()?;
+        let restored = self.storage.restore_version(&mut tx, file_id, version_id)?;
+        self.storage.commit(&mut tx)?;
+        Ok(restored)
+    }
+
+    /// Take a named, read-only snapshot of the subtree under `dir_id`
+    ///
+    /// The versions are captured in one pass, so the snapshot is
+    /// consistent with the commit sequence it records.
+    pub fn create_snapshot(
+        &mut self,
+        name: impl Into<String>,
+        dir_id: ObjectId,
+        timestamp: u64,
+    ) -> Result<Snapshot, TransactionError> {
+        let name = name.into();
+        if self.snapshots.contains_key(&name) {
+            return Err(TransactionError::InvalidOperation(format!(
+                "snapshot exists: {}",
+                name
+            )));
+        }
+        let mut snapshot = self.capture(dir_id, timestamp)?;
+        snapshot.name = name.clone();
+
+        let mut tx = self.storage.begin_transaction()?;
+        self.snapshots.insert(name.clone(), snapshot.clone());
+        let saved = self
+            .write_catalog(&mut tx)
+            .and_then(|()| self.storage.commit(&mut tx));
+        if let Err(e) = saved {
+            let _ = self.storage.rollback(&mut tx);
+            self.snapshots.remove(&name);
+            return Err(e);
+        }
+
+        for (object_id, version_id) in &snapshot.versions {
+            self.storage.pin_version(*object_id, *version_id);
+        }
+        Ok(snapshot)
+    }
+
+    /// Snapshot by name
+    pub fn snapshot(&self, name: &str) -> Option<&Snapshot> {
+        self.snapshots.get(name)
+    }
+
+    /// All snapshots, ordered by name
+    pub fn snapshots(&self) -> Vec<&Snapshot> {
+        self.snapshots.values().collect()
+    }
+
+    /// Delete a snapshot, reclaiming captured objects nothing links to
+    pub fn delete_snapshot(&mut self, name: &str) -> Result<Snapshot, TransactionError> {
+        let snapshot = self
+            .snapshots
+            .remove(name)
+            .ok_or_else(|| TransactionError::ObjectNotFound(format!("snapshot {}", name)))?;
+
+        let mut tx = self.storage.begin_transaction()?;
+        let result = self.write_catalog(&mut tx).and_then(|()| {
+            for object_id in snapshot.versions.keys() {
+                if self.is_orphan(*object_id) && self.storage.read(&tx, *object_id).is_ok() {
+                    self.storage.delete(&mut tx, *object_id)?;
+                }
+            }
+            self.storage.commit(&mut tx)
+        });
+        if let Err(e) = result {
+            let _ = self.storage.rollback(&mut tx);
+            self.snapshots.insert(name.into(), snapshot);
+            return Err(e);
+        }
+
+        for (object_id, version_id) in &snapshot.versions {
+            self.storage.unpin_version(*object_id, *version_id);
+        }
+        Ok(snapshot)
+    }
+
+    /// List a directory as it was in a snapshot
+    pub fn snapshot_list(
+        &mut self,
+        name: &str,
+        dir_id: ObjectId,
+    ) -> Result<Vec<(String, DirectoryEntry)>, TransactionError> {
+        let version_id = self.snapshot_version(name, dir_id)?;
+        Ok(self.read_directory_at(dir_id, version_id)?.list_entries())
+    }
+
+    /// Read a file as it was in a snapshot
+    pub fn snapshot_read_file(
+        &mut self,
+        name: &str,
+        file_id: ObjectId,
+    ) -> Result<Vec<u8>, TransactionError> {
+        let version_id = self.snapshot_version(name, file_id)?;
+        self.storage.read_version(file_id, version_id)
+    }
+
+    /// Compare a snapshot with the current state of its subtree
+    ///
+    /// Directories are reported only when added, removed, or relinked;
+    /// changes inside them are reported by path. A file whose version
+    /// changed but whose content matches (after a restore) is unchanged.
+    pub fn diff_snapshot(&mut self, name: &str) -> Result<Vec<TreeChange>, TransactionError> {
+        let snapshot = self.snapshot_by_name(name)?;
+        let then = self.tree_paths(snapshot.root, Some(&snapshot))?;
+        let now = self.tree_paths(snapshot.root, None)?;
+
+        let mut changes = Vec::new();
+        for (path, (object_id, version_id, is_dir)) in &then {
+            match now.get(path) {
+                None => changes.push(TreeChange::Removed(path.clone())),
+                Some((now_id, now_version, _)) => {
+                    let modified = now_id != object_id
+                        || (!is_dir
+                            && now_version != version_id
+                            && self.storage.read_version(*object_id, *version_id)?
+                                != self.storage.read_version(*now_id, *now_version)?);
+                    if modified {
+                        changes.push(TreeChange::Modified(path.clone()));
+                    }
+                }
+            }
+        }
+        for path in now.keys().filter(|path| !then.contains_key(*path)) {
+            changes.push(TreeChange::Added(path.clone()));
+        }
+        changes.sort();
+        Ok(changes)
+    }
+
+    /// Roll the snapshot's subtree back to the captured state
+    ///
+    /// Every captured object that changed gets its captured version back
+    /// as a new version, in one transaction. Objects added since, and no
+    /// longer linked afterwards, are deleted in a second transaction; a
+    /// crash between the two only leaves them unreferenced. Returns the
+    /// number of objects restored.
+    pub fn restore_snapshot(&mut self, name: &str) -> Result<usize, TransactionError> {
+        let snapshot = self.snapshot_by_name(name)?;
+        let before = self.tree_paths(snapshot.root, None)?;
+
+        let mut tx = self.storage.begin_transaction()?;
+        let mut restored = 0;
+        for (object_id, version_id) in &snapshot.versions {
+            if self.storage.read(&tx, *object_id).ok() != Some(*version_id) {
+                self.storage
+                    .restore_version(&mut tx, *object_id, *version_id)?;
+                restored += 1;
+            }
+        }
+        self.storage.commit(&mut tx)?;
+        self.rebuild_link_counts()?;
+
+        let mut tx = self.storage.begin_transaction()?;
+        let mut deleted = BTreeSet::new();
+        for (object_id, _, _) in before.values() {
+            if self.is_orphan(*object_id) && deleted.insert(*object_id) {
+                self.storage.delete(&mut tx, *object_id)?;
+            }
+        }
+        self.storage.commit(&mut tx)?;
+        Ok(restored)
+    }
+
+    /// Create a writable copy-on-write clone of a snapshot
+    ///
+    /// The clone is linked as `dest_name` in `parent_dir_id`. Returns the
+    /// clone's root directory.
+    pub fn clone_snapshot(
+        &mut self,
+        name: &str,
+        dest_name: &str,
+        parent_dir_id: ObjectId,
+        timestamp: u64,
+    ) -> Result<ObjectId, TransactionError> {
+        let snapshot = self.snapshot_by_name(name)?;
+        self.clone_captured(&snapshot, dest_name, parent_dir_id, timestamp)
+    }
+
+    /// Create a writable copy-on-write clone of a live subtree
+    pub fn clone_tree(
+        &mut self,
+        dir_id: ObjectId,
+        dest_name: &str,
+        parent_dir_id: ObjectId,
+        timestamp: u64,
+    ) -> Result<ObjectId, TransactionError> {
+        let snapshot = self.capture(dir_id, timestamp)?;
+        self.clone_captured(&snapshot, dest_name, parent_dir_id, timestamp)
+    }
+
+    /// Clones captured versions under fresh IDs in one transaction
+    fn clone_captured(
+        &mut self,
+        snapshot: &Snapshot,
+        dest_name: &str,
+        parent_dir_id: ObjectId,
+        timestamp: u64,
+    ) -> Result<ObjectId, TransactionError> {
+        let mut parent = self.read_directory(parent_dir_id)?;
+        if parent.get_entry(dest_name).is_some() {
+            return Err(TransactionError::InvalidOperation(format!(
+                "entry exists: {}",
+                dest_name
+            )));
+        }
+        let renamed: BTreeMap<ObjectId, ObjectId> = snapshot
+            .versions
+            .keys()
+            .map(|object_id| (*object_id, ObjectId::new()))
+            .collect();
+
+        let mut tx = self.storage.begin_transaction()?;
+        let result = (|| {
+            for (object_id, version_id) in &snapshot.versions {
+                let new_id = renamed[object_id];
+                match self.read_directory_at(*object_id, *version_id) {
+                    Ok(mut dir) => {
+                        for entry in dir.entries.values_mut() {
+                            if let Some(new_target) = renamed.get(&entry.object_id) {
+                                entry.object_id = *new_target;
+                            }
+                        }
+                        dir.parent = if *object_id == snapshot.root {
+                            Some(parent_dir_id)
+                        } else {
+                            dir.parent.and_then(|p| renamed.get(&p).copied())
+                        };
+                        let json = serde_json::to_vec(&dir).map_err(|e| {
+                            TransactionError::StorageError(format!("serialize failed: {:?}", e))
+                        })?;
+                        self.storage.write(&mut tx, new_id, &json)?;
+                    }
+                    Err(_) => {
+                        self.storage
+                            .clone_version(&mut tx, *object_id, *version_id, new_id)?;
+                    }
+                }
+            }
+
+            let root = renamed[&snapshot.root];
+            let entry = DirectoryEntry::new(dest_name.into(), root, ObjectKind::Map);
+            parent.add_entry(entry.name.clone(), entry, timestamp);
+            let json = serde_json::to_vec(&parent).map_err(|e| {
+                TransactionError::StorageError(format!("serialize failed: {:?}", e))
+            })?;
+            self.storage.write(&mut tx, parent_dir_id, &json)?;
+            self.storage.commit(&mut tx)
+        })();
+        if let Err(e) = result {
+            let _ = self.storage.rollback(&mut tx);
+            return Err(e);
+        }
+
+        self.rebuild_link_counts()?;
+        Ok(renamed[&snapshot.root])
+    }
+
+    /// Captures the latest version of every object under `dir_id`
+    fn capture(&mut self, dir_id: ObjectId, timestamp: u64) -> Result<Snapshot, TransactionError> {
+        let sequence = self.storage.commit_sequence();
+        let tx = self.storage.begin_transaction()?;
+        let mut versions = BTreeMap::new();
+        let mut stack = alloc::vec![dir_id];
+        while let Some(object_id) = stack.pop() {
+            if versions.contains_key(&object_id) {
+                continue;
+            }
+            let Ok(version_id) = self.storage.read(&tx, object_id) else {
+                // Dangling link
+                continue;
+            };
+            versions.insert(object_id, version_id);
+            if let Ok(dir) = self.read_directory_at(object_id, version_id) {
+                stack.extend(dir.entries.values().map(|entry| entry.object_id));
+            }
+        }
+        if !versions.contains_key(&dir_id) {
+            return Err(TransactionError::ObjectNotFound(format!("{}", dir_id)));
+        }
+
+        Ok(Snapshot {
+            name: String::new(),
+            root: dir_id,
+            sequence,
+            created_at: timestamp,
+            versions,
+        })
+    }
+
+    /// Maps every path under `root` to its target, live or as captured
+    fn tree_paths(
+        &mut self,
+        root: ObjectId,
+        snapshot: Option<&Snapshot>,
+    ) -> Result<TreePaths, TransactionError> {
+        let tx = self.storage.begin_transaction()?;
+        let mut paths = BTreeMap::new();
+        let mut visited = BTreeSet::new();
+        let mut stack = alloc::vec![(String::new(), root)];
+        while let Some((prefix, dir_id)) = stack.pop() {
+            if !visited.insert(dir_id) {
+                continue;
+            }
+            let version_id = match snapshot {
+                Some(snapshot) => snapshot.version_of(dir_id),
+                None => self.storage.read(&tx, dir_id).ok(),
+            };
+            let Some(version_id) = version_id else {
+                continue;
+            };
+            let dir = self.read_directory_at(dir_id, version_id)?;
+            for (name, entry) in dir.entries {
+                let path = if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{}/{}", prefix, name)
+                };
+                let version_id = match snapshot {
+                    Some(snapshot) => snapshot.version_of(entry.object_id),
+                    None => self.storage.read(&tx, entry.object_id).ok(),
+                };
+                let Some(version_id) = version_id else {
+                    continue;
+                };
+                let is_dir = entry.kind == ObjectKind::Map
+                    && self.read_directory_at(entry.object_id, version_id).is_ok();
+                if is_dir {
+                    stack.push((path.clone(), entry.object_id));
+                }
+                paths.insert(path, (entry.object_id, version_id, is_dir));
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Read a directory at a specific version
+    fn read_directory_at(
+        &mut self,
+        dir_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<PersistentDirectory, TransactionError> {
+        let data = self.storage.read_version(dir_id, version_id)?;
+        serde_json::from_slice(&data)
+            .map_err(|e| TransactionError::StorageError(format!("deserialize failed: {:?}", e)))
+    }
+
+    fn snapshot_by_name(&self, name: &str) -> Result<Snapshot, TransactionError> {
+        self.snapshots
+            .get(name)
+            .cloned()
+            .ok_or_else(|| TransactionError::ObjectNotFound(format!("snapshot {}", name)))
+    }
+
+    fn snapshot_version(
+        &self,
+        name: &str,
+        object_id: ObjectId,
+    ) -> Result<VersionId, TransactionError> {
+        let snapshot = self
+            .snapshots
+            .get(name)
+            .ok_or_else(|| TransactionError::ObjectNotFound(format!("snapshot {}", name)))?;
+        snapshot.version_of(object_id).ok_or_else(|| {
+            TransactionError::ObjectNotFound(format!("{} in snapshot {}", object_id, name))
+        })
+    }
+
+    /// Whether any snapshot captured an object
+    fn in_snapshot(&self, object_id: ObjectId) -> bool {
+        self.snapshots
+            .values()
+            .any(|snapshot| snapshot.contains(object_id))
+    }
+
+    /// Whether nothing links to or captures an object any more
+    fn is_orphan(&self, object_id: ObjectId) -> bool {
+        object_id != self.root_dir_id
+            && self.link_count(object_id) == 0
+            && !self.in_snapshot(object_id)
+    }
+
+    fn write_catalog(&mut self, tx: &mut Transaction) -> Result<(), TransactionError> {
+        let catalog = SnapshotCatalog {
+            snapshots: self.snapshots.clone(),
+        };
+        let json = serde_json::to_vec(&catalog)
+            .map_err(|e| TransactionError::StorageError(format!("serialize failed: {:?}", e)))?;
+        self.storage
+            .write(tx, catalog_id(self.root_dir_id), &json)?;
+        Ok(())
+    }
+
+    /// Loads the snapshot catalog and re-pins its versions
+    fn load_snapshots(&mut self) -> Result<(), TransactionError> {
+        let catalog_id = catalog_id(self.root_dir_id);
+        let tx = self.storage.begin_transaction()?;
+        let Ok(version_id) = self.storage.read(&tx, catalog_id) else {
+            return Ok(());
+        };
+        let data = self.storage.read_version(catalog_id, version_id)?;
+        let catalog: SnapshotCatalog = serde_json::from_slice(&data)
+            .map_err(|e| TransactionError::StorageError(format!("deserialize failed: {:?}", e)))?;
+
+        for snapshot in catalog.snapshots.values() {
+            for (object_id, version_id) in &snapshot.versions {
+                self.storage.pin_version(*object_id, *version_id);
+            }
+        }
+        self.snapshots = catalog.snapshots;
+        Ok(())
+    }
+
     /// Read file content
     pub fn read_file(&mut self, file_id: ObjectId) -> Result<Vec<u8>, TransactionError> {
         let tx = self.storage.begin_transaction()?;
@@ -259,16 +898,104 @@ impl<D: BlockDevice> PersistentFilesystem<D> {
         let data = self
             .storage
             .read_object_data(file_id, version_id)
-            .map_err(|e| TransactionError::StorageError(format!("read data failed: {:?}", e)))?;
+            .map_err(|e| match e {
+                BlockStorageError::Corrupted { .. } => e.into(),
+                e => TransactionError::StorageError(format!("read data failed: {:?}", e)),
+            })?;
         Ok(data)
     }
+
+    /// Read up to `len` bytes of file content starting at `offset`
+    pub fn read_file_range(
+        &mut self,
+        file_id: ObjectId,
+        offset: u64,
+        len: u64,
+    ) -> Result<Vec<u8>, TransactionError> {
+        let tx = self.storage.begin_transaction()?;
+        let version_id = self.storage.read(&tx, file_id)?;
+        self.storage
+            .read_object_range(file_id, version_id, offset, len)
+            .map_err(|e| TransactionError::StorageError(format!("read data failed: {:?}", e)))
+    }
+
+    /// Size of a file in bytes
+    pub fn file_size(&mut self, file_id: ObjectId) -> Result<u64, TransactionError> {
+        let tx = self.storage.begin_transaction()?;
+        let version_id = self.storage.read(&tx, file_id)?;
+        self.storage
+            .object_size(file_id, version_id)
+            .map_err(|e| TransactionError::StorageError(format!("stat failed: {:?}", e)))
+    }
+
+    /// Open a reader over a file's current content
+    pub fn open_reader(
+        &mut self,
+        file_id: ObjectId,
+    ) -> Result<ObjectReader<'_, D>, TransactionError> {
+        let tx = self.storage.begin_transaction()?;
+        let version_id = self.storage.read(&tx, file_id)?;
+        self.storage
+            .reader(file_id, version_id)
+            .map_err(|e| TransactionError::StorageError(format!("open failed: {:?}", e)))
+    }
+
+    /// Write file content produced by `fill` as a stream (as a Blob object)
+    ///
+    /// Nothing is committed if `fill` fails.
+    pub fn write_file_with<F>(&mut self, fill: F) -> Result<ObjectId, TransactionError>
+    where
+        F: FnOnce(&mut ObjectWriter<'_, D>) -> Result<(), TransactionError>,
+    {
+        let file_id = ObjectId::new();
+        let mut tx = self.storage.begin_transaction()?;
+        let result = {
+            let mut writer = self.storage.stream_writer(&tx, file_id)?;
+            fill(&mut writer).and_then(|()| writer.finish())
+        };
+        match result {
+            Ok(_) => self.storage.commit(&mut tx)?,
+            Err(e) => {
+                self.storage.rollback(&mut tx)?;
+                return Err(e);
+            }
+        }
+        Ok(file_id)
+    }
+
+    /// Append to a `Log` entry in a directory
+    ///
+    /// Only the tail block of the previous version is written again.
+    pub fn append(
+        &mut self,
+        name: &str,
+        dir_id: ObjectId,
+        data: &[u8],
+    ) -> Result<VersionId, TransactionError> {
+        let dir = self.read_directory(dir_id)?;
+        let entry = dir
+            .get_entry(name)
+            .ok_or_else(|| TransactionError::ObjectNotFound(name.into()))?;
+        if entry.kind != ObjectKind::Log {
+            return Err(TransactionError::InvalidOperation(format!(
+                "not a log: {}",
+                name
+            )));
+        }
+
+        let mut tx = self.storage.begin_transaction()?;
+        let version_id = self.storage.append(&mut tx, entry.object_id, data)?;
+        self.storage.commit(&mut tx)?;
+        Ok(version_id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ContentType;
     use alloc::string::ToString;
-    use hal::RamDisk;
+    use hal::{RamDisk, BLOCK_SIZE};
 
     #[test]
     fn test_link_then_read() {
@@ -336,6 +1063,83 @@ mod tests {
         assert_eq!(read_content, content);
     }
 
+    #[test]
+    fn test_stream_and_range_read_file() {
+        let disk = RamDisk::with_capacity_mb(10);
+        let mut fs = PersistentFilesystem::format(disk, "root").unwrap();
+
+        let file_id = fs
+            .write_file_with(|writer| {
+                for line in 0..2000 {
+                    writer.write(format!("line {:05}\n", line).as_bytes())?;
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(fs.file_size(file_id).unwrap(), 2000 * 11);
+        assert_eq!(
+            fs.read_file_range(file_id, 1500 * 11, 11).unwrap(),
+            b"line 01500\n"
+        );
+
+        let mut reader = fs.open_reader(file_id).unwrap();
+        reader.seek(1999 * 11);
+        assert_eq!(reader.read(100).unwrap(), b"line 01999\n");
+        assert!(reader.read(100).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_append_to_log_only() {
+        let disk = RamDisk::with_capacity_mb(10);
+        let mut fs = PersistentFilesystem::format(disk, "root").unwrap();
+        let root_id = fs.root_dir_id();
+
+        let log_id = fs.write_file(b"boot\n").unwrap();
+        fs.link("events", root_id, log_id, ObjectKind::Log, 1000)
+            .unwrap();
+        let blob_id = fs.write_file(b"blob").unwrap();
+        fs.link("blob", root_id, blob_id, ObjectKind::Blob, 1000)
+            .unwrap();
+
+        fs.append("events", root_id, b"login\n").unwrap();
+        fs.append("events", root_id, b"logout\n").unwrap();
+        assert_eq!(fs.read_file(log_id).unwrap(), b"boot\nlogin\nlogout\n");
+
+        assert!(matches!(
+            fs.append("blob", root_id, b"x"),
+            Err(TransactionError::InvalidOperation(_))
+        ));
+        assert!(matches!(
+            fs.append("missing", root_id, b"x"),
+            Err(TransactionError::ObjectNotFound(_))
+        ));
+        assert_eq!(fs.read_file(blob_id).unwrap(), b"blob");
+    }
+
+    #[test]
+    fn test_file_history_and_restore() {
+        let disk = RamDisk::with_capacity_mb(10);
+        let mut fs = PersistentFilesystem::format(disk, "root").unwrap();
+
+        let file_id = fs.write_file(b"one").unwrap();
+        fs.update_file(file_id, b"two").unwrap();
+        fs.update_file(file_id, b"three").unwrap();
+        assert!(fs.update_file(ObjectId::new(), b"x").is_err());
+
+        let history = fs.file_history(file_id).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(
+            fs.read_file_version(file_id, history[1].version_id)
+                .unwrap(),
+            b"two"
+        );
+
+        fs.restore_file(file_id, history[0].version_id).unwrap();
+        assert_eq!(fs.read_file(file_id).unwrap(), b"one");
+        assert_eq!(fs.file_history(file_id).unwrap().len(), 4);
+    }
+
     #[test]
     fn test_link_and_list() {
         let disk = RamDisk::with_capacity_mb(10);
@@ -371,4 +1175,238 @@ mod tests {
         let entries = fs.list(root_id).unwrap();
         assert_eq!(entries.len(), 0);
     }
+
+    #[test]
+    fn test_rm_reclaims_after_last_link() {
+        let disk = RamDisk::with_capacity_mb(10);
+        let mut fs = PersistentFilesystem::format(disk, "root").unwrap();
+
+        let root_id = fs.root_dir_id();
+        let docs_id = fs.mkdir("docs", root_id, "root", 1000).unwrap();
+        let file_id = fs.write_file(b"shared").unwrap();
+        fs.link("a.txt", root_id, file_id, ObjectKind::Blob, 1000)
+            .unwrap();
+        fs.link("b.txt", docs_id, file_id, ObjectKind::Blob, 1000)
+            .unwrap();
+        assert_eq!(fs.link_count(file_id), 2);
+
+        fs.rm("a.txt", root_id, 2000).unwrap();
+        assert_eq!(fs.link_count(file_id), 1);
+        assert_eq!(fs.read_file(file_id).unwrap(), b"shared");
+
+        // Directory still has an entry
+        assert!(matches!(
+            fs.rm("docs", root_id, 3000),
+            Err(TransactionError::InvalidOperation(_))
+        ));
+
+        let removed = fs.rm("b.txt", docs_id, 4000).unwrap().unwrap();
+        assert_eq!(removed.object_id, file_id);
+        assert_eq!(fs.link_count(file_id), 0);
+        assert!(matches!(
+            fs.read_file(file_id),
+            Err(TransactionError::ObjectNotFound(_))
+        ));
+
+        fs.rm("docs", root_id, 5000).unwrap();
+        assert!(fs.read_directory(docs_id).is_err());
+        assert!(fs.rm("missing", root_id, 6000).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_link_counts_rebuilt_on_open() {
+        let disk = RamDisk::with_capacity_mb(10);
+        let mut fs = PersistentFilesystem::format(disk, "root").unwrap();
+
+        let root_id = fs.root_dir_id();
+        let docs_id = fs.mkdir("docs", root_id, "root", 1000).unwrap();
+        let file_id = fs.write_file(b"data").unwrap();
+        fs.link("one", root_id, file_id, ObjectKind::Blob, 1000)
+            .unwrap();
+        fs.link("two", docs_id, file_id, ObjectKind::Blob, 1000)
+            .unwrap();
+        // Relinking a name replaces its old target
+        fs.link("two", docs_id, file_id, ObjectKind::Blob, 1000)
+            .unwrap();
+
+        let reopened = PersistentFilesystem::open(fs.into_device(), root_id).unwrap();
+        assert_eq!(reopened.link_count(file_id), 2);
+        assert_eq!(reopened.link_count(docs_id), 1);
+    }
+
+    #[test]
+    fn test_entry_metadata_persists() {
+        let disk = RamDisk::with_capacity_mb(10);
+        let mut fs = PersistentFilesystem::format(disk, "root").unwrap();
+
+        let root_id = fs.root_dir_id();
+        let file_id = fs.write_file(b"# Notes").unwrap();
+        fs.link("notes", root_id, file_id, ObjectKind::Blob, 1000)
+            .unwrap();
+        fs.update_metadata("notes", root_id, 1500, |metadata| {
+            metadata.content_type = Some(ContentType::Markdown);
+            metadata
+                .attributes
+                .insert("tag".to_string(), "draft".to_string());
+        })
+        .unwrap();
+        // Relinking keeps the creation time and attributes
+        let new_id = fs.write_file(b"# Notes v2").unwrap();
+        fs.link("notes", root_id, new_id, ObjectKind::Blob, 2000)
+            .unwrap();
+
+        let mut reopened = PersistentFilesystem::open(fs.into_device(), root_id).unwrap();
+        let root = reopened.read_directory(root_id).unwrap();
+        let metadata = &root.get_entry("notes").unwrap().metadata;
+        assert_eq!((metadata.created_at, metadata.modified_at), (1000, 2000));
+        assert_eq!(metadata.content_type, Some(ContentType::Markdown));
+        assert_eq!(metadata.attribute("tag"), Some("draft"));
+        assert!(matches!(
+            reopened.update_metadata("missing", root_id, 3000, |_| {}),
+            Err(TransactionError::ObjectNotFound(_))
+        ));
+    }
+
+    fn snapshot_fixture() -> (PersistentFilesystem<RamDisk>, ObjectId, ObjectId, ObjectId) {
+        let disk = RamDisk::with_capacity_mb(10);
+        let mut fs = PersistentFilesystem::format(disk, "root").unwrap();
+        let root_id = fs.root_dir_id();
+        let docs_id = fs.mkdir("docs", root_id, "root", 1000).unwrap();
+        let file_id = fs.write_file(b"draft").unwrap();
+        fs.link("a.txt", docs_id, file_id, ObjectKind::Blob, 1000)
+            .unwrap();
+        (fs, root_id, docs_id, file_id)
+    }
+
+    #[test]
+    fn test_snapshot_diff_and_restore() {
+        let (mut fs, root_id, docs_id, file_id) = snapshot_fixture();
+        let snapshot = fs.create_snapshot("before", root_id, 2000).unwrap();
+        assert!(snapshot.contains(file_id));
+        assert!(fs.create_snapshot("before", root_id, 2000).is_err());
+
+        fs.update_file(file_id, b"final").unwrap();
+        let new_id = fs.write_file(b"new").unwrap();
+        fs.link("b.txt", docs_id, new_id, ObjectKind::Blob, 3000)
+            .unwrap();
+
+        assert_eq!(fs.snapshot_read_file("before", file_id).unwrap(), b"draft");
+        assert_eq!(fs.snapshot_list("before", docs_id).unwrap().len(), 1);
+        assert_eq!(
+            fs.diff_snapshot("before").unwrap(),
+            vec![
+                TreeChange::Added("docs/b.txt".to_string()),
+                TreeChange::Modified("docs/a.txt".to_string()),
+            ]
+        );
+
+        assert_eq!(fs.restore_snapshot("before").unwrap(), 2);
+        assert_eq!(fs.read_file(file_id).unwrap(), b"draft");
+        assert_eq!(fs.list(docs_id).unwrap().len(), 1);
+        assert!(fs.read_file(new_id).is_err());
+        assert!(fs.diff_snapshot("before").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_clone_shares_blocks_until_written() {
+        let (mut fs, root_id, docs_id, file_id) = snapshot_fixture();
+        fs.create_snapshot("base", docs_id, 2000).unwrap();
+
+        let free_before = fs.storage.free_block_count();
+        let clone_id = fs.clone_snapshot("base", "copy", root_id, 3000).unwrap();
+        // Only the cloned directories need new blocks
+        assert_eq!(free_before - fs.storage.free_block_count(), 2);
+        assert!(fs.clone_snapshot("base", "copy", root_id, 3000).is_err());
+
+        let entries = fs.list(clone_id).unwrap();
+        assert_eq!(entries.len(), 1);
+        let cloned_file = entries[0].1.object_id;
+        assert_ne!(cloned_file, file_id);
+        assert_eq!(fs.read_file(cloned_file).unwrap(), b"draft");
+
+        fs.update_file(cloned_file, b"changed").unwrap();
+        assert_eq!(fs.read_file(file_id).unwrap(), b"draft");
+
+        let tree_id = fs.clone_tree(clone_id, "copy2", root_id, 4000).unwrap();
+        let entries = fs.list(tree_id).unwrap();
+        assert_eq!(fs.read_file(entries[0].1.object_id).unwrap(), b"changed");
+        assert_eq!(fs.read_directory(tree_id).unwrap().parent, Some(root_id));
+    }
+
+    #[test]
+    fn test_snapshots_survive_reopen_and_gc() {
+        let (mut fs, root_id, _docs_id, file_id) = snapshot_fixture();
+        fs.create_snapshot("kept", root_id, 2000).unwrap();
+        fs.update_file(file_id, b"newer").unwrap();
+
+        let mut fs = PersistentFilesystem::open(fs.into_device(), root_id).unwrap();
+        assert_eq!(fs.snapshots().len(), 1);
+        fs.storage.collect_garbage().unwrap();
+        assert_eq!(fs.snapshot_read_file("kept", file_id).unwrap(), b"draft");
+
+        fs.delete_snapshot("kept").unwrap();
+        assert!(fs.delete_snapshot("kept").is_err());
+        fs.storage.collect_garbage().unwrap();
+        assert_eq!(fs.file_history(file_id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rm_rolls_back_when_delete_fails() {
+        let disk = RamDisk::with_capacity_mb(10);
+        let mut fs = PersistentFilesystem::format(disk, "root").unwrap();
+
+        let root_id = fs.root_dir_id();
+        fs.link("ghost", root_id, ObjectId::new(), ObjectKind::Blob, 1000)
+            .unwrap();
+        let free = fs.storage.free_block_count();
+
+        assert!(matches!(
+            fs.rm("ghost", root_id, 2000),
+            Err(TransactionError::ObjectNotFound(_))
+        ));
+        // The staged directory write was released, not left pending
+        assert_eq!(fs.storage.pending_transaction_count(), 0);
+        assert_eq!(fs.storage.free_block_count(), free);
+        assert_eq!(fs.list(root_id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_create_snapshot_rolls_back_when_catalog_commit_fails() {
+        let disk = RamDisk::new(64);
+        let mut fs = PersistentFilesystem::format(disk, "root").unwrap();
+        let root_id = fs.root_dir_id();
+        let file_id = fs.write_file(b"draft").unwrap();
+        fs.link("a.txt", root_id, file_id, ObjectKind::Blob, 1000)
+            .unwrap();
+
+        // Fill the device so the catalog has nowhere to go
+        let free = fs.storage.free_block_count() as usize;
+        fs.write_file(&vec![7u8; free * BLOCK_SIZE]).unwrap();
+        assert_eq!(fs.storage.free_block_count(), 0);
+
+        assert!(fs.create_snapshot("full", root_id, 2000).is_err());
+        assert!(fs.snapshot("full").is_none());
+        assert_eq!(fs.storage.pending_transaction_count(), 0);
+        assert_eq!(fs.storage.pinned_version_count(), 0);
+        assert_eq!(fs.storage.free_block_count(), 0);
+
+        let fs = PersistentFilesystem::open(fs.into_device(), root_id).unwrap();
+        assert!(fs.snapshots().is_empty());
+    }
+
+    #[test]
+    fn test_rm_defers_delete_of_snapshotted_objects() {
+        let (mut fs, root_id, docs_id, file_id) = snapshot_fixture();
+        fs.create_snapshot("held", root_id, 2000).unwrap();
+
+        fs.rm("a.txt", docs_id, 3000).unwrap();
+        assert!(fs.read_file(file_id).is_ok());
+        assert_eq!(
+            fs.diff_snapshot("held").unwrap(),
+            vec![TreeChange::Removed("docs/a.txt".to_string())]
+        );
+
+        fs.delete_snapshot("held").unwrap();
+        assert!(fs.read_file(file_id).is_err());
+    }
 }
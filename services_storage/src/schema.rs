@@ -0,0 +1,238 @@
+//! Schema tags and lazy migration on read
+//!
+//! Objects written with a schema carry a [`SchemaTag`]: the schema they
+//! follow, the schema version their bytes are in, and the migrations that
+//! produced them. Nothing is migrated eagerly. When a reader asks for a
+//! newer version, the [`SchemaRegistry`] runs the registered migrator one
+//! version step at a time, so a failure names the exact step that broke.
+
+use crate::migration::{create_lineage, MigrationError, Migrator};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+use core_types::{MigrationLineage, ObjectSchemaId, ObjectSchemaVersion};
+use serde::{Deserialize, Serialize};
+
+/// Schema identity recorded with an object version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaTag {
+    /// Schema the data follows
+    pub schema_id: ObjectSchemaId,
+    /// Schema version the data is in
+    pub version: ObjectSchemaVersion,
+    /// Migrations applied to reach `version`, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lineage: Vec<MigrationLineage>,
+}
+
+impl SchemaTag {
+    /// Creates a tag for data written directly in `version`
+    pub fn new(schema_id: impl Into<ObjectSchemaId>, version: ObjectSchemaVersion) -> Self {
+        Self {
+            schema_id: schema_id.into(),
+            version,
+            lineage: Vec::new(),
+        }
+    }
+}
+
+/// A migration that could not be completed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMigrationError {
+    /// Schema being migrated
+    pub schema_id: ObjectSchemaId,
+    /// Version the stored data is in
+    pub from: ObjectSchemaVersion,
+    /// Version the reader asked for
+    pub to: ObjectSchemaVersion,
+    /// Lineage up to and including the step that failed
+    pub lineage: Vec<MigrationLineage>,
+    /// Error reported by the migrator
+    pub error: MigrationError,
+}
+
+impl fmt::Display for SchemaMigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cannot migrate {} from {} to {}: {}",
+            self.schema_id, self.from, self.to, self.error
+        )?;
+        if let Some(first) = self.lineage.first() {
+            write!(f, " (lineage: {}", first.from_version)?;
+            for step in &self.lineage {
+                write!(f, " -> {}", step.to_version)?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+struct RegisteredSchema {
+    current: ObjectSchemaVersion,
+    migrator: Box<dyn Migrator>,
+}
+
+/// Migrators and current versions, by schema
+#[derive(Default)]
+pub struct SchemaRegistry {
+    schemas: BTreeMap<ObjectSchemaId, RegisteredSchema>,
+}
+
+impl SchemaRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the migrator for a schema and the version readers get by default
+    ///
+    /// Replaces any earlier registration for the same schema.
+    pub fn register(
+        &mut self,
+        schema_id: impl Into<ObjectSchemaId>,
+        current: ObjectSchemaVersion,
+        migrator: impl Migrator + 'static,
+    ) {
+        self.schemas.insert(
+            schema_id.into(),
+            RegisteredSchema {
+                current,
+                migrator: Box::new(migrator),
+            },
+        );
+    }
+
+    /// Returns the current version registered for a schema
+    pub fn current_version(&self, schema_id: &ObjectSchemaId) -> Option<ObjectSchemaVersion> {
+        self.schemas.get(schema_id).map(|schema| schema.current)
+    }
+
+    /// Migrates tagged data to `target`, one version at a time
+    ///
+    /// Returns the migrated data and its new tag, whose lineage gains one
+    /// entry per step. Data already in `target` is returned unchanged.
+    pub fn migrate(
+        &self,
+        tag: &SchemaTag,
+        data: &[u8],
+        target: ObjectSchemaVersion,
+        migrated_at: Option<u64>,
+    ) -> Result<(Vec<u8>, SchemaTag), SchemaMigrationError> {
+        if tag.version == target {
+            return Ok((data.to_vec(), tag.clone()));
+        }
+
+        let mut lineage = tag.lineage.clone();
+        let fail = |lineage: Vec<MigrationLineage>, error| SchemaMigrationError {
+            schema_id: tag.schema_id.clone(),
+            from: tag.version,
+            to: target,
+            lineage,
+            error,
+        };
+        let unsupported = MigrationError::UnsupportedMigration {
+            from: tag.version,
+            to: target,
+        };
+        let schema = match self.schemas.get(&tag.schema_id) {
+            Some(schema) if tag.version < target => schema,
+            _ => return Err(fail(lineage, unsupported)),
+        };
+
+        let mut data = data.to_vec();
+        let mut version = tag.version;
+        while version < target {
+            let next = version.next();
+            lineage.push(create_lineage(version, next, migrated_at));
+            data = match schema.migrator.migrate(version, next, &data) {
+                Ok(migrated) => migrated,
+                Err(error) => return Err(fail(lineage, error)),
+            };
+            version = next;
+        }
+
+        Ok((
+            data,
+            SchemaTag {
+                schema_id: tag.schema_id.clone(),
+                version,
+                lineage,
+            },
+        ))
+    }
+}
+
+impl fmt::Debug for SchemaRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.schemas
+                    .iter()
+                    .map(|(schema_id, schema)| (schema_id, schema.current)),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SequentialMigrator;
+    use alloc::format;
+    use alloc::string::ToString;
+
+    fn v(version: u32) -> ObjectSchemaVersion {
+        ObjectSchemaVersion::new(version)
+    }
+
+    fn registry() -> SchemaRegistry {
+        let migrator = SequentialMigrator::new()
+            .add_migration(|data| Ok([data, b"+2"].concat()))
+            .add_migration(|_| Err(MigrationError::InvalidData("no v3 yet".to_string())));
+        let mut registry = SchemaRegistry::new();
+        registry.register("note", v(3), migrator);
+        registry
+    }
+
+    #[test]
+    fn test_migrate_records_each_step() {
+        let registry = registry();
+        assert_eq!(registry.current_version(&"note".into()), Some(v(3)));
+
+        let tag = SchemaTag::new("note", v(1));
+        let (data, migrated) = registry.migrate(&tag, b"hi", v(2), Some(7)).unwrap();
+        assert_eq!(data, b"hi+2");
+        assert_eq!(migrated.version, v(2));
+        assert_eq!(migrated.lineage, vec![create_lineage(v(1), v(2), Some(7))]);
+
+        // Nothing to do when already there
+        assert_eq!(
+            registry.migrate(&migrated, b"x", v(2), None).unwrap().1,
+            migrated
+        );
+    }
+
+    #[test]
+    fn test_migrate_failure_reports_lineage() {
+        let registry = registry();
+        let err = registry
+            .migrate(&SchemaTag::new("note", v(1)), b"hi", v(3), None)
+            .unwrap_err();
+        assert_eq!(err.lineage.len(), 2);
+        assert_eq!(err.lineage[1].from_version, v(2));
+        assert!(matches!(err.error, MigrationError::InvalidData(_)));
+        assert!(format!("{}", err).contains("lineage: v1 -> v2 -> v3"));
+
+        // Unknown schemas and downgrades fail before any step runs
+        let err = registry
+            .migrate(&SchemaTag::new("todo", v(1)), b"", v(2), None)
+            .unwrap_err();
+        assert!(err.lineage.is_empty());
+        assert!(registry
+            .migrate(&SchemaTag::new("note", v(2)), b"", v(1), None)
+            .is_err());
+    }
+}
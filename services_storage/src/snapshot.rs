@@ -0,0 +1,83 @@
+//! Snapshots of directory trees.
+//!
+//! A snapshot records the version of every object in a directory subtree
+//! at one commit sequence number. Versions are immutable, so the record is
+//! all a snapshot needs: reading through it sees the tree exactly as it
+//! was, and its versions are pinned so garbage collection keeps them.
+//!
+//! Copy-on-write clones start from a snapshot too. Directories are written
+//! again with fresh IDs, while files share the snapshot's blocks until the
+//! clone writes to them.
+
+use crate::{ObjectId, VersionId};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use serde::{Deserialize, Serialize};
+
+/// A named, read-only capture of a directory subtree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Snapshot name
+    pub name: String,
+    /// Directory the snapshot was taken of
+    pub root: ObjectId,
+    /// Commit sequence the captured versions are consistent with
+    pub sequence: u64,
+    /// Capture timestamp
+    pub created_at: u64,
+    /// Captured version of every object in the subtree, root included
+    pub versions: BTreeMap<ObjectId, VersionId>,
+}
+
+impl Snapshot {
+    /// Whether the snapshot captured an object
+    pub fn contains(&self, object_id: ObjectId) -> bool {
+        self.versions.contains_key(&object_id)
+    }
+
+    /// Captured version of an object
+    pub fn version_of(&self, object_id: ObjectId) -> Option<VersionId> {
+        self.versions.get(&object_id).copied()
+    }
+}
+
+/// A difference between a snapshot and the current tree
+///
+/// Paths are relative to the snapshot root and use `/` separators.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TreeChange {
+    /// Present now, absent from the snapshot
+    Added(String),
+    /// Present in the snapshot, absent now
+    Removed(String),
+    /// Present in both with different content or target
+    Modified(String),
+}
+
+impl TreeChange {
+    /// Path the change applies to
+    pub fn path(&self) -> &str {
+        match self {
+            TreeChange::Added(path) | TreeChange::Removed(path) | TreeChange::Modified(path) => {
+                path
+            }
+        }
+    }
+}
+
+/// Snapshots persisted alongside a filesystem
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct SnapshotCatalog {
+    pub(crate) snapshots: BTreeMap<String, Snapshot>,
+}
+
+/// Object holding the snapshot catalog of the filesystem rooted at `root`
+///
+/// Derived from the root ID, so the catalog needs no link in the tree and
+/// is never captured by a snapshot itself.
+pub(crate) fn catalog_id(root: ObjectId) -> ObjectId {
+    const CATALOG_MASK: u128 = 0x736e_6170_7368_6f74_2d63_6174_616c_6f67;
+    ObjectId::from_uuid(uuid::Uuid::from_u128(
+        root.as_uuid().as_u128() ^ CATALOG_MASK,
+    ))
+}
@@ -0,0 +1,184 @@
+//! Streaming readers and writers for block storage objects.
+//!
+//! [`ObjectWriter`] writes a new version in chunks, allocating extents as
+//! data arrives instead of buffering the whole object in memory. Nothing it
+//! writes is visible until the owning transaction commits: the data lands in
+//! free blocks and only the commit record makes it reachable, so a crash
+//! mid-stream leaves the previous version intact.
+//!
+//! [`ObjectReader`] reads a version in ranges, touching only the blocks it
+//! needs.
+
+use crate::block_storage::{push_extent, AllocationEntry, BlockStorage, BlockStorageError, Extent};
+use crate::{ObjectId, Transaction, TransactionError, TransactionId, VersionId};
+use alloc::format;
+use alloc::vec::Vec;
+use hal::{BlockDevice, BLOCK_SIZE};
+
+/// Bytes buffered by a writer before they are flushed to disk
+pub const STREAM_CHUNK_SIZE: usize = 16 * BLOCK_SIZE;
+
+/// Writes a new object version in chunks
+///
+/// Call [`ObjectWriter::finish`] to stage the version in the transaction.
+/// Dropping an unfinished writer releases the blocks it allocated.
+pub struct ObjectWriter<'a, D: BlockDevice> {
+    storage: &'a mut BlockStorage<D>,
+    tx_id: TransactionId,
+    object_id: ObjectId,
+    version_id: VersionId,
+    /// Extents of the version so far (shared base extents come first)
+    extents: Vec<Extent>,
+    /// Extents allocated by this writer
+    allocated: Vec<Extent>,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+impl<'a, D: BlockDevice> ObjectWriter<'a, D> {
+    pub(crate) fn new(
+        storage: &'a mut BlockStorage<D>,
+        tx: &Transaction,
+        object_id: ObjectId,
+        base: Vec<Extent>,
+        tail: Vec<u8>,
+    ) -> Result<Self, TransactionError> {
+        if tx.state() != crate::transaction::TransactionState::Active {
+            return Err(TransactionError::AlreadyFinalized);
+        }
+        Ok(Self {
+            storage,
+            tx_id: tx.id(),
+            object_id,
+            version_id: VersionId::new(),
+            extents: base,
+            allocated: Vec::new(),
+            buffer: tail,
+            finished: false,
+        })
+    }
+
+    /// Object being written
+    pub fn object_id(&self) -> ObjectId {
+        self.object_id
+    }
+
+    /// Version this writer will stage
+    pub fn version_id(&self) -> VersionId {
+        self.version_id
+    }
+
+    /// Total bytes in the version so far
+    pub fn len(&self) -> u64 {
+        self.extents.iter().map(|extent| extent.length).sum::<u64>() + self.buffer.len() as u64
+    }
+
+    /// Returns true if nothing has been written
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends data, flushing whole chunks to disk
+    pub fn write(&mut self, data: &[u8]) -> Result<(), TransactionError> {
+        self.buffer.extend_from_slice(data);
+        while self.buffer.len() >= STREAM_CHUNK_SIZE {
+            let chunk: Vec<u8> = self.buffer.drain(..STREAM_CHUNK_SIZE).collect();
+            self.flush_chunk(&chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the remaining data and stages the version
+    pub fn finish(mut self) -> Result<VersionId, TransactionError> {
+        let rest = core::mem::take(&mut self.buffer);
+        self.flush_chunk(&rest)?;
+
+        let entry = AllocationEntry::from_extents(
+            self.object_id,
+            self.version_id,
+            core::mem::take(&mut self.extents),
+        );
+        self.storage.stage_written(self.tx_id, entry);
+        self.finished = true;
+        Ok(self.version_id)
+    }
+
+    fn flush_chunk(&mut self, chunk: &[u8]) -> Result<(), TransactionError> {
+        let extents = self
+            .storage
+            .store_data(self.tx_id, chunk)
+            .map_err(|e| TransactionError::StorageError(format!("{:?}", e)))?;
+        // Shared chunks are referenced elsewhere, so releasing them is a no-op
+        self.allocated.extend_from_slice(&extents);
+        for extent in extents {
+            push_extent(&mut self.extents, extent);
+        }
+        Ok(())
+    }
+}
+
+impl<D: BlockDevice> Drop for ObjectWriter<'_, D> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.storage.release_extents(&self.allocated);
+        }
+    }
+}
+
+/// Reads an object version in ranges
+pub struct ObjectReader<'a, D: BlockDevice> {
+    storage: &'a mut BlockStorage<D>,
+    object_id: ObjectId,
+    version_id: VersionId,
+    position: u64,
+    len: u64,
+}
+
+impl<'a, D: BlockDevice> ObjectReader<'a, D> {
+    pub(crate) fn new(
+        storage: &'a mut BlockStorage<D>,
+        object_id: ObjectId,
+        version_id: VersionId,
+    ) -> Result<Self, BlockStorageError> {
+        let len = storage.object_size(object_id, version_id)?;
+        Ok(Self {
+            storage,
+            object_id,
+            version_id,
+            position: 0,
+            len,
+        })
+    }
+
+    /// Size of the version in bytes
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if the version is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Current read position
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Moves the read position (clamped to the end)
+    pub fn seek(&mut self, position: u64) {
+        self.position = position.min(self.len);
+    }
+
+    /// Reads up to `max_len` bytes and advances; empty at the end
+    pub fn read(&mut self, max_len: usize) -> Result<Vec<u8>, BlockStorageError> {
+        let data = self.storage.read_object_range(
+            self.object_id,
+            self.version_id,
+            self.position,
+            max_len as u64,
+        )?;
+        self.position += data.len() as u64;
+        Ok(data)
+    }
+}
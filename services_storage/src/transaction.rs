@@ -1,6 +1,6 @@
 //! Transaction support for storage operations
 
-use crate::{ObjectId, VersionId};
+use crate::{AccessDenialReason, ObjectId, VersionId};
 use alloc::string::String;
 use alloc::vec::Vec;
 use core_types::new_uuid;
@@ -44,6 +44,12 @@ pub enum TransactionError {
 
     /// Storage error (I/O, block device, etc.)
     StorageError(String),
+
+    /// Stored data failed its integrity check
+    Corrupted(String),
+
+    /// The acting principal lacks the required capability
+    AccessDenied(AccessDenialReason),
 }
 
 impl core::fmt::Display for TransactionError {
@@ -54,6 +60,8 @@ impl core::fmt::Display for TransactionError {
             TransactionError::AlreadyFinalized => write!(f, "Transaction already finalized"),
             TransactionError::InvalidOperation(msg) => write!(f, "Invalid operation: {}", msg),
             TransactionError::StorageError(msg) => write!(f, "Storage error: {}", msg),
+            TransactionError::Corrupted(msg) => write!(f, "Data corrupted: {}", msg),
+            TransactionError::AccessDenied(reason) => write!(f, "Access denied: {}", reason),
         }
     }
 }
@@ -178,6 +186,13 @@ pub trait TransactionalStorage {
         data: &[u8],
     ) -> Result<VersionId, TransactionError>;
 
+    /// Deletes an object within a transaction
+    ///
+    /// Once committed, every version of the object is gone and a tombstone
+    /// makes sure recovery does not bring it back.
+    fn delete(&mut self, tx: &mut Transaction, object_id: ObjectId)
+        -> Result<(), TransactionError>;
+
     /// Commits a transaction
     fn commit(&mut self, tx: &mut Transaction) -> Result<(), TransactionError>;
 
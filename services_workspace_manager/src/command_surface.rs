@@ -188,6 +188,26 @@ pub(crate) const NON_LAUNCH_PALETTE_SPECS: &[PaletteDescriptorSpec] = &[
         prompt_pattern: None,
         requires_args: false,
     },
+    PaletteDescriptorSpec {
+        id: "find",
+        name: "Find Files",
+        description: "List paths matching a glob",
+        tags: &["find", "search", "glob", "files"],
+        category: "Workspace",
+        keybinding: None,
+        prompt_pattern: Some("find "),
+        requires_args: true,
+    },
+    PaletteDescriptorSpec {
+        id: "grep",
+        name: "Search File Contents",
+        description: "Search file contents for a pattern",
+        tags: &["grep", "search", "content", "regex"],
+        category: "Workspace",
+        keybinding: None,
+        prompt_pattern: Some("grep "),
+        requires_args: true,
+    },
     PaletteDescriptorSpec {
         id: "close",
         name: "Close Component",
@@ -321,6 +341,9 @@ pub(crate) const COMPONENT_ID_COMMAND_SPECS: &[ComponentIdCommandSpec] = &[
     },
 ];
 
+pub(crate) const FIND_USAGE: &str = "Usage: find <glob>";
+pub(crate) const GREP_USAGE: &str = "Usage: grep [-i] [-F] <pattern> [glob]";
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct HelpTopicSpec {
     pub topic: HelpCategory,
@@ -450,6 +473,8 @@ pub(crate) const VALID_PREFIX_GROUPS: &[&[&str]] = &[
     &["st", "sta", "stat", "statu"],
     &["he", "hel"],
     &["re", "rec", "rece", "recen"],
+    &["fi", "fin"],
+    &["gr", "gre"],
 ];
 
 pub(crate) fn launch_command_by_token(token: &str) -> Option<&'static LaunchCommandSpec> {
@@ -483,11 +508,17 @@ pub(crate) fn component_id_usage_pattern(token: &str) -> Option<&'static str> {
         .map(|spec| spec.usage.strip_prefix("Usage: ").unwrap_or(spec.usage))
 }
 
+fn usage_pattern(usage: &'static str) -> &'static str {
+    usage.strip_prefix("Usage: ").unwrap_or(usage)
+}
+
 fn non_launch_prompt_pattern(spec: &PaletteDescriptorSpec) -> Option<String> {
     match spec.id {
         "focus_next" => Some("next".to_string()),
         "focus_prev" => Some("prev".to_string()),
         "close" => component_id_usage_pattern(spec.id).map(|pattern| pattern.to_string()),
+        "find" => Some(usage_pattern(FIND_USAGE).to_string()),
+        "grep" => Some(usage_pattern(GREP_USAGE).to_string()),
         _ => {
             if let Some(pattern) = spec.prompt_pattern {
                 Some(pattern.trim_end().to_string())
@@ -594,6 +625,30 @@ pub(crate) fn help_usage_pattern() -> String {
     format!("help [{}]", topics.join("|"))
 }
 
+/// Validates `find <glob>` and `grep [-i] [-F] <pattern> [glob]`
+pub(crate) fn validate_search_invocation(parts: &[&str]) -> CommandInvocationValidation {
+    let args = match parts.first() {
+        Some(&"find") => {
+            return match parts.len() {
+                1 => CommandInvocationValidation::ValidPrefix,
+                2 => CommandInvocationValidation::ValidComplete,
+                _ => CommandInvocationValidation::Invalid,
+            }
+        }
+        Some(&"grep") => &parts[1..],
+        _ => return CommandInvocationValidation::Invalid,
+    };
+    let operands = args
+        .iter()
+        .skip_while(|arg| matches!(**arg, "-i" | "-F"))
+        .count();
+    match operands {
+        0 => CommandInvocationValidation::ValidPrefix,
+        1 | 2 => CommandInvocationValidation::ValidComplete,
+        _ => CommandInvocationValidation::Invalid,
+    }
+}
+
 pub(crate) fn validate_open_invocation(parts: &[&str]) -> OpenInvocationValidation {
     if parts.is_empty() || parts[0] != "open" {
         return OpenInvocationValidation::Invalid;
@@ -709,5 +764,32 @@ mod tests {
         let close = non_launch_prompt_suggestion_by_id("close").expect("close suggestion");
         assert_eq!(close.pattern, "close <component_id>");
         assert_eq!(close.description, "Close a component by ID");
+
+        let grep = non_launch_prompt_suggestion_by_id("grep").expect("grep suggestion");
+        assert_eq!(grep.pattern, "grep [-i] [-F] <pattern> [glob]");
+    }
+
+    #[test]
+    fn test_search_validation() {
+        assert_eq!(
+            validate_search_invocation(&["find"]),
+            CommandInvocationValidation::ValidPrefix
+        );
+        assert_eq!(
+            validate_search_invocation(&["find", "**/*.md"]),
+            CommandInvocationValidation::ValidComplete
+        );
+        assert_eq!(
+            validate_search_invocation(&["grep", "-i"]),
+            CommandInvocationValidation::ValidPrefix
+        );
+        assert_eq!(
+            validate_search_invocation(&["grep", "-i", "todo", "docs/**"]),
+            CommandInvocationValidation::ValidComplete
+        );
+        assert_eq!(
+            validate_search_invocation(&["grep", "a", "b", "c"]),
+            CommandInvocationValidation::Invalid
+        );
     }
 }
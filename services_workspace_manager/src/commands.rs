@@ -7,12 +7,18 @@ use crate::boot_profile::BootProfile;
 use crate::command_surface::{
     component_id_command_by_token, help_usage_pattern, helper_command_by_alias,
     helper_command_by_open_token, launch_command_by_token, parse_help_topic, HelperCommandKind,
+    FIND_USAGE, GREP_USAGE,
 };
 use crate::{
     ComponentId, ComponentType, HelpCategory, LaunchConfig, WorkspaceError, WorkspaceManager,
 };
 use identity::{ExitReason, IdentityKind, TrustDomain};
 use serde::{Deserialize, Serialize};
+use services_fs_view::{Glob, Regex, SearchOptions, SearchResults};
+use services_storage::ObjectKind;
+
+/// Maximum number of results `find` and `grep` report
+const SEARCH_RESULT_LIMIT: usize = 100;
 
 /// Workspace command
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -56,6 +62,15 @@ pub enum WorkspaceCommand {
     BootProfileSet { profile: BootProfile },
     /// Boot profile: persist current configuration
     BootProfileSave,
+    /// Find paths matching a glob
+    Find { pattern: String },
+    /// Search file contents for a pattern
+    Grep {
+        pattern: String,
+        files: String,
+        ignore_case: bool,
+        fixed: bool,
+    },
 }
 
 /// Result of executing a workspace command
@@ -156,6 +171,13 @@ impl WorkspaceManager {
             WorkspaceCommand::BootProfileShow => self.cmd_boot_profile_show(),
             WorkspaceCommand::BootProfileSet { profile } => self.cmd_boot_profile_set(profile),
             WorkspaceCommand::BootProfileSave => self.cmd_boot_profile_save(),
+            WorkspaceCommand::Find { pattern } => self.cmd_find(&pattern),
+            WorkspaceCommand::Grep {
+                pattern,
+                files,
+                ignore_case,
+                fixed,
+            } => self.cmd_grep(&pattern, &files, ignore_case, fixed),
         }
     }
 
@@ -512,6 +534,113 @@ impl WorkspaceManager {
             },
         }
     }
+
+    fn cmd_find(&mut self, pattern: &str) -> CommandResult {
+        let glob = match Glob::new(pattern) {
+            Ok(glob) => glob,
+            Err(err) => {
+                return CommandResult::Error {
+                    message: err.to_string(),
+                }
+            }
+        };
+        let principal = self.principal();
+        let Some(context) = self.editor_io_context.as_mut() else {
+            return search_unavailable();
+        };
+        let (Some(fs_view), Some(root)) = (context.fs_view.as_mut(), context.root.as_ref()) else {
+            return search_unavailable();
+        };
+
+        let options = SearchOptions::new().with_limit(SEARCH_RESULT_LIMIT);
+        let results = fs_view.as_principal(principal).glob(root, &glob, &options);
+        let lines = results
+            .matches
+            .iter()
+            .map(|found| {
+                if found.kind == ObjectKind::Map {
+                    format!("{}/", found.path)
+                } else {
+                    found.path.clone()
+                }
+            })
+            .collect();
+        search_result(lines, &results, "No paths match")
+    }
+
+    fn cmd_grep(
+        &mut self,
+        pattern: &str,
+        files: &str,
+        ignore_case: bool,
+        fixed: bool,
+    ) -> CommandResult {
+        let source = if fixed {
+            text_pattern::escape(pattern)
+        } else {
+            pattern.to_string()
+        };
+        let regex = match Regex::new(&source) {
+            Ok(regex) => regex.ignore_case(ignore_case),
+            Err(err) => {
+                return CommandResult::Error {
+                    message: format!("Invalid pattern: {}", err),
+                }
+            }
+        };
+        let files = match Glob::new(files) {
+            Ok(glob) => glob,
+            Err(err) => {
+                return CommandResult::Error {
+                    message: err.to_string(),
+                }
+            }
+        };
+        let principal = self.principal();
+        let Some(context) = self.editor_io_context.as_mut() else {
+            return search_unavailable();
+        };
+        let (Some(fs_view), Some(root)) = (context.fs_view.as_mut(), context.root.as_ref()) else {
+            return search_unavailable();
+        };
+
+        let options = SearchOptions::new().with_limit(SEARCH_RESULT_LIMIT);
+        let results = fs_view.as_principal(principal).grep(
+            root,
+            &files,
+            &regex,
+            &mut context.storage,
+            &options,
+        );
+        let lines = results
+            .matches
+            .iter()
+            .map(|found| format!("{}:{}: {}", found.path, found.line_number, found.line))
+            .collect();
+        search_result(lines, &results, "No matches")
+    }
+}
+
+fn search_unavailable() -> CommandResult {
+    CommandResult::Error {
+        message: "Search unavailable: no filesystem view".to_string(),
+    }
+}
+
+fn search_result<T>(lines: Vec<String>, results: &SearchResults<T>, empty: &str) -> CommandResult {
+    if lines.is_empty() {
+        return CommandResult::Success {
+            message: empty.to_string(),
+        };
+    }
+    let mut message = lines.join("\n");
+    if results.truncated {
+        message.push_str(&format!(
+            "\n(showing first {} results)",
+            SEARCH_RESULT_LIMIT
+        ));
+    }
+    CommandResult::Success { message }
 }
 
 /// Parses a command string into a WorkspaceCommand
@@ -585,6 +714,13 @@ pub fn parse_command(input: &str) -> Result<WorkspaceCommand, WorkspaceError> {
                 ))),
             }
         }
+        "find" => match parts.len() {
+            2 => Ok(WorkspaceCommand::Find {
+                pattern: parts[1].to_string(),
+            }),
+            _ => Err(WorkspaceError::InvalidCommand(FIND_USAGE.to_string())),
+        },
+        "grep" => parse_grep_command(&parts[1..]),
         "settings" => {
             if parts.len() < 2 {
                 return Err(WorkspaceError::InvalidCommand(
@@ -674,6 +810,30 @@ pub fn parse_command(input: &str) -> Result<WorkspaceCommand, WorkspaceError> {
     }
 }
 
+/// Parses `grep [-i] [-F] <pattern> [glob]`; the glob defaults to every file
+fn parse_grep_command(args: &[&str]) -> Result<WorkspaceCommand, WorkspaceError> {
+    let mut ignore_case = false;
+    let mut fixed = false;
+    let mut operands = Vec::new();
+    for arg in args {
+        match *arg {
+            "-i" if operands.is_empty() => ignore_case = true,
+            "-F" if operands.is_empty() => fixed = true,
+            operand => operands.push(operand),
+        }
+    }
+
+    match operands.as_slice() {
+        [pattern] | [pattern, _] => Ok(WorkspaceCommand::Grep {
+            pattern: pattern.to_string(),
+            files: operands.get(1).unwrap_or(&"**").to_string(),
+            ignore_case,
+            fixed,
+        }),
+        _ => Err(WorkspaceError::InvalidCommand(GREP_USAGE.to_string())),
+    }
+}
+
 /// Helper function to parse non-launch commands that target a component ID.
 fn parse_component_id_surface_command(parts: &[&str]) -> Result<WorkspaceCommand, WorkspaceError> {
     let spec = component_id_command_by_token(parts[0]).ok_or_else(|| {
@@ -745,6 +905,22 @@ fn format_command(command: &WorkspaceCommand) -> String {
             format!("boot profile set {}", profile.name().to_lowercase())
         }
         WorkspaceCommand::BootProfileSave => "boot profile save".to_string(),
+        WorkspaceCommand::Find { pattern } => format!("find {}", pattern),
+        WorkspaceCommand::Grep {
+            pattern,
+            files,
+            ignore_case,
+            fixed,
+        } => {
+            let mut command = "grep".to_string();
+            if *ignore_case {
+                command.push_str(" -i");
+            }
+            if *fixed {
+                command.push_str(" -F");
+            }
+            format!("{} {} {}", command, pattern, files)
+        }
     }
 }
 
@@ -754,8 +930,8 @@ mod tests {
     use crate::boot_profile::BootProfile;
     use crate::{EditorIoContext, IdentityMetadata};
     use fs_view::DirectoryView;
-    use services_fs_view::FileSystemViewService;
-    use services_storage::JournaledStorage;
+    use services_fs_view::{FileSystemOperations, FileSystemViewService};
+    use services_storage::{JournaledStorage, TransactionalStorage};
 
     fn create_test_workspace() -> WorkspaceManager {
         let workspace_identity = IdentityMetadata::new(
@@ -1110,6 +1286,86 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_search_commands() {
+        assert_eq!(
+            parse_command("find docs/**/*.md").unwrap(),
+            WorkspaceCommand::Find {
+                pattern: "docs/**/*.md".to_string()
+            }
+        );
+        assert_eq!(
+            parse_command("grep -i -F a.b").unwrap(),
+            WorkspaceCommand::Grep {
+                pattern: "a.b".to_string(),
+                files: "**".to_string(),
+                ignore_case: true,
+                fixed: true,
+            }
+        );
+        assert!(matches!(
+            parse_command("grep todo *.md").unwrap(),
+            WorkspaceCommand::Grep { files, ignore_case: false, .. } if files == "*.md"
+        ));
+        assert!(parse_command("find").is_err());
+        assert!(parse_command("grep -i").is_err());
+        assert!(parse_command("grep a b c").is_err());
+    }
+
+    #[test]
+    fn test_execute_find_and_grep() {
+        let mut workspace = create_test_workspace();
+        let mut storage = JournaledStorage::new();
+        let mut fs_view = FileSystemViewService::new();
+        let mut root = DirectoryView::new(services_storage::ObjectId::new());
+        let principal = workspace.principal();
+        fs_view
+            .permissions_mut()
+            .register_object(root.id, services_storage::Ownership::new(principal, 0));
+        let mut fs = fs_view.as_principal(principal);
+        fs.mkdir(&mut root, "docs").unwrap();
+        for (path, text) in [
+            ("docs/intro.md", "Hello\nTODO: intro\n"),
+            ("notes.txt", "todo list\n"),
+        ] {
+            let object_id = services_storage::ObjectId::new();
+            let mut tx = storage.begin_transaction().unwrap();
+            storage.write(&mut tx, object_id, text.as_bytes()).unwrap();
+            storage.commit(&mut tx).unwrap();
+            fs.link(&mut root, path, object_id, ObjectKind::Blob)
+                .unwrap();
+        }
+        workspace.set_editor_io_context(EditorIoContext::with_fs_view(storage, fs_view, root));
+
+        let message = |result| match result {
+            CommandResult::Success { message } => message,
+            other => panic!("Expected Success, got {:?}", other),
+        };
+        assert_eq!(
+            message(workspace.execute_command(parse_command("find **").unwrap())),
+            "docs/\ndocs/intro.md\nnotes.txt"
+        );
+        assert_eq!(
+            message(workspace.execute_command(parse_command("grep -i todo").unwrap())),
+            "docs/intro.md:2: TODO: intro\nnotes.txt:1: todo list"
+        );
+        assert_eq!(
+            message(workspace.execute_command(parse_command("grep todo *.md").unwrap())),
+            "No matches"
+        );
+        assert!(matches!(
+            workspace.execute_command(parse_command("grep (").unwrap()),
+            CommandResult::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn test_execute_find_without_fs_view() {
+        let mut workspace = create_test_workspace();
+        let result = workspace.execute_command(parse_command("find *").unwrap());
+        assert!(matches!(result, CommandResult::Error { .. }));
+    }
+
     #[test]
     fn test_parse_settings_list_command() {
         let cmd = parse_command("settings list").unwrap();
@@ -94,7 +94,11 @@ impl HelpCategory {
 
         // Workspace command descriptors are sourced from shared palette specs.
         for spec in NON_LAUNCH_PALETTE_SPECS.iter().filter(|spec| {
-            spec.category == "Workspace" && matches!(spec.id, "list" | "focus_next" | "focus_prev")
+            spec.category == "Workspace"
+                && matches!(
+                    spec.id,
+                    "list" | "focus_next" | "focus_prev" | "find" | "grep"
+                )
         }) {
             if let Some(suggestion) = non_launch_prompt_suggestion_by_id(spec.id) {
                 lines.push(format_help_line(&suggestion.pattern, spec.description));
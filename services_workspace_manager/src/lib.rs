@@ -37,6 +37,8 @@ extern crate alloc;
 #[cfg(not(feature = "std"))]
 use alloc::boxed::Box;
 #[cfg(not(feature = "std"))]
+use alloc::rc::Rc;
+#[cfg(not(feature = "std"))]
 use alloc::string::{String, ToString};
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
@@ -48,10 +50,14 @@ use std::boxed::Box;
 #[cfg(feature = "std")]
 use std::collections::{HashMap, HashSet};
 #[cfg(feature = "std")]
+use std::rc::Rc;
+#[cfg(feature = "std")]
 use std::string::{String, ToString};
 #[cfg(feature = "std")]
 use std::vec::Vec;
 
+use console_vga::SelectionManager;
+use core::cell::RefCell;
 use core_types::uuid_tools::new_uuid;
 use core_types::{ServiceId, TaskId};
 use fs_view::{DirectoryResolver, DirectoryView};
@@ -69,7 +75,10 @@ use pipeline::{PayloadSchemaId, PayloadSchemaVersion, PipelineSpec, StageSpec, T
 use policy::{PolicyContext, PolicyDecision, PolicyEngine, PolicyEvent};
 use resources::ResourceBudget;
 use serde::{Deserialize, Serialize};
-use services_editor_vi::{Editor, OpenOptions, StorageEditorIo};
+use services_clipboard::{
+    ClipboardAccess, ClipboardClient, ClipboardError, ClipboardService, GrantToken,
+};
+use services_editor_vi::{Editor, EditorAction, OpenOptions, StorageEditorIo};
 use services_focus_manager::{FocusError, FocusManager};
 use services_fs_view::{FileSystemOperations, FileSystemViewService};
 use services_input::InputSubscriptionCap;
@@ -77,7 +86,8 @@ use services_input::InputSubscriptionCap;
 use services_pipeline_executor::PipelineExecutor;
 use services_settings::{self, SettingKey, SettingValue, SettingsRegistry};
 use services_storage::{
-    JournaledStorage, ObjectId, ObjectKind, TransactionError, TransactionalStorage,
+    CheckedStorage, JournaledStorage, ObjectId, ObjectKind, Ownership, PrincipalId,
+    TransactionError, TransactionalStorage,
 };
 use services_view_host::{ViewHandleCap, ViewHost, ViewSubscriptionCap};
 #[cfg(feature = "std")]
@@ -808,6 +818,8 @@ struct InlineConsole {
     max_output_lines: usize,
     next_main_revision: u64,
     next_status_revision: u64,
+    /// Clipboard that Ctrl+U publishes to and Ctrl+Y reads from
+    clipboard: Option<ClipboardClient>,
 }
 
 impl InlineConsole {
@@ -822,6 +834,7 @@ impl InlineConsole {
             max_output_lines: 256,
             next_main_revision: 1,
             next_status_revision: 1,
+            clipboard: None,
         }
     }
 
@@ -844,6 +857,33 @@ impl InlineConsole {
                     self.trim_output();
                     return None;
                 }
+                KeyCode::U => {
+                    let killed: String = self.input_buffer.drain(..self.cursor_pos).collect();
+                    self.cursor_pos = 0;
+                    if let Some(clipboard) = self.clipboard.as_mut().filter(|_| !killed.is_empty())
+                    {
+                        if let Err(err) = clipboard.publish(&killed, false) {
+                            self.push_output_lines(vec![format!("Clipboard: {}", err)]);
+                        }
+                    }
+                    return None;
+                }
+                KeyCode::Y => {
+                    let Some(clipboard) = &mut self.clipboard else {
+                        return None;
+                    };
+                    match clipboard.latest() {
+                        Ok(Some(entry)) => {
+                            // Only the first line of a multi-line clip is inserted
+                            let text = entry.text.lines().next().unwrap_or("");
+                            self.input_buffer.insert_str(self.cursor_pos, text);
+                            self.cursor_pos += text.len();
+                        }
+                        Ok(None) => {}
+                        Err(err) => self.push_output_lines(vec![format!("Clipboard: {}", err)]),
+                    }
+                    return None;
+                }
                 _ => {}
             }
         }
@@ -1121,6 +1161,13 @@ impl KernelApi for PipelineHarnessKernel {
         self.inner.receive_message(channel, timeout)
     }
 
+    fn select(
+        &mut self,
+        wait_set: &kernel_api::WaitSet,
+    ) -> Result<kernel_api::WaitOutcome, KernelError> {
+        self.inner.select(wait_set)
+    }
+
     fn now(&self) -> kernel_api::Instant {
         self.inner.now()
     }
@@ -1410,6 +1457,10 @@ pub struct WorkspaceManager {
     workspace_identity: IdentityMetadata,
     /// Optional editor I/O context for capability-scoped storage
     editor_io_context: Option<EditorIoContext>,
+    /// Clipboard shared by the workspace's components
+    clipboard: Rc<RefCell<ClipboardService>>,
+    /// Clipboard grants issued to running components
+    clipboard_grants: HashMap<ComponentId, GrantToken>,
     /// Debug info for keyboard routing (gated behind debug_assertions)
     #[cfg(debug_assertions)]
     key_routing_debug: KeyRoutingDebug,
@@ -1448,6 +1499,8 @@ impl WorkspaceManager {
             next_timestamp: 0,
             workspace_identity,
             editor_io_context: None,
+            clipboard: Rc::new(RefCell::new(ClipboardService::new())),
+            clipboard_grants: HashMap::new(),
             #[cfg(debug_assertions)]
             key_routing_debug: KeyRoutingDebug::new(),
             workspace_status: WorkspaceStatus::new(),
@@ -1463,11 +1516,69 @@ impl WorkspaceManager {
     }
 
     /// Sets the editor I/O context used to configure new editor components.
+    ///
+    /// A root directory nobody owns yet becomes the workspace's.
     pub fn set_editor_io_context(&mut self, mut context: EditorIoContext) {
         let _ = self.boot_profile_manager.load(Some(&mut context.storage));
+        if let (Some(fs_view), Some(root)) = (context.fs_view.as_mut(), context.root.as_ref()) {
+            let timestamp = fs_view.timestamp();
+            let permissions = fs_view.permissions_mut();
+            if permissions.get_ownership(root.id).is_none() {
+                permissions.register_object(root.id, Ownership::new(self.principal(), timestamp));
+            }
+        }
         self.editor_io_context = Some(context);
     }
 
+    /// Principal the workspace's storage accesses are made for
+    ///
+    /// Editors launched by the workspace act for it too.
+    pub fn principal(&self) -> PrincipalId {
+        PrincipalId::from(self.workspace_identity.execution_id)
+    }
+
+    /// Connects console selection to the workspace clipboard
+    ///
+    /// The console gets a read-write grant of its own, as editors and CLIs
+    /// do at launch; it never sees the service itself.
+    pub fn connect_console_selection(
+        &mut self,
+        selection: &mut SelectionManager,
+    ) -> Result<(), ClipboardError> {
+        let client = self.clipboard_client("console", ClipboardAccess::ReadWrite)?;
+        selection.connect_clipboard(client);
+        Ok(())
+    }
+
+    fn clipboard_client(
+        &mut self,
+        component: impl Into<String>,
+        access: ClipboardAccess,
+    ) -> Result<ClipboardClient, ClipboardError> {
+        let grant = self.clipboard.borrow_mut().grant(component, access)?;
+        Ok(ClipboardClient::new(grant, self.clipboard.clone()))
+    }
+
+    /// Issues a read-write clipboard grant to a launched component
+    ///
+    /// The grant is revoked when the component terminates. Without entropy
+    /// no grant is issued and the component keeps its clips to itself.
+    fn grant_clipboard(
+        &mut self,
+        component_id: ComponentId,
+        label: &str,
+    ) -> Option<ClipboardClient> {
+        let client = self
+            .clipboard_client(
+                format!("{}:{}", label, component_id),
+                ClipboardAccess::ReadWrite,
+            )
+            .ok()?;
+        self.clipboard_grants
+            .insert(component_id, client.grant().id);
+        Some(client)
+    }
+
     /// Sets the policy engine
     pub fn with_policy(mut self, policy: Box<dyn PolicyEngine>) -> Self {
         self.policy = Some(policy);
@@ -1601,6 +1712,10 @@ impl WorkspaceManager {
                 {
                     editor.set_view_handles(*main_view, *status_view);
                 }
+                if let Some(clipboard) = self.grant_clipboard(component_id, "editor") {
+                    editor.set_clipboard(clipboard);
+                }
+                editor.set_settings(self.settings_registry.clone(), self.current_user.clone());
                 // Configure editor I/O context if available
                 if let Some(context) = &self.editor_io_context {
                     let io = match (&context.fs_view, &context.root) {
@@ -1608,8 +1723,12 @@ impl WorkspaceManager {
                             context.storage.clone(),
                             fs_view.clone(),
                             root.clone(),
+                            self.principal(),
+                        ),
+                        _ => StorageEditorIo::new(
+                            CheckedStorage::new(context.storage.clone()),
+                            self.principal(),
                         ),
-                        _ => StorageEditorIo::new(context.storage.clone()),
                     };
                     editor.set_io(Box::new(io));
 
@@ -1634,6 +1753,7 @@ impl WorkspaceManager {
                         "Type `help` for built-in hints".to_string(),
                     ],
                 );
+                cli.clipboard = self.grant_clipboard(component_id, "cli");
                 if let (Some(main_view), Some(status_view)) =
                     (&component.main_view, &component.status_view)
                 {
@@ -2059,6 +2179,9 @@ impl WorkspaceManager {
 
         // Clean up component instance
         self.component_instances.remove(&component_id);
+        if let Some(grant) = self.clipboard_grants.remove(&component_id) {
+            self.clipboard.borrow_mut().revoke(grant);
+        }
         self.window_layout.remove_component(component_id);
 
         // Record event
@@ -2275,9 +2398,18 @@ impl WorkspaceManager {
         ids.into_iter().next()
     }
 
+    /// Lists the commands matching `query`; a query starting with `/` is a
+    /// case-insensitive regular expression
     fn command_palette_preview(&self, query: &str, limit: usize) -> (Vec<String>, usize, usize) {
         let mut commands = if query.trim().is_empty() {
             self.command_palette.list_commands()
+        } else if let Some(pattern) = query.strip_prefix('/') {
+            match text_pattern::Regex::new(pattern) {
+                Ok(regex) => self
+                    .command_palette
+                    .filter_commands_by_pattern(&regex.ignore_case(true)),
+                Err(err) => return (vec![format!("Invalid pattern: {}", err)], 0, 0),
+            }
         } else {
             self.command_palette.filter_commands(query)
         };
@@ -2420,6 +2552,7 @@ impl WorkspaceManager {
         let timestamp = self.next_timestamp();
         let mut pending_cli_command: Option<String> = None;
         let mut pending_custom_command: Option<String> = None;
+        let mut pending_settings: Vec<(String, SettingValue)> = Vec::new();
         #[cfg(feature = "std")]
         let mut pending_pipeline_command: Option<String> = None;
 
@@ -2429,7 +2562,17 @@ impl WorkspaceManager {
                 ComponentInstance::Editor(editor) => {
                     // Process input
                     match editor.process_input(event.clone()) {
-                        Ok(_action) => {
+                        Ok(action) => {
+                            // `:set` changes become the user's settings
+                            if let (EditorAction::SettingsChanged(keys), Some(settings)) =
+                                (action, editor.settings())
+                            {
+                                pending_settings.extend(keys.into_iter().filter_map(|key| {
+                                    let value = settings
+                                        .get(&self.current_user, &SettingKey::new(key.as_str()))?;
+                                    Some((key, value.clone()))
+                                }));
+                            }
                             // Publish updated views
                             let _ = editor.publish_views(&mut self.view_host, timestamp);
                         }
@@ -2565,6 +2708,9 @@ impl WorkspaceManager {
         if let Some(command) = pending_cli_command {
             self.execute_cli_component_command(component_id, command);
         }
+        for (key, value) in pending_settings {
+            self.set_setting(key, value);
+        }
         if let Some(command) = pending_custom_command {
             self.execute_custom_component_command(component_id, command);
         }
@@ -3075,15 +3221,14 @@ impl WorkspaceManager {
                 }
             }
             services_settings::keys::EDITOR_TAB_SIZE => {
-                // Apply to all editor instances
-                // TODO: When editors support runtime configuration, apply here
+                self.apply_editor_setting(key, &value);
                 if let Some(tab_size) = value.as_integer() {
                     self.workspace_status
                         .set_last_action(format!("Tab size set to: {}", tab_size));
                 }
             }
             services_settings::keys::EDITOR_LINE_NUMBERS => {
-                // Apply to all editor instances
+                self.apply_editor_setting(key, &value);
                 if let Some(show) = value.as_boolean() {
                     self.workspace_status.set_last_action(format!(
                         "Line numbers: {}",
@@ -3091,6 +3236,15 @@ impl WorkspaceManager {
                     ));
                 }
             }
+            services_settings::keys::EDITOR_WORD_WRAP => {
+                self.apply_editor_setting(key, &value);
+                if let Some(wrap) = value.as_boolean() {
+                    self.workspace_status.set_last_action(format!(
+                        "Word wrap: {}",
+                        if wrap { "enabled" } else { "disabled" }
+                    ));
+                }
+            }
             services_settings::keys::KEYBINDINGS_PROFILE => {
                 // Apply keybinding profile changes
                 if let Some(profile) = value.as_string() {
@@ -3104,6 +3258,15 @@ impl WorkspaceManager {
         }
     }
 
+    /// Applies an editor setting to every open editor
+    fn apply_editor_setting(&mut self, key: &str, value: &SettingValue) {
+        for instance in self.component_instances.values_mut() {
+            if let ComponentInstance::Editor(editor) = instance {
+                editor.apply_setting(key, value);
+            }
+        }
+    }
+
     /// Saves settings to storage (if storage context is available)
     pub fn save_settings(&mut self) -> Result<(), String> {
         // Export overrides
@@ -3115,6 +3278,7 @@ impl WorkspaceManager {
         let bytes = services_settings::persistence::serialize_overrides(&data)
             .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
+        let principal = self.principal();
         let result = {
             let context = match self.editor_io_context.as_mut() {
                 Some(context) => context,
@@ -3125,7 +3289,7 @@ impl WorkspaceManager {
                 }
             };
 
-            let object_id = Self::resolve_settings_object_for_write(context)?;
+            let object_id = Self::resolve_settings_object_for_write(context, principal)?;
             let mut tx = context
                 .storage
                 .begin_transaction()
@@ -3159,6 +3323,7 @@ impl WorkspaceManager {
 
     /// Loads settings from storage (if storage context is available)
     pub fn load_settings(&mut self) -> Result<(), String> {
+        let principal = self.principal();
         let (overrides, recovered_from_corruption, bytes_len) = {
             let context = match self.editor_io_context.as_mut() {
                 Some(context) => context,
@@ -3169,7 +3334,7 @@ impl WorkspaceManager {
                 }
             };
 
-            let object_id = match Self::resolve_settings_object_for_read(context)? {
+            let object_id = match Self::resolve_settings_object_for_read(context, principal)? {
                 Some(id) => id,
                 None => {
                     self.workspace_status.set_last_action(
@@ -3264,10 +3429,14 @@ impl WorkspaceManager {
     }
 
     fn resolve_settings_object_for_read(
-        context: &EditorIoContext,
+        context: &mut EditorIoContext,
+        principal: PrincipalId,
     ) -> Result<Option<ObjectId>, String> {
-        match (&context.fs_view, &context.root) {
-            (Some(fs_view), Some(root)) => match fs_view.open(root, SETTINGS_OVERRIDES_PATH) {
+        match (&mut context.fs_view, &context.root) {
+            (Some(fs_view), Some(root)) => match fs_view
+                .as_principal(principal)
+                .open(root, SETTINGS_OVERRIDES_PATH)
+            {
                 Ok(object_id) => Ok(Some(object_id)),
                 Err(services_fs_view::OperationError::NotFound(_)) => Ok(None),
                 Err(err) => Err(format!(
@@ -3281,43 +3450,52 @@ impl WorkspaceManager {
 
     fn resolve_settings_object_for_write(
         context: &mut EditorIoContext,
+        principal: PrincipalId,
     ) -> Result<ObjectId, String> {
         match (&mut context.fs_view, &mut context.root) {
-            (Some(fs_view), Some(root)) => match fs_view.open(root, SETTINGS_OVERRIDES_PATH) {
-                Ok(object_id) => Ok(object_id),
-                Err(services_fs_view::OperationError::NotFound(_)) => {
-                    match fs_view.mkdir(root, SETTINGS_DIR_PATH) {
-                        Ok(_) | Err(services_fs_view::OperationError::AlreadyExists(_)) => {}
-                        Err(err) => {
-                            return Err(format!(
-                                "Failed to create settings directory {}: {}",
-                                SETTINGS_DIR_PATH, err
-                            ));
+            (Some(fs_view), Some(root)) => {
+                let mut fs_view = fs_view.as_principal(principal);
+                match fs_view.open(root, SETTINGS_OVERRIDES_PATH) {
+                    Ok(object_id) => Ok(object_id),
+                    Err(services_fs_view::OperationError::NotFound(_)) => {
+                        match fs_view.mkdir(root, SETTINGS_DIR_PATH) {
+                            Ok(_) | Err(services_fs_view::OperationError::AlreadyExists(_)) => {}
+                            Err(err) => {
+                                return Err(format!(
+                                    "Failed to create settings directory {}: {}",
+                                    SETTINGS_DIR_PATH, err
+                                ));
+                            }
                         }
-                    }
 
-                    let object_id = Self::settings_object_id();
-                    match fs_view.link(root, SETTINGS_OVERRIDES_PATH, object_id, ObjectKind::Blob) {
-                        Ok(()) => Ok(object_id),
-                        Err(services_fs_view::OperationError::AlreadyExists(_)) => {
-                            fs_view.open(root, SETTINGS_OVERRIDES_PATH).map_err(|err| {
-                                format!(
-                                    "Failed to resolve settings path {} after link race: {}",
-                                    SETTINGS_OVERRIDES_PATH, err
-                                )
-                            })
+                        let object_id = Self::settings_object_id();
+                        match fs_view.link(
+                            root,
+                            SETTINGS_OVERRIDES_PATH,
+                            object_id,
+                            ObjectKind::Blob,
+                        ) {
+                            Ok(()) => Ok(object_id),
+                            Err(services_fs_view::OperationError::AlreadyExists(_)) => {
+                                fs_view.open(root, SETTINGS_OVERRIDES_PATH).map_err(|err| {
+                                    format!(
+                                        "Failed to resolve settings path {} after link race: {}",
+                                        SETTINGS_OVERRIDES_PATH, err
+                                    )
+                                })
+                            }
+                            Err(err) => Err(format!(
+                                "Failed to link settings path {}: {}",
+                                SETTINGS_OVERRIDES_PATH, err
+                            )),
                         }
-                        Err(err) => Err(format!(
-                            "Failed to link settings path {}: {}",
-                            SETTINGS_OVERRIDES_PATH, err
-                        )),
                     }
+                    Err(err) => Err(format!(
+                        "Failed to resolve settings path {}: {}",
+                        SETTINGS_OVERRIDES_PATH, err
+                    )),
                 }
-                Err(err) => Err(format!(
-                    "Failed to resolve settings path {}: {}",
-                    SETTINGS_OVERRIDES_PATH, err
-                )),
-            },
+            }
             _ => Ok(Self::settings_object_id()),
         }
     }
@@ -4509,6 +4687,72 @@ mod tests {
         assert_eq!(workspace.get_focused_component(), None);
     }
 
+    #[test]
+    fn test_editor_clipboard_grant_revoked_on_terminate() {
+        let mut workspace = create_test_workspace();
+
+        let config = LaunchConfig::new(
+            ComponentType::Editor,
+            "editor",
+            IdentityKind::Component,
+            TrustDomain::user(),
+        );
+        let component_id = workspace.launch_component(config).unwrap();
+        let grant = workspace.clipboard_grants[&component_id];
+        assert!(workspace.clipboard.borrow().latest(grant).is_ok());
+
+        workspace
+            .terminate_component(component_id, ExitReason::Normal)
+            .unwrap();
+        assert!(workspace.clipboard.borrow().latest(grant).is_err());
+    }
+
+    #[test]
+    fn test_cli_and_console_share_the_clipboard_through_their_own_grants() {
+        let mut workspace = create_test_workspace();
+        let mut selection = SelectionManager::new();
+        workspace.connect_console_selection(&mut selection).unwrap();
+        selection.copy_selection(b"from the console");
+
+        let config = LaunchConfig::new(
+            ComponentType::Cli,
+            "cli",
+            IdentityKind::Component,
+            TrustDomain::user(),
+        );
+        let component_id = workspace.launch_component(config).unwrap();
+        let grant = workspace.clipboard_grants[&component_id];
+        let Some(ComponentInstance::Cli(cli)) =
+            workspace.component_instances.get_mut(&component_id)
+        else {
+            panic!("CLI instance missing");
+        };
+
+        cli.process_input(InputEvent::key(KeyEvent::pressed(
+            KeyCode::Y,
+            Modifiers::CTRL,
+        )));
+        assert_eq!(cli.input_buffer, "from the console");
+        cli.process_input(InputEvent::key(KeyEvent::pressed(
+            KeyCode::Left,
+            Modifiers::none(),
+        )));
+        cli.process_input(InputEvent::key(KeyEvent::pressed(
+            KeyCode::U,
+            Modifiers::CTRL,
+        )));
+        assert_eq!(cli.input_buffer, "e");
+        let latest = workspace.clipboard.borrow().latest(grant).unwrap().cloned();
+        assert_eq!(latest.unwrap().source, format!("cli:{}", component_id));
+        assert!(selection.fetch_clipboard().unwrap());
+        assert_eq!(selection.paste(), b"from the consol");
+
+        workspace
+            .terminate_component(component_id, ExitReason::Normal)
+            .unwrap();
+        assert!(workspace.clipboard.borrow().latest(grant).is_err());
+    }
+
     #[test]
     fn test_terminate_removes_focus() {
         let mut workspace = create_test_workspace();
@@ -4692,6 +4936,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_editor_set_command_updates_settings() {
+        use input_types::{KeyCode, KeyEvent, Modifiers};
+
+        let mut workspace = create_test_workspace();
+        let config = LaunchConfig::new(
+            ComponentType::Editor,
+            "test-editor",
+            IdentityKind::Component,
+            TrustDomain::user(),
+        );
+        let editor_id = workspace.launch_component(config).unwrap();
+
+        let press = |code| InputEvent::key(KeyEvent::pressed(code, Modifiers::none()));
+        workspace.route_input(&InputEvent::key(KeyEvent::pressed(
+            KeyCode::Semicolon,
+            Modifiers::SHIFT,
+        )));
+        for code in [
+            KeyCode::S,
+            KeyCode::E,
+            KeyCode::T,
+            KeyCode::Space,
+            KeyCode::N,
+            KeyCode::O,
+            KeyCode::N,
+            KeyCode::U,
+            KeyCode::Enter,
+        ] {
+            workspace.route_input(&press(code));
+        }
+        assert_eq!(
+            workspace.get_setting(services_settings::keys::EDITOR_LINE_NUMBERS),
+            Some(&SettingValue::Boolean(false))
+        );
+
+        // Changes made in the workspace reach open editors
+        workspace.set_setting(
+            services_settings::keys::EDITOR_LINE_NUMBERS,
+            SettingValue::Boolean(true),
+        );
+        let Some(ComponentInstance::Editor(editor)) = workspace.component_instances.get(&editor_id)
+        else {
+            panic!("Expected an editor instance");
+        };
+        assert!(editor.state().options().number);
+    }
+
     #[test]
     fn test_global_binding_consumption() {
         use input_types::{KeyCode, KeyEvent, Modifiers};
@@ -5038,6 +5330,21 @@ mod tests {
         assert!(status_text.contains("Command palette ready:"));
     }
 
+    #[test]
+    fn test_command_palette_preview_pattern() {
+        let workspace = create_test_workspace();
+
+        let (_, _, all) = workspace.command_palette_preview("", usize::MAX);
+        let (lines, shown, total) = workspace.command_palette_preview("/^open", usize::MAX);
+        assert!(total > 0 && total < all);
+        assert_eq!(shown, total);
+        assert!(lines[1..].iter().all(|line| line.contains("Open")));
+
+        let (lines, _, total) = workspace.command_palette_preview("/(", 10);
+        assert_eq!(total, 0);
+        assert!(lines[0].starts_with("Invalid pattern"));
+    }
+
     #[test]
     fn test_action_command_mode_reuses_existing_cli() {
         use crate::keybindings::Action;
@@ -5264,13 +5571,65 @@ mod tests {
         workspace.set_setting("editor.tab_size".to_string(), SettingValue::Integer(2));
         workspace.save_settings().unwrap();
 
-        let context = workspace.editor_io_context.as_ref().unwrap();
-        let fs_view = context.fs_view.as_ref().unwrap();
+        let principal = workspace.principal();
+        let context = workspace.editor_io_context.as_mut().unwrap();
+        let fs_view = context.fs_view.as_mut().unwrap();
         let root = context.root.as_ref().unwrap();
-        let object_id = fs_view.open(root, SETTINGS_OVERRIDES_PATH).unwrap();
+        let object_id = fs_view
+            .as_principal(principal)
+            .open(root, SETTINGS_OVERRIDES_PATH)
+            .unwrap();
         assert_eq!(object_id, WorkspaceManager::settings_object_id());
     }
 
+    #[test]
+    fn test_editors_open_files_as_the_workspace() {
+        use services_fs_view::FileSystemOperations;
+
+        let mut workspace = create_test_workspace();
+        let stranger = PrincipalId::new();
+        let mut storage = JournaledStorage::new();
+        let mut fs_view = FileSystemViewService::new();
+        let mut root = DirectoryView::new(services_storage::ObjectId::new());
+        fs_view
+            .permissions_mut()
+            .register_object(root.id, Ownership::new(workspace.principal(), 0));
+        let mut tx = storage.begin_transaction().unwrap();
+        for (path, owner) in [
+            ("mine.txt", workspace.principal()),
+            ("theirs.txt", stranger),
+        ] {
+            let object_id = services_storage::ObjectId::new();
+            storage.write(&mut tx, object_id, path.as_bytes()).unwrap();
+            fs_view
+                .permissions_mut()
+                .register_object(object_id, Ownership::new(owner, 0));
+            fs_view
+                .as_principal(workspace.principal())
+                .link(&mut root, path, object_id, ObjectKind::Blob)
+                .unwrap();
+        }
+        storage.commit(&mut tx).unwrap();
+        workspace.set_editor_io_context(EditorIoContext::with_fs_view(storage, fs_view, root));
+
+        let mut open = |path: &str| {
+            let config = LaunchConfig::new(
+                ComponentType::Editor,
+                path,
+                IdentityKind::Component,
+                TrustDomain::user(),
+            )
+            .with_metadata("path", path);
+            let editor_id = workspace.launch_component(config).unwrap();
+            match workspace.component_instances.get(&editor_id) {
+                Some(ComponentInstance::Editor(editor)) => editor.get_content(),
+                _ => panic!("Expected an editor instance"),
+            }
+        };
+        assert_eq!(open("mine.txt"), "mine.txt");
+        assert_eq!(open("theirs.txt"), "");
+    }
+
     #[test]
     fn test_file_picker_status_breadcrumb_tracks_directory_path() {
         use input_types::{InputEvent, KeyCode, KeyEvent, Modifiers};
@@ -9,9 +9,10 @@
 use crate::command_surface::{
     helper_command_by_alias, is_known_command_prefix, non_launch_prefix_suggestions,
     non_launch_prompt_suggestion_by_id, validate_component_id_invocation, validate_help_invocation,
-    validate_open_invocation, CommandInvocationValidation, HelperCommandKind,
-    OpenInvocationValidation, PromptSuggestionSpec, SuggestionSpec, DEFAULT_SUGGESTIONS,
-    HELP_PREFIX_SUGGESTIONS, OPEN_PREFIX_SUGGESTIONS, RECENT_PREFIX_SUGGESTIONS,
+    validate_open_invocation, validate_search_invocation, CommandInvocationValidation,
+    HelperCommandKind, OpenInvocationValidation, PromptSuggestionSpec, SuggestionSpec,
+    DEFAULT_SUGGESTIONS, HELP_PREFIX_SUGGESTIONS, OPEN_PREFIX_SUGGESTIONS,
+    RECENT_PREFIX_SUGGESTIONS,
 };
 use serde::{Deserialize, Serialize};
 
@@ -458,6 +459,11 @@ pub fn validate_command(input: &str) -> PromptValidation {
             CommandInvocationValidation::ValidComplete => PromptValidation::ValidComplete,
             CommandInvocationValidation::Invalid => PromptValidation::Invalid,
         },
+        "find" | "grep" => match validate_search_invocation(&parts) {
+            CommandInvocationValidation::ValidPrefix => PromptValidation::ValidPrefix,
+            CommandInvocationValidation::ValidComplete => PromptValidation::ValidComplete,
+            CommandInvocationValidation::Invalid => PromptValidation::Invalid,
+        },
         "help" => match validate_help_invocation(&parts) {
             CommandInvocationValidation::ValidPrefix => PromptValidation::ValidPrefix,
             CommandInvocationValidation::ValidComplete => PromptValidation::ValidComplete,
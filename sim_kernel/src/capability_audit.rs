@@ -86,6 +86,7 @@ impl CapabilityAuditLog {
                 CapabilityEvent::Invalidated { cap_id: id, .. } => *id == cap_id,
                 CapabilityEvent::Revoked { cap_id: id, .. } => *id == cap_id,
                 CapabilityEvent::LeaseExpired { cap_id: id, .. } => *id == cap_id,
+                CapabilityEvent::InTransit { cap_id: id, .. } => *id == cap_id,
             })
             .collect()
     }
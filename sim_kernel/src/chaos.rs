@@ -0,0 +1,643 @@
+//! Seeded randomized fault campaigns
+//!
+//! A [`FaultProfile`] describes how likely each kind of fault is. From a
+//! seed it generates a [`FaultPlan`], always the same plan for the same seed.
+//! A [`ChaosCampaign`] runs a scenario once per seed; when a run fails it
+//! shrinks the plan, property-test style, to a minimal list of faults that
+//! still reproduces the failure.
+//!
+//! Scenarios report failures by returning `Err`. The workspace builds with
+//! `panic = "abort"`, so a panicking scenario only counts as a failure where
+//! panics unwind (test builds); otherwise it aborts the process.
+//!
+//! ## Example
+//!
+//! ```
+//! use sim_kernel::chaos::{ChaosCampaign, FaultProfile};
+//! use sim_kernel::SimulatedKernel;
+//! use kernel_api::KernelApi;
+//!
+//! let campaign = ChaosCampaign::new(FaultProfile::default()).with_runs(8);
+//! let report = campaign
+//!     .run(|plan| {
+//!         let mut kernel = SimulatedKernel::new().with_fault_plan(plan);
+//!         kernel.create_channel().map(|_| ()).map_err(|err| err.to_string())
+//!     })
+//!     .unwrap();
+//! assert_eq!(report.runs, 8);
+//! ```
+
+use crate::fault_injection::{Fault, FaultPlan, LifecycleFault, MessageFault};
+use kernel_api::Duration;
+use std::fmt;
+
+/// Small deterministic PRNG (SplitMix64)
+///
+/// Good enough to spread faults; not for anything security related.
+#[derive(Debug, Clone)]
+pub struct ChaosRng {
+    state: u64,
+}
+
+impl ChaosRng {
+    /// Creates a generator from a seed
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns the next 64 random bits
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a value in `[low, high]`
+    pub fn range_inclusive(&mut self, low: u64, high: u64) -> u64 {
+        if high <= low {
+            return low;
+        }
+        low + self.next_u64() % (high - low + 1)
+    }
+
+    /// Returns true with the given probability
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+}
+
+/// How likely each kind of fault is
+///
+/// A plan has up to `max_faults` slots. Each slot independently becomes a
+/// drop, delay, reorder, crash, duplicate or corruption fault with the
+/// given probabilities, or stays empty with the remaining probability.
+/// Partitions name concrete tasks, so campaigns add them to plans by hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultProfile {
+    pub max_faults: usize,
+    pub drop_probability: f64,
+    pub delay_probability: f64,
+    pub reorder_probability: f64,
+    pub crash_probability: f64,
+    pub duplicate_probability: f64,
+    pub corrupt_probability: f64,
+    /// Upper bound for `DropNext` counts
+    pub max_drop_count: usize,
+    /// Upper bound for `Delay` durations
+    pub max_delay: Duration,
+    /// Upper bound for reorder indices and offsets
+    pub reorder_window: usize,
+    /// Upper bound for `CrashAfterMessages` counts
+    pub max_messages_before_crash: usize,
+    /// Actions eligible for `DropMatching` (drops use `DropNext` if empty)
+    pub actions: Vec<String>,
+}
+
+impl Default for FaultProfile {
+    fn default() -> Self {
+        Self {
+            max_faults: 4,
+            drop_probability: 0.3,
+            delay_probability: 0.2,
+            reorder_probability: 0.2,
+            crash_probability: 0.05,
+            duplicate_probability: 0.0,
+            corrupt_probability: 0.0,
+            max_drop_count: 3,
+            max_delay: Duration::from_millis(50),
+            reorder_window: 4,
+            max_messages_before_crash: 8,
+            actions: Vec::new(),
+        }
+    }
+}
+
+impl FaultProfile {
+    /// Creates a profile that never injects faults
+    pub fn none() -> Self {
+        Self {
+            drop_probability: 0.0,
+            delay_probability: 0.0,
+            reorder_probability: 0.0,
+            crash_probability: 0.0,
+            duplicate_probability: 0.0,
+            corrupt_probability: 0.0,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the number of fault slots
+    pub fn with_max_faults(mut self, max_faults: usize) -> Self {
+        self.max_faults = max_faults;
+        self
+    }
+
+    /// Sets the probability of a drop fault per slot
+    pub fn with_drops(mut self, probability: f64) -> Self {
+        self.drop_probability = probability;
+        self
+    }
+
+    /// Sets the probability of a delay fault per slot
+    pub fn with_delays(mut self, probability: f64) -> Self {
+        self.delay_probability = probability;
+        self
+    }
+
+    /// Sets the probability of a reorder fault per slot
+    pub fn with_reorders(mut self, probability: f64) -> Self {
+        self.reorder_probability = probability;
+        self
+    }
+
+    /// Sets the probability of a crash fault per slot
+    pub fn with_crashes(mut self, probability: f64) -> Self {
+        self.crash_probability = probability;
+        self
+    }
+
+    /// Sets the probability of a duplicate fault per slot
+    pub fn with_duplicates(mut self, probability: f64) -> Self {
+        self.duplicate_probability = probability;
+        self
+    }
+
+    /// Sets the probability of a payload corruption fault per slot
+    pub fn with_corruptions(mut self, probability: f64) -> Self {
+        self.corrupt_probability = probability;
+        self
+    }
+
+    /// Makes drops target specific message actions
+    pub fn with_actions<S: Into<String>>(mut self, actions: impl IntoIterator<Item = S>) -> Self {
+        self.actions = actions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Generates the fault plan for a seed
+    pub fn generate(&self, seed: u64) -> FaultPlan {
+        let mut rng = ChaosRng::new(seed);
+        let faults = (0..self.max_faults).filter_map(|_| self.generate_fault(&mut rng));
+        FaultPlan::from_faults(faults.collect::<Vec<_>>())
+    }
+
+    fn generate_fault(&self, rng: &mut ChaosRng) -> Option<Fault> {
+        let roll = rng.next_f64();
+        let mut threshold = self.drop_probability;
+        if roll < threshold {
+            return Some(Fault::Message(self.generate_drop(rng)));
+        }
+        threshold += self.delay_probability;
+        if roll < threshold {
+            let max_nanos = self.max_delay.as_nanos().max(1);
+            let nanos = rng.range_inclusive(1, max_nanos);
+            return Some(Fault::Message(MessageFault::Delay {
+                duration: Duration::from_nanos(nanos),
+            }));
+        }
+        threshold += self.reorder_probability;
+        if roll < threshold {
+            let window = self.reorder_window.max(1) as u64;
+            return Some(Fault::Message(MessageFault::ReorderWindow {
+                index: rng.range_inclusive(0, window - 1) as usize,
+                offset: rng.range_inclusive(1, window) as usize,
+            }));
+        }
+        threshold += self.crash_probability;
+        if roll < threshold {
+            let fault = match rng.range_inclusive(0, 2) {
+                0 => LifecycleFault::CrashOnSend,
+                1 => LifecycleFault::CrashOnRecv,
+                _ => LifecycleFault::CrashAfterMessages {
+                    count: rng.range_inclusive(1, self.max_messages_before_crash.max(1) as u64)
+                        as usize,
+                },
+            };
+            return Some(Fault::Lifecycle(fault));
+        }
+        threshold += self.duplicate_probability;
+        if roll < threshold {
+            let count = rng.range_inclusive(1, self.max_drop_count.max(1) as u64) as usize;
+            return Some(Fault::Message(MessageFault::Duplicate { count }));
+        }
+        threshold += self.corrupt_probability;
+        if roll < threshold {
+            let count = rng.range_inclusive(1, self.max_drop_count.max(1) as u64) as usize;
+            return Some(Fault::Message(MessageFault::CorruptPayload { count }));
+        }
+        None
+    }
+
+    fn generate_drop(&self, rng: &mut ChaosRng) -> MessageFault {
+        if !self.actions.is_empty() && rng.chance(0.5) {
+            let index = rng.range_inclusive(0, self.actions.len() as u64 - 1) as usize;
+            MessageFault::DropMatching {
+                action: self.actions[index].clone(),
+            }
+        } else {
+            MessageFault::DropNext {
+                count: rng.range_inclusive(1, self.max_drop_count.max(1) as u64) as usize,
+            }
+        }
+    }
+}
+
+/// Summary of a campaign in which every run passed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CampaignReport {
+    pub runs: usize,
+    /// Total faults across all generated plans
+    pub faults_injected: usize,
+}
+
+/// A failing run, with the plan shrunk to a minimal reproduction
+#[derive(Debug, Clone)]
+pub struct CampaignFailure {
+    pub seed: u64,
+    /// The plan generated for `seed`
+    pub original: FaultPlan,
+    /// A smallest plan found that still fails
+    pub minimal: FaultPlan,
+    /// The failure reported by the minimal plan
+    pub error: String,
+    /// Scenario runs spent shrinking
+    pub shrink_runs: usize,
+}
+
+impl fmt::Display for CampaignFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "chaos campaign failed for seed {} ({} faults, shrunk to {} in {} runs): {}",
+            self.seed,
+            self.original.len(),
+            self.minimal.len(),
+            self.shrink_runs,
+            self.error
+        )?;
+        for fault in self.minimal.faults() {
+            writeln!(f, "  {:?}", fault)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CampaignFailure {}
+
+/// Runs a scenario against many seeded fault plans
+#[derive(Debug, Clone)]
+pub struct ChaosCampaign {
+    profile: FaultProfile,
+    first_seed: u64,
+    runs: usize,
+    max_shrink_runs: usize,
+}
+
+impl ChaosCampaign {
+    /// Creates a campaign of 64 runs starting at seed 0
+    pub fn new(profile: FaultProfile) -> Self {
+        Self {
+            profile,
+            first_seed: 0,
+            runs: 64,
+            max_shrink_runs: 256,
+        }
+    }
+
+    /// Sets the first seed; run `i` uses `seed + i`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.first_seed = seed;
+        self
+    }
+
+    /// Sets the number of runs
+    pub fn with_runs(mut self, runs: usize) -> Self {
+        self.runs = runs;
+        self
+    }
+
+    /// Caps the scenario runs spent shrinking a failure
+    pub fn with_max_shrink_runs(mut self, max_shrink_runs: usize) -> Self {
+        self.max_shrink_runs = max_shrink_runs;
+        self
+    }
+
+    /// Returns the profile plans are generated from
+    pub fn profile(&self) -> &FaultProfile {
+        &self.profile
+    }
+
+    /// Runs the scenario once per seed, stopping at the first failure
+    ///
+    /// The scenario builds its own kernel from the plan and reports failure
+    /// by returning `Err`. Panics are also caught when panics unwind.
+    pub fn run<F>(&self, mut scenario: F) -> Result<CampaignReport, Box<CampaignFailure>>
+    where
+        F: FnMut(FaultPlan) -> Result<(), String>,
+    {
+        let mut faults_injected = 0;
+        for run in 0..self.runs {
+            let seed = self.first_seed.wrapping_add(run as u64);
+            let plan = self.profile.generate(seed);
+            faults_injected += plan.len();
+
+            if let Err(error) = run_scenario(&mut scenario, plan.clone()) {
+                let (minimal, error, shrink_runs) = self.shrink(&mut scenario, plan.clone(), error);
+                return Err(Box::new(CampaignFailure {
+                    seed,
+                    original: plan,
+                    minimal,
+                    error,
+                    shrink_runs,
+                }));
+            }
+        }
+
+        Ok(CampaignReport {
+            runs: self.runs,
+            faults_injected,
+        })
+    }
+
+    /// Shrinks a failing plan: first drop whole faults, then simplify the
+    /// remaining ones, until no candidate fails or the budget runs out
+    fn shrink<F>(
+        &self,
+        scenario: &mut F,
+        plan: FaultPlan,
+        error: String,
+    ) -> (FaultPlan, String, usize)
+    where
+        F: FnMut(FaultPlan) -> Result<(), String>,
+    {
+        let mut faults = plan.faults();
+        let mut error = error;
+        let mut shrink_runs = 0;
+
+        'shrinking: loop {
+            let candidates = (0..faults.len())
+                .map(|index| {
+                    let mut candidate = faults.clone();
+                    candidate.remove(index);
+                    candidate
+                })
+                .chain((0..faults.len()).flat_map(|index| {
+                    let faults = &faults;
+                    simplifications(&faults[index])
+                        .into_iter()
+                        .map(move |fault| {
+                            let mut candidate = faults.clone();
+                            candidate[index] = fault;
+                            candidate
+                        })
+                }))
+                .collect::<Vec<_>>();
+
+            for candidate in candidates {
+                if shrink_runs >= self.max_shrink_runs {
+                    break 'shrinking;
+                }
+                shrink_runs += 1;
+                if let Err(candidate_error) =
+                    run_scenario(scenario, FaultPlan::from_faults(candidate.clone()))
+                {
+                    faults = candidate;
+                    error = candidate_error;
+                    continue 'shrinking;
+                }
+            }
+            break;
+        }
+
+        (FaultPlan::from_faults(faults), error, shrink_runs)
+    }
+}
+
+/// Runs a scenario, turning panics into errors
+#[cfg(panic = "unwind")]
+fn run_scenario<F>(scenario: &mut F, plan: FaultPlan) -> Result<(), String>
+where
+    F: FnMut(FaultPlan) -> Result<(), String>,
+{
+    use std::panic::{self, AssertUnwindSafe};
+
+    match panic::catch_unwind(AssertUnwindSafe(|| scenario(plan))) {
+        Ok(result) => result,
+        Err(payload) => Err(payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "scenario panicked".to_string())),
+    }
+}
+
+/// Runs a scenario; under `panic = "abort"` a panic cannot be caught
+#[cfg(not(panic = "unwind"))]
+fn run_scenario<F>(scenario: &mut F, plan: FaultPlan) -> Result<(), String>
+where
+    F: FnMut(FaultPlan) -> Result<(), String>,
+{
+    scenario(plan)
+}
+
+/// Smaller versions of a fault, most aggressive first
+fn simplifications(fault: &Fault) -> Vec<Fault> {
+    fn smaller(value: usize, floor: usize) -> Vec<usize> {
+        let mut values = Vec::new();
+        if value > floor {
+            values.push(floor);
+            if value / 2 > floor {
+                values.push(value / 2);
+            }
+            if value - 1 > floor && value - 1 != value / 2 {
+                values.push(value - 1);
+            }
+        }
+        values
+    }
+
+    match fault {
+        Fault::Message(MessageFault::DropNext { count }) => smaller(*count, 1)
+            .into_iter()
+            .map(|count| Fault::Message(MessageFault::DropNext { count }))
+            .collect(),
+        Fault::Message(MessageFault::DropNextOnChannel { channel, count }) => smaller(*count, 1)
+            .into_iter()
+            .map(|count| {
+                Fault::Message(MessageFault::DropNextOnChannel {
+                    channel: *channel,
+                    count,
+                })
+            })
+            .collect(),
+        Fault::Message(MessageFault::Delay { duration }) => {
+            let nanos = duration.as_nanos();
+            if nanos > 1 {
+                vec![Fault::Message(MessageFault::Delay {
+                    duration: Duration::from_nanos(nanos / 2),
+                })]
+            } else {
+                Vec::new()
+            }
+        }
+        Fault::Message(MessageFault::ReorderWindow { index, offset }) => smaller(*index, 0)
+            .into_iter()
+            .map(|index| (index, *offset))
+            .chain(
+                smaller(*offset, 1)
+                    .into_iter()
+                    .map(|offset| (*index, offset)),
+            )
+            .map(|(index, offset)| Fault::Message(MessageFault::ReorderWindow { index, offset }))
+            .collect(),
+        Fault::Message(MessageFault::Duplicate { count }) => smaller(*count, 1)
+            .into_iter()
+            .map(|count| Fault::Message(MessageFault::Duplicate { count }))
+            .collect(),
+        Fault::Message(MessageFault::CorruptPayload { count }) => smaller(*count, 1)
+            .into_iter()
+            .map(|count| Fault::Message(MessageFault::CorruptPayload { count }))
+            .collect(),
+        Fault::Message(MessageFault::Partition { groups, heal_after }) => {
+            // A partition that heals immediately is the weakest version
+            if *heal_after == Some(Duration::from_nanos(0)) {
+                Vec::new()
+            } else {
+                vec![Fault::Message(MessageFault::Partition {
+                    groups: groups.clone(),
+                    heal_after: Some(Duration::from_nanos(0)),
+                })]
+            }
+        }
+        Fault::Lifecycle(LifecycleFault::CrashAfterMessages { count }) => smaller(*count, 0)
+            .into_iter()
+            .map(|count| Fault::Lifecycle(LifecycleFault::CrashAfterMessages { count }))
+            .collect(),
+        Fault::Message(MessageFault::DropMatching { .. })
+        | Fault::Lifecycle(LifecycleFault::CrashOnSend)
+        | Fault::Lifecycle(LifecycleFault::CrashOnRecv) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimulatedKernel;
+    use core_types::ServiceId;
+    use ipc::{MessageEnvelope, MessagePayload, SchemaVersion};
+    use kernel_api::KernelApi;
+
+    /// Sends three messages and expects all of them back
+    fn delivery_scenario(plan: FaultPlan) -> Result<(), String> {
+        let mut kernel = SimulatedKernel::new().with_fault_plan(plan);
+        let channel = kernel.create_channel().map_err(|err| err.to_string())?;
+        for index in 0..3u32 {
+            let message = MessageEnvelope::new(
+                ServiceId::new(),
+                "chaos.test".to_string(),
+                SchemaVersion::new(1, 0),
+                MessagePayload::new(&index).unwrap(),
+            );
+            kernel
+                .send_message(channel, message)
+                .map_err(|err| err.to_string())?;
+        }
+        kernel.run_until_idle();
+
+        let mut received = 0;
+        while kernel.receive_message(channel, None).is_ok() {
+            received += 1;
+        }
+        if received != 3 {
+            return Err(format!("lost messages: received {} of 3", received));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_seed_same_plan() {
+        let profile = FaultProfile::default().with_max_faults(8);
+        assert_eq!(profile.generate(42), profile.generate(42));
+        assert!((0..32).any(|seed| profile.generate(seed) != profile.generate(42)));
+        assert!(FaultProfile::none().generate(42).is_empty());
+    }
+
+    #[test]
+    fn test_campaign_passes_when_scenario_tolerates_faults() {
+        let campaign =
+            ChaosCampaign::new(FaultProfile::default().with_crashes(0.0).with_drops(0.0))
+                .with_runs(32);
+        let report = campaign.run(delivery_scenario).unwrap();
+        assert_eq!(report.runs, 32);
+        assert!(report.faults_injected > 0);
+    }
+
+    #[test]
+    fn test_failure_shrinks_to_single_fault() {
+        let profile = FaultProfile::default()
+            .with_max_faults(6)
+            .with_drops(0.4)
+            .with_delays(0.3)
+            .with_reorders(0.3)
+            .with_crashes(0.0);
+        let failure = ChaosCampaign::new(profile)
+            .with_seed(7)
+            .run(delivery_scenario)
+            .unwrap_err();
+
+        assert!(failure.original.len() >= failure.minimal.len());
+        assert_eq!(
+            failure.minimal.faults(),
+            vec![Fault::Message(MessageFault::DropNext { count: 1 })]
+        );
+        assert!(failure.error.contains("lost messages"));
+        assert!(delivery_scenario(failure.minimal.clone()).is_err());
+    }
+
+    #[test]
+    #[cfg(panic = "unwind")]
+    fn test_scenario_panics_count_as_failures() {
+        let mut scenario = |_: FaultPlan| -> Result<(), String> { panic!("invariant broken") };
+        assert_eq!(
+            run_scenario(&mut scenario, FaultPlan::new()),
+            Err("invariant broken".to_string())
+        );
+    }
+
+    #[test]
+    fn test_shrinking_simplifies_parameters() {
+        let campaign = ChaosCampaign::new(FaultProfile::none());
+        let plan = FaultPlan::new()
+            .with_message_fault(MessageFault::ReorderWindow {
+                index: 3,
+                offset: 4,
+            })
+            .with_lifecycle_fault(LifecycleFault::CrashAfterMessages { count: 6 });
+
+        // Fails whenever any reorder fault is present
+        let mut scenario = |plan: FaultPlan| {
+            if plan.message_faults().is_empty() {
+                Ok(())
+            } else {
+                Err("reordered".to_string())
+            }
+        };
+        let (minimal, error, _) = campaign.shrink(&mut scenario, plan, "reordered".into());
+
+        assert_eq!(error, "reordered");
+        assert_eq!(
+            minimal.faults(),
+            vec![Fault::Message(MessageFault::ReorderWindow {
+                index: 0,
+                offset: 1
+            })]
+        );
+    }
+}
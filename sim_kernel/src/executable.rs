@@ -586,6 +586,13 @@ mod tests {
                 unimplemented!()
             }
 
+            fn select(
+                &mut self,
+                _wait_set: &kernel_api::WaitSet,
+            ) -> Result<kernel_api::WaitOutcome, KernelError> {
+                unimplemented!()
+            }
+
             fn now(&self) -> Instant {
                 Instant::from_nanos(0)
             }
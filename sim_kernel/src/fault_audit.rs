@@ -0,0 +1,114 @@
+//! Fault Audit Log
+//!
+//! Records every fault the kernel's delivery path applied, so tests can
+//! tell an injected failure apart from a real one.
+
+use core_types::TaskId;
+use ipc::{ChannelId, MessageId};
+use kernel_api::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+/// A fault applied to a message or operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FaultEvent {
+    /// A message was silently dropped
+    MessageDropped {
+        channel: ChannelId,
+        message_id: MessageId,
+    },
+    /// A message was held back before delivery
+    MessageDelayed {
+        channel: ChannelId,
+        message_id: MessageId,
+        duration: Duration,
+    },
+    /// Queued messages were swapped around
+    MessagesReordered {
+        channel: ChannelId,
+        swaps: Vec<(usize, usize)>,
+    },
+    /// A message was enqueued a second time
+    MessageDuplicated {
+        channel: ChannelId,
+        message_id: MessageId,
+    },
+    /// A message payload was corrupted in flight
+    PayloadCorrupted {
+        channel: ChannelId,
+        message_id: MessageId,
+    },
+    /// A message was dropped because a partition cut the sender off
+    PartitionDropped {
+        channel: ChannelId,
+        message_id: MessageId,
+        from: TaskId,
+        partition: usize,
+    },
+    /// A partition healed and traffic flows again
+    PartitionHealed { partition: usize },
+    /// A send failed with a simulated crash
+    CrashOnSend { channel: ChannelId },
+    /// A receive failed with a simulated crash
+    CrashOnRecv { channel: ChannelId },
+}
+
+/// Fault audit event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultAuditEvent {
+    /// When the fault was applied (simulated time)
+    pub timestamp: Instant,
+    /// What happened
+    pub event: FaultEvent,
+}
+
+/// Fault audit log for testing
+///
+/// Records all faults applied during execution.
+#[derive(Debug, Clone)]
+pub struct FaultAuditLog {
+    events: Vec<FaultAuditEvent>,
+}
+
+impl FaultAuditLog {
+    /// Creates a new empty audit log
+    pub fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    /// Records an applied fault
+    pub fn record(&mut self, timestamp: Instant, event: FaultEvent) {
+        self.events.push(FaultAuditEvent { timestamp, event });
+    }
+
+    /// Returns all recorded events
+    pub fn events(&self) -> &[FaultAuditEvent] {
+        &self.events
+    }
+
+    /// Clears all recorded events
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// Checks if any event matches a predicate
+    pub fn has_event<F>(&self, predicate: F) -> bool
+    where
+        F: Fn(&FaultEvent) -> bool,
+    {
+        self.events.iter().any(|e| predicate(&e.event))
+    }
+
+    /// Counts events matching a predicate
+    pub fn count_events<F>(&self, predicate: F) -> usize
+    where
+        F: Fn(&FaultEvent) -> bool,
+    {
+        self.events.iter().filter(|e| predicate(&e.event)).count()
+    }
+}
+
+impl Default for FaultAuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
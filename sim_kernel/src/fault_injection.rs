@@ -21,12 +21,13 @@
 //!     .with_message_fault(MessageFault::Delay { duration: Duration::from_millis(100) });
 //! ```
 
-use ipc::{ChannelId, MessageEnvelope};
-use kernel_api::Duration;
+use core_types::TaskId;
+use ipc::{ChannelId, MessageEnvelope, MessagePayload};
+use kernel_api::{Duration, Instant};
 use std::collections::VecDeque;
 
 /// A fault to inject into message delivery
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MessageFault {
     /// Drop the next N messages on any channel
     DropNext { count: usize },
@@ -43,10 +44,26 @@ pub enum MessageFault {
     /// Reorder messages within a bounded window (swap positions)
     /// Swaps message at index with the one at index + offset
     ReorderWindow { index: usize, offset: usize },
+
+    /// Deliver each of the next N messages twice
+    Duplicate { count: usize },
+
+    /// Corrupt the payload bytes of the next N messages
+    CorruptPayload { count: usize },
+
+    /// Drop messages between tasks in different groups
+    ///
+    /// A message is cut off when its source task and every receiver of the
+    /// channel sit in different groups. Tasks in no group are unaffected.
+    /// It heals `heal_after` once the injector is installed, or never.
+    Partition {
+        groups: Vec<Vec<TaskId>>,
+        heal_after: Option<Duration>,
+    },
 }
 
 /// A fault to inject into task/service lifecycle
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LifecycleFault {
     /// Crash a task on the next send operation
     CrashOnSend,
@@ -58,11 +75,18 @@ pub enum LifecycleFault {
     CrashAfterMessages { count: usize },
 }
 
+/// Any fault a plan can contain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    Message(MessageFault),
+    Lifecycle(LifecycleFault),
+}
+
 /// A plan describing all faults to inject
 ///
 /// This is configured per-test and provides a deterministic way to
 /// inject various failure modes into the system.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct FaultPlan {
     /// Message-level faults
     message_faults: Vec<MessageFault>,
@@ -101,6 +125,39 @@ impl FaultPlan {
     pub fn lifecycle_faults(&self) -> &[LifecycleFault] {
         &self.lifecycle_faults
     }
+
+    /// Builds a plan from a list of faults
+    pub fn from_faults(faults: impl IntoIterator<Item = Fault>) -> Self {
+        faults.into_iter().fold(Self::new(), Self::with_fault)
+    }
+
+    /// Adds a fault of either kind to the plan
+    pub fn with_fault(self, fault: Fault) -> Self {
+        match fault {
+            Fault::Message(fault) => self.with_message_fault(fault),
+            Fault::Lifecycle(fault) => self.with_lifecycle_fault(fault),
+        }
+    }
+
+    /// Returns all faults: message faults first, then lifecycle faults
+    pub fn faults(&self) -> Vec<Fault> {
+        self.message_faults
+            .iter()
+            .cloned()
+            .map(Fault::Message)
+            .chain(self.lifecycle_faults.iter().cloned().map(Fault::Lifecycle))
+            .collect()
+    }
+
+    /// Returns the total number of faults
+    pub fn len(&self) -> usize {
+        self.message_faults.len() + self.lifecycle_faults.len()
+    }
+
+    /// Returns true if the plan injects no faults
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 /// Fault injector that applies faults to message delivery
@@ -120,6 +177,33 @@ pub struct FaultInjector {
     crash_after_messages: Option<usize>,
     should_crash_on_send: bool,
     should_crash_on_recv: bool,
+    duplicate_next_count: usize,
+    corrupt_next_count: usize,
+    partitions: Vec<PartitionState>,
+}
+
+/// An active or healed partition
+#[derive(Debug)]
+struct PartitionState {
+    groups: Vec<Vec<TaskId>>,
+    heal_after: Option<Duration>,
+    heals_at: Option<Instant>,
+    healed: bool,
+}
+
+impl PartitionState {
+    fn group_of(&self, task_id: TaskId) -> Option<usize> {
+        self.groups
+            .iter()
+            .position(|group| group.contains(&task_id))
+    }
+
+    fn separates(&self, from: TaskId, to: TaskId) -> bool {
+        match (self.group_of(from), self.group_of(to)) {
+            (Some(from_group), Some(to_group)) => from_group != to_group,
+            _ => false,
+        }
+    }
 }
 
 impl FaultInjector {
@@ -135,6 +219,9 @@ impl FaultInjector {
             crash_after_messages: None,
             should_crash_on_send: false,
             should_crash_on_recv: false,
+            duplicate_next_count: 0,
+            corrupt_next_count: 0,
+            partitions: Vec::new(),
         };
 
         // Initialize state from plan
@@ -156,6 +243,20 @@ impl FaultInjector {
                 MessageFault::ReorderWindow { .. } => {
                     // Handled when applying to queue
                 }
+                MessageFault::Duplicate { count } => {
+                    injector.duplicate_next_count = *count;
+                }
+                MessageFault::CorruptPayload { count } => {
+                    injector.corrupt_next_count = *count;
+                }
+                MessageFault::Partition { groups, heal_after } => {
+                    injector.partitions.push(PartitionState {
+                        groups: groups.clone(),
+                        heal_after: *heal_after,
+                        heals_at: heal_after.map(|after| Instant::from_nanos(0) + after),
+                        healed: false,
+                    });
+                }
             }
         }
 
@@ -206,6 +307,57 @@ impl FaultInjector {
         false
     }
 
+    /// Starts partition heal timers from the given time
+    ///
+    /// The kernel calls this when the injector is installed.
+    pub fn set_start_time(&mut self, now: Instant) {
+        for partition in &mut self.partitions {
+            partition.heals_at = partition.heal_after.map(|after| now + after);
+        }
+    }
+
+    /// Returns the index of an unhealed partition separating two tasks
+    pub fn partition_between(&self, from: TaskId, to: TaskId) -> Option<usize> {
+        self.partitions
+            .iter()
+            .position(|partition| !partition.healed && partition.separates(from, to))
+    }
+
+    /// Heals partitions whose time is up, returning their indices
+    pub fn heal_partitions(&mut self, now: Instant) -> Vec<usize> {
+        self.partitions
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, partition)| {
+                !partition.healed && partition.heals_at.is_some_and(|at| now >= at)
+            })
+            .map(|(index, partition)| {
+                partition.healed = true;
+                index
+            })
+            .collect()
+    }
+
+    /// Checks if the next message should be delivered twice
+    pub fn should_duplicate(&mut self) -> bool {
+        if self.duplicate_next_count > 0 {
+            self.duplicate_next_count -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Checks if the next message payload should be corrupted
+    pub fn should_corrupt(&mut self) -> bool {
+        if self.corrupt_next_count > 0 {
+            self.corrupt_next_count -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Returns the delay to apply to the next message, if any
     pub fn get_message_delay(&mut self) -> Option<Duration> {
         if self.delay_next_count > 0 {
@@ -218,15 +370,27 @@ impl FaultInjector {
 
     /// Applies reordering faults to a message queue
     pub fn apply_reordering(&self, messages: &mut VecDeque<MessageEnvelope>) {
-        for fault in self.plan.message_faults() {
-            if let MessageFault::ReorderWindow { index, offset } = fault {
-                if *index < messages.len() && *index + offset < messages.len() {
-                    messages.swap(*index, *index + offset);
-                }
-            }
+        for (index, other) in self.reorder_swaps(messages.len()) {
+            messages.swap(index, other);
         }
     }
 
+    /// Returns the swaps reordering faults make on a queue of this length
+    pub fn reorder_swaps(&self, queue_len: usize) -> Vec<(usize, usize)> {
+        self.plan
+            .message_faults()
+            .iter()
+            .filter_map(|fault| match fault {
+                MessageFault::ReorderWindow { index, offset }
+                    if *index < queue_len && *index + offset < queue_len =>
+                {
+                    Some((*index, *index + offset))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Checks if a crash should occur on send
     pub fn should_crash_on_send(&mut self) -> bool {
         self.should_crash_on_send
@@ -256,6 +420,21 @@ impl FaultInjector {
     }
 }
 
+/// Deterministically corrupts a message payload
+///
+/// Inverts the middle byte (or appends one to an empty payload), which
+/// breaks both JSON and binary decoding in a reproducible way.
+pub fn corrupt_payload(message: &mut MessageEnvelope) {
+    let mut bytes = message.payload.as_bytes().to_vec();
+    if bytes.is_empty() {
+        bytes.push(0xff);
+    } else {
+        let middle = bytes.len() / 2;
+        bytes[middle] ^= 0xff;
+    }
+    message.payload = MessagePayload::from_bytes(bytes, message.payload.encoding());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -376,6 +555,51 @@ mod tests {
     }
 
     // Helper functions
+    #[test]
+    fn test_fault_injector_duplicate_and_corrupt() {
+        let plan = FaultPlan::new()
+            .with_message_fault(MessageFault::Duplicate { count: 1 })
+            .with_message_fault(MessageFault::CorruptPayload { count: 2 });
+        let mut injector = FaultInjector::new(plan);
+
+        assert!(injector.should_duplicate());
+        assert!(!injector.should_duplicate());
+        assert!(injector.should_corrupt());
+        assert!(injector.should_corrupt());
+        assert!(!injector.should_corrupt());
+
+        let mut message = create_test_message();
+        corrupt_payload(&mut message);
+        assert!(message.payload.deserialize::<String>().is_err());
+    }
+
+    #[test]
+    fn test_fault_injector_partition_heals() {
+        let (a, b, c) = (TaskId::new(), TaskId::new(), TaskId::new());
+        let plan = FaultPlan::new().with_message_fault(MessageFault::Partition {
+            groups: vec![vec![a], vec![b]],
+            heal_after: Some(Duration::from_millis(10)),
+        });
+        let mut injector = FaultInjector::new(plan);
+        injector.set_start_time(Instant::from_nanos(5_000_000));
+
+        assert_eq!(injector.partition_between(a, b), Some(0));
+        assert_eq!(injector.partition_between(a, a), None);
+        assert_eq!(injector.partition_between(a, c), None);
+
+        assert!(injector
+            .heal_partitions(Instant::from_nanos(10_000_000))
+            .is_empty());
+        assert_eq!(
+            injector.heal_partitions(Instant::from_nanos(15_000_000)),
+            vec![0]
+        );
+        assert_eq!(injector.partition_between(a, b), None);
+        assert!(injector
+            .heal_partitions(Instant::from_nanos(20_000_000))
+            .is_empty());
+    }
+
     fn create_test_message() -> MessageEnvelope {
         create_test_message_with_action("test.action")
     }
@@ -23,11 +23,14 @@
 
 pub mod address_space;
 pub mod capability_audit;
+pub mod chaos;
 pub mod executable;
+pub mod fault_audit;
 pub mod fault_injection;
 pub mod message_queue;
 pub mod page_table_bridge;
 pub mod policy_audit;
+pub mod replay;
 pub mod resource_audit;
 pub mod scheduler;
 pub mod smp;
@@ -47,6 +50,7 @@ use identity::{ExecutionId, ExitNotification, ExitReason, IdentityMetadata};
 use ipc::{ChannelId, Compatibility, MessageEnvelope, SchemaMismatchError, VersionPolicy};
 use kernel_api::{
     Duration, Instant, KernelApi, KernelApiV0, KernelError, TaskDescriptor, TaskHandle,
+    WaitOutcome, WaitSet,
 };
 use policy::{PolicyContext, PolicyDecision, PolicyEngine, PolicyEvent};
 use resources::CpuTicks;
@@ -75,6 +79,8 @@ pub struct SimulatedKernel {
     service_policies: HashMap<ServiceId, VersionPolicy>,
     /// Fault injector (optional, for testing)
     fault_injector: Option<FaultInjector>,
+    /// Audit log of applied faults (test-only)
+    fault_audit: fault_audit::FaultAuditLog,
     /// Pending delayed messages
     delayed_messages: Vec<DelayedMessage>,
     /// Capability authority table: tracks which tasks own which capabilities
@@ -108,6 +114,14 @@ pub struct SimulatedKernel {
     channel_capacity: usize,
     /// Syscall gate for user/kernel isolation (Phase 61)
     syscall_gate: syscall_gate::SyscallGate,
+    /// Tasks blocked on channel wait sets, in registration order
+    channel_waiters: Vec<ChannelWaiter>,
+    /// Capabilities attached to undelivered messages: cap_id -> sender
+    capabilities_in_transit: HashMap<u64, TaskId>,
+    /// Active run recorder (see `start_recording`)
+    recorder: Option<replay::Recorder>,
+    /// IDs and fault decisions forced during replay
+    script: Option<replay::Script>,
 }
 
 #[derive(Debug)]
@@ -160,6 +174,14 @@ pub struct CoreServiceHandles {
     pub timer: (ServiceId, ChannelId),
 }
 
+/// A task blocked on a wait set, plus the outcome once it is woken
+#[derive(Debug)]
+struct ChannelWaiter {
+    task_id: TaskId,
+    wait_set: WaitSet,
+    outcome: Option<WaitOutcome>,
+}
+
 #[derive(Debug)]
 struct DelayedMessage {
     channel: ChannelId,
@@ -200,6 +222,7 @@ impl SimulatedKernel {
             exit_notifications: Vec::new(),
             policy_engine: None,
             policy_audit: policy_audit::PolicyAuditLog::new(),
+            fault_audit: fault_audit::FaultAuditLog::new(),
             resource_audit: resource_audit::ResourceAuditLog::new(),
             cancelled_identities: HashMap::new(),
             current_receive_task: None,
@@ -208,6 +231,10 @@ impl SimulatedKernel {
             address_space_manager: address_space::AddressSpaceManager::new(),
             channel_capacity: 64,
             syscall_gate: syscall_gate::SyscallGate::new(),
+            channel_waiters: Vec::new(),
+            capabilities_in_transit: HashMap::new(),
+            recorder: None,
+            script: None,
         }
     }
 
@@ -320,13 +347,38 @@ impl SimulatedKernel {
     /// Call before spawning tasks to ensure new tasks are scheduled
     /// on SMP cores (tasks are not migrated from the single-core scheduler).
     pub fn enable_smp(&mut self, core_count: usize) {
-        let config = smp::SmpConfig {
+        self.enable_smp_with_config(smp::SmpConfig {
             core_count,
             ..Default::default()
-        };
+        });
+    }
+
+    /// Enables SMP runtime with a full configuration.
+    pub fn enable_smp_with_config(&mut self, config: smp::SmpConfig) {
         self.smp = Some(smp::SmpRuntime::new(config));
     }
 
+    /// Restricts a task to the given cores (SMP only).
+    pub fn set_task_affinity(
+        &mut self,
+        task_id: TaskId,
+        affinity: smp::AffinityMask,
+    ) -> Result<(), KernelError> {
+        let runtime = self
+            .smp
+            .as_mut()
+            .ok_or_else(|| KernelError::SpawnFailed("SMP is not enabled".to_string()))?;
+        let timestamp_ticks = runtime
+            .scheduler
+            .task_core(task_id)
+            .map(|core_id| runtime.time.ticks(core_id))
+            .unwrap_or(0);
+        runtime
+            .scheduler
+            .set_affinity(task_id, affinity, timestamp_ticks)
+            .map_err(|err| KernelError::SpawnFailed(format!("{:?}", err)))
+    }
+
     /// Returns the SMP runtime if enabled.
     pub fn smp(&self) -> Option<&smp::SmpRuntime> {
         self.smp.as_ref()
@@ -452,7 +504,9 @@ impl SimulatedKernel {
     ///
     /// This enables fault injection for testing. The fault injector
     /// will be applied to all message operations.
-    pub fn with_fault_injector(mut self, injector: FaultInjector) -> Self {
+    /// Partition heal timers start from the current simulated time.
+    pub fn with_fault_injector(mut self, mut injector: FaultInjector) -> Self {
+        injector.set_start_time(self.current_time);
         self.fault_injector = Some(injector);
         self
     }
@@ -481,6 +535,13 @@ impl SimulatedKernel {
         &self.policy_audit
     }
 
+    /// Returns a reference to the fault audit log
+    ///
+    /// Used in tests to see which injected faults were actually applied.
+    pub fn fault_audit(&self) -> &fault_audit::FaultAuditLog {
+        &self.fault_audit
+    }
+
     /// Returns a reference to the resource audit log
     ///
     /// Phase 12: Used in tests to verify resource consumption and exhaustion.
@@ -495,6 +556,20 @@ impl SimulatedKernel {
         self.scheduler.audit_log()
     }
 
+    /// Overrides the scheduling parameters of a task
+    ///
+    /// Tasks spawned with an identity get parameters from the configured
+    /// `TaskClassTable`; this replaces them.
+    pub fn set_task_sched_params(
+        &mut self,
+        task_id: TaskId,
+        params: scheduler::SchedParams,
+    ) -> Result<(), KernelError> {
+        self.scheduler
+            .set_sched_params(task_id, params)
+            .map_err(|_| KernelError::SpawnFailed(format!("Task {} not scheduled", task_id)))
+    }
+
     /// Returns a reference to the scheduler
     ///
     /// Phase 23: Provides access to scheduler state for testing.
@@ -521,14 +596,18 @@ impl SimulatedKernel {
     /// Phase 12: Workaround for KernelApi not passing TaskId to receive_message.
     /// Call this before receive_message to enable budget enforcement for receives.
     pub fn set_receive_context(&mut self, task_id: TaskId) {
+        let step = self.begin_step(|| replay::KernelInput::SetReceiveContext { task_id });
         self.current_receive_task = Some(task_id);
+        self.end_step(step, |_| replay::StepOutcome::Done);
     }
 
     /// Clears the current receive task context
     ///
     /// Phase 12: Call this after receive_message to clean up context.
     pub fn clear_receive_context(&mut self) {
+        let step = self.begin_step(|| replay::KernelInput::ClearReceiveContext);
         self.current_receive_task = None;
+        self.end_step(step, |_| replay::StepOutcome::Done);
     }
 
     /// Attempts to consume CPU ticks for an execution identity
@@ -774,6 +853,12 @@ impl SimulatedKernel {
 
     /// Advances simulated time
     pub fn advance_time(&mut self, duration: Duration) {
+        let step = self.begin_step(|| replay::KernelInput::AdvanceTime { duration });
+        self.advance_time_inner(duration);
+        self.end_step(step, |_| replay::StepOutcome::Done);
+    }
+
+    fn advance_time_inner(&mut self, duration: Duration) {
         // Calculate how many ticks this duration represents
         let ticks_to_advance = duration.as_nanos() / self.nanos_per_tick;
 
@@ -783,9 +868,15 @@ impl SimulatedKernel {
         // Update current_time from timer
         self.sync_time_from_timer();
 
+        // Heal partitions whose time is up
+        self.heal_partitions();
+
         // Process delayed messages
         self.process_delayed_messages();
 
+        // Time out wait sets whose deadline has passed
+        self.expire_wait_deadlines();
+
         // Phase 23: Notify scheduler of tick advancement
         self.scheduler.on_tick_advanced(ticks_to_advance);
     }
@@ -794,6 +885,8 @@ impl SimulatedKernel {
     fn process_delayed_messages(&mut self) {
         let current_time = self.current_time;
         let mut remaining = Vec::new();
+        let mut delivered = Vec::new();
+        let mut lost = Vec::new();
 
         for delayed in self.delayed_messages.drain(..) {
             if delayed.deliver_at > current_time {
@@ -807,11 +900,156 @@ impl SimulatedKernel {
                     remaining.push(delayed);
                 } else {
                     let _ = ch.queue.push(delayed.message);
+                    delivered.push(delayed.channel);
                 }
+            } else {
+                lost.push(delayed.message);
             }
         }
 
         self.delayed_messages = remaining;
+
+        for message in lost {
+            self.revoke_capabilities_in_transit(&message, "channel closed before delivery");
+        }
+
+        for channel in delivered {
+            self.notify_channel_ready(channel);
+        }
+    }
+
+    /// Blocks a task on a wait set
+    ///
+    /// If a channel in the set already has a message (or the deadline has
+    /// already passed) the outcome is returned immediately and the task is
+    /// not blocked. Otherwise the task is parked in the scheduler, stops
+    /// consuming quanta, and `None` is returned; the kernel wakes it when a
+    /// message is delivered to one of the channels or the deadline passes.
+    /// Collect the outcome with `take_wait_outcome`.
+    pub fn wait_on_channels(
+        &mut self,
+        task_id: TaskId,
+        wait_set: &WaitSet,
+    ) -> Result<Option<WaitOutcome>, KernelError> {
+        self.validate_wait_set(wait_set, Some(task_id))?;
+
+        if let Some(channel) = self.first_ready_channel(wait_set) {
+            return Ok(Some(WaitOutcome::Ready(channel)));
+        }
+        if let Some(deadline) = wait_set.deadline() {
+            if self.current_time >= deadline {
+                return Ok(Some(WaitOutcome::TimedOut));
+            }
+        }
+
+        self.channel_waiters
+            .retain(|waiter| waiter.task_id != task_id);
+        self.channel_waiters.push(ChannelWaiter {
+            task_id,
+            wait_set: wait_set.clone(),
+            outcome: None,
+        });
+
+        let deadline_tick = wait_set
+            .deadline()
+            .map(|deadline| deadline.as_nanos().div_ceil(self.nanos_per_tick));
+        self.scheduler.wait_on_channels(task_id, deadline_tick);
+
+        Ok(None)
+    }
+
+    /// Takes the outcome of a completed wait, removing the waiter
+    ///
+    /// Returns `None` while the task is still blocked (or was never waiting).
+    pub fn take_wait_outcome(&mut self, task_id: TaskId) -> Option<WaitOutcome> {
+        let index = self
+            .channel_waiters
+            .iter()
+            .position(|waiter| waiter.task_id == task_id && waiter.outcome.is_some())?;
+        self.channel_waiters.remove(index).outcome
+    }
+
+    /// Returns true if the task is blocked on a wait set
+    pub fn is_task_waiting(&self, task_id: TaskId) -> bool {
+        self.channel_waiters
+            .iter()
+            .any(|waiter| waiter.task_id == task_id && waiter.outcome.is_none())
+    }
+
+    fn validate_wait_set(
+        &self,
+        wait_set: &WaitSet,
+        task_id: Option<TaskId>,
+    ) -> Result<(), KernelError> {
+        if wait_set.is_empty() && wait_set.deadline().is_none() {
+            return Err(KernelError::ChannelError(
+                "Wait set has no channels and no deadline".to_string(),
+            ));
+        }
+
+        for channel in wait_set.channels() {
+            if !self.channels.contains_key(channel) {
+                return Err(KernelError::ChannelError("Channel not found".to_string()));
+            }
+            if let Some(task_id) = task_id {
+                if let Some(access) = self.channel_access.get(channel) {
+                    if !access.allows_receive(task_id) {
+                        return Err(KernelError::ReceiveFailed(format!(
+                            "Channel receive denied for task {}",
+                            task_id
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn first_ready_channel(&self, wait_set: &WaitSet) -> Option<ChannelId> {
+        wait_set.channels().iter().copied().find(|channel| {
+            self.channels
+                .get(channel)
+                .map(|ch| !ch.queue.is_empty())
+                .unwrap_or(false)
+        })
+    }
+
+    /// Earliest pending delayed delivery to any channel in the wait set
+    fn next_delivery_for(&self, wait_set: &WaitSet) -> Option<Instant> {
+        self.delayed_messages
+            .iter()
+            .filter(|delayed| wait_set.contains(delayed.channel))
+            .map(|delayed| delayed.deliver_at)
+            .min()
+    }
+
+    /// Wakes every task waiting on `channel`
+    fn notify_channel_ready(&mut self, channel: ChannelId) {
+        for waiter in self.channel_waiters.iter_mut() {
+            if waiter.outcome.is_none() && waiter.wait_set.contains(channel) {
+                waiter.outcome = Some(WaitOutcome::Ready(channel));
+                self.scheduler
+                    .wake_waiting(waiter.task_id, scheduler::WakeReason::ChannelReady);
+            }
+        }
+    }
+
+    /// Times out waiters whose deadline has passed
+    fn expire_wait_deadlines(&mut self) {
+        let current_time = self.current_time;
+        for waiter in self.channel_waiters.iter_mut() {
+            let expired = waiter
+                .wait_set
+                .deadline()
+                .map(|deadline| current_time >= deadline)
+                .unwrap_or(false);
+            if waiter.outcome.is_none() && expired {
+                waiter.outcome = Some(WaitOutcome::TimedOut);
+                self.scheduler
+                    .wake_waiting(waiter.task_id, scheduler::WakeReason::DeadlineReached);
+            }
+        }
     }
 
     /// Runs until no more messages are pending
@@ -819,6 +1057,12 @@ impl SimulatedKernel {
     /// This advances time in small increments until all channels are empty
     /// and no delayed messages remain. Useful for test scenarios.
     pub fn run_until_idle(&mut self) {
+        let step = self.begin_step(|| replay::KernelInput::RunUntilIdle);
+        self.run_until_idle_inner();
+        self.end_step(step, |_| replay::StepOutcome::Done);
+    }
+
+    fn run_until_idle_inner(&mut self) {
         const MAX_ITERATIONS: usize = 1000;
         const TIME_STEP: Duration = Duration::from_millis(10);
 
@@ -941,6 +1185,13 @@ impl SimulatedKernel {
     ///
     /// Returns the number of task scheduling rounds executed.
     pub fn run_for_ticks(&mut self, ticks: u64) -> usize {
+        let step = self.begin_step(|| replay::KernelInput::RunForTicks { ticks });
+        let result = self.run_for_ticks_inner(ticks);
+        self.end_step(step, |_| replay::StepOutcome::Ran { count: result });
+        result
+    }
+
+    fn run_for_ticks_inner(&mut self, ticks: u64) -> usize {
         if self.smp.is_some() {
             return self.run_for_ticks_smp(ticks);
         }
@@ -1010,6 +1261,13 @@ impl SimulatedKernel {
     ///
     /// Returns the number of steps actually executed.
     pub fn run_for_steps(&mut self, steps: usize) -> usize {
+        let step = self.begin_step(|| replay::KernelInput::RunForSteps { steps });
+        let result = self.run_for_steps_inner(steps);
+        self.end_step(step, |_| replay::StepOutcome::Ran { count: result });
+        result
+    }
+
+    fn run_for_steps_inner(&mut self, steps: usize) -> usize {
         if self.smp.is_some() {
             return self.run_for_steps_smp(steps);
         }
@@ -1112,6 +1370,15 @@ impl SimulatedKernel {
     /// Creates an exit notification with the specified reason and cleans up
     /// task resources including capabilities.
     pub fn terminate_task_with_reason(&mut self, task_id: TaskId, reason: ExitReason) {
+        let step = self.begin_step(|| replay::KernelInput::TerminateTask {
+            task_id,
+            reason: reason.clone(),
+        });
+        self.terminate_task_inner(task_id, reason);
+        self.end_step(step, |_| replay::StepOutcome::Done);
+    }
+
+    fn terminate_task_inner(&mut self, task_id: TaskId, reason: ExitReason) {
         let scheduler_reason = match &reason {
             ExitReason::Normal => scheduler::ExitReason::Normal,
             ExitReason::Failure { .. } => scheduler::ExitReason::Failed,
@@ -1141,6 +1408,8 @@ impl SimulatedKernel {
 
         // Remove task
         self.tasks.remove(&task_id);
+        self.channel_waiters
+            .retain(|waiter| waiter.task_id != task_id);
 
         // Invalidate all capabilities owned by this task
         self.invalidate_task_capabilities(task_id);
@@ -1160,7 +1429,10 @@ impl SimulatedKernel {
         let cap_ids: Vec<u64> = self
             .capability_table
             .iter()
-            .filter(|(_, meta)| meta.owner == task_id)
+            .filter(|(id, meta)| {
+                // In-transit capabilities belong to the message, not the sender
+                meta.owner == task_id && !self.capabilities_in_transit.contains_key(id)
+            })
             .map(|(id, _)| *id)
             .collect();
 
@@ -1447,6 +1719,163 @@ impl SimulatedKernel {
         Ok(())
     }
 
+    /// Validates the capabilities attached to an outgoing message
+    ///
+    /// The message must name its source task, and that task must own every
+    /// attached capability. Returns the sending task.
+    fn validate_attached_capabilities(
+        &self,
+        channel: ChannelId,
+        message: &MessageEnvelope,
+    ) -> Result<TaskId, KernelError> {
+        let sender = message.source.ok_or_else(|| {
+            KernelError::SendFailed("Capability transfer requires a message source".to_string())
+        })?;
+
+        if !self.channels.contains_key(&channel) {
+            return Err(KernelError::ChannelError("Channel not found".to_string()));
+        }
+
+        let mut seen = HashSet::new();
+        for cap in &message.capabilities {
+            if !seen.insert(cap.id()) {
+                return Err(KernelError::InvalidCapability(format!(
+                    "Capability {} attached more than once",
+                    cap.id()
+                )));
+            }
+            self.validate_capability(cap.id(), sender)
+                .map_err(|reason| {
+                    KernelError::InvalidCapability(format!("Cannot transfer: {:?}", reason))
+                })?;
+        }
+
+        Ok(sender)
+    }
+
+    /// Takes attached capabilities into kernel custody
+    ///
+    /// From this point the sender can no longer use them. They are handed to
+    /// the receiver when the message is received, or revoked if the message
+    /// never arrives.
+    fn take_capabilities_into_transit(&mut self, sender: TaskId, message: &MessageEnvelope) {
+        for cap in &message.capabilities {
+            let cap_id = cap.id();
+            if let Some(meta) = self.capability_table.get_mut(&cap_id) {
+                meta.status = CapabilityStatus::Transferred;
+                self.capabilities_in_transit.insert(cap_id, sender);
+                self.capability_audit.record_event(
+                    self.current_time,
+                    CapabilityEvent::InTransit {
+                        cap_id,
+                        from_task: sender,
+                        cap_type: meta.cap_type.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Hands in-transit capabilities to the receiving task
+    ///
+    /// If no live receiving task is known the capabilities are revoked
+    /// rather than left orphaned.
+    fn deliver_capabilities(&mut self, message: &MessageEnvelope, receiver: Option<TaskId>) {
+        let Some(receiver) = receiver.filter(|task_id| self.tasks.contains_key(task_id)) else {
+            self.revoke_capabilities_in_transit(message, "no receiving task");
+            return;
+        };
+
+        let receiver_identity = self
+            .task_to_identity
+            .get(&receiver)
+            .and_then(|exec_id| self.identity_table.get(exec_id))
+            .cloned();
+
+        for cap in &message.capabilities {
+            let cap_id = cap.id();
+            let Some(sender) = self.capabilities_in_transit.remove(&cap_id) else {
+                continue;
+            };
+            let Some(meta) = self.capability_table.get_mut(&cap_id) else {
+                continue;
+            };
+            let cap_type = meta.cap_type.clone();
+            meta.owner = receiver;
+            meta.status = CapabilityStatus::Valid;
+
+            let sender_identity = self
+                .task_to_identity
+                .get(&sender)
+                .and_then(|exec_id| self.identity_table.get(exec_id));
+            if let (Some(from_id), Some(to_id)) = (sender_identity, receiver_identity.as_ref()) {
+                if !from_id.same_domain(to_id) {
+                    let from_domain = from_id.trust_domain.name().to_string();
+                    self.capability_audit.record_event(
+                        self.current_time,
+                        CapabilityEvent::CrossDomainDelegation {
+                            cap_id,
+                            from_task: sender,
+                            from_domain,
+                            to_task: receiver,
+                            to_domain: to_id.trust_domain.name().to_string(),
+                        },
+                    );
+                }
+            }
+
+            self.capability_audit.record_event(
+                self.current_time,
+                CapabilityEvent::Delegated {
+                    cap_id,
+                    from_task: sender,
+                    to_task: receiver,
+                    cap_type,
+                },
+            );
+        }
+    }
+
+    /// Revokes capabilities attached to a message that will not be delivered
+    fn revoke_capabilities_in_transit(&mut self, message: &MessageEnvelope, reason: &str) {
+        for cap in &message.capabilities {
+            let cap_id = cap.id();
+            let Some(sender) = self.capabilities_in_transit.remove(&cap_id) else {
+                continue;
+            };
+            if let Some(meta) = self.capability_table.get_mut(&cap_id) {
+                meta.status = CapabilityStatus::Invalid;
+                meta.revoked = true;
+                self.capability_audit.record_event(
+                    self.current_time,
+                    CapabilityEvent::Revoked {
+                        cap_id,
+                        owner: sender,
+                        cap_type: meta.cap_type.clone(),
+                        reason: reason.to_string(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Returns the task that receives from a channel, if it is unambiguous
+    fn channel_receiver(&self, channel: ChannelId) -> Option<TaskId> {
+        self.current_receive_task.or_else(|| {
+            let access = self.channel_access.get(&channel)?;
+            if access.receivers.len() == 1 {
+                access.receivers.iter().next().copied()
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns the number of capabilities held in transit by the kernel
+    pub fn capabilities_in_transit(&self) -> usize {
+        self.capabilities_in_transit.len()
+    }
+
     /// Drops a capability (explicitly releases it)
     pub fn drop_capability(&mut self, cap_id: u64, owner: TaskId) -> Result<(), KernelError> {
         // Validate ownership
@@ -1762,7 +2191,45 @@ impl SimulatedKernel {
         parent_id: Option<ExecutionId>,
         creator_id: Option<ExecutionId>,
     ) -> Result<(TaskHandle, ExecutionId), KernelError> {
-        let task_id = TaskId::new();
+        let step = self.begin_step(|| replay::KernelInput::SpawnTaskWithIdentity {
+            descriptor: descriptor.clone(),
+            kind,
+            trust_domain: trust_domain.clone(),
+            parent_id,
+            creator_id,
+        });
+        let result = self.spawn_task_with_identity_inner(
+            descriptor,
+            kind,
+            trust_domain,
+            parent_id,
+            creator_id,
+        );
+        self.end_step(step, |_| {
+            replay::StepOutcome::from_result(&result, |(handle, execution_id)| {
+                replay::StepOutcome::Spawned {
+                    task_id: handle.task_id,
+                    execution_id: *execution_id,
+                }
+            })
+        });
+        result
+    }
+
+    fn spawn_task_with_identity_inner(
+        &mut self,
+        descriptor: TaskDescriptor,
+        kind: identity::IdentityKind,
+        trust_domain: identity::TrustDomain,
+        parent_id: Option<ExecutionId>,
+        creator_id: Option<ExecutionId>,
+    ) -> Result<(TaskHandle, ExecutionId), KernelError> {
+        let task_id = self.next_task_id();
+        let sched_params = self
+            .scheduler
+            .config
+            .task_classes
+            .params_for(kind, &trust_domain);
 
         let mut metadata = identity::IdentityMetadata::new(
             kind,
@@ -1771,6 +2238,9 @@ impl SimulatedKernel {
             self.current_time.as_nanos(),
         )
         .with_task_id(task_id);
+        if let Some(execution_id) = self.scripted_execution_id() {
+            metadata.execution_id = execution_id;
+        }
 
         if let Some(parent) = parent_id {
             metadata = metadata.with_parent(parent);
@@ -1832,6 +2302,11 @@ impl SimulatedKernel {
             smp.scheduler.enqueue(task_id);
         } else {
             self.scheduler.enqueue(task_id);
+            if sched_params != scheduler::SchedParams::default() {
+                self.scheduler
+                    .set_sched_params(task_id, sched_params)
+                    .map_err(|err| KernelError::SpawnFailed(format!("{:?}", err)))?;
+            }
         }
 
         // Phase 24: Create address space for this task
@@ -1979,76 +2454,289 @@ impl Default for SimulatedKernel {
     }
 }
 
-impl KernelApi for SimulatedKernel {
-    fn spawn_task(&mut self, descriptor: TaskDescriptor) -> Result<TaskHandle, KernelError> {
-        let task_id = TaskId::new();
-
-        // Create execution identity for this task
-        // Defaults: IdentityKind::Component, TrustDomain::user()
-        // For full control over identity, use spawn_task_with_identity()
-        let metadata = identity::IdentityMetadata::new(
-            identity::IdentityKind::Component,
-            identity::TrustDomain::user(),
-            descriptor.name.clone(),
-            self.current_time.as_nanos(),
-        )
-        .with_task_id(task_id);
-
-        let execution_id = metadata.execution_id;
-
-        // Store identity in kernel tables
-        self.identity_table.insert(execution_id, metadata);
-        self.task_to_identity.insert(task_id, execution_id);
-
-        let task_info = TaskInfo {
-            descriptor,
-            execution_id,
-        };
-        self.tasks.insert(task_id, task_info);
+// ============================================================================
+// Record-and-replay
+// ============================================================================
 
-        // Phase 23: Enqueue task in scheduler
-        if let Some(smp) = self.smp.as_mut() {
-            smp.scheduler.enqueue(task_id);
-        } else {
-            self.scheduler.enqueue(task_id);
-        }
+impl SimulatedKernel {
+    /// Starts recording every input to the kernel
+    ///
+    /// Any previous unfinished recording is discarded.
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(replay::Recorder::new(self));
+    }
 
-        // Phase 24: Create address space for this task
-        // Result intentionally discarded (see spawn_task_with_identity for details)
-        let _ = self
-            .address_space_manager
-            .create_address_space(execution_id, self.current_time.as_nanos());
+    /// Stops recording and returns what was captured
+    pub fn stop_recording(&mut self) -> Option<replay::KernelRecording> {
+        self.recorder.take().map(replay::Recorder::into_recording)
+    }
 
-        Ok(TaskHandle::new(task_id))
+    /// Returns true while a recording is in progress
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
     }
 
-    fn create_channel(&mut self) -> Result<ChannelId, KernelError> {
-        let channel_id = ChannelId::new();
-        let channel = Channel {
-            queue: message_queue::MessageQueue::with_capacity(self.channel_capacity),
-        };
-        self.channels.insert(channel_id, channel);
-        Ok(channel_id)
+    /// Returns a stable digest of the observable kernel state
+    ///
+    /// Two kernels driven by the same inputs have equal digests.
+    pub fn state_digest(&self) -> u64 {
+        replay::state_digest(self)
     }
 
-    fn send_message(
+    /// Delivers a device-originated input event to a channel
+    ///
+    /// Behaves like `send_message`, but is recorded as an input injection
+    /// so replays can tell device input apart from task traffic.
+    pub fn inject_input(
         &mut self,
         channel: ChannelId,
         message: MessageEnvelope,
     ) -> Result<(), KernelError> {
-        if let Some(source_task) = message.source {
-            if let Some(access) = self.channel_access.get(&channel) {
-                if !access.allows_send(source_task) {
-                    return Err(KernelError::SendFailed(format!(
-                        "Channel send denied for task {}",
-                        source_task
-                    )));
-                }
+        let step = self.begin_step(|| replay::KernelInput::InjectInput {
+            channel,
+            message: message.clone(),
+        });
+        let result = self.send_message_inner(channel, message);
+        self.end_step(step, |_| {
+            replay::StepOutcome::from_result(&result, |_| replay::StepOutcome::Done)
+        });
+        result
+    }
+
+    /// Starts a recorded step; returns its input only for top-level calls
+    ///
+    /// Operations that call other recorded operations (e.g. `run_for_ticks`
+    /// advancing time) record a single step for the outer call.
+    fn begin_step(
+        &mut self,
+        input: impl FnOnce() -> replay::KernelInput,
+    ) -> Option<replay::KernelInput> {
+        let top_level = self
+            .recorder
+            .as_mut()
+            .is_some_and(|recorder| recorder.enter());
+        top_level.then(input)
+    }
+
+    /// Finishes a step started with `begin_step`
+    fn end_step(
+        &mut self,
+        step: Option<replay::KernelInput>,
+        outcome: impl FnOnce(&Self) -> replay::StepOutcome,
+    ) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.exit();
+        }
+        if let Some(input) = step {
+            let outcome = outcome(self);
+            let digest = self.state_digest();
+            if let Some(recorder) = self.recorder.as_mut() {
+                recorder.push_step(input, outcome, digest);
             }
         }
+    }
 
-        if let Some(policy) = self.service_policies.get(&message.destination) {
-            match policy.check_compatibility(&message.schema_version) {
+    fn note_fault(&mut self, decision: replay::FaultDecision) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.note_fault(decision);
+        }
+    }
+
+    /// Decides the fault for a send, from the replay script or the injector
+    fn send_fault(
+        &mut self,
+        channel: ChannelId,
+        message: &MessageEnvelope,
+    ) -> replay::FaultDecision {
+        if let Some(script) = self.script.as_mut() {
+            return script
+                .faults
+                .pop_front()
+                .unwrap_or(replay::FaultDecision::Deliver {
+                    swaps: Vec::new(),
+                    duplicated: false,
+                    corrupted: false,
+                });
+        }
+        self.heal_partitions();
+        let partition = self.partition_for(channel, message);
+        let Some(injector) = self.fault_injector.as_mut() else {
+            return replay::FaultDecision::Deliver {
+                swaps: Vec::new(),
+                duplicated: false,
+                corrupted: false,
+            };
+        };
+
+        if injector.should_crash_on_send() {
+            replay::FaultDecision::CrashOnSend
+        } else if let Some(partition) = partition {
+            replay::FaultDecision::Partitioned { partition }
+        } else if injector.should_drop_message(channel, message) {
+            replay::FaultDecision::Drop
+        } else if let Some(duration) = injector.get_message_delay() {
+            replay::FaultDecision::Delay { duration }
+        } else {
+            replay::FaultDecision::Deliver {
+                swaps: Vec::new(),
+                duplicated: injector.should_duplicate(),
+                corrupted: injector.should_corrupt(),
+            }
+        }
+    }
+
+    /// Returns the partition cutting a message's sender off from every
+    /// receiver of the channel, if any
+    fn partition_for(&self, channel: ChannelId, message: &MessageEnvelope) -> Option<usize> {
+        let injector = self.fault_injector.as_ref()?;
+        let from = message.source?;
+        let access = self.channel_access.get(&channel)?;
+        if access.receivers.is_empty() {
+            return None;
+        }
+        access
+            .receivers
+            .iter()
+            .map(|receiver| injector.partition_between(from, *receiver))
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .min()
+    }
+
+    /// Heals expired partitions and records them in the fault audit log
+    fn heal_partitions(&mut self) {
+        let Some(injector) = self.fault_injector.as_mut() else {
+            return;
+        };
+        for partition in injector.heal_partitions(self.current_time) {
+            self.fault_audit.record(
+                self.current_time,
+                fault_audit::FaultEvent::PartitionHealed { partition },
+            );
+        }
+    }
+
+    /// Decides the fault for a receive, from the replay script or the injector
+    fn receive_fault(&mut self) -> replay::FaultDecision {
+        if let Some(script) = self.script.as_mut() {
+            return script
+                .faults
+                .pop_front()
+                .unwrap_or(replay::FaultDecision::Receive);
+        }
+        let crash = self
+            .fault_injector
+            .as_mut()
+            .is_some_and(|injector| injector.should_crash_on_recv());
+        if crash {
+            replay::FaultDecision::CrashOnRecv
+        } else {
+            replay::FaultDecision::Receive
+        }
+    }
+
+    fn next_task_id(&mut self) -> TaskId {
+        self.script
+            .as_mut()
+            .and_then(|script| script.task_ids.pop_front())
+            .unwrap_or_default()
+    }
+
+    fn next_channel_id(&mut self) -> ChannelId {
+        self.script
+            .as_mut()
+            .and_then(|script| script.channel_ids.pop_front())
+            .unwrap_or_default()
+    }
+
+    fn scripted_execution_id(&mut self) -> Option<ExecutionId> {
+        self.script
+            .as_mut()
+            .and_then(|script| script.execution_ids.pop_front())
+    }
+
+    fn spawned_outcome(&self, task_id: TaskId) -> replay::StepOutcome {
+        match self.get_task_identity(task_id) {
+            Some(execution_id) => replay::StepOutcome::Spawned {
+                task_id,
+                execution_id,
+            },
+            None => replay::StepOutcome::Done,
+        }
+    }
+
+    fn spawn_task_inner(&mut self, descriptor: TaskDescriptor) -> Result<TaskHandle, KernelError> {
+        let task_id = self.next_task_id();
+
+        // Create execution identity for this task
+        // Defaults: IdentityKind::Component, TrustDomain::user()
+        // For full control over identity, use spawn_task_with_identity()
+        let mut metadata = identity::IdentityMetadata::new(
+            identity::IdentityKind::Component,
+            identity::TrustDomain::user(),
+            descriptor.name.clone(),
+            self.current_time.as_nanos(),
+        )
+        .with_task_id(task_id);
+        if let Some(execution_id) = self.scripted_execution_id() {
+            metadata.execution_id = execution_id;
+        }
+
+        let execution_id = metadata.execution_id;
+
+        // Store identity in kernel tables
+        self.identity_table.insert(execution_id, metadata);
+        self.task_to_identity.insert(task_id, execution_id);
+
+        let task_info = TaskInfo {
+            descriptor,
+            execution_id,
+        };
+        self.tasks.insert(task_id, task_info);
+
+        // Phase 23: Enqueue task in scheduler
+        if let Some(smp) = self.smp.as_mut() {
+            smp.scheduler.enqueue(task_id);
+        } else {
+            self.scheduler.enqueue(task_id);
+        }
+
+        // Phase 24: Create address space for this task
+        // Result intentionally discarded (see spawn_task_with_identity for details)
+        let _ = self
+            .address_space_manager
+            .create_address_space(execution_id, self.current_time.as_nanos());
+
+        Ok(TaskHandle::new(task_id))
+    }
+
+    fn create_channel_inner(&mut self) -> Result<ChannelId, KernelError> {
+        let channel_id = self.next_channel_id();
+        let channel = Channel {
+            queue: message_queue::MessageQueue::with_capacity(self.channel_capacity),
+        };
+        self.channels.insert(channel_id, channel);
+        Ok(channel_id)
+    }
+
+    fn send_message_inner(
+        &mut self,
+        channel: ChannelId,
+        message: MessageEnvelope,
+    ) -> Result<(), KernelError> {
+        if let Some(source_task) = message.source {
+            if let Some(access) = self.channel_access.get(&channel) {
+                if !access.allows_send(source_task) {
+                    return Err(KernelError::SendFailed(format!(
+                        "Channel send denied for task {}",
+                        source_task
+                    )));
+                }
+            }
+        }
+
+        if let Some(policy) = self.service_policies.get(&message.destination) {
+            match policy.check_compatibility(&message.schema_version) {
                 Compatibility::Compatible => {}
                 Compatibility::UpgradeRequired => {
                     let error = SchemaMismatchError::upgrade_required(
@@ -2069,27 +2757,74 @@ impl KernelApi for SimulatedKernel {
             }
         }
 
+        let capability_sender = if message.carries_capabilities() {
+            Some(self.validate_attached_capabilities(channel, &message)?)
+        } else {
+            None
+        };
+
         // Phase 12: Try to enforce message budget if source task is known
         if let Some(source_task) = message.source {
             self.try_consume_message(source_task, resource_audit::MessageOperation::Send)?;
         }
         // else: No source - backward compat, skip enforcement
 
+        let fault = self.send_fault(channel, &message);
+        if !matches!(fault, replay::FaultDecision::Deliver { .. }) {
+            self.note_fault(fault.clone());
+        }
+
         // Check for crash-on-send fault
-        if let Some(ref mut injector) = self.fault_injector {
-            if injector.should_crash_on_send() {
-                return Err(KernelError::SendFailed("Task crashed on send".to_string()));
-            }
+        if fault == replay::FaultDecision::CrashOnSend {
+            self.fault_audit.record(
+                self.current_time,
+                fault_audit::FaultEvent::CrashOnSend { channel },
+            );
+            return Err(KernelError::SendFailed("Task crashed on send".to_string()));
+        }
 
-            // Check if message should be dropped
-            if injector.should_drop_message(channel, &message) {
+        // Attached capabilities leave the sender atomically with the message
+        if let Some(sender) = capability_sender {
+            self.take_capabilities_into_transit(sender, &message);
+        }
+
+        let now = self.current_time;
+        let (swaps, duplicated, corrupted) = match fault {
+            replay::FaultDecision::Drop => {
                 // Message dropped by fault injector
+                self.fault_audit.record(
+                    now,
+                    fault_audit::FaultEvent::MessageDropped {
+                        channel,
+                        message_id: message.id,
+                    },
+                );
+                self.revoke_capabilities_in_transit(&message, "message dropped by fault injector");
                 return Ok(());
             }
-
-            // Check for delay
-            if let Some(delay) = injector.get_message_delay() {
-                let deliver_at = self.current_time + delay;
+            replay::FaultDecision::Partitioned { partition } => {
+                self.fault_audit.record(
+                    now,
+                    fault_audit::FaultEvent::PartitionDropped {
+                        channel,
+                        message_id: message.id,
+                        from: message.source.unwrap_or_default(),
+                        partition,
+                    },
+                );
+                self.revoke_capabilities_in_transit(&message, "message dropped by partition");
+                return Ok(());
+            }
+            replay::FaultDecision::Delay { duration } => {
+                self.fault_audit.record(
+                    now,
+                    fault_audit::FaultEvent::MessageDelayed {
+                        channel,
+                        message_id: message.id,
+                        duration,
+                    },
+                );
+                let deliver_at = self.current_time + duration;
                 self.delayed_messages.push(DelayedMessage {
                     channel,
                     message,
@@ -2097,26 +2832,90 @@ impl KernelApi for SimulatedKernel {
                 });
                 return Ok(());
             }
+            replay::FaultDecision::Deliver {
+                swaps,
+                duplicated,
+                corrupted,
+            } => (swaps, duplicated, corrupted),
+            _ => (Vec::new(), false, false),
+        };
+
+        let mut message = message;
+        let message_id = message.id;
+        if corrupted {
+            fault_injection::corrupt_payload(&mut message);
+            self.fault_audit.record(
+                now,
+                fault_audit::FaultEvent::PayloadCorrupted {
+                    channel,
+                    message_id,
+                },
+            );
         }
+        let duplicate = duplicated.then(|| message.clone());
 
         let channel_obj = self
             .channels
             .get_mut(&channel)
             .ok_or_else(|| KernelError::ChannelError("Channel not found".to_string()))?;
+        if channel_obj.queue.remaining_capacity() == 0 {
+            self.revoke_capabilities_in_transit(&message, "channel queue full");
+            return Err(KernelError::SendFailed("Channel queue full".to_string()));
+        }
         channel_obj
             .queue
             .push(message)
             .map_err(|_| KernelError::SendFailed("Channel queue full".to_string()))?;
 
+        // A duplicate only lands if the queue has room for it
+        if let Some(duplicate) = duplicate {
+            if channel_obj.queue.push(duplicate).is_ok() {
+                self.fault_audit.record(
+                    now,
+                    fault_audit::FaultEvent::MessageDuplicated {
+                        channel,
+                        message_id,
+                    },
+                );
+            }
+        }
+
         // Apply reordering faults if present
-        if let Some(ref injector) = self.fault_injector {
-            injector.apply_reordering(channel_obj.queue.messages_mut());
+        let swaps = if self.script.is_some() {
+            swaps
+        } else {
+            self.fault_injector
+                .as_ref()
+                .map(|injector| injector.reorder_swaps(channel_obj.queue.len()))
+                .unwrap_or_default()
+        };
+        let messages = channel_obj.queue.messages_mut();
+        for (index, other) in &swaps {
+            if *index < messages.len() && *other < messages.len() {
+                messages.swap(*index, *other);
+            }
         }
+        if !swaps.is_empty() {
+            self.fault_audit.record(
+                now,
+                fault_audit::FaultEvent::MessagesReordered {
+                    channel,
+                    swaps: swaps.clone(),
+                },
+            );
+        }
+        self.note_fault(replay::FaultDecision::Deliver {
+            swaps,
+            duplicated,
+            corrupted,
+        });
+
+        self.notify_channel_ready(channel);
 
         Ok(())
     }
 
-    fn receive_message(
+    fn receive_message_inner(
         &mut self,
         channel: ChannelId,
         _timeout: Option<Duration>,
@@ -2139,12 +2938,16 @@ impl KernelApi for SimulatedKernel {
         // else: No context - backward compat, skip enforcement
 
         // Check for crash-on-recv fault
-        if let Some(ref mut injector) = self.fault_injector {
-            if injector.should_crash_on_recv() {
-                return Err(KernelError::ReceiveFailed(
-                    "Task crashed on recv".to_string(),
-                ));
-            }
+        let fault = self.receive_fault();
+        self.note_fault(fault.clone());
+        if fault == replay::FaultDecision::CrashOnRecv {
+            self.fault_audit.record(
+                self.current_time,
+                fault_audit::FaultEvent::CrashOnRecv { channel },
+            );
+            return Err(KernelError::ReceiveFailed(
+                "Task crashed on recv".to_string(),
+            ));
         }
 
         let channel_obj = self
@@ -2154,6 +2957,11 @@ impl KernelApi for SimulatedKernel {
 
         let message = channel_obj.queue.pop().ok_or(KernelError::Timeout)?;
 
+        if message.carries_capabilities() {
+            let receiver = self.channel_receiver(channel);
+            self.deliver_capabilities(&message, receiver);
+        }
+
         // Record message processed for fault injection tracking
         if let Some(ref mut injector) = self.fault_injector {
             injector.record_message_processed();
@@ -2162,11 +2970,58 @@ impl KernelApi for SimulatedKernel {
         Ok(message)
     }
 
-    fn now(&self) -> Instant {
-        self.current_time
+    fn select_inner(&mut self, wait_set: &WaitSet) -> Result<WaitOutcome, KernelError> {
+        let task_id = self
+            .current_receive_task
+            .or_else(|| self.scheduler.current_task());
+        self.validate_wait_set(wait_set, task_id)?;
+
+        if let Some(channel) = self.first_ready_channel(wait_set) {
+            return Ok(WaitOutcome::Ready(channel));
+        }
+
+        // Nothing else runs during a synchronous call, so without a deadline
+        // or an in-flight delayed message this would block forever. Report it
+        // the same way receive_message reports an empty queue.
+        if wait_set.deadline().is_none() && self.next_delivery_for(wait_set).is_none() {
+            return Err(KernelError::Timeout);
+        }
+
+        if let Some(task_id) = task_id {
+            if let Some(outcome) = self.wait_on_channels(task_id, wait_set)? {
+                return Ok(outcome);
+            }
+        }
+
+        let outcome = loop {
+            if let Some(channel) = self.first_ready_channel(wait_set) {
+                break WaitOutcome::Ready(channel);
+            }
+            let target = match (self.next_delivery_for(wait_set), wait_set.deadline()) {
+                (Some(delivery), Some(deadline)) => delivery.min(deadline),
+                (Some(delivery), None) => delivery,
+                (None, Some(deadline)) => deadline,
+                (None, None) => break WaitOutcome::TimedOut,
+            };
+            if self.current_time >= target && wait_set.deadline() == Some(target) {
+                break WaitOutcome::TimedOut;
+            }
+
+            // Round up to whole ticks so progress is always made
+            let remaining = target.duration_since(self.current_time).as_nanos();
+            let ticks = remaining.div_ceil(self.nanos_per_tick).max(1);
+            self.advance_time(Duration::from_nanos(ticks * self.nanos_per_tick));
+        };
+
+        if let Some(task_id) = task_id {
+            self.channel_waiters
+                .retain(|waiter| waiter.task_id != task_id);
+        }
+
+        Ok(outcome)
     }
 
-    fn sleep(&mut self, duration: Duration) -> Result<(), KernelError> {
+    fn sleep_inner(&mut self, duration: Duration) -> Result<(), KernelError> {
         // Calculate wake tick based on duration
         let ticks_to_sleep = duration.as_nanos() / self.nanos_per_tick;
         let wake_tick = self.timer.poll_ticks() + ticks_to_sleep;
@@ -2181,7 +3036,11 @@ impl KernelApi for SimulatedKernel {
         Ok(())
     }
 
-    fn grant_capability(&mut self, task: TaskId, capability: Cap<()>) -> Result<(), KernelError> {
+    fn grant_capability_inner(
+        &mut self,
+        task: TaskId,
+        capability: Cap<()>,
+    ) -> Result<(), KernelError> {
         // Verify task exists
         if !self.tasks.contains_key(&task) {
             return Err(KernelError::SendFailed("Task not found".to_string()));
@@ -2194,7 +3053,7 @@ impl KernelApi for SimulatedKernel {
         Ok(())
     }
 
-    fn register_service(
+    fn register_service_inner(
         &mut self,
         service_id: ServiceId,
         channel: ChannelId,
@@ -2207,6 +3066,117 @@ impl KernelApi for SimulatedKernel {
         self.services.insert(service_id, channel);
         Ok(())
     }
+}
+
+impl KernelApi for SimulatedKernel {
+    fn spawn_task(&mut self, descriptor: TaskDescriptor) -> Result<TaskHandle, KernelError> {
+        let step = self.begin_step(|| replay::KernelInput::SpawnTask {
+            descriptor: descriptor.clone(),
+        });
+        let result = self.spawn_task_inner(descriptor);
+        self.end_step(step, |kernel| {
+            replay::StepOutcome::from_result(&result, |handle| {
+                kernel.spawned_outcome(handle.task_id)
+            })
+        });
+        result
+    }
+
+    fn create_channel(&mut self) -> Result<ChannelId, KernelError> {
+        let step = self.begin_step(|| replay::KernelInput::CreateChannel);
+        let result = self.create_channel_inner();
+        self.end_step(step, |_| {
+            replay::StepOutcome::from_result(&result, |channel| {
+                replay::StepOutcome::ChannelCreated { channel: *channel }
+            })
+        });
+        result
+    }
+
+    fn send_message(
+        &mut self,
+        channel: ChannelId,
+        message: MessageEnvelope,
+    ) -> Result<(), KernelError> {
+        let step = self.begin_step(|| replay::KernelInput::SendMessage {
+            channel,
+            message: message.clone(),
+        });
+        let result = self.send_message_inner(channel, message);
+        self.end_step(step, |_| {
+            replay::StepOutcome::from_result(&result, |_| replay::StepOutcome::Done)
+        });
+        result
+    }
+
+    fn receive_message(
+        &mut self,
+        channel: ChannelId,
+        timeout: Option<Duration>,
+    ) -> Result<MessageEnvelope, KernelError> {
+        let step = self.begin_step(|| replay::KernelInput::ReceiveMessage { channel, timeout });
+        let result = self.receive_message_inner(channel, timeout);
+        self.end_step(step, |_| {
+            replay::StepOutcome::from_result(&result, |message| replay::StepOutcome::Received {
+                message_id: message.id,
+            })
+        });
+        result
+    }
+
+    fn select(&mut self, wait_set: &WaitSet) -> Result<WaitOutcome, KernelError> {
+        let step = self.begin_step(|| replay::KernelInput::Select {
+            wait_set: wait_set.clone(),
+        });
+        let result = self.select_inner(wait_set);
+        self.end_step(step, |_| {
+            replay::StepOutcome::from_result(&result, |outcome| replay::StepOutcome::Selected {
+                outcome: *outcome,
+            })
+        });
+        result
+    }
+
+    fn now(&self) -> Instant {
+        self.current_time
+    }
+
+    fn sleep(&mut self, duration: Duration) -> Result<(), KernelError> {
+        let step = self.begin_step(|| replay::KernelInput::Sleep { duration });
+        let result = self.sleep_inner(duration);
+        self.end_step(step, |_| {
+            replay::StepOutcome::from_result(&result, |_| replay::StepOutcome::Done)
+        });
+        result
+    }
+
+    fn grant_capability(&mut self, task: TaskId, capability: Cap<()>) -> Result<(), KernelError> {
+        let step = self.begin_step(|| replay::KernelInput::GrantCapability {
+            task_id: task,
+            capability,
+        });
+        let result = self.grant_capability_inner(task, capability);
+        self.end_step(step, |_| {
+            replay::StepOutcome::from_result(&result, |_| replay::StepOutcome::Done)
+        });
+        result
+    }
+
+    fn register_service(
+        &mut self,
+        service_id: ServiceId,
+        channel: ChannelId,
+    ) -> Result<(), KernelError> {
+        let step = self.begin_step(|| replay::KernelInput::RegisterService {
+            service_id,
+            channel,
+        });
+        let result = self.register_service_inner(service_id, channel);
+        self.end_step(step, |_| {
+            replay::StepOutcome::from_result(&result, |_| replay::StepOutcome::Done)
+        });
+        result
+    }
 
     fn lookup_service(&self, service_id: ServiceId) -> Result<ChannelId, KernelError> {
         self.services
@@ -2760,6 +3730,121 @@ mod tests {
         assert!(result.is_err());
     }
 
+    fn spawn_transfer_pair(kernel: &mut SimulatedKernel) -> (TaskId, TaskId, ChannelId) {
+        let sender = kernel
+            .spawn_task(TaskDescriptor::new("sender".to_string()))
+            .unwrap()
+            .task_id;
+        let receiver = kernel
+            .spawn_task(TaskDescriptor::new("receiver".to_string()))
+            .unwrap()
+            .task_id;
+        let channel = KernelApi::create_channel(kernel).unwrap();
+        kernel
+            .grant_channel_access(channel, sender, ChannelAccessMode::Send)
+            .unwrap();
+        kernel
+            .grant_channel_access(channel, receiver, ChannelAccessMode::Receive)
+            .unwrap();
+        (sender, receiver, channel)
+    }
+
+    fn transfer_message(sender: TaskId, cap_id: u64) -> MessageEnvelope {
+        MessageEnvelope::new(
+            ServiceId::new(),
+            "transfer".to_string(),
+            ipc::SchemaVersion::new(1, 0),
+            ipc::MessagePayload::new(&"transfer").unwrap(),
+        )
+        .with_source(sender)
+        .with_capability(Cap::new(cap_id))
+    }
+
+    #[test]
+    fn test_message_moves_capability_to_receiver() {
+        let mut kernel = SimulatedKernel::new();
+        let (sender, receiver, channel) = spawn_transfer_pair(&mut kernel);
+        kernel.grant_capability(sender, Cap::<()>::new(42)).unwrap();
+
+        kernel
+            .send_message(channel, transfer_message(sender, 42))
+            .unwrap();
+
+        // In transit: nobody can use it
+        assert!(!kernel.is_capability_valid(42, sender));
+        assert!(!kernel.is_capability_valid(42, receiver));
+        assert_eq!(kernel.capabilities_in_transit(), 1);
+
+        let message = kernel.receive_message(channel, None).unwrap();
+        assert_eq!(message.capabilities.len(), 1);
+        assert!(kernel.is_capability_valid(42, receiver));
+        assert!(!kernel.is_capability_valid(42, sender));
+        assert_eq!(kernel.capabilities_in_transit(), 0);
+
+        let audit = kernel.audit_log();
+        assert!(audit.has_event(|e| matches!(e, CapabilityEvent::InTransit { cap_id: 42, .. })));
+        assert!(audit.has_event(|e| matches!(
+            e,
+            CapabilityEvent::Delegated { cap_id: 42, from_task, to_task, .. }
+                if *from_task == sender && *to_task == receiver
+        )));
+    }
+
+    #[test]
+    fn test_message_with_unowned_capability_rejected() {
+        let mut kernel = SimulatedKernel::new();
+        let (sender, receiver, channel) = spawn_transfer_pair(&mut kernel);
+        kernel
+            .grant_capability(receiver, Cap::<()>::new(7))
+            .unwrap();
+
+        let result = kernel.send_message(channel, transfer_message(sender, 7));
+        assert!(matches!(result, Err(KernelError::InvalidCapability(_))));
+        assert!(kernel.is_capability_valid(7, receiver));
+        assert_eq!(kernel.pending_message_count(), 0);
+    }
+
+    #[test]
+    fn test_dropped_message_revokes_capability() {
+        let plan = fault_injection::FaultPlan::new()
+            .with_message_fault(fault_injection::MessageFault::DropNext { count: 1 });
+        let mut kernel = SimulatedKernel::new().with_fault_plan(plan);
+        let (sender, receiver, channel) = spawn_transfer_pair(&mut kernel);
+        kernel.grant_capability(sender, Cap::<()>::new(42)).unwrap();
+
+        kernel
+            .send_message(channel, transfer_message(sender, 42))
+            .unwrap();
+
+        assert!(!kernel.is_capability_valid(42, sender));
+        assert!(!kernel.is_capability_valid(42, receiver));
+        assert_eq!(kernel.capabilities_in_transit(), 0);
+        assert!(kernel
+            .audit_log()
+            .has_event(|e| matches!(e, CapabilityEvent::Revoked { cap_id: 42, .. })));
+    }
+
+    #[test]
+    fn test_queue_overflow_revokes_capability() {
+        let mut kernel = SimulatedKernel::new().with_channel_capacity(1);
+        let (sender, _receiver, channel) = spawn_transfer_pair(&mut kernel);
+        kernel.grant_capability(sender, Cap::<()>::new(1)).unwrap();
+        kernel.grant_capability(sender, Cap::<()>::new(2)).unwrap();
+
+        kernel
+            .send_message(channel, transfer_message(sender, 1))
+            .unwrap();
+        let result = kernel.send_message(channel, transfer_message(sender, 2));
+        assert!(matches!(result, Err(KernelError::SendFailed(_))));
+
+        assert!(!kernel.is_capability_valid(2, sender));
+        assert_eq!(kernel.capabilities_in_transit(), 1);
+        assert!(kernel.audit_log().has_event(|e| matches!(
+            e,
+            CapabilityEvent::Revoked { cap_id: 2, reason, .. } if reason == "channel queue full"
+        )));
+    }
+
     #[test]
     fn test_identity_tracking() {
         let mut kernel = SimulatedKernel::new();
@@ -3309,6 +4394,59 @@ mod tests {
         assert_eq!(smp.time.ticks(smp::CoreId(1)), 5);
     }
 
+    #[test]
+    fn test_smp_work_stealing_balances_cores() {
+        let mut kernel = SimulatedKernel::new();
+        kernel.enable_smp(2);
+        kernel.smp_mut().unwrap().scheduler.set_work_stealing(true);
+
+        let pinned: Vec<TaskId> = (0..3)
+            .map(|i| {
+                kernel
+                    .spawn_task(TaskDescriptor::new(format!("pinned-{}", i)))
+                    .unwrap()
+                    .task_id
+            })
+            .collect();
+        for task_id in &pinned {
+            kernel
+                .set_task_affinity(*task_id, smp::AffinityMask::single(smp::CoreId(0)))
+                .unwrap();
+        }
+        let free = kernel
+            .spawn_task(TaskDescriptor::new("free".to_string()))
+            .unwrap()
+            .task_id;
+
+        kernel.run_for_ticks(40);
+
+        let smp = kernel.smp().unwrap();
+        let audit = smp.scheduler.audit_log();
+        // Pinned tasks only ever run on core 0
+        assert!(audit.iter().all(|event| match event {
+            smp::CoreScheduleEvent::TaskSelected {
+                core_id, task_id, ..
+            } => !pinned.contains(task_id) || *core_id == smp::CoreId(0),
+            _ => true,
+        }));
+        assert!(audit.iter().any(|event| matches!(
+            event,
+            smp::CoreScheduleEvent::Migrated {
+                reason: smp::MigrationReason::AffinityChanged,
+                ..
+            }
+        )));
+
+        assert_eq!(smp.scheduler.task_core(free), Some(smp::CoreId(1)));
+        assert_eq!(
+            kernel.set_task_affinity(free, smp::AffinityMask::none()),
+            Err(KernelError::SpawnFailed(format!(
+                "{:?}",
+                smp::SmpError::EmptyAffinity(free)
+            )))
+        );
+    }
+
     #[test]
     fn test_timer_monotonic_with_advance_time() {
         let mut kernel = SimulatedKernel::new();
@@ -3349,6 +4487,7 @@ mod tests {
             quantum_ticks: 5,
             max_steps_per_tick: None,
             realtime_policy: scheduler::RealTimePolicy::None,
+            ..Default::default()
         };
         let mut kernel = SimulatedKernel::new().with_scheduler_config(config);
 
@@ -3427,6 +4566,7 @@ mod tests {
             quantum_ticks: 3,
             max_steps_per_tick: None,
             realtime_policy: scheduler::RealTimePolicy::None,
+            ..Default::default()
         };
         let mut kernel = SimulatedKernel::new().with_scheduler_config(config);
 
@@ -3462,6 +4602,7 @@ mod tests {
             quantum_ticks: 5,
             max_steps_per_tick: None,
             realtime_policy: scheduler::RealTimePolicy::None,
+            ..Default::default()
         };
         let mut kernel = SimulatedKernel::new().with_scheduler_config(config);
 
@@ -3528,6 +4669,7 @@ mod tests {
             quantum_ticks: 5,
             max_steps_per_tick: None,
             realtime_policy: scheduler::RealTimePolicy::None,
+            ..Default::default()
         };
 
         let mut kernel1 = SimulatedKernel::new().with_scheduler_config(config.clone());
@@ -3553,6 +4695,328 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_priority_policy_prefers_components_over_pipeline_stages() {
+        let config = scheduler::SchedulerConfig {
+            quantum_ticks: 5,
+            max_steps_per_tick: None,
+            realtime_policy: scheduler::RealTimePolicy::None,
+            policy: scheduler::SchedulingPolicy::Priority {
+                aging_interval_ticks: 0,
+            },
+            ..Default::default()
+        };
+        let mut kernel = SimulatedKernel::new().with_scheduler_config(config);
+
+        let (stage, _) = kernel
+            .spawn_task_with_identity(
+                TaskDescriptor::new("stage".to_string()),
+                identity::IdentityKind::PipelineStage,
+                identity::TrustDomain::user(),
+                None,
+                None,
+            )
+            .unwrap();
+        let (editor, _) = kernel
+            .spawn_task_with_identity(
+                TaskDescriptor::new("editor".to_string()),
+                identity::IdentityKind::Component,
+                identity::TrustDomain::user(),
+                None,
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(
+            kernel.scheduler().sched_params(editor.task_id),
+            Some(scheduler::SchedParams::new(16, scheduler::DEFAULT_WEIGHT))
+        );
+
+        kernel.run_for_steps(3);
+        assert_eq!(
+            kernel.scheduler().selection_order(),
+            vec![editor.task_id; 3]
+        );
+
+        kernel
+            .set_task_sched_params(
+                stage.task_id,
+                scheduler::SchedParams::new(31, scheduler::DEFAULT_WEIGHT),
+            )
+            .unwrap();
+        kernel.run_for_steps(1);
+        assert_eq!(
+            kernel.scheduler().selection_order().last(),
+            Some(&stage.task_id)
+        );
+    }
+
+    #[test]
+    fn test_spawn_with_default_params_logs_no_params_change() {
+        let mut kernel = SimulatedKernel::new();
+
+        let (handle, _) = kernel
+            .spawn_task_with_identity(
+                TaskDescriptor::new("editor".to_string()),
+                identity::IdentityKind::Component,
+                identity::TrustDomain::user(),
+                None,
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(
+            kernel.scheduler().sched_params(handle.task_id),
+            Some(scheduler::SchedParams::default())
+        );
+        assert!(!kernel
+            .scheduler()
+            .audit_log()
+            .iter()
+            .any(|event| matches!(event, scheduler::ScheduleEvent::ParamsChanged { .. })));
+    }
+
+    fn test_message(action: &str) -> MessageEnvelope {
+        MessageEnvelope::new(
+            ServiceId::new(),
+            action.to_string(),
+            ipc::SchemaVersion::new(1, 0),
+            ipc::MessagePayload::new(&action).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_select_reports_first_ready_channel_in_order() {
+        let mut kernel = SimulatedKernel::new();
+        let first = KernelApi::create_channel(&mut kernel).unwrap();
+        let second = KernelApi::create_channel(&mut kernel).unwrap();
+        let third = KernelApi::create_channel(&mut kernel).unwrap();
+
+        kernel.send_message(third, test_message("c")).unwrap();
+        kernel.send_message(second, test_message("b")).unwrap();
+
+        let wait_set = WaitSet::new()
+            .with_channel(first)
+            .with_channel(second)
+            .with_channel(third);
+        assert_eq!(
+            kernel.select(&wait_set).unwrap(),
+            WaitOutcome::Ready(second)
+        );
+
+        // select does not consume the message
+        assert_eq!(kernel.pending_message_count(), 2);
+
+        let (channel, message) = kernel.receive_any(&wait_set).unwrap();
+        assert_eq!(channel, second);
+        assert_eq!(message.action, "b");
+        let (channel, _) = kernel.receive_any(&wait_set).unwrap();
+        assert_eq!(channel, third);
+    }
+
+    #[test]
+    fn test_select_times_out_at_deadline() {
+        let mut kernel = SimulatedKernel::new();
+        let channel = KernelApi::create_channel(&mut kernel).unwrap();
+
+        let deadline = kernel.now() + Duration::from_millis(5);
+        let wait_set = WaitSet::new().with_channel(channel).with_deadline(deadline);
+
+        assert_eq!(kernel.select(&wait_set).unwrap(), WaitOutcome::TimedOut);
+        assert_eq!(kernel.now(), deadline);
+        assert!(matches!(
+            kernel.receive_any(&wait_set),
+            Err(KernelError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_select_without_deadline_on_idle_channels() {
+        let mut kernel = SimulatedKernel::new();
+        let channel = KernelApi::create_channel(&mut kernel).unwrap();
+
+        let wait_set = WaitSet::new().with_channel(channel);
+        assert!(matches!(
+            kernel.select(&wait_set),
+            Err(KernelError::Timeout)
+        ));
+        assert!(matches!(
+            kernel.select(&WaitSet::new()),
+            Err(KernelError::ChannelError(_))
+        ));
+        assert!(matches!(
+            kernel.select(&WaitSet::new().with_channel(ChannelId::new())),
+            Err(KernelError::ChannelError(_))
+        ));
+    }
+
+    #[test]
+    fn test_select_waits_for_delayed_message() {
+        let plan = fault_injection::FaultPlan::new().with_message_fault(
+            fault_injection::MessageFault::Delay {
+                duration: Duration::from_millis(3),
+            },
+        );
+        let mut kernel = SimulatedKernel::new().with_fault_plan(plan);
+        let channel = KernelApi::create_channel(&mut kernel).unwrap();
+        kernel.send_message(channel, test_message("late")).unwrap();
+
+        let wait_set = WaitSet::new()
+            .with_channel(channel)
+            .with_deadline(Instant::from_nanos(Duration::from_millis(10).as_nanos()));
+        assert_eq!(
+            kernel.select(&wait_set).unwrap(),
+            WaitOutcome::Ready(channel)
+        );
+        assert_eq!(kernel.now(), Instant::from_nanos(3_000_000));
+    }
+
+    #[test]
+    fn test_duplicate_and_corrupt_faults_are_audited() {
+        let plan = fault_injection::FaultPlan::new()
+            .with_message_fault(fault_injection::MessageFault::Duplicate { count: 1 })
+            .with_message_fault(fault_injection::MessageFault::CorruptPayload { count: 1 });
+        let mut kernel = SimulatedKernel::new().with_fault_plan(plan);
+        let channel = KernelApi::create_channel(&mut kernel).unwrap();
+        let message = test_message("twice");
+        let message_id = message.id;
+        kernel.send_message(channel, message).unwrap();
+        kernel.send_message(channel, test_message("once")).unwrap();
+
+        let first = kernel.receive_message(channel, None).unwrap();
+        let second = kernel.receive_message(channel, None).unwrap();
+        assert_eq!(first.id, message_id);
+        assert_eq!(second.id, message_id);
+        assert!(first.payload.deserialize::<String>().is_err());
+        let third = kernel.receive_message(channel, None).unwrap();
+        assert_eq!(third.payload.deserialize::<String>().unwrap(), "once");
+
+        let audit = kernel.fault_audit();
+        assert_eq!(
+            audit.count_events(|e| matches!(e, fault_audit::FaultEvent::MessageDuplicated { .. })),
+            1
+        );
+        assert!(audit.has_event(|e| matches!(
+            e,
+            fault_audit::FaultEvent::PayloadCorrupted { message_id: id, .. } if *id == message_id
+        )));
+    }
+
+    #[test]
+    fn test_partition_drops_until_healed() {
+        let mut kernel = SimulatedKernel::new();
+        let (sender, receiver, channel) = spawn_transfer_pair(&mut kernel);
+        let plan = fault_injection::FaultPlan::new().with_message_fault(
+            fault_injection::MessageFault::Partition {
+                groups: vec![vec![sender], vec![receiver]],
+                heal_after: Some(Duration::from_millis(5)),
+            },
+        );
+        let mut kernel = kernel.with_fault_plan(plan);
+
+        let mut message = test_message("cut off");
+        message.source = Some(sender);
+        kernel.send_message(channel, message).unwrap();
+        assert_eq!(kernel.pending_message_count(), 0);
+        assert!(kernel.fault_audit().has_event(|e| matches!(
+            e,
+            fault_audit::FaultEvent::PartitionDropped { from, partition: 0, .. } if *from == sender
+        )));
+
+        kernel.advance_time(Duration::from_millis(5));
+        assert!(kernel
+            .fault_audit()
+            .has_event(|e| *e == fault_audit::FaultEvent::PartitionHealed { partition: 0 }));
+
+        let mut message = test_message("healed");
+        message.source = Some(sender);
+        kernel.send_message(channel, message).unwrap();
+        assert_eq!(kernel.pending_message_count(), 1);
+    }
+
+    #[test]
+    fn test_waiting_task_woken_by_send() {
+        let mut kernel = SimulatedKernel::new();
+        let waiter = kernel
+            .spawn_task(TaskDescriptor::new("waiter".to_string()))
+            .unwrap()
+            .task_id;
+        let input = KernelApi::create_channel(&mut kernel).unwrap();
+        let view = KernelApi::create_channel(&mut kernel).unwrap();
+
+        let wait_set = WaitSet::new().with_channel(input).with_channel(view);
+        assert_eq!(kernel.wait_on_channels(waiter, &wait_set).unwrap(), None);
+        assert!(kernel.is_task_waiting(waiter));
+        assert!(matches!(
+            kernel.scheduler().task_state(waiter),
+            Some(scheduler::TaskState::Waiting { .. })
+        ));
+        assert_eq!(kernel.take_wait_outcome(waiter), None);
+
+        kernel.send_message(view, test_message("frame")).unwrap();
+
+        assert!(!kernel.is_task_waiting(waiter));
+        assert_eq!(
+            kernel.scheduler().task_state(waiter),
+            Some(scheduler::TaskState::Runnable)
+        );
+        assert_eq!(
+            kernel.take_wait_outcome(waiter),
+            Some(WaitOutcome::Ready(view))
+        );
+        assert!(kernel.scheduler_audit().iter().any(|e| matches!(
+            e,
+            scheduler::ScheduleEvent::TaskWoken {
+                task_id,
+                reason: scheduler::WakeReason::ChannelReady,
+                ..
+            } if *task_id == waiter
+        )));
+    }
+
+    #[test]
+    fn test_waiting_task_consumes_no_quanta() {
+        let config = scheduler::SchedulerConfig {
+            quantum_ticks: 5,
+            max_steps_per_tick: None,
+            realtime_policy: scheduler::RealTimePolicy::None,
+            ..Default::default()
+        };
+        let mut kernel = SimulatedKernel::new().with_scheduler_config(config);
+        let waiter = kernel
+            .spawn_task(TaskDescriptor::new("waiter".to_string()))
+            .unwrap()
+            .task_id;
+        let worker = kernel
+            .spawn_task(TaskDescriptor::new("worker".to_string()))
+            .unwrap()
+            .task_id;
+        let channel = KernelApi::create_channel(&mut kernel).unwrap();
+
+        let deadline = Instant::from_nanos(Duration::from_micros(40).as_nanos());
+        let wait_set = WaitSet::new().with_channel(channel).with_deadline(deadline);
+        kernel.wait_on_channels(waiter, &wait_set).unwrap();
+
+        kernel.run_for_ticks(30);
+        let waiter_selected = kernel.scheduler_audit().iter().any(|e| {
+            matches!(e, scheduler::ScheduleEvent::TaskSelected { task_id, .. } if *task_id == waiter)
+        });
+        assert!(!waiter_selected);
+        assert!(kernel.scheduler_audit().iter().any(|e| {
+            matches!(e, scheduler::ScheduleEvent::TaskSelected { task_id, .. } if *task_id == worker)
+        }));
+
+        // Deadline wakes the waiter with a timeout
+        kernel.run_for_ticks(20);
+        assert_eq!(
+            kernel.take_wait_outcome(waiter),
+            Some(WaitOutcome::TimedOut)
+        );
+        assert!(kernel.scheduler_audit().iter().any(|e| {
+            matches!(e, scheduler::ScheduleEvent::TaskSelected { task_id, .. } if *task_id == waiter)
+        }));
+    }
+
     // ============================================================================
     // Phase 24: Memory Management Integration Tests
     // ============================================================================
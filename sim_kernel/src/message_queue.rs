@@ -61,6 +61,11 @@ impl MessageQueue {
         self.messages.pop_front()
     }
 
+    /// Read-only view of the queued messages, oldest first.
+    pub fn messages(&self) -> &VecDeque<MessageEnvelope> {
+        &self.messages
+    }
+
     /// Mutable access to the underlying deque (for fault injection only).
     pub fn messages_mut(&mut self) -> &mut VecDeque<MessageEnvelope> {
         &mut self.messages
@@ -0,0 +1,824 @@
+//! Record-and-replay of whole simulated kernel runs
+//!
+//! A [`KernelRecording`] captures every input that drives a
+//! [`SimulatedKernel`]: API calls, time advancement, and the identifiers and
+//! fault decisions the kernel would otherwise pick for itself. A
+//! [`Replayer`] feeds the recording into a fresh kernel, forcing the same
+//! identifiers and fault decisions, and checks after every step that the
+//! outcome and the state digest match.
+//!
+//! ## Example
+//!
+//! ```
+//! use sim_kernel::replay::{KernelRecording, Replayer};
+//! use sim_kernel::SimulatedKernel;
+//! use kernel_api::{Duration, KernelApi};
+//!
+//! let mut kernel = SimulatedKernel::new();
+//! kernel.start_recording();
+//! let channel = kernel.create_channel().unwrap();
+//! kernel.advance_time(Duration::from_millis(5));
+//! let recording = kernel.stop_recording().unwrap();
+//!
+//! let restored = KernelRecording::from_json(&recording.to_json()).unwrap();
+//! let mut fresh = SimulatedKernel::new();
+//! let report = Replayer::new(restored).replay(&mut fresh).unwrap();
+//! assert_eq!(report.final_digest, kernel.state_digest());
+//! # let _ = channel;
+//! ```
+
+use crate::SimulatedKernel;
+use core_types::{Cap, CapabilityMetadata, ServiceId, TaskId};
+use identity::{ExecutionId, ExitReason, IdentityKind, IdentityMetadata, TrustDomain};
+use ipc::{ChannelId, MessageEnvelope, MessageId};
+use kernel_api::{Duration, KernelApi, TaskDescriptor, WaitOutcome, WaitSet};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Current recording file format version
+pub const RECORDING_FORMAT_VERSION: u32 = 1;
+
+/// An input applied to the kernel from outside
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum KernelInput {
+    SpawnTask {
+        descriptor: TaskDescriptor,
+    },
+    SpawnTaskWithIdentity {
+        descriptor: TaskDescriptor,
+        kind: IdentityKind,
+        trust_domain: TrustDomain,
+        parent_id: Option<ExecutionId>,
+        creator_id: Option<ExecutionId>,
+    },
+    CreateChannel,
+    RegisterService {
+        service_id: ServiceId,
+        channel: ChannelId,
+    },
+    GrantCapability {
+        task_id: TaskId,
+        capability: Cap<()>,
+    },
+    SendMessage {
+        channel: ChannelId,
+        message: MessageEnvelope,
+    },
+    /// A device-originated input event (see `SimulatedKernel::inject_input`)
+    InjectInput {
+        channel: ChannelId,
+        message: MessageEnvelope,
+    },
+    ReceiveMessage {
+        channel: ChannelId,
+        timeout: Option<Duration>,
+    },
+    Select {
+        wait_set: WaitSet,
+    },
+    SetReceiveContext {
+        task_id: TaskId,
+    },
+    ClearReceiveContext,
+    AdvanceTime {
+        duration: Duration,
+    },
+    Sleep {
+        duration: Duration,
+    },
+    RunForTicks {
+        ticks: u64,
+    },
+    RunForSteps {
+        steps: usize,
+    },
+    RunUntilIdle,
+    TerminateTask {
+        task_id: TaskId,
+        reason: ExitReason,
+    },
+}
+
+/// What an input produced
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StepOutcome {
+    Done,
+    Spawned {
+        task_id: TaskId,
+        execution_id: ExecutionId,
+    },
+    ChannelCreated {
+        channel: ChannelId,
+    },
+    Received {
+        message_id: MessageId,
+    },
+    Selected {
+        outcome: WaitOutcome,
+    },
+    /// Scheduling rounds or steps executed
+    Ran {
+        count: usize,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+impl StepOutcome {
+    pub(crate) fn from_result<T, E: fmt::Display>(
+        result: &Result<T, E>,
+        ok: impl FnOnce(&T) -> StepOutcome,
+    ) -> Self {
+        match result {
+            Ok(value) => ok(value),
+            Err(err) => StepOutcome::Failed {
+                error: err.to_string(),
+            },
+        }
+    }
+}
+
+/// A fault injection decision taken by the kernel
+///
+/// Every send and receive records one decision, whether or not a fault
+/// injector is installed, so replay does not need the original plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FaultDecision {
+    /// The message was queued, then these reorder swaps were applied
+    Deliver {
+        swaps: Vec<(usize, usize)>,
+        /// A second copy was queued (if there was room)
+        #[serde(default)]
+        duplicated: bool,
+        /// The payload was corrupted before queueing
+        #[serde(default)]
+        corrupted: bool,
+    },
+    Drop,
+    /// The message was dropped by the given partition
+    Partitioned {
+        partition: usize,
+    },
+    Delay {
+        duration: Duration,
+    },
+    CrashOnSend,
+    /// The receive went ahead
+    Receive,
+    CrashOnRecv,
+}
+
+/// One top-level input and everything it produced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedStep {
+    pub input: KernelInput,
+    pub outcome: StepOutcome,
+    pub faults: Vec<FaultDecision>,
+    /// State digest after the step (see `SimulatedKernel::state_digest`)
+    pub digest: u64,
+}
+
+/// A versioned recording of a kernel run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KernelRecording {
+    pub format_version: u32,
+    pub nanos_per_tick: u64,
+    pub channel_capacity: usize,
+    /// Digest of the kernel when recording started
+    pub initial_digest: u64,
+    pub steps: Vec<RecordedStep>,
+}
+
+impl KernelRecording {
+    /// Serializes the recording as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("kernel recordings are always serializable")
+    }
+
+    /// Parses a recording, rejecting unknown format versions
+    pub fn from_json(json: &str) -> Result<Self, ReplayError> {
+        #[derive(Deserialize)]
+        struct Header {
+            format_version: u32,
+        }
+
+        let header: Header =
+            serde_json::from_str(json).map_err(|err| ReplayError::Malformed(err.to_string()))?;
+        if header.format_version != RECORDING_FORMAT_VERSION {
+            return Err(ReplayError::UnsupportedVersion(header.format_version));
+        }
+        serde_json::from_str(json).map_err(|err| ReplayError::Malformed(err.to_string()))
+    }
+
+    /// Writes the recording to a file
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Reads a recording from a file
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, ReplayError> {
+        let json =
+            std::fs::read_to_string(path).map_err(|err| ReplayError::Malformed(err.to_string()))?;
+        Self::from_json(&json)
+    }
+}
+
+/// The first point where a replay differs from its recording
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    /// Index into `KernelRecording::steps`
+    pub step_index: usize,
+    pub expected: RecordedStep,
+    pub actual: RecordedStep,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "step {} ({:?}): ", self.step_index, self.expected.input)?;
+        if self.expected.outcome != self.actual.outcome {
+            write!(
+                f,
+                "expected {:?}, got {:?}",
+                self.expected.outcome, self.actual.outcome
+            )
+        } else if self.expected.faults != self.actual.faults {
+            write!(
+                f,
+                "expected faults {:?}, got {:?}",
+                self.expected.faults, self.actual.faults
+            )
+        } else {
+            write!(
+                f,
+                "expected digest {:#018x}, got {:#018x}",
+                self.expected.digest, self.actual.digest
+            )
+        }
+    }
+}
+
+/// Replay errors
+#[derive(Debug, Clone)]
+pub enum ReplayError {
+    /// The recording could not be parsed
+    Malformed(String),
+    /// The recording uses a format this build cannot read
+    UnsupportedVersion(u32),
+    /// The kernel is not configured like the recorded one
+    ConfigMismatch(String),
+    /// The kernel produced a different result than recorded
+    Diverged(Box<Divergence>),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Malformed(err) => write!(f, "malformed recording: {}", err),
+            ReplayError::UnsupportedVersion(version) => {
+                write!(f, "unsupported recording format version {}", version)
+            }
+            ReplayError::ConfigMismatch(detail) => write!(f, "kernel config mismatch: {}", detail),
+            ReplayError::Diverged(divergence) => write!(f, "replay diverged at {}", divergence),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Summary of a successful replay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayReport {
+    pub steps_replayed: usize,
+    pub final_digest: u64,
+}
+
+/// Re-drives a fresh kernel from a recording
+#[derive(Debug, Clone)]
+pub struct Replayer {
+    recording: KernelRecording,
+}
+
+impl Replayer {
+    /// Creates a replayer for a recording
+    pub fn new(recording: KernelRecording) -> Self {
+        Self { recording }
+    }
+
+    /// Returns the recording being replayed
+    pub fn recording(&self) -> &KernelRecording {
+        &self.recording
+    }
+
+    /// Replays every step, stopping at the first divergence
+    ///
+    /// `kernel` must be freshly built with the same tick resolution, channel
+    /// capacity and configuration (scheduler, SMP, policy engine) as the
+    /// recorded kernel. Any fault injector it has is bypassed in favor of the
+    /// recorded decisions.
+    pub fn replay(&self, kernel: &mut SimulatedKernel) -> Result<ReplayReport, ReplayError> {
+        let recording = &self.recording;
+        if kernel.nanos_per_tick != recording.nanos_per_tick {
+            return Err(ReplayError::ConfigMismatch(format!(
+                "tick resolution {}ns, recorded {}ns",
+                kernel.nanos_per_tick, recording.nanos_per_tick
+            )));
+        }
+        if kernel.channel_capacity != recording.channel_capacity {
+            return Err(ReplayError::ConfigMismatch(format!(
+                "channel capacity {}, recorded {}",
+                kernel.channel_capacity, recording.channel_capacity
+            )));
+        }
+        if kernel.state_digest() != recording.initial_digest {
+            return Err(ReplayError::ConfigMismatch(
+                "initial state differs from the recorded kernel".to_string(),
+            ));
+        }
+
+        kernel.start_recording();
+        let result = self.replay_steps(kernel);
+        kernel.stop_recording();
+        kernel.script = None;
+        result
+    }
+
+    fn replay_steps(&self, kernel: &mut SimulatedKernel) -> Result<ReplayReport, ReplayError> {
+        for (step_index, expected) in self.recording.steps.iter().enumerate() {
+            kernel.script = Some(Script::for_step(expected));
+            apply_input(kernel, &expected.input);
+
+            let actual = kernel
+                .recorder
+                .as_mut()
+                .and_then(|recorder| recorder.recording.steps.pop())
+                .expect("every replayed input records a step");
+            if actual.outcome != expected.outcome
+                || actual.faults != expected.faults
+                || actual.digest != expected.digest
+            {
+                return Err(ReplayError::Diverged(Box::new(Divergence {
+                    step_index,
+                    expected: expected.clone(),
+                    actual,
+                })));
+            }
+        }
+
+        Ok(ReplayReport {
+            steps_replayed: self.recording.steps.len(),
+            final_digest: kernel.state_digest(),
+        })
+    }
+}
+
+/// Applies one recorded input through the public kernel API
+fn apply_input(kernel: &mut SimulatedKernel, input: &KernelInput) {
+    match input.clone() {
+        KernelInput::SpawnTask { descriptor } => {
+            let _ = kernel.spawn_task(descriptor);
+        }
+        KernelInput::SpawnTaskWithIdentity {
+            descriptor,
+            kind,
+            trust_domain,
+            parent_id,
+            creator_id,
+        } => {
+            let _ = kernel.spawn_task_with_identity(
+                descriptor,
+                kind,
+                trust_domain,
+                parent_id,
+                creator_id,
+            );
+        }
+        KernelInput::CreateChannel => {
+            let _ = kernel.create_channel();
+        }
+        KernelInput::RegisterService {
+            service_id,
+            channel,
+        } => {
+            let _ = kernel.register_service(service_id, channel);
+        }
+        KernelInput::GrantCapability {
+            task_id,
+            capability,
+        } => {
+            let _ = kernel.grant_capability(task_id, capability);
+        }
+        KernelInput::SendMessage { channel, message } => {
+            let _ = kernel.send_message(channel, message);
+        }
+        KernelInput::InjectInput { channel, message } => {
+            let _ = kernel.inject_input(channel, message);
+        }
+        KernelInput::ReceiveMessage { channel, timeout } => {
+            let _ = kernel.receive_message(channel, timeout);
+        }
+        KernelInput::Select { wait_set } => {
+            let _ = kernel.select(&wait_set);
+        }
+        KernelInput::SetReceiveContext { task_id } => kernel.set_receive_context(task_id),
+        KernelInput::ClearReceiveContext => kernel.clear_receive_context(),
+        KernelInput::AdvanceTime { duration } => kernel.advance_time(duration),
+        KernelInput::Sleep { duration } => {
+            let _ = kernel.sleep(duration);
+        }
+        KernelInput::RunForTicks { ticks } => {
+            kernel.run_for_ticks(ticks);
+        }
+        KernelInput::RunForSteps { steps } => {
+            kernel.run_for_steps(steps);
+        }
+        KernelInput::RunUntilIdle => kernel.run_until_idle(),
+        KernelInput::TerminateTask { task_id, reason } => {
+            kernel.terminate_task_with_reason(task_id, reason)
+        }
+    }
+}
+
+/// Collects steps while a kernel is recording
+#[derive(Debug)]
+pub(crate) struct Recorder {
+    recording: KernelRecording,
+    /// Nesting depth of recorded calls; only depth 1 calls become steps
+    depth: usize,
+    faults: Vec<FaultDecision>,
+}
+
+impl Recorder {
+    pub(crate) fn new(kernel: &SimulatedKernel) -> Self {
+        Self {
+            recording: KernelRecording {
+                format_version: RECORDING_FORMAT_VERSION,
+                nanos_per_tick: kernel.nanos_per_tick,
+                channel_capacity: kernel.channel_capacity,
+                initial_digest: kernel.state_digest(),
+                steps: Vec::new(),
+            },
+            depth: 0,
+            faults: Vec::new(),
+        }
+    }
+
+    /// Enters a recorded call; returns true for a top-level call
+    pub(crate) fn enter(&mut self) -> bool {
+        self.depth += 1;
+        self.depth == 1
+    }
+
+    pub(crate) fn exit(&mut self) {
+        self.depth -= 1;
+    }
+
+    pub(crate) fn note_fault(&mut self, decision: FaultDecision) {
+        self.faults.push(decision);
+    }
+
+    pub(crate) fn push_step(&mut self, input: KernelInput, outcome: StepOutcome, digest: u64) {
+        self.recording.steps.push(RecordedStep {
+            input,
+            outcome,
+            faults: std::mem::take(&mut self.faults),
+            digest,
+        });
+    }
+
+    pub(crate) fn into_recording(self) -> KernelRecording {
+        self.recording
+    }
+}
+
+/// Identifiers and fault decisions forced on the kernel during replay
+#[derive(Debug, Default)]
+pub(crate) struct Script {
+    pub(crate) task_ids: VecDeque<TaskId>,
+    pub(crate) execution_ids: VecDeque<ExecutionId>,
+    pub(crate) channel_ids: VecDeque<ChannelId>,
+    pub(crate) faults: VecDeque<FaultDecision>,
+}
+
+impl Script {
+    fn for_step(step: &RecordedStep) -> Self {
+        let mut script = Self {
+            faults: step.faults.iter().cloned().collect(),
+            ..Self::default()
+        };
+        match &step.outcome {
+            StepOutcome::Spawned {
+                task_id,
+                execution_id,
+            } => {
+                script.task_ids.push_back(*task_id);
+                script.execution_ids.push_back(*execution_id);
+            }
+            StepOutcome::ChannelCreated { channel } => script.channel_ids.push_back(*channel),
+            _ => {}
+        }
+        script
+    }
+}
+
+/// Computes a stable 64-bit digest of the kernel state
+///
+/// Hash maps are flattened into vectors sorted by ID so the digest does not
+/// depend on iteration order.
+pub(crate) fn state_digest(kernel: &SimulatedKernel) -> u64 {
+    let mut tasks: Vec<_> = kernel
+        .tasks
+        .iter()
+        .map(|(task_id, info)| (*task_id, info.execution_id))
+        .collect();
+    tasks.sort_by_key(|(task_id, _)| task_id.as_uuid());
+
+    let mut identities: Vec<_> = kernel.identity_table.values().collect();
+    identities.sort_by_key(|identity| identity.execution_id.as_uuid());
+
+    let mut channels: Vec<_> = kernel
+        .channels
+        .iter()
+        .map(|(channel_id, channel)| {
+            let queued: Vec<MessageId> = channel
+                .queue
+                .messages()
+                .iter()
+                .map(|message| message.id)
+                .collect();
+            (*channel_id, queued)
+        })
+        .collect();
+    channels.sort_by_key(|(channel_id, _)| channel_id.as_uuid());
+
+    let delayed: Vec<_> = kernel
+        .delayed_messages
+        .iter()
+        .map(|delayed| {
+            (
+                delayed.channel,
+                delayed.message.id,
+                delayed.deliver_at.as_nanos(),
+            )
+        })
+        .collect();
+
+    let mut services: Vec<_> = kernel
+        .services
+        .iter()
+        .map(|(service_id, channel)| (*service_id, *channel))
+        .collect();
+    services.sort_by_key(|(service_id, _)| service_id.as_uuid());
+
+    let mut capabilities: Vec<_> = kernel.capability_table.values().collect();
+    capabilities.sort_by_key(|metadata| metadata.cap_id);
+
+    let mut in_transit: Vec<_> = kernel
+        .capabilities_in_transit
+        .iter()
+        .map(|(cap_id, sender)| (*cap_id, *sender))
+        .collect();
+    in_transit.sort_by_key(|(cap_id, _)| *cap_id);
+
+    let mut hasher = Fnv1a::new();
+    kernel.current_time.as_nanos().hash(&mut hasher);
+    kernel.timer.current_ticks().hash(&mut hasher);
+    tasks.hash(&mut hasher);
+    identities.len().hash(&mut hasher);
+    for identity in identities {
+        hash_identity(identity, &mut hasher);
+    }
+    channels.hash(&mut hasher);
+    delayed.hash(&mut hasher);
+    services.hash(&mut hasher);
+    capabilities.len().hash(&mut hasher);
+    for metadata in capabilities {
+        hash_capability(metadata, &mut hasher);
+    }
+    in_transit.hash(&mut hasher);
+    kernel.exit_notifications.len().hash(&mut hasher);
+    kernel.scheduler.audit_log().len().hash(&mut hasher);
+    kernel.scheduler.current_task().hash(&mut hasher);
+    kernel.channel_waiters.len().hash(&mut hasher);
+    for waiter in &kernel.channel_waiters {
+        waiter.task_id.hash(&mut hasher);
+    }
+    kernel.current_receive_task.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn hash_identity(identity: &IdentityMetadata, hasher: &mut Fnv1a) {
+    identity.execution_id.hash(hasher);
+    identity.kind.hash(hasher);
+    identity.task_id.hash(hasher);
+    identity.parent_id.hash(hasher);
+    identity.creator_id.hash(hasher);
+    identity.created_at_nanos.hash(hasher);
+    identity.trust_domain.hash(hasher);
+    identity.name.hash(hasher);
+    identity
+        .budget
+        .map(|budget| {
+            (
+                budget.cpu_ticks,
+                budget.memory_units,
+                budget.message_count,
+                budget.packet_count,
+                budget.storage_ops,
+                budget.pipeline_stages,
+            )
+        })
+        .hash(hasher);
+    let usage = identity.usage;
+    (
+        usage.cpu_ticks,
+        usage.memory_units,
+        usage.message_count,
+        usage.packet_count,
+        usage.storage_ops,
+        usage.pipeline_stages,
+    )
+        .hash(hasher);
+}
+
+fn hash_capability(metadata: &CapabilityMetadata, hasher: &mut Fnv1a) {
+    metadata.cap_id.hash(hasher);
+    metadata.owner.hash(hasher);
+    metadata.cap_type.hash(hasher);
+    (metadata.status as u8).hash(hasher);
+    metadata.grantor.hash(hasher);
+    metadata.revoked.hash(hasher);
+    metadata.lease_expires_at_nanos.hash(hasher);
+}
+
+/// 64-bit FNV-1a, stable across builds and platforms
+///
+/// Integers are written little-endian at a fixed width, so `usize` lengths
+/// and enum discriminants hash identically on every target.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, value: u128) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fault_injection::{FaultPlan, MessageFault};
+    use ipc::{MessagePayload, SchemaVersion};
+
+    fn message(action: &str) -> MessageEnvelope {
+        MessageEnvelope::new(
+            ServiceId::new(),
+            action.to_string(),
+            SchemaVersion::new(1, 0),
+            MessagePayload::new(&action).unwrap(),
+        )
+    }
+
+    /// Records a run that spawns, sends under faults, receives and schedules
+    fn record_run(kernel: &mut SimulatedKernel) -> KernelRecording {
+        kernel.start_recording();
+        let task = kernel
+            .spawn_task(TaskDescriptor::new("worker".to_string()))
+            .unwrap();
+        let channel = kernel.create_channel().unwrap();
+        for action in ["a", "b", "c", "d"] {
+            kernel.send_message(channel, message(action)).unwrap();
+        }
+        kernel.inject_input(channel, message("key")).unwrap();
+        kernel.set_receive_context(task.task_id);
+        let _ = kernel.receive_message(channel, None);
+        kernel.clear_receive_context();
+        kernel.run_for_ticks(25);
+        kernel.run_until_idle();
+        let _ = kernel.receive_message(channel, None);
+        kernel.stop_recording().unwrap()
+    }
+
+    fn faulty_kernel() -> SimulatedKernel {
+        SimulatedKernel::new().with_fault_plan(
+            FaultPlan::new()
+                .with_message_fault(MessageFault::DropNext { count: 1 })
+                .with_message_fault(MessageFault::Delay {
+                    duration: Duration::from_millis(3),
+                })
+                .with_message_fault(MessageFault::ReorderWindow {
+                    index: 0,
+                    offset: 1,
+                }),
+        )
+    }
+
+    #[test]
+    fn test_replay_reproduces_run_without_fault_plan() {
+        let mut kernel = faulty_kernel();
+        let recording = record_run(&mut kernel);
+        assert_eq!(recording.steps.len(), 13);
+        assert_eq!(recording.steps[2].faults, vec![FaultDecision::Drop]);
+
+        let restored = KernelRecording::from_json(&recording.to_json()).unwrap();
+        let mut fresh = SimulatedKernel::new();
+        let report = Replayer::new(restored).replay(&mut fresh).unwrap();
+
+        assert_eq!(report.steps_replayed, 13);
+        assert_eq!(report.final_digest, kernel.state_digest());
+        assert_eq!(
+            fresh.pending_message_count(),
+            kernel.pending_message_count()
+        );
+        assert!(!fresh.is_recording());
+    }
+
+    #[test]
+    fn test_replay_reports_first_divergence() {
+        let mut kernel = faulty_kernel();
+        let mut recording = record_run(&mut kernel);
+        // Pretend the first send was delivered instead of dropped
+        recording.steps[2].faults = vec![FaultDecision::Deliver {
+            swaps: Vec::new(),
+            duplicated: false,
+            corrupted: false,
+        }];
+
+        let mut fresh = SimulatedKernel::new();
+        match Replayer::new(recording).replay(&mut fresh) {
+            Err(ReplayError::Diverged(divergence)) => {
+                assert_eq!(divergence.step_index, 2);
+                assert_ne!(divergence.expected.digest, divergence.actual.digest);
+            }
+            other => panic!("expected divergence, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_recording_rejects_other_versions_and_configs() {
+        let mut kernel = SimulatedKernel::new();
+        let mut recording = record_run(&mut kernel);
+
+        let mut fresh = SimulatedKernel::with_tick_resolution(Duration::from_millis(1));
+        assert!(matches!(
+            Replayer::new(recording.clone()).replay(&mut fresh),
+            Err(ReplayError::ConfigMismatch(_))
+        ));
+
+        recording.format_version = RECORDING_FORMAT_VERSION + 1;
+        assert!(matches!(
+            KernelRecording::from_json(&recording.to_json()),
+            Err(ReplayError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_nested_operations_record_one_step() {
+        let mut kernel = SimulatedKernel::new();
+        kernel.start_recording();
+        kernel
+            .spawn_task(TaskDescriptor::new("spinner".to_string()))
+            .unwrap();
+        kernel.run_for_ticks(10);
+
+        let recording = kernel.stop_recording().unwrap();
+        assert_eq!(recording.steps.len(), 2);
+        assert_eq!(recording.steps[1].outcome, StepOutcome::Ran { count: 1 });
+    }
+}
@@ -4,18 +4,24 @@
 //!
 //! ## Philosophy
 //!
-//! - **Mechanism, not policy**: This scheduler provides the foundation for
-//!   preemption without imposing fairness or priority policies.
+//! - **Mechanism, then policy**: Preemption is the mechanism; which task runs
+//!   next is a pluggable [`SchedulingPolicy`] chosen via [`SchedulerConfig`].
 //! - **Determinism first**: Same inputs + same ticks => same schedule.
 //! - **No hidden yields**: Preemption is explicit and testable.
 //! - **Correctness over performance**: We aim for correct behavior, not optimal throughput.
 //!
 //! ## Design
 //!
-//! - **Round-robin scheduling**: Tasks are scheduled in FIFO order.
 //! - **Time-sliced execution**: Each task gets a quantum of ticks before preemption.
-//! - **No priorities**: All tasks are equal (for now).
-//! - **No fairness guarantees**: We don't compensate for uneven execution.
+//! - **Round-robin by default**: Tasks are scheduled in FIFO order.
+//! - **Strict priority with aging**: Highest effective priority runs first;
+//!   waiting tasks gain priority over time so they cannot starve.
+//! - **Weighted fair**: The task with the lowest weighted virtual runtime runs
+//!   next, so CPU time is shared in proportion to task weights.
+//! - **Identity-keyed parameters**: Priority and weight come from the task's
+//!   `IdentityKind` and `TrustDomain` via a [`TaskClassTable`].
+//! - **Ties are FIFO**: Every policy breaks ties by run-queue order, keeping
+//!   schedules deterministic.
 //!
 //! ## Future Hardware Seam
 //!
@@ -25,8 +31,15 @@
 //! - The scheduler state remains separate from interrupt handling logic
 
 use core_types::TaskId;
+use identity::{IdentityKind, TrustDomain};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+
+/// Highest scheduling priority
+pub const MAX_PRIORITY: u8 = 31;
+
+/// Weight of a task with an ordinary share of the CPU
+pub const DEFAULT_WEIGHT: u32 = 1024;
 
 /// Task state in the scheduler
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -36,6 +49,10 @@ pub enum TaskState {
     /// Task is blocked waiting for a specific tick count
     /// The task will be unblocked when current_ticks >= wake_tick
     Blocked { wake_tick: u64 },
+    /// Task is blocked waiting for a message on one of several channels
+    /// The task is unblocked when the kernel reports a ready channel, or
+    /// when current_ticks >= deadline_tick (if a deadline was given)
+    Waiting { deadline_tick: Option<u64> },
     /// Task has exited normally or abnormally
     Exited,
     /// Task was cancelled due to resource exhaustion
@@ -51,6 +68,10 @@ pub struct SchedulerConfig {
     pub max_steps_per_tick: Option<u64>,
     /// Real-time scheduling policy
     pub realtime_policy: RealTimePolicy,
+    /// Policy for choosing among ordinary (non-real-time) tasks
+    pub policy: SchedulingPolicy,
+    /// Scheduling parameters assigned to tasks by identity
+    pub task_classes: TaskClassTable,
 }
 
 impl Default for SchedulerConfig {
@@ -59,10 +80,129 @@ impl Default for SchedulerConfig {
             quantum_ticks: 10, // Small quantum for testing
             max_steps_per_tick: Some(1000),
             realtime_policy: RealTimePolicy::None,
+            policy: SchedulingPolicy::RoundRobin,
+            task_classes: TaskClassTable::default(),
+        }
+    }
+}
+
+/// Policy for choosing the next ordinary task to run
+///
+/// Real-time tasks under `RealTimePolicy::EarliestDeadlineFirst` always run
+/// before tasks selected by this policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchedulingPolicy {
+    /// FIFO round-robin; priorities and weights are ignored
+    #[default]
+    RoundRobin,
+    /// Strict priority; ties are FIFO
+    ///
+    /// A runnable task gains one priority level for every
+    /// `aging_interval_ticks` it spends in the run queue (0 disables aging).
+    Priority { aging_interval_ticks: u64 },
+    /// Weighted fair sharing
+    ///
+    /// Each task accrues virtual runtime at a rate inversely proportional to
+    /// its weight; the task with the lowest virtual runtime runs next.
+    WeightedFair,
+}
+
+/// Per-task scheduling parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchedParams {
+    /// Base priority (0 = lowest, `MAX_PRIORITY` = highest)
+    pub priority: u8,
+    /// Relative CPU share under `SchedulingPolicy::WeightedFair`
+    pub weight: u32,
+}
+
+impl SchedParams {
+    /// Creates scheduling parameters, clamping priority and weight to valid ranges
+    pub const fn new(priority: u8, weight: u32) -> Self {
+        Self {
+            priority: if priority > MAX_PRIORITY {
+                MAX_PRIORITY
+            } else {
+                priority
+            },
+            weight: if weight == 0 { 1 } else { weight },
         }
     }
 }
 
+impl Default for SchedParams {
+    fn default() -> Self {
+        Self::new(16, DEFAULT_WEIGHT)
+    }
+}
+
+/// Maps task identities to scheduling parameters
+///
+/// A trust domain entry takes precedence over the identity kind entry, so a
+/// whole domain (e.g. sandbox) can be pinned low regardless of kind. Tasks
+/// matching neither get `SchedParams::default()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskClassTable {
+    kinds: HashMap<IdentityKind, SchedParams>,
+    domains: HashMap<TrustDomain, SchedParams>,
+}
+
+impl TaskClassTable {
+    /// Creates a table with no entries
+    pub fn empty() -> Self {
+        Self {
+            kinds: HashMap::new(),
+            domains: HashMap::new(),
+        }
+    }
+
+    /// Sets the parameters for an identity kind
+    pub fn with_kind(mut self, kind: IdentityKind, params: SchedParams) -> Self {
+        self.kinds.insert(kind, params);
+        self
+    }
+
+    /// Sets the parameters for a trust domain
+    pub fn with_domain(mut self, domain: TrustDomain, params: SchedParams) -> Self {
+        self.domains.insert(domain, params);
+        self
+    }
+
+    /// Returns the parameters for a task with the given identity
+    pub fn params_for(&self, kind: IdentityKind, domain: &TrustDomain) -> SchedParams {
+        self.domains
+            .get(domain)
+            .or_else(|| self.kinds.get(&kind))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl Default for TaskClassTable {
+    /// Interactive components and services outrank background pipeline
+    /// stages; sandboxed tasks get the smallest share.
+    fn default() -> Self {
+        Self::empty()
+            .with_kind(
+                IdentityKind::System,
+                SchedParams::new(24, 2 * DEFAULT_WEIGHT),
+            )
+            .with_kind(IdentityKind::Service, SchedParams::new(20, DEFAULT_WEIGHT))
+            .with_kind(
+                IdentityKind::Component,
+                SchedParams::new(16, DEFAULT_WEIGHT),
+            )
+            .with_kind(
+                IdentityKind::PipelineStage,
+                SchedParams::new(8, DEFAULT_WEIGHT / 4),
+            )
+            .with_domain(
+                TrustDomain::sandbox(),
+                SchedParams::new(4, DEFAULT_WEIGHT / 8),
+            )
+    }
+}
+
 /// Real-time scheduling policy
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RealTimePolicy {
@@ -135,6 +275,40 @@ pub enum ScheduleEvent {
         deadline_tick: u64,
         timestamp_ticks: u64,
     },
+    /// Task blocked waiting on a channel wait set
+    TaskWaiting {
+        task_id: TaskId,
+        deadline_tick: Option<u64>,
+        timestamp_ticks: u64,
+    },
+    /// Task waiting on a channel wait set was woken
+    TaskWoken {
+        task_id: TaskId,
+        reason: WakeReason,
+        timestamp_ticks: u64,
+    },
+    /// Task scheduling parameters were set
+    ParamsChanged {
+        task_id: TaskId,
+        params: SchedParams,
+        timestamp_ticks: u64,
+    },
+    /// Task was selected with a priority raised above its base by aging
+    PriorityAged {
+        task_id: TaskId,
+        base_priority: u8,
+        effective_priority: u8,
+        timestamp_ticks: u64,
+    },
+}
+
+/// Reason a waiting task was woken
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WakeReason {
+    /// A channel in the task's wait set has a message
+    ChannelReady,
+    /// The wait deadline passed
+    DeadlineReached,
 }
 
 /// Reason for preemption
@@ -168,6 +342,11 @@ struct TaskInfo {
     /// Ticks consumed since last scheduling
     ticks_in_quantum: u64,
     realtime: Option<RealTimeTask>,
+    params: SchedParams,
+    /// Tick at which the task last entered the run queue
+    ready_since: u64,
+    /// Weighted runtime used by `SchedulingPolicy::WeightedFair`
+    vruntime: u64,
 }
 
 /// Run queue for tasks
@@ -190,31 +369,12 @@ impl RunQueue {
         self.queue.push_back(task_id);
     }
 
-    fn dequeue(&mut self) -> Option<TaskId> {
-        self.queue.pop_front()
+    fn remove_at(&mut self, index: usize) -> Option<TaskId> {
+        self.queue.remove(index)
     }
 
-    fn dequeue_earliest_deadline<F>(&mut self, mut deadline_of: F) -> Option<TaskId>
-    where
-        F: FnMut(TaskId) -> Option<u64>,
-    {
-        let mut best_index: Option<usize> = None;
-        let mut best_deadline: u64 = u64::MAX;
-
-        for (index, task_id) in self.queue.iter().copied().enumerate() {
-            if let Some(deadline) = deadline_of(task_id) {
-                if deadline < best_deadline {
-                    best_deadline = deadline;
-                    best_index = Some(index);
-                }
-            }
-        }
-
-        if let Some(index) = best_index {
-            return self.queue.remove(index);
-        }
-
-        self.dequeue()
+    fn iter(&self) -> impl Iterator<Item = TaskId> + '_ {
+        self.queue.iter().copied()
     }
 
     fn is_empty(&self) -> bool {
@@ -239,6 +399,8 @@ pub struct Scheduler {
     tasks: std::collections::HashMap<TaskId, TaskInfo>,
     current_task: Option<TaskId>,
     current_ticks: u64,
+    /// Lowest virtual runtime handed out so far (weighted fair policy)
+    min_vruntime: u64,
     /// Audit log for scheduling events (test-only)
     audit_log: Vec<ScheduleEvent>,
 }
@@ -257,6 +419,7 @@ impl Scheduler {
             tasks: std::collections::HashMap::new(),
             current_task: None,
             current_ticks: 0,
+            min_vruntime: 0,
             audit_log: Vec::new(),
         }
     }
@@ -269,6 +432,9 @@ impl Scheduler {
             state: TaskState::Runnable,
             ticks_in_quantum: 0,
             realtime: None,
+            params: SchedParams::default(),
+            ready_since: self.current_ticks,
+            vruntime: 0,
         };
         self.tasks.insert(task_id, task_info);
         self.enqueue_runnable(task_id);
@@ -278,28 +444,28 @@ impl Scheduler {
     ///
     /// Returns None if no tasks are runnable.
     pub fn dequeue_next(&mut self) -> Option<TaskId> {
-        let next = match self.config.realtime_policy {
-            RealTimePolicy::None => self.run_queue.dequeue(),
-            RealTimePolicy::EarliestDeadlineFirst => {
-                let deadlines: std::collections::HashMap<TaskId, u64> = self
-                    .tasks
-                    .iter()
-                    .filter_map(|(task_id, info)| {
-                        info.realtime
-                            .as_ref()
-                            .map(|realtime| (*task_id, realtime.next_deadline))
-                    })
-                    .collect();
-
-                self.run_queue
-                    .dequeue_earliest_deadline(|task_id| deadlines.get(&task_id).copied())
-            }
-        };
+        let next = self
+            .select_index()
+            .and_then(|index| self.run_queue.remove_at(index));
 
         if let Some(task_id) = next {
+            let aged = self.tasks.get(&task_id).and_then(|info| {
+                let effective = self.effective_priority_of(info);
+                (effective > info.params.priority).then_some((info.params.priority, effective))
+            });
+            if let Some((base_priority, effective_priority)) = aged {
+                self.audit_log.push(ScheduleEvent::PriorityAged {
+                    task_id,
+                    base_priority,
+                    effective_priority,
+                    timestamp_ticks: self.current_ticks,
+                });
+            }
+
             // Reset quantum counter for this task
             if let Some(task_info) = self.tasks.get_mut(&task_id) {
                 task_info.ticks_in_quantum = 0;
+                self.min_vruntime = self.min_vruntime.max(task_info.vruntime);
             }
             self.current_task = Some(task_id);
 
@@ -327,6 +493,9 @@ impl Scheduler {
         if let Some(task_id) = self.current_task {
             if let Some(task_info) = self.tasks.get_mut(&task_id) {
                 task_info.ticks_in_quantum += delta_ticks;
+                task_info.vruntime = task_info
+                    .vruntime
+                    .saturating_add(weighted_runtime(delta_ticks, task_info.params.weight));
                 if let Some(realtime) = task_info.realtime.as_mut() {
                     realtime.remaining_budget =
                         realtime.remaining_budget.saturating_sub(delta_ticks);
@@ -372,6 +541,45 @@ impl Scheduler {
         Ok(())
     }
 
+    /// Sets scheduling parameters for a task
+    ///
+    /// Takes effect at the next scheduling decision.
+    pub fn set_sched_params(
+        &mut self,
+        task_id: TaskId,
+        params: SchedParams,
+    ) -> Result<(), SchedulerError> {
+        let task_info = self
+            .tasks
+            .get_mut(&task_id)
+            .ok_or(SchedulerError::TaskNotFound(task_id))?;
+        task_info.params = SchedParams::new(params.priority, params.weight);
+
+        self.audit_log.push(ScheduleEvent::ParamsChanged {
+            task_id,
+            params: task_info.params,
+            timestamp_ticks: self.current_ticks,
+        });
+        Ok(())
+    }
+
+    /// Returns the scheduling parameters for a task
+    pub fn sched_params(&self, task_id: TaskId) -> Option<SchedParams> {
+        self.tasks.get(&task_id).map(|info| info.params)
+    }
+
+    /// Returns the task's priority including any aging boost
+    pub fn effective_priority(&self, task_id: TaskId) -> Option<u8> {
+        self.tasks
+            .get(&task_id)
+            .map(|info| self.effective_priority_of(info))
+    }
+
+    /// Returns the task's weighted virtual runtime
+    pub fn virtual_runtime(&self, task_id: TaskId) -> Option<u64> {
+        self.tasks.get(&task_id).map(|info| info.vruntime)
+    }
+
     /// Returns the real-time parameters for a task.
     pub fn real_time_params(&self, task_id: TaskId) -> Option<RealTimeParams> {
         self.tasks
@@ -396,7 +604,8 @@ impl Scheduler {
     /// Wakes up tasks that have reached their wake_tick
     ///
     /// This should be called after advancing time to unblock tasks
-    /// that were sleeping and are now ready to run.
+    /// that were sleeping and are now ready to run. Tasks waiting on
+    /// channels are woken here only when their deadline has passed.
     pub fn wake_ready_tasks(&mut self) {
         let current_ticks = self.current_ticks;
         let tasks_to_wake: Vec<TaskId> = self
@@ -415,6 +624,21 @@ impl Scheduler {
         for task_id in tasks_to_wake {
             self.unblock_task(task_id);
         }
+
+        let timed_out: Vec<TaskId> = self
+            .tasks
+            .iter()
+            .filter_map(|(task_id, info)| match info.state {
+                TaskState::Waiting {
+                    deadline_tick: Some(deadline_tick),
+                } if current_ticks >= deadline_tick => Some(*task_id),
+                _ => None,
+            })
+            .collect();
+
+        for task_id in timed_out {
+            self.wake_waiting(task_id, WakeReason::DeadlineReached);
+        }
     }
 
     /// Checks if the current task should be preempted
@@ -490,6 +714,55 @@ impl Scheduler {
         }
     }
 
+    /// Blocks a task until one of its channels becomes ready
+    ///
+    /// Waiting tasks are removed from the run queue and consume no quanta.
+    /// The kernel calls `wake_waiting` when a message arrives; if a
+    /// `deadline_tick` is given the scheduler also wakes the task once
+    /// current_ticks >= deadline_tick.
+    pub fn wait_on_channels(&mut self, task_id: TaskId, deadline_tick: Option<u64>) {
+        let Some(task_info) = self.tasks.get_mut(&task_id) else {
+            return;
+        };
+        if matches!(task_info.state, TaskState::Exited | TaskState::Cancelled) {
+            return;
+        }
+        task_info.state = TaskState::Waiting { deadline_tick };
+        task_info.ticks_in_quantum = 0;
+        self.run_queue.remove(task_id);
+        if self.current_task == Some(task_id) {
+            self.current_task = None;
+        }
+
+        self.audit_log.push(ScheduleEvent::TaskWaiting {
+            task_id,
+            deadline_tick,
+            timestamp_ticks: self.current_ticks,
+        });
+    }
+
+    /// Wakes a task that is waiting on channels
+    ///
+    /// Returns true if the task was waiting and is now runnable.
+    pub fn wake_waiting(&mut self, task_id: TaskId, reason: WakeReason) -> bool {
+        let Some(task_info) = self.tasks.get_mut(&task_id) else {
+            return false;
+        };
+        if !matches!(task_info.state, TaskState::Waiting { .. }) {
+            return false;
+        }
+        task_info.state = TaskState::Runnable;
+        task_info.ticks_in_quantum = 0;
+        self.enqueue_runnable(task_id);
+
+        self.audit_log.push(ScheduleEvent::TaskWoken {
+            task_id,
+            reason,
+            timestamp_ticks: self.current_ticks,
+        });
+        true
+    }
+
     /// Marks a task as runnable (unblocks it)
     ///
     /// The task is added to the run queue.
@@ -579,11 +852,91 @@ impl Scheduler {
         &self.audit_log
     }
 
+    /// Returns the tasks in the order they were selected to run
+    ///
+    /// Derived from the audit log, so tests can assert on the schedule.
+    pub fn selection_order(&self) -> Vec<TaskId> {
+        self.audit_log
+            .iter()
+            .filter_map(|event| match event {
+                ScheduleEvent::TaskSelected { task_id, .. } => Some(*task_id),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Clears the audit log
     pub fn clear_audit_log(&mut self) {
         self.audit_log.clear();
     }
 
+    /// Picks the run-queue index of the next task
+    ///
+    /// Real-time tasks under EDF come first; otherwise the configured policy
+    /// decides. Ties always go to the task nearest the front of the queue.
+    fn select_index(&self) -> Option<usize> {
+        if self.config.realtime_policy == RealTimePolicy::EarliestDeadlineFirst {
+            let earliest = self
+                .run_queue
+                .iter()
+                .enumerate()
+                .filter_map(|(index, task_id)| {
+                    self.task_deadline(task_id)
+                        .map(|deadline| (index, deadline))
+                })
+                .min_by_key(|&(index, deadline)| (deadline, index));
+            if let Some((index, _)) = earliest {
+                return Some(index);
+            }
+        }
+
+        if self.run_queue.is_empty() {
+            return None;
+        }
+
+        match self.config.policy {
+            SchedulingPolicy::RoundRobin => Some(0),
+            SchedulingPolicy::Priority { .. } => self
+                .run_queue
+                .iter()
+                .enumerate()
+                .max_by_key(|&(index, task_id)| {
+                    let priority = self
+                        .tasks
+                        .get(&task_id)
+                        .map(|info| self.effective_priority_of(info))
+                        .unwrap_or(0);
+                    (priority, core::cmp::Reverse(index))
+                })
+                .map(|(index, _)| index),
+            SchedulingPolicy::WeightedFair => self
+                .run_queue
+                .iter()
+                .enumerate()
+                .min_by_key(|&(index, task_id)| {
+                    let vruntime = self
+                        .tasks
+                        .get(&task_id)
+                        .map_or(u64::MAX, |info| info.vruntime);
+                    (vruntime, index)
+                })
+                .map(|(index, _)| index),
+        }
+    }
+
+    fn effective_priority_of(&self, info: &TaskInfo) -> u8 {
+        match self.config.policy {
+            SchedulingPolicy::Priority {
+                aging_interval_ticks,
+            } if aging_interval_ticks > 0 && info.state == TaskState::Runnable => {
+                let waited = self.current_ticks.saturating_sub(info.ready_since);
+                let boost = (waited / aging_interval_ticks).min(MAX_PRIORITY as u64) as u8;
+                info.params.priority.saturating_add(boost).min(MAX_PRIORITY)
+            }
+            _ => info.params.priority,
+        }
+    }
+
     fn task_deadline(&self, task_id: TaskId) -> Option<u64> {
         self.tasks
             .get(&task_id)
@@ -591,6 +944,15 @@ impl Scheduler {
     }
 
     fn enqueue_runnable(&mut self, task_id: TaskId) {
+        let current_ticks = self.current_ticks;
+        let min_vruntime = self.min_vruntime;
+        if let Some(info) = self.tasks.get_mut(&task_id) {
+            info.ready_since = current_ticks;
+            // Tasks returning from sleep start level with the queue instead
+            // of claiming the CPU time they did not use
+            info.vruntime = info.vruntime.max(min_vruntime);
+        }
+
         match self.config.realtime_policy {
             RealTimePolicy::None => self.run_queue.enqueue(task_id),
             RealTimePolicy::EarliestDeadlineFirst => {
@@ -666,6 +1028,12 @@ impl Default for Scheduler {
     }
 }
 
+/// Scales real ticks into virtual runtime for a task of the given weight
+fn weighted_runtime(delta_ticks: u64, weight: u32) -> u64 {
+    let scaled = delta_ticks as u128 * DEFAULT_WEIGHT as u128 / weight.max(1) as u128;
+    scaled.min(u64::MAX as u128) as u64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -727,6 +1095,7 @@ mod tests {
             quantum_ticks: 10,
             max_steps_per_tick: None,
             realtime_policy: RealTimePolicy::None,
+            ..Default::default()
         };
         let mut scheduler = Scheduler::with_config(config);
         let task = TaskId::new();
@@ -756,6 +1125,7 @@ mod tests {
             quantum_ticks: 5,
             max_steps_per_tick: None,
             realtime_policy: RealTimePolicy::None,
+            ..Default::default()
         };
         let mut scheduler = Scheduler::with_config(config);
         let task1 = TaskId::new();
@@ -864,6 +1234,7 @@ mod tests {
             quantum_ticks: 10,
             max_steps_per_tick: None,
             realtime_policy: RealTimePolicy::None,
+            ..Default::default()
         };
         let mut scheduler = Scheduler::with_config(config);
         let task = TaskId::new();
@@ -970,6 +1341,7 @@ mod tests {
             quantum_ticks: 5,
             max_steps_per_tick: None,
             realtime_policy: RealTimePolicy::None,
+            ..Default::default()
         };
         let mut scheduler = Scheduler::with_config(config);
         let task1 = TaskId::new();
@@ -1114,6 +1486,7 @@ mod tests {
             quantum_ticks: 10,
             max_steps_per_tick: None,
             realtime_policy: RealTimePolicy::EarliestDeadlineFirst,
+            ..Default::default()
         };
         let mut scheduler = Scheduler::with_config(config);
         let task1 = TaskId::new();
@@ -1153,6 +1526,7 @@ mod tests {
             quantum_ticks: 10,
             max_steps_per_tick: None,
             realtime_policy: RealTimePolicy::EarliestDeadlineFirst,
+            ..Default::default()
         };
         let mut scheduler = Scheduler::with_config(config);
         let task = TaskId::new();
@@ -1181,4 +1555,214 @@ mod tests {
         scheduler.on_tick_advanced(15);
         assert_eq!(scheduler.task_state(task), Some(TaskState::Runnable));
     }
+
+    #[test]
+    fn test_waiting_task_leaves_run_queue() {
+        let mut scheduler = Scheduler::new();
+        let waiter = TaskId::new();
+        let worker = TaskId::new();
+
+        scheduler.enqueue(waiter);
+        scheduler.enqueue(worker);
+        scheduler.wait_on_channels(waiter, None);
+
+        assert_eq!(
+            scheduler.task_state(waiter),
+            Some(TaskState::Waiting {
+                deadline_tick: None
+            })
+        );
+        assert_eq!(scheduler.runnable_count(), 1);
+        assert_eq!(scheduler.dequeue_next(), Some(worker));
+
+        // Without a deadline, time alone never wakes the task
+        scheduler.on_tick_advanced(1_000);
+        assert!(matches!(
+            scheduler.task_state(waiter),
+            Some(TaskState::Waiting { .. })
+        ));
+    }
+
+    #[test]
+    fn test_wake_waiting_on_channel_ready() {
+        let mut scheduler = Scheduler::new();
+        let task = TaskId::new();
+
+        scheduler.enqueue(task);
+        scheduler.dequeue_next();
+        scheduler.wait_on_channels(task, Some(50));
+        assert_eq!(scheduler.current_task(), None);
+
+        assert!(scheduler.wake_waiting(task, WakeReason::ChannelReady));
+        assert_eq!(scheduler.task_state(task), Some(TaskState::Runnable));
+        assert_eq!(scheduler.runnable_count(), 1);
+
+        // Waking twice is a no-op
+        assert!(!scheduler.wake_waiting(task, WakeReason::ChannelReady));
+
+        assert!(scheduler.audit_log().contains(&ScheduleEvent::TaskWoken {
+            task_id: task,
+            reason: WakeReason::ChannelReady,
+            timestamp_ticks: 0,
+        }));
+    }
+
+    #[test]
+    fn test_waiting_task_woken_at_deadline() {
+        let mut scheduler = Scheduler::new();
+        let task = TaskId::new();
+
+        scheduler.enqueue(task);
+        scheduler.wait_on_channels(task, Some(20));
+
+        scheduler.on_tick_advanced(19);
+        assert!(matches!(
+            scheduler.task_state(task),
+            Some(TaskState::Waiting { .. })
+        ));
+
+        scheduler.on_tick_advanced(1);
+        assert_eq!(scheduler.task_state(task), Some(TaskState::Runnable));
+        assert!(scheduler.audit_log().contains(&ScheduleEvent::TaskWoken {
+            task_id: task,
+            reason: WakeReason::DeadlineReached,
+            timestamp_ticks: 20,
+        }));
+    }
+
+    fn policy_scheduler(policy: SchedulingPolicy) -> Scheduler {
+        Scheduler::with_config(SchedulerConfig {
+            quantum_ticks: 5,
+            max_steps_per_tick: None,
+            realtime_policy: RealTimePolicy::None,
+            policy,
+            ..Default::default()
+        })
+    }
+
+    /// Runs `slices` full quanta and returns the tasks that ran, in order
+    fn run_slices(scheduler: &mut Scheduler, slices: usize) -> Vec<TaskId> {
+        let mut order = Vec::new();
+        for _ in 0..slices {
+            let Some(task_id) = scheduler.dequeue_next() else {
+                break;
+            };
+            order.push(task_id);
+            scheduler.on_tick_advanced(scheduler.config.quantum_ticks);
+            scheduler.preempt_current();
+        }
+        order
+    }
+
+    #[test]
+    fn test_priority_policy_runs_highest_first() {
+        let mut scheduler = policy_scheduler(SchedulingPolicy::Priority {
+            aging_interval_ticks: 0,
+        });
+        let background = TaskId::new();
+        let editor = TaskId::new();
+        scheduler.enqueue(background);
+        scheduler.enqueue(editor);
+        scheduler
+            .set_sched_params(background, SchedParams::new(8, DEFAULT_WEIGHT))
+            .unwrap();
+        scheduler
+            .set_sched_params(editor, SchedParams::new(16, DEFAULT_WEIGHT))
+            .unwrap();
+
+        // Without aging the background task never runs
+        assert_eq!(run_slices(&mut scheduler, 4), vec![editor; 4]);
+    }
+
+    #[test]
+    fn test_priority_aging_prevents_starvation() {
+        let mut scheduler = policy_scheduler(SchedulingPolicy::Priority {
+            aging_interval_ticks: 5,
+        });
+        let background = TaskId::new();
+        let editor = TaskId::new();
+        scheduler.enqueue(background);
+        scheduler.enqueue(editor);
+        scheduler
+            .set_sched_params(background, SchedParams::new(14, DEFAULT_WEIGHT))
+            .unwrap();
+        scheduler
+            .set_sched_params(editor, SchedParams::new(16, DEFAULT_WEIGHT))
+            .unwrap();
+
+        // Background gains a level per 5 ticks waited and ties go to the
+        // task that has waited longest
+        let order = run_slices(&mut scheduler, 4);
+        assert_eq!(order, vec![editor, editor, background, editor]);
+        assert!(scheduler.audit_log().iter().any(|event| matches!(
+            event,
+            ScheduleEvent::PriorityAged {
+                task_id,
+                base_priority: 14,
+                effective_priority: 16,
+                ..
+            } if *task_id == background
+        )));
+    }
+
+    #[test]
+    fn test_weighted_fair_shares_by_weight() {
+        let mut scheduler = policy_scheduler(SchedulingPolicy::WeightedFair);
+        let heavy = TaskId::new();
+        let light = TaskId::new();
+        scheduler.enqueue(heavy);
+        scheduler.enqueue(light);
+        scheduler
+            .set_sched_params(heavy, SchedParams::new(16, 3 * DEFAULT_WEIGHT))
+            .unwrap();
+        scheduler
+            .set_sched_params(light, SchedParams::new(16, DEFAULT_WEIGHT))
+            .unwrap();
+
+        let order = run_slices(&mut scheduler, 8);
+        let heavy_slices = order.iter().filter(|&&id| id == heavy).count();
+        assert_eq!(heavy_slices, 6);
+        assert_eq!(order.len() - heavy_slices, 2);
+        assert_eq!(scheduler.selection_order(), order);
+    }
+
+    #[test]
+    fn test_weighted_fair_woken_task_does_not_monopolize() {
+        let mut scheduler = policy_scheduler(SchedulingPolicy::WeightedFair);
+        let busy = TaskId::new();
+        let sleeper = TaskId::new();
+        scheduler.enqueue(busy);
+        scheduler.enqueue(sleeper);
+        scheduler.block_task(sleeper, 50);
+
+        run_slices(&mut scheduler, 10);
+        assert_eq!(scheduler.task_state(sleeper), Some(TaskState::Runnable));
+
+        // The woken task starts level with the busy one, so they alternate
+        let order = run_slices(&mut scheduler, 4);
+        assert_eq!(order.iter().filter(|&&id| id == sleeper).count(), 2);
+    }
+
+    #[test]
+    fn test_task_class_table_defaults() {
+        let classes = TaskClassTable::default();
+        let component = classes.params_for(IdentityKind::Component, &TrustDomain::user());
+        let stage = classes.params_for(IdentityKind::PipelineStage, &TrustDomain::user());
+        let sandboxed = classes.params_for(IdentityKind::Component, &TrustDomain::sandbox());
+
+        assert!(component.priority > stage.priority);
+        assert!(component.weight > stage.weight);
+        assert!(sandboxed.priority < stage.priority);
+        assert_eq!(
+            TaskClassTable::empty().params_for(IdentityKind::System, &TrustDomain::core()),
+            SchedParams::default()
+        );
+    }
+
+    #[test]
+    fn test_sched_params_clamped() {
+        let params = SchedParams::new(200, 0);
+        assert_eq!(params.priority, MAX_PRIORITY);
+        assert_eq!(params.weight, 1);
+    }
 }
@@ -1,4 +1,10 @@
 //! SMP bring-up: multi-core scheduler and per-core time sources.
+//!
+//! Tasks are spread across cores round-robin at spawn. Each task carries an
+//! [`AffinityMask`] restricting where it may run. When a core runs out of
+//! work it steals from the most loaded core, taking the most recently queued
+//! task that is allowed to move. Victim and task selection break ties by
+//! core index and queue position, so schedules stay deterministic.
 
 use crate::scheduler::{ExitReason, PreemptionReason, TaskState};
 use core_types::TaskId;
@@ -35,6 +41,67 @@ impl PerCoreTimeSources {
     }
 }
 
+/// Set of cores a task may run on (up to 64 cores).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AffinityMask(u64);
+
+impl AffinityMask {
+    /// Allows every core.
+    pub const fn all() -> Self {
+        Self(u64::MAX)
+    }
+
+    /// Allows no core (invalid as a task affinity).
+    pub const fn none() -> Self {
+        Self(0)
+    }
+
+    /// Allows a single core.
+    pub fn single(core_id: CoreId) -> Self {
+        Self::none().with_core(core_id)
+    }
+
+    /// Allows the given cores.
+    pub fn from_cores(cores: &[CoreId]) -> Self {
+        cores
+            .iter()
+            .fold(Self::none(), |mask, core| mask.with_core(*core))
+    }
+
+    /// Adds a core to the mask.
+    pub fn with_core(self, core_id: CoreId) -> Self {
+        if core_id.0 < 64 {
+            Self(self.0 | (1 << core_id.0))
+        } else {
+            self
+        }
+    }
+
+    /// Returns true if the task may run on the core.
+    pub fn allows(&self, core_id: CoreId) -> bool {
+        core_id.0 < 64 && self.0 & (1 << core_id.0) != 0
+    }
+
+    /// Returns true if any of the first `core_count` cores is allowed.
+    pub fn allows_any(&self, core_count: usize) -> bool {
+        (0..core_count.min(64)).any(|idx| self.allows(CoreId(idx)))
+    }
+}
+
+impl Default for AffinityMask {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// SMP scheduler errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmpError {
+    TaskNotFound(TaskId),
+    /// The mask allows none of the configured cores.
+    EmptyAffinity(TaskId),
+}
+
 /// SMP scheduler configuration.
 #[derive(Debug, Clone)]
 pub struct SmpConfig {
@@ -51,6 +118,15 @@ impl Default for SmpConfig {
     }
 }
 
+/// Why a task moved between cores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrationReason {
+    /// An idle core stole the task from a loaded core.
+    WorkStealing,
+    /// The task's affinity no longer allowed its core.
+    AffinityChanged,
+}
+
 /// Scheduling event tagged with core.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CoreScheduleEvent {
@@ -71,6 +147,13 @@ pub enum CoreScheduleEvent {
         reason: ExitReason,
         timestamp_ticks: u64,
     },
+    Migrated {
+        task_id: TaskId,
+        from_core: CoreId,
+        to_core: CoreId,
+        reason: MigrationReason,
+        timestamp_ticks: u64,
+    },
 }
 
 #[derive(Debug)]
@@ -88,11 +171,17 @@ impl CoreState {
             ticks_in_quantum: 0,
         }
     }
+
+    /// Running plus queued tasks.
+    fn load(&self) -> usize {
+        self.run_queue.len() + usize::from(self.current_task.is_some())
+    }
 }
 
 #[derive(Debug)]
 struct TaskInfo {
     _state: TaskState,
+    affinity: AffinityMask,
 }
 
 /// Deterministic multi-core scheduler.
@@ -101,6 +190,8 @@ pub struct MultiCoreScheduler {
     cores: Vec<CoreState>,
     tasks: HashMap<TaskId, TaskInfo>,
     next_core: usize,
+    /// Idle cores steal queued tasks from the most loaded core.
+    work_stealing: bool,
     audit_log: Vec<CoreScheduleEvent>,
 }
 
@@ -115,10 +206,20 @@ impl MultiCoreScheduler {
             cores,
             tasks: HashMap::new(),
             next_core: 0,
+            work_stealing: false,
             audit_log: Vec::new(),
         }
     }
 
+    /// Enables or disables work stealing (disabled by default).
+    pub fn set_work_stealing(&mut self, enabled: bool) {
+        self.work_stealing = enabled;
+    }
+
+    pub fn work_stealing(&self) -> bool {
+        self.work_stealing
+    }
+
     pub fn core_count(&self) -> usize {
         self.cores.len()
     }
@@ -138,16 +239,97 @@ impl MultiCoreScheduler {
     }
 
     pub fn enqueue(&mut self, task_id: TaskId) {
+        // The full mask always allows core 0, so this cannot fail.
+        let _ = self.enqueue_with_affinity(task_id, AffinityMask::all());
+    }
+
+    /// Enqueues a task restricted to the cores in `affinity`.
+    ///
+    /// The task goes to the next allowed core in round-robin order.
+    pub fn enqueue_with_affinity(
+        &mut self,
+        task_id: TaskId,
+        affinity: AffinityMask,
+    ) -> Result<CoreId, SmpError> {
+        let core_count = self.cores.len();
+        let core_idx = (0..core_count)
+            .map(|offset| (self.next_core + offset) % core_count)
+            .find(|idx| affinity.allows(CoreId(*idx)))
+            .ok_or(SmpError::EmptyAffinity(task_id))?;
+        self.next_core = core_idx + 1;
+
         let task_info = TaskInfo {
             _state: TaskState::Runnable,
+            affinity,
         };
         self.tasks.insert(task_id, task_info);
-        let core_idx = self.next_core % self.cores.len();
-        self.next_core += 1;
         self.cores[core_idx].run_queue.push_back(task_id);
+        Ok(CoreId(core_idx))
+    }
+
+    /// Restricts a task to the cores in `affinity`.
+    ///
+    /// A queued task on a core that is no longer allowed moves immediately;
+    /// a running task moves when it is next preempted.
+    pub fn set_affinity(
+        &mut self,
+        task_id: TaskId,
+        affinity: AffinityMask,
+        timestamp_ticks: u64,
+    ) -> Result<(), SmpError> {
+        if !affinity.allows_any(self.cores.len()) {
+            return Err(SmpError::EmptyAffinity(task_id));
+        }
+        let task_info = self
+            .tasks
+            .get_mut(&task_id)
+            .ok_or(SmpError::TaskNotFound(task_id))?;
+        task_info.affinity = affinity;
+
+        let queued_on = self
+            .cores
+            .iter()
+            .position(|core| core.run_queue.contains(&task_id))
+            .map(CoreId);
+        if let Some(from_core) = queued_on.filter(|core| !affinity.allows(*core)) {
+            self.cores[from_core.0]
+                .run_queue
+                .retain(|queued| *queued != task_id);
+            self.place_on_allowed_core(
+                task_id,
+                from_core,
+                MigrationReason::AffinityChanged,
+                timestamp_ticks,
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the affinity mask of a task.
+    pub fn task_affinity(&self, task_id: TaskId) -> Option<AffinityMask> {
+        self.tasks.get(&task_id).map(|info| info.affinity)
+    }
+
+    /// Returns the core currently running or queueing a task.
+    pub fn task_core(&self, task_id: TaskId) -> Option<CoreId> {
+        self.cores
+            .iter()
+            .position(|core| {
+                core.current_task == Some(task_id) || core.run_queue.contains(&task_id)
+            })
+            .map(CoreId)
+    }
+
+    /// Returns the number of running plus queued tasks on a core.
+    pub fn core_load(&self, core_id: CoreId) -> usize {
+        self.cores[core_id.0].load()
     }
 
     pub fn dequeue_next(&mut self, core_id: CoreId, timestamp_ticks: u64) -> Option<TaskId> {
+        if self.cores[core_id.0].run_queue.is_empty() && self.work_stealing {
+            self.steal_for(core_id, timestamp_ticks);
+        }
+
         let core = &mut self.cores[core_id.0];
         let task_id = core.run_queue.pop_front()?;
         core.current_task = Some(task_id);
@@ -187,7 +369,21 @@ impl MultiCoreScheduler {
                 timestamp_ticks,
             });
             core.ticks_in_quantum = 0;
-            core.run_queue.push_back(task_id);
+
+            let allowed_here = self
+                .tasks
+                .get(&task_id)
+                .is_none_or(|info| info.affinity.allows(core_id));
+            if allowed_here {
+                self.cores[core_id.0].run_queue.push_back(task_id);
+            } else {
+                self.place_on_allowed_core(
+                    task_id,
+                    core_id,
+                    MigrationReason::AffinityChanged,
+                    timestamp_ticks,
+                );
+            }
         }
     }
 
@@ -256,6 +452,69 @@ impl MultiCoreScheduler {
     pub fn audit_log(&self) -> &[CoreScheduleEvent] {
         &self.audit_log
     }
+
+    /// Moves one queued task from the most loaded core to an idle core.
+    ///
+    /// Only cores with tasks waiting behind a running task are victims; the
+    /// most recently queued task that may run on `thief` is taken.
+    fn steal_for(&mut self, thief: CoreId, timestamp_ticks: u64) -> Option<TaskId> {
+        let mut victims: Vec<usize> = (0..self.cores.len())
+            .filter(|idx| *idx != thief.0 && self.cores[*idx].load() >= 2)
+            .collect();
+        victims.sort_by_key(|idx| (core::cmp::Reverse(self.cores[*idx].load()), *idx));
+
+        for victim in victims {
+            let position = self.cores[victim].run_queue.iter().rposition(|task_id| {
+                self.tasks
+                    .get(task_id)
+                    .is_some_and(|info| info.affinity.allows(thief))
+            });
+            if let Some(position) = position {
+                let task_id = self.cores[victim].run_queue.remove(position)?;
+                self.cores[thief.0].run_queue.push_back(task_id);
+                self.audit_log.push(CoreScheduleEvent::Migrated {
+                    task_id,
+                    from_core: CoreId(victim),
+                    to_core: thief,
+                    reason: MigrationReason::WorkStealing,
+                    timestamp_ticks,
+                });
+                return Some(task_id);
+            }
+        }
+        None
+    }
+
+    /// Queues a task on its least loaded allowed core, recording the move.
+    fn place_on_allowed_core(
+        &mut self,
+        task_id: TaskId,
+        from_core: CoreId,
+        reason: MigrationReason,
+        timestamp_ticks: u64,
+    ) {
+        let affinity = self
+            .tasks
+            .get(&task_id)
+            .map(|info| info.affinity)
+            .unwrap_or_default();
+        let to_core = (0..self.cores.len())
+            .filter(|idx| affinity.allows(CoreId(*idx)))
+            .min_by_key(|idx| (self.cores[*idx].load(), *idx))
+            .map(CoreId)
+            .unwrap_or(from_core);
+
+        self.cores[to_core.0].run_queue.push_back(task_id);
+        if to_core != from_core {
+            self.audit_log.push(CoreScheduleEvent::Migrated {
+                task_id,
+                from_core,
+                to_core,
+                reason,
+                timestamp_ticks,
+            });
+        }
+    }
 }
 
 /// SMP runtime combining scheduler + per-core time sources.
@@ -315,4 +574,122 @@ mod tests {
         runtime.advance_core_time(CoreId(0), 3);
         assert!(runtime.scheduler.should_preempt(CoreId(0)));
     }
+
+    fn queue_tasks(scheduler: &mut MultiCoreScheduler, count: usize) -> Vec<TaskId> {
+        (0..count)
+            .map(|_| {
+                let task_id = TaskId::new();
+                scheduler.enqueue(task_id);
+                task_id
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_affinity_mask() {
+        let mask = AffinityMask::from_cores(&[CoreId(0), CoreId(2)]);
+        assert!(mask.allows(CoreId(0)));
+        assert!(!mask.allows(CoreId(1)));
+        assert!(mask.allows(CoreId(2)));
+        assert!(!AffinityMask::single(CoreId(3)).allows_any(2));
+        assert!(AffinityMask::all().allows_any(4));
+    }
+
+    #[test]
+    fn test_idle_core_steals_from_loaded_core() {
+        let mut scheduler = MultiCoreScheduler::new(SmpConfig::default());
+        scheduler.set_work_stealing(true);
+        let pinned = TaskId::new();
+        let tasks = queue_tasks(&mut scheduler, 2);
+        scheduler
+            .enqueue_with_affinity(pinned, AffinityMask::single(CoreId(0)))
+            .unwrap();
+        // Core 0: [tasks[0], pinned], core 1: [tasks[1]]
+        assert_eq!(scheduler.task_core(pinned), Some(CoreId(0)));
+
+        let late = TaskId::new();
+        scheduler
+            .enqueue_with_affinity(late, AffinityMask::single(CoreId(0)))
+            .unwrap();
+        scheduler
+            .set_affinity(late, AffinityMask::all(), 0)
+            .unwrap();
+        // Core 0: [tasks[0], pinned, late]
+
+        assert_eq!(scheduler.dequeue_next(CoreId(0), 0), Some(tasks[0]));
+        assert_eq!(scheduler.dequeue_next(CoreId(1), 0), Some(tasks[1]));
+        scheduler.exit_task(CoreId(1), tasks[1], ExitReason::Normal, 1);
+
+        // Core 1 is idle: it takes the newest task it is allowed to run
+        assert_eq!(scheduler.dequeue_next(CoreId(1), 2), Some(late));
+        assert!(scheduler
+            .audit_log()
+            .contains(&CoreScheduleEvent::Migrated {
+                task_id: late,
+                from_core: CoreId(0),
+                to_core: CoreId(1),
+                reason: MigrationReason::WorkStealing,
+                timestamp_ticks: 2,
+            }));
+
+        // The pinned task is never stolen
+        scheduler.exit_task(CoreId(1), late, ExitReason::Normal, 3);
+        assert_eq!(scheduler.dequeue_next(CoreId(1), 4), None);
+        assert_eq!(scheduler.task_core(pinned), Some(CoreId(0)));
+    }
+
+    #[test]
+    fn test_no_stealing_by_default() {
+        let mut scheduler = MultiCoreScheduler::new(SmpConfig::default());
+        assert!(!scheduler.work_stealing());
+        let tasks = queue_tasks(&mut scheduler, 3);
+        // Core 0: [tasks[0], tasks[2]], core 1: [tasks[1]]
+        scheduler.dequeue_next(CoreId(0), 0);
+        scheduler.dequeue_next(CoreId(1), 0);
+        scheduler.exit_task(CoreId(1), tasks[1], ExitReason::Normal, 1);
+
+        assert_eq!(scheduler.dequeue_next(CoreId(1), 1), None);
+        assert_eq!(scheduler.core_load(CoreId(0)), 2);
+    }
+
+    #[test]
+    fn test_set_affinity_moves_queued_task() {
+        let mut scheduler = MultiCoreScheduler::new(SmpConfig::default());
+        let tasks = queue_tasks(&mut scheduler, 2);
+
+        scheduler
+            .set_affinity(tasks[0], AffinityMask::single(CoreId(1)), 5)
+            .unwrap();
+        assert_eq!(scheduler.task_core(tasks[0]), Some(CoreId(1)));
+        assert!(scheduler
+            .audit_log()
+            .contains(&CoreScheduleEvent::Migrated {
+                task_id: tasks[0],
+                from_core: CoreId(0),
+                to_core: CoreId(1),
+                reason: MigrationReason::AffinityChanged,
+                timestamp_ticks: 5,
+            }));
+
+        assert_eq!(
+            scheduler.set_affinity(tasks[0], AffinityMask::single(CoreId(7)), 6),
+            Err(SmpError::EmptyAffinity(tasks[0]))
+        );
+    }
+
+    #[test]
+    fn test_running_task_migrates_on_preemption() {
+        let mut scheduler = MultiCoreScheduler::new(SmpConfig::default());
+        let tasks = queue_tasks(&mut scheduler, 1);
+        assert_eq!(scheduler.dequeue_next(CoreId(0), 0), Some(tasks[0]));
+
+        scheduler
+            .set_affinity(tasks[0], AffinityMask::single(CoreId(1)), 1)
+            .unwrap();
+        assert_eq!(scheduler.task_core(tasks[0]), Some(CoreId(0)));
+
+        scheduler.preempt_current(CoreId(0), PreemptionReason::QuantumExpired, 2);
+        assert_eq!(scheduler.task_core(tasks[0]), Some(CoreId(1)));
+        assert_eq!(scheduler.dequeue_next(CoreId(0), 2), None);
+    }
 }
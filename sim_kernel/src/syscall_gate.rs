@@ -9,7 +9,9 @@ use core_types::{
 };
 use identity::ExecutionId;
 use ipc::{ChannelId, MessageEnvelope};
-use kernel_api::{Duration, KernelApi, KernelError, TaskDescriptor, TaskHandle};
+use kernel_api::{
+    Duration, KernelApi, KernelError, TaskDescriptor, TaskHandle, WaitOutcome, WaitSet,
+};
 use serde::{Deserialize, Serialize};
 
 /// Complete syscall set for user tasks.
@@ -30,6 +32,9 @@ pub enum Syscall {
     Recv {
         channel: ChannelId,
     },
+    Select {
+        wait_set: WaitSet,
+    },
 
     // Time operations
     Sleep {
@@ -74,6 +79,7 @@ pub enum SyscallResult {
     TaskHandle(TaskHandle),
     ChannelId(ChannelId),
     Message(MessageEnvelope),
+    WaitOutcome(WaitOutcome),
     Instant(kernel_api::Instant),
     AddressSpaceCap(AddressSpaceCap),
     MemoryRegionCap(MemoryRegionCap),
@@ -204,6 +210,9 @@ impl SyscallGate {
             Syscall::Recv { channel } => kernel
                 .receive_message(channel, None)
                 .map(SyscallResult::Message),
+            Syscall::Select { wait_set } => {
+                kernel.select(&wait_set).map(SyscallResult::WaitOutcome)
+            }
             Syscall::Sleep { duration } => kernel.sleep(duration).map(|_| SyscallResult::Ok),
             Syscall::Now => Ok(SyscallResult::Instant(kernel.now())),
             Syscall::Yield => {
@@ -336,6 +345,7 @@ impl SyscallGate {
             Syscall::CreateChannel => "CreateChannel",
             Syscall::Send { .. } => "Send",
             Syscall::Recv { .. } => "Recv",
+            Syscall::Select { .. } => "Select",
             Syscall::Sleep { .. } => "Sleep",
             Syscall::Now => "Now",
             Syscall::Yield => "Yield",
@@ -502,6 +512,37 @@ mod tests {
         assert_eq!(invocations, 3);
     }
 
+    #[test]
+    fn test_syscall_gate_select() {
+        let mut gate = SyscallGate::new();
+        let mut kernel = SimulatedKernel::new();
+        let exec_id = ExecutionId::new();
+
+        let idle = kernel.create_channel().unwrap();
+        let busy = kernel.create_channel().unwrap();
+        let message = MessageEnvelope::new(
+            ServiceId::new(),
+            "test".to_string(),
+            ipc::SchemaVersion::new(1, 0),
+            ipc::MessagePayload::new(&"hello").unwrap(),
+        );
+        kernel.send_message(busy, message).unwrap();
+
+        let wait_set = WaitSet::new().with_channel(idle).with_channel(busy);
+        let result = gate
+            .execute(&mut kernel, exec_id, Syscall::Select { wait_set }, 1000)
+            .unwrap();
+
+        assert!(matches!(
+            result,
+            SyscallResult::WaitOutcome(WaitOutcome::Ready(channel)) if channel == busy
+        ));
+        assert!(gate.audit_log().has_event(|e| matches!(
+            e,
+            SyscallEvent::Completed { syscall_name, .. } if syscall_name == "Select"
+        )));
+    }
+
     #[test]
     fn test_syscall_gate_bypass_attempt_recording() {
         let mut gate = SyscallGate::new();
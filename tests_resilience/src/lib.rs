@@ -11,6 +11,8 @@
 
 use kernel_api::{KernelApi, TaskDescriptor};
 use services_registry::ServiceRegistry;
+use sim_kernel::chaos::{CampaignReport, ChaosCampaign};
+use sim_kernel::fault_injection::FaultPlan;
 use sim_kernel::SimulatedKernel;
 
 /// Bootstrap helper for tests
@@ -35,3 +37,17 @@ pub fn spawn_test_service(
     let handle = kernel.spawn_task(descriptor)?;
     Ok(handle.task_id)
 }
+
+/// Runs a chaos campaign and panics with the minimal failing plan
+///
+/// The panic message names the failing seed and lists the shrunk faults,
+/// so the failure can be pinned as a regression test with `FaultPlan`.
+pub fn assert_survives_chaos<F>(campaign: &ChaosCampaign, scenario: F) -> CampaignReport
+where
+    F: FnMut(FaultPlan) -> Result<(), String>,
+{
+    match campaign.run(scenario) {
+        Ok(report) => report,
+        Err(failure) => panic!("{}", failure),
+    }
+}
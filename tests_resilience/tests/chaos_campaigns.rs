@@ -0,0 +1,141 @@
+//! Chaos Campaign Tests
+//!
+//! Runs messaging and capability scenarios against seeded random fault
+//! plans. A failure reports the seed and a shrunk, minimal fault list.
+
+use core_types::{Cap, ServiceId};
+use ipc::{MessageEnvelope, MessagePayload, SchemaVersion};
+use kernel_api::{KernelApi, TaskDescriptor};
+use sim_kernel::chaos::{ChaosCampaign, FaultProfile};
+use sim_kernel::fault_injection::{FaultPlan, MessageFault};
+use sim_kernel::SimulatedKernel;
+use std::collections::HashSet;
+use tests_resilience::assert_survives_chaos;
+
+fn message(sequence: u32) -> MessageEnvelope {
+    MessageEnvelope::new(
+        ServiceId::new(),
+        "chaos.ping".to_string(),
+        SchemaVersion::new(1, 0),
+        MessagePayload::new(&sequence).expect("Failed to create payload"),
+    )
+}
+
+/// Sends a burst of messages and drains the channel
+///
+/// Invariant: at-most-once delivery. Every received message was sent and
+/// none is received twice, whatever the faults.
+fn at_most_once_scenario(plan: FaultPlan) -> Result<(), String> {
+    let mut kernel = SimulatedKernel::new().with_fault_plan(plan);
+    let channel = kernel.create_channel().map_err(|err| err.to_string())?;
+
+    let mut sent = HashSet::new();
+    for sequence in 0..8 {
+        let message = message(sequence);
+        let id = message.id;
+        if kernel.send_message(channel, message).is_ok() {
+            sent.insert(id);
+        }
+    }
+    kernel.run_until_idle();
+
+    let mut received = HashSet::new();
+    while let Ok(message) = kernel.receive_message(channel, None) {
+        if !sent.contains(&message.id) {
+            return Err(format!("received unsent message {}", message.id));
+        }
+        if !received.insert(message.id) {
+            return Err(format!("message {} delivered twice", message.id));
+        }
+    }
+    Ok(())
+}
+
+/// Transfers capabilities with messages under faults
+///
+/// Invariants: a capability sent successfully has left the sender, and once
+/// the channel is drained no capability is left in transit.
+fn capability_transfer_scenario(plan: FaultPlan) -> Result<(), String> {
+    let mut kernel = SimulatedKernel::new().with_fault_plan(plan);
+    let sender = kernel
+        .spawn_task(TaskDescriptor::new("sender".to_string()))
+        .map_err(|err| err.to_string())?
+        .task_id;
+    let channel = kernel.create_channel().map_err(|err| err.to_string())?;
+
+    let mut moved = Vec::new();
+    for cap_id in 9_000..9_004u64 {
+        kernel
+            .grant_capability(sender, Cap::new(cap_id))
+            .map_err(|err| err.to_string())?;
+        let envelope = message(cap_id as u32)
+            .with_source(sender)
+            .with_capability(Cap::new(cap_id));
+        if kernel.send_message(channel, envelope).is_ok() {
+            moved.push(cap_id);
+        }
+    }
+    kernel.run_until_idle();
+    while kernel.receive_message(channel, None).is_ok() {}
+
+    if let Some(cap_id) = moved
+        .iter()
+        .find(|cap_id| kernel.is_capability_valid(**cap_id, sender))
+    {
+        return Err(format!("capability {} still held by its sender", cap_id));
+    }
+    // Capabilities still queued (e.g. after a receive crash) stay in transit
+    if kernel.pending_message_count() == 0 && kernel.capabilities_in_transit() != 0 {
+        return Err(format!(
+            "{} capabilities stuck in transit with an empty channel",
+            kernel.capabilities_in_transit()
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_at_most_once_delivery_under_chaos() {
+    let campaign = ChaosCampaign::new(FaultProfile::default().with_max_faults(6)).with_runs(128);
+    let report = assert_survives_chaos(&campaign, at_most_once_scenario);
+    assert_eq!(report.runs, 128);
+    assert!(report.faults_injected > 0);
+}
+
+#[test]
+fn test_capability_transfer_under_chaos() {
+    let campaign = ChaosCampaign::new(FaultProfile::default().with_actions(["chaos.ping"]))
+        .with_seed(1_000)
+        .with_runs(64);
+    assert_survives_chaos(&campaign, capability_transfer_scenario);
+}
+
+#[test]
+fn test_campaign_failure_reports_minimal_plan() {
+    // A scenario that (wrongly) requires lossless delivery
+    let lossless = |plan: FaultPlan| {
+        let mut kernel = SimulatedKernel::new().with_fault_plan(plan);
+        let channel = kernel.create_channel().map_err(|err| err.to_string())?;
+        kernel
+            .send_message(channel, message(0))
+            .map_err(|err| err.to_string())?;
+        kernel.run_until_idle();
+        kernel
+            .receive_message(channel, None)
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    };
+
+    let profile = FaultProfile::default().with_crashes(0.0).with_drops(0.5);
+    let failure = ChaosCampaign::new(profile)
+        .run(lossless)
+        .expect_err("drops must break a lossless scenario");
+
+    assert_eq!(
+        failure.minimal,
+        FaultPlan::new().with_message_fault(MessageFault::DropNext { count: 1 })
+    );
+    assert!(failure
+        .to_string()
+        .contains(&format!("seed {}", failure.seed)));
+}
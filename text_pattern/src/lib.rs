@@ -0,0 +1,26 @@
+#![no_std]
+
+//! # Text Pattern
+//!
+//! Pattern matching over text for search in editors, the filesystem view and
+//! command filtering.
+//!
+//! ## Philosophy
+//!
+//! - **No_std compatible**: Uses alloc but not std
+//! - **Predictable cost**: Matching runs in time linear in the text, whatever
+//!   the pattern; there is no backtracking to blow up
+//! - **Line oriented**: `^` and `$` match at line boundaries, `.` stops at newlines
+//!
+//! ## Syntax
+//!
+//! - Literals, `.`, classes `[a-z]` / `[^0-9]`, escapes `\d \w \s \D \W \S`
+//! - Anchors `^`, `$`, word boundaries `\b`, `\B`
+//! - Groups `(...)`, `(?:...)` and alternation `|`
+//! - Repetition `*`, `+`, `?`, `{n}`, `{n,}`, `{n,m}`; a trailing `?` makes it lazy
+
+extern crate alloc;
+
+pub mod regex;
+
+pub use regex::{escape, Match, Matches, PatternError, Regex};
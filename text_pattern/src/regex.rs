@@ -0,0 +1,798 @@
+//! Regular expressions
+//!
+//! Patterns are parsed into a small instruction program and run by a Pike
+//! VM: every thread advances in lockstep over the text, so no input is read
+//! twice. Threads are kept in priority order, which gives the leftmost match
+//! with the same greedy/lazy choices a backtracking engine would make.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Largest count accepted in `{n,m}`
+const MAX_REPEAT: u32 = 1000;
+
+/// Largest compiled program accepted
+const MAX_PROGRAM: usize = 20_000;
+
+/// Why a pattern could not be compiled
+///
+/// Positions are character offsets into the pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// Pattern ends with a lone backslash
+    TrailingBackslash,
+    /// `(` without `)` or `)` without `(`
+    UnmatchedParen(usize),
+    /// `[` without `]`
+    UnclosedClass(usize),
+    /// Repetition with nothing before it
+    NothingToRepeat(usize),
+    /// Range whose end comes before its start, or a bad `{n,m}`
+    InvalidRange(usize),
+    /// Unknown escape sequence
+    InvalidEscape(usize),
+    /// Compiled program exceeds the size limit
+    TooLarge,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::TrailingBackslash => write!(f, "Pattern ends with a backslash"),
+            PatternError::UnmatchedParen(at) => write!(f, "Unmatched parenthesis at {}", at),
+            PatternError::UnclosedClass(at) => write!(f, "Unclosed character class at {}", at),
+            PatternError::NothingToRepeat(at) => write!(f, "Nothing to repeat at {}", at),
+            PatternError::InvalidRange(at) => write!(f, "Invalid range at {}", at),
+            PatternError::InvalidEscape(at) => write!(f, "Invalid escape at {}", at),
+            PatternError::TooLarge => write!(f, "Pattern is too large"),
+        }
+    }
+}
+
+/// A match, as byte offsets into the searched text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    /// Offset of the first matched byte
+    pub start: usize,
+    /// Offset just past the last matched byte
+    pub end: usize,
+}
+
+impl Match {
+    /// Returns the matched text
+    pub fn as_str<'t>(&self, text: &'t str) -> &'t str {
+        &text[self.start..self.end]
+    }
+
+    /// Returns whether the match is empty
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Escapes every character that has a meaning in patterns
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Assertion {
+    LineStart,
+    LineEnd,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Class {
+    ranges: Vec<(char, char)>,
+    negated: bool,
+}
+
+impl Class {
+    fn contains(&self, c: char, ignore_case: bool) -> bool {
+        let hit = |c: char| self.ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi);
+        let found = hit(c) || (ignore_case && (hit(lower(c)) || hit(upper(c))));
+        found != self.negated
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+        greedy: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    /// Try the first target before the second
+    Split(usize, usize),
+    Jmp(usize),
+    Match,
+}
+
+/// A compiled regular expression
+#[derive(Debug, Clone)]
+pub struct Regex {
+    pattern: String,
+    program: Vec<Inst>,
+    ignore_case: bool,
+}
+
+impl Regex {
+    /// Compiles a pattern
+    pub fn new(pattern: &str) -> Result<Self, PatternError> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+        };
+        let node = parser.parse_alternation()?;
+        if parser.pos < parser.chars.len() {
+            return Err(PatternError::UnmatchedParen(parser.pos));
+        }
+
+        let mut compiler = Compiler {
+            program: Vec::new(),
+        };
+        compiler.compile(&node)?;
+        compiler.emit(Inst::Match)?;
+        Ok(Self {
+            pattern: String::from(pattern),
+            program: compiler.program,
+            ignore_case: false,
+        })
+    }
+
+    /// Makes letters match regardless of case
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Returns the pattern this was compiled from
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns whether the pattern matches anywhere in `text`
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+
+    /// Finds the leftmost match in `text`
+    pub fn find(&self, text: &str) -> Option<Match> {
+        self.find_at(text, 0)
+    }
+
+    /// Finds the leftmost match starting at or after byte offset `start`
+    ///
+    /// Anchors and word boundaries still see the text before `start`.
+    pub fn find_at(&self, text: &str, start: usize) -> Option<Match> {
+        if start > text.len() || !text.is_char_boundary(start) {
+            return None;
+        }
+
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        let mut found = None;
+        let mut pos = start;
+        loop {
+            if found.is_none() {
+                self.add_thread(&mut current, 0, pos, text, pos);
+            }
+            if current.threads.is_empty() && found.is_some() {
+                break;
+            }
+
+            let c = text[pos..].chars().next();
+            for &Thread { pc, start } in &current.threads {
+                match &self.program[pc] {
+                    Inst::Match => {
+                        // Lower-priority threads can only find worse matches
+                        found = Some(Match { start, end: pos });
+                        break;
+                    }
+                    inst => {
+                        if let Some(c) = c {
+                            if self.consumes(inst, c) {
+                                self.add_thread(&mut next, pc + 1, start, text, pos + c.len_utf8());
+                            }
+                        }
+                    }
+                }
+            }
+
+            let Some(c) = c else { break };
+            pos += c.len_utf8();
+            core::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+        found
+    }
+
+    /// Iterates over successive non-overlapping matches
+    pub fn find_iter<'r, 't>(&'r self, text: &'t str) -> Matches<'r, 't> {
+        Matches {
+            regex: self,
+            text,
+            pos: 0,
+            last_end: None,
+        }
+    }
+
+    fn consumes(&self, inst: &Inst, c: char) -> bool {
+        match inst {
+            Inst::Char(expected) => {
+                *expected == c || (self.ignore_case && lower(*expected) == lower(c))
+            }
+            Inst::Any => c != '\n',
+            Inst::Class(class) => class.contains(c, self.ignore_case),
+            _ => false,
+        }
+    }
+
+    /// Adds the thread at `pc`, following jumps and assertions in priority order
+    fn add_thread(&self, list: &mut Threads, pc: usize, start: usize, text: &str, pos: usize) {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if !list.visit(pc) {
+                continue;
+            }
+            match &self.program[pc] {
+                Inst::Jmp(target) => stack.push(*target),
+                Inst::Split(first, second) => {
+                    stack.push(*second);
+                    stack.push(*first);
+                }
+                Inst::Assert(assertion) => {
+                    if holds(*assertion, text, pos) {
+                        stack.push(pc + 1);
+                    }
+                }
+                _ => list.threads.push(Thread { pc, start }),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+/// Iterator over the matches of a [`Regex`]
+#[derive(Debug)]
+pub struct Matches<'r, 't> {
+    regex: &'r Regex,
+    text: &'t str,
+    pos: usize,
+    last_end: Option<usize>,
+}
+
+impl Iterator for Matches<'_, '_> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        loop {
+            let found = self.regex.find_at(self.text, self.pos)?;
+            if found.is_empty() {
+                // Step past an empty match so the search makes progress
+                self.pos = found.end
+                    + self.text[found.end..]
+                        .chars()
+                        .next()
+                        .map_or(1, char::len_utf8);
+                if self.last_end == Some(found.end) {
+                    if self.pos > self.text.len() {
+                        return None;
+                    }
+                    continue;
+                }
+            } else {
+                self.pos = found.end;
+            }
+            self.last_end = Some(found.end);
+            return Some(found);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Thread {
+    pc: usize,
+    start: usize,
+}
+
+/// Threads for one text position, each program counter at most once
+struct Threads {
+    threads: Vec<Thread>,
+    visited: Vec<bool>,
+    visited_list: Vec<usize>,
+}
+
+impl Threads {
+    fn new(size: usize) -> Self {
+        Self {
+            threads: Vec::new(),
+            visited: vec![false; size],
+            visited_list: Vec::new(),
+        }
+    }
+
+    fn visit(&mut self, pc: usize) -> bool {
+        if self.visited[pc] {
+            return false;
+        }
+        self.visited[pc] = true;
+        self.visited_list.push(pc);
+        true
+    }
+
+    fn clear(&mut self) {
+        self.threads.clear();
+        for pc in self.visited_list.drain(..) {
+            self.visited[pc] = false;
+        }
+    }
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn upper(c: char) -> char {
+    c.to_uppercase().next().unwrap_or(c)
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn holds(assertion: Assertion, text: &str, pos: usize) -> bool {
+    let before = text[..pos].chars().next_back();
+    let after = text[pos..].chars().next();
+    match assertion {
+        Assertion::LineStart => before.is_none_or(|c| c == '\n'),
+        Assertion::LineEnd => after.is_none_or(|c| c == '\n'),
+        Assertion::WordBoundary => before.is_some_and(is_word) != after.is_some_and(is_word),
+        Assertion::NotWordBoundary => before.is_some_and(is_word) == after.is_some_and(is_word),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node, PatternError> {
+        let mut branches = vec![self.parse_concat()?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            branches.push(self.parse_concat()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.pop().unwrap()
+        } else {
+            Node::Alternate(branches)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, PatternError> {
+        let mut items = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            items.push(self.parse_repeats(atom)?);
+        }
+        Ok(match items.len() {
+            0 => Node::Empty,
+            1 => items.pop().unwrap(),
+            _ => Node::Concat(items),
+        })
+    }
+
+    fn parse_repeats(&mut self, mut node: Node) -> Result<Node, PatternError> {
+        loop {
+            let at = self.pos;
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') => match self.parse_counts()? {
+                    Some(counts) => counts,
+                    None => return Ok(node),
+                },
+                _ => return Ok(node),
+            };
+            if at == self.pos {
+                self.pos += 1;
+            }
+            let greedy = if self.peek() == Some('?') {
+                self.pos += 1;
+                false
+            } else {
+                true
+            };
+            node = Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+                greedy,
+            };
+        }
+    }
+
+    /// Parses `{n}`, `{n,}` or `{n,m}`; anything else leaves `{` a literal
+    fn parse_counts(&mut self) -> Result<Option<(u32, Option<u32>)>, PatternError> {
+        let start = self.pos;
+        let Some(close) = self.chars[start..].iter().position(|&c| c == '}') else {
+            return Ok(None);
+        };
+        let body: String = self.chars[start + 1..start + close].iter().collect();
+        let number = |s: &str| s.parse::<u32>().ok();
+        let counts = match body.split_once(',') {
+            None => number(&body).map(|n| (n, Some(n))),
+            Some((min, "")) => number(min).map(|n| (n, None)),
+            Some((min, max)) => number(min).zip(number(max)).map(|(n, m)| (n, Some(m))),
+        };
+        let Some((min, max)) = counts else {
+            return Ok(None);
+        };
+        if max.is_some_and(|max| max < min) || max.unwrap_or(min) > MAX_REPEAT {
+            return Err(PatternError::InvalidRange(start));
+        }
+        self.pos = start + close + 1;
+        Ok(Some((min, max)))
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, PatternError> {
+        let at = self.pos;
+        let c = self.chars[at];
+        self.pos += 1;
+        match c {
+            '(' => {
+                if self.chars[self.pos..].starts_with(&['?', ':']) {
+                    self.pos += 2;
+                }
+                let inner = self.parse_alternation()?;
+                if self.peek() != Some(')') {
+                    return Err(PatternError::UnmatchedParen(at));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            '[' => self.parse_class(at),
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Assert(Assertion::LineStart)),
+            '$' => Ok(Node::Assert(Assertion::LineEnd)),
+            '*' | '+' | '?' => Err(PatternError::NothingToRepeat(at)),
+            '\\' => self.parse_escape(at),
+            c => Ok(Node::Char(c)),
+        }
+    }
+
+    fn parse_escape(&mut self, at: usize) -> Result<Node, PatternError> {
+        let c = self.peek().ok_or(PatternError::TrailingBackslash)?;
+        self.pos += 1;
+        Ok(match c {
+            'b' => Node::Assert(Assertion::WordBoundary),
+            'B' => Node::Assert(Assertion::NotWordBoundary),
+            'd' | 'w' | 's' | 'D' | 'W' | 'S' => Node::Class(Class {
+                ranges: perl_class(c.to_ascii_lowercase()),
+                negated: c.is_ascii_uppercase(),
+            }),
+            c => Node::Char(escaped_char(c).ok_or(PatternError::InvalidEscape(at))?),
+        })
+    }
+
+    fn parse_class(&mut self, at: usize) -> Result<Node, PatternError> {
+        let mut class = Class {
+            ranges: Vec::new(),
+            negated: false,
+        };
+        if self.peek() == Some('^') {
+            class.negated = true;
+            self.pos += 1;
+        }
+        let mut first = true;
+        loop {
+            let c = self.peek().ok_or(PatternError::UnclosedClass(at))?;
+            let item_at = self.pos;
+            self.pos += 1;
+            if c == ']' && !first {
+                return Ok(Node::Class(class));
+            }
+            first = false;
+
+            let lo = if c == '\\' {
+                let e = self.peek().ok_or(PatternError::UnclosedClass(at))?;
+                self.pos += 1;
+                if matches!(e, 'd' | 'w' | 's') {
+                    class.ranges.extend(perl_class(e));
+                    continue;
+                }
+                escaped_char(e).ok_or(PatternError::InvalidEscape(item_at))?
+            } else {
+                c
+            };
+
+            let is_range =
+                self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']');
+            if !is_range {
+                class.ranges.push((lo, lo));
+                continue;
+            }
+            self.pos += 1;
+            let mut hi = self.chars[self.pos];
+            self.pos += 1;
+            if hi == '\\' {
+                let e = self.peek().ok_or(PatternError::UnclosedClass(at))?;
+                self.pos += 1;
+                hi = escaped_char(e).ok_or(PatternError::InvalidEscape(item_at))?;
+            }
+            if hi < lo {
+                return Err(PatternError::InvalidRange(item_at));
+            }
+            class.ranges.push((lo, hi));
+        }
+    }
+}
+
+/// Character an escape stands for, if it is a literal escape
+fn escaped_char(c: char) -> Option<char> {
+    match c {
+        'n' => Some('\n'),
+        't' => Some('\t'),
+        'r' => Some('\r'),
+        '0' => Some('\0'),
+        c if c.is_ascii_alphanumeric() => None,
+        c => Some(c),
+    }
+}
+
+fn perl_class(c: char) -> Vec<(char, char)> {
+    match c {
+        'd' => vec![('0', '9')],
+        'w' => vec![('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')],
+        _ => vec![(' ', ' '), ('\t', '\r')],
+    }
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    fn emit(&mut self, inst: Inst) -> Result<usize, PatternError> {
+        if self.program.len() >= MAX_PROGRAM {
+            return Err(PatternError::TooLarge);
+        }
+        self.program.push(inst);
+        Ok(self.program.len() - 1)
+    }
+
+    fn next(&self) -> usize {
+        self.program.len()
+    }
+
+    fn compile(&mut self, node: &Node) -> Result<(), PatternError> {
+        match node {
+            Node::Empty => {}
+            Node::Char(c) => {
+                self.emit(Inst::Char(*c))?;
+            }
+            Node::Any => {
+                self.emit(Inst::Any)?;
+            }
+            Node::Class(class) => {
+                self.emit(Inst::Class(class.clone()))?;
+            }
+            Node::Assert(assertion) => {
+                self.emit(Inst::Assert(*assertion))?;
+            }
+            Node::Concat(items) => {
+                for item in items {
+                    self.compile(item)?;
+                }
+            }
+            Node::Alternate(branches) => {
+                let mut jumps = Vec::new();
+                for (i, branch) in branches.iter().enumerate() {
+                    if i + 1 == branches.len() {
+                        self.compile(branch)?;
+                        break;
+                    }
+                    let split = self.emit(Inst::Split(0, 0))?;
+                    self.compile(branch)?;
+                    jumps.push(self.emit(Inst::Jmp(0))?);
+                    self.program[split] = Inst::Split(split + 1, self.next());
+                }
+                let end = self.next();
+                for jump in jumps {
+                    self.program[jump] = Inst::Jmp(end);
+                }
+            }
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => {
+                for _ in 0..*min {
+                    self.compile(node)?;
+                }
+                match max {
+                    None => {
+                        let split = self.emit(Inst::Split(0, 0))?;
+                        self.compile(node)?;
+                        self.emit(Inst::Jmp(split))?;
+                        let end = self.next();
+                        self.program[split] = self.split(split + 1, end, *greedy);
+                    }
+                    Some(max) => {
+                        let mut splits = Vec::new();
+                        for _ in *min..*max {
+                            splits.push(self.emit(Inst::Split(0, 0))?);
+                            self.compile(node)?;
+                        }
+                        let end = self.next();
+                        for split in splits {
+                            self.program[split] = self.split(split + 1, end, *greedy);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn split(&self, body: usize, skip: usize, greedy: bool) -> Inst {
+        if greedy {
+            Inst::Split(body, skip)
+        } else {
+            Inst::Split(skip, body)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'t>(pattern: &str, text: &'t str) -> Option<&'t str> {
+        Regex::new(pattern)
+            .unwrap()
+            .find(text)
+            .map(|m| m.as_str(text))
+    }
+
+    #[test]
+    fn test_literals_classes_and_repeats() {
+        assert_eq!(find("b+", "abbbc"), Some("bbb"));
+        assert_eq!(find("b+?", "abbbc"), Some("b"));
+        assert_eq!(find("a.c", "xxabcxx"), Some("abc"));
+        assert_eq!(find("[0-9]{2,3}", "a1b22c4444"), Some("22"));
+        assert_eq!(find("[^a-z]+", "abc123def"), Some("123"));
+        assert_eq!(find(r"\d+\.\d+", "v1.25!"), Some("1.25"));
+        assert_eq!(find("colou?r", "the color"), Some("color"));
+        assert_eq!(find("x{3}", "xx"), None);
+        assert_eq!(find("a{,", "a{,"), Some("a{,"));
+        assert_eq!(find("é+", "café!"), Some("é"));
+    }
+
+    #[test]
+    fn test_alternation_prefers_leftmost_then_first_branch() {
+        assert_eq!(find("cat|category", "category"), Some("cat"));
+        assert_eq!(find("(?:dog|cat)s", "hotdogs and cats"), Some("dogs"));
+        assert_eq!(find("a(b|)c", "ac"), Some("ac"));
+        assert_eq!(find("(a*)*b", "aaab"), Some("aaab"));
+    }
+
+    #[test]
+    fn test_anchors_are_line_based() {
+        let text = "one\ntwo\nthree";
+        let starts: Vec<_> = Regex::new("^t\\w+")
+            .unwrap()
+            .find_iter(text)
+            .map(|m| m.as_str(text))
+            .collect();
+        assert_eq!(starts, vec!["two", "three"]);
+        assert_eq!(find("one$", text), Some("one"));
+        assert_eq!(find(r"\bcat\b", "concat cat"), Some("cat"));
+        assert_eq!(
+            Regex::new(r"\bcat\b").unwrap().find("concat cat"),
+            Some(Match { start: 7, end: 10 })
+        );
+        assert_eq!(find(r"\Bcat", "concat cat"), Some("cat"));
+    }
+
+    #[test]
+    fn test_find_iter_and_ignore_case() {
+        let regex = Regex::new("x*").unwrap();
+        assert_eq!(regex.find_iter("axxb").count(), 3);
+
+        let regex = Regex::new("hello").unwrap().ignore_case(true);
+        assert_eq!(regex.find_iter("Hello HELLO hello").count(), 3);
+        assert!(Regex::new("[a-c]+")
+            .unwrap()
+            .ignore_case(true)
+            .is_match("ABC"));
+
+        // A start offset keeps the context before it
+        let regex = Regex::new("^b").unwrap();
+        assert_eq!(regex.find_at("ab", 1), None);
+    }
+
+    #[test]
+    fn test_escape_and_errors() {
+        let pattern = escape("a.b*(c)");
+        assert_eq!(find(&pattern, "xa.b*(c)"), Some("a.b*(c)"));
+        assert_eq!(find(&pattern, "aXbb(c)"), None);
+
+        assert_eq!(
+            Regex::new("(ab").unwrap_err(),
+            PatternError::UnmatchedParen(0)
+        );
+        assert_eq!(
+            Regex::new("ab)").unwrap_err(),
+            PatternError::UnmatchedParen(2)
+        );
+        assert_eq!(
+            Regex::new("[ab").unwrap_err(),
+            PatternError::UnclosedClass(0)
+        );
+        assert_eq!(
+            Regex::new("*a").unwrap_err(),
+            PatternError::NothingToRepeat(0)
+        );
+        assert_eq!(
+            Regex::new("[z-a]").unwrap_err(),
+            PatternError::InvalidRange(1)
+        );
+        assert_eq!(
+            Regex::new(r"\q").unwrap_err(),
+            PatternError::InvalidEscape(0)
+        );
+        assert_eq!(
+            Regex::new("a\\").unwrap_err(),
+            PatternError::TrailingBackslash
+        );
+        assert_eq!(
+            Regex::new("(a{1000}){1000}").unwrap_err(),
+            PatternError::TooLarge
+        );
+    }
+}
@@ -29,9 +29,15 @@
 //! - Generate input events
 //!
 //! This is presentation, not authority.
+//!
+//! Text buffers show the cursor as `|` and wrap highlighted ranges, such as
+//! an editor selection, in `{` and `}`.
 
 use std::collections::HashMap;
-use view_types::{CursorPosition, ViewContent, ViewFrame};
+use view_types::{CursorPosition, TextHighlight, ViewContent, ViewFrame};
+
+/// Limit on padding drawn past the end of a line or buffer
+const MAX_PADDING: usize = 1000;
 
 /// Default separator width for status line
 /// This could be made configurable in the future based on terminal width
@@ -211,7 +217,7 @@ impl TextRenderer {
     fn render_view_incremental(&mut self, frame: &ViewFrame) -> String {
         match &frame.content {
             ViewContent::TextBuffer { lines } => {
-                self.render_text_buffer_incremental(lines, frame.cursor.as_ref())
+                self.render_text_buffer_incremental(lines, frame.cursor.as_ref(), &frame.highlights)
             }
             _ => {
                 // For non-text buffers, fall back to full render
@@ -225,6 +231,7 @@ impl TextRenderer {
         &mut self,
         lines: &[String],
         cursor: Option<&CursorPosition>,
+        highlights: &[TextHighlight],
     ) -> String {
         let mut output = String::new();
         let mut lines_changed = 0;
@@ -232,12 +239,12 @@ impl TextRenderer {
 
         // Check each line against cache
         for (line_idx, line) in lines.iter().enumerate() {
-            let cursor_on_line = cursor.is_some_and(|c| c.line == line_idx);
+            let cursor_col = cursor.filter(|c| c.line == line_idx).map(|c| c.column);
+            let line_highlights = highlights_on_line(highlights, line_idx);
 
-            if cursor_on_line {
-                // Cursor is on this line - must render with cursor
-                let col = cursor.unwrap().column;
-                let rendered_line = self.render_line_with_cursor(line, col);
+            if cursor_col.is_some() || !line_highlights.is_empty() {
+                // Cursor or highlight on this line - must render decorated
+                let rendered_line = self.render_decorated_line(line, cursor_col, &line_highlights);
 
                 let line_changed = match self.view_cache.get_line(line_idx) {
                     Some(cached) => cached != rendered_line,
@@ -251,7 +258,7 @@ impl TextRenderer {
                     lines_changed += 1;
                 }
             } else {
-                // Cursor NOT on this line - compare raw line directly
+                // Nothing drawn over this line - compare raw line directly
                 let line_changed = match self.view_cache.get_line(line_idx) {
                     Some(cached) => cached != line,
                     None => true,
@@ -289,83 +296,90 @@ impl TextRenderer {
         output
     }
 
-    /// Helper to render a line with cursor marker
-    fn render_line_with_cursor(&self, line: &str, col: usize) -> String {
-        // Find byte offset for the cursor column
-        let byte_pos = line.char_indices().map(|(i, _)| i).nth(col);
+    /// Helper to render a line with cursor marker and highlight brackets
+    ///
+    /// Columns are characters, so Unicode lines are handled correctly. A
+    /// cursor or highlight past the end of the line pads it with spaces.
+    fn render_decorated_line(
+        &self,
+        line: &str,
+        cursor_col: Option<usize>,
+        highlights: &[&TextHighlight],
+    ) -> String {
+        let chars: Vec<char> = line.chars().collect();
+        let limit = chars.len() + MAX_PADDING;
+        let cursor_col = cursor_col.map(|col| col.min(limit));
+        let spans: Vec<(usize, usize)> = highlights
+            .iter()
+            .map(|h| (h.start_column.min(limit), h.end_column.min(limit)))
+            .filter(|(start, end)| start < end)
+            .collect();
+        let width = spans
+            .iter()
+            .map(|&(_, end)| end)
+            .chain(cursor_col)
+            .fold(chars.len(), usize::max);
 
-        match byte_pos {
-            Some(pos) => {
-                // Cursor is inside the string
-                let (before, after) = line.split_at(pos);
-                format!("{}|{}", before, after)
+        let mut output = String::new();
+        for col in 0..=width {
+            for &(_, end) in &spans {
+                if end == col {
+                    output.push('}');
+                }
             }
-            None => {
-                // Cursor is at the end or beyond
-                let char_count = line.chars().count();
-                if col == char_count {
-                    format!("{}|", line)
-                } else {
-                    let padding = (col - char_count).min(1000);
-                    format!("{}{}|", line, " ".repeat(padding))
+            for &(start, _) in &spans {
+                if start == col {
+                    output.push('{');
                 }
             }
+            if cursor_col == Some(col) {
+                output.push('|');
+            }
+            if col < width {
+                output.push(chars.get(col).copied().unwrap_or(' '));
+            }
         }
+        output
     }
 
     /// Renders a single view frame
     fn render_view_frame(&self, frame: &ViewFrame) -> String {
         match &frame.content {
             ViewContent::TextBuffer { lines } => {
-                self.render_text_buffer(lines, frame.cursor.as_ref())
+                self.render_text_buffer(lines, frame.cursor.as_ref(), &frame.highlights)
             }
             ViewContent::StatusLine { text } => format!("{}\n", text),
             ViewContent::Panel { metadata } => format!("[Panel: {}]\n", metadata),
         }
     }
 
-    /// Renders a text buffer with optional cursor
-    fn render_text_buffer(&self, lines: &[String], cursor: Option<&CursorPosition>) -> String {
+    /// Renders a text buffer with optional cursor and highlights
+    fn render_text_buffer(
+        &self,
+        lines: &[String],
+        cursor: Option<&CursorPosition>,
+        highlights: &[TextHighlight],
+    ) -> String {
         let mut output = String::new();
 
         for (line_idx, line) in lines.iter().enumerate() {
-            if let Some(cursor_pos) = cursor {
-                if cursor_pos.line == line_idx {
-                    // Insert cursor marker at the correct column
-                    // Use character-based indexing to handle Unicode correctly
-                    let col = cursor_pos.column;
-                    let chars: Vec<char> = line.chars().collect();
-
-                    if col <= chars.len() {
-                        // Cursor within or at end of line
-                        let before: String = chars.iter().take(col).collect();
-                        let after: String = chars.iter().skip(col).collect();
-                        output.push_str(&before);
-                        output.push('|'); // Cursor marker
-                        output.push_str(&after);
-                        output.push('\n');
-                    } else {
-                        // Cursor beyond line end - limit padding to reasonable max (1000 chars)
-                        let padding = (col.saturating_sub(chars.len())).min(1000);
-                        output.push_str(line);
-                        output.push_str(&" ".repeat(padding));
-                        output.push('|');
-                        output.push('\n');
-                    }
-                    continue;
-                }
+            let cursor_col = cursor.filter(|c| c.line == line_idx).map(|c| c.column);
+            let line_highlights = highlights_on_line(highlights, line_idx);
+            if cursor_col.is_some() || !line_highlights.is_empty() {
+                output.push_str(&self.render_decorated_line(line, cursor_col, &line_highlights));
+            } else {
+                output.push_str(line);
             }
-            output.push_str(line);
             output.push('\n');
         }
 
         // If cursor is on a line beyond the buffer (limit to reasonable max of 1000 lines)
         if let Some(cursor_pos) = cursor {
-            if cursor_pos.line >= lines.len() && cursor_pos.line < lines.len() + 1000 {
+            if cursor_pos.line >= lines.len() && cursor_pos.line < lines.len() + MAX_PADDING {
                 for _ in lines.len()..cursor_pos.line {
                     output.push('\n');
                 }
-                let padding = cursor_pos.column.min(1000);
+                let padding = cursor_pos.column.min(MAX_PADDING);
                 output.push_str(&" ".repeat(padding));
                 output.push_str("|\n");
             }
@@ -389,10 +403,15 @@ impl Default for TextRenderer {
     }
 }
 
+/// Highlights that fall on one line
+fn highlights_on_line(highlights: &[TextHighlight], line_idx: usize) -> Vec<&TextHighlight> {
+    highlights.iter().filter(|h| h.line == line_idx).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use view_types::{ViewId, ViewKind};
+    use view_types::{HighlightKind, ViewId, ViewKind};
 
     fn create_text_buffer_frame(
         lines: Vec<String>,
@@ -629,6 +648,40 @@ mod tests {
         assert!(output.contains("[CURSOR]") || output.contains("[L0]"));
     }
 
+    #[test]
+    fn test_render_selection_highlights() {
+        let mut renderer = TextRenderer::new();
+        let lines = vec!["Hello".to_string(), "".to_string(), "World".to_string()];
+        let frame = create_text_buffer_frame(lines, Some(CursorPosition::new(2, 1)), 1)
+            .with_highlights(vec![
+                TextHighlight::new(0, 1, 5, HighlightKind::Selection),
+                TextHighlight::new(1, 0, 1, HighlightKind::Selection),
+                TextHighlight::new(2, 0, 2, HighlightKind::Selection),
+            ]);
+        let output = renderer.render_snapshot(Some(&frame), None);
+        assert!(output.starts_with("H{ello}\n{ }\n{W|o}rld\n"));
+    }
+
+    #[test]
+    fn test_incremental_render_selection_change() {
+        let mut renderer = TextRenderer::new();
+        let lines = vec!["Hello".to_string(), "World".to_string()];
+        let selection = |end| vec![TextHighlight::new(1, 0, end, HighlightKind::Selection)];
+
+        let frame1 = create_text_buffer_frame(lines.clone(), None, 1).with_highlights(selection(2));
+        renderer.render_incremental(Some(&frame1), None);
+
+        // Growing the selection redraws only its line
+        let frame2 = create_text_buffer_frame(lines.clone(), None, 2).with_highlights(selection(4));
+        let output = renderer.render_incremental(Some(&frame2), None);
+        assert_eq!(output, "[L1] {Worl}d\n");
+
+        // Clearing it restores the plain line
+        let frame3 = create_text_buffer_frame(lines, None, 3);
+        let output = renderer.render_incremental(Some(&frame3), None);
+        assert_eq!(output, "[L1] World\n");
+    }
+
     #[test]
     fn test_render_stats() {
         let mut renderer = TextRenderer::new();
@@ -131,6 +131,45 @@ impl fmt::Display for CursorPosition {
     }
 }
 
+/// Why a range of text is highlighted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HighlightKind {
+    /// Visual-mode selection
+    Selection,
+    /// Text matching the current search pattern
+    SearchMatch,
+}
+
+/// Highlighted columns of one line in a text buffer view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextHighlight {
+    /// Line number (0-indexed)
+    pub line: usize,
+    /// First highlighted column
+    pub start_column: usize,
+    /// Column just past the highlight
+    pub end_column: usize,
+    /// What the highlight shows
+    pub kind: HighlightKind,
+}
+
+impl TextHighlight {
+    /// Creates a highlight of `start_column..end_column` on a line
+    pub fn new(line: usize, start_column: usize, end_column: usize, kind: HighlightKind) -> Self {
+        Self {
+            line,
+            start_column,
+            end_column,
+            kind,
+        }
+    }
+
+    /// Checks if a column lies inside the highlight
+    pub fn contains(&self, column: usize) -> bool {
+        (self.start_column..self.end_column).contains(&column)
+    }
+}
+
 /// View frame - immutable snapshot of view state
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ViewFrame {
@@ -145,6 +184,9 @@ pub struct ViewFrame {
     /// Optional cursor position
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cursor: Option<CursorPosition>,
+    /// Highlighted ranges, such as a selection
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<TextHighlight>,
     /// Optional title
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
@@ -170,6 +212,7 @@ impl ViewFrame {
             revision,
             content,
             cursor: None,
+            highlights: Vec::new(),
             title: None,
             component_id: None,
             timestamp_ns,
@@ -182,6 +225,12 @@ impl ViewFrame {
         self
     }
 
+    /// Sets the highlighted ranges
+    pub fn with_highlights(mut self, highlights: Vec<TextHighlight>) -> Self {
+        self.highlights = highlights;
+        self
+    }
+
     /// Sets the title
     pub fn with_title(mut self, title: impl Into<String>) -> Self {
         self.title = Some(title.into());
@@ -474,6 +523,24 @@ mod tests {
         assert_eq!(frame, deserialized);
     }
 
+    #[test]
+    fn test_view_frame_highlights_serialization() {
+        let view_id = ViewId::new();
+        let content = ViewContent::text_buffer(vec!["select me".to_string()]);
+        let plain = ViewFrame::new(view_id, ViewKind::TextBuffer, 1, content, 1000);
+        let json = serde_json::to_string(&plain).unwrap();
+        assert!(!json.contains("highlights"));
+        let deserialized: ViewFrame = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.highlights.is_empty());
+
+        let highlight = TextHighlight::new(0, 0, 6, HighlightKind::Selection);
+        let frame = plain.with_highlights(vec![highlight]);
+        let json = serde_json::to_string(&frame).unwrap();
+        let deserialized: ViewFrame = serde_json::from_str(&json).unwrap();
+        assert_eq!(frame, deserialized);
+        assert!(highlight.contains(5) && !highlight.contains(6));
+    }
+
     #[test]
     fn test_view_content_serialization() {
         let text_buffer = ViewContent::text_buffer(vec!["line1".to_string()]);